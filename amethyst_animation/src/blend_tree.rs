@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+
+use crate::graph::AnimationParameters;
+
+/// The animations played by a state of an `AnimationGraph`: a single animation, or several
+/// blended by the float parameters of the `AnimationStateMachine`, such as the walk and run
+/// animations blended by the speed of a character.
+///
+/// The blend weights are computed from the positions of the children and the parameters, and
+/// all the animations of the tree keep playing, even when their weight is zero.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the animations in the `AnimationSet`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum AnimationBlendTree<I> {
+    /// Plays an animation of the `AnimationSet`.
+    Clip(I),
+    /// Blends the children placed on a line by a float parameter, interpolating between the two
+    /// closest children.
+    Blend1D {
+        /// Name of the float parameter.
+        parameter: String,
+        /// The children, with their position on the line.
+        children: Vec<(f32, AnimationBlendTree<I>)>,
+    },
+    /// Blends the children placed on a plane by two float parameters, such as the velocity of a
+    /// character for walking, running and strafing, using gradient band interpolation.
+    Blend2D {
+        /// Names of the float parameters for the x and y axes.
+        parameters: (String, String),
+        /// The children, with their position on the plane.
+        children: Vec<((f32, f32), AnimationBlendTree<I>)>,
+    },
+}
+
+impl<I> AnimationBlendTree<I>
+where
+    I: Copy + PartialEq,
+{
+    /// Returns the animations of the tree.
+    pub fn clips(&self) -> Vec<I> {
+        let mut clips = Vec::new();
+        self.add_clips(&mut clips);
+        clips
+    }
+
+    fn add_clips(&self, clips: &mut Vec<I>) {
+        match *self {
+            AnimationBlendTree::Clip(id) => {
+                if !clips.contains(&id) {
+                    clips.push(id);
+                }
+            }
+            AnimationBlendTree::Blend1D { ref children, .. } => {
+                for &(_, ref child) in children {
+                    child.add_clips(clips);
+                }
+            }
+            AnimationBlendTree::Blend2D { ref children, .. } => {
+                for &(_, ref child) in children {
+                    child.add_clips(clips);
+                }
+            }
+        }
+    }
+
+    /// Returns the animations of the tree with their blend weight, adding up to one.
+    pub(crate) fn weights(&self, parameters: &AnimationParameters) -> Vec<(I, f32)> {
+        let mut weights = Vec::new();
+        self.add_weights(parameters, 1., &mut weights);
+        weights
+    }
+
+    fn add_weights(
+        &self,
+        parameters: &AnimationParameters,
+        scale: f32,
+        weights: &mut Vec<(I, f32)>,
+    ) {
+        match *self {
+            AnimationBlendTree::Clip(id) => match weights.iter_mut().find(|w| w.0 == id) {
+                Some(&mut (_, ref mut weight)) => *weight += scale,
+                None => weights.push((id, scale)),
+            },
+            AnimationBlendTree::Blend1D {
+                ref parameter,
+                ref children,
+            } => {
+                let positions = children.iter().map(|c| c.0).collect::<Vec<_>>();
+                let blend = blend_1d(&positions, parameters.float(parameter));
+                for (&(_, ref child), weight) in children.iter().zip(blend) {
+                    child.add_weights(parameters, scale * weight, weights);
+                }
+            }
+            AnimationBlendTree::Blend2D {
+                parameters: (ref x, ref y),
+                ref children,
+            } => {
+                let positions = children.iter().map(|c| c.0).collect::<Vec<_>>();
+                let point = (parameters.float(x), parameters.float(y));
+                for (&(_, ref child), weight) in children.iter().zip(blend_2d(&positions, point)) {
+                    child.add_weights(parameters, scale * weight, weights);
+                }
+            }
+        }
+    }
+}
+
+/// Weights of points on a line: the two points around `x` are interpolated, and the closest
+/// point is used outside of the line.
+fn blend_1d(positions: &[f32], x: f32) -> Vec<f32> {
+    let mut weights = vec![0.; positions.len()];
+    let below = positions
+        .iter()
+        .enumerate()
+        .filter(|(_, position)| **position <= x)
+        .max_by(|(_, p1), (_, p2)| p1.partial_cmp(p2).expect("Unexpected NaN"));
+    let above = positions
+        .iter()
+        .enumerate()
+        .filter(|(_, position)| **position > x)
+        .min_by(|(_, p1), (_, p2)| p1.partial_cmp(p2).expect("Unexpected NaN"));
+    match (below, above) {
+        (Some((i, &a)), Some((j, &b))) => {
+            let t = (x - a) / (b - a);
+            weights[i] = 1. - t;
+            weights[j] = t;
+        }
+        (Some((i, _)), None) | (None, Some((i, _))) => weights[i] = 1.,
+        (None, None) => {}
+    }
+    weights
+}
+
+/// Weights of points on a plane by gradient band interpolation: the weight of each point
+/// decreases to zero towards each other point, and the weights are normalized.
+fn blend_2d(positions: &[(f32, f32)], point: (f32, f32)) -> Vec<f32> {
+    let weights = positions
+        .iter()
+        .enumerate()
+        .map(|(i, &(xi, yi))| {
+            positions
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &(xj, yj))| {
+                    let (edge_x, edge_y) = (xj - xi, yj - yi);
+                    let length = edge_x * edge_x + edge_y * edge_y;
+                    if length <= std::f32::EPSILON {
+                        return 1.;
+                    }
+                    1. - ((point.0 - xi) * edge_x + (point.1 - yi) * edge_y) / length
+                })
+                .fold(1., f32::min)
+                .max(0.)
+        })
+        .collect::<Vec<_>>();
+    let total = weights.iter().sum::<f32>();
+    if total > 0. {
+        weights.iter().map(|weight| weight / total).collect()
+    } else {
+        weights
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blend_1d, blend_2d, AnimationBlendTree};
+    use crate::graph::AnimationParameters;
+
+    #[test]
+    fn blends_by_parameters() {
+        let positions = [0., 2., 6.];
+        assert_eq!(vec![1., 0., 0.], blend_1d(&positions, -1.));
+        assert_eq!(vec![0.5, 0.5, 0.], blend_1d(&positions, 1.));
+        assert_eq!(vec![0., 0.75, 0.25], blend_1d(&positions, 3.));
+        assert_eq!(vec![0., 0., 1.], blend_1d(&positions, 8.));
+
+        let positions = [(0., 0.), (1., 0.), (0., 1.)];
+        assert_eq!(vec![1., 0., 0.], blend_2d(&positions, (0., 0.)));
+        assert_eq!(vec![0., 1., 0.], blend_2d(&positions, (1., 0.)));
+        assert_eq!(vec![0.5, 0.5, 0.], blend_2d(&positions, (0.5, 0.)));
+
+        let tree = AnimationBlendTree::Blend1D {
+            parameter: "speed".to_string(),
+            children: vec![
+                (0., AnimationBlendTree::Clip(0)),
+                (1., AnimationBlendTree::Clip(1)),
+                (3., AnimationBlendTree::Clip(2)),
+            ],
+        };
+        let mut parameters = AnimationParameters::default();
+        parameters.floats.insert("speed".to_string(), 2.);
+        assert_eq!(vec![0, 1, 2], tree.clips());
+        assert_eq!(vec![(0, 0.), (1, 0.5), (2, 0.5)], tree.weights(&parameters));
+    }
+}
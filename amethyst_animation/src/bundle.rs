@@ -4,7 +4,8 @@ use crate::{
     resources::AnimationSampling,
     skinning::VertexSkinningSystem,
     systems::{
-        AnimationControlSystem, AnimationProcessor, SamplerInterpolationSystem, SamplerProcessor,
+        AnimationControlSystem, AnimationGraphProcessor, AnimationGraphSystem, AnimationProcessor,
+        SamplerInterpolationSystem, SamplerProcessor,
     },
 };
 use amethyst_error::Error;
@@ -98,8 +99,9 @@ where
 ///
 /// This will also add `SamplingBundle`, because it is a dependency of this bundle.
 ///
-/// Will add `AnimationControlSystem<T>` with the given name.
-/// Will also add `AnimationProcessor<T>`.
+/// Will add `AnimationControlSystem<T>` with the given name, after an `AnimationGraphSystem<I, T>`
+/// named with a `_graph` suffix.
+/// Will also add `AnimationProcessor<T>` and `AnimationGraphProcessor<I>`.
 ///
 /// ### Type parameters:
 ///
@@ -144,10 +146,13 @@ where
 {
     fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
         builder.add(AnimationProcessor::<T>::new(), "", &[]);
+        builder.add(AnimationGraphProcessor::<I>::new(), "", &[]);
+        let graph_name = format!("{}_graph", self.animation_name);
+        builder.add(AnimationGraphSystem::<I, T>::new(), &graph_name, self.dep);
         builder.add(
             AnimationControlSystem::<I, T>::new(),
             self.animation_name,
-            self.dep,
+            &[graph_name.as_str()],
         );
         SamplingBundle::<T>::new(self.sampling_name)
             .with_dep(&[self.animation_name])
@@ -0,0 +1,399 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use fnv::FnvHashMap;
+use minterpolate::{InterpolationFunction, InterpolationPrimitive};
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{AssetStorage, Handle, Loader, Progress, WeakHandle};
+use amethyst_core::{
+    alga::general::{SubsetOf, SupersetOf},
+    math::{convert, RealField},
+};
+
+use crate::{
+    material::MaterialPrimitive,
+    resources::{Animation, AnimationSampling, Sampler},
+    sprite::SpriteRenderPrimitive,
+    util::SamplerPrimitive,
+};
+
+/// Sampler primitives which can be compressed, and compared to share identical samplers.
+pub trait CompressiblePrimitive: InterpolationPrimitive + Clone + PartialEq {
+    /// Distance between two values, used to drop the key frames reproduced by interpolation
+    fn distance(&self, other: &Self) -> f32;
+
+    /// Rounds the value to a multiple of `precision`
+    fn quantize(&self, precision: f32) -> Self;
+
+    /// Feeds the value into the hasher
+    fn hash_value<H: Hasher>(&self, state: &mut H);
+}
+
+impl<S> CompressiblePrimitive for SamplerPrimitive<S>
+where
+    S: RealField + SubsetOf<f32> + SubsetOf<f64> + SupersetOf<f32>,
+{
+    fn distance(&self, other: &Self) -> f32 {
+        self.sub(other).magnitude2().sqrt()
+    }
+
+    fn quantize(&self, precision: f32) -> Self {
+        let precision = convert::<f32, S>(precision);
+        let round = |s: S| (s / precision).round() * precision;
+        match *self {
+            SamplerPrimitive::Scalar(s) => SamplerPrimitive::Scalar(round(s)),
+            SamplerPrimitive::Vec2(s) => SamplerPrimitive::Vec2([round(s[0]), round(s[1])]),
+            SamplerPrimitive::Vec3(s) => {
+                SamplerPrimitive::Vec3([round(s[0]), round(s[1]), round(s[2])])
+            }
+            SamplerPrimitive::Vec4(s) => {
+                SamplerPrimitive::Vec4([round(s[0]), round(s[1]), round(s[2]), round(s[3])])
+            }
+        }
+    }
+
+    fn hash_value<H: Hasher>(&self, state: &mut H) {
+        let values: &[S] = match *self {
+            SamplerPrimitive::Scalar(ref s) => std::slice::from_ref(s),
+            SamplerPrimitive::Vec2(ref s) => s,
+            SamplerPrimitive::Vec3(ref s) => s,
+            SamplerPrimitive::Vec4(ref s) => s,
+        };
+        values.len().hash(state);
+        for value in values {
+            convert::<S, f64>(*value).to_bits().hash(state);
+        }
+    }
+}
+
+impl CompressiblePrimitive for SpriteRenderPrimitive {
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.
+        } else {
+            std::f32::INFINITY
+        }
+    }
+
+    fn quantize(&self, _: f32) -> Self {
+        self.clone()
+    }
+
+    fn hash_value<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            SpriteRenderPrimitive::SpriteSheet(ref handle) => handle.id().hash(state),
+            SpriteRenderPrimitive::SpriteIndex(index) => index.hash(state),
+        }
+    }
+}
+
+impl CompressiblePrimitive for MaterialPrimitive {
+    fn distance(&self, other: &Self) -> f32 {
+        if self == other {
+            0.
+        } else {
+            std::f32::INFINITY
+        }
+    }
+
+    fn quantize(&self, _: f32) -> Self {
+        self.clone()
+    }
+
+    fn hash_value<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match *self {
+            MaterialPrimitive::Texture(ref handle) => handle.id().hash(state),
+            MaterialPrimitive::Offset((left, right), (top, bottom)) => {
+                for value in &[left, right, top, bottom] {
+                    value.to_bits().hash(state);
+                }
+            }
+        }
+    }
+}
+
+/// Compression of the `Sampler`s of an animation when it is loaded.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnimationCompression {
+    /// Key frames reproduced within this distance by interpolating their neighbours are dropped
+    pub tolerance: f32,
+    /// When set, the values are rounded to a multiple of it, so nearly identical key frames and
+    /// samplers become identical
+    pub precision: Option<f32>,
+}
+
+impl Default for AnimationCompression {
+    fn default() -> Self {
+        AnimationCompression {
+            tolerance: 0.0001,
+            precision: None,
+        }
+    }
+}
+
+impl<T> Sampler<T>
+where
+    T: CompressiblePrimitive,
+{
+    /// Drops the key frames reproduced within `tolerance` by interpolating the key frames kept
+    /// around them. The first and last key frames are always kept.
+    ///
+    /// Only `Linear`, `SphericalLinear` and `Step` samplers are reduced, as the outputs of the
+    /// splines hold tangents.
+    pub fn reduce_keyframes(&mut self, tolerance: f32) {
+        match self.function {
+            InterpolationFunction::Linear
+            | InterpolationFunction::SphericalLinear
+            | InterpolationFunction::Step => {}
+            _ => return,
+        }
+        if self.input.len() < 3 || self.input.len() != self.output.len() {
+            return;
+        }
+
+        let last = self.input.len() - 1;
+        let mut kept = vec![0];
+        for i in 1..last {
+            let start = kept[kept.len() - 1];
+            let end = i + 1;
+            let inputs = [self.input[start], self.input[end]];
+            let outputs = [self.output[start].clone(), self.output[end].clone()];
+            let reproduced = (start + 1..end).all(|j| {
+                self.function
+                    .interpolate(self.input[j], &inputs, &outputs, false)
+                    .distance(&self.output[j])
+                    <= tolerance
+            });
+            if !reproduced {
+                kept.push(i);
+            }
+        }
+        kept.push(last);
+
+        self.input = kept.iter().map(|&i| self.input[i]).collect();
+        self.output = kept.iter().map(|&i| self.output[i].clone()).collect();
+    }
+
+    /// Rounds the output values to a multiple of `precision`.
+    pub fn quantize(&mut self, precision: f32) {
+        for value in &mut self.output {
+            *value = value.quantize(precision);
+        }
+    }
+
+    /// Quantizes the sampler, then drops its redundant key frames.
+    pub fn compress(&mut self, compression: &AnimationCompression) {
+        if let Some(precision) = compression.precision {
+            self.quantize(precision);
+        }
+        self.reduce_keyframes(compression.tolerance);
+    }
+
+    /// Hash of the key frames and interpolation function of the sampler.
+    pub fn fingerprint(&self) -> u64 {
+        let mut state = DefaultHasher::new();
+        mem::discriminant(&self.function).hash(&mut state);
+        self.input.len().hash(&mut state);
+        for time in &self.input {
+            time.to_bits().hash(&mut state);
+        }
+        self.output.len().hash(&mut state);
+        for value in &self.output {
+            value.hash_value(&mut state);
+        }
+        state.finish()
+    }
+
+    /// Returns `true` if both samplers have the same key frames and interpolation function.
+    pub fn same_keyframes(&self, other: &Self) -> bool {
+        mem::discriminant(&self.function) == mem::discriminant(&other.function)
+            && self.input == other.input
+            && self.output == other.output
+    }
+}
+
+/// Sampler in the `AnimationCache`, with a copy of its data until the asset is loaded.
+struct CachedSampler<T> {
+    handle: WeakHandle<Sampler<T>>,
+    data: Option<Sampler<T>>,
+}
+
+/// Shares identical samplers and animations between the loaded prefabs, so the characters
+/// loaded from different files with the same animations use the same assets.
+///
+/// Assets are found by the hash of their data, and only kept in the cache while used. Samplers
+/// with the same hash are compared before being shared.
+///
+/// ### Type parameters:
+///
+/// - `T`: the component type that the animations should be applied to
+pub struct AnimationCache<T>
+where
+    T: AnimationSampling,
+{
+    samplers: FnvHashMap<u64, CachedSampler<T::Primitive>>,
+    animations: FnvHashMap<u64, WeakHandle<Animation<T>>>,
+}
+
+impl<T> Default for AnimationCache<T>
+where
+    T: AnimationSampling,
+{
+    fn default() -> Self {
+        AnimationCache {
+            samplers: FnvHashMap::default(),
+            animations: FnvHashMap::default(),
+        }
+    }
+}
+
+impl<T> AnimationCache<T>
+where
+    T: AnimationSampling,
+    T::Primitive: CompressiblePrimitive,
+{
+    /// Returns the handle of an identical sampler, or loads the sampler.
+    pub fn load_sampler<P: Progress>(
+        &mut self,
+        sampler: Sampler<T::Primitive>,
+        loader: &Loader,
+        progress: P,
+        storage: &AssetStorage<Sampler<T::Primitive>>,
+    ) -> Handle<Sampler<T::Primitive>> {
+        let fingerprint = sampler.fingerprint();
+        if let Some(handle) = self.find_sampler(fingerprint, &sampler, storage) {
+            return handle;
+        }
+        self.samplers
+            .retain(|_, entry| match entry.handle.upgrade() {
+                Some(handle) => {
+                    if storage.get(&handle).is_some() {
+                        entry.data = None;
+                    }
+                    true
+                }
+                None => false,
+            });
+        let data = sampler.clone();
+        let handle = loader.load_from_data(sampler, progress, storage);
+        self.samplers.insert(
+            fingerprint,
+            CachedSampler {
+                handle: handle.downgrade(),
+                data: Some(data),
+            },
+        );
+        handle
+    }
+
+    fn find_sampler(
+        &self,
+        fingerprint: u64,
+        sampler: &Sampler<T::Primitive>,
+        storage: &AssetStorage<Sampler<T::Primitive>>,
+    ) -> Option<Handle<Sampler<T::Primitive>>> {
+        let entry = self.samplers.get(&fingerprint)?;
+        let handle = entry.handle.upgrade()?;
+        let cached = storage.get(&handle).or_else(|| entry.data.as_ref())?;
+        if cached.same_keyframes(sampler) {
+            Some(handle)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the handle of an identical animation, or loads the animation.
+    pub fn load_animation<P: Progress>(
+        &mut self,
+        animation: Animation<T>,
+        loader: &Loader,
+        progress: P,
+        storage: &AssetStorage<Animation<T>>,
+    ) -> Handle<Animation<T>> {
+        let mut state = DefaultHasher::new();
+        for &(node_index, ref channel, ref sampler) in &animation.nodes {
+            node_index.hash(&mut state);
+            channel.hash(&mut state);
+            sampler.id().hash(&mut state);
+        }
+        for event in &animation.events {
+            event.time.to_bits().hash(&mut state);
+            event.name.hash(&mut state);
+            event.payload.hash(&mut state);
+        }
+        let fingerprint = state.finish();
+        if let Some(handle) = self
+            .animations
+            .get(&fingerprint)
+            .and_then(WeakHandle::upgrade)
+        {
+            return handle;
+        }
+        self.animations.retain(|_, handle| !handle.is_dead());
+        let handle = loader.load_from_data(animation, progress, storage);
+        self.animations.insert(fingerprint, handle.downgrade());
+        handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use minterpolate::InterpolationFunction;
+
+    use super::AnimationCompression;
+    use crate::{resources::Sampler, util::SamplerPrimitive};
+
+    fn sampler(
+        function: InterpolationFunction<SamplerPrimitive<f32>>,
+        output: &[f32],
+    ) -> Sampler<SamplerPrimitive<f32>> {
+        Sampler {
+            input: (0..output.len()).map(|i| i as f32).collect(),
+            output: output
+                .iter()
+                .map(|&v| SamplerPrimitive::Scalar(v))
+                .collect(),
+            function,
+        }
+    }
+
+    #[test]
+    fn compares_keyframes() {
+        let sampler = sampler(InterpolationFunction::Linear, &[0., 1., 2.]);
+        let mut other = sampler.clone();
+        assert!(sampler.same_keyframes(&other));
+        other.output[2] = SamplerPrimitive::Scalar(2.5);
+        assert!(!sampler.same_keyframes(&other));
+        other = sampler.clone();
+        other.function = InterpolationFunction::Step;
+        assert!(!sampler.same_keyframes(&other));
+    }
+
+    #[test]
+    fn drops_reproduced_keyframes() {
+        let mut linear = sampler(InterpolationFunction::Linear, &[0., 1., 2.001, 0., 0.]);
+        linear.reduce_keyframes(0.01);
+        assert_eq!(vec![0., 2., 3., 4.], linear.input);
+
+        let compression = AnimationCompression {
+            tolerance: 0.,
+            precision: Some(0.5),
+        };
+        let mut step = sampler(InterpolationFunction::Step, &[0., 0.001, 0.002, 1., 1.]);
+        let mut other = sampler(InterpolationFunction::Step, &[0., 0.002, 0., 1., 1.]);
+        assert_ne!(step.fingerprint(), other.fingerprint());
+        step.reduce_keyframes(0.);
+        assert_eq!(vec![0., 1., 2., 3., 4.], step.input);
+        step.compress(&compression);
+        other.compress(&compression);
+        assert_eq!(vec![0., 3., 4.], step.input);
+        assert_eq!(step.fingerprint(), other.fingerprint());
+    }
+}
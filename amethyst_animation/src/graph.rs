@@ -0,0 +1,339 @@
+use std::marker;
+
+use fnv::{FnvHashMap, FnvHashSet};
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, Handle, ProcessingState};
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage, VecStorage};
+use amethyst_error::Error;
+
+use crate::{
+    blend_tree::AnimationBlendTree,
+    resources::{AnimationSampling, EndControl},
+};
+
+/// A state of an `AnimationGraph`, playing animations of the `AnimationSet` of the entity.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the animations in the `AnimationSet`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AnimationGraphState<I> {
+    /// Name of the state, used by the transitions.
+    pub name: String,
+    /// The animations played, blended by the parameters of the `AnimationStateMachine`.
+    pub motion: AnimationBlendTree<I>,
+    /// Whether the animations loop, or stay on their last frame when done.
+    #[serde(default)]
+    pub looping: bool,
+    /// Rate of the animations.
+    #[serde(default = "default_rate")]
+    pub rate: f32,
+}
+
+fn default_rate() -> f32 {
+    1.0
+}
+
+/// A condition on the parameters of an `AnimationStateMachine`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum AnimationCondition {
+    /// The bool parameter has this value, false when not set.
+    Bool(String, bool),
+    /// The float parameter, zero when not set, is greater than the value.
+    Greater(String, f32),
+    /// The float parameter, zero when not set, is less than the value.
+    Less(String, f32),
+    /// The trigger parameter is set. It is reset when the transition is taken.
+    Trigger(String),
+    /// The animations of the current state are done, which never happens for looping states.
+    Done,
+}
+
+/// A transition between two states of an `AnimationGraph`, taken when all its conditions hold.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AnimationTransition {
+    /// Name of the state the transition leaves, or `None` to leave any state.
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Name of the state the transition enters.
+    pub to: String,
+    /// The conditions of the transition.
+    #[serde(default)]
+    pub conditions: Vec<AnimationCondition>,
+    /// Duration in seconds of the cross fade between the animations of the states.
+    #[serde(default)]
+    pub blend: f32,
+}
+
+/// An animation state machine: the states of an entity, each playing an animation, and the
+/// transitions between them, depending on the parameters of its `AnimationStateMachine`.
+///
+/// Transitions are checked in order and the first one whose conditions hold is taken.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the animations in the `AnimationSet`
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AnimationGraph<I> {
+    /// Name of the state entered first.
+    pub initial: String,
+    /// The states.
+    pub states: Vec<AnimationGraphState<I>>,
+    /// The transitions.
+    #[serde(default)]
+    pub transitions: Vec<AnimationTransition>,
+}
+
+impl<I> AnimationGraph<I> {
+    /// Returns the state with this name.
+    pub fn state(&self, name: &str) -> Option<&AnimationGraphState<I>> {
+        self.states.iter().find(|state| state.name == name)
+    }
+
+    /// Returns the first transition leaving the `current` state whose conditions hold, if any.
+    pub(crate) fn next_transition(
+        &self,
+        current: &str,
+        parameters: &AnimationParameters,
+        done: bool,
+    ) -> Option<&AnimationTransition> {
+        self.transitions
+            .iter()
+            .filter(|transition| {
+                transition.to != current
+                    && transition
+                        .from
+                        .as_ref()
+                        .map_or(true, |from| from == current)
+            })
+            .find(|transition| {
+                transition
+                    .conditions
+                    .iter()
+                    .all(|condition| parameters.holds(condition, done))
+            })
+    }
+}
+
+impl<I> Asset for AnimationGraph<I>
+where
+    I: Send + Sync + 'static,
+{
+    const NAME: &'static str = "animation::AnimationGraph";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl<I> Into<Result<ProcessingState<AnimationGraph<I>>, Error>> for AnimationGraph<I> {
+    fn into(self) -> Result<ProcessingState<AnimationGraph<I>>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+/// Parameters checked by the conditions of the transitions and blending the animations.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AnimationParameters {
+    pub bools: FnvHashMap<String, bool>,
+    pub floats: FnvHashMap<String, f32>,
+    pub triggers: FnvHashSet<String>,
+}
+
+impl AnimationParameters {
+    pub fn float(&self, name: &str) -> f32 {
+        self.floats.get(name).cloned().unwrap_or(0.)
+    }
+
+    fn holds(&self, condition: &AnimationCondition, done: bool) -> bool {
+        match *condition {
+            AnimationCondition::Bool(ref name, value) => {
+                self.bools.get(name).cloned().unwrap_or(false) == value
+            }
+            AnimationCondition::Greater(ref name, value) => self.float(name) > value,
+            AnimationCondition::Less(ref name, value) => self.float(name) < value,
+            AnimationCondition::Trigger(ref name) => self.triggers.contains(name),
+            AnimationCondition::Done => done,
+        }
+    }
+}
+
+/// An animation playing for an `AnimationStateMachine`.
+#[derive(Clone, Debug)]
+pub(crate) struct PlayingAnimation<I> {
+    pub id: I,
+    /// Weight of the cross fade: the animations of the current state fade in while the others
+    /// fade out.
+    pub fade: f32,
+    /// Weight in the blend tree of its state.
+    pub weight: f32,
+    /// Blend weight last set on the samplers.
+    pub applied: Option<f32>,
+}
+
+/// Plays the animations of an entity following an `AnimationGraph`, driving its
+/// `AnimationControlSet` from the animations of its `AnimationSet` through the
+/// `AnimationGraphSystem`.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the animations in the `AnimationSet`
+/// - `T`: the component type that the animations should be applied to
+#[derive(Clone, Debug)]
+pub struct AnimationStateMachine<I, T> {
+    /// The states and transitions.
+    pub graph: Handle<AnimationGraph<I>>,
+    pub(crate) parameters: AnimationParameters,
+    pub(crate) current: Option<String>,
+    pub(crate) playing: Vec<PlayingAnimation<I>>,
+    /// Duration of the cross fade in progress.
+    pub(crate) blend: f32,
+    /// Animations of the current state to start, once their aborted instance is removed.
+    pub(crate) pending: Vec<I>,
+    m: marker::PhantomData<T>,
+}
+
+impl<I, T> AnimationStateMachine<I, T> {
+    /// Creates a state machine, entering the initial state of the graph once it is loaded.
+    pub fn new(graph: Handle<AnimationGraph<I>>) -> Self {
+        AnimationStateMachine {
+            graph,
+            parameters: AnimationParameters::default(),
+            current: None,
+            playing: Vec::new(),
+            blend: 0.,
+            pending: Vec::new(),
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Returns the name of the current state.
+    pub fn current_state(&self) -> Option<&str> {
+        self.current.as_ref().map(String::as_str)
+    }
+
+    /// Sets a bool parameter.
+    pub fn set_bool<S: Into<String>>(&mut self, name: S, value: bool) -> &mut Self {
+        self.parameters.bools.insert(name.into(), value);
+        self
+    }
+
+    /// Returns a bool parameter, false when not set.
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.parameters.bools.get(name).cloned().unwrap_or(false)
+    }
+
+    /// Sets a float parameter.
+    pub fn set_float<S: Into<String>>(&mut self, name: S, value: f32) -> &mut Self {
+        self.parameters.floats.insert(name.into(), value);
+        self
+    }
+
+    /// Returns a float parameter, zero when not set.
+    pub fn get_float(&self, name: &str) -> f32 {
+        self.parameters.float(name)
+    }
+
+    /// Sets a trigger parameter, which stays set until a transition using it is taken.
+    pub fn set_trigger<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.parameters.triggers.insert(name.into());
+        self
+    }
+
+    /// Resets a trigger parameter.
+    pub fn reset_trigger(&mut self, name: &str) -> &mut Self {
+        self.parameters.triggers.remove(name);
+        self
+    }
+}
+
+impl<I, T> Component for AnimationStateMachine<I, T>
+where
+    I: Send + Sync + 'static,
+    T: AnimationSampling,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl<I> AnimationGraphState<I> {
+    pub(crate) fn end_control(&self) -> EndControl {
+        if self.looping {
+            EndControl::Loop(None)
+        } else {
+            EndControl::Stay
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blend_tree::AnimationBlendTree;
+
+    use super::{
+        AnimationCondition, AnimationGraph, AnimationGraphState, AnimationParameters,
+        AnimationTransition,
+    };
+
+    fn state(name: &str, animation: u32) -> AnimationGraphState<u32> {
+        AnimationGraphState {
+            name: name.to_string(),
+            motion: AnimationBlendTree::Clip(animation),
+            looping: true,
+            rate: 1.0,
+        }
+    }
+
+    fn transition(
+        from: Option<&str>,
+        to: &str,
+        conditions: Vec<AnimationCondition>,
+    ) -> AnimationTransition {
+        AnimationTransition {
+            from: from.map(str::to_string),
+            to: to.to_string(),
+            conditions,
+            blend: 0.2,
+        }
+    }
+
+    #[test]
+    fn takes_first_matching_transition() {
+        let graph = AnimationGraph {
+            initial: "idle".to_string(),
+            states: vec![state("idle", 0), state("run", 1), state("jump", 2)],
+            transitions: vec![
+                transition(
+                    None,
+                    "jump",
+                    vec![AnimationCondition::Trigger("jump".to_string())],
+                ),
+                transition(
+                    Some("idle"),
+                    "run",
+                    vec![AnimationCondition::Greater("speed".to_string(), 0.1)],
+                ),
+                transition(
+                    Some("run"),
+                    "idle",
+                    vec![AnimationCondition::Less("speed".to_string(), 0.1)],
+                ),
+                transition(Some("jump"), "idle", vec![AnimationCondition::Done]),
+            ],
+        };
+        let mut parameters = AnimationParameters::default();
+        let next = |parameters: &AnimationParameters, current, done| {
+            graph
+                .next_transition(current, parameters, done)
+                .map(|transition| transition.to.clone())
+        };
+
+        assert_eq!(None, next(&parameters, "idle", false));
+        parameters.floats.insert("speed".to_string(), 1.0);
+        assert_eq!(Some("run".to_string()), next(&parameters, "idle", false));
+        assert_eq!(None, next(&parameters, "run", false));
+        parameters.triggers.insert("jump".to_string());
+        assert_eq!(Some("jump".to_string()), next(&parameters, "run", false));
+        assert_eq!(None, next(&parameters, "jump", false));
+        parameters.triggers.clear();
+        assert_eq!(Some("idle".to_string()), next(&parameters, "jump", true));
+    }
+}
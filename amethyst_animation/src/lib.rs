@@ -50,26 +50,39 @@
 pub use minterpolate::{InterpolationFunction, InterpolationPrimitive};
 
 pub use self::{
+    blend_tree::AnimationBlendTree,
     bundle::{AnimationBundle, SamplingBundle, VertexSkinningBundle},
+    compression::{AnimationCache, AnimationCompression, CompressiblePrimitive},
+    graph::{
+        AnimationCondition, AnimationGraph, AnimationGraphState, AnimationStateMachine,
+        AnimationTransition,
+    },
     material::{MaterialChannel, MaterialPrimitive},
     prefab::{AnimatablePrefab, AnimationHierarchyPrefab, AnimationPrefab, AnimationSetPrefab},
+    property::AnimatableProperty,
     resources::{
-        Animation, AnimationCommand, AnimationControl, AnimationControlSet, AnimationHierarchy,
-        AnimationSampling, AnimationSet, ApplyData, BlendMethod, ControlState, DeferStartRelation,
-        EndControl, RestState, Sampler, SamplerControl, SamplerControlSet, StepDirection,
+        Animation, AnimationCommand, AnimationControl, AnimationControlSet, AnimationEvent,
+        AnimationEventKey, AnimationHierarchy, AnimationSampling, AnimationSet, ApplyData,
+        BlendMethod, ControlState, DeferStartRelation, EndControl, RestState, Sampler,
+        SamplerControl, SamplerControlSet, StepDirection,
     },
     skinning::{Joint, JointPrefab, Skin, SkinPrefab, SkinnablePrefab, VertexSkinningSystem},
     sprite::{SpriteRenderChannel, SpriteRenderPrimitive},
     systems::{
-        AnimationControlSystem, AnimationProcessor, SamplerInterpolationSystem, SamplerProcessor,
+        AnimationControlSystem, AnimationGraphProcessor, AnimationGraphSystem, AnimationProcessor,
+        SamplerInterpolationSystem, SamplerProcessor,
     },
     transform::TransformChannel,
     util::{get_animation_set, SamplerPrimitive},
 };
 
+mod blend_tree;
 mod bundle;
+mod compression;
+mod graph;
 mod material;
 mod prefab;
+mod property;
 mod resources;
 mod skinning;
 mod sprite;
@@ -8,17 +8,23 @@ use std::{
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use amethyst_assets::{AssetStorage, Handle, Loader, PrefabData, ProgressCounter};
-use amethyst_core::ecs::prelude::{Entity, Read, ReadExpect, WriteStorage};
+use amethyst_core::ecs::prelude::{Entity, Read, ReadExpect, Write, WriteStorage};
 use amethyst_derive::PrefabData;
 use amethyst_error::Error;
 
-use crate::{Animation, AnimationHierarchy, AnimationSampling, AnimationSet, RestState, Sampler};
+use crate::{
+    Animation, AnimationCache, AnimationCompression, AnimationEventKey, AnimationHierarchy,
+    AnimationSampling, AnimationSet, CompressiblePrimitive, RestState, Sampler,
+};
 
 /// `PrefabData` for loading a single `Animation`
 ///
 /// This should be used primarily from inside other `PrefabData`, because this will not place
 /// anything on the `Entity`, it will only return a `Handle<Animation>` when loaded.
 ///
+/// Identical samplers and animations are shared with the other prefabs through the
+/// `AnimationCache`.
+///
 /// ### Type parameters
 ///
 /// - `T`: The animatable `Component`
@@ -31,6 +37,12 @@ where
 {
     /// All samplers in the `Animation`
     pub samplers: Vec<(usize, T::Channel, Sampler<T::Primitive>)>,
+    /// Events emitted when the playback crosses them
+    #[serde(default)]
+    pub events: Vec<AnimationEventKey>,
+    /// Compression of the samplers when loaded
+    #[serde(default)]
+    pub compression: Option<AnimationCompression>,
     #[serde(skip, default = "default_handle")]
     handle: Option<Handle<Animation<T>>>,
 }
@@ -51,6 +63,8 @@ where
     fn default() -> Self {
         AnimationPrefab {
             samplers: Vec::default(),
+            events: Vec::default(),
+            compression: None,
             handle: None,
         }
     }
@@ -74,12 +88,13 @@ impl<'a, T> PrefabData<'a> for AnimationPrefab<T>
 where
     T: AnimationSampling,
     T::Channel: DeserializeOwned + Serialize,
-    T::Primitive: DeserializeOwned + Serialize,
+    T::Primitive: CompressiblePrimitive + DeserializeOwned + Serialize,
 {
     type SystemData = (
         ReadExpect<'a, Loader>,
         Read<'a, AssetStorage<Sampler<T::Primitive>>>,
         Read<'a, AssetStorage<Animation<T>>>,
+        Write<'a, AnimationCache<T>>,
     );
     type Result = Handle<Animation<T>>;
 
@@ -100,22 +115,32 @@ where
     fn load_sub_assets(
         &mut self,
         progress: &mut ProgressCounter,
-        &mut (ref loader, ref sampler_storage, ref animation_storage): &mut Self::SystemData,
+        system_data: &mut Self::SystemData,
     ) -> Result<bool, Error> {
-        let animation = Animation::<T> {
+        let (ref loader, ref sampler_storage, ref animation_storage, ref mut cache) = *system_data;
+        let compression = self.compression.as_ref();
+        let mut animation = Animation::<T> {
             nodes: self
                 .samplers
                 .iter()
                 .map(|(node_index, channel, sampler)| {
+                    let mut sampler = sampler.clone();
+                    if let Some(compression) = compression {
+                        sampler.compress(compression);
+                    }
                     (
                         *node_index,
                         channel.clone(),
-                        loader.load_from_data(sampler.clone(), &mut *progress, sampler_storage),
+                        cache.load_sampler(sampler, loader, &mut *progress, sampler_storage),
                     )
                 })
                 .collect(),
+            events: Vec::default(),
         };
-        self.handle = Some(loader.load_from_data(animation, progress, animation_storage));
+        for event in &self.events {
+            animation.add_event(event.clone());
+        }
+        self.handle = Some(cache.load_animation(animation, loader, progress, animation_storage));
         Ok(true)
     }
 }
@@ -142,7 +167,7 @@ impl<'a, I, T> PrefabData<'a> for AnimationSetPrefab<I, T>
 where
     T: AnimationSampling,
     T::Channel: for<'b> Deserialize<'b> + Serialize,
-    T::Primitive: Debug + for<'b> Deserialize<'b> + Serialize,
+    T::Primitive: CompressiblePrimitive + Debug + for<'b> Deserialize<'b> + Serialize,
     I: Clone + Hash + Eq + Send + Sync + 'static,
 {
     type SystemData = (
@@ -240,7 +265,7 @@ pub struct AnimatablePrefab<I, T>
 where
     T: AnimationSampling + Clone,
     T::Channel: for<'a> Deserialize<'a> + Serialize,
-    T::Primitive: Debug + for<'a> Deserialize<'a> + Serialize,
+    T::Primitive: CompressiblePrimitive + Debug + for<'a> Deserialize<'a> + Serialize,
     I: Clone + Hash + Eq + Send + Sync + 'static,
 {
     /// Place an `AnimationSet` on the `Entity`
@@ -0,0 +1,224 @@
+use amethyst_core::math::{Vector2, Vector3, Vector4};
+use amethyst_renderer::Rgba;
+
+use crate::util::SamplerPrimitive;
+
+/// Types of the component fields that can be animated, by deriving `AnimationSampling` on the
+/// component and marking the fields with `#[animate]`:
+///
+/// ```rust,ignore
+/// use amethyst::{
+///     animation::{
+///         AnimatableProperty, AnimationSampling, ApplyData, BlendMethod, SamplerPrimitive,
+///     },
+///     renderer::Rgba,
+/// };
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Clone, AnimationSampling)]
+/// pub struct Flicker {
+///     #[animate]
+///     pub intensity: f32,
+///     #[animate]
+///     pub color: Rgba,
+///     pub enabled: bool,
+/// }
+/// ```
+///
+/// The animations of the component, using the generated `FlickerChannel`, are then played by
+/// adding an `AnimationBundle<I, Flicker>`, once the component also implements `Component`.
+pub trait AnimatableProperty {
+    /// Returns the value as a primitive for the samplers
+    fn to_primitive(&self) -> SamplerPrimitive<f32>;
+
+    /// Sets the value from a sampled primitive
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>);
+
+    /// Returns the primitive used when a sampler of the channel is missing
+    fn default_primitive() -> SamplerPrimitive<f32>;
+}
+
+impl AnimatableProperty for f32 {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Scalar(*self)
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Scalar(s) => *self = s,
+            _ => panic!("Attempt to apply invalid sample to f32"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Scalar(0.)
+    }
+}
+
+impl AnimatableProperty for [f32; 2] {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec2(*self)
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec2(s) => *self = s,
+            _ => panic!("Attempt to apply invalid sample to [f32; 2]"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec2([0.; 2])
+    }
+}
+
+impl AnimatableProperty for [f32; 3] {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec3(*self)
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec3(s) => *self = s,
+            _ => panic!("Attempt to apply invalid sample to [f32; 3]"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec3([0.; 3])
+    }
+}
+
+impl AnimatableProperty for [f32; 4] {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4(*self)
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec4(s) => *self = s,
+            _ => panic!("Attempt to apply invalid sample to [f32; 4]"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4([0.; 4])
+    }
+}
+
+impl AnimatableProperty for Vector2<f32> {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec2([self.x, self.y])
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec2(s) => *self = Vector2::new(s[0], s[1]),
+            _ => panic!("Attempt to apply invalid sample to Vector2"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec2([0.; 2])
+    }
+}
+
+impl AnimatableProperty for Vector3<f32> {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec3([self.x, self.y, self.z])
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec3(s) => *self = Vector3::new(s[0], s[1], s[2]),
+            _ => panic!("Attempt to apply invalid sample to Vector3"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec3([0.; 3])
+    }
+}
+
+impl AnimatableProperty for Vector4<f32> {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4([self.x, self.y, self.z, self.w])
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec4(s) => *self = Vector4::new(s[0], s[1], s[2], s[3]),
+            _ => panic!("Attempt to apply invalid sample to Vector4"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4([0.; 4])
+    }
+}
+
+impl AnimatableProperty for Rgba {
+    fn to_primitive(&self) -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4([self.0, self.1, self.2, self.3])
+    }
+
+    fn apply_primitive(&mut self, primitive: &SamplerPrimitive<f32>) {
+        match *primitive {
+            SamplerPrimitive::Vec4(s) => *self = Rgba(s[0], s[1], s[2], s[3]),
+            _ => panic!("Attempt to apply invalid sample to Rgba"),
+        }
+    }
+
+    fn default_primitive() -> SamplerPrimitive<f32> {
+        SamplerPrimitive::Vec4([0.; 4])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_derive::AnimationSampling;
+    use amethyst_renderer::Rgba;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{AnimatableProperty, AnimationSampling, ApplyData, BlendMethod, SamplerPrimitive};
+
+    #[derive(Clone, Debug, PartialEq, AnimationSampling)]
+    struct Flicker {
+        #[animate]
+        intensity: f32,
+        #[animate]
+        light_color: Rgba,
+        enabled: bool,
+    }
+
+    #[test]
+    fn samples_animated_fields() {
+        let mut flicker = Flicker {
+            intensity: 1.,
+            light_color: Rgba::WHITE,
+            enabled: true,
+        };
+        flicker.apply_sample(
+            &FlickerChannel::Intensity,
+            &SamplerPrimitive::Scalar(0.5),
+            &(),
+        );
+        flicker.apply_sample(
+            &FlickerChannel::LightColor,
+            &SamplerPrimitive::Vec4([1., 0., 0., 1.]),
+            &(),
+        );
+        assert_eq!(
+            Flicker {
+                intensity: 0.5,
+                light_color: Rgba::RED,
+                enabled: true,
+            },
+            flicker
+        );
+        match flicker.current_sample(&FlickerChannel::Intensity, &()) {
+            SamplerPrimitive::Scalar(s) => assert_eq!(0.5, s),
+            _ => panic!("Unexpected primitive"),
+        }
+    }
+}
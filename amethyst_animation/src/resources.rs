@@ -235,6 +235,8 @@ where
 {
     /// node index -> sampler handle
     pub nodes: Vec<(usize, T::Channel, Handle<Sampler<T::Primitive>>)>,
+    /// Events emitted when the playback crosses their time, in order of time
+    pub events: Vec<AnimationEventKey>,
 }
 
 impl<T> Animation<T>
@@ -243,7 +245,10 @@ where
 {
     /// Create new empty animation
     pub fn new() -> Self {
-        Animation { nodes: vec![] }
+        Animation {
+            nodes: vec![],
+            events: vec![],
+        }
     }
 
     /// Create an animation with a single sampler
@@ -254,6 +259,7 @@ where
     ) -> Self {
         Animation {
             nodes: vec![(index, channel, sampler)],
+            events: vec![],
         }
     }
 
@@ -277,6 +283,70 @@ where
         self.nodes.push((node_index, channel, sampler));
         self
     }
+
+    /// Add an event to the animation
+    pub fn add_event(&mut self, event: AnimationEventKey) {
+        let index = self
+            .events
+            .iter()
+            .position(|e| e.time > event.time)
+            .unwrap_or_else(|| self.events.len());
+        self.events.insert(index, event);
+    }
+
+    /// Add an event to the animation
+    pub fn with_event(mut self, event: AnimationEventKey) -> Self {
+        self.add_event(event);
+        self
+    }
+}
+
+/// An event keyframed in an `Animation`, emitted as an `AnimationEvent` when the playback crosses
+/// its time, for footstep sounds, hit frames or effects synchronized with the animation.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AnimationEventKey {
+    /// Time of the event in the animation, in seconds
+    pub time: f32,
+    /// Name of the event
+    pub name: String,
+    /// Data of the event
+    #[serde(default)]
+    pub payload: String,
+}
+
+impl AnimationEventKey {
+    /// Creates a new event, without payload
+    pub fn new<S: Into<String>>(time: f32, name: S) -> Self {
+        AnimationEventKey {
+            time,
+            name: name.into(),
+            payload: String::new(),
+        }
+    }
+
+    /// Sets the data of the event
+    pub fn with_payload<S: Into<String>>(mut self, payload: S) -> Self {
+        self.payload = payload.into();
+        self
+    }
+}
+
+/// Event written to the `EventChannel<AnimationEvent<I>>` by the `AnimationControlSystem` when
+/// a running animation crosses an `AnimationEventKey`.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the running animations
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationEvent<I> {
+    /// The entity of the `AnimationControlSet` playing the animation
+    pub entity: Entity,
+    /// Id of the animation in the `AnimationControlSet`
+    pub animation: I,
+    /// Name of the event
+    pub name: String,
+    /// Data of the event
+    pub payload: String,
 }
 
 impl<T> Asset for Animation<T>
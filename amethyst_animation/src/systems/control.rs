@@ -1,4 +1,4 @@
-use std::{hash::Hash, marker, time::Duration};
+use std::{hash::Hash, marker, mem, time::Duration};
 
 use fnv::FnvHashMap;
 use log::error;
@@ -7,16 +7,18 @@ use minterpolate::InterpolationPrimitive;
 use amethyst_assets::{AssetStorage, Handle};
 use amethyst_core::{
     ecs::prelude::{
-        Component, Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData,
+        Component, Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, Write,
         WriteStorage,
     },
+    shrev::EventChannel,
     timing::secs_to_duration,
 };
 
 use crate::resources::{
-    Animation, AnimationCommand, AnimationControl, AnimationControlSet, AnimationHierarchy,
-    AnimationSampling, AnimationSet, ApplyData, ControlState, DeferStartRelation, RestState,
-    Sampler, SamplerControl, SamplerControlSet, StepDirection,
+    Animation, AnimationCommand, AnimationControl, AnimationControlSet, AnimationEvent,
+    AnimationEventKey, AnimationHierarchy, AnimationSampling, AnimationSet, ApplyData,
+    ControlState, DeferStartRelation, EndControl, RestState, Sampler, SamplerControl,
+    SamplerControlSet, StepDirection,
 };
 
 /// System for setting up animations, should run before `SamplerInterpolationSystem`.
@@ -25,6 +27,9 @@ use crate::resources::{
 /// animations they describe. If an animation only targets a single node/entity, there is no need
 /// for `AnimationHierarchy`.
 ///
+/// Writes an `AnimationEvent` to the `EventChannel<AnimationEvent<I>>` when a running animation
+/// crosses one of its `AnimationEventKey`s.
+///
 /// ### Type parameters:
 ///
 /// - `I`: identifier type for running animations, only one animation can be run at the same time
@@ -40,6 +45,8 @@ where
     remove_ids: Vec<I>,
     state_set: FnvHashMap<I, f32>,
     deferred_start: Vec<(I, f32)>,
+    event_times: FnvHashMap<u64, f32>,
+    next_event_times: FnvHashMap<u64, f32>,
 }
 
 impl<I, T> AnimationControlSystem<I, T>
@@ -54,6 +61,8 @@ where
             remove_ids: Vec::default(),
             state_set: FnvHashMap::default(),
             deferred_start: Vec::default(),
+            event_times: FnvHashMap::default(),
+            next_event_times: FnvHashMap::default(),
         }
     }
 }
@@ -73,6 +82,7 @@ where
         ReadStorage<'a, T>,
         WriteStorage<'a, RestState<T>>,
         <T as ApplyData<'a>>::ApplyData,
+        Write<'a, EventChannel<AnimationEvent<I>>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -86,8 +96,10 @@ where
             transforms,
             mut rest_states,
             apply_data,
+            mut events,
         ) = data;
         let mut remove_sets = Vec::default();
+        self.next_event_times.clear();
         for (entity, control_set) in (&*entities, &mut controls).join() {
             self.remove_ids.clear();
             self.state_set.clear();
@@ -121,13 +133,50 @@ where
                 if let AnimationCommand::SetInputValue(_) = control.command {
                     control.command = AnimationCommand::Start;
                 }
+                let keys = animation_storage
+                    .get(&control.animation)
+                    .map(|animation| &animation.events[..])
+                    .unwrap_or(&[]);
+                let mut emit = |key: &AnimationEventKey| {
+                    events.single_write(AnimationEvent {
+                        entity,
+                        animation: *id,
+                        name: key.name.clone(),
+                        payload: key.payload.clone(),
+                    });
+                };
                 if remove {
                     self.remove_ids.push(*id);
+                    // The events after the last sampled time are crossed when the animation ends.
+                    if let Some(&last) = self.event_times.get(&control.id) {
+                        if control.state.is_running() {
+                            crossed_events(keys, last, std::f32::INFINITY, false)
+                                .into_iter()
+                                .flatten()
+                                .for_each(&mut emit);
+                        }
+                    }
                 } else {
-                    self.state_set.insert(
-                        *id,
-                        get_running_duration(&entity, control, hierarchies.get(entity), &samplers),
-                    );
+                    let duration =
+                        get_running_duration(&entity, control, hierarchies.get(entity), &samplers);
+                    self.state_set.insert(*id, duration);
+                    if duration >= 0. {
+                        let last = self.event_times.get(&control.id).cloned().unwrap_or(-1.);
+                        let looping = match control.end {
+                            EndControl::Loop(_) => true,
+                            _ => false,
+                        };
+                        let time = match crossed_events(keys, last, duration, looping) {
+                            Some(crossed) => {
+                                crossed.into_iter().for_each(&mut emit);
+                                duration
+                            }
+                            None => last,
+                        };
+                        self.next_event_times.insert(control.id, time);
+                    } else if let Some(&last) = self.event_times.get(&control.id) {
+                        self.next_event_times.insert(control.id, last);
+                    }
                 }
             }
             for deferred_animation in &control_set.deferred_animations {
@@ -204,6 +253,7 @@ where
         for entity in remove_sets {
             controls.remove(entity);
         }
+        mem::swap(&mut self.event_times, &mut self.next_event_times);
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -243,6 +293,32 @@ where
         .unwrap_or(0.)
 }
 
+/// Returns the events crossed by the playback going from `from` to `to` seconds, or `None` when
+/// it went backwards without looping, such as at the end of the animation.
+fn crossed_events(
+    events: &[AnimationEventKey],
+    from: f32,
+    to: f32,
+    looping: bool,
+) -> Option<Vec<&AnimationEventKey>> {
+    let between = |from: f32, to: f32| {
+        events
+            .iter()
+            .filter(move |event| event.time > from && event.time <= to)
+    };
+    if to >= from {
+        Some(between(from, to).collect())
+    } else if looping && to > 0. {
+        Some(
+            between(from, std::f32::INFINITY)
+                .chain(between(-1., to))
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
 /// Check if the given animation list is for a single node. If so, we don't need an
 /// `AnimationHierarchy`.
 fn only_one_index<C, P>(nodes: &[(usize, C, Handle<Sampler<P>>)]) -> bool
@@ -651,3 +727,45 @@ where
         .flat_map(|(_, node_entity)| samplers.get(*node_entity))
         .all(|s| s.check_termination(control_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::crossed_events;
+    use crate::resources::AnimationEventKey;
+
+    fn names<'a>(crossed: Option<Vec<&'a AnimationEventKey>>) -> Option<Vec<&'a str>> {
+        crossed.map(|crossed| {
+            crossed
+                .into_iter()
+                .map(|event| event.name.as_str())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn emits_crossed_events() {
+        let events = vec![
+            AnimationEventKey::new(0., "start"),
+            AnimationEventKey::new(0.5, "step"),
+            AnimationEventKey::new(1., "step"),
+        ];
+
+        assert_eq!(
+            Some(vec!["start"]),
+            names(crossed_events(&events, -1., 0.2, false))
+        );
+        assert_eq!(
+            Some(vec![]),
+            names(crossed_events(&events, 0.2, 0.4, false))
+        );
+        assert_eq!(
+            Some(vec!["step", "step"]),
+            names(crossed_events(&events, 0.4, 1., false))
+        );
+        assert_eq!(
+            Some(vec!["step", "start", "step"]),
+            names(crossed_events(&events, 0.8, 0.6, true))
+        );
+        assert_eq!(None, names(crossed_events(&events, 0.8, 0., false)));
+    }
+}
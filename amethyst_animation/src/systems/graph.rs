@@ -0,0 +1,238 @@
+use std::{hash::Hash, marker};
+
+use log::error;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    ecs::prelude::{Component, Entities, Join, Read, ReadStorage, System, WriteStorage},
+    timing::Time,
+};
+
+use crate::{
+    graph::{AnimationCondition, AnimationGraph, AnimationStateMachine, PlayingAnimation},
+    resources::{
+        Animation, AnimationCommand, AnimationControlSet, AnimationSampling, AnimationSet,
+        ControlState,
+    },
+};
+
+/// Plays the animations of the `AnimationStateMachine`s, taking the transitions of their
+/// `AnimationGraph`, blending the animations of the states by the parameters and cross fading
+/// them. Should run before the `AnimationControlSystem`.
+///
+/// ### Type parameters:
+///
+/// - `I`: identifier type of the animations in the `AnimationSet`
+/// - `T`: the component type that the animations should be applied to
+#[derive(Default)]
+pub struct AnimationGraphSystem<I, T> {
+    m: marker::PhantomData<(I, T)>,
+}
+
+impl<I, T> AnimationGraphSystem<I, T> {
+    /// Creates a new `AnimationGraphSystem`
+    pub fn new() -> Self {
+        AnimationGraphSystem {
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<'a, I, T> System<'a> for AnimationGraphSystem<I, T>
+where
+    I: PartialEq + Eq + Hash + Copy + Send + Sync + 'static,
+    T: AnimationSampling + Component + Clone,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, AssetStorage<AnimationGraph<I>>>,
+        Read<'a, AssetStorage<Animation<T>>>,
+        Read<'a, Time>,
+        ReadStorage<'a, AnimationSet<I, T>>,
+        WriteStorage<'a, AnimationStateMachine<I, T>>,
+        WriteStorage<'a, AnimationControlSet<I, T>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, graphs, animations, time, sets, mut machines, mut controls): Self::SystemData,
+    ) {
+        for (entity, set, machine) in (&*entities, &sets, &mut machines).join() {
+            let graph = match graphs.get(&machine.graph) {
+                Some(graph) => graph,
+                None => continue,
+            };
+            if !controls.contains(entity) {
+                controls
+                    .insert(entity, AnimationControlSet::default())
+                    .expect("Unreachable: Animated entities are alive");
+            }
+            let control_set = controls
+                .get_mut(entity)
+                .expect("Unreachable: The control set was just inserted");
+
+            let next = match machine.current {
+                None => Some((graph.initial.clone(), 0., Vec::new())),
+                Some(ref current) => {
+                    let done = machine.pending.is_empty()
+                        && graph.state(current).map_or(false, |state| {
+                            state
+                                .motion
+                                .clips()
+                                .into_iter()
+                                .all(|id| !control_set.has_animation(id))
+                        });
+                    graph
+                        .next_transition(current, &machine.parameters, done)
+                        .map(|transition| {
+                            let triggers = transition
+                                .conditions
+                                .iter()
+                                .filter_map(|condition| match *condition {
+                                    AnimationCondition::Trigger(ref name) => Some(name.clone()),
+                                    _ => None,
+                                })
+                                .collect::<Vec<_>>();
+                            (transition.to.clone(), transition.blend, triggers)
+                        })
+                }
+            };
+
+            if let Some((to, blend, triggers)) = next {
+                for trigger in triggers {
+                    machine.parameters.triggers.remove(&trigger);
+                }
+                let state = match graph.state(&to) {
+                    Some(state) => state,
+                    None => {
+                        error!("Animation graph state `{}` does not exist", to);
+                        continue;
+                    }
+                };
+                let clips = state.motion.clips();
+                if clips.iter().any(|id| set.get(id).is_none()) {
+                    error!(
+                        "Animation of the graph state `{}` is missing from the animation set",
+                        to
+                    );
+                    continue;
+                }
+                machine.current = Some(to.clone());
+                machine.blend = blend;
+                machine.pending.clear();
+                for id in clips {
+                    let fading_out = control_set.animations.iter().any(|&(other, ref control)| {
+                        other == id && control.state != ControlState::Abort
+                    });
+                    if fading_out {
+                        // Fades back in.
+                        control_set.start(id);
+                    } else {
+                        machine.pending.push(id);
+                    }
+                }
+            }
+
+            let current = match machine
+                .current
+                .as_ref()
+                .and_then(|current| graph.state(current))
+            {
+                Some(state) => state,
+                None => continue,
+            };
+            // Started once the aborted instance, if any, is removed.
+            machine.pending.retain(|&id| {
+                if control_set.has_animation(id) {
+                    return true;
+                }
+                if let Some(handle) = set.get(&id) {
+                    control_set.add_animation(
+                        id,
+                        handle,
+                        current.end_control(),
+                        current.rate,
+                        AnimationCommand::Start,
+                    );
+                }
+                false
+            });
+
+            let weights = current.motion.weights(&machine.parameters);
+            let fade = if machine.blend > 0. { 0. } else { 1. };
+            for &(id, weight) in &weights {
+                if machine.pending.contains(&id) {
+                    continue;
+                }
+                match machine.playing.iter_mut().find(|playing| playing.id == id) {
+                    Some(playing) => playing.weight = weight,
+                    None => machine.playing.push(PlayingAnimation {
+                        id,
+                        fade,
+                        weight,
+                        applied: None,
+                    }),
+                }
+            }
+
+            let step = if machine.blend > 0. {
+                time.delta_seconds() / machine.blend
+            } else {
+                1.
+            };
+            let is_current = |id| weights.iter().any(|&(other, _)| other == id);
+            for playing in &mut machine.playing {
+                if is_current(playing.id) {
+                    playing.fade = (playing.fade + step).min(1.);
+                } else {
+                    playing.fade = (playing.fade - step).max(0.);
+                }
+            }
+            // Faded out animations are stopped, and finished ones forgotten.
+            for playing in &machine.playing {
+                if !is_current(playing.id) && playing.fade <= 0. {
+                    control_set.abort(playing.id);
+                }
+            }
+            machine.playing.retain(|playing| {
+                control_set.has_animation(playing.id)
+                    && (is_current(playing.id) || playing.fade > 0.)
+            });
+
+            for playing in &mut machine.playing {
+                let control = match control_set
+                    .animations
+                    .iter_mut()
+                    .find(|a| a.0 == playing.id)
+                {
+                    Some(&mut (_, ref mut control)) => control,
+                    None => continue,
+                };
+                // Blend weights can only be set on running animations.
+                if !control.state.is_running() {
+                    continue;
+                }
+                let weight = playing.fade * playing.weight;
+                let changed = playing
+                    .applied
+                    .map_or(true, |applied| (applied - weight).abs() > std::f32::EPSILON);
+                if changed {
+                    if let Some(animation) = animations.get(&control.animation) {
+                        let weights = animation
+                            .nodes
+                            .iter()
+                            .map(|&(node_index, ref channel, _)| {
+                                (node_index, channel.clone(), weight)
+                            })
+                            .collect();
+                        control.command = AnimationCommand::SetBlendWeights(weights);
+                        playing.applied = Some(weight);
+                    }
+                } else if let AnimationCommand::SetBlendWeights(_) = control.command {
+                    // The weights are set, let the animation run again.
+                    control.command = AnimationCommand::Start;
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,17 @@
 use amethyst_assets::Processor;
 
-use crate::resources::{Animation, Sampler};
+use crate::{
+    graph::AnimationGraph,
+    resources::{Animation, Sampler},
+};
 
-pub use self::{control::AnimationControlSystem, sampling::SamplerInterpolationSystem};
+pub use self::{
+    control::AnimationControlSystem, graph::AnimationGraphSystem,
+    sampling::SamplerInterpolationSystem,
+};
 
 mod control;
+mod graph;
 mod sampling;
 
 /// Asset storage processor for `Sampler`
@@ -12,3 +19,6 @@ pub type SamplerProcessor<S> = Processor<Sampler<S>>;
 
 /// Asset storage processor for `Animation`
 pub type AnimationProcessor<T> = Processor<Animation<T>>;
+
+/// Asset storage processor for `AnimationGraph`
+pub type AnimationGraphProcessor<I> = Processor<AnimationGraph<I>>;
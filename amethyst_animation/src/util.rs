@@ -34,7 +34,7 @@ where
 }
 
 /// Sampler primitive
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SamplerPrimitive<S>
 where
     S: RealField + SubsetOf<f32> + SupersetOf<f32>,
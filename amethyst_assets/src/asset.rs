@@ -31,6 +31,14 @@ pub trait Asset: Send + Sync + 'static {
 
     /// The ECS storage type to be used. You'll want to use `VecStorage` in most cases.
     type HandleStorage: UnprotectedStorage<Handle<Self>> + Send + Sync;
+
+    /// Estimated memory used by the asset in bytes, counted against the budget of its
+    /// `AssetStorage`.
+    ///
+    /// Defaults to the size of the asset type, assets owning heap or GPU memory should report it.
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
 }
 
 /// A format, providing a conversion from bytes to asset data, which is then
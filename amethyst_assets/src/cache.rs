@@ -13,10 +13,7 @@ pub struct Cache<A> {
     map: FnvHashMap<String, WeakHandle<A>>,
 }
 
-impl<A> Cache<A>
-where
-    A: Clone,
-{
+impl<A> Cache<A> {
     /// Creates a new `Cache` and initializes it with the default values.
     pub fn new() -> Self {
         Default::default()
@@ -45,4 +42,12 @@ where
     pub fn clear_all(&mut self) {
         self.map.clear();
     }
+
+    /// Retrieves the `key` an asset handle was cached with.
+    pub fn key(&self, handle: &Handle<A>) -> Option<&str> {
+        self.map
+            .iter()
+            .find(|(_, h)| h.upgrade().map_or(false, |h| h == *handle))
+            .map(|(key, _)| key.as_str())
+    }
 }
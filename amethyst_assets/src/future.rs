@@ -0,0 +1,166 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+use parking_lot::Mutex;
+
+use amethyst_error::Error;
+
+use crate::{Asset, Handle, Progress, Tracker};
+
+/// Future returned by `Loader::load_async`, resolving to the handle of the asset once it is
+/// loaded.
+///
+/// Assets are finished by the system processing their storage, so the future only resolves
+/// while the game is running. Poll it from a state every frame or await it from an executor on
+/// another thread; blocking the main thread on it never completes.
+pub struct AssetFuture<A> {
+    handle: Option<Handle<A>>,
+    state: Arc<Mutex<FutureState>>,
+}
+
+impl<A> AssetFuture<A> {
+    pub(crate) fn with_handle(mut self, handle: Handle<A>) -> Self {
+        self.handle = Some(handle);
+        self
+    }
+
+    /// The handle of the asset, usable before the asset is loaded.
+    ///
+    /// Returns `None` once the future has resolved.
+    pub fn handle(&self) -> Option<&Handle<A>> {
+        self.handle.as_ref()
+    }
+}
+
+impl<A: Asset> Future for AssetFuture<A> {
+    type Output = Result<Handle<A>, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = {
+            let mut state = self.state.lock();
+            match state.result.take() {
+                Some(result) => result,
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+        let handle = self
+            .handle
+            .take()
+            .expect("`AssetFuture` polled after it resolved");
+        Poll::Ready(result.map(|()| handle))
+    }
+}
+
+#[derive(Default)]
+struct FutureState {
+    result: Option<Result<(), Error>>,
+    waker: Option<Waker>,
+}
+
+impl FutureState {
+    fn resolve(state: &Mutex<FutureState>, result: Result<(), Error>) {
+        let mut state = state.lock();
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// `Progress` resolving an `AssetFuture`.
+#[derive(Default)]
+pub(crate) struct FutureProgress {
+    state: Arc<Mutex<FutureState>>,
+}
+
+impl FutureProgress {
+    /// Create the future for the asset tracked by this progress, see `AssetFuture::with_handle`.
+    pub(crate) fn future<A>(&self) -> AssetFuture<A> {
+        AssetFuture {
+            handle: None,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Progress for FutureProgress {
+    type Tracker = FutureTracker;
+
+    fn add_assets(&mut self, _: usize) {}
+
+    fn create_tracker(self) -> FutureTracker {
+        FutureTracker { state: self.state }
+    }
+}
+
+pub(crate) struct FutureTracker {
+    state: Arc<Mutex<FutureState>>,
+}
+
+impl Tracker for FutureTracker {
+    fn success(self: Box<Self>) {
+        FutureState::resolve(&self.state, Ok(()));
+    }
+
+    fn fail(self: Box<Self>, _: u32, _: &'static str, _: String, error: Error) {
+        FutureState::resolve(&self.state, Err(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::Pin,
+        ptr,
+        sync::Arc,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_core::ecs::prelude::VecStorage;
+
+    use crate::{Asset, AssetStorage, Handle, Loader, ProcessingState};
+
+    struct Number(u32);
+
+    impl Asset for Number {
+        const NAME: &'static str = "Number";
+        type Data = u32;
+        type HandleStorage = VecStorage<Handle<Self>>;
+    }
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker {
+            RawWaker::new(ptr::null(), &VTABLE)
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        unsafe { Waker::from_raw(clone(ptr::null())) }
+    }
+
+    #[test]
+    fn resolves_when_processed() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Number>::default();
+        let mut future = loader.load_from_data_async(3, &storage);
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+
+        storage.process(|n| Ok(ProcessingState::Loaded(Number(n))), 0, &pool, None);
+        match Pin::new(&mut future).poll(&mut cx) {
+            Poll::Ready(Ok(handle)) => assert_eq!(3, storage.get(&handle).unwrap().0),
+            _ => panic!("Expected the asset to be loaded"),
+        }
+    }
+}
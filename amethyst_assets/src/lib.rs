@@ -11,27 +11,43 @@
 
 #[cfg(feature = "json")]
 pub use crate::formats::JsonFormat;
+#[cfg(feature = "saveload")]
+pub use crate::save::{LoadContext, SaveContext, SaveRegistry, Saveable, SAVE_FORMAT_VERSION};
+#[cfg(feature = "http")]
+pub use crate::source::Http;
+#[cfg(feature = "zip")]
+pub use crate::source::Zip;
 pub use crate::{
     asset::{Asset, Format, FormatValue, SimpleFormat},
     cache::Cache,
     formats::RonFormat,
+    future::AssetFuture,
     helper::AssetLoaderSystemData,
     loader::Loader,
-    prefab::{AssetPrefab, Prefab, PrefabData, PrefabLoader, PrefabLoaderSystem},
-    progress::{Completion, Progress, ProgressCounter, Tracker},
-    reload::{HotReloadBundle, HotReloadStrategy, HotReloadSystem, Reload, SingleFile},
-    source::{Directory, Source},
-    storage::{AssetStorage, Handle, ProcessingState, Processor, WeakHandle},
+    prefab::{
+        bake_prefab, despawn_prefab, export_prefab, export_prefab_tree, AssetPrefab, Prefab,
+        PrefabBinaryFormat, PrefabData, PrefabExport, PrefabLoader, PrefabLoaderSystem,
+        PrefabParameters, PrefabPatchFormat, PrefabSpawner, PrefabTemplateFormat, SpawnedPrefab,
+    },
+    progress::{AssetProgress, AssetState, Completion, Progress, ProgressCounter, Tracker},
+    reload::{
+        HotReloadBundle, HotReloadStrategy, HotReloadSystem, Reload, ReloadEvent, SingleFile,
+    },
+    source::{Directory, Overlay, Pack, PackBuilder, Source},
+    storage::{AssetStorage, AssetStorageStats, Handle, ProcessingState, Processor, WeakHandle},
 };
 
 mod asset;
 mod cache;
 mod error;
 mod formats;
+mod future;
 mod helper;
 mod loader;
 mod prefab;
 mod progress;
 mod reload;
+#[cfg(feature = "saveload")]
+mod save;
 mod source;
 mod storage;
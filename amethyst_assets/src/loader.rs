@@ -10,8 +10,10 @@ use thread_profiler::profile_scope;
 
 use crate::{
     error::Error,
+    future::{AssetFuture, FutureProgress},
+    source::CountingSource,
     storage::{AssetStorage, Handle, Processed},
-    Asset, Directory, Format, FormatValue, Progress, Source,
+    Asset, Directory, Format, FormatValue, Progress, Source, Tracker,
 };
 
 /// The asset loader, holding the sources and a reference to the `ThreadPool`.
@@ -130,7 +132,6 @@ impl Loader {
     {
         #[cfg(feature = "profiler")]
         profile_scope!("load_asset_from");
-        use crate::progress::Tracker;
 
         let name = name.into();
         let source = source.as_ref();
@@ -153,7 +154,8 @@ impl Loader {
         );
 
         progress.add_assets(1);
-        let tracker = progress.create_tracker();
+        let mut tracker = progress.create_tracker();
+        tracker.queued(handle.id(), A::NAME, &name);
 
         let source = self.source(source);
         let handle_clone = handle.clone();
@@ -164,9 +166,23 @@ impl Loader {
         let cl = move || {
             #[cfg(feature = "profiler")]
             profile_scope!("load_asset_from_worker");
+            let mut tracker = tracker;
+            tracker.loading();
+            let source = Arc::new(CountingSource::new(source));
             let data = format
-                .import(name.clone(), source, options, hot_reload)
+                .import(name.clone(), source.clone(), options, hot_reload)
                 .with_context(|_| Error::Format(F::NAME));
+            // Files other than the asset, like the prefabs referenced by a prefab, are tracked
+            // on their own
+            let mut files = source.files();
+            let bytes = files
+                .iter()
+                .position(|file| file.0 == name)
+                .map_or(0, |index| files.remove(index).1);
+            tracker.imported(bytes);
+            for (path, bytes) in files {
+                tracker.nested(&path, bytes);
+            }
             let tracker = Box::new(tracker) as Box<dyn Tracker>;
 
             processed.push(Processed::NewAsset {
@@ -181,6 +197,72 @@ impl Loader {
         handle_clone
     }
 
+    /// Loads an asset with a given format from the default (directory) source, returning a future
+    /// resolving to its handle once it is loaded.
+    ///
+    /// ```rust,ignore
+    /// let (mesh, texture) = futures::join!(
+    ///     loader.load_async("mesh/cone.obj", ObjFormat, (), &meshes),
+    ///     loader.load_async("texture/logo.png", PngFormat, Default::default(), &textures),
+    /// );
+    /// ```
+    ///
+    /// See `AssetFuture` for how the future is completed.
+    pub fn load_async<A, F, N>(
+        &self,
+        name: N,
+        format: F,
+        options: F::Options,
+        storage: &AssetStorage<A>,
+    ) -> AssetFuture<A>
+    where
+        A: Asset,
+        F: Format<A>,
+        N: Into<String>,
+    {
+        self.load_from_async::<A, F, _, _>(name, format, options, "", storage)
+    }
+
+    /// Loads an asset with a given id and format from a custom source, returning a future
+    /// resolving to its handle once it is loaded.
+    ///
+    /// See `load_from` for the parameters.
+    pub fn load_from_async<A, F, N, S>(
+        &self,
+        name: N,
+        format: F,
+        options: F::Options,
+        source: &S,
+        storage: &AssetStorage<A>,
+    ) -> AssetFuture<A>
+    where
+        A: Asset,
+        F: Format<A> + 'static,
+        N: Into<String>,
+        S: AsRef<str> + Eq + Hash + ?Sized,
+        String: Borrow<S>,
+    {
+        let progress = FutureProgress::default();
+        let future = progress.future();
+        let handle = self.load_from(name, format, options, source, progress, storage);
+        future.with_handle(handle)
+    }
+
+    /// Load an asset from data, returning a future resolving to its handle once it is processed.
+    pub fn load_from_data_async<A>(
+        &self,
+        data: A::Data,
+        storage: &AssetStorage<A>,
+    ) -> AssetFuture<A>
+    where
+        A: Asset,
+    {
+        let progress = FutureProgress::default();
+        let future = progress.future();
+        let handle = self.load_from_data(data, progress, storage);
+        future.with_handle(handle)
+    }
+
     /// Load an asset from data and return a handle.
     pub fn load_from_data<A, P>(
         &self,
@@ -193,9 +275,11 @@ impl Loader {
         P: Progress,
     {
         progress.add_assets(1);
-        let tracker = progress.create_tracker();
-        let tracker = Box::new(tracker);
+        let mut tracker = progress.create_tracker();
         let handle = storage.allocate();
+        tracker.queued(handle.id(), A::NAME, "<Data>");
+        tracker.imported(0);
+        let tracker = Box::new(tracker);
         storage.processed.push(Processed::NewAsset {
             data: Ok(FormatValue::data(data)),
             handle: handle.clone(),
@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Directory, Format, Prefab, RonFormat, SimpleFormat};
+
+use super::{nested::NestedPrefabFormat, PrefabEntity};
+
+/// Bytes starting every binary prefab.
+const MAGIC: &[u8; 4] = b"AMPF";
+
+/// Version of the binary layout, increased on incompatible changes.
+const VERSION: u32 = 1;
+
+/// `PrefabEntity` as stored in binary prefabs, with every field present.
+#[derive(Deserialize, Serialize)]
+struct BinaryEntity<T, S> {
+    parent: Option<usize>,
+    data: Option<T>,
+    prefab: Option<S>,
+}
+
+impl<T> Prefab<T> {
+    /// Serialize the prefab to the binary format read by `PrefabBinaryFormat`.
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize,
+    {
+        let entities = self
+            .entities
+            .iter()
+            .map(|entity| BinaryEntity {
+                parent: entity.parent,
+                data: entity.data.as_ref(),
+                prefab: entity.prefab.as_ref().map(String::as_str),
+            })
+            .collect::<Vec<_>>();
+        let mut bytes = MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &(VERSION, entities))
+            .with_context(|_| format_err!("Failed serializing binary prefab"))?;
+        Ok(bytes)
+    }
+
+    /// Deserialize a prefab written by `to_binary`.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, Error>
+    where
+        T: for<'a> Deserialize<'a>,
+    {
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            return Err(format_err!("Not a binary prefab"));
+        }
+        let version: u32 = bincode::deserialize(&bytes[4..8])
+            .with_context(|_| format_err!("Failed reading binary prefab version"))?;
+        if version != VERSION {
+            return Err(format_err!(
+                "Unsupported binary prefab version {}, expected {}",
+                version,
+                VERSION
+            ));
+        }
+        let entities: Vec<BinaryEntity<T, String>> = bincode::deserialize(&bytes[8..])
+            .with_context(|_| format_err!("Failed deserializing binary prefab"))?;
+        Ok(Prefab {
+            tag: None,
+            entities: entities
+                .into_iter()
+                .map(|entity| PrefabEntity {
+                    parent: entity.parent,
+                    data: entity.data,
+                    prefab: entity.prefab,
+                })
+                .collect(),
+            counter: None,
+        })
+    }
+}
+
+/// Format for loading `Prefab`s written by `Prefab::to_binary` or `bake_prefab`.
+///
+/// Binary prefabs load much faster than Ron files, and are meant for shipping builds. The prefab
+/// data is stored with bincode, so data types relying on self-describing formats, like untagged
+/// enums, can't be stored in binary prefabs.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PrefabBinaryFormat;
+
+impl<T> SimpleFormat<Prefab<T>> for PrefabBinaryFormat
+where
+    T: for<'a> Deserialize<'a> + Send + Sync + 'static,
+{
+    const NAME: &'static str = "PrefabBinary";
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<Prefab<T>, Error> {
+        Prefab::from_binary(&bytes)
+    }
+}
+
+/// Convert the Ron prefab `name` in `directory` to a binary prefab written to `destination`.
+///
+/// Prefabs referenced by the entities of the prefab are loaded from `directory` and baked in, so
+/// the binary prefab doesn't depend on other files. Fails if the prefab can't be read back from
+/// the binary format.
+///
+/// This is meant to be called at build time, for example from a build script or a small tool:
+///
+/// ```rust,ignore
+/// bake_prefab::<MyPrefabData, _, _>("assets", "prefab/level.ron", "dist/prefab/level.bin")?;
+/// ```
+pub fn bake_prefab<T, D, P>(directory: D, name: &str, destination: P) -> Result<(), Error>
+where
+    T: for<'a> Deserialize<'a> + Serialize + Send + Sync + 'static,
+    D: Into<PathBuf>,
+    P: AsRef<Path>,
+{
+    let prefab: Prefab<T> = NestedPrefabFormat(RonFormat)
+        .import(
+            name.to_owned(),
+            Arc::new(Directory::new(directory)),
+            (),
+            false,
+        )
+        .with_context(|_| format_err!("Failed loading prefab {:?}", name))?
+        .data;
+    let bytes = prefab.to_binary()?;
+    Prefab::<T>::from_binary(&bytes).with_context(|_| {
+        format_err!(
+            "Prefab {:?} can't be read back from the binary format, its data may use untagged enums",
+            name
+        )
+    })?;
+    let destination = destination.as_ref();
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|_| format_err!("Failed creating directory {:?}", parent))?;
+    }
+    fs::write(destination, bytes)
+        .with_context(|_| format_err!("Failed writing binary prefab {:?}", destination))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Prefab;
+
+    #[test]
+    fn binary_round_trip() {
+        let mut prefab = Prefab::new_main(1u32);
+        let child = prefab.add(Some(0), None);
+        prefab
+            .entity(child)
+            .unwrap()
+            .set_prefab("prefab/turret.bin");
+
+        let prefab = Prefab::<u32>::from_binary(&prefab.to_binary().unwrap()).unwrap();
+        let entities = prefab
+            .entities()
+            .map(|entity| (entity.parent, entity.data, entity.prefab()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (None, Some(1), None),
+                (Some(0), None, Some("prefab/turret.bin")),
+            ],
+            entities
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(Prefab::<u32>::from_binary(b"Prefab(entities: [])").is_err());
+    }
+}
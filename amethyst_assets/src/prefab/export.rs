@@ -0,0 +1,158 @@
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::Serialize;
+
+use amethyst_core::{
+    ecs::prelude::{Entity, ReadStorage, SystemData, World},
+    HierarchyExt, Parent,
+};
+use amethyst_error::{format_err, Error, ResultExt};
+
+use super::{Prefab, PrefabEntity};
+
+/// Trait for creating the prefab data of a single entity from its current state, the inverse of
+/// `PrefabData::add_to_entity`.
+///
+/// Used by `export_prefab` to write the entities of a running game back to a prefab, for example
+/// to save a level edited in game.
+pub trait PrefabExport<'a>: Sized {
+    /// `SystemData` needed to read the state of the entity
+    type SystemData: SystemData<'a>;
+
+    /// Create the prefab data of `entity`, `None` if it has none of the data.
+    ///
+    /// ### Parameters:
+    ///
+    /// - `entity`: `Entity` to read the components of
+    /// - `system_data`: `SystemData` needed to read the components
+    /// - `entities`: All the exported entities, in the order of the prefab, for the components
+    ///               referring to other entities.
+    fn export(
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        entities: &[Entity],
+    ) -> Result<Option<Self>, Error>;
+}
+
+/// Create a `Prefab` from the current state of `entities`.
+///
+/// The first entity becomes the main entity of the prefab. The other entities are parented to
+/// the exported entity of their `Parent`, and aren't parented if their parent isn't exported.
+///
+/// ```rust,ignore
+/// let prefab = export_prefab_tree::<MyPrefabData>(&world, level)?;
+/// fs::write("assets/prefab/level.ron", prefab.to_ron()?)?;
+/// ```
+pub fn export_prefab<'a, T>(world: &'a World, entities: &[Entity]) -> Result<Prefab<T>, Error>
+where
+    T: PrefabExport<'a>,
+{
+    if entities.is_empty() {
+        return Err(format_err!("A prefab can't be exported without entities"));
+    }
+    let parents = world.read_storage::<Parent>();
+    let mut system_data = T::SystemData::fetch(&world.res);
+    let prefab_entities = entities
+        .iter()
+        .enumerate()
+        .map(|(index, entity)| {
+            let parent = if index == 0 {
+                None
+            } else {
+                parent_index(&parents, *entity, entities)
+            };
+            let data = T::export(*entity, &mut system_data, entities)
+                .with_context(|_| format_err!("Failed exporting entity {}", index))?;
+            Ok(PrefabEntity::new(parent, data))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Prefab {
+        tag: None,
+        entities: prefab_entities,
+        counter: None,
+    })
+}
+
+/// Create a `Prefab` from the current state of `root` and all its descendants, see
+/// `export_prefab`.
+pub fn export_prefab_tree<'a, T>(world: &'a World, root: Entity) -> Result<Prefab<T>, Error>
+where
+    T: PrefabExport<'a>,
+{
+    let mut entities = world.descendants(root);
+    entities.insert(0, root);
+    export_prefab(world, &entities)
+}
+
+fn parent_index(
+    parents: &ReadStorage<'_, Parent>,
+    entity: Entity,
+    entities: &[Entity],
+) -> Option<usize> {
+    parents
+        .get(entity)
+        .and_then(|parent| entities.iter().position(|e| *e == parent.entity))
+}
+
+impl<T> Prefab<T>
+where
+    T: Serialize,
+{
+    /// Serialize the prefab to Ron, in the format loaded by `RonFormat`.
+    pub fn to_ron(&self) -> Result<String, Error> {
+        to_string_pretty(self, PrettyConfig::default())
+            .with_context(|_| format_err!("Failed serializing prefab"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, World},
+        Named, Parent, Transform,
+    };
+
+    use crate::Prefab;
+
+    use super::{export_prefab, export_prefab_tree};
+
+    type Data = (Option<Transform>, Option<Named>);
+
+    #[test]
+    fn exports_entities_with_their_parents() {
+        let mut world = World::new();
+        world.register::<Transform>();
+        world.register::<Named>();
+        world.register::<Parent>();
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(1.0, 2.0, 3.0);
+        let root = world.create_entity().with(Named::new("level")).build();
+        let child = world
+            .create_entity()
+            .with(transform.clone())
+            .with(Parent { entity: root })
+            .build();
+        let grandchild = world.create_entity().with(Parent { entity: child }).build();
+
+        let prefab = export_prefab_tree::<Data>(&world, root).unwrap();
+        let entities = prefab.entities().collect::<Vec<_>>();
+        assert_eq!(entities.len(), 3);
+        assert_eq!(entities[1].parent(), Some(0));
+        assert_eq!(entities[2].parent(), Some(1));
+        assert_eq!(
+            entities[0]
+                .data()
+                .unwrap()
+                .1
+                .as_ref()
+                .map(|name| &*name.name),
+            Some("level")
+        );
+        assert_eq!(entities[1].data().unwrap().0, Some(transform));
+
+        let prefab = export_prefab::<Data>(&world, &[child, grandchild]).unwrap();
+        assert_eq!(prefab.entities().nth(1).unwrap().parent(), Some(0));
+        let ron = prefab.to_ron().unwrap();
+        let loaded = ron::de::from_str::<Prefab<Data>>(&ron).unwrap();
+        assert_eq!(loaded.len(), 2);
+    }
+}
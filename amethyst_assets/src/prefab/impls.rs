@@ -1,10 +1,10 @@
 use amethyst_core::{
-    ecs::{Entity, WriteStorage},
+    ecs::{Entity, ReadStorage, WriteStorage},
     Named, Transform,
 };
 use amethyst_error::Error;
 
-use crate::{PrefabData, ProgressCounter};
+use crate::{PrefabData, PrefabExport, ProgressCounter};
 
 impl<'a, T> PrefabData<'a> for Option<T>
 where
@@ -77,6 +77,45 @@ impl<'a> PrefabData<'a> for Named {
     }
 }
 
+impl<'a, T> PrefabExport<'a> for Option<T>
+where
+    T: PrefabExport<'a>,
+{
+    type SystemData = <T as PrefabExport<'a>>::SystemData;
+
+    fn export(
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        entities: &[Entity],
+    ) -> Result<Option<Self>, Error> {
+        Ok(Some(T::export(entity, system_data, entities)?))
+    }
+}
+
+impl<'a> PrefabExport<'a> for Transform {
+    type SystemData = ReadStorage<'a, Transform>;
+
+    fn export(
+        entity: Entity,
+        storage: &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<Option<Self>, Error> {
+        Ok(storage.get(entity).cloned())
+    }
+}
+
+impl<'a> PrefabExport<'a> for Named {
+    type SystemData = ReadStorage<'a, Named>;
+
+    fn export(
+        entity: Entity,
+        storage: &mut Self::SystemData,
+        _: &[Entity],
+    ) -> Result<Option<Self>, Error> {
+        Ok(storage.get(entity).cloned())
+    }
+}
+
 macro_rules! impl_data {
     ( $($ty:ident:$i:tt),* ) => {
         #[allow(unused)]
@@ -121,6 +160,37 @@ macro_rules! impl_data {
     };
 }
 
+macro_rules! impl_export {
+    ( $($ty:ident:$i:tt),* ) => {
+        #[allow(unused)]
+        impl<'a, $($ty),*> PrefabExport<'a> for ( $( $ty , )* )
+            where $( $ty : PrefabExport<'a> ),*
+        {
+            type SystemData = (
+                $(
+                    $ty::SystemData,
+                )*
+            );
+
+            fn export(
+                entity: Entity,
+                system_data: &mut Self::SystemData,
+                entities: &[Entity],
+            ) -> Result<Option<Self>, Error> {
+                #![allow(unused_variables)]
+                Ok(Some((
+                    $(
+                        match $ty::export(entity, &mut system_data.$i, entities)? {
+                            Some(data) => data,
+                            None => return Ok(None),
+                        },
+                    )*
+                )))
+            }
+        }
+    };
+}
+
 impl_data!();
 impl_data!(A:0);
 impl_data!(A:0, B:1);
@@ -143,3 +213,26 @@ impl_data!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N
 impl_data!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18);
 impl_data!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18, T:19);
 impl_data!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18, T:19, U:20);
+
+impl_export!();
+impl_export!(A:0);
+impl_export!(A:0, B:1);
+impl_export!(A:0, B:1, C:2);
+impl_export!(A:0, B:1, C:2, D:3);
+impl_export!(A:0, B:1, C:2, D:3, E:4);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18, T:19);
+impl_export!(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12, N:13, O:14, P:15, Q:16, R:17, S:18, T:19, U:20);
@@ -10,10 +10,25 @@ use amethyst_error::Error;
 
 use crate::{Asset, AssetStorage, Format, Handle, Loader, Progress, ProgressCounter};
 
-pub use self::system::PrefabLoaderSystem;
+use self::nested::NestedPrefabFormat;
+
+pub use self::{
+    binary::{bake_prefab, PrefabBinaryFormat},
+    export::{export_prefab, export_prefab_tree, PrefabExport},
+    patch::PrefabPatchFormat,
+    spawner::{despawn_prefab, PrefabSpawner, SpawnedPrefab},
+    system::PrefabLoaderSystem,
+    template::{PrefabParameters, PrefabTemplateFormat},
+};
 
+mod binary;
+mod export;
 mod impls;
+mod nested;
+mod patch;
+mod spawner;
 mod system;
+mod template;
 
 /// Trait for loading a prefabs data for a single entity
 pub trait PrefabData<'a> {
@@ -113,6 +128,20 @@ pub struct Prefab<T> {
 
 /// Prefab data container for a single entity
 ///
+/// An entity can reference another prefab file with `prefab`. When loaded with
+/// `PrefabLoader::load_nested`, the entities of the referenced prefab are added to this prefab,
+/// parented to this entity. The data of the root entity of the referenced prefab is used for this
+/// entity if it has no data of its own, and otherwise goes to a new child entity.
+///
+/// ```ron
+/// Prefab(
+///     entities: [
+///         (data: (transform: (translation: (0.0, 0.0, 0.0)))),
+///         (parent: 0, data: (transform: (translation: (4.0, 0.0, 0.0))), prefab: "prefab/turret.ron"),
+///     ],
+/// )
+/// ```
+///
 /// ### Type parameters:
 ///
 /// - `T`: `PrefabData`
@@ -121,6 +150,8 @@ pub struct Prefab<T> {
 pub struct PrefabEntity<T> {
     parent: Option<usize>,
     data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefab: Option<String>,
 }
 
 impl<T> Default for PrefabEntity<T> {
@@ -132,7 +163,11 @@ impl<T> Default for PrefabEntity<T> {
 impl<T> PrefabEntity<T> {
     /// New prefab entity
     pub fn new(parent: Option<usize>, data: Option<T>) -> Self {
-        PrefabEntity { parent, data }
+        PrefabEntity {
+            parent,
+            data,
+            prefab: None,
+        }
     }
 
     /// Set parent index
@@ -140,11 +175,26 @@ impl<T> PrefabEntity<T> {
         self.parent = Some(parent);
     }
 
+    /// Get parent index
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
     /// Set data
     pub fn set_data(&mut self, data: T) {
         self.data = Some(data);
     }
 
+    /// Set the path of a prefab to add under this entity
+    pub fn set_prefab<N: Into<String>>(&mut self, path: N) {
+        self.prefab = Some(path.into());
+    }
+
+    /// Get the path of the prefab added under this entity
+    pub fn prefab(&self) -> Option<&str> {
+        self.prefab.as_ref().map(String::as_str)
+    }
+
     /// Get immutable access to the data
     pub fn data(&self) -> Option<&T> {
         self.data.as_ref()
@@ -439,6 +489,34 @@ where
             .load(name, format, options, progress, &self.storage)
     }
 
+    /// Load prefab from source, adding the prefabs referenced by its entities, see
+    /// `PrefabEntity`.
+    ///
+    /// Referenced prefabs are loaded with the same format and options before the prefab is
+    /// processed. They are tracked by `progress` as loaded along with the prefab, and changing
+    /// any of them hot reloads the prefab.
+    pub fn load_nested<F, N, P>(
+        &self,
+        name: N,
+        format: F,
+        options: F::Options,
+        progress: P,
+    ) -> Handle<Prefab<T>>
+    where
+        F: Format<Prefab<T>> + Clone + Sync,
+        F::Options: Clone + Sync,
+        N: Into<String>,
+        P: Progress,
+    {
+        self.loader.load(
+            name,
+            NestedPrefabFormat(format),
+            options,
+            progress,
+            &self.storage,
+        )
+    }
+
     /// Load prefab from explicit data
     pub fn load_from_data<P>(&self, data: Prefab<T>, progress: P) -> Handle<Prefab<T>>
     where
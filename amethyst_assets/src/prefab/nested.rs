@@ -0,0 +1,173 @@
+use std::{cell::RefCell, sync::Arc};
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Format, FormatValue, Prefab, Reload, SingleFile, Source};
+
+use super::PrefabEntity;
+
+/// Wraps the format given to `PrefabLoader::load_nested`, adding the prefabs referenced by
+/// `PrefabEntity::prefab` to the loaded prefab.
+///
+/// Referenced prefabs are loaded from the same source, with the same format and options. Changing
+/// any of the referenced files hot reloads the outer prefab.
+#[derive(Clone)]
+pub(crate) struct NestedPrefabFormat<F>(pub F);
+
+impl<T, F> Format<Prefab<T>> for NestedPrefabFormat<F>
+where
+    T: Send + Sync + 'static,
+    F: Format<Prefab<T>> + Clone + Sync,
+    F::Options: Clone + Sync,
+{
+    const NAME: &'static str = F::NAME;
+    type Options = F::Options;
+
+    fn import(
+        &self,
+        name: String,
+        source: Arc<dyn Source>,
+        options: Self::Options,
+        create_reload: bool,
+    ) -> Result<FormatValue<Prefab<T>>, Error> {
+        let dependencies = RefCell::new(Vec::new());
+        let load = |path: &str| {
+            dependencies.borrow_mut().push(path.to_owned());
+            self.0
+                .import(path.to_owned(), source.clone(), options.clone(), false)
+                .map(|value| value.data)
+        };
+        let mut prefab = load(&name)?;
+        prefab.add_references(&mut vec![name.clone()], &load)?;
+
+        let reload = if create_reload {
+            let modified = source
+                .modified(&name)
+                .with_context(|_| crate::error::Error::Source)?;
+            let reload = SingleFile::new(self.clone(), modified, options, name, source)
+                .with_dependencies(dependencies.into_inner());
+            Some(Box::new(reload) as Box<dyn Reload<Prefab<T>>>)
+        } else {
+            None
+        };
+        Ok(FormatValue {
+            data: prefab,
+            reload,
+        })
+    }
+}
+
+impl<T> Prefab<T> {
+    /// Add the entities of the prefabs referenced by the entities of this prefab.
+    ///
+    /// `loading` holds the paths of the prefabs being loaded, to detect cycles.
+    fn add_references<L>(&mut self, loading: &mut Vec<String>, load: &L) -> Result<(), Error>
+    where
+        L: Fn(&str) -> Result<Prefab<T>, Error>,
+    {
+        // Entities are only appended, and the added entities have their references resolved
+        let mut index = 0;
+        while index < self.entities.len() {
+            if let Some(path) = self.entities[index].prefab.take() {
+                if loading.contains(&path) {
+                    return Err(format_err!(
+                        "Prefab {:?} references itself through {:?}",
+                        path,
+                        loading
+                    ));
+                }
+                let mut nested = load(&path)
+                    .with_context(|_| format_err!("Failed loading prefab {:?}", path))?;
+                loading.push(path);
+                nested.add_references(loading, load)?;
+                loading.pop();
+                self.add_nested(index, nested);
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Add the entities of `nested` under the entity at `index`.
+    fn add_nested(&mut self, index: usize, nested: Prefab<T>) {
+        let mut entities = nested.entities.into_iter();
+        let root = match entities.next() {
+            Some(root) => root,
+            None => return,
+        };
+        let root_index = if self.entities[index].data.is_none() {
+            self.entities[index].data = root.data;
+            index
+        } else {
+            self.add(Some(index), root.data)
+        };
+        // Nested entity `i` is added at `first + i - 1`, its root maps to `root_index`
+        let first = self.entities.len();
+        let map = |parent: usize| match parent {
+            0 => root_index,
+            parent => first + parent - 1,
+        };
+        for entity in entities {
+            self.entities.push(PrefabEntity {
+                parent: entity.parent.map(map),
+                data: entity.data,
+                prefab: None,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_error::Error;
+
+    use crate::Prefab;
+
+    fn turret() -> Prefab<u32> {
+        let mut prefab = Prefab::new_main(10);
+        prefab.add(Some(0), Some(11));
+        prefab
+    }
+
+    #[test]
+    fn add_references() {
+        let mut prefab = Prefab::new_main(0);
+        let empty = prefab.add(Some(0), None);
+        prefab.entity(empty).unwrap().set_prefab("turret");
+        let full = prefab.add(Some(0), Some(2));
+        prefab.entity(full).unwrap().set_prefab("turret");
+
+        prefab
+            .add_references(&mut vec!["root".to_owned()], &|_| Ok(turret()))
+            .unwrap();
+
+        let entities = prefab
+            .entities()
+            .map(|entity| (entity.parent, entity.data))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (None, Some(0)),
+                (Some(0), Some(10)),
+                (Some(0), Some(2)),
+                (Some(1), Some(11)),
+                (Some(2), Some(10)),
+                (Some(4), Some(11)),
+            ],
+            entities
+        );
+    }
+
+    #[test]
+    fn detect_cycles() {
+        let load = |_: &str| -> Result<Prefab<u32>, Error> {
+            let mut prefab = Prefab::new_main(0);
+            prefab.entity(0).unwrap().set_prefab("root");
+            Ok(prefab)
+        };
+        let mut prefab = load("root").unwrap();
+        assert!(prefab
+            .add_references(&mut vec!["root".to_owned()], &load)
+            .is_err());
+    }
+}
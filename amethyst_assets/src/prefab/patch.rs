@@ -0,0 +1,449 @@
+use std::{cell::RefCell, fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Format, FormatValue, Prefab, Reload, RonFormat, SimpleFormat, SingleFile, Source};
+
+use super::template::literal_end;
+
+/// Format for loading `Prefab`s from Ron files, with support for patch files.
+///
+/// A patch file names the prefab it applies to, and overrides fields of some of its entities,
+/// found by index:
+///
+/// ```ron
+/// PrefabPatch(
+///     prefab: "prefab/enemies.ron",
+///     entities: {
+///         0: (data: (transform: (translation: (120.0, 40.0, 0.0)))),
+///         2: (data: (sprite_scene: (render: (sprite_number: 3)))),
+///     },
+/// )
+/// ```
+///
+/// Structs are patched field by field, and tuples or enum variants of the same shape element by
+/// element. Any other value, including lists, is replaced by the value of the patch. The patched
+/// prefab can itself be a patch file, and files that are not patches are loaded as regular
+/// prefabs, so this format can also be used for prefabs referenced by patched prefabs.
+///
+/// Changing the patch file or any of the prefabs it patches hot reloads the patched prefab.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PrefabPatchFormat;
+
+impl<T> Format<Prefab<T>> for PrefabPatchFormat
+where
+    T: for<'a> Deserialize<'a> + Send + Sync + 'static,
+{
+    const NAME: &'static str = "PrefabPatch";
+    type Options = ();
+
+    fn import(
+        &self,
+        name: String,
+        source: Arc<dyn Source>,
+        _: (),
+        create_reload: bool,
+    ) -> Result<FormatValue<Prefab<T>>, Error> {
+        let dependencies = RefCell::new(Vec::new());
+        let load = |path: &str| {
+            dependencies.borrow_mut().push(path.to_owned());
+            let bytes = source
+                .load(path)
+                .with_context(|_| crate::error::Error::Source)?;
+            String::from_utf8(bytes)
+                .with_context(|_| format_err!("Prefab {:?} is not valid UTF-8", path))
+        };
+        let document = patched_document(&name, &load, &mut Vec::new())?;
+        let data =
+            SimpleFormat::<Prefab<T>>::import(&RonFormat, document.to_string().into_bytes(), ())?;
+
+        let reload = if create_reload {
+            let modified = source
+                .modified(&name)
+                .with_context(|_| crate::error::Error::Source)?;
+            let reload = SingleFile::new(self.clone(), modified, (), name, source)
+                .with_dependencies(dependencies.into_inner());
+            Some(Box::new(reload) as Box<dyn Reload<Prefab<T>>>)
+        } else {
+            None
+        };
+        Ok(FormatValue { data, reload })
+    }
+}
+
+/// Load the document `name`, applying it to the prefab it patches if it is a patch.
+///
+/// `loading` holds the paths of the documents being loaded, to detect cycles.
+fn patched_document<L>(name: &str, load: &L, loading: &mut Vec<String>) -> Result<Document, Error>
+where
+    L: Fn(&str) -> Result<String, Error>,
+{
+    if loading.iter().any(|path| path == name) {
+        return Err(format_err!(
+            "Prefab patch {:?} applies to itself through {:?}",
+            name,
+            loading
+        ));
+    }
+    let document = Document::parse(&load(name)?)
+        .with_context(|_| format_err!("Failed parsing prefab {:?}", name))?;
+    let (base, entities) = match document.patch()? {
+        Some(patch) => patch,
+        None => return Ok(document),
+    };
+    loading.push(name.to_owned());
+    let mut base_document = patched_document(&base, load, loading)?;
+    loading.pop();
+    base_document.apply(entities)?;
+    for attribute in document.attributes {
+        if !base_document.attributes.contains(&attribute) {
+            base_document.attributes.push(attribute);
+        }
+    }
+    Ok(base_document)
+}
+
+/// A Ron value, keeping the source of everything but structs, tuples, lists and maps.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Atom(String),
+    Group {
+        name: Option<String>,
+        open: char,
+        items: Vec<(Option<String>, Node)>,
+    },
+}
+
+impl Node {
+    /// Override this value with `patch`.
+    fn merge(&mut self, patch: Node) {
+        let (patch_name, patch_open, patch_items) = match patch {
+            Node::Group { name, open, items } => (name, open, items),
+            atom => {
+                *self = atom;
+                return;
+            }
+        };
+        if let Node::Group { name, open, items } = self {
+            let has_keys = |items: &[(Option<String>, Node)]| {
+                !items.is_empty() && items.iter().all(|(key, _)| key.is_some())
+            };
+            let same_group = *open == patch_open && (patch_name.is_none() || *name == patch_name);
+            if same_group && *open != '[' && has_keys(items) && has_keys(&patch_items) {
+                for (key, value) in patch_items {
+                    match items.iter_mut().find(|(item_key, _)| *item_key == key) {
+                        Some((_, item)) => item.merge(value),
+                        None => items.push((key, value)),
+                    }
+                }
+                return;
+            }
+            if same_group && *open == '(' && *name == patch_name && items.len() == patch_items.len()
+            {
+                for ((_, item), (_, value)) in items.iter_mut().zip(patch_items) {
+                    item.merge(value);
+                }
+                return;
+            }
+        }
+        *self = Node::Group {
+            name: patch_name,
+            open: patch_open,
+            items: patch_items,
+        };
+    }
+
+    fn field(&self, field: &str) -> Option<&Node> {
+        match self {
+            Node::Group { items, .. } => items
+                .iter()
+                .find(|(key, _)| key.as_ref().map(String::as_str) == Some(field))
+                .map(|(_, value)| value),
+            Node::Atom(_) => None,
+        }
+    }
+
+    fn field_mut(&mut self, field: &str) -> Option<&mut Node> {
+        match self {
+            Node::Group { items, .. } => items
+                .iter_mut()
+                .find(|(key, _)| key.as_ref().map(String::as_str) == Some(field))
+                .map(|(_, value)| value),
+            Node::Atom(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Atom(source) => f.write_str(source),
+            Node::Group { name, open, items } => {
+                if let Some(name) = name {
+                    f.write_str(name)?;
+                }
+                write!(f, "{}", open)?;
+                for (i, (key, value)) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    if let Some(key) = key {
+                        write!(f, "{}: ", key)?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "{}", closing(*open))
+            }
+        }
+    }
+}
+
+/// A Ron file, made of its extension attributes and its value.
+#[derive(Clone, Debug, PartialEq)]
+struct Document {
+    attributes: Vec<String>,
+    root: Node,
+}
+
+impl Document {
+    fn parse(source: &str) -> Result<Document, Error> {
+        let mut parser = Parser { source, pos: 0 };
+        let mut attributes = Vec::new();
+        parser.skip_whitespace();
+        while parser.rest().starts_with("#!") {
+            parser.pos += 2;
+            attributes.push(format!("#!{}", parser.value()?));
+            parser.skip_whitespace();
+        }
+        let root = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < source.len() {
+            return Err(parser.error("Expected end of file"));
+        }
+        Ok(Document { attributes, root })
+    }
+
+    /// If this is a patch, get the path of the patched prefab and the patched entities.
+    fn patch(&self) -> Result<Option<(String, Vec<(usize, Node)>)>, Error> {
+        match &self.root {
+            Node::Group {
+                name: Some(name), ..
+            } if name == "PrefabPatch" => {}
+            _ => return Ok(None),
+        }
+        let base = match self.root.field("prefab") {
+            Some(Node::Atom(path)) => ron::de::from_str::<String>(path)
+                .with_context(|_| format_err!("Expected a path for `prefab`, got {}", path))?,
+            _ => return Err(format_err!("Prefab patch is missing the `prefab` field")),
+        };
+        let entities = match self.root.field("entities") {
+            Some(Node::Group {
+                open: '{', items, ..
+            }) => items
+                .iter()
+                .map(|(key, value)| {
+                    let key = key.as_ref().map(String::as_str).unwrap_or("");
+                    key.parse::<usize>()
+                        .map(|index| (index, value.clone()))
+                        .with_context(|_| format_err!("Expected an entity index, got {:?}", key))
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+            Some(_) => {
+                return Err(format_err!(
+                    "Expected a map of entity indices for `entities`"
+                ))
+            }
+        };
+        Ok(Some((base, entities)))
+    }
+
+    fn apply(&mut self, entities: Vec<(usize, Node)>) -> Result<(), Error> {
+        let items = match self.root.field_mut("entities") {
+            Some(Node::Group {
+                open: '[', items, ..
+            }) => items,
+            _ => return Err(format_err!("Patched prefab has no `entities` list")),
+        };
+        for (index, patch) in entities {
+            let count = items.len();
+            let (_, entity) = items.get_mut(index).ok_or_else(|| {
+                format_err!(
+                    "Prefab patch targets entity {}, but the prefab has {} entities",
+                    index,
+                    count
+                )
+            })?;
+            entity.merge(patch);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for attribute in &self.attributes {
+            writeln!(f, "{}", attribute)?;
+        }
+        write!(f, "{}", self.root)
+    }
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn rest(&self) -> &'s str {
+        &self.source[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        let line = self.source[..self.pos].matches('\n').count() + 1;
+        format_err!("{} at line {}", message, line)
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") || trimmed.starts_with("/*") {
+                self.pos = literal_end(self.source, self.pos).expect("Unreachable: comment");
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Node, Error> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(open @ '(') | Some(open @ '[') | Some(open @ '{') => self.group(None, open),
+            Some('"') | Some('\'') => {
+                let start = self.pos;
+                self.pos = literal_end(self.source, start).expect("Unreachable: literal");
+                Ok(Node::Atom(self.source[start..self.pos].to_owned()))
+            }
+            Some(_) => {
+                let token_len = self
+                    .rest()
+                    .find(|c: char| c.is_whitespace() || ",:()[]{}\"'".contains(c))
+                    .unwrap_or_else(|| self.rest().len());
+                if token_len == 0 {
+                    return Err(self.error("Unexpected character"));
+                }
+                let token = self.rest()[..token_len].to_owned();
+                self.pos += token_len;
+                let end = self.pos;
+                self.skip_whitespace();
+                if self.peek() == Some('(') {
+                    self.group(Some(token), '(')
+                } else {
+                    self.pos = end;
+                    Ok(Node::Atom(token))
+                }
+            }
+            None => Err(self.error("Unexpected end of file")),
+        }
+    }
+
+    fn group(&mut self, name: Option<String>, open: char) -> Result<Node, Error> {
+        let close = closing(open);
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(Node::Group { name, open, items });
+            }
+            let value = self.value()?;
+            self.skip_whitespace();
+            if self.peek() == Some(':') {
+                self.pos += 1;
+                items.push((Some(value.to_string()), self.value()?));
+            } else {
+                items.push((None, value));
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {}
+                _ => return Err(self.error(&format!("Expected `,` or `{}`", close))),
+            }
+        }
+    }
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_error::Error;
+
+    use super::{patched_document, Document};
+
+    fn load(path: &str) -> Result<String, Error> {
+        Ok(match path {
+            "base.ron" => {
+                "#![enable(implicit_some)]
+                Prefab(
+                    entities: [
+                        // Root
+                        (data: (position: (0.0, 1.0), sprite: (sheet: \"bat\", sprite_number: 0))),
+                        (parent: 0, data: (color: Rgba(1.0, 1.0, 1.0, 1.0), shape: Circle(1.0))),
+                    ],
+                )"
+            }
+            "patch.ron" => {
+                "PrefabPatch(
+                    prefab: \"base.ron\",
+                    entities: {
+                        0: (data: (sprite: (sprite_number: 3))),
+                        1: (data: (color: Rgba(1.0, 0.0, 0.0, 1.0), shape: Square(2.0), extra: ())),
+                    },
+                )"
+            }
+            "cycle.ron" => "PrefabPatch(prefab: \"cycle.ron\")",
+            _ => unreachable!(),
+        }
+        .to_owned())
+    }
+
+    #[test]
+    fn apply_patch() {
+        let document = patched_document("patch.ron", &load, &mut Vec::new()).unwrap();
+        assert_eq!(
+            "#![enable(implicit_some)]\nPrefab(entities: [\
+             (data: (position: (0.0, 1.0), sprite: (sheet: \"bat\", sprite_number: 3))), \
+             (parent: 0, data: (color: Rgba(1.0, 0.0, 0.0, 1.0), shape: Square(2.0), extra: ()))])",
+            document.to_string()
+        );
+    }
+
+    #[test]
+    fn plain_prefab() {
+        let source = load("base.ron").unwrap();
+        let document = patched_document("base.ron", &load, &mut Vec::new()).unwrap();
+        assert_eq!(Document::parse(&source).unwrap(), document);
+    }
+
+    #[test]
+    fn detect_cycles() {
+        assert!(patched_document("cycle.ron", &load, &mut Vec::new()).is_err());
+    }
+}
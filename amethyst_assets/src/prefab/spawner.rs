@@ -0,0 +1,259 @@
+use std::{collections::HashMap, ops::Deref};
+
+use shred_derive::SystemData;
+
+use amethyst_core::{
+    ecs::{
+        prelude::{Entities, Entity, Join, LazyUpdate, Read, SystemData, WriteStorage},
+        storage::{MaskedStorage, Storage},
+    },
+    Parent, Transform,
+};
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{AssetStorage, Handle};
+
+use super::{system::instantiate, Prefab, PrefabData, PrefabTag};
+
+/// Entities created by `PrefabSpawner`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnedPrefab {
+    /// The entities of the prefab, in the same order as in the prefab, starting with the root.
+    pub entities: Vec<Entity>,
+}
+
+impl SpawnedPrefab {
+    /// The root entity, created for the first entity of the prefab.
+    pub fn root(&self) -> Entity {
+        self.entities[0]
+    }
+
+    /// The entities created for the other entities of the prefab.
+    pub fn children(&self) -> &[Entity] {
+        &self.entities[1..]
+    }
+}
+
+/// Helper structure for instantiating loaded prefabs right away.
+///
+/// Unlike adding a `Handle<Prefab<T>>` to an entity and waiting for the `PrefabLoaderSystem`, the
+/// entities are created immediately and returned. All created entities, including the root, get a
+/// `PrefabTag`.
+///
+/// ### Example
+///
+/// ```rust,ignore
+/// let bullet = world.exec(|mut spawner: PrefabSpawner<'_, BulletPrefab>| {
+///     spawner.spawn_at(&bullet_prefab, transform)
+/// })?;
+/// ```
+///
+/// ### Type parameters:
+///
+/// - `T`: `PrefabData`
+#[derive(SystemData)]
+pub struct PrefabSpawner<'a, T>
+where
+    T: PrefabData<'a> + Send + Sync + 'static,
+{
+    entities: Entities<'a>,
+    storage: Read<'a, AssetStorage<Prefab<T>>>,
+    parents: WriteStorage<'a, Parent>,
+    tags: WriteStorage<'a, PrefabTag<T>>,
+    lazy: Read<'a, LazyUpdate>,
+    data: T::SystemData,
+}
+
+impl<'a, T> PrefabSpawner<'a, T>
+where
+    T: PrefabData<'a> + Send + Sync + 'static,
+{
+    /// Instantiate the prefab on new entities.
+    ///
+    /// Fails if the prefab is not loaded yet.
+    pub fn spawn(&mut self, handle: &Handle<Prefab<T>>) -> Result<SpawnedPrefab, Error> {
+        let prefab = self
+            .storage
+            .get(handle)
+            .ok_or_else(|| format_err!("Prefab {} is not loaded yet", handle.id()))?;
+        let root = self.entities.create();
+        let mut entities = Vec::with_capacity(prefab.len());
+        instantiate(
+            prefab,
+            root,
+            &self.entities,
+            &mut self.parents,
+            &mut self.tags,
+            &mut self.data,
+            &mut entities,
+        )
+        .with_context(|_| format_err!("Failed spawning prefab {}", handle.id()))?;
+        self.tags.insert(
+            root,
+            PrefabTag::new(
+                prefab
+                    .tag
+                    .expect("Unreachable: Every loaded prefab should have a `PrefabTag`"),
+            ),
+        )?;
+        Ok(SpawnedPrefab { entities })
+    }
+
+    /// Instantiate the prefab on new entities, placing the root at `transform`.
+    ///
+    /// The `Transform` is inserted when the world is maintained, replacing any `Transform` the
+    /// prefab gives to the root.
+    pub fn spawn_at(
+        &mut self,
+        handle: &Handle<Prefab<T>>,
+        transform: Transform,
+    ) -> Result<SpawnedPrefab, Error> {
+        let spawned = self.spawn(handle)?;
+        self.lazy.insert(spawned.root(), transform);
+        Ok(spawned)
+    }
+
+    /// Delete a spawned prefab, see `despawn_prefab`.
+    pub fn despawn(&self, root: Entity) -> Result<(), Error> {
+        despawn_prefab(&self.entities, &self.parents, root)
+    }
+}
+
+/// Delete `root` and all the entities below it in the hierarchy, like the entities of a spawned
+/// prefab.
+///
+/// Unlike waiting for the `TransformSystem` to delete the children of deleted entities, the whole
+/// hierarchy is deleted at once, including children not yet seen by the `ParentHierarchy`.
+///
+/// Deleting the entities drops the asset handles of their components when the world is
+/// maintained. Asset storages free the assets no longer referenced by any handle on their next
+/// `process`, so unloading a level only requires despawning its entities and dropping the
+/// handles kept elsewhere, like the handle of the prefab itself.
+pub fn despawn_prefab<D>(
+    entities: &Entities<'_>,
+    parents: &Storage<'_, Parent, D>,
+    root: Entity,
+) -> Result<(), Error>
+where
+    D: Deref<Target = MaskedStorage<Parent>>,
+{
+    for entity in Some(root)
+        .into_iter()
+        .chain(descendants(entities, parents, root))
+    {
+        entities
+            .delete(entity)
+            .with_context(|_| format_err!("Failed despawning prefab entity {:?}", entity))?;
+    }
+    Ok(())
+}
+
+/// Get all the entities below `root` in the hierarchy.
+pub(crate) fn descendants<D>(
+    entities: &Entities<'_>,
+    parents: &Storage<'_, Parent, D>,
+    root: Entity,
+) -> Vec<Entity>
+where
+    D: Deref<Target = MaskedStorage<Parent>>,
+{
+    let mut children = HashMap::<Entity, Vec<Entity>>::new();
+    for (entity, parent) in (&**entities, parents).join() {
+        children.entry(parent.entity).or_default().push(entity);
+    }
+    let mut descendants = Vec::new();
+    let mut next = vec![root];
+    while let Some(entity) = next.pop() {
+        if let Some(children) = children.remove(&entity) {
+            descendants.extend_from_slice(&children);
+            next.extend(children);
+        }
+    }
+    descendants
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_core::{
+        ecs::{RunNow, World},
+        Parent, Time, Transform,
+    };
+
+    use crate::{AssetStorage, Handle, Loader, Prefab, PrefabLoaderSystem};
+
+    use super::PrefabSpawner;
+
+    fn setup() -> (
+        World,
+        PrefabLoaderSystem<Transform>,
+        Handle<Prefab<Transform>>,
+    ) {
+        let mut world = World::new();
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        world.add_resource(pool.clone());
+        world.add_resource(Loader::new(".", pool));
+        world.add_resource(Time::default());
+        let mut system = PrefabLoaderSystem::<Transform>::default();
+        RunNow::setup(&mut system, &mut world.res);
+
+        let mut prefab = Prefab::new_main(Transform::default());
+        prefab.add(Some(0), Some(Transform::default()));
+        let handle = world.read_resource::<Loader>().load_from_data(
+            prefab,
+            (),
+            &world.read_resource::<AssetStorage<Prefab<Transform>>>(),
+        );
+        system.run_now(&world.res);
+        (world, system, handle)
+    }
+
+    #[test]
+    fn spawn_prefab() {
+        let (world, _, handle) = setup();
+        let spawned = world
+            .exec(|mut spawner: PrefabSpawner<'_, Transform>| spawner.spawn(&handle))
+            .unwrap();
+        assert_eq!(2, spawned.entities.len());
+        assert!(world
+            .read_storage::<Transform>()
+            .get(spawned.root())
+            .is_some());
+        assert_eq!(
+            Some(&Parent {
+                entity: spawned.root()
+            }),
+            world.read_storage::<Parent>().get(spawned.children()[0])
+        );
+    }
+
+    #[test]
+    fn despawn_prefab() {
+        let (mut world, mut system, handle) = setup();
+        let spawned = world
+            .exec(|mut spawner: PrefabSpawner<'_, Transform>| spawner.spawn(&handle))
+            .unwrap();
+        let other = world
+            .exec(|mut spawner: PrefabSpawner<'_, Transform>| spawner.spawn(&handle))
+            .unwrap();
+        world
+            .exec(|spawner: PrefabSpawner<'_, Transform>| spawner.despawn(spawned.root()))
+            .unwrap();
+        world.maintain();
+        assert!(spawned
+            .entities
+            .iter()
+            .all(|entity| !world.is_alive(*entity)));
+        assert!(other.entities.iter().all(|entity| world.is_alive(*entity)));
+
+        let weak = handle.downgrade();
+        drop(handle);
+        world.delete_entities(&other.entities).unwrap();
+        world.maintain();
+        system.run_now(&world.res);
+        assert!(weak.is_dead());
+    }
+}
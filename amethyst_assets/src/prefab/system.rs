@@ -7,16 +7,23 @@ use amethyst_core::{
         storage::ComponentEvent, BitSet, Entities, Entity, Join, Read, ReadExpect, ReadStorage,
         ReaderId, Resources, System, Write, WriteStorage,
     },
+    shrev::EventChannel,
     ArcThreadPool, Parent, Time,
 };
 use amethyst_error::{format_err, Error, ResultExt};
 
-use crate::{AssetStorage, Completion, Handle, HotReloadStrategy, ProcessingState};
+use crate::{AssetStorage, Completion, Handle, HotReloadStrategy, ProcessingState, ReloadEvent};
 
-use super::{Prefab, PrefabData, PrefabTag};
+use super::{spawner::descendants, Prefab, PrefabData, PrefabTag};
 
 /// System that load `Prefab`s for `PrefabData` `T`.
 ///
+/// When a prefab is hot reloaded, the entities created from it are deleted and the prefab is
+/// instantiated again on the entities holding its handle. Only the entities tagged with the
+/// previous version of that prefab are deleted, so entities of other prefabs below the same root
+/// are kept. Components of the previous version of the prefab are kept on the root entities
+/// unless the new version replaces them.
+///
 /// ### Type parameters:
 ///
 /// - `T`: `PrefabData`
@@ -26,7 +33,10 @@ pub struct PrefabLoaderSystem<T> {
     finished: Vec<Entity>,
     to_process: BitSet,
     insert_reader: Option<ReaderId<ComponentEvent>>,
+    reload_reader: Option<ReaderId<ReloadEvent>>,
     next_tag: u64,
+    /// Tag of the prefab instantiated on each root entity.
+    instances: HashMap<Entity, u64>,
 }
 
 impl<T> Default for PrefabLoaderSystem<T> {
@@ -37,7 +47,9 @@ impl<T> Default for PrefabLoaderSystem<T> {
             finished: Vec::default(),
             to_process: BitSet::default(),
             insert_reader: None,
+            reload_reader: None,
             next_tag: 0,
+            instances: HashMap::new(),
         }
     }
 }
@@ -53,6 +65,7 @@ where
         Read<'a, Time>,
         ReadExpect<'a, ArcThreadPool>,
         Option<Read<'a, HotReloadStrategy>>,
+        Read<'a, EventChannel<ReloadEvent>>,
         WriteStorage<'a, Parent>,
         WriteStorage<'a, PrefabTag<T>>,
         T::SystemData,
@@ -66,6 +79,7 @@ where
             time,
             pool,
             strategy,
+            reload_events,
             mut parents,
             mut tags,
             mut prefab_system_data,
@@ -105,59 +119,52 @@ where
                     self.to_process.add(*id);
                 }
             });
+        let reloaded = reload_events
+            .read(self.reload_reader.as_mut().expect(
+                "`PrefabLoaderSystem::setup` was not called before `PrefabLoaderSystem::run`",
+            ))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !reloaded.is_empty() {
+            self.instances
+                .retain(|entity, _| entities.is_alive(*entity));
+            for (root_entity, handle) in (&*entities, &prefab_handles).join() {
+                if self.to_process.contains(root_entity.id())
+                    || !reloaded.iter().any(|event| event.is(handle))
+                {
+                    continue;
+                }
+                let tag = match self.instances.get(&root_entity) {
+                    Some(tag) => *tag,
+                    None => continue,
+                };
+                for entity in descendants(&entities, &parents, root_entity) {
+                    if tags.get(entity).map(PrefabTag::tag) == Some(tag) {
+                        if let Err(err) = entities.delete(entity) {
+                            error!("Failed deleting entity of reloaded prefab: {}", err);
+                        }
+                    }
+                }
+                self.to_process.add(root_entity.id());
+            }
+        }
         self.finished.clear();
         for (root_entity, handle, _) in (&*entities, &prefab_handles, &self.to_process).join() {
             if let Some(prefab) = prefab_storage.get(handle) {
                 self.finished.push(root_entity);
-                // create entities
-                self.entities.clear();
-                self.entities.push(root_entity);
-
-                let mut children = HashMap::new();
-                for entity_data in prefab.entities.iter().skip(1) {
-                    let new_entity = entities.create();
-                    self.entities.push(new_entity);
-                    if let Some(parent) = entity_data.parent {
-                        parents
-                            .insert(
-                                new_entity,
-                                Parent {
-                                    entity: self.entities[parent],
-                                },
-                            )
-                            .expect("Unable to insert `Parent` for prefab");
-
-                        children
-                            .entry(parent)
-                            .or_insert(vec![])
-                            .push(new_entity.clone());
-                    }
-                    tags.insert(
-                        new_entity,
-                        PrefabTag::new(
-                            prefab.tag.expect(
-                                "Unreachable: Every loaded prefab should have a `PrefabTag`",
-                            ),
-                        ),
-                    )
-                    .expect("Unable to insert `PrefabTag` for prefab entity");
-                }
-                // create components
-                for (index, entity_data) in prefab.entities.iter().enumerate() {
-                    if let Some(ref prefab_data) = &entity_data.data {
-                        prefab_data
-                            .add_to_entity(
-                                self.entities[index],
-                                &mut prefab_system_data,
-                                &self.entities,
-                                children
-                                    .get(&index)
-                                    .map(|children| &children[..])
-                                    .unwrap_or(&[]),
-                            )
-                            .expect("Unable to add prefab system data to entity");
-                    }
+                if let Some(tag) = prefab.tag {
+                    self.instances.insert(root_entity, tag);
                 }
+                instantiate(
+                    prefab,
+                    root_entity,
+                    &entities,
+                    &mut parents,
+                    &mut tags,
+                    &mut prefab_system_data,
+                    &mut self.entities,
+                )
+                .expect("Unable to add prefab system data to entity");
             }
         }
 
@@ -170,5 +177,74 @@ where
         use amethyst_core::ecs::prelude::SystemData;
         Self::SystemData::setup(res);
         self.insert_reader = Some(WriteStorage::<Handle<Prefab<T>>>::fetch(&res).register_reader());
+        self.reload_reader = Some(
+            res.fetch_mut::<EventChannel<ReloadEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Create the entities of `prefab` below `root`, and add the prefab data to them.
+///
+/// `created` is filled with the entities of the prefab, in the order of the prefab entities and
+/// starting with `root`. All entities but `root` get a `PrefabTag`.
+pub(crate) fn instantiate<'a, T>(
+    prefab: &Prefab<T>,
+    root: Entity,
+    entities: &Entities<'_>,
+    parents: &mut WriteStorage<'_, Parent>,
+    tags: &mut WriteStorage<'_, PrefabTag<T>>,
+    system_data: &mut T::SystemData,
+    created: &mut Vec<Entity>,
+) -> Result<(), Error>
+where
+    T: PrefabData<'a> + Send + Sync + 'static,
+{
+    let tag = prefab
+        .tag
+        .expect("Unreachable: Every loaded prefab should have a `PrefabTag`");
+
+    // create entities
+    created.clear();
+    created.push(root);
+    for _ in 1..prefab.entities.len() {
+        let new_entity = entities.create();
+        created.push(new_entity);
+        tags.insert(new_entity, PrefabTag::new(tag))
+            .with_context(|_| format_err!("Unable to insert `PrefabTag` for prefab entity"))?;
+    }
+
+    let mut children = HashMap::new();
+    for (index, entity_data) in prefab.entities.iter().enumerate().skip(1) {
+        if let Some(parent) = entity_data.parent {
+            parents
+                .insert(
+                    created[index],
+                    Parent {
+                        entity: created[parent],
+                    },
+                )
+                .with_context(|_| format_err!("Unable to insert `Parent` for prefab"))?;
+            children
+                .entry(parent)
+                .or_insert_with(Vec::new)
+                .push(created[index]);
+        }
+    }
+
+    // create components
+    for (index, entity_data) in prefab.entities.iter().enumerate() {
+        if let Some(ref prefab_data) = &entity_data.data {
+            prefab_data.add_to_entity(
+                created[index],
+                system_data,
+                created,
+                children
+                    .get(&index)
+                    .map(|children| &children[..])
+                    .unwrap_or(&[]),
+            )?;
+        }
     }
+    Ok(())
 }
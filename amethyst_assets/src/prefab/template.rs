@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Prefab, RonFormat, SimpleFormat};
+
+const DECLARATION: &str = "#![parameters(";
+
+/// Values for the parameters of a prefab loaded with `PrefabTemplateFormat`.
+///
+/// Values are stored as Ron source, and replace the parameters as is.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PrefabParameters {
+    values: HashMap<String, String>,
+}
+
+impl PrefabParameters {
+    /// Create an empty set of parameters, using the defaults declared by the prefab.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the parameter `name` to the Ron serialization of `value`.
+    pub fn with<N, V>(mut self, name: N, value: &V) -> Result<Self, Error>
+    where
+        N: Into<String>,
+        V: Serialize,
+    {
+        let name = name.into();
+        let value = ron::ser::to_string(value)
+            .with_context(|_| format_err!("Failed serializing prefab parameter `{}`", name))?;
+        self.values.insert(name, value);
+        Ok(self)
+    }
+
+    /// Set the parameter `name` to the given Ron source.
+    pub fn with_raw<N, S>(mut self, name: N, source: S) -> Self
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.values.insert(name.into(), source.into());
+        self
+    }
+
+    /// Get the Ron source of the parameter `name`, if set.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+}
+
+/// Format for loading parameterized `Prefab`s from Ron files.
+///
+/// The file is a regular prefab, where `$name` is replaced by the value of the parameter `name`
+/// given in the `PrefabParameters` options. Default values can be declared at the top of the file,
+/// before the parameters are used:
+///
+/// ```ron
+/// #![enable(implicit_some)]
+/// #![parameters(speed: 1.0, texture: "texture/bat.png")]
+/// Prefab(
+///     entities: [
+///         (
+///             data: (
+///                 speed: $speed,
+///                 texture: File($texture, Png, (channel: Srgb)),
+///             ),
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// Parameters in strings and comments are left untouched. Loading fails when a parameter has no
+/// value and no default.
+///
+/// ```rust,ignore
+/// let parameters = PrefabParameters::new().with("speed", &2.5)?;
+/// let handle = loader.load("prefab/bat.ron", PrefabTemplateFormat, parameters, ());
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PrefabTemplateFormat;
+
+impl<T> SimpleFormat<Prefab<T>> for PrefabTemplateFormat
+where
+    T: for<'a> Deserialize<'a> + Send + Sync + 'static,
+{
+    const NAME: &'static str = "PrefabTemplate";
+    type Options = PrefabParameters;
+
+    fn import(&self, bytes: Vec<u8>, parameters: PrefabParameters) -> Result<Prefab<T>, Error> {
+        let source = String::from_utf8(bytes)
+            .with_context(|_| format_err!("Prefab template is not valid UTF-8"))?;
+        let source = substitute(&source, &parameters)?;
+        SimpleFormat::<Prefab<T>>::import(&RonFormat, source.into_bytes(), ())
+    }
+}
+
+/// Replace the parameters in `source` by their values, removing the declarations.
+fn substitute(source: &str, parameters: &PrefabParameters) -> Result<String, Error> {
+    let mut defaults = HashMap::new();
+    let mut out = String::with_capacity(source.len());
+    let mut i = 0;
+    while i < source.len() {
+        let rest = &source[i..];
+        if let Some(end) = literal_end(source, i) {
+            out.push_str(&source[i..end]);
+            i = end;
+        } else if rest.starts_with(DECLARATION) {
+            let start = i + DECLARATION.len();
+            let end = declaration_end(source, start)?;
+            parse_declarations(&source[start..end], &mut defaults)?;
+            i = end + 2;
+        } else if rest.starts_with('$') {
+            let name_len = rest[1..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(rest.len() - 1);
+            let name = &rest[1..=name_len];
+            if name.is_empty() {
+                return Err(format_err!("Expected a parameter name after `$`"));
+            }
+            let value = parameters
+                .get(name)
+                .or_else(|| defaults.get(name).map(String::as_str))
+                .ok_or_else(|| format_err!("Missing value for prefab parameter `${}`", name))?;
+            out.push_str(value);
+            i += 1 + name_len;
+        } else {
+            let c = rest.chars().next().expect("Unreachable: rest is not empty");
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+    Ok(out)
+}
+
+/// If a string, char literal or comment starts at `start`, get the index right after its end.
+pub(super) fn literal_end(source: &str, start: usize) -> Option<usize> {
+    let rest = &source[start..];
+    let quoted_end = |quote: char| {
+        let mut escaped = false;
+        rest.char_indices()
+            .skip(1)
+            .find(|&(_, c)| {
+                let end = !escaped && c == quote;
+                escaped = !escaped && c == '\\';
+                end
+            })
+            .map(|(offset, _)| start + offset + 1)
+            .unwrap_or_else(|| source.len())
+    };
+    if rest.starts_with('"') {
+        Some(quoted_end('"'))
+    } else if rest.starts_with('\'') {
+        Some(quoted_end('\''))
+    } else if rest.starts_with("//") {
+        Some(rest.find('\n').map_or(source.len(), |end| start + end))
+    } else if rest.starts_with("/*") {
+        Some(rest.find("*/").map_or(source.len(), |end| start + end + 2))
+    } else {
+        None
+    }
+}
+
+/// Get the index of the `)]` closing the declaration whose content starts at `start`.
+fn declaration_end(source: &str, start: usize) -> Result<usize, Error> {
+    let mut depth = 0;
+    let mut i = start;
+    while i < source.len() {
+        if let Some(end) = literal_end(source, i) {
+            i = end;
+            continue;
+        }
+        let c = source[i..]
+            .chars()
+            .next()
+            .expect("Unreachable: i is in bounds");
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => {
+                if source[i..].starts_with(")]") {
+                    return Ok(i);
+                }
+                break;
+            }
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    Err(format_err!(
+        "Unterminated `#![parameters(...)]` declaration"
+    ))
+}
+
+/// Parse `name: value` pairs separated by commas into `defaults`.
+fn parse_declarations(content: &str, defaults: &mut HashMap<String, String>) -> Result<(), Error> {
+    let mut depth = 0;
+    let mut item_start = 0;
+    let mut i = 0;
+    while i <= content.len() {
+        if i < content.len() {
+            if let Some(end) = literal_end(content, i) {
+                i = end;
+                continue;
+            }
+        }
+        let c = content[i..].chars().next();
+        match c {
+            Some('(') | Some('[') | Some('{') => depth += 1,
+            Some(')') | Some(']') | Some('}') => depth -= 1,
+            Some(',') | None if depth == 0 => {
+                let item = content[item_start..i].trim();
+                if !item.is_empty() {
+                    let colon = item.find(':').ok_or_else(|| {
+                        format_err!(
+                            "Expected `name: value` in prefab parameters, got `{}`",
+                            item
+                        )
+                    })?;
+                    let name = item[..colon].trim();
+                    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                        return Err(format_err!("Invalid prefab parameter name `{}`", name));
+                    }
+                    defaults.insert(name.to_owned(), item[colon + 1..].trim().to_owned());
+                }
+                item_start = i + 1;
+            }
+            _ => {}
+        }
+        i += c.map_or(1, char::len_utf8);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{substitute, PrefabParameters};
+
+    #[test]
+    fn substitutes_parameters() {
+        let source = "#![parameters(speed: 1.0, name: \"a, b\")]\n(speed: $speed, name: $name)";
+        let parameters = PrefabParameters::new().with("speed", &2.5).unwrap();
+        assert_eq!(
+            "\n(speed: 2.5, name: \"a, b\")",
+            substitute(source, &parameters).unwrap()
+        );
+    }
+
+    #[test]
+    fn ignores_strings_and_comments() {
+        let source = "// $comment\n(name: \"$name\", value: $value)";
+        let parameters = PrefabParameters::new().with_raw("value", "Some(3)");
+        assert_eq!(
+            "// $comment\n(name: \"$name\", value: Some(3))",
+            substitute(source, &parameters).unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_parameter() {
+        assert!(substitute("(value: $value)", &PrefabParameters::new()).is_err());
+    }
+}
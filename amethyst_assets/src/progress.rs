@@ -41,12 +41,46 @@ impl Progress for () {
     fn create_tracker(self) {}
 }
 
+/// Loading state of an asset tracked by a `ProgressCounter`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AssetState {
+    /// Waiting for a worker thread.
+    Queued,
+    /// Being read from its source and imported by its format.
+    Loading,
+    /// Imported, waiting to be processed into its asset storage.
+    Processing,
+    /// Loaded successfully.
+    Loaded,
+    /// Failed to load, with the error and its causes.
+    Failed(String),
+}
+
+/// Loading progress of a single asset tracked by a `ProgressCounter`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssetProgress {
+    /// Id of the handle to the asset.
+    pub handle_id: u32,
+    /// Name of the asset type, see `Asset::NAME`.
+    pub asset_type_name: &'static str,
+    /// Name the asset was loaded with, usually its path.
+    pub asset_name: String,
+    /// Loading state of the asset.
+    pub state: AssetState,
+    /// Number of bytes read from the source of the asset.
+    pub bytes: usize,
+}
+
 /// A progress tracker which is passed to the `Loader`
 /// in order to check how many assets are loaded.
+///
+/// Besides counting assets, the counter keeps the `AssetProgress` of every asset loaded by the
+/// `Loader`, so loading screens can show what is loading and what failed.
 #[derive(Default)]
 pub struct ProgressCounter {
+    assets: Arc<Mutex<Vec<AssetProgress>>>,
     errors: Arc<Mutex<Vec<AssetErrorMeta>>>,
-    num_assets: usize,
+    num_assets: Arc<AtomicUsize>,
     num_failed: Arc<AtomicUsize>,
     num_loading: Arc<AtomicUsize>,
 }
@@ -65,7 +99,7 @@ impl ProgressCounter {
 
     /// Returns the number of assets this struct is tracking.
     pub fn num_assets(&self) -> usize {
-        self.num_assets
+        self.num_assets.load(Ordering::Relaxed)
     }
 
     /// Returns the number of assets that have failed.
@@ -80,7 +114,7 @@ impl ProgressCounter {
 
     /// Returns the number of assets that have successfully loaded.
     pub fn num_finished(&self) -> usize {
-        self.num_assets - self.num_loading() - self.num_failed()
+        self.num_assets() - self.num_loading() - self.num_failed()
     }
 
     /// Returns `Completion::Complete` if all tracked assets are finished.
@@ -99,23 +133,49 @@ impl ProgressCounter {
     pub fn is_complete(&self) -> bool {
         self.complete() == Completion::Complete
     }
+
+    /// Returns the progress of every tracked asset, in the order they were queued.
+    pub fn assets(&self) -> Vec<AssetProgress> {
+        self.assets.lock().clone()
+    }
+
+    /// Returns the number of bytes read from sources for the tracked assets.
+    pub fn bytes_loaded(&self) -> usize {
+        self.assets.lock().iter().map(|asset| asset.bytes).sum()
+    }
+
+    /// Returns the percentage of tracked assets that are no longer loading, either because they
+    /// have loaded or failed.
+    pub fn percent_complete(&self) -> f32 {
+        let num_assets = self.num_assets();
+        if num_assets == 0 {
+            100.0
+        } else {
+            100.0 * (num_assets - self.num_loading()) as f32 / num_assets as f32
+        }
+    }
 }
 
 impl<'a> Progress for &'a mut ProgressCounter {
     type Tracker = ProgressCounterTracker;
 
     fn add_assets(&mut self, num: usize) {
-        self.num_assets += num;
+        self.num_assets.fetch_add(num, Ordering::Relaxed);
     }
 
     fn create_tracker(self) -> Self::Tracker {
+        let assets = self.assets.clone();
         let errors = self.errors.clone();
+        let num_assets = self.num_assets.clone();
         let num_failed = self.num_failed.clone();
         let num_loading = self.num_loading.clone();
         num_loading.fetch_add(1, Ordering::Relaxed);
 
         ProgressCounterTracker {
+            assets,
+            index: None,
             errors,
+            num_assets,
             num_failed,
             num_loading,
         }
@@ -125,13 +185,67 @@ impl<'a> Progress for &'a mut ProgressCounter {
 /// Progress tracker for `ProgressCounter`.
 #[derive(Default)]
 pub struct ProgressCounterTracker {
+    assets: Arc<Mutex<Vec<AssetProgress>>>,
+    index: Option<usize>,
     errors: Arc<Mutex<Vec<AssetErrorMeta>>>,
+    num_assets: Arc<AtomicUsize>,
     num_failed: Arc<AtomicUsize>,
     num_loading: Arc<AtomicUsize>,
 }
 
+impl ProgressCounterTracker {
+    fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut AssetProgress),
+    {
+        if let Some(index) = self.index {
+            f(&mut self.assets.lock()[index]);
+        }
+    }
+}
+
 impl Tracker for ProgressCounterTracker {
+    fn queued(&mut self, handle_id: u32, asset_type_name: &'static str, asset_name: &str) {
+        let mut assets = self.assets.lock();
+        self.index = Some(assets.len());
+        assets.push(AssetProgress {
+            handle_id,
+            asset_type_name,
+            asset_name: asset_name.to_owned(),
+            state: AssetState::Queued,
+            bytes: 0,
+        });
+    }
+
+    fn loading(&mut self) {
+        self.update(|asset| asset.state = AssetState::Loading);
+    }
+
+    fn imported(&mut self, bytes: usize) {
+        self.update(|asset| {
+            asset.state = AssetState::Processing;
+            asset.bytes = bytes;
+        });
+    }
+
+    fn nested(&mut self, asset_name: &str, bytes: usize) {
+        let index = match self.index {
+            Some(index) => index,
+            None => return,
+        };
+        let mut assets = self.assets.lock();
+        let nested = AssetProgress {
+            asset_name: asset_name.to_owned(),
+            state: AssetState::Loaded,
+            bytes,
+            ..assets[index].clone()
+        };
+        assets.push(nested);
+        self.num_assets.fetch_add(1, Ordering::Relaxed);
+    }
+
     fn success(self: Box<Self>) {
+        self.update(|asset| asset.state = AssetState::Loaded);
         self.num_loading.fetch_sub(1, Ordering::Relaxed);
     }
 
@@ -143,6 +257,12 @@ impl Tracker for ProgressCounterTracker {
         error: Error,
     ) {
         show_error(handle_id, asset_type_name, &asset_name, &error);
+        let message = error
+            .causes()
+            .map(|cause| cause.to_string())
+            .collect::<Vec<_>>()
+            .join(": ");
+        self.update(|asset| asset.state = AssetState::Failed(message));
         self.errors.lock().push(AssetErrorMeta {
             error,
             handle_id,
@@ -168,7 +288,16 @@ pub struct AssetErrorMeta {
 /// The `Tracker` trait which will be used by the loader to report
 /// back to `Progress`.
 pub trait Tracker: Send + 'static {
-    // TODO: maybe add handles as parameters?
+    /// Called when the asset is queued for loading.
+    fn queued(&mut self, _handle_id: u32, _asset_type_name: &'static str, _asset_name: &str) {}
+    /// Called when a worker thread starts loading the asset.
+    fn loading(&mut self) {}
+    /// Called when the asset has been imported, with the number of bytes read from its source.
+    fn imported(&mut self, _bytes: usize) {}
+    /// Called for the other files read from the source to import the asset, like the prefabs
+    /// referenced by a prefab, with the number of bytes read from them. They are tracked as
+    /// loaded assets with the handle id of the asset.
+    fn nested(&mut self, _asset_name: &str, _bytes: usize) {}
     /// Called if the asset could be imported.
     fn success(self: Box<Self>);
     /// Called if the asset couldn't be imported to an error.
@@ -211,7 +340,7 @@ fn show_error(handle_id: u32, asset_type_name: &'static str, asset_name: &String
 mod tests {
     use amethyst_error::Error;
 
-    use super::{Completion, Progress, ProgressCounter, Tracker};
+    use super::{AssetState, Completion, Progress, ProgressCounter, Tracker};
 
     #[test]
     fn progress_counter_complete_returns_correct_completion_status_when_loading_or_complete() {
@@ -289,4 +418,65 @@ mod tests {
         tracker_2.success();
         assert_eq!(2, progress.num_finished());
     }
+
+    #[test]
+    fn progress_counter_reports_asset_states() {
+        let mut progress_counter = ProgressCounter::new();
+        let mut progress = &mut progress_counter;
+        progress.add_assets(2);
+        let mut tracker_0 = Box::new(progress.create_tracker());
+        let mut tracker_1 = Box::new(progress.create_tracker());
+        tracker_0.queued(0, "AssetType", "a.asset");
+        tracker_1.queued(1, "AssetType", "b.asset");
+        tracker_0.loading();
+        tracker_0.imported(16);
+        tracker_0.success();
+        tracker_1.loading();
+        tracker_1.fail(
+            1,
+            "AssetType",
+            String::from("b.asset"),
+            Error::from_string("Missing file"),
+        );
+
+        let states = progress
+            .assets()
+            .into_iter()
+            .map(|asset| (asset.asset_name, asset.state))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("a.asset".to_owned(), AssetState::Loaded),
+                (
+                    "b.asset".to_owned(),
+                    AssetState::Failed("Missing file".to_owned())
+                ),
+            ],
+            states
+        );
+        assert_eq!(16, progress.bytes_loaded());
+        assert_eq!(100.0, progress.percent_complete());
+    }
+
+    #[test]
+    fn progress_counter_tracks_nested_files() {
+        let mut progress_counter = ProgressCounter::new();
+        let mut progress = &mut progress_counter;
+        progress.add_assets(1);
+        let mut tracker = Box::new(progress.create_tracker());
+        tracker.queued(3, "Prefab", "level.ron");
+        tracker.loading();
+        tracker.imported(16);
+        tracker.nested("turret.ron", 8);
+        assert_eq!(2, progress.num_assets());
+        assert_eq!(1, progress.num_finished());
+
+        tracker.success();
+        let assets = progress.assets();
+        assert_eq!(3, assets[1].handle_id);
+        assert_eq!("turret.ron", assets[1].asset_name);
+        assert_eq!(AssetState::Loaded, assets[1].state);
+        assert_eq!(24, progress.bytes_loaded());
+        assert!(progress.is_complete());
+    }
 }
@@ -1,9 +1,12 @@
 //! Defines the `Reload` trait.
 
-use std::{sync::Arc, time::Instant};
+use std::{any::TypeId, sync::Arc, time::Instant};
+
+use crossbeam::queue::MsQueue;
 
 use amethyst_core::{
     ecs::prelude::{DispatcherBuilder, Read, Resources, System, Write},
+    shrev::EventChannel,
     SystemBundle, Time,
 };
 use amethyst_error::Error;
@@ -11,10 +14,13 @@ use amethyst_error::Error;
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-use crate::{Asset, Format, FormatValue, Loader, Source};
+use crate::{Asset, Format, FormatValue, Handle, Loader, Source};
 
 /// This bundle activates hot reload for the `Loader`,
 /// adds a `HotReloadStrategy` and the `HotReloadSystem`.
+///
+/// Reloaded assets, including textures, sprite sheets and prefabs, replace the old assets for all
+/// existing handles, and are announced with `ReloadEvent`s.
 #[derive(Default)]
 pub struct HotReloadBundle {
     strategy: HotReloadStrategy,
@@ -34,6 +40,38 @@ impl<'a, 'b> SystemBundle<'a, 'b> for HotReloadBundle {
     }
 }
 
+/// Event sent to the `EventChannel<ReloadEvent>` resource by the `HotReloadSystem` when an asset
+/// has been hot reloaded.
+///
+/// The reloaded asset replaces the old one for all existing handles, so games only need to
+/// observe these events to update state derived from the asset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReloadEvent {
+    /// Name of the asset type, see `Asset::NAME`.
+    pub asset: &'static str,
+    /// Name the asset was loaded with, usually its path.
+    pub name: String,
+    /// Id of the handles to the asset, see `Handle::id`.
+    pub handle_id: u32,
+    type_id: TypeId,
+}
+
+impl ReloadEvent {
+    pub(crate) fn new<A: Asset>(name: String, handle_id: u32) -> Self {
+        ReloadEvent {
+            asset: A::NAME,
+            name,
+            handle_id,
+            type_id: TypeId::of::<A>(),
+        }
+    }
+
+    /// Check if the reloaded asset is the asset of `handle`.
+    pub fn is<A: Asset>(&self, handle: &Handle<A>) -> bool {
+        self.type_id == TypeId::of::<A>() && self.handle_id == handle.id()
+    }
+}
+
 /// An ECS resource which allows to configure hot reloading.
 ///
 /// ## Examples
@@ -51,6 +89,7 @@ impl<'a, 'b> SystemBundle<'a, 'b> for HotReloadBundle {
 #[derive(Clone)]
 pub struct HotReloadStrategy {
     inner: HotReloadStrategyInner,
+    events: Arc<MsQueue<ReloadEvent>>,
 }
 
 impl HotReloadStrategy {
@@ -64,6 +103,7 @@ impl HotReloadStrategy {
                 last: Instant::now(),
                 frame_number: MAX,
             },
+            events: Default::default(),
         }
     }
 
@@ -76,6 +116,7 @@ impl HotReloadStrategy {
                 triggered: false,
                 frame_number: MAX,
             },
+            events: Default::default(),
         }
     }
 
@@ -83,6 +124,7 @@ impl HotReloadStrategy {
     pub fn never() -> Self {
         HotReloadStrategy {
             inner: HotReloadStrategyInner::Never,
+            events: Default::default(),
         }
     }
 
@@ -107,6 +149,11 @@ impl HotReloadStrategy {
             HotReloadStrategyInner::Never => false,
         }
     }
+
+    /// Queue an event for the `HotReloadSystem` to send.
+    pub(crate) fn reloaded(&self, event: ReloadEvent) {
+        self.events.push(event);
+    }
 }
 
 impl Default for HotReloadStrategy {
@@ -129,7 +176,7 @@ enum HotReloadStrategyInner {
     Never,
 }
 
-/// System for updating `HotReloadStrategy`.
+/// System for updating `HotReloadStrategy` and sending the `ReloadEvent`s of reloaded assets.
 pub struct HotReloadSystem {
     initial_strategy: HotReloadStrategy,
 }
@@ -144,9 +191,16 @@ impl HotReloadSystem {
 }
 
 impl<'a> System<'a> for HotReloadSystem {
-    type SystemData = (Read<'a, Time>, Write<'a, HotReloadStrategy>);
-
-    fn run(&mut self, (time, mut strategy): Self::SystemData) {
+    type SystemData = (
+        Read<'a, Time>,
+        Write<'a, HotReloadStrategy>,
+        Write<'a, EventChannel<ReloadEvent>>,
+    );
+
+    fn run(&mut self, (time, mut strategy, mut events): Self::SystemData) {
+        while let Some(event) = strategy.events.try_pop() {
+            events.single_write(event);
+        }
         match strategy.inner {
             HotReloadStrategyInner::Trigger {
                 ref mut triggered,
@@ -213,12 +267,17 @@ impl<A: Asset> Clone for Box<dyn Reload<A>> {
 
 /// An implementation of `Reload` which just stores the modification time
 /// and the path of the file.
+///
+/// Assets built from several files of the source, like prefabs referencing other prefabs, also
+/// store the modification times of these files with `with_dependencies`, so that changing any of
+/// them reloads the asset.
 pub struct SingleFile<A: Asset, F: Format<A>> {
     format: F,
     modified: u64,
     options: F::Options,
     path: String,
     source: Arc<dyn Source>,
+    dependencies: Vec<(String, u64)>,
 }
 
 impl<A: Asset, F: Format<A>> SingleFile<A, F> {
@@ -236,7 +295,22 @@ impl<A: Asset, F: Format<A>> SingleFile<A, F> {
             options,
             path,
             source,
+            dependencies: Vec::new(),
+        }
+    }
+
+    /// Also reload the asset when one of the files at `paths` in the source changes.
+    pub fn with_dependencies<I>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        for path in paths {
+            if path != self.path && self.dependencies.iter().all(|(other, _)| *other != path) {
+                let modified = self.source.modified(&path).unwrap_or(0);
+                self.dependencies.push((path, modified));
+            }
         }
+        self
     }
 }
 
@@ -253,6 +327,7 @@ where
             options: self.options.clone(),
             path: self.path.clone(),
             source: self.source.clone(),
+            dependencies: self.dependencies.clone(),
         }
     }
 }
@@ -264,7 +339,14 @@ where
     <F as Format<A>>::Options: Clone + Sync,
 {
     fn needs_reload(&self) -> bool {
-        self.modified != 0 && (self.source.modified(&self.path).unwrap_or(0) > self.modified)
+        let changed =
+            |path: &str, modified: u64| self.source.modified(path).unwrap_or(0) > modified;
+        self.modified != 0
+            && (changed(&self.path, self.modified)
+                || self
+                    .dependencies
+                    .iter()
+                    .any(|(path, modified)| changed(path, *modified)))
     }
 
     fn name(&self) -> String {
@@ -291,3 +373,101 @@ where
         format.import(path, source, options, true)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use parking_lot::Mutex;
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_core::ecs::prelude::VecStorage;
+    use amethyst_error::{format_err, Error};
+
+    use crate::{
+        storage::Processed, Asset, AssetStorage, Directory, FormatValue, Handle, Loader,
+        ProcessingState, RonFormat, Source,
+    };
+
+    use super::{HotReloadStrategy, Reload, ReloadEvent, SingleFile};
+
+    #[derive(Default)]
+    struct Files(Mutex<HashMap<String, u64>>);
+
+    impl Source for Files {
+        fn modified(&self, path: &str) -> Result<u64, Error> {
+            self.0
+                .lock()
+                .get(path)
+                .cloned()
+                .ok_or_else(|| format_err!("Missing"))
+        }
+
+        fn load(&self, _: &str) -> Result<Vec<u8>, Error> {
+            Ok(b"1".to_vec())
+        }
+    }
+
+    struct Number(u32);
+
+    impl Asset for Number {
+        const NAME: &'static str = "Number";
+        type Data = u32;
+        type HandleStorage = VecStorage<Handle<Self>>;
+    }
+
+    #[test]
+    fn queues_reload_events() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let strategy = HotReloadStrategy::never();
+        let mut storage = AssetStorage::<Number>::default();
+        let handle = loader.load_from_data(1, (), &storage);
+        let process = |storage: &mut AssetStorage<Number>| {
+            storage.process(
+                |n| Ok(ProcessingState::Loaded(Number(n))),
+                0,
+                &pool,
+                Some(&strategy),
+            )
+        };
+        process(&mut storage);
+
+        storage.processed.push(Processed::HotReload {
+            data: Ok(FormatValue::data(2)),
+            handle: handle.clone(),
+            name: "number.ron".to_owned(),
+            old_reload: Box::new(SingleFile::new(
+                RonFormat,
+                0,
+                (),
+                "number.ron".to_owned(),
+                Arc::new(Directory::new(".")),
+            )),
+        });
+        process(&mut storage);
+
+        assert_eq!(2, storage.get(&handle).unwrap().0);
+        let event = strategy.events.try_pop().unwrap();
+        assert_eq!(
+            ReloadEvent::new::<Number>("number.ron".to_owned(), handle.id()),
+            event
+        );
+        assert!(event.is(&handle));
+        assert!(strategy.events.try_pop().is_none());
+    }
+
+    #[test]
+    fn reloads_on_dependency_change() {
+        let files = Arc::new(Files::default());
+        files.0.lock().insert("main.ron".to_owned(), 1);
+        files.0.lock().insert("nested.ron".to_owned(), 1);
+        let reload: SingleFile<Number, _> =
+            SingleFile::new(RonFormat, 1, (), "main.ron".to_owned(), files.clone())
+                .with_dependencies(vec!["nested.ron".to_owned()]);
+        assert!(!reload.needs_reload());
+
+        files.0.lock().insert("nested.ron".to_owned(), 2);
+        assert!(reload.needs_reload());
+    }
+}
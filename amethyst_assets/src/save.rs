@@ -0,0 +1,655 @@
+//! Saving and loading of the game state.
+//!
+//! Components and resources are registered by name in a `SaveRegistry`, which writes them to
+//! a versioned save file and loads them back into new entities. References to entities are
+//! remapped with the `SaveContext` and `LoadContext`, and asset handles are stored as the path
+//! they were cached with.
+
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use fnv::FnvHashMap;
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use amethyst_core::{
+    ecs::prelude::{BitSet, Builder, Component, Entity, Join, World},
+    ComponentRegistration, ComponentRegistry,
+};
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{Asset, AssetStorage, Cache, Format, Handle, Loader};
+
+/// Version of the layout of the save files.
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+/// Converts a component or resource from and to the data stored in a save file.
+///
+/// Types which are already `Serialize` and `Deserialize` can be registered with
+/// `SaveRegistry::with_serde_component` and `SaveRegistry::with_serde_resource` instead.
+pub trait Saveable: Sized {
+    /// The data stored in the save file.
+    type Data: Serialize + DeserializeOwned;
+
+    /// Converts `self` to the data stored in the save file.
+    fn save(&self, ctx: &SaveContext<'_>) -> Result<Self::Data, Error>;
+
+    /// Creates the value back from the data stored in the save file.
+    fn load(data: Self::Data, ctx: &LoadContext<'_>) -> Result<Self, Error>;
+}
+
+/// Context given to `Saveable::save`, mapping entities to their index in the save file.
+pub struct SaveContext<'a> {
+    world: &'a World,
+    indices: FnvHashMap<Entity, u32>,
+}
+
+impl<'a> SaveContext<'a> {
+    /// Returns the index of `entity` in the save file, `None` if it isn't saved.
+    pub fn entity(&self, entity: Entity) -> Option<u32> {
+        self.indices.get(&entity).cloned()
+    }
+
+    /// Returns the path an asset was loaded from.
+    ///
+    /// The path is the key of the handle in the `Cache<A>` resource, so assets referenced by
+    /// saved components have to be cached, which `LoadContext::load_asset` does.
+    pub fn asset_path<A>(&self, handle: &Handle<A>) -> Option<String>
+    where
+        A: Asset,
+    {
+        self.world
+            .res
+            .try_fetch::<Cache<A>>()
+            .and_then(|cache| cache.key(handle).map(String::from))
+    }
+
+    /// Returns the world being saved.
+    pub fn world(&self) -> &World {
+        self.world
+    }
+}
+
+/// Context given to `Saveable::load`, mapping the indices of the save file to the new entities.
+pub struct LoadContext<'a> {
+    world: &'a World,
+    entities: &'a [Entity],
+}
+
+impl<'a> LoadContext<'a> {
+    /// Returns the entity created for the `index` of the save file.
+    pub fn entity(&self, index: u32) -> Result<Entity, Error> {
+        self.entities
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| format_err!("No entity {} in the save file", index))
+    }
+
+    /// Loads the asset at `path`, reusing the handle in the `Cache<A>` resource if it was
+    /// already loaded.
+    ///
+    /// The handle is cached when the `Cache<A>` resource exists, so it can be saved again.
+    pub fn load_asset<A, F>(&self, path: &str, format: F, options: F::Options) -> Handle<A>
+    where
+        A: Asset,
+        F: Format<A> + 'static,
+    {
+        let cache = self.world.res.try_fetch_mut::<Cache<A>>();
+        if let Some(handle) = cache.as_ref().and_then(|cache| cache.get(path)) {
+            return handle;
+        }
+        let handle = self.world.read_resource::<Loader>().load(
+            path,
+            format,
+            options,
+            (),
+            &self.world.read_resource::<AssetStorage<A>>(),
+        );
+        if let Some(mut cache) = cache {
+            cache.insert(path, &handle);
+        }
+        handle
+    }
+
+    /// Returns the world being loaded into.
+    pub fn world(&self) -> &World {
+        self.world
+    }
+}
+
+/// Adapts a serde type to `Saveable`, storing it as is.
+struct Plain<T>(T);
+
+impl<T> Saveable for Plain<T>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    type Data = T;
+
+    fn save(&self, _: &SaveContext<'_>) -> Result<T, Error> {
+        Ok(self.0.clone())
+    }
+
+    fn load(data: T, _: &LoadContext<'_>) -> Result<Self, Error> {
+        Ok(Plain(data))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFile {
+    format: u32,
+    version: u32,
+    entities: u32,
+    components: BTreeMap<String, Vec<(u32, Value)>>,
+    resources: BTreeMap<String, Value>,
+}
+
+struct ComponentEntry {
+    name: String,
+    mask: Box<dyn Fn(&World) -> BitSet + Send + Sync>,
+    save: Box<dyn Fn(&SaveContext<'_>) -> Result<Vec<(u32, Value)>, Error> + Send + Sync>,
+    load: Box<dyn Fn(&LoadContext<'_>, Vec<(u32, Value)>) -> Result<(), Error> + Send + Sync>,
+}
+
+/// Inserts a loaded resource into the world.
+type InsertResource = Box<dyn FnOnce(&mut World)>;
+
+struct ResourceEntry {
+    name: String,
+    save: fn(&SaveContext<'_>) -> Result<Option<Value>, Error>,
+    load: fn(&LoadContext<'_>, Value) -> Result<InsertResource, Error>,
+}
+
+/// Registry of the components and resources written to save files.
+///
+/// Every entity with at least one of the registered components is saved. Loading creates new
+/// entities, the world isn't cleared first. Save files store the `version` of the registry and
+/// are only loaded by a registry of the same version.
+///
+/// ```rust,ignore
+/// let registry = SaveRegistry::new()
+///     .with_version(2)
+///     .with_serde_component::<Player>("player")
+///     .with_component::<Target>("target")
+///     .with_serde_resource::<Score>("score");
+/// registry.save(&world, File::create("save.json")?)?;
+/// ```
+#[derive(Default)]
+pub struct SaveRegistry {
+    version: u32,
+    components: Vec<ComponentEntry>,
+    resources: Vec<ResourceEntry>,
+}
+
+impl SaveRegistry {
+    /// Creates an empty registry of version 0.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the version of the save files, to increase when the saved data changes.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Returns the version of the save files.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Registers a component converted with `Saveable`.
+    pub fn with_component<T>(mut self, name: &str) -> Self
+    where
+        T: Component + Saveable + Send + Sync,
+    {
+        self.components.push(ComponentEntry {
+            name: name.to_string(),
+            mask: Box::new(component_mask::<T>),
+            save: Box::new(save_component::<T>),
+            load: Box::new(load_component::<T>),
+        });
+        self
+    }
+
+    /// Registers a component stored as is.
+    pub fn with_serde_component<T>(mut self, name: &str) -> Self
+    where
+        T: Component + Clone + Serialize + DeserializeOwned + Send + Sync,
+    {
+        self.components.push(ComponentEntry {
+            name: name.to_string(),
+            mask: Box::new(component_mask::<T>),
+            save: Box::new(save_serde_component::<T>),
+            load: Box::new(load_serde_component::<T>),
+        });
+        self
+    }
+
+    /// Registers all the components of a `ComponentRegistry` under their registered name,
+    /// stored as is.
+    pub fn with_registered_components(mut self, registry: &ComponentRegistry) -> Self {
+        for registration in registry.iter() {
+            let mask = registration.clone();
+            let save = registration.clone();
+            let load = registration.clone();
+            self.components.push(ComponentEntry {
+                name: registration.name().to_string(),
+                mask: Box::new(move |world| mask.entities(&world.res)),
+                save: Box::new(move |ctx| save_registered_component(&save, ctx)),
+                load: Box::new(move |ctx, values| load_registered_component(&load, ctx, values)),
+            });
+        }
+        self
+    }
+
+    /// Registers a resource converted with `Saveable`.
+    pub fn with_resource<T>(mut self, name: &str) -> Self
+    where
+        T: Saveable + Send + Sync + 'static,
+    {
+        self.resources.push(ResourceEntry {
+            name: name.to_string(),
+            save: save_resource::<T>,
+            load: load_resource::<T>,
+        });
+        self
+    }
+
+    /// Registers a resource stored as is.
+    pub fn with_serde_resource<T>(mut self, name: &str) -> Self
+    where
+        T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.resources.push(ResourceEntry {
+            name: name.to_string(),
+            save: save_serde_resource::<T>,
+            load: load_serde_resource::<T>,
+        });
+        self
+    }
+
+    /// Writes the registered components and resources of `world` to `writer`.
+    ///
+    /// Missing resources are skipped.
+    pub fn save<W: Write>(&self, world: &World, writer: W) -> Result<(), Error> {
+        let mut mask = BitSet::new();
+        for entry in &self.components {
+            mask |= &(entry.mask)(world);
+        }
+        let indices = (&*world.entities(), &mask)
+            .join()
+            .enumerate()
+            .map(|(index, (entity, _))| (entity, index as u32))
+            .collect::<FnvHashMap<_, _>>();
+        let ctx = SaveContext { world, indices };
+
+        let mut components = BTreeMap::new();
+        for entry in &self.components {
+            let values = (entry.save)(&ctx)
+                .with_context(|_| format_err!("Failed saving component {:?}", entry.name))?;
+            components.insert(entry.name.clone(), values);
+        }
+        let mut resources = BTreeMap::new();
+        for entry in &self.resources {
+            let value = (entry.save)(&ctx)
+                .with_context(|_| format_err!("Failed saving resource {:?}", entry.name))?;
+            if let Some(value) = value {
+                resources.insert(entry.name.clone(), value);
+            }
+        }
+
+        let file = SaveFile {
+            format: SAVE_FORMAT_VERSION,
+            version: self.version,
+            entities: ctx.indices.len() as u32,
+            components,
+            resources,
+        };
+        serde_json::to_writer_pretty(writer, &file)
+            .with_context(|_| format_err!("Failed writing save file"))
+    }
+
+    /// Reads a save file from `reader`, creating its entities and inserting its resources
+    /// into `world`.
+    ///
+    /// Returns the created entities, in the order they were saved. Components and resources
+    /// which aren't registered are skipped with a warning. When a component or resource fails
+    /// to load, the created entities are deleted and no resource is inserted.
+    pub fn load<R: Read>(&self, world: &mut World, reader: R) -> Result<Vec<Entity>, Error> {
+        let file: SaveFile = serde_json::from_reader(reader)
+            .with_context(|_| format_err!("Failed reading save file"))?;
+        if file.format != SAVE_FORMAT_VERSION {
+            return Err(format_err!(
+                "Unsupported save file format {}, expected {}",
+                file.format,
+                SAVE_FORMAT_VERSION
+            ));
+        }
+        if file.version != self.version {
+            return Err(format_err!(
+                "Save file has version {}, expected {}",
+                file.version,
+                self.version
+            ));
+        }
+
+        let entities = (0..file.entities)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        let inserts = {
+            let ctx = LoadContext {
+                world: &*world,
+                entities: &entities,
+            };
+            self.load_contents(&ctx, file.components, file.resources)
+        };
+        match inserts {
+            Ok(inserts) => {
+                for insert in inserts {
+                    insert(world);
+                }
+                Ok(entities)
+            }
+            Err(err) => {
+                // The entities were just created, so they can't have a wrong generation.
+                let _ = world.delete_entities(&entities);
+                Err(err)
+            }
+        }
+    }
+
+    /// Loads the components of a save file into the created entities, returning the resources to
+    /// insert.
+    fn load_contents(
+        &self,
+        ctx: &LoadContext<'_>,
+        components: BTreeMap<String, Vec<(u32, Value)>>,
+        resources: BTreeMap<String, Value>,
+    ) -> Result<Vec<InsertResource>, Error> {
+        for (name, values) in components {
+            match self.components.iter().find(|entry| entry.name == name) {
+                Some(entry) => (entry.load)(ctx, values)
+                    .with_context(|_| format_err!("Failed loading component {:?}", name))?,
+                None => warn!("Skipping unregistered component {:?} of save file", name),
+            }
+        }
+        let mut inserts = Vec::new();
+        for (name, value) in resources {
+            match self.resources.iter().find(|entry| entry.name == name) {
+                Some(entry) => inserts.push(
+                    (entry.load)(ctx, value)
+                        .with_context(|_| format_err!("Failed loading resource {:?}", name))?,
+                ),
+                None => warn!("Skipping unregistered resource {:?} of save file", name),
+            }
+        }
+        Ok(inserts)
+    }
+}
+
+fn save_registered_component(
+    registration: &ComponentRegistration,
+    ctx: &SaveContext<'_>,
+) -> Result<Vec<(u32, Value)>, Error> {
+    let mask = registration.entities(&ctx.world.res);
+    let entities = ctx.world.entities();
+    let mut values = Vec::new();
+    for (entity, _) in (&*entities, &mask).join() {
+        if let Some(index) = ctx.entity(entity) {
+            if let Some(value) = registration.serialize(&ctx.world.res, entity)? {
+                values.push((index, value));
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn load_registered_component(
+    registration: &ComponentRegistration,
+    ctx: &LoadContext<'_>,
+    values: Vec<(u32, Value)>,
+) -> Result<(), Error> {
+    for (index, value) in values {
+        registration.deserialize(&ctx.world.res, ctx.entity(index)?, value)?;
+    }
+    Ok(())
+}
+
+fn component_mask<T: Component>(world: &World) -> BitSet {
+    world.read_storage::<T>().mask().clone()
+}
+
+fn to_value<T: Saveable>(value: &T, ctx: &SaveContext<'_>) -> Result<Value, Error> {
+    let data = value.save(ctx)?;
+    serde_json::to_value(data).with_context(|_| format_err!("Failed serializing"))
+}
+
+fn from_value<T: Saveable>(value: Value, ctx: &LoadContext<'_>) -> Result<T, Error> {
+    let data =
+        serde_json::from_value(value).with_context(|_| format_err!("Failed deserializing"))?;
+    T::load(data, ctx)
+}
+
+fn save_component<T>(ctx: &SaveContext<'_>) -> Result<Vec<(u32, Value)>, Error>
+where
+    T: Component + Saveable,
+{
+    let entities = ctx.world.entities();
+    let storage = ctx.world.read_storage::<T>();
+    (&*entities, &storage)
+        .join()
+        .filter_map(|(entity, component)| ctx.entity(entity).map(|index| (index, component)))
+        .map(|(index, component)| Ok((index, to_value(component, ctx)?)))
+        .collect()
+}
+
+fn load_component<T>(ctx: &LoadContext<'_>, values: Vec<(u32, Value)>) -> Result<(), Error>
+where
+    T: Component + Saveable,
+{
+    let components = values
+        .into_iter()
+        .map(|(index, value)| Ok((ctx.entity(index)?, from_value::<T>(value, ctx)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut storage = ctx.world.write_storage::<T>();
+    for (entity, component) in components {
+        storage.insert(entity, component)?;
+    }
+    Ok(())
+}
+
+fn save_serde_component<T>(ctx: &SaveContext<'_>) -> Result<Vec<(u32, Value)>, Error>
+where
+    T: Component + Clone + Serialize + DeserializeOwned,
+{
+    let entities = ctx.world.entities();
+    let storage = ctx.world.read_storage::<T>();
+    (&*entities, &storage)
+        .join()
+        .filter_map(|(entity, component)| ctx.entity(entity).map(|index| (index, component)))
+        .map(|(index, component)| {
+            let value = serde_json::to_value(component)
+                .with_context(|_| format_err!("Failed serializing"))?;
+            Ok((index, value))
+        })
+        .collect()
+}
+
+fn load_serde_component<T>(ctx: &LoadContext<'_>, values: Vec<(u32, Value)>) -> Result<(), Error>
+where
+    T: Component + Clone + Serialize + DeserializeOwned,
+{
+    let components = values
+        .into_iter()
+        .map(|(index, value)| {
+            let Plain(component) = from_value::<Plain<T>>(value, ctx)?;
+            Ok((ctx.entity(index)?, component))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut storage = ctx.world.write_storage::<T>();
+    for (entity, component) in components {
+        storage.insert(entity, component)?;
+    }
+    Ok(())
+}
+
+fn save_resource<T>(ctx: &SaveContext<'_>) -> Result<Option<Value>, Error>
+where
+    T: Saveable + Send + Sync + 'static,
+{
+    match ctx.world.res.try_fetch::<T>() {
+        Some(resource) => to_value(&*resource, ctx).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn load_resource<T>(ctx: &LoadContext<'_>, value: Value) -> Result<InsertResource, Error>
+where
+    T: Saveable + Send + Sync + 'static,
+{
+    let resource = from_value::<T>(value, ctx)?;
+    Ok(Box::new(move |world: &mut World| {
+        world.add_resource(resource)
+    }))
+}
+
+fn save_serde_resource<T>(ctx: &SaveContext<'_>) -> Result<Option<Value>, Error>
+where
+    T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    match ctx.world.res.try_fetch::<T>() {
+        Some(resource) => serde_json::to_value(&*resource)
+            .map(Some)
+            .with_context(|_| format_err!("Failed serializing")),
+        None => Ok(None),
+    }
+}
+
+fn load_serde_resource<T>(ctx: &LoadContext<'_>, value: Value) -> Result<InsertResource, Error>
+where
+    T: Clone + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let Plain(resource) = from_value::<Plain<T>>(value, ctx)?;
+    Ok(Box::new(move |world: &mut World| {
+        world.add_resource(resource)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use amethyst_core::ecs::prelude::{Builder, Component, DenseVecStorage, Entity, Join, World};
+    use amethyst_error::{format_err, Error};
+
+    use super::{LoadContext, SaveContext, SaveRegistry, Saveable};
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    struct Target(Entity);
+
+    impl Component for Target {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    impl Saveable for Target {
+        type Data = u32;
+
+        fn save(&self, ctx: &SaveContext<'_>) -> Result<u32, Error> {
+            ctx.entity(self.0)
+                .ok_or_else(|| format_err!("Target is not saved"))
+        }
+
+        fn load(data: u32, ctx: &LoadContext<'_>) -> Result<Self, Error> {
+            ctx.entity(data).map(Target)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Score(u64);
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register::<Health>();
+        world.register::<Target>();
+        world
+    }
+
+    #[test]
+    fn saves_and_remaps_entities() {
+        let registry = SaveRegistry::new()
+            .with_version(3)
+            .with_serde_component::<Health>("health")
+            .with_component::<Target>("target")
+            .with_serde_resource::<Score>("score");
+        let mut world = world();
+        world.create_entity().build();
+        let player = world.create_entity().with(Health(10)).build();
+        world
+            .create_entity()
+            .with(Health(4))
+            .with(Target(player))
+            .build();
+        world.add_resource(Score(42));
+        let mut file = Vec::new();
+        registry
+            .save(&world, &mut file)
+            .expect("Failed to save the world");
+
+        let mut loaded = self::world();
+        for _ in 0..5 {
+            loaded.create_entity().build();
+        }
+        let entities = registry
+            .load(&mut loaded, &file[..])
+            .expect("Failed to load the save file");
+        assert_eq!(2, entities.len());
+        let health = loaded.read_storage::<Health>();
+        assert_eq!(Some(&Health(10)), health.get(entities[0]));
+        assert_eq!(Some(&Health(4)), health.get(entities[1]));
+        let target = loaded.read_storage::<Target>();
+        assert_eq!(
+            entities[0],
+            target.get(entities[1]).expect("Missing target").0
+        );
+        assert_eq!(Score(42), *loaded.read_resource::<Score>());
+
+        assert!(SaveRegistry::new()
+            .load(&mut self::world(), &file[..])
+            .is_err());
+    }
+
+    #[test]
+    fn deletes_entities_when_loading_fails() {
+        let registry = SaveRegistry::new()
+            .with_serde_component::<Health>("health")
+            .with_component::<Target>("target")
+            .with_serde_resource::<Score>("score");
+        let file = r#"{
+            "format": 1,
+            "version": 0,
+            "entities": 2,
+            "components": {
+                "health": [[0, 10], [1, 4]],
+                "target": [[1, 7]]
+            },
+            "resources": {
+                "score": 42
+            }
+        }"#;
+        let mut world = world();
+        assert!(registry.load(&mut world, file.as_bytes()).is_err());
+        assert_eq!(0, world.entities().join().count());
+        assert_eq!(0, world.read_storage::<Health>().join().count());
+        assert!(!world.res.has_value::<Score>());
+    }
+}
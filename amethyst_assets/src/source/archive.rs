@@ -0,0 +1,72 @@
+use std::{fs::File, io::Read, path::PathBuf};
+
+use parking_lot::Mutex;
+use zip::ZipArchive;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+use super::file_modified;
+
+/// Zip archive source.
+///
+/// Paths are relative to the root of the archive. All assets share the modification time of the
+/// archive file.
+pub struct Zip {
+    path: PathBuf,
+    archive: Mutex<ZipArchive<File>>,
+}
+
+impl Zip {
+    /// Open the zip archive at `path`.
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let file = File::open(&path)
+            .with_context(|_| format_err!("Failed to open zip archive {:?}", path))?;
+        let archive = ZipArchive::new(file)
+            .with_context(|_| format_err!("Failed to read zip archive {:?}", path))?;
+        Ok(Zip {
+            path,
+            archive: Mutex::new(archive),
+        })
+    }
+}
+
+impl Source for Zip {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("zip_modified_asset");
+
+        self.archive
+            .lock()
+            .by_name(path)
+            .with_context(|_| format_err!("No file {:?} in zip archive {:?}", path, self.path))?;
+        file_modified(&self.path)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("zip_load_asset");
+
+        let mut archive = self.archive.lock();
+        let mut file = archive
+            .by_name(path)
+            .with_context(|_| format_err!("No file {:?} in zip archive {:?}", path, self.path))
+            .with_context(|_| error::Error::Source)?;
+        let mut v = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut v)
+            .with_context(|_| {
+                format_err!("Failed to read {:?} from zip archive {:?}", path, self.path)
+            })
+            .with_context(|_| error::Error::Source)?;
+
+        Ok(v)
+    }
+}
@@ -1,7 +1,6 @@
 use std::{
     fs::File,
     path::{Path, PathBuf},
-    time::UNIX_EPOCH,
 };
 
 #[cfg(feature = "profiler")]
@@ -11,6 +10,8 @@ use amethyst_error::{format_err, Error, ResultExt};
 
 use crate::{error, source::Source};
 
+use super::file_modified;
+
 /// Directory source.
 ///
 /// Please note that there is a default directory source
@@ -43,19 +44,8 @@ impl Source for Directory {
     fn modified(&self, path: &str) -> Result<u64, Error> {
         #[cfg(feature = "profiler")]
         profile_scope!("dir_modified_asset");
-        use std::fs::metadata;
-
-        let path = self.path(path);
 
-        metadata(&path)
-            .with_context(|_| format_err!("Failed to fetch metadata for {:?}", path))?
-            .modified()
-            .with_context(|_| format_err!("Could not get modification time"))?
-            .duration_since(UNIX_EPOCH)
-            .with_context(|_| {
-                format_err!("Anomalies with the system clock caused `duration_since` to fail")
-            })
-            .map(|d| d.as_secs())
+        file_modified(&self.path(path))
     }
 
     fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
@@ -0,0 +1,272 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use reqwest::{
+    header::{ETAG, IF_NONE_MATCH},
+    Client, StatusCode,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+use super::file_modified;
+
+/// HTTP source, downloading assets from a web server into a local cache directory.
+///
+/// Assets are stored in the cache with the `ETag` sent by the server, and revalidated on every
+/// load, so unchanged assets are not downloaded again. When the server can't be reached or fails
+/// with a server error, the cached asset is used. Downloaded files are written next to the cache
+/// and then moved into it, so an interrupted download never leaves a partial asset behind. Like
+/// other sources, loading happens on the thread pool of the `Loader`.
+///
+/// The modification time of an asset is the time it was last downloaded. Checking it revalidates
+/// the asset at most once per revalidation interval, 10 seconds by default, so hot reloading
+/// picks up assets changed on the server without sending a request on every poll.
+///
+/// ```rust,ignore
+/// let source = Http::new("https://example.com/assets/", "cache/assets");
+/// loader.add_source("web", source);
+/// ```
+#[derive(Debug)]
+pub struct Http {
+    base: String,
+    cache: PathBuf,
+    client: Client,
+    revalidate_interval: Duration,
+    revalidated: Mutex<HashMap<String, Instant>>,
+}
+
+impl Http {
+    /// Create a source loading the assets under the `base` url, cached in `cache`.
+    pub fn new<U, P>(base: U, cache: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<PathBuf>,
+    {
+        let mut base = base.into();
+        if !base.ends_with('/') {
+            base.push('/');
+        }
+        Http {
+            base,
+            cache: cache.into(),
+            client: Client::new(),
+            revalidate_interval: Duration::from_secs(10),
+            revalidated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Set the minimum time between two revalidations of an asset when checking its
+    /// modification time. Loading an asset always revalidates it.
+    pub fn with_revalidate_interval(mut self, interval: Duration) -> Self {
+        self.revalidate_interval = interval;
+        self
+    }
+
+    /// Returns `true` if the modification time of `path` should be revalidated with the server.
+    fn should_revalidate(&self, path: &str, now: Instant) -> bool {
+        self.revalidated
+            .lock()
+            .expect("The mutex of `revalidated` in `Http` was poisoned")
+            .get(path)
+            .map_or(true, |last| {
+                now.duration_since(*last) >= self.revalidate_interval
+            })
+    }
+
+    fn mark_revalidated(&self, path: &str, now: Instant) {
+        self.revalidated
+            .lock()
+            .expect("The mutex of `revalidated` in `Http` was poisoned")
+            .insert(path.to_owned(), now);
+    }
+
+    fn cache_path(&self, path: &str) -> Result<PathBuf, Error> {
+        let relative = Path::new(path);
+        let normal = relative.components().all(|component| match component {
+            Component::Normal(_) => true,
+            _ => false,
+        });
+        if !normal {
+            return Err(format_err!("Invalid asset path {:?}", path));
+        }
+        Ok(self.cache.join(relative))
+    }
+
+    /// Download the asset if it changed since it was cached, returning the path of the cached
+    /// asset.
+    fn fetch(&self, path: &str) -> Result<PathBuf, Error> {
+        let cached = self.cache_path(path)?;
+        self.mark_revalidated(path, Instant::now());
+        let etag_path = with_extension(&cached, "etag");
+        let etag = if cached.exists() {
+            fs::read_to_string(&etag_path).ok()
+        } else {
+            None
+        };
+
+        let url = format!("{}{}", self.base, path);
+        let mut request = self.client.get(&url);
+        if let Some(etag) = &etag {
+            request = request.header(IF_NONE_MATCH, etag.as_str());
+        }
+        let mut response = match request.send() {
+            Ok(response) => response,
+            Err(err) if cached.exists() => {
+                warn!("Using cached {:?}, failed to revalidate: {}", path, err);
+                return Ok(cached);
+            }
+            Err(err) => {
+                return Err(err).with_context(|_| format_err!("Failed to download {:?}", url));
+            }
+        };
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(cached);
+        }
+        if response.status().is_server_error() && cached.exists() {
+            warn!(
+                "Using cached {:?}, failed to revalidate: {}",
+                path,
+                response.status()
+            );
+            return Ok(cached);
+        }
+        if !response.status().is_success() {
+            return Err(format_err!(
+                "Failed to download {:?}: {}",
+                url,
+                response.status()
+            ));
+        }
+
+        let mut bytes = Vec::new();
+        match response.copy_to(&mut bytes) {
+            Ok(_) => {}
+            Err(err) if cached.exists() => {
+                warn!("Using cached {:?}, failed to revalidate: {}", path, err);
+                return Ok(cached);
+            }
+            Err(err) => {
+                return Err(err).with_context(|_| format_err!("Failed to download {:?}", url));
+            }
+        }
+        if let Some(parent) = cached.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|_| format_err!("Failed to create directory {:?}", parent))?;
+        }
+
+        // The old `ETag` is removed first, so the asset is downloaded again if the new one isn't
+        // written.
+        if etag_path.exists() {
+            fs::remove_file(&etag_path)
+                .with_context(|_| format_err!("Failed to remove file {:?}", etag_path))?;
+        }
+        write_file(&cached, &bytes)?;
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok());
+        if let Some(etag) = etag {
+            write_file(&etag_path, etag.as_bytes())?;
+        }
+        Ok(cached)
+    }
+}
+
+/// Returns `path` with `extension` appended to its file name.
+fn with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+/// Writes `bytes` into a temporary file next to `path`, and then moves it to `path`.
+fn write_file(path: &Path, bytes: &[u8]) -> Result<(), Error> {
+    let temporary = with_extension(path, "download");
+    fs::write(&temporary, bytes)
+        .with_context(|_| format_err!("Failed to write file {:?}", temporary))?;
+    fs::rename(&temporary, path)
+        .with_context(|_| format_err!("Failed to move {:?} to {:?}", temporary, path))?;
+    Ok(())
+}
+
+impl Source for Http {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("http_modified_asset");
+
+        let cached = self.cache_path(path)?;
+        if cached.exists() && !self.should_revalidate(path, Instant::now()) {
+            return file_modified(&cached);
+        }
+        file_modified(&self.fetch(path)?)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("http_load_asset");
+
+        self.load_with_metadata(path).map(|(bytes, _)| bytes)
+    }
+
+    fn load_with_metadata(&self, path: &str) -> Result<(Vec<u8>, u64), Error> {
+        let cached = self.fetch(path).with_context(|_| error::Error::Source)?;
+        let bytes = fs::read(&cached)
+            .with_context(|_| format_err!("Failed to read file {:?}", cached))
+            .with_context(|_| error::Error::Source)?;
+
+        Ok((bytes, file_modified(&cached)?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::Path,
+        time::{Duration, Instant},
+    };
+
+    use super::{with_extension, Http};
+
+    #[test]
+    fn caches_inside_cache_directory() {
+        let http = Http::new("http://localhost/assets", "cache");
+        assert_eq!(
+            Path::new("cache").join("texture").join("bat.png"),
+            http.cache_path("texture/bat.png").unwrap()
+        );
+        assert!(http.cache_path("../secret").is_err());
+        assert!(http.cache_path("/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn names_files_next_to_cached_asset() {
+        assert_eq!(
+            Path::new("cache").join("texture").join("bat.png.etag"),
+            with_extension(&Path::new("cache").join("texture").join("bat.png"), "etag")
+        );
+    }
+
+    #[test]
+    fn rate_limits_revalidation() {
+        let http = Http::new("http://localhost/assets", "cache")
+            .with_revalidate_interval(Duration::from_secs(5));
+        let now = Instant::now();
+        assert!(http.should_revalidate("texture/bat.png", now));
+        http.mark_revalidated("texture/bat.png", now);
+        assert!(!http.should_revalidate("texture/bat.png", now + Duration::from_secs(4)));
+        assert!(http.should_revalidate("texture/bat.png", now + Duration::from_secs(5)));
+        assert!(http.should_revalidate("texture/cat.png", now));
+    }
+}
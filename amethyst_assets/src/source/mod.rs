@@ -1,11 +1,29 @@
-use amethyst_error::Error;
+use std::{path::Path, sync::Arc, time::UNIX_EPOCH};
 
-pub use self::dir::Directory;
+use parking_lot::Mutex;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+#[cfg(feature = "zip")]
+pub use self::archive::Zip;
+#[cfg(feature = "http")]
+pub use self::http::Http;
+pub use self::{
+    dir::Directory,
+    overlay::Overlay,
+    pack::{Pack, PackBuilder},
+};
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
+#[cfg(feature = "zip")]
+mod archive;
 mod dir;
+#[cfg(feature = "http")]
+mod http;
+mod overlay;
+mod pack;
 
 /// A trait for asset sources, which provides
 /// methods for loading bytes.
@@ -33,3 +51,62 @@ pub trait Source: Send + Sync + 'static {
         Ok((b, m))
     }
 }
+
+/// Get the modification time of the file at `path` as seconds since `UNIX_EPOCH`.
+fn file_modified(path: &Path) -> Result<u64, Error> {
+    path.metadata()
+        .with_context(|_| format_err!("Failed to fetch metadata for {:?}", path))?
+        .modified()
+        .with_context(|_| format_err!("Could not get modification time"))?
+        .duration_since(UNIX_EPOCH)
+        .with_context(|_| {
+            format_err!("Anomalies with the system clock caused `duration_since` to fail")
+        })
+        .map(|d| d.as_secs())
+}
+
+/// Source counting the bytes loaded from another source, to report the progress of loading.
+pub(crate) struct CountingSource {
+    inner: Arc<dyn Source>,
+    files: Mutex<Vec<(String, usize)>>,
+}
+
+impl CountingSource {
+    pub(crate) fn new(inner: Arc<dyn Source>) -> Self {
+        CountingSource {
+            inner,
+            files: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the files loaded so far, with the number of bytes loaded from each of them.
+    pub(crate) fn files(&self) -> Vec<(String, usize)> {
+        self.files.lock().clone()
+    }
+
+    fn count(&self, path: &str, bytes: usize) {
+        let mut files = self.files.lock();
+        match files.iter_mut().find(|file| file.0 == path) {
+            Some(file) => file.1 += bytes,
+            None => files.push((path.to_owned(), bytes)),
+        }
+    }
+}
+
+impl Source for CountingSource {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        self.inner.modified(path)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        let bytes = self.inner.load(path)?;
+        self.count(path, bytes.len());
+        Ok(bytes)
+    }
+
+    fn load_with_metadata(&self, path: &str) -> Result<(Vec<u8>, u64), Error> {
+        let (bytes, modified) = self.inner.load_with_metadata(path)?;
+        self.count(path, bytes.len());
+        Ok((bytes, modified))
+    }
+}
@@ -0,0 +1,109 @@
+use amethyst_error::{format_err, Error};
+
+use crate::source::Source;
+
+/// Source combining several sources, looking assets up by priority.
+///
+/// Each asset is loaded from the source with the highest priority that has it, so sources with a
+/// higher priority override the assets of the others. Sources with the same priority are checked
+/// in the order they were mounted.
+///
+/// ```rust,ignore
+/// let source = Overlay::new()
+///     .with(0, Pack::open("assets.pak")?)
+///     .with(10, Directory::new("mods"));
+/// let loader = Loader::with_default_source(source, pool);
+/// ```
+#[derive(Default)]
+pub struct Overlay {
+    sources: Vec<(i32, Box<dyn Source>)>,
+}
+
+impl Overlay {
+    /// Create an overlay without sources.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Mount `source` with the given priority.
+    pub fn with<S>(mut self, priority: i32, source: S) -> Self
+    where
+        S: Source,
+    {
+        self.mount(priority, source);
+        self
+    }
+
+    /// Mount `source` with the given priority.
+    pub fn mount<S>(&mut self, priority: i32, source: S)
+    where
+        S: Source,
+    {
+        let index = self
+            .sources
+            .iter()
+            .position(|&(other, _)| other < priority)
+            .unwrap_or_else(|| self.sources.len());
+        self.sources.insert(index, (priority, Box::new(source)));
+    }
+
+    /// Get the source with the highest priority that has the asset `path`.
+    fn find(&self, path: &str) -> Result<&dyn Source, Error> {
+        self.sources
+            .iter()
+            .map(|(_, source)| &**source)
+            .find(|source| source.modified(path).is_ok())
+            .ok_or_else(|| format_err!("No mounted source has the asset {:?}", path))
+    }
+}
+
+impl Source for Overlay {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        self.find(path)?.modified(path)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        self.find(path)?.load(path)
+    }
+
+    fn load_with_metadata(&self, path: &str) -> Result<(Vec<u8>, u64), Error> {
+        self.find(path)?.load_with_metadata(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amethyst_error::{format_err, Error};
+
+    use crate::source::Source;
+
+    use super::Overlay;
+
+    struct Single(&'static str, &'static [u8]);
+
+    impl Source for Single {
+        fn modified(&self, path: &str) -> Result<u64, Error> {
+            if path == self.0 {
+                Ok(0)
+            } else {
+                Err(format_err!("Missing"))
+            }
+        }
+
+        fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+            self.modified(path).map(|_| self.1.to_vec())
+        }
+    }
+
+    #[test]
+    fn loads_by_priority() {
+        let overlay = Overlay::new()
+            .with(0, Single("a", b"base"))
+            .with(0, Single("b", b"base"))
+            .with(10, Single("a", b"mod"));
+
+        assert_eq!(b"mod".to_vec(), overlay.load("a").unwrap());
+        assert_eq!(b"base".to_vec(), overlay.load("b").unwrap());
+        assert!(overlay.load("c").is_err());
+    }
+}
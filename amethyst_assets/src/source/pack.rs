@@ -0,0 +1,267 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_error::{format_err, Error, ResultExt};
+
+use crate::{error, source::Source};
+
+use super::file_modified;
+
+/// Bytes starting every pack file.
+const MAGIC: &[u8; 4] = b"AMPK";
+
+/// Version of the pack layout, increased on incompatible changes.
+const VERSION: u32 = 1;
+
+/// Location of a file in a pack, relative to the end of the index.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct PackEntry {
+    offset: u64,
+    size: u64,
+    compressed: bool,
+}
+
+/// Pack file source, reading the assets packed in a single file by `PackBuilder`.
+///
+/// A pack starts with an index of the files it contains, so assets are read directly without
+/// scanning the pack. All assets share the modification time of the pack file.
+#[derive(Debug)]
+pub struct Pack {
+    path: PathBuf,
+    index: BTreeMap<String, PackEntry>,
+    data_start: u64,
+}
+
+impl Pack {
+    /// Open the pack file at `path`, reading its index.
+    pub fn open<P>(path: P) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let mut file =
+            File::open(&path).with_context(|_| format_err!("Failed to open pack {:?}", path))?;
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic)
+            .with_context(|_| format_err!("Failed to read pack {:?}", path))?;
+        if &magic != MAGIC {
+            return Err(format_err!("{:?} is not a pack file", path));
+        }
+        let version: u32 = bincode::deserialize_from(&mut file)
+            .with_context(|_| format_err!("Failed to read pack {:?}", path))?;
+        if version != VERSION {
+            return Err(format_err!(
+                "Unsupported version {} of pack {:?}, expected {}",
+                version,
+                path,
+                VERSION
+            ));
+        }
+        let index = bincode::deserialize_from(&mut file)
+            .with_context(|_| format_err!("Failed to read the index of pack {:?}", path))?;
+        let data_start = file
+            .seek(SeekFrom::Current(0))
+            .with_context(|_| format_err!("Failed to read pack {:?}", path))?;
+        Ok(Pack {
+            path,
+            index,
+            data_start,
+        })
+    }
+
+    /// Iterate over the paths of the files in the pack.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.index.keys().map(String::as_str)
+    }
+
+    fn read(&self, path: &str) -> Result<Vec<u8>, Error> {
+        let entry = self
+            .index
+            .get(path)
+            .ok_or_else(|| format_err!("No file {:?} in pack {:?}", path, self.path))?;
+        let mut file = File::open(&self.path)
+            .with_context(|_| format_err!("Failed to open pack {:?}", self.path))?;
+        file.seek(SeekFrom::Start(self.data_start + entry.offset))
+            .with_context(|_| format_err!("Failed to read pack {:?}", self.path))?;
+        let mut stored = file.take(entry.size);
+        let mut bytes = Vec::new();
+        let read = if entry.compressed {
+            DeflateDecoder::new(stored).read_to_end(&mut bytes)
+        } else {
+            stored.read_to_end(&mut bytes)
+        };
+        read.with_context(|_| format_err!("Failed to read {:?} from pack {:?}", path, self.path))?;
+        Ok(bytes)
+    }
+}
+
+impl Source for Pack {
+    fn modified(&self, path: &str) -> Result<u64, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("pack_modified_asset");
+
+        if !self.index.contains_key(path) {
+            return Err(format_err!("No file {:?} in pack {:?}", path, self.path));
+        }
+        file_modified(&self.path)
+    }
+
+    fn load(&self, path: &str) -> Result<Vec<u8>, Error> {
+        #[cfg(feature = "profiler")]
+        profile_scope!("pack_load_asset");
+
+        self.read(path).with_context(|_| error::Error::Source)
+    }
+}
+
+/// Builder for pack files read by the `Pack` source.
+///
+/// ```rust,ignore
+/// PackBuilder::new()
+///     .with_compression(true)
+///     .with_directory("assets")?
+///     .write("dist/assets.pak")?;
+/// ```
+#[derive(Debug, Default)]
+pub struct PackBuilder {
+    files: BTreeMap<String, Vec<u8>>,
+    compression: bool,
+}
+
+impl PackBuilder {
+    /// Create an empty pack.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Compress the files with deflate, keeping files that don't get smaller uncompressed.
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Add a file to the pack, loaded from the `Pack` source with `path`.
+    pub fn with_file<N>(mut self, path: N, bytes: Vec<u8>) -> Self
+    where
+        N: Into<String>,
+    {
+        self.files.insert(path.into(), bytes);
+        self
+    }
+
+    /// Add all the files in `directory` and its subdirectories, with paths relative to
+    /// `directory`.
+    pub fn with_directory<P>(mut self, directory: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        self.add_directory(directory.as_ref(), "")?;
+        Ok(self)
+    }
+
+    fn add_directory(&mut self, directory: &Path, prefix: &str) -> Result<(), Error> {
+        let entries = fs::read_dir(directory)
+            .with_context(|_| format_err!("Failed to read directory {:?}", directory))?;
+        for entry in entries {
+            let path = entry
+                .with_context(|_| format_err!("Failed to read directory {:?}", directory))?
+                .path();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| format_err!("Path {:?} is not valid UTF-8", path))?;
+            let name = format!("{}{}", prefix, name);
+            if path.is_dir() {
+                self.add_directory(&path, &format!("{}/", name))?;
+            } else {
+                let bytes = fs::read(&path)
+                    .with_context(|_| format_err!("Failed to read file {:?}", path))?;
+                self.files.insert(name, bytes);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the pack to `path`.
+    pub fn write<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut index = BTreeMap::new();
+        let mut data = Vec::new();
+        for (name, bytes) in &self.files {
+            let deflated = if self.compression {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder
+                    .write_all(bytes)
+                    .and_then(|_| encoder.finish())
+                    .with_context(|_| format_err!("Failed to compress {:?}", name))?
+            } else {
+                Vec::new()
+            };
+            let compressed = self.compression && deflated.len() < bytes.len();
+            let stored = if compressed {
+                &deflated[..]
+            } else {
+                &bytes[..]
+            };
+            index.insert(
+                name.clone(),
+                PackEntry {
+                    offset: data.len() as u64,
+                    size: stored.len() as u64,
+                    compressed,
+                },
+            );
+            data.extend_from_slice(stored);
+        }
+
+        let mut pack = MAGIC.to_vec();
+        bincode::serialize_into(&mut pack, &VERSION)
+            .and_then(|_| bincode::serialize_into(&mut pack, &index))
+            .with_context(|_| format_err!("Failed to write the index of pack {:?}", path))?;
+        pack.extend_from_slice(&data);
+        fs::write(path, pack).with_context(|_| format_err!("Failed to write pack {:?}", path))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use crate::source::Source;
+
+    use super::{Pack, PackBuilder};
+
+    #[test]
+    fn loads_packed_files() {
+        let path = env::temp_dir().join("amethyst_assets_loads_packed_files.pak");
+        PackBuilder::new()
+            .with_compression(true)
+            .with_file("text/repeated", "data".repeat(64).into_bytes())
+            .with_file("subdir/asset", b"data".to_vec())
+            .write(&path)
+            .expect("Failed to write pack");
+
+        let pack = Pack::open(&path).expect("Failed to open pack");
+        assert_eq!(
+            "data".repeat(64).into_bytes(),
+            pack.load("text/repeated").unwrap()
+        );
+        assert_eq!(b"data".to_vec(), pack.load("subdir/asset").unwrap());
+        assert!(pack.load("missing").is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
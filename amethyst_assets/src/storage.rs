@@ -1,5 +1,6 @@
 use std::{
     marker::PhantomData,
+    mem,
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc, Mutex, Weak,
@@ -8,6 +9,7 @@ use std::{
 
 use crossbeam::queue::MsQueue;
 use derivative::Derivative;
+use fnv::FnvHashMap;
 use hibitset::BitSet;
 use log::{debug, error, trace, warn};
 use rayon::ThreadPool;
@@ -25,7 +27,7 @@ use crate::{
     asset::{Asset, FormatValue},
     error,
     progress::Tracker,
-    reload::{HotReloadStrategy, Reload},
+    reload::{HotReloadStrategy, Reload, ReloadEvent},
 };
 
 /// An `Allocator`, holding a counter for producing unique IDs.
@@ -41,12 +43,37 @@ impl Allocator {
     }
 }
 
+/// Memory used by the assets of an `AssetStorage`, see `AssetStorage::stats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AssetStorageStats {
+    /// Number of stored assets.
+    pub assets: usize,
+    /// Number of stored assets no longer referenced by any handle.
+    pub unused: usize,
+    /// Estimated memory used by the stored assets, see `Asset::memory_size`.
+    pub bytes: usize,
+    /// Estimated memory used by the unused assets.
+    pub unused_bytes: usize,
+    /// Memory budget of the storage, if any.
+    pub budget: Option<usize>,
+}
+
 /// An asset storage, storing the actual assets and allocating
 /// handles to them.
+///
+/// Assets are freed by `process` once every handle to them has been dropped. With a memory
+/// budget set by `set_budget`, unused assets are kept until the memory used by the storage goes
+/// over the budget, and are then freed starting with the least recently used ones. Weak handles,
+/// like the ones of `Cache`, can still be upgraded to the kept assets.
 pub struct AssetStorage<A: Asset> {
     assets: VecStorage<A>,
     bitset: BitSet,
+    budget: Option<usize>,
+    bytes: usize,
+    sizes: FnvHashMap<u32, usize>,
+    unused: FnvHashMap<u32, u64>,
     handles: Vec<Handle<A>>,
+    discarded: Vec<Handle<A>>,
     handle_alloc: Allocator,
     pub(crate) processed: Arc<MsQueue<Processed<A>>>,
     reloads: Vec<(WeakHandle<A>, Box<dyn Reload<A>>)>,
@@ -63,6 +90,9 @@ where
     Loading(A::Data),
     /// Asset have finished loading, can now be inserted into storage and tracker notified
     Loaded(A),
+    /// A lower quality version of the asset is ready and inserted into storage, while the
+    /// remaining data is processed again next frame. The asset is replaced in place once loaded.
+    Streaming(A, A::Data),
 }
 
 impl<A: Asset> AssetStorage<A> {
@@ -101,6 +131,9 @@ impl<A: Asset> AssetStorage<A> {
             let id = h.id();
             self.bitset.add(id);
             self.handles.push(h.clone());
+            let size = asset.memory_size();
+            self.sizes.insert(id, size);
+            self.bytes += size;
 
             unsafe {
                 self.assets.insert(id, asset);
@@ -112,6 +145,26 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Set the memory budget of the storage in bytes, or `None` to free unused assets right
+    /// away.
+    pub fn set_budget(&mut self, budget: Option<usize>) {
+        self.budget = budget;
+    }
+
+    /// Returns the memory currently used by the assets of the storage.
+    ///
+    /// Sizes are measured when assets are inserted, changes made through `get_mut` are not
+    /// accounted for.
+    pub fn stats(&self) -> AssetStorageStats {
+        AssetStorageStats {
+            assets: self.handles.len(),
+            unused: self.unused.len(),
+            bytes: self.bytes,
+            unused_bytes: self.unused.keys().map(|id| self.sizes[id]).sum(),
+            budget: self.budget,
+        }
+    }
+
     /// Get an asset from a given asset handle.
     pub fn get(&self, handle: &Handle<A>) -> Option<&A> {
         if self.bitset.contains(handle.id()) {
@@ -143,6 +196,60 @@ impl<A: Asset> AssetStorage<A> {
         self.process_custom_drop(f, |_| {}, frame_number, pool, strategy);
     }
 
+    /// Drops the finished asset data without creating the assets, reporting them as loaded to
+    /// their progress trackers.
+    ///
+    /// Headless applications use this for the assets which can't be created without a device,
+    /// like textures, so they are loaded by the same code as in the client. `get` returns `None`
+    /// for their handles, and their ids are reused once every handle to them has been dropped.
+    pub fn discard_processed(&mut self) {
+        while let Some(processed) = self.processed.try_pop() {
+            match processed {
+                Processed::NewAsset {
+                    data: Ok(_),
+                    handle,
+                    tracker,
+                    ..
+                } => {
+                    tracker.success();
+                    self.discarded.push(handle);
+                }
+                Processed::NewAsset {
+                    data: Err(e),
+                    handle,
+                    name,
+                    tracker,
+                } => {
+                    error!(
+                        "{:?}: Asset {:?} (handle id: {:?}) could not be loaded: {}",
+                        A::NAME,
+                        name,
+                        handle,
+                        e,
+                    );
+                    tracker.fail(handle.id(), A::NAME, name, e);
+                    self.discarded.push(handle);
+                }
+                // No reload object is kept for discarded assets.
+                Processed::HotReload { .. } => {}
+            }
+        }
+
+        let unused_handles = &self.unused_handles;
+        self.discarded.retain(|handle| {
+            if handle.is_unique() {
+                // A new handle, so the weak handles to the discarded asset stay dead.
+                unused_handles.push(Handle {
+                    id: Arc::new(handle.id()),
+                    marker: PhantomData,
+                });
+                false
+            } else {
+                true
+            }
+        });
+    }
+
     /// Process finished asset data and maintain the storage.
     /// This calls the `drop_fn` closure for assets that were removed from the storage.
     pub fn process_custom_drop<F, D>(
@@ -164,10 +271,13 @@ impl<A: Asset> AssetStorage<A> {
             while let Some(processed) = self.processed.try_pop() {
                 let assets = &mut self.assets;
                 let bitset = &mut self.bitset;
+                let bytes = &mut self.bytes;
+                let sizes = &mut self.sizes;
                 let handles = &mut self.handles;
                 let reloads = &mut self.reloads;
 
                 let f = &mut f;
+                let drop_fn = &mut drop_fn;
                 let (reload_obj, handle) = match processed {
                     Processed::NewAsset {
                         data,
@@ -222,6 +332,25 @@ impl<A: Asset> AssetStorage<A> {
                                 });
                                 continue;
                             }
+                            Ok((ProcessingState::Streaming(x, rest), r)) => {
+                                debug!(
+                                        "{:?}: Asset {:?} (handle id: {:?}) is streaming, readding the remaining data to queue",
+                                        A::NAME,
+                                        name,
+                                        handle,
+                                    );
+                                requeue.push(Processed::NewAsset {
+                                    data: Ok(FormatValue {
+                                        data: rest,
+                                        reload: r,
+                                    }),
+                                    handle: handle.clone(),
+                                    name,
+                                    tracker,
+                                });
+
+                                (x, None)
+                            }
                             Err(e) => {
                                 error!(
                                     "{:?}: Asset {:?} (handle id: {:?}) could not be loaded: {}",
@@ -237,13 +366,24 @@ impl<A: Asset> AssetStorage<A> {
                         };
 
                         let id = handle.id();
-                        bitset.add(id);
-                        handles.push(handle.clone());
-
-                        // NOTE: the loader has to ensure that a handle will be used
-                        // together with a `Data` only once.
-                        unsafe {
-                            assets.insert(id, asset);
+                        let size = asset.memory_size();
+                        if bitset.contains(id) {
+                            // Replace the lower quality version of a streamed asset
+                            *bytes = *bytes + size - sizes.insert(id, size).unwrap_or(0);
+                            unsafe {
+                                drop_fn(mem::replace(assets.get_mut(id), asset));
+                            }
+                        } else {
+                            bitset.add(id);
+                            handles.push(handle.clone());
+                            sizes.insert(id, size);
+                            *bytes += size;
+
+                            // NOTE: the loader has to ensure that a handle will be used
+                            // together with a `Data` only once.
+                            unsafe {
+                                assets.insert(id, asset);
+                            }
                         }
 
                         (reload_obj, handle)
@@ -275,6 +415,20 @@ impl<A: Asset> AssetStorage<A> {
                                 });
                                 continue;
                             }
+                            Ok((ProcessingState::Streaming(x, rest), r)) => {
+                                // Keep the current asset until the reloaded one is complete
+                                drop_fn(x);
+                                requeue.push(Processed::HotReload {
+                                    data: Ok(FormatValue {
+                                        data: rest,
+                                        reload: r,
+                                    }),
+                                    handle,
+                                    name,
+                                    old_reload,
+                                });
+                                continue;
+                            }
                             Err(e) => {
                                 error!(
                                     "{:?}: Failed to hot-reload asset {:?} (handle id: {:?}): {}\n\
@@ -292,14 +446,23 @@ impl<A: Asset> AssetStorage<A> {
                         };
 
                         let id = handle.id();
-                        assert!(
-                            bitset.contains(id),
-                            "Expected handle {:?} to be valid, but the asset storage says otherwise",
-                            handle,
-                        );
-                        unsafe {
-                            let old = assets.get_mut(id);
-                            *old = asset;
+                        let size = asset.memory_size();
+                        *bytes = *bytes + size - sizes.insert(id, size).unwrap_or(0);
+                        if bitset.contains(id) {
+                            unsafe {
+                                let old = assets.get_mut(id);
+                                *old = asset;
+                            }
+                        } else {
+                            // The asset was removed by `reload_all` while it was reloading
+                            bitset.add(id);
+                            handles.push(handle.clone());
+                            unsafe {
+                                assets.insert(id, asset);
+                            }
+                        }
+                        if let Some(strategy) = strategy {
+                            strategy.reloaded(ReloadEvent::new::<A>(name, id));
                         }
 
                         (reload_obj, handle)
@@ -317,15 +480,43 @@ impl<A: Asset> AssetStorage<A> {
             }
         }
 
-        let mut count = 0;
-        let mut skip = 0;
-        while let Some(i) = self.handles.iter().skip(skip).position(Handle::is_unique) {
-            count += 1;
-            // Re-normalize index
-            let i = skip + i;
-            skip = i;
-            let handle = self.handles.swap_remove(i);
-            let id = handle.id();
+        for handle in &self.handles {
+            if handle.is_unique() {
+                self.unused.entry(handle.id()).or_insert(frame_number);
+            } else {
+                self.unused.remove(&handle.id());
+            }
+        }
+        let mut evict = self
+            .unused
+            .iter()
+            .map(|(&id, &frame)| (frame, id))
+            .collect::<Vec<_>>();
+        if let Some(budget) = self.budget {
+            // Free the least recently used assets until the storage fits in its budget
+            evict.sort();
+            let mut bytes = self.bytes;
+            let count = evict
+                .iter()
+                .take_while(|&&(_, id)| {
+                    let over = bytes > budget;
+                    bytes -= self.sizes[&id];
+                    over
+                })
+                .count();
+            evict.truncate(count);
+        }
+
+        let count = evict.len();
+        for (_, id) in evict {
+            let i = self
+                .handles
+                .iter()
+                .position(|handle| handle.id() == id)
+                .expect("Unreachable: Unused assets have a handle in the storage");
+            self.handles.swap_remove(i);
+            self.unused.remove(&id);
+            self.bytes -= self.sizes.remove(&id).unwrap_or(0);
             unsafe {
                 drop_fn(self.assets.remove(id));
             }
@@ -351,6 +542,55 @@ impl<A: Asset> AssetStorage<A> {
         }
     }
 
+    /// Removes every asset from the storage and loads them again, calling `drop_fn` for the
+    /// removed assets.
+    ///
+    /// This is used when the assets can no longer be used, like the meshes and textures of a lost
+    /// graphics context. Handles stay valid, `get` returns `None` for them until their asset is
+    /// loaded again. Only assets loaded with hot reloading enabled in the `Loader` can be loaded
+    /// again, the others stay removed.
+    pub fn reload_all<D>(&mut self, mut drop_fn: D, pool: &ThreadPool)
+    where
+        D: FnMut(A),
+    {
+        for handle in self.handles.drain(..) {
+            let id = handle.id();
+            unsafe {
+                drop_fn(self.assets.remove(id));
+            }
+            self.bitset.remove(id);
+            if handle.is_unique() {
+                self.unused_handles.push(Handle {
+                    id: Arc::new(id),
+                    marker: PhantomData,
+                });
+            }
+        }
+        self.sizes.clear();
+        self.unused.clear();
+        self.bytes = 0;
+
+        let count = self.reloads.len();
+        for (handle, rel) in self.reloads.drain(..) {
+            if let Some(handle) = handle.upgrade() {
+                let processed = self.processed.clone();
+                pool.spawn(move || {
+                    let name = rel.name();
+                    let format = rel.format();
+                    let data = rel.reload().with_context(|_| error::Error::Format(format));
+
+                    processed.push(Processed::NewAsset {
+                        data,
+                        handle,
+                        name,
+                        tracker: Box::new(()),
+                    });
+                });
+            }
+        }
+        debug!("{:?}: Loading {} assets again", A::NAME, count);
+    }
+
     fn hot_reload(&mut self, pool: &ThreadPool) {
         self.reloads.retain(|&(ref handle, _)| !handle.is_dead());
         while let Some(p) = self
@@ -396,7 +636,12 @@ impl<A: Asset> Default for AssetStorage<A> {
         AssetStorage {
             assets: Default::default(),
             bitset: Default::default(),
+            budget: None,
+            bytes: 0,
+            sizes: Default::default(),
+            unused: Default::default(),
             handles: Default::default(),
+            discarded: Default::default(),
             handle_alloc: Default::default(),
             processed: Arc::new(MsQueue::new()),
             reloads: Default::default(),
@@ -520,10 +765,13 @@ pub(crate) enum Processed<A: Asset> {
 
 /// A weak handle, which is useful if you don't directly need the asset
 /// like in caches. This way, the asset can still get dropped (if you want that).
+///
+/// The default weak handle is dead, it never upgrades.
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""))]
+#[derivative(Clone(bound = ""), Debug(bound = ""), Default(bound = ""))]
 pub struct WeakHandle<A> {
     id: Weak<u32>,
+    #[derivative(Debug = "ignore")]
     marker: PhantomData<A>,
 }
 
@@ -543,3 +791,191 @@ impl<A> WeakHandle<A> {
         self.upgrade().is_none()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use rayon::ThreadPoolBuilder;
+
+    use amethyst_core::ecs::prelude::VecStorage;
+    use amethyst_error::Error;
+
+    use crate::{
+        Asset, FormatValue, Handle, Loader, ProcessingState, ProgressCounter, RonFormat,
+        SingleFile, Source,
+    };
+
+    use super::{AssetStorage, Processed, WeakHandle};
+
+    struct Blob(usize);
+
+    impl Asset for Blob {
+        const NAME: &'static str = "Blob";
+        type Data = usize;
+        type HandleStorage = VecStorage<Handle<Self>>;
+
+        fn memory_size(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn weak_handle_upgrades_until_freed() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        let handle = loader.load_from_data(1, (), &storage);
+        storage.process(|n| Ok(ProcessingState::Loaded(Blob(n))), 0, &pool, None);
+
+        let weak = handle.downgrade();
+        assert_eq!(Some(&handle), weak.upgrade().as_ref());
+        drop(handle);
+        assert!(!weak.is_dead());
+        storage.process(|n| Ok(ProcessingState::Loaded(Blob(n))), 1, &pool, None);
+        assert!(weak.is_dead());
+        assert!(WeakHandle::<Blob>::default().is_dead());
+    }
+
+    #[test]
+    fn discards_processed_data() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        let mut progress = ProgressCounter::new();
+        let handle = loader.load_from_data(1, &mut progress, &storage);
+
+        storage.discard_processed();
+        assert!(storage.get(&handle).is_none());
+        assert_eq!(0, storage.stats().assets);
+        assert!(progress.is_complete());
+    }
+
+    #[test]
+    fn reuses_ids_of_discarded_handles() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        let handle = loader.load_from_data(1, (), &storage);
+        let id = handle.id();
+
+        storage.discard_processed();
+        let weak = handle.downgrade();
+        drop(handle);
+        storage.discard_processed();
+        assert!(weak.is_dead());
+        assert_eq!(id, storage.allocate().id());
+    }
+
+    #[test]
+    fn replaces_streamed_assets() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        let handle = loader.load_from_data(40, (), &storage);
+        let stream = |n| {
+            if n < 100 {
+                Ok(ProcessingState::Streaming(Blob(n), n * 10))
+            } else {
+                Ok(ProcessingState::Loaded(Blob(n)))
+            }
+        };
+
+        storage.process(stream, 0, &pool, None);
+        assert_eq!(40, storage.get(&handle).unwrap().0);
+        storage.process(stream, 1, &pool, None);
+        assert_eq!(400, storage.get(&handle).unwrap().0);
+        assert_eq!(1, storage.stats().assets);
+        assert_eq!(400, storage.stats().bytes);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_over_budget() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        storage.set_budget(Some(100));
+        let process = |storage: &mut AssetStorage<Blob>, frame| {
+            storage.process(|n| Ok(ProcessingState::Loaded(Blob(n))), frame, &pool, None)
+        };
+
+        let first = loader.load_from_data(60, (), &storage);
+        let second = loader.load_from_data(30, (), &storage);
+        process(&mut storage, 0);
+        let (weak_first, weak_second) = (first.downgrade(), second.downgrade());
+        drop(first);
+        process(&mut storage, 1);
+        drop(second);
+        process(&mut storage, 2);
+        assert_eq!(2, storage.stats().unused);
+        assert_eq!(90, storage.stats().unused_bytes);
+
+        let third = loader.load_from_data(40, (), &storage);
+        process(&mut storage, 3);
+        assert!(weak_first.is_dead());
+        assert!(!weak_second.is_dead());
+        assert_eq!(70, storage.stats().bytes);
+        assert_eq!(40, storage.get(&third).unwrap().0);
+
+        storage.set_budget(None);
+        process(&mut storage, 4);
+        assert!(weak_second.is_dead());
+        assert_eq!(40, storage.stats().bytes);
+    }
+
+    struct Seven;
+
+    impl Source for Seven {
+        fn modified(&self, _: &str) -> Result<u64, Error> {
+            Ok(0)
+        }
+
+        fn load(&self, _: &str) -> Result<Vec<u8>, Error> {
+            Ok(b"7".to_vec())
+        }
+    }
+
+    #[test]
+    fn reloads_all_assets() {
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let mut storage = AssetStorage::<Blob>::default();
+        let process = |storage: &mut AssetStorage<Blob>| {
+            storage.process(|n| Ok(ProcessingState::Loaded(Blob(n))), 0, &pool, None)
+        };
+
+        let data = loader.load_from_data(3, (), &storage);
+        let reloaded = storage.allocate();
+        storage.processed.push(Processed::NewAsset {
+            data: Ok(FormatValue {
+                data: 5,
+                reload: Some(Box::new(SingleFile::new(
+                    RonFormat,
+                    0,
+                    (),
+                    "seven.ron".to_owned(),
+                    Arc::new(Seven),
+                ))),
+            }),
+            handle: reloaded.clone(),
+            name: "seven.ron".to_owned(),
+            tracker: Box::new(()),
+        });
+        process(&mut storage);
+        assert_eq!(5, storage.get(&reloaded).unwrap().0);
+
+        let mut dropped = 0;
+        storage.reload_all(|blob| dropped += blob.0, &pool);
+        assert_eq!(8, dropped);
+        assert!(storage.get(&data).is_none());
+        assert_eq!(0, storage.stats().bytes);
+
+        while storage.get(&reloaded).is_none() {
+            thread::yield_now();
+            process(&mut storage);
+        }
+        assert_eq!(7, storage.get(&reloaded).unwrap().0);
+        assert!(storage.get(&data).is_none());
+        assert_eq!(1, storage.stats().assets);
+    }
+}
@@ -4,11 +4,15 @@ use amethyst_assets::Processor;
 use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
 use amethyst_error::Error;
 
-use crate::{output::Output, source::*, systems::AudioSystem};
+use crate::{
+    output::Output,
+    source::*,
+    systems::{AudioSystem, MixerSystem},
+};
 
 /// Audio bundle
 ///
-/// This will only add the audio system and the asset processor for `Source`.
+/// This will only add the audio system, the mixer system and the asset processor for `Source`.
 ///
 /// `DjSystem` must be added separately if you want to use our background music system.
 ///
@@ -18,7 +22,8 @@ pub struct AudioBundle(Output);
 
 impl<'a, 'b> SystemBundle<'a, 'b> for AudioBundle {
     fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
-        builder.add(AudioSystem::new(self.0), "audio_system", &[]);
+        builder.add(MixerSystem, "mixer_system", &[]);
+        builder.add(AudioSystem::new(self.0), "audio_system", &["mixer_system"]);
         builder.add(Processor::<Source>::new(), "source_processor", &[]);
         Ok(())
     }
@@ -1,21 +1,39 @@
 use std::{
     io::Cursor,
-    sync::{atomic::AtomicBool, Arc},
+    sync::{atomic::AtomicBool, Arc, Mutex},
 };
 
-use rodio::{Decoder, SpatialSink};
+use rodio::{Decoder, Sink};
 use smallvec::SmallVec;
 
+use amethyst_assets::AssetStorage;
 use amethyst_core::ecs::{prelude::Component, storage::BTreeStorage};
 
-use crate::{source::Source, DecoderError};
+use crate::{
+    mixer::Mixer,
+    source::{Source, SourceHandle},
+    spatial::{Attenuation, SpatialParams},
+    DecoderError,
+};
 
 /// An audio source, add this component to anything that emits sound.
+///
+/// Sounds are spatialized relative to the `AudioListener` by the `AudioSystem`: their volume
+/// decreases with distance following the `Attenuation` of the emitter, and they are panned
+/// between the ears of the listener. They play on the `Mixer::SFX` bus unless routed elsewhere
+/// with `set_bus`.
 #[derive(Default)]
 pub struct AudioEmitter {
-    pub(crate) sinks: SmallVec<[(SpatialSink, Arc<AtomicBool>); 4]>,
-    pub(crate) sound_queue: SmallVec<[Decoder<Cursor<Source>>; 4]>,
+    pub(crate) sinks: SmallVec<[(Sink, Arc<AtomicBool>, Option<SourceHandle>); 4]>,
+    pub(crate) sound_queue: SmallVec<[(Decoder<Cursor<Source>>, Option<SourceHandle>); 4]>,
     pub(crate) picker: Option<Box<dyn FnMut(&mut AudioEmitter) -> bool + Send + Sync>>,
+    pub(crate) attenuation: Attenuation,
+    pub(crate) doppler: Option<f32>,
+    pub(crate) last_distance: Option<f32>,
+    pub(crate) params: Arc<Mutex<SpatialParams>>,
+    pub(crate) bus: Option<String>,
+    pub(crate) non_spatial: bool,
+    pub(crate) sounds: Vec<SourceHandle>,
 }
 
 impl AudioEmitter {
@@ -28,8 +46,25 @@ impl AudioEmitter {
 
     /// Plays an audio source from this emitter.
     pub fn play(&mut self, source: &Source) -> Result<(), DecoderError> {
-        self.sound_queue
-            .push(Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?);
+        self.sound_queue.push((
+            Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?,
+            None,
+        ));
+        Ok(())
+    }
+
+    /// Plays a loaded audio source from this emitter, the handle is sent back in the
+    /// `AudioEvent::Finished` event of the sound.
+    pub fn play_handle(
+        &mut self,
+        handle: &SourceHandle,
+        storage: &AssetStorage<Source>,
+    ) -> Result<(), DecoderError> {
+        let source = storage.get(handle).ok_or(DecoderError)?;
+        self.sound_queue.push((
+            Decoder::new(Cursor::new(source.clone())).map_err(|_| DecoderError)?,
+            Some(handle.clone()),
+        ));
         Ok(())
     }
 
@@ -48,6 +83,40 @@ impl AudioEmitter {
     pub fn clear_picker(&mut self) {
         self.picker = None;
     }
+
+    /// Sets how the volume of the sounds decreases with the distance to the listener.
+    pub fn set_attenuation(&mut self, attenuation: Attenuation) {
+        self.attenuation = attenuation;
+    }
+
+    /// Returns the mixer bus the sounds of this emitter play on.
+    pub fn bus(&self) -> &str {
+        self.bus.as_ref().map_or(Mixer::SFX, String::as_str)
+    }
+
+    /// Routes the sounds of this emitter to another mixer bus.
+    pub fn set_bus<S: Into<String>>(&mut self, bus: S) {
+        self.bus = Some(bus.into());
+    }
+
+    /// Spatializes the sounds of this emitter, which is the default. Sounds that are not
+    /// spatialized play at the volume of their bus wherever the emitter is.
+    pub fn set_spatial(&mut self, spatial: bool) {
+        self.non_spatial = !spatial;
+    }
+
+    /// Returns the sounds declared for this emitter by an `AudioPrefab`, to play them with `play`.
+    pub fn sounds(&self) -> &[SourceHandle] {
+        &self.sounds
+    }
+
+    /// Enables the doppler effect, changing the pitch of the sounds when the emitter moves
+    /// towards or away from the listener. A factor of 1.0 is physically accurate, higher
+    /// factors exaggerate the effect. `None` disables it.
+    pub fn set_doppler(&mut self, factor: Option<f32>) {
+        self.doppler = factor;
+        self.last_distance = None;
+    }
 }
 
 impl Component for AudioEmitter {
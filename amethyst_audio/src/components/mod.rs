@@ -2,29 +2,89 @@
 
 pub use self::{audio_emitter::AudioEmitter, audio_listener::AudioListener};
 
-use amethyst_assets::PrefabData;
+use amethyst_assets::{AssetStorage, Loader, PrefabData, ProgressCounter};
 use amethyst_core::{
-    ecs::prelude::{Entity, Read, WriteStorage},
+    ecs::prelude::{Entity, Read, ReadExpect, WriteStorage},
     math::Point3,
     Float,
 };
-use amethyst_error::Error;
+use amethyst_error::{format_err, Error};
 
 use serde::{Deserialize, Serialize};
 
-use crate::output::Output;
+use crate::{
+    formats::AudioFormat,
+    output::Output,
+    source::{Source, SourceHandle},
+};
 
 mod audio_emitter;
 mod audio_listener;
 
+/// A sound declared by an `AudioPrefab`, loaded through the asset system.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SoundPrefab {
+    /// Path of the audio file.
+    pub file: String,
+    /// Format of the audio file.
+    pub format: AudioFormat,
+    /// Play the sound as soon as the entity is created.
+    #[serde(default)]
+    pub autoplay: bool,
+    /// Play the sound again every time the emitter runs out of sounds to play.
+    #[serde(default)]
+    pub looping: bool,
+    #[serde(skip)]
+    handle: Option<SourceHandle>,
+}
+
 /// `PrefabData` for loading audio components
 ///
 /// For `AudioListener`, the currently registered `Output` in the `World` will be used.
-#[derive(Clone, Default, Deserialize, Serialize)]
+///
+/// Declaring `sounds` adds an `AudioEmitter` even if `emitter` is false. Sounds that don't
+/// autoplay can be played later from `AudioEmitter::sounds`.
+///
+/// ```ron
+/// (
+///     emitter: true,
+///     bus: Some("voice"),
+///     sounds: [
+///         (file: "audio/engine.ogg", format: Ogg, autoplay: true, looping: true),
+///         (file: "audio/horn.ogg", format: Ogg),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Deserialize, Serialize)]
 pub struct AudioPrefab {
     emitter: bool,
     /// Left, Right
     listener: Option<(Point3<Float>, Point3<Float>)>,
+    /// Mixer bus of the emitter, `Mixer::SFX` by default.
+    #[serde(default)]
+    bus: Option<String>,
+    /// Spatialize the sounds of the emitter, true by default.
+    #[serde(default = "default_spatial")]
+    spatial: bool,
+    /// Sounds of the emitter.
+    #[serde(default)]
+    sounds: Vec<SoundPrefab>,
+}
+
+fn default_spatial() -> bool {
+    true
+}
+
+impl Default for AudioPrefab {
+    fn default() -> Self {
+        AudioPrefab {
+            emitter: false,
+            listener: None,
+            bus: None,
+            spatial: default_spatial(),
+            sounds: Vec::new(),
+        }
+    }
 }
 
 impl<'a> PrefabData<'a> for AudioPrefab {
@@ -32,6 +92,8 @@ impl<'a> PrefabData<'a> for AudioPrefab {
         WriteStorage<'a, AudioEmitter>,
         WriteStorage<'a, AudioListener>,
         Option<Read<'a, Output>>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<Source>>,
     );
     type Result = ();
 
@@ -42,8 +104,39 @@ impl<'a> PrefabData<'a> for AudioPrefab {
         _: &[Entity],
         _: &[Entity],
     ) -> Result<(), Error> {
-        if self.emitter {
-            system_data.0.insert(entity, AudioEmitter::default())?;
+        if self.emitter || !self.sounds.is_empty() {
+            let mut emitter = AudioEmitter::default();
+            if let Some(ref bus) = self.bus {
+                emitter.set_bus(bus.clone());
+            }
+            emitter.set_spatial(self.spatial);
+            let mut looping = Vec::new();
+            for sound in &self.sounds {
+                let handle = sound.handle.clone().ok_or_else(|| {
+                    format_err!(
+                        "Sound {:?} was not loaded before `add_to_entity()`",
+                        sound.file
+                    )
+                })?;
+                if sound.autoplay {
+                    if sound.looping {
+                        let source = system_data
+                            .4
+                            .get(&handle)
+                            .ok_or_else(|| format_err!("Sound {:?} is not loaded", sound.file))?;
+                        looping.push(source.clone());
+                    } else {
+                        emitter.play_handle(&handle, &system_data.4)?;
+                    }
+                }
+                emitter.sounds.push(handle);
+            }
+            if !looping.is_empty() {
+                emitter.set_picker(Box::new(move |emitter| {
+                    looping.iter().all(|source| emitter.play(source).is_ok())
+                }));
+            }
+            system_data.0.insert(entity, emitter)?;
         }
         if let Some((left_ear, right_ear)) = self.listener {
             system_data.1.insert(
@@ -56,4 +149,27 @@ impl<'a> PrefabData<'a> for AudioPrefab {
         }
         Ok(())
     }
+
+    fn load_sub_assets(
+        &mut self,
+        progress: &mut ProgressCounter,
+        system_data: &mut Self::SystemData,
+    ) -> Result<bool, Error> {
+        let mut loading = false;
+        for sound in self
+            .sounds
+            .iter_mut()
+            .filter(|sound| sound.handle.is_none())
+        {
+            sound.handle = Some(system_data.3.load(
+                sound.file.clone(),
+                sound.format.clone(),
+                (),
+                &mut *progress,
+                &system_data.4,
+            ));
+            loading = true;
+        }
+        Ok(loading)
+    }
 }
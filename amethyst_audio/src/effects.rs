@@ -0,0 +1,244 @@
+//! Provides DSP effects applied to the sounds of mixer buses.
+
+use serde::{Deserialize, Serialize};
+
+/// Lengths of the comb filters of the reverb at 44.1kHz, from Freeverb.
+const COMB_LENGTHS: [usize; 4] = [1116, 1188, 1277, 1356];
+/// Lengths of the allpass filters of the reverb at 44.1kHz, from Freeverb.
+const ALLPASS_LENGTHS: [usize; 2] = [556, 441];
+/// Extra length of the filters of the right channel, decorrelating the channels.
+const STEREO_SPREAD: usize = 23;
+
+/// An effect applied to the sounds of a mixer bus, see `Mixer::add_effect`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Effect {
+    /// Removes the frequencies above `cutoff`, in Hz, like sounds heard underwater.
+    LowPass {
+        /// Cutoff frequency in Hz.
+        cutoff: f32,
+    },
+    /// Repeats the sound after `time` seconds, each echo `feedback` times quieter.
+    Delay {
+        /// Time between echoes in seconds.
+        time: f32,
+        /// Volume of each echo relative to the previous one, between 0.0 and 1.0.
+        feedback: f32,
+        /// Volume of the echoes relative to the sound, between 0.0 and 1.0.
+        mix: f32,
+    },
+    /// Simulates the reflections of a room, like a cave.
+    Reverb {
+        /// Size of the room, between 0.0 and 1.0, larger rooms reverberate longer.
+        room_size: f32,
+        /// Absorption of high frequencies by the walls, between 0.0 and 1.0.
+        damping: f32,
+        /// Volume of the reverberation relative to the sound, between 0.0 and 1.0.
+        mix: f32,
+    },
+}
+
+/// Effects of a bus, processing one channel of a sound.
+pub(crate) struct EffectChain {
+    channels: [Vec<Processor>; 2],
+}
+
+impl EffectChain {
+    /// Creates the processors of `effects` for sounds at `sample_rate`.
+    pub fn new(effects: &[Effect], sample_rate: u32) -> Self {
+        let channel = |spread| {
+            effects
+                .iter()
+                .map(|effect| Processor::new(effect, sample_rate, spread))
+                .collect()
+        };
+        EffectChain {
+            channels: [channel(0), channel(STEREO_SPREAD)],
+        }
+    }
+
+    /// Applies the effects to the next sample of `channel`.
+    pub fn process(&mut self, channel: usize, sample: f32) -> f32 {
+        self.channels[channel]
+            .iter_mut()
+            .fold(sample, |sample, processor| processor.process(sample))
+    }
+}
+
+enum Processor {
+    LowPass {
+        alpha: f32,
+        last: f32,
+    },
+    Delay {
+        line: DelayLine,
+        feedback: f32,
+        mix: f32,
+    },
+    Reverb {
+        combs: Vec<Comb>,
+        allpasses: Vec<DelayLine>,
+        mix: f32,
+    },
+}
+
+impl Processor {
+    fn new(effect: &Effect, sample_rate: u32, spread: usize) -> Self {
+        let rate = sample_rate as f32;
+        let scaled = |length: usize| ((length + spread) as f32 * rate / 44100.0) as usize;
+        match *effect {
+            Effect::LowPass { cutoff } => Processor::LowPass {
+                alpha: 1.0 - (-2.0 * std::f32::consts::PI * cutoff / rate).exp(),
+                last: 0.0,
+            },
+            Effect::Delay {
+                time,
+                feedback,
+                mix,
+            } => Processor::Delay {
+                line: DelayLine::new((time * rate) as usize),
+                feedback,
+                mix,
+            },
+            Effect::Reverb {
+                room_size,
+                damping,
+                mix,
+            } => Processor::Reverb {
+                combs: COMB_LENGTHS
+                    .iter()
+                    .map(|&length| Comb {
+                        line: DelayLine::new(scaled(length)),
+                        feedback: room_size * 0.28 + 0.7,
+                        damping: damping * 0.4,
+                        filtered: 0.0,
+                    })
+                    .collect(),
+                allpasses: ALLPASS_LENGTHS
+                    .iter()
+                    .map(|&length| DelayLine::new(scaled(length)))
+                    .collect(),
+                mix,
+            },
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        match self {
+            Processor::LowPass { alpha, last } => {
+                *last += *alpha * (sample - *last);
+                *last
+            }
+            Processor::Delay {
+                line,
+                feedback,
+                mix,
+            } => {
+                let echo = line.front();
+                line.push(sample + echo * *feedback);
+                sample + echo * *mix
+            }
+            Processor::Reverb {
+                combs,
+                allpasses,
+                mix,
+            } => {
+                let input = sample * 0.015;
+                let mut wet = combs
+                    .iter_mut()
+                    .map(|comb| comb.process(input))
+                    .sum::<f32>();
+                for allpass in allpasses {
+                    let delayed = allpass.front();
+                    allpass.push(wet + delayed * 0.5);
+                    wet = delayed - wet;
+                }
+                sample * (1.0 - *mix) + wet * 3.0 * *mix
+            }
+        }
+    }
+}
+
+/// Fixed length delay, returning the samples pushed `length` samples ago.
+struct DelayLine {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl DelayLine {
+    fn new(length: usize) -> Self {
+        DelayLine {
+            buffer: vec![0.0; length.max(1)],
+            index: 0,
+        }
+    }
+
+    fn front(&self) -> f32 {
+        self.buffer[self.index]
+    }
+
+    fn push(&mut self, sample: f32) {
+        self.buffer[self.index] = sample;
+        self.index = (self.index + 1) % self.buffer.len();
+    }
+}
+
+/// Lowpass feedback comb filter of the reverb.
+struct Comb {
+    line: DelayLine,
+    feedback: f32,
+    damping: f32,
+    filtered: f32,
+}
+
+impl Comb {
+    fn process(&mut self, sample: f32) -> f32 {
+        let output = self.line.front();
+        self.filtered = output * (1.0 - self.damping) + self.filtered * self.damping;
+        self.line.push(sample + self.filtered * self.feedback);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Effect, EffectChain};
+
+    #[test]
+    fn low_pass_smooths() {
+        let mut chain = EffectChain::new(&[Effect::LowPass { cutoff: 1000.0 }], 44100);
+        let first = chain.process(0, 1.0);
+        assert!(first > 0.0 && first < 1.0);
+        let last = (0..1000).map(|_| chain.process(0, 1.0)).last().unwrap();
+        assert!((last - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn delay_echoes() {
+        let delay = Effect::Delay {
+            time: 0.001,
+            feedback: 0.5,
+            mix: 0.5,
+        };
+        let mut chain = EffectChain::new(&[delay], 10000);
+        let output = (0..25)
+            .map(|i| chain.process(0, if i == 0 { 1.0 } else { 0.0 }))
+            .collect::<Vec<_>>();
+        assert_eq!(1.0, output[0]);
+        assert_eq!(0.5, output[10]);
+        assert_eq!(0.25, output[20]);
+        assert_eq!(0.0, output[15]);
+    }
+
+    #[test]
+    fn reverb_leaves_a_tail() {
+        let reverb = Effect::Reverb {
+            room_size: 0.5,
+            damping: 0.5,
+            mix: 0.5,
+        };
+        let mut chain = EffectChain::new(&[reverb], 44100);
+        chain.process(1, 1.0);
+        let tail = (0..44100).map(|_| chain.process(1, 0.0).abs()).sum::<f32>();
+        assert!(tail > 0.0 && tail.is_finite());
+    }
+}
@@ -0,0 +1,14 @@
+//! Provides the events sent by the audio systems.
+
+use amethyst_core::ecs::prelude::Entity;
+
+use crate::source::SourceHandle;
+
+/// Event sent by the `AudioSystem` into the `EventChannel<AudioEvent>` resource.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioEvent {
+    /// A sound of the `AudioEmitter` of the entity finished playing, or couldn't be played
+    /// because there is no audio output. The handle is set for sounds played with
+    /// `AudioEmitter::play_handle`.
+    Finished(Entity, Option<SourceHandle>),
+}
@@ -4,9 +4,14 @@
 pub use self::{
     bundle::AudioBundle,
     components::*,
+    effects::Effect,
+    event::AudioEvent,
     formats::{AudioFormat, FlacFormat, Mp3Format, OggFormat, WavFormat},
+    mixer::Mixer,
     sink::AudioSink,
     source::{Source, SourceHandle},
+    spatial::Attenuation,
+    stream::StreamSource,
     systems::*,
 };
 
@@ -19,10 +24,15 @@ pub mod output;
 
 mod bundle;
 mod components;
+mod effects;
 mod end_signal;
+mod event;
 mod formats;
+mod mixer;
 mod sink;
 mod source;
+mod spatial;
+mod stream;
 mod systems;
 
 /// An error occurred while decoding the source.
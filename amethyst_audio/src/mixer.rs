@@ -0,0 +1,236 @@
+//! Provides the mixer, controlling the volume of groups of sounds.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::effects::Effect;
+
+/// Volume of a bus, possibly fading to another volume.
+#[derive(Clone, Debug)]
+struct Bus {
+    volume: f32,
+    muted: bool,
+    fade: Option<Fade>,
+    effects: Arc<Vec<Effect>>,
+}
+
+impl Default for Bus {
+    fn default() -> Self {
+        Bus {
+            volume: 1.0,
+            muted: false,
+            fade: None,
+            effects: Arc::new(Vec::new()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Fade {
+    from: f32,
+    to: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// Mixer resource, grouping sounds into named buses with their own volume.
+///
+/// `AudioEmitter`s play on the `SFX` bus and the `AudioSink` on the `MUSIC` bus unless routed
+/// elsewhere. The volume of every bus is multiplied by the volume of the `MASTER` bus. Buses are
+/// created on first use with a volume of 1.0.
+///
+/// Effects added to a bus apply to the sounds of the `AudioEmitter`s playing on it, so an
+/// underwater area can filter the whole `SFX` bus.
+///
+/// Fades are advanced by the `MixerSystem`, which also applies the volume of its bus to the
+/// `AudioSink`.
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use amethyst_audio::Mixer;
+/// let mut mixer = Mixer::default();
+/// mixer.set_bus_volume(Mixer::MASTER, 0.8);
+/// mixer.set_muted(Mixer::VOICE, true);
+/// mixer.fade(Mixer::MUSIC, 0.0, Duration::from_secs(2));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mixer {
+    buses: HashMap<String, Bus>,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        let mut mixer = Mixer {
+            buses: HashMap::new(),
+        };
+        for bus in &[Mixer::MASTER, Mixer::MUSIC, Mixer::SFX, Mixer::VOICE] {
+            mixer.add_bus(*bus);
+        }
+        mixer
+    }
+}
+
+impl Mixer {
+    /// Bus controlling the volume of all the other buses.
+    pub const MASTER: &'static str = "master";
+    /// Bus for background music.
+    pub const MUSIC: &'static str = "music";
+    /// Bus for sound effects.
+    pub const SFX: &'static str = "sfx";
+    /// Bus for dialogue.
+    pub const VOICE: &'static str = "voice";
+
+    /// Adds a bus with a volume of 1.0, if it doesn't exist yet.
+    pub fn add_bus<S: Into<String>>(&mut self, bus: S) {
+        self.buses.entry(bus.into()).or_insert_with(Bus::default);
+    }
+
+    /// Iterates over the names of the buses.
+    pub fn buses(&self) -> impl Iterator<Item = &str> {
+        self.buses.keys().map(String::as_str)
+    }
+
+    fn bus_mut(&mut self, bus: &str) -> &mut Bus {
+        self.buses
+            .entry(bus.to_owned())
+            .or_insert_with(Bus::default)
+    }
+
+    /// Returns the volume of the bus, not taking mute and the master bus into account.
+    pub fn bus_volume(&self, bus: &str) -> f32 {
+        self.buses.get(bus).map_or(1.0, |bus| bus.volume)
+    }
+
+    /// Sets the volume of the bus, stopping its fade. A volume of 1.0 is unchanged, while 0.0 is
+    /// silent.
+    pub fn set_bus_volume(&mut self, bus: &str, volume: f32) {
+        let bus = self.bus_mut(bus);
+        bus.volume = volume;
+        bus.fade = None;
+    }
+
+    /// Returns true if the bus is muted.
+    pub fn is_muted(&self, bus: &str) -> bool {
+        self.buses.get(bus).map_or(false, |bus| bus.muted)
+    }
+
+    /// Mutes or unmutes the bus, keeping its volume.
+    pub fn set_muted(&mut self, bus: &str, muted: bool) {
+        self.bus_mut(bus).muted = muted;
+    }
+
+    /// Fades the volume of the bus to `volume` over `duration`.
+    pub fn fade(&mut self, bus: &str, volume: f32, duration: Duration) {
+        let bus = self.bus_mut(bus);
+        bus.fade = Some(Fade {
+            from: bus.volume,
+            to: volume,
+            duration: duration.as_secs() as f32 + duration.subsec_nanos() as f32 * 1e-9,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Returns true if the bus is fading.
+    pub fn is_fading(&self, bus: &str) -> bool {
+        self.buses.get(bus).map_or(false, |bus| bus.fade.is_some())
+    }
+
+    /// Returns the effects of the bus, in the order they are applied.
+    pub fn effects(&self, bus: &str) -> &[Effect] {
+        self.buses.get(bus).map_or(&[][..], |bus| &bus.effects[..])
+    }
+
+    /// Adds an effect to the bus, applied after its other effects.
+    pub fn add_effect(&mut self, bus: &str, effect: Effect) {
+        let mut effects = self.effects(bus).to_vec();
+        effects.push(effect);
+        self.set_effects(bus, effects);
+    }
+
+    /// Replaces the effects of the bus.
+    pub fn set_effects(&mut self, bus: &str, effects: Vec<Effect>) {
+        self.bus_mut(bus).effects = Arc::new(effects);
+    }
+
+    /// Removes all the effects of the bus.
+    pub fn clear_effects(&mut self, bus: &str) {
+        self.set_effects(bus, Vec::new());
+    }
+
+    pub(crate) fn shared_effects(&self, bus: &str) -> Option<Arc<Vec<Effect>>> {
+        self.buses.get(bus).map(|bus| bus.effects.clone())
+    }
+
+    /// Returns the volume sounds on the bus play at, including mute and the master bus.
+    pub fn volume(&self, bus: &str) -> f32 {
+        let gain = |name: &str| match self.buses.get(name) {
+            Some(bus) if bus.muted => 0.0,
+            Some(bus) => bus.volume,
+            None => 1.0,
+        };
+        if bus == Mixer::MASTER {
+            gain(bus)
+        } else {
+            gain(Mixer::MASTER) * gain(bus)
+        }
+    }
+
+    /// Advances the fades by `delta` seconds.
+    pub fn update(&mut self, delta: f32) {
+        for bus in self.buses.values_mut() {
+            if let Some(mut fade) = bus.fade.take() {
+                fade.elapsed += delta;
+                if fade.elapsed >= fade.duration {
+                    bus.volume = fade.to;
+                } else {
+                    bus.volume = fade.from + (fade.to - fade.from) * fade.elapsed / fade.duration;
+                    bus.fade = Some(fade);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::effects::Effect;
+
+    use super::Mixer;
+
+    #[test]
+    fn mixes_bus_volumes() {
+        let mut mixer = Mixer::default();
+        mixer.set_bus_volume(Mixer::MASTER, 0.5);
+        mixer.set_bus_volume(Mixer::SFX, 0.5);
+        assert_eq!(0.25, mixer.volume(Mixer::SFX));
+        assert_eq!(0.5, mixer.volume("custom"));
+
+        mixer.set_muted(Mixer::SFX, true);
+        assert_eq!(0.0, mixer.volume(Mixer::SFX));
+        assert_eq!(0.5, mixer.bus_volume(Mixer::SFX));
+    }
+
+    #[test]
+    fn fades_over_time() {
+        let mut mixer = Mixer::default();
+        mixer.fade(Mixer::MUSIC, 0.0, Duration::from_secs(2));
+        mixer.update(1.0);
+        assert_eq!(0.5, mixer.volume(Mixer::MUSIC));
+        mixer.update(1.5);
+        assert_eq!(0.0, mixer.volume(Mixer::MUSIC));
+        assert!(!mixer.is_fading(Mixer::MUSIC));
+    }
+
+    #[test]
+    fn adds_bus_effects() {
+        let mut mixer = Mixer::default();
+        mixer.add_effect(Mixer::SFX, Effect::LowPass { cutoff: 500.0 });
+        assert_eq!(
+            &[Effect::LowPass { cutoff: 500.0 }],
+            mixer.effects(Mixer::SFX)
+        );
+        mixer.clear_effects(Mixer::SFX);
+        assert!(mixer.effects(Mixer::SFX).is_empty());
+    }
+}
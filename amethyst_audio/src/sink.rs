@@ -2,11 +2,17 @@ use std::io::Cursor;
 
 use rodio::{Decoder, Sink};
 
-use crate::{output::Output, source::Source, DecoderError};
+use crate::{mixer::Mixer, output::Output, source::Source, stream::StreamSource, DecoderError};
 
 /// This structure provides a way to programmatically pick and play music.
+///
+/// The sink plays on the `Mixer::MUSIC` bus by default, its volume is multiplied by the volume
+/// of its bus.
 pub struct AudioSink {
     sink: Sink,
+    volume: f32,
+    bus: String,
+    bus_volume: f32,
 }
 
 impl AudioSink {
@@ -14,6 +20,9 @@ impl AudioSink {
     pub fn new(output: &Output) -> AudioSink {
         AudioSink {
             sink: Sink::new(&output.device),
+            volume: 1.0,
+            bus: Mixer::MUSIC.to_owned(),
+            bus_volume: 1.0,
         }
     }
 
@@ -24,6 +33,13 @@ impl AudioSink {
         Ok(())
     }
 
+    /// Adds a stream to the sink's queue of music to play, decoding it from its file while
+    /// playing.
+    pub fn append_stream(&self, stream: &StreamSource) -> Result<(), DecoderError> {
+        self.sink.append(stream.open()?);
+        Ok(())
+    }
+
     /// Returns true if the sink has no more music to play.
     pub fn empty(&self) -> bool {
         self.sink.empty()
@@ -31,12 +47,31 @@ impl AudioSink {
 
     /// Retrieves the volume of the sink, between 0.0 and 1.0;
     pub fn volume(&self) -> f32 {
-        self.sink.volume()
+        self.volume
     }
 
     /// Sets the volume of the sink.
     pub fn set_volume(&mut self, volume: f32) {
-        self.sink.set_volume(volume);
+        self.volume = volume;
+        self.sink.set_volume(self.volume * self.bus_volume);
+    }
+
+    /// Returns the mixer bus the sink plays on.
+    pub fn bus(&self) -> &str {
+        &self.bus
+    }
+
+    /// Routes the sink to another mixer bus.
+    pub fn set_bus<S: Into<String>>(&mut self, bus: S) {
+        self.bus = bus.into();
+    }
+
+    /// Applies the volume of the mixer bus of the sink.
+    pub(crate) fn set_bus_volume(&mut self, bus_volume: f32) {
+        if (self.bus_volume - bus_volume).abs() > std::f32::EPSILON {
+            self.bus_volume = bus_volume;
+            self.sink.set_volume(self.volume * self.bus_volume);
+        }
     }
 
     /// Resumes playback of a paused sink. Has no effect if this sink was never paused.
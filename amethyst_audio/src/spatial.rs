@@ -0,0 +1,279 @@
+//! Spatialization of the sounds played by `AudioEmitter`s.
+
+use std::{
+    f32::consts::FRAC_PI_4,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rodio::{Sample, Source};
+use serde::{Deserialize, Serialize};
+
+use crate::effects::{Effect, EffectChain};
+
+/// Speed of sound in air, in units per second, used for the doppler effect.
+pub(crate) const SPEED_OF_SOUND: f32 = 343.0;
+
+/// Number of frames played between updates of the spatial parameters.
+const UPDATE_FRAMES: usize = 512;
+
+/// How the volume of an `AudioEmitter` decreases with its distance to the `AudioListener`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum Attenuation {
+    /// The volume doesn't depend on the distance.
+    None,
+    /// Full volume up to `min`, then decreasing linearly to silence at `max`.
+    Linear {
+        /// Distance up to which sounds play at full volume.
+        min: f32,
+        /// Distance from which sounds are silent.
+        max: f32,
+    },
+    /// Full volume up to `reference`, then `reference / (reference + rolloff * (distance -
+    /// reference))`.
+    Inverse {
+        /// Distance up to which sounds play at full volume.
+        reference: f32,
+        /// How fast the volume decreases, `1.0` halves it at twice the reference distance.
+        rolloff: f32,
+    },
+    /// Full volume up to `reference`, then `(distance / reference) ^ -rolloff`.
+    Exponential {
+        /// Distance up to which sounds play at full volume.
+        reference: f32,
+        /// How fast the volume decreases, `1.0` halves it at twice the reference distance.
+        rolloff: f32,
+    },
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Attenuation::Inverse {
+            reference: 1.0,
+            rolloff: 1.0,
+        }
+    }
+}
+
+impl Attenuation {
+    /// Returns the volume of a sound at `distance`, between 0.0 and 1.0.
+    pub fn gain(&self, distance: f32) -> f32 {
+        match *self {
+            Attenuation::None => 1.0,
+            Attenuation::Linear { min, max } => {
+                if distance <= min {
+                    1.0
+                } else if distance >= max {
+                    0.0
+                } else {
+                    1.0 - (distance - min) / (max - min)
+                }
+            }
+            Attenuation::Inverse { reference, rolloff } => {
+                if distance <= reference {
+                    1.0
+                } else {
+                    reference / (reference + rolloff * (distance - reference))
+                }
+            }
+            Attenuation::Exponential { reference, rolloff } => {
+                if distance <= reference {
+                    1.0
+                } else {
+                    (distance / reference).powf(-rolloff)
+                }
+            }
+        }
+    }
+}
+
+/// Gains of the left and right channels for a sound coming from `pan`, between -1.0 (left)
+/// and 1.0 (right), keeping the same total power.
+pub(crate) fn pan_gains(pan: f32) -> [f32; 2] {
+    let angle = (pan.max(-1.0).min(1.0) + 1.0) * FRAC_PI_4;
+    [angle.cos(), angle.sin()]
+}
+
+/// Spatial parameters of an emitter, shared with the sounds it plays.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SpatialParams {
+    /// Gains of the left and right channels.
+    pub gains: [f32; 2],
+    /// Playback speed, changed by the doppler effect.
+    pub speed: f32,
+    /// Effects of the mixer bus of the emitter.
+    pub effects: Option<Arc<Vec<Effect>>>,
+}
+
+impl Default for SpatialParams {
+    fn default() -> Self {
+        SpatialParams {
+            gains: [1.0, 1.0],
+            speed: 1.0,
+            effects: None,
+        }
+    }
+}
+
+/// Plays a sound in stereo with the parameters of its emitter.
+///
+/// The sound is mixed down to mono before panning. Parameters are read every `UPDATE_FRAMES`
+/// frames, which are reported as the frame length so speed changes are resampled correctly.
+pub(crate) struct SpatialSource<I> {
+    input: I,
+    params: Arc<Mutex<SpatialParams>>,
+    current: SpatialParams,
+    effects: Option<EffectChain>,
+    mono: f32,
+    channel: usize,
+    until_update: usize,
+}
+
+impl<I> SpatialSource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    pub fn new(input: I, params: Arc<Mutex<SpatialParams>>) -> Self {
+        let current = params
+            .lock()
+            .expect("Spatial parameters mutex was poisoned")
+            .clone();
+        let effects = current
+            .effects
+            .as_ref()
+            .map(|effects| EffectChain::new(effects, input.sample_rate()));
+        SpatialSource {
+            input,
+            params,
+            current,
+            effects,
+            mono: 0.0,
+            channel: 2,
+            until_update: UPDATE_FRAMES,
+        }
+    }
+}
+
+impl<I> Iterator for SpatialSource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.channel == 2 {
+            let channels = self.input.channels().max(1);
+            let (mut sum, mut count) = (0.0, 0);
+            for sample in self.input.by_ref().take(channels as usize) {
+                sum += sample.to_f32();
+                count += 1;
+            }
+            if count == 0 {
+                return None;
+            }
+            self.mono = sum / count as f32;
+            self.channel = 0;
+            self.until_update -= 1;
+        }
+
+        let mut sample = self.mono * self.current.gains[self.channel];
+        if let Some(ref mut effects) = self.effects {
+            sample = effects.process(self.channel, sample);
+        }
+        self.channel += 1;
+        if self.channel == 2 && self.until_update == 0 {
+            let params = self
+                .params
+                .lock()
+                .expect("Spatial parameters mutex was poisoned")
+                .clone();
+            let changed = match (&params.effects, &self.current.effects) {
+                (Some(new), Some(old)) => !Arc::ptr_eq(new, old),
+                (None, None) => false,
+                _ => true,
+            };
+            if changed {
+                let rate = self.input.sample_rate();
+                self.effects = params
+                    .effects
+                    .as_ref()
+                    .map(|effects| EffectChain::new(effects, rate));
+            }
+            self.current = params;
+            self.until_update = UPDATE_FRAMES;
+        }
+        Some(sample)
+    }
+}
+
+impl<I> Source for SpatialSource<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.until_update * 2 + (2 - self.channel))
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        (self.input.sample_rate() as f32 * self.current.speed) as u32
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rodio::{buffer::SamplesBuffer, Source};
+
+    use super::{pan_gains, Attenuation, SpatialParams, SpatialSource};
+
+    #[test]
+    fn attenuates_with_distance() {
+        let linear = Attenuation::Linear { min: 1.0, max: 3.0 };
+        assert_eq!(1.0, linear.gain(0.5));
+        assert_eq!(0.5, linear.gain(2.0));
+        assert_eq!(0.0, linear.gain(4.0));
+        assert_eq!(0.5, Attenuation::default().gain(2.0));
+        let exponential = Attenuation::Exponential {
+            reference: 1.0,
+            rolloff: 2.0,
+        };
+        assert_eq!(0.25, exponential.gain(2.0));
+        assert_eq!(1.0, Attenuation::None.gain(100.0));
+    }
+
+    #[test]
+    fn pans_to_stereo() {
+        let [left, right] = pan_gains(-1.0);
+        assert!((left - 1.0).abs() < 1e-6 && right.abs() < 1e-6);
+        let [left, right] = pan_gains(0.0);
+        assert!((left - right).abs() < 1e-6);
+        assert!((left * left + right * right - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn applies_spatial_params() {
+        let params = Arc::new(Mutex::new(SpatialParams {
+            gains: [0.5, 0.25],
+            speed: 2.0,
+            effects: None,
+        }));
+        let input = SamplesBuffer::new(2, 44100, vec![1.0f32, 0.0, 0.5, 0.5]);
+        let source = SpatialSource::new(input, params);
+        assert_eq!(88200, source.sample_rate());
+        assert_eq!(2, source.channels());
+        let samples = source.collect::<Vec<_>>();
+        assert_eq!(vec![0.25, 0.125, 0.25, 0.125], samples);
+    }
+}
@@ -0,0 +1,172 @@
+//! Provides audio streamed from files, for long music tracks.
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::error;
+use rodio::{Decoder, Source as RSource};
+
+use crate::DecoderError;
+
+/// Audio streamed from a file.
+///
+/// Unlike a `Source`, which keeps the whole file in memory, the file is read and decoded in
+/// chunks while it plays, so long music tracks only use a small buffer. Play it with
+/// `AudioSink::append_stream`.
+///
+/// To seek in a playing stream, stop the sink and append the stream again with `starting_at`.
+///
+/// ```rust,no_run
+/// # use std::time::Duration;
+/// # use amethyst_audio::{output::Output, AudioSink, StreamSource};
+/// let sink = AudioSink::new(&Output::default());
+/// let music = StreamSource::new("assets/music/theme.ogg").looping(true);
+/// sink.append_stream(&music).expect("Failed to decode music");
+///
+/// // Skip to 1:30
+/// sink.stop();
+/// sink.append_stream(&music.starting_at(Duration::from_secs(90)))
+///     .expect("Failed to decode music");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamSource {
+    path: PathBuf,
+    looping: bool,
+    start: Duration,
+}
+
+impl StreamSource {
+    /// Creates a stream of the audio file at `path`, in any of the supported formats.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        StreamSource {
+            path: path.into(),
+            looping: false,
+            start: Duration::from_secs(0),
+        }
+    }
+
+    /// Restart the stream from the beginning of the file when it ends, without a gap.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Start playing from `position` instead of the beginning of the file.
+    pub fn starting_at(&self, position: Duration) -> Self {
+        StreamSource {
+            start: position,
+            ..self.clone()
+        }
+    }
+
+    /// The path of the streamed file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Opens the file, returning the decoded samples.
+    pub(crate) fn open(&self) -> Result<FileStream, DecoderError> {
+        let mut decoder = decode(&self.path)?;
+        let rate = u64::from(decoder.sample_rate());
+        let frames = self.start.as_secs() * rate
+            + u64::from(self.start.subsec_nanos()) * rate / 1_000_000_000;
+        let samples = frames as usize * decoder.channels() as usize;
+        decoder.by_ref().take(samples).for_each(drop);
+        Ok(FileStream {
+            path: self.path.clone(),
+            looping: self.looping,
+            decoder,
+        })
+    }
+}
+
+fn decode(path: &Path) -> Result<Decoder<BufReader<File>>, DecoderError> {
+    let file = File::open(path).map_err(|_| DecoderError)?;
+    Decoder::new(BufReader::new(file)).map_err(|_| DecoderError)
+}
+
+/// Samples decoded from a file while playing.
+pub(crate) struct FileStream {
+    path: PathBuf,
+    looping: bool,
+    decoder: Decoder<BufReader<File>>,
+}
+
+impl Iterator for FileStream {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        match self.decoder.next() {
+            None if self.looping => match decode(&self.path) {
+                Ok(decoder) => {
+                    self.decoder = decoder;
+                    self.decoder.next()
+                }
+                Err(err) => {
+                    error!("Failed to loop the audio stream {:?}: {}", self.path, err);
+                    None
+                }
+            },
+            sample => sample,
+        }
+    }
+}
+
+impl RSource for FileStream {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.decoder.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.decoder.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.decoder.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        if self.looping {
+            None
+        } else {
+            self.decoder.total_duration()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::Source as RSource;
+
+    use amethyst_utils::app_root_dir::application_root_dir;
+
+    use super::StreamSource;
+
+    #[test]
+    fn loops_and_seeks() {
+        let path = application_root_dir().unwrap().join("tests/sound_test.ogg");
+        let samples = StreamSource::new(&path).open().unwrap().count();
+        assert!(samples > 0);
+
+        let looping = StreamSource::new(&path).looping(true);
+        assert_eq!(
+            samples * 2,
+            looping.open().unwrap().take(samples * 2).count()
+        );
+
+        let stream = StreamSource::new(&path).open().unwrap();
+        let skip = stream.sample_rate() as usize * stream.channels() as usize / 100;
+        let seeked = StreamSource::new(&path)
+            .starting_at(Duration::from_millis(10))
+            .open()
+            .unwrap()
+            .count();
+        assert_eq!(samples - skip, seeked);
+    }
+}
@@ -7,26 +7,44 @@ use std::{
     },
 };
 
-use rodio::SpatialSink;
+use rodio::Sink;
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
 use amethyst_core::{
     ecs::prelude::{
-        Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, WriteStorage,
+        Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, Write,
+        WriteStorage,
     },
-    math::convert,
+    math::{convert, Point3, Vector3},
+    shrev::EventChannel,
+    timing::Time,
     transform::Transform,
+    Float,
 };
 
 use crate::{
     components::{AudioEmitter, AudioListener},
     end_signal::EndSignalSource,
+    event::AudioEvent,
+    mixer::Mixer,
     output::Output,
+    spatial::{pan_gains, SpatialParams, SpatialSource, SPEED_OF_SOUND},
 };
 
+/// Distance under which emitters are considered to be at the position of the listener.
+const EPSILON: f32 = 1e-6;
+
+fn position(point: &Point3<Float>) -> Vector3<f32> {
+    Vector3::new(convert(point.x), convert(point.y), convert(point.z))
+}
+
 /// Syncs 3D transform data with the audio engine to provide 3D audio.
+///
+/// Every frame, the sounds of each `AudioEmitter` are attenuated with their distance to the
+/// listener, panned between its ears and, if enabled, pitched by the doppler effect.
+/// An `AudioEvent::Finished` is sent when a sound of an emitter ends.
 #[derive(Default)]
 pub struct AudioSystem(Output);
 
@@ -45,16 +63,29 @@ pub struct SelectedListener(pub Entity);
 impl<'a> System<'a> for AudioSystem {
     type SystemData = (
         Option<Read<'a, Output>>,
+        Option<Read<'a, Mixer>>,
         Option<Read<'a, SelectedListener>>,
         Entities<'a>,
+        Read<'a, Time>,
         ReadStorage<'a, Transform>,
         ReadStorage<'a, AudioListener>,
         WriteStorage<'a, AudioEmitter>,
+        Write<'a, EventChannel<AudioEvent>>,
     );
 
     fn run(
         &mut self,
-        (output, select_listener, entities, transform, listener, mut audio_emitter): Self::SystemData,
+        (
+            output,
+            mixer,
+            select_listener,
+            entities,
+            time,
+            transform,
+            listener,
+            mut audio_emitter,
+            mut events,
+        ): Self::SystemData,
     ) {
         #[cfg(feature = "profiler")]
         profile_scope!("audio_system");
@@ -70,34 +101,70 @@ impl<'a> System<'a> for AudioSystem {
                 .or_else(|| transform.get(entity))
             {
                 let listener_transform = listener_transform.global_matrix();
-                let left_ear_position: [f32; 3] = {
-                    let pos = listener_transform
-                        .transform_point(&listener.left_ear)
-                        .to_homogeneous()
-                        .xyz();
-                    [convert(pos.x), convert(pos.y), convert(pos.z)]
-                };
-                let right_ear_position: [f32; 3] = {
-                    let pos = listener_transform
-                        .transform_point(&listener.right_ear)
-                        .to_homogeneous()
-                        .xyz();
-                    [convert(pos.x), convert(pos.y), convert(pos.z)]
-                };
-                for (transform, mut audio_emitter) in (&transform, &mut audio_emitter).join() {
-                    let emitter_position: [f32; 3] = {
-                        let x = transform.global_matrix()[(0, 3)];
-                        let y = transform.global_matrix()[(1, 3)];
-                        let z = transform.global_matrix()[(2, 3)];
-                        [convert(x), convert(y), convert(z)]
+                let left_ear = position(&listener_transform.transform_point(&listener.left_ear));
+                let right_ear = position(&listener_transform.transform_point(&listener.right_ear));
+                let ear_axis = right_ear - left_ear;
+                let center = left_ear + ear_axis * 0.5;
+                let delta = time.delta_seconds();
+                for (entity, transform, mut audio_emitter) in
+                    (&*entities, &transform, &mut audio_emitter).join()
+                {
+                    let global = transform.global_matrix();
+                    let emitter_position =
+                        position(&Point3::new(global[(0, 3)], global[(1, 3)], global[(2, 3)]));
+                    let offset = emitter_position - center;
+                    let distance = offset.norm();
+                    let volume = mixer
+                        .as_ref()
+                        .map_or(1.0, |m| m.volume(audio_emitter.bus()));
+                    let effects = mixer
+                        .as_ref()
+                        .and_then(|m| m.shared_effects(audio_emitter.bus()))
+                        .filter(|effects| !effects.is_empty());
+                    let params = if audio_emitter.non_spatial {
+                        SpatialParams {
+                            gains: [volume, volume],
+                            speed: 1.0,
+                            effects,
+                        }
+                    } else {
+                        let pan = if distance > EPSILON && ear_axis.norm() > EPSILON {
+                            offset.dot(&ear_axis) / (distance * ear_axis.norm())
+                        } else {
+                            0.0
+                        };
+                        let gain = audio_emitter.attenuation.gain(distance) * volume;
+                        let [left, right] = pan_gains(pan);
+                        let speed = match (audio_emitter.doppler, audio_emitter.last_distance) {
+                            (Some(factor), Some(last)) if delta > 0.0 => {
+                                // Positive when moving away from the listener
+                                let velocity = (distance - last) / delta;
+                                (SPEED_OF_SOUND / (SPEED_OF_SOUND + factor * velocity))
+                                    .max(0.5)
+                                    .min(2.0)
+                            }
+                            _ => 1.0,
+                        };
+                        SpatialParams {
+                            gains: [left * gain, right * gain],
+                            speed,
+                            effects,
+                        }
                     };
+                    audio_emitter.last_distance = Some(distance);
+                    *audio_emitter
+                        .params
+                        .lock()
+                        .expect("Spatial parameters mutex was poisoned") = params;
+
                     // Remove all sinks whose sounds have ended.
-                    audio_emitter.sinks.retain(|s| !s.1.load(Ordering::Relaxed));
-                    for &mut (ref mut sink, _) in &mut audio_emitter.sinks {
-                        sink.set_emitter_position(emitter_position);
-                        sink.set_left_ear_position(left_ear_position);
-                        sink.set_right_ear_position(right_ear_position);
-                    }
+                    audio_emitter.sinks.retain(|s| {
+                        let ended = s.1.load(Ordering::Relaxed);
+                        if ended {
+                            events.single_write(AudioEvent::Finished(entity, s.2.take()));
+                        }
+                        !ended
+                    });
                     if audio_emitter.sinks.is_empty() {
                         if let Some(mut picker) = replace(&mut audio_emitter.picker, None) {
                             if picker(&mut audio_emitter) {
@@ -105,20 +172,18 @@ impl<'a> System<'a> for AudioSystem {
                             }
                         }
                     }
-                    while let Some(source) = audio_emitter.sound_queue.pop() {
+                    while let Some((source, handle)) = audio_emitter.sound_queue.pop() {
                         if let Some(output) = &output {
-                            let sink = SpatialSink::new(
-                                &output.device,
-                                emitter_position,
-                                left_ear_position,
-                                right_ear_position,
-                            );
+                            let sink = Sink::new(&output.device);
                             let atomic_bool = Arc::new(AtomicBool::new(false));
                             let clone = atomic_bool.clone();
+                            let source = SpatialSource::new(source, audio_emitter.params.clone());
                             sink.append(EndSignalSource::new(source, move || {
                                 clone.store(true, Ordering::Relaxed);
                             }));
-                            audio_emitter.sinks.push((sink, atomic_bool));
+                            audio_emitter.sinks.push((sink, atomic_bool, handle));
+                        } else {
+                            events.single_write(AudioEvent::Finished(entity, handle));
                         }
                     }
                 }
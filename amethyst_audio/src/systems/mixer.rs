@@ -0,0 +1,33 @@
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_core::{
+    ecs::prelude::{Read, System, Write},
+    timing::Time,
+};
+
+use crate::{mixer::Mixer, sink::AudioSink};
+
+/// Advances the fades of the `Mixer` and applies the volume of its bus to the `AudioSink`.
+///
+/// Fades use the real time, so they go on while the game is paused.
+#[derive(Debug, Default)]
+pub struct MixerSystem;
+
+impl<'a> System<'a> for MixerSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        Write<'a, Mixer>,
+        Option<Write<'a, AudioSink>>,
+    );
+
+    fn run(&mut self, (time, mut mixer, sink): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("mixer_system");
+        mixer.update(time.delta_real_seconds());
+        if let Some(mut sink) = sink {
+            let volume = mixer.volume(sink.bus());
+            sink.set_bus_volume(volume);
+        }
+    }
+}
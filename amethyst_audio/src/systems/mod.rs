@@ -1,6 +1,7 @@
 //! `amethyst` audio ecs systems
 
-pub use self::{audio::AudioSystem, dj::DjSystem};
+pub use self::{audio::AudioSystem, dj::DjSystem, mixer::MixerSystem};
 
 mod audio;
 mod dj;
+mod mixer;
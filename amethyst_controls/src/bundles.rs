@@ -12,7 +12,8 @@ use super::*;
 ///
 /// You might want to add `"fly_movement"` and `"free_rotation"` as dependencies of the
 /// `TransformSystem` in order to apply changes made by these systems in the same frame.
-/// Adding this bundle will grab the mouse, hide it and keep it centered.
+/// Adding this bundle will grab the mouse, hide it and keep it centered. Set the `CursorMode`
+/// resource to `Free` or `Confined` to release the mouse, which also stops the rotation.
 ///
 /// # Type parameters
 ///
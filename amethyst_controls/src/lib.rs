@@ -5,7 +5,7 @@
 pub use self::{
     bundles::{ArcBallControlBundle, FlyControlBundle},
     components::{ArcBallControlTag, ControlTagPrefab, FlyControlTag},
-    resources::{HideCursor, WindowFocus},
+    resources::{CursorMode, WindowFocus},
     systems::{
         ArcBallRotationSystem, CursorHideSystem, FlyMovementSystem, FreeRotationSystem,
         MouseFocusUpdateSystem,
@@ -15,16 +15,24 @@ impl WindowFocus {
     }
 }
 
-/// Resource indicating if the mouse should be grabbed and hidden by the CursorHideSystem
-/// when the window is focused.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct HideCursor {
-    /// If true this system will take control of the cursor.
-    pub hide: bool,
+/// Resource holding how the CursorHideSystem controls the cursor when the window is focused.
+///
+/// Change it at runtime to switch modes, for example to `Free` when opening a pause menu. The
+/// cursor is released while the window is not focused, and the mode is applied again when the
+/// window regains focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CursorMode {
+    /// The cursor is visible and moves freely.
+    Free,
+    /// The cursor is visible but can't leave the window.
+    Confined,
+    /// The cursor is hidden and kept at the center of the window, only the relative mouse
+    /// motion is used. This is the default, used by the fly and arc ball controls.
+    Locked,
 }
 
-impl Default for HideCursor {
+impl Default for CursorMode {
     fn default() -> Self {
-        HideCursor { hide: true }
+        CursorMode::Locked
     }
 }
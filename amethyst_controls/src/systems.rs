@@ -4,7 +4,7 @@ use winit::{DeviceEvent, Event, WindowEvent};
 
 use crate::{
     components::{ArcBallControlTag, FlyControlTag},
-    resources::{HideCursor, WindowFocus},
+    resources::{CursorMode, WindowFocus},
 };
 use amethyst_core::{
     ecs::prelude::{Join, Read, ReadStorage, Resources, System, Write, WriteStorage},
@@ -124,8 +124,8 @@ impl<'a> System<'a> for ArcBallRotationSystem {
 /// Controlled by the mouse.
 /// Goes into an inactive state if the window is not focused (`WindowFocus` resource).
 ///
-/// Can be manually disabled by making the mouse visible using the `HideCursor` resource:
-/// `HideCursor.hide = false`
+/// Only rotates while the cursor is locked, so it can be disabled by changing the `CursorMode`
+/// resource: `*cursor_mode = CursorMode::Free`
 ///
 /// # Type parameters
 ///
@@ -162,17 +162,17 @@ where
         WriteStorage<'a, Transform>,
         ReadStorage<'a, FlyControlTag>,
         Read<'a, WindowFocus>,
-        Read<'a, HideCursor>,
+        Read<'a, CursorMode>,
     );
 
-    fn run(&mut self, (events, mut transform, tag, focus, hide): Self::SystemData) {
+    fn run(&mut self, (events, mut transform, tag, focus, cursor_mode): Self::SystemData) {
         let focused = focus.is_focused;
         for event in
             events.read(&mut self.event_reader.as_mut().expect(
                 "`FreeRotationSystem::setup` was not called before `FreeRotationSystem::run`",
             ))
         {
-            if focused && hide.hide {
+            if focused && *cursor_mode == CursorMode::Locked {
                 if let Event::DeviceEvent { ref event, .. } = *event {
                     if let DeviceEvent::MouseMotion { delta: (x, y) } = *event {
                         for (transform, _) in (&mut transform, &tag).join() {
@@ -231,52 +231,53 @@ impl<'a> System<'a> for MouseFocusUpdateSystem {
     }
 }
 
-/// System which hides the cursor when the window is focused.
+/// System which applies the `CursorMode` when the window is focused, and releases the cursor
+/// when it loses focus.
 /// Requires the usage MouseFocusUpdateSystem at the same time.
 pub struct CursorHideSystem {
-    is_hidden: bool,
+    /// Mode currently applied to the window.
+    applied: CursorMode,
 }
 
 impl CursorHideSystem {
     /// Constructs a new CursorHideSystem
     pub fn new() -> CursorHideSystem {
-        CursorHideSystem { is_hidden: false }
+        CursorHideSystem {
+            applied: CursorMode::Free,
+        }
     }
 }
 
 impl<'a> System<'a> for CursorHideSystem {
     type SystemData = (
         Write<'a, WindowMessages>,
-        Read<'a, HideCursor>,
+        Read<'a, CursorMode>,
         Read<'a, WindowFocus>,
     );
 
-    fn run(&mut self, (mut msg, hide, focus): Self::SystemData) {
+    fn run(&mut self, (mut msg, cursor_mode, focus): Self::SystemData) {
         use amethyst_renderer::mouse::*;
-        if focus.is_focused {
-            if !self.is_hidden && hide.hide {
-                grab_cursor(&mut msg);
-                hide_cursor(&mut msg);
-                self.is_hidden = true;
-            } else if self.is_hidden && !hide.hide {
-                release_cursor(&mut msg);
-                self.is_hidden = false;
+        let mode = if focus.is_focused {
+            *cursor_mode
+        } else {
+            CursorMode::Free
+        };
+        if mode != self.applied {
+            match mode {
+                CursorMode::Free => release_cursor(&mut msg),
+                CursorMode::Confined => {
+                    grab_cursor(&mut msg);
+                    show_cursor(&mut msg);
+                }
+                CursorMode::Locked => {
+                    grab_cursor(&mut msg);
+                    hide_cursor(&mut msg);
+                }
             }
-        } else if self.is_hidden {
-            release_cursor(&mut msg);
-            self.is_hidden = false;
+            self.applied = mode;
+        }
+        if mode == CursorMode::Locked {
+            center_cursor(&mut msg);
         }
-    }
-
-    fn setup(&mut self, res: &mut Resources) {
-        use amethyst_core::ecs::prelude::SystemData;
-        use amethyst_renderer::mouse::*;
-
-        Self::SystemData::setup(res);
-
-        let mut msg = res.fetch_mut::<WindowMessages>();
-        grab_cursor(&mut msg);
-        hide_cursor(&mut msg);
-        self.is_hidden = true;
     }
 }
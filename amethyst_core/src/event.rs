@@ -1,4 +1,6 @@
-use crate::ecs::{Resources, SystemData, World};
+use shrev::{EventChannel, ReaderId};
+
+use crate::ecs::{Resources, System, SystemData, World};
 
 /// Read events generically
 pub trait EventReader<'a> {
@@ -21,9 +23,108 @@ pub trait EventReader<'a> {
     }
 }
 
+/// Reader ids of the `EventChannel`s read by a system, a `ReaderId` or a tuple of them.
+///
+/// Registering creates the missing channels, so it doesn't depend on the order the systems
+/// are set up.
+pub trait EventReaders: Sized {
+    /// Registers the readers in the channels of `res`.
+    fn register(res: &mut Resources) -> Self;
+}
+
+impl<T> EventReaders for ReaderId<T>
+where
+    T: Send + Sync + 'static,
+{
+    fn register(res: &mut Resources) -> Self {
+        res.entry::<EventChannel<T>>()
+            .or_insert_with(EventChannel::new)
+            .register_reader()
+    }
+}
+
+macro_rules! impl_readers {
+    ( $($ty:ident),* ) => {
+        impl<$($ty),*> EventReaders for ( $( $ty , )* )
+            where $( $ty : EventReaders ),*
+        {
+            fn register(res: &mut Resources) -> Self {
+                ( $( $ty::register(res), )* )
+            }
+        }
+    };
+}
+
+impl_readers!(A);
+impl_readers!(A, B);
+impl_readers!(A, B, C);
+impl_readers!(A, B, C, D);
+impl_readers!(A, B, C, D, E);
+impl_readers!(A, B, C, D, E, F);
+
+/// A system reading event channels, which gets its `Readers` registered when it's set up.
+///
+/// Wrap it in `WithReaders` to add it to a dispatcher.
+pub trait ReaderSystem<'a> {
+    /// The reader ids of the channels read by the system.
+    type Readers: EventReaders;
+    /// The resources and storages used by the system.
+    type SystemData: SystemData<'a>;
+
+    /// Runs the system with its reader ids.
+    fn run(&mut self, readers: &mut Self::Readers, data: Self::SystemData);
+
+    /// Sets up the resources of the system, before its readers are registered.
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+    }
+}
+
+/// Runs a `ReaderSystem`, registering its readers in `System::setup`.
+#[derive(Debug)]
+pub struct WithReaders<S, R> {
+    system: S,
+    readers: Option<R>,
+}
+
+impl<S, R> WithReaders<S, R>
+where
+    S: for<'a> ReaderSystem<'a, Readers = R>,
+    R: EventReaders,
+{
+    /// Wraps `system`.
+    pub fn new(system: S) -> Self {
+        WithReaders {
+            system,
+            readers: None,
+        }
+    }
+}
+
+impl<'a, S, R> System<'a> for WithReaders<S, R>
+where
+    S: ReaderSystem<'a, Readers = R>,
+    R: EventReaders,
+{
+    type SystemData = S::SystemData;
+
+    fn run(&mut self, data: Self::SystemData) {
+        let readers = self
+            .readers
+            .as_mut()
+            .expect("`WithReaders::setup` was not called before `WithReaders::run`");
+        self.system.run(readers, data);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        self.system.setup(res);
+        self.readers = Some(R::register(res));
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::ecs::Read;
+    use crate::ecs::{Read, RunNow, System, Write};
     use shrev::{EventChannel, ReaderId};
 
     use super::*;
@@ -96,4 +197,39 @@ mod tests {
             data.extend(system_data.1.read(&mut self.other).cloned().map(Into::into));
         }
     }
+
+    struct CountSystem;
+
+    impl<'a> ReaderSystem<'a> for CountSystem {
+        type Readers = (ReaderId<TestEvent>, ReaderId<OtherEvent>);
+        type SystemData = (
+            Read<'a, EventChannel<TestEvent>>,
+            Read<'a, EventChannel<OtherEvent>>,
+            Write<'a, usize>,
+        );
+
+        fn run(
+            &mut self,
+            readers: &mut Self::Readers,
+            (tests, others, mut count): Self::SystemData,
+        ) {
+            *count += tests.read(&mut readers.0).count() + others.read(&mut readers.1).count();
+        }
+    }
+
+    #[test]
+    fn registers_readers_on_setup() {
+        let mut world = World::new();
+        let mut system = WithReaders::new(CountSystem);
+        System::setup(&mut system, &mut world.res);
+        world
+            .write_resource::<EventChannel<TestEvent>>()
+            .iter_write(vec![TestEvent, TestEvent]);
+        world
+            .write_resource::<EventChannel<OtherEvent>>()
+            .single_write(OtherEvent);
+        system.run_now(&world.res);
+        system.run_now(&world.res);
+        assert_eq!(3, *world.read_resource::<usize>());
+    }
 }
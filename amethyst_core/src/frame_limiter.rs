@@ -38,7 +38,7 @@
 //!
 //! # Frame Rate Limiting Strategies
 //!
-//! The four possible strategies described by [`FrameRateLimitStrategy`] are as follows:
+//! The five possible strategies described by [`FrameRateLimitStrategy`] are as follows:
 //!
 //! * `Unlimited` will not try to limit the frame rate to the specified maximum. Amethyst
 //!   will call [`thread::yield_now`] once and then continue to the next frame.
@@ -53,6 +53,8 @@
 //!   and then will yield until the next frame starts. This approach attempts to get the
 //!   consistent frame timings of yielding, while reducing CPU usage compared to the yield-only
 //!   approach.
+//! * `Adaptive` works like `SleepAndYield`, but measures how late the operating system wakes the
+//!   game up after sleeping, and only yields for that amount of time.
 //!
 //! By default amethyst will use the `Yield` strategy, which is fine for desktop and console
 //! games that aren't as affected by extra CPU usage. For mobile devices, the `Sleep` strategy
@@ -61,6 +63,15 @@
 //! `SleepAndYield` can potentially be as accurate as `Yield` while using less CPU time, but you
 //! will have to test different grace period timings to determine how much time needs to be left
 //! to ensure that the main thread doesn't sleep too long and miss the start of the next frame.
+//! `Adaptive` finds this grace period by itself.
+//!
+//! # Per-state and Background Frame Rates
+//!
+//! The active state can override the maximum frame rate with `State::frame_rate_limit`, such as a
+//! lower rate for the menus. This rate applies whatever the strategy, sleeping when the strategy
+//! is `Unlimited`. While the window is not focused, for example when it is minimized,
+//! the frame rate is limited to the background frame rate if one is set, sleeping whatever the
+//! strategy, so the game doesn't keep a core busy in the background.
 //!
 //! [`Application`]: ../../amethyst/struct.Application.html
 //! [`FrameRateLimitStrategy`]: ./enum.FrameRateLimitStrategy.html
@@ -83,7 +94,7 @@ const ZERO: Duration = Duration::from_millis(0);
 /// these different strategies should be used.
 ///
 /// [module documentation]: ./index.html#frame-rate-limiting-strategies
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub enum FrameRateLimitStrategy {
     /// No limit, will do a single yield and then continue with the next frame.
     Unlimited,
@@ -99,6 +110,9 @@ pub enum FrameRateLimitStrategy {
     /// Will sleep repeatedly until the given duration remains, and then will yield repeatedly
     /// for the remaining frame time.
     SleepAndYield(Duration),
+
+    /// Use sleep and yield combined, yielding for as long as the sleeps were measured to overrun.
+    Adaptive,
 }
 
 impl Default for FrameRateLimitStrategy {
@@ -129,6 +143,10 @@ pub struct FrameRateLimitConfig {
     pub strategy: FrameRateLimitStrategy,
     /// The FPS to limit the game loop execution.
     pub fps: u32,
+    /// The FPS to limit the game loop execution to while the window is not focused.
+    #[serde(default)]
+    #[new(default)]
+    pub background_fps: Option<u32>,
 }
 
 impl Default for FrameRateLimitConfig {
@@ -136,6 +154,7 @@ impl Default for FrameRateLimitConfig {
         FrameRateLimitConfig {
             fps: 144,
             strategy: Default::default(),
+            background_fps: None,
         }
     }
 }
@@ -149,6 +168,10 @@ impl Default for FrameRateLimitConfig {
 pub struct FrameLimiter {
     frame_duration: Duration,
     strategy: FrameRateLimitStrategy,
+    state_frame_duration: Option<Duration>,
+    background_frame_duration: Option<Duration>,
+    focused: bool,
+    sleep_overrun: Duration,
     last_call: Instant,
 }
 
@@ -164,6 +187,10 @@ impl FrameLimiter {
         let mut s = Self {
             frame_duration: Duration::from_secs(0),
             strategy: Default::default(),
+            state_frame_duration: None,
+            background_frame_duration: None,
+            focused: true,
+            sleep_overrun: ZERO,
             last_call: Instant::now(),
         };
         s.set_rate(strategy, fps);
@@ -180,9 +207,37 @@ impl FrameLimiter {
         self.frame_duration = Duration::from_secs(1) / fps;
     }
 
+    /// Sets the maximum fps while the window is not focused, `None` keeping the usual rate.
+    pub fn set_background_rate(&mut self, fps: Option<u32>) {
+        self.background_frame_duration = fps.map(frame_duration);
+    }
+
+    /// Overrides the maximum fps with the rate of the active state, `Some(0)` removing the limit.
+    ///
+    /// The rate is also applied under the `Unlimited` strategy, by sleeping.
+    ///
+    /// This is called by the [`Application`] every frame with `State::frame_rate_limit`.
+    ///
+    /// [`Application`]: ../../amethyst/struct.Application.html
+    pub fn set_state_rate(&mut self, fps: Option<u32>) {
+        self.state_frame_duration = fps.map(frame_duration);
+    }
+
+    /// Tells whether the window is focused, throttling the frame rate to the background rate if
+    /// it is not.
+    ///
+    /// This is called by the [`Application`] when the focus of the window changes.
+    ///
+    /// [`Application`]: ../../amethyst/struct.Application.html
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
     /// Creates a new frame limiter with the given config.
     pub fn from_config(config: FrameRateLimitConfig) -> Self {
-        Self::new(config.strategy, config.fps)
+        let mut s = Self::new(config.strategy, config.fps);
+        s.set_background_rate(config.background_fps);
+        s
     }
 
     /// Resets the frame start time to the current instant.
@@ -202,29 +257,71 @@ impl FrameLimiter {
     /// [`Application`]: ../../amethyst/struct.Application.html
     pub fn wait(&mut self) {
         use self::FrameRateLimitStrategy::*;
-        match self.strategy {
+        let (frame_duration, strategy) = self.limit();
+        match strategy {
             Unlimited => yield_now(),
 
-            Yield => self.do_yield(),
+            Yield => self.do_yield(frame_duration),
 
-            Sleep => self.do_sleep(ZERO),
+            Sleep => self.do_sleep(frame_duration, ZERO),
 
             SleepAndYield(dur) => {
-                self.do_sleep(dur);
-                self.do_yield();
+                self.do_sleep(frame_duration, dur);
+                self.do_yield(frame_duration);
+            }
+
+            Adaptive => {
+                self.do_adaptive_sleep(frame_duration);
+                self.do_yield(frame_duration);
             }
         }
         self.last_call = Instant::now();
     }
 
-    fn do_yield(&self) {
-        while Instant::now() - self.last_call < self.frame_duration {
+    /// Returns the duration and limiting strategy of the current frame, from the background
+    /// rate, the rate of the active state or the usual rate.
+    fn limit(&self) -> (Duration, FrameRateLimitStrategy) {
+        use self::FrameRateLimitStrategy::*;
+        if let (false, Some(duration)) = (self.focused, self.background_frame_duration) {
+            return (duration, Sleep);
+        }
+        match (self.state_frame_duration, &self.strategy) {
+            (Some(ZERO), _) => (ZERO, Unlimited),
+            (Some(duration), &Unlimited) => (duration, Sleep),
+            (Some(duration), strategy) => (duration, strategy.clone()),
+            (None, strategy) => (self.frame_duration, strategy.clone()),
+        }
+    }
+
+    fn do_yield(&self, frame_duration: Duration) {
+        while Instant::now() - self.last_call < frame_duration {
             yield_now();
         }
     }
 
-    fn do_sleep(&self, stop_on_remaining: Duration) {
-        let frame_duration = self.frame_duration - stop_on_remaining;
+    /// Sleeps while more than the expected overrun of a sleep is left, and updates the expected
+    /// overrun: raised at once to the longest overrun, and slowly lowered.
+    fn do_adaptive_sleep(&mut self, frame_duration: Duration) {
+        loop {
+            let elapsed = Instant::now() - self.last_call;
+            let remaining = match frame_duration.checked_sub(elapsed) {
+                Some(remaining) if remaining > self.sleep_overrun => remaining,
+                _ => break,
+            };
+            let requested = remaining - self.sleep_overrun;
+            let start = Instant::now();
+            sleep(requested);
+            let overrun = (Instant::now() - start)
+                .checked_sub(requested)
+                .unwrap_or(ZERO);
+            self.sleep_overrun = next_sleep_overrun(self.sleep_overrun, overrun);
+        }
+    }
+
+    fn do_sleep(&self, frame_duration: Duration, stop_on_remaining: Duration) {
+        let frame_duration = frame_duration
+            .checked_sub(stop_on_remaining)
+            .unwrap_or(ZERO);
         loop {
             let elapsed = Instant::now() - self.last_call;
             if elapsed >= frame_duration {
@@ -235,3 +332,78 @@ impl FrameLimiter {
         }
     }
 }
+
+fn frame_duration(fps: u32) -> Duration {
+    if fps == 0 {
+        ZERO
+    } else {
+        Duration::from_secs(1) / fps
+    }
+}
+
+fn next_sleep_overrun(expected: Duration, overrun: Duration) -> Duration {
+    if overrun > expected {
+        overrun
+    } else {
+        (expected * 7 + overrun) / 8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{next_sleep_overrun, FrameLimiter, FrameRateLimitStrategy};
+
+    #[test]
+    fn applies_state_rate_under_every_strategy() {
+        let ms = Duration::from_millis;
+        let mut limiter = FrameLimiter::new(FrameRateLimitStrategy::Unlimited, 144);
+        assert_eq!(FrameRateLimitStrategy::Unlimited, limiter.limit().1);
+        limiter.set_state_rate(Some(50));
+        assert_eq!((ms(20), FrameRateLimitStrategy::Sleep), limiter.limit());
+
+        let mut limiter = FrameLimiter::new(FrameRateLimitStrategy::Adaptive, 144);
+        limiter.set_state_rate(Some(50));
+        assert_eq!((ms(20), FrameRateLimitStrategy::Adaptive), limiter.limit());
+        limiter.set_state_rate(Some(0));
+        assert_eq!(FrameRateLimitStrategy::Unlimited, limiter.limit().1);
+    }
+
+    #[test]
+    fn sleeps_at_background_rate_when_unfocused() {
+        let ms = Duration::from_millis;
+        let mut limiter = FrameLimiter::new(FrameRateLimitStrategy::Yield, 144);
+        limiter.set_background_rate(Some(100));
+        limiter.set_state_rate(Some(0));
+        limiter.set_focused(false);
+        assert_eq!((ms(10), FrameRateLimitStrategy::Sleep), limiter.limit());
+
+        let start = Instant::now();
+        limiter.start();
+        limiter.wait();
+        assert!(Instant::now() - start >= ms(10));
+
+        limiter.set_focused(true);
+        assert_eq!(FrameRateLimitStrategy::Unlimited, limiter.limit().1);
+    }
+
+    #[test]
+    fn adaptive_waits_for_the_frame_duration() {
+        let mut limiter = FrameLimiter::new(FrameRateLimitStrategy::Adaptive, 100);
+        for _ in 0..3 {
+            let start = Instant::now();
+            limiter.start();
+            limiter.wait();
+            assert!(Instant::now() - start >= Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn sleep_overrun_rises_fast_and_decays() {
+        let ms = Duration::from_millis;
+        assert_eq!(ms(3), next_sleep_overrun(ms(1), ms(3)));
+        assert_eq!(ms(7), next_sleep_overrun(ms(8), ms(0)));
+        assert_eq!(ms(2), next_sleep_overrun(ms(2), ms(2)));
+    }
+}
@@ -32,20 +32,23 @@ use std::sync::Arc;
 
 pub use crate::{
     bundle::SystemBundle,
-    event::EventReader,
+    event::{EventReader, EventReaders, ReaderSystem, WithReaders},
     float::Float,
-    system_ext::{Pausable, SystemExt},
+    registry::{ComponentRegistration, ComponentRegistry},
+    system_ext::{Pausable, Profiled, RunIf, SystemExt, SystemToggles, Toggled},
+    system_profile::{SystemProfile, SystemTiming},
     timing::*,
     transform::*,
 };
 
 pub use self::{
     axis::{Axis2, Axis3},
-    named::{Named, WithNamed},
+    named::{NameIndex, NameIndexSystem, Named, WithNamed},
 };
 
 pub mod bundle;
 pub mod frame_limiter;
+pub mod system_profile;
 pub mod timing;
 pub mod transform;
 
@@ -53,6 +56,7 @@ mod axis;
 mod event;
 mod float;
 mod named;
+mod registry;
 mod system_ext;
 
 /// A rayon thread pool wrapped in an `Arc`. This should be used as resource in `World`.
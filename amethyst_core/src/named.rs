@@ -1,6 +1,15 @@
 use std::borrow::Cow;
 
-use crate::ecs::{world::LazyBuilder, Component, DenseVecStorage, EntityBuilder, WriteStorage};
+use fnv::FnvHashMap;
+use hibitset::BitSet;
+
+use crate::ecs::{
+    prelude::{
+        ComponentEvent, Entities, Entity, Join, ReadStorage, ReaderId, Resources, System, Write,
+    },
+    world::LazyBuilder,
+    Component, DenseVecStorage, EntityBuilder, FlaggedStorage, WriteStorage,
+};
 use serde::{Deserialize, Serialize};
 
 /// A component that gives a name to an [`Entity`].
@@ -77,6 +86,17 @@ use serde::{Deserialize, Serialize};
 ///     }
 /// }
 /// ```
+///
+/// Finding an entity by its name, once the `NameIndexSystem` has run:
+///
+/// ```
+/// use amethyst::core::NameIndex;
+/// use amethyst::ecs::prelude::*;
+///
+/// fn find_player(world: &World) -> Option<Entity> {
+///     world.read_resource::<NameIndex>().find_by_name("player")
+/// }
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Named {
     /// The name of the entity this component is attached to.
@@ -113,7 +133,7 @@ impl Named {
 }
 
 impl Component for Named {
-    type Storage = DenseVecStorage<Self>;
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
 }
 
 /// An easy way to name an `Entity` and give it a `Named` `Component`.
@@ -150,3 +170,154 @@ impl<'a> WithNamed for LazyBuilder<'a> {
         self
     }
 }
+
+/// Resource finding the entities by their `Named` component, kept up to date by the
+/// `NameIndexSystem`, which the `TransformBundle` registers.
+///
+/// Names don't have to be unique, entities sharing a name are returned in the order they were
+/// named.
+#[derive(Debug, Default)]
+pub struct NameIndex {
+    entities: FnvHashMap<Cow<'static, str>, Vec<Entity>>,
+    names: FnvHashMap<u32, (Entity, Cow<'static, str>)>,
+}
+
+impl NameIndex {
+    /// Returns the first entity named `name`.
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.entities
+            .get(name)
+            .and_then(|entities| entities.first().cloned())
+    }
+
+    /// Returns all the entities named `name`.
+    pub fn find_all_by_name(&self, name: &str) -> &[Entity] {
+        self.entities
+            .get(name)
+            .map(|entities| entities.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the name of `entity`.
+    pub fn name_of(&self, entity: Entity) -> Option<&str> {
+        match self.names.get(&entity.id()) {
+            Some(&(named, ref name)) if named == entity => Some(name),
+            _ => None,
+        }
+    }
+
+    fn insert(&mut self, entity: Entity, name: Cow<'static, str>) {
+        self.remove(entity.id());
+        self.entities
+            .entry(name.clone())
+            .or_insert_with(Vec::new)
+            .push(entity);
+        self.names.insert(entity.id(), (entity, name));
+    }
+
+    fn remove(&mut self, id: u32) {
+        if let Some((entity, name)) = self.names.remove(&id) {
+            let empty = match self.entities.get_mut(&name) {
+                Some(entities) => {
+                    entities.retain(|e| *e != entity);
+                    entities.is_empty()
+                }
+                None => false,
+            };
+            if empty {
+                self.entities.remove(&name);
+            }
+        }
+    }
+}
+
+/// Keeps the `NameIndex` in sync with the `Named` components.
+///
+/// Registered by the `TransformBundle` as "name_index_system"; add it yourself when not using
+/// that bundle.
+#[derive(Debug, Default)]
+pub struct NameIndexSystem {
+    named: BitSet,
+    reader: Option<ReaderId<ComponentEvent>>,
+}
+
+impl NameIndexSystem {
+    /// Creates a new `NameIndexSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for NameIndexSystem {
+    type SystemData = (Entities<'a>, ReadStorage<'a, Named>, Write<'a, NameIndex>);
+
+    fn run(&mut self, (entities, names, mut index): Self::SystemData) {
+        self.named.clear();
+        let events = names.channel().read(
+            self.reader
+                .as_mut()
+                .expect("`NameIndexSystem::setup` was not called before `NameIndexSystem::run`"),
+        );
+        for event in events {
+            match *event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.named.add(id);
+                }
+                ComponentEvent::Removed(id) => {
+                    self.named.remove(id);
+                    index.remove(id);
+                }
+            }
+        }
+        for (entity, name, _) in (&*entities, &names, &self.named).join() {
+            index.insert(entity, name.name.clone());
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use crate::ecs::prelude::SystemData;
+        Self::SystemData::setup(res);
+        self.reader = Some(WriteStorage::<Named>::fetch(res).register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::prelude::{Builder, RunNow, System, World};
+
+    use super::{NameIndex, NameIndexSystem, Named, WithNamed};
+
+    #[test]
+    fn finds_entities_by_name() {
+        let mut world = World::new();
+        let mut system = NameIndexSystem::new();
+        System::setup(&mut system, &mut world.res);
+        let player = world.create_entity().named("player").build();
+        let enemy = world.create_entity().named("enemy").build();
+        let other = world.create_entity().named("enemy").build();
+        system.run_now(&world.res);
+
+        {
+            let index = world.read_resource::<NameIndex>();
+            assert_eq!(Some(player), index.find_by_name("player"));
+            assert_eq!(&[enemy, other], index.find_all_by_name("enemy"));
+            assert_eq!(Some("enemy"), index.name_of(other));
+            assert_eq!(None, index.find_by_name("camera"));
+        }
+
+        world
+            .write_storage::<Named>()
+            .get_mut(player)
+            .expect("Missing name")
+            .name = "hero".into();
+        world.delete_entity(enemy).expect("Failed to delete");
+        world.maintain();
+        system.run_now(&world.res);
+
+        let index = world.read_resource::<NameIndex>();
+        assert_eq!(None, index.find_by_name("player"));
+        assert_eq!(Some(player), index.find_by_name("hero"));
+        assert_eq!(Some(other), index.find_by_name("enemy"));
+        assert_eq!(None, index.name_of(enemy));
+    }
+}
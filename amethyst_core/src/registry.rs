@@ -0,0 +1,349 @@
+//! Registry of the components manipulated by name.
+
+use std::{
+    any::TypeId,
+    collections::{btree_map, BTreeMap},
+    fmt,
+};
+
+use amethyst_error::{format_err, Error};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::ecs::prelude::{
+    BitSet, Component, Entity, ReadStorage, Resources, SystemData, WriteStorage,
+};
+
+/// The functions of a component registered in the `ComponentRegistry`, converting it from and
+/// to a `serde_json::Value`.
+#[derive(Clone)]
+pub struct ComponentRegistration {
+    name: String,
+    type_id: TypeId,
+    setup: fn(&mut Resources),
+    entities: fn(&Resources) -> BitSet,
+    serialize: fn(&Resources, Entity) -> Result<Option<Value>, Error>,
+    deserialize: fn(&Resources, Entity, Value) -> Result<(), Error>,
+    default: Option<fn(&Resources, Entity) -> Result<(), Error>>,
+    remove: fn(&Resources, Entity) -> bool,
+}
+
+impl fmt::Debug for ComponentRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentRegistration")
+            .field("name", &self.name)
+            .field("type_id", &self.type_id)
+            .field("default", &self.default.is_some())
+            .finish()
+    }
+}
+
+impl ComponentRegistration {
+    fn new<C>(name: &str) -> Self
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned,
+    {
+        ComponentRegistration {
+            name: name.to_string(),
+            type_id: TypeId::of::<C>(),
+            setup: setup_component::<C>,
+            entities: component_entities::<C>,
+            serialize: serialize_component::<C>,
+            deserialize: deserialize_component::<C>,
+            default: None,
+            remove: remove_component::<C>,
+        }
+    }
+
+    /// Returns the name of the component.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the `TypeId` of the component.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Registers the storage of the component.
+    pub fn setup(&self, res: &mut Resources) {
+        (self.setup)(res)
+    }
+
+    /// Returns the ids of the entities which have the component.
+    pub fn entities(&self, res: &Resources) -> BitSet {
+        (self.entities)(res)
+    }
+
+    /// Returns the component of `entity`, `None` if it doesn't have one.
+    pub fn serialize(&self, res: &Resources, entity: Entity) -> Result<Option<Value>, Error> {
+        (self.serialize)(res, entity)
+    }
+
+    /// Inserts the component deserialized from `value` into `entity`, replacing its component.
+    pub fn deserialize(&self, res: &Resources, entity: Entity, value: Value) -> Result<(), Error> {
+        (self.deserialize)(res, entity, value)
+    }
+
+    /// Returns `true` if the component was registered with a default value.
+    pub fn has_default(&self) -> bool {
+        self.default.is_some()
+    }
+
+    /// Inserts the default value of the component into `entity`, replacing its component.
+    ///
+    /// Fails if the component wasn't registered with a default value.
+    pub fn insert_default(&self, res: &Resources, entity: Entity) -> Result<(), Error> {
+        match self.default {
+            Some(default) => default(res, entity),
+            None => Err(format_err!(
+                "Component {:?} was registered without a default value",
+                self.name
+            )),
+        }
+    }
+
+    /// Removes the component of `entity`, returning `true` if it had one.
+    pub fn remove(&self, res: &Resources, entity: Entity) -> bool {
+        (self.remove)(res, entity)
+    }
+}
+
+fn setup_component<C>(res: &mut Resources)
+where
+    C: Component + Send + Sync,
+{
+    WriteStorage::<C>::setup(res);
+}
+
+fn component_entities<C>(res: &Resources) -> BitSet
+where
+    C: Component + Send + Sync,
+{
+    ReadStorage::<C>::fetch(res).mask().clone()
+}
+
+fn serialize_component<C>(res: &Resources, entity: Entity) -> Result<Option<Value>, Error>
+where
+    C: Component + Send + Sync + Serialize,
+{
+    match ReadStorage::<C>::fetch(res).get(entity) {
+        Some(component) => Ok(Some(serde_json::to_value(component)?)),
+        None => Ok(None),
+    }
+}
+
+fn deserialize_component<C>(res: &Resources, entity: Entity, value: Value) -> Result<(), Error>
+where
+    C: Component + Send + Sync + DeserializeOwned,
+{
+    let component = serde_json::from_value::<C>(value)?;
+    WriteStorage::<C>::fetch(res).insert(entity, component)?;
+    Ok(())
+}
+
+fn insert_default_component<C>(res: &Resources, entity: Entity) -> Result<(), Error>
+where
+    C: Component + Send + Sync + Default,
+{
+    WriteStorage::<C>::fetch(res).insert(entity, C::default())?;
+    Ok(())
+}
+
+fn remove_component<C>(res: &Resources, entity: Entity) -> bool
+where
+    C: Component + Send + Sync,
+{
+    WriteStorage::<C>::fetch(res).remove(entity).is_some()
+}
+
+/// Resource registering components by name, so editors, consoles, scripts and save games can
+/// manipulate them without knowing their type.
+///
+/// Components are converted from and to `serde_json::Value`s with their `Serialize` and
+/// `Deserialize` implementations. Registering a name again replaces the previous component.
+///
+/// ```rust,ignore
+/// let registry = ComponentRegistry::new()
+///     .with_default_component::<Transform>("transform")
+///     .with_component::<Named>("named");
+/// registry.setup(&mut world.res);
+/// let components = registry.serialize_entity(&world.res, entity)?;
+/// world.add_resource(registry);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ComponentRegistry {
+    components: BTreeMap<String, ComponentRegistration>,
+}
+
+impl ComponentRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Registers the component `C` under `name`.
+    pub fn with_component<C>(mut self, name: &str) -> Self
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned,
+    {
+        self.register::<C>(name);
+        self
+    }
+
+    /// Registers the component `C` under `name`, with its default value.
+    pub fn with_default_component<C>(mut self, name: &str) -> Self
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned + Default,
+    {
+        self.register_default::<C>(name);
+        self
+    }
+
+    /// Registers the component `C` under `name`.
+    pub fn register<C>(&mut self, name: &str)
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned,
+    {
+        self.components
+            .insert(name.to_string(), ComponentRegistration::new::<C>(name));
+    }
+
+    /// Registers the component `C` under `name`, with its default value.
+    pub fn register_default<C>(&mut self, name: &str)
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned + Default,
+    {
+        let mut registration = ComponentRegistration::new::<C>(name);
+        registration.default = Some(insert_default_component::<C>);
+        self.components.insert(name.to_string(), registration);
+    }
+
+    /// Returns the component registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&ComponentRegistration> {
+        self.components.get(name)
+    }
+
+    /// Returns the registration of the component `C`.
+    pub fn get_type<C: 'static>(&self) -> Option<&ComponentRegistration> {
+        let type_id = TypeId::of::<C>();
+        self.components
+            .values()
+            .find(|registration| registration.type_id == type_id)
+    }
+
+    /// Iterates over the registered components, ordered by name.
+    pub fn iter(&self) -> btree_map::Values<'_, String, ComponentRegistration> {
+        self.components.values()
+    }
+
+    /// Registers the storages of all the components.
+    pub fn setup(&self, res: &mut Resources) {
+        for registration in self.components.values() {
+            registration.setup(res);
+        }
+    }
+
+    /// Returns the registered components of `entity`, by name.
+    pub fn serialize_entity(
+        &self,
+        res: &Resources,
+        entity: Entity,
+    ) -> Result<BTreeMap<String, Value>, Error> {
+        let mut components = BTreeMap::new();
+        for (name, registration) in &self.components {
+            if let Some(value) = registration.serialize(res, entity)? {
+                components.insert(name.clone(), value);
+            }
+        }
+        Ok(components)
+    }
+
+    /// Inserts the components into `entity`, by name.
+    ///
+    /// Fails on the first component which isn't registered or can't be deserialized.
+    pub fn deserialize_entity(
+        &self,
+        res: &Resources,
+        entity: Entity,
+        components: BTreeMap<String, Value>,
+    ) -> Result<(), Error> {
+        for (name, value) in components {
+            match self.components.get(&name) {
+                Some(registration) => registration.deserialize(res, entity, value)?,
+                None => return Err(format_err!("Component {:?} isn't registered", name)),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::ecs::prelude::{Builder, Component, DenseVecStorage, World};
+
+    use super::ComponentRegistry;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Health {
+        value: u32,
+    }
+
+    impl Component for Health {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Tag(String);
+
+    impl Component for Tag {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    #[test]
+    fn manipulates_components_by_name() {
+        let mut world = World::new();
+        let registry = ComponentRegistry::new()
+            .with_default_component::<Health>("health")
+            .with_component::<Tag>("tag");
+        registry.setup(&mut world.res);
+        let entity = world.create_entity().build();
+
+        let health = registry.get("health").unwrap();
+        health.insert_default(&world.res, entity).unwrap();
+        assert_eq!(
+            world.read_storage::<Health>().get(entity),
+            Some(&Health { value: 0 })
+        );
+        assert!(registry
+            .get_type::<Tag>()
+            .unwrap()
+            .insert_default(&world.res, entity)
+            .is_err());
+
+        registry
+            .get("tag")
+            .unwrap()
+            .deserialize(&world.res, entity, json!("crate"))
+            .unwrap();
+        health
+            .deserialize(&world.res, entity, json!({ "value": 3 }))
+            .unwrap();
+        let components = registry.serialize_entity(&world.res, entity).unwrap();
+        assert_eq!(components["health"], json!({ "value": 3 }));
+        assert_eq!(components["tag"], json!("crate"));
+
+        assert!(health.remove(&world.res, entity));
+        assert!(!health.entities(&world.res).contains(entity.id()));
+        assert!(registry
+            .deserialize_entity(&world.res, entity, components)
+            .is_ok());
+        assert_eq!(
+            world.read_storage::<Health>().get(entity),
+            Some(&Health { value: 3 })
+        );
+    }
+}
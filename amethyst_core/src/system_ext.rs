@@ -3,7 +3,17 @@
 //! This modules contains an extension trait for the System trait which adds useful transformation
 //! functions.
 
-use crate::ecs::prelude::{Read, System};
+use std::{marker::PhantomData, time::Instant};
+
+use fnv::FnvHashSet;
+
+use crate::{
+    ecs::{
+        prelude::{Read, System},
+        Resources,
+    },
+    system_profile::SystemProfile,
+};
 use shred::{RunningTime, SystemData};
 
 /// Extension functionality associated systems.
@@ -75,6 +85,96 @@ pub trait SystemExt {
     where
         Self: Sized,
         V: Send + Sync + Default + PartialEq;
+
+    /// Times the runs of the system, recording them as `name` in the `SystemProfile` resource.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use amethyst::{
+    ///     core::{SystemExt, SystemProfile},
+    ///     ecs::{System, Write},
+    ///     shred::DispatcherBuilder,
+    ///     prelude::*,
+    /// };
+    ///
+    /// struct AddNumber(u32);
+    ///
+    /// impl<'s> System<'s> for AddNumber {
+    ///     type SystemData = Write<'s, u32>;
+    ///
+    ///     fn run(&mut self, mut number: Self::SystemData) {
+    ///         *number += self.0;
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// let mut dispatcher = DispatcherBuilder::default()
+    ///     .with(AddNumber(1).profiled("add_number"), "add_number", &[])
+    ///     .build();
+    /// dispatcher.setup(&mut world.res);
+    /// dispatcher.dispatch(&mut world.res);
+    ///
+    /// let timing = world.read_resource::<SystemProfile>().timing("add_number");
+    /// assert_eq!(1, timing.expect("The system was not profiled").runs);
+    /// ```
+    fn profiled(self, name: &str) -> Profiled<Self>
+    where
+        Self: Sized;
+
+    /// Runs the system only when `condition` returns true for the resource `R`.
+    ///
+    /// The same notes as for `pausable` apply about reading from an `EventChannel`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use amethyst::{
+    ///     core::SystemExt,
+    ///     ecs::{System, Write},
+    ///     shred::DispatcherBuilder,
+    ///     prelude::*,
+    /// };
+    ///
+    /// #[derive(Default)]
+    /// struct DebugMode(bool);
+    ///
+    /// struct AddNumber(u32);
+    ///
+    /// impl<'s> System<'s> for AddNumber {
+    ///     type SystemData = Write<'s, u32>;
+    ///
+    ///     fn run(&mut self, mut number: Self::SystemData) {
+    ///         *number += self.0;
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// let mut dispatcher = DispatcherBuilder::default()
+    ///     .with(AddNumber(1).run_if(|debug: &DebugMode| debug.0), "debug_number", &[])
+    ///     .build();
+    /// dispatcher.setup(&mut world.res);
+    ///
+    /// dispatcher.dispatch(&mut world.res);
+    /// assert_eq!(0, *world.read_resource::<u32>());
+    ///
+    /// world.write_resource::<DebugMode>().0 = true;
+    /// dispatcher.dispatch(&mut world.res);
+    /// assert_eq!(1, *world.read_resource::<u32>());
+    /// ```
+    fn run_if<R, F>(self, condition: F) -> RunIf<Self, R, F>
+    where
+        Self: Sized,
+        R: Send + Sync + Default + 'static,
+        F: Fn(&R) -> bool + Send;
+
+    /// Runs the system only while `group` is enabled in the `SystemToggles` resource.
+    ///
+    /// Groups are enabled until they are disabled, for example by a state in its `on_start`.
+    /// The same notes as for `pausable` apply about reading from an `EventChannel`.
+    fn toggled(self, group: &str) -> Toggled<Self>
+    where
+        Self: Sized;
 }
 
 impl<'s, S> SystemExt for S
@@ -91,6 +191,39 @@ where
             value,
         }
     }
+
+    fn profiled(self, name: &str) -> Profiled<Self>
+    where
+        Self: Sized,
+    {
+        Profiled {
+            system: self,
+            name: name.to_string(),
+        }
+    }
+
+    fn run_if<R, F>(self, condition: F) -> RunIf<Self, R, F>
+    where
+        Self: Sized,
+        R: Send + Sync + Default + 'static,
+        F: Fn(&R) -> bool + Send,
+    {
+        RunIf {
+            system: self,
+            condition,
+            marker: PhantomData,
+        }
+    }
+
+    fn toggled(self, group: &str) -> Toggled<Self>
+    where
+        Self: Sized,
+    {
+        Toggled {
+            system: self,
+            groups: vec![group.to_string()],
+        }
+    }
 }
 
 /// A system that is enabled when `V` has a specific value.
@@ -123,3 +256,193 @@ where
         self.system.running_time()
     }
 }
+
+/// A system whose runs are timed in the `SystemProfile` resource.
+///
+/// This is created using the [`SystemExt::profiled`] method.
+///
+/// [`SystemExt::profiled`]: trait.SystemExt.html#tymethod.profiled
+pub struct Profiled<S> {
+    system: S,
+    name: String,
+}
+
+impl<'s, S> System<'s> for Profiled<S>
+where
+    S::SystemData: SystemData<'s>,
+    S: System<'s>,
+{
+    type SystemData = (Read<'s, SystemProfile>, S::SystemData);
+
+    fn run(&mut self, (profile, data): Self::SystemData) {
+        let start = Instant::now();
+        self.system.run(data);
+        profile.record(&self.name, start, start.elapsed());
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        <Read<'_, SystemProfile> as SystemData<'_>>::setup(res);
+        self.system.setup(res);
+    }
+}
+
+/// A system that runs when a condition on the resource `R` is true.
+///
+/// This is created using the [`SystemExt::run_if`] method.
+///
+/// [`SystemExt::run_if`]: trait.SystemExt.html#tymethod.run_if
+pub struct RunIf<S, R, F> {
+    system: S,
+    condition: F,
+    marker: PhantomData<fn(&R)>,
+}
+
+impl<'s, S, R, F> System<'s> for RunIf<S, R, F>
+where
+    S::SystemData: SystemData<'s>,
+    S: System<'s>,
+    R: Send + Sync + Default + 'static,
+    F: Fn(&R) -> bool + Send,
+{
+    type SystemData = (Read<'s, R>, S::SystemData);
+
+    fn run(&mut self, (resource, data): Self::SystemData) {
+        if (self.condition)(&resource) {
+            self.system.run(data);
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        <Read<'_, R> as SystemData<'_>>::setup(res);
+        self.system.setup(res);
+    }
+}
+
+/// Resource enabling and disabling groups of systems at runtime, without rebuilding the
+/// dispatcher.
+///
+/// Systems join groups with `SystemExt::toggled`, or when added to a `GameDataBuilder` with
+/// `with_group`. All the groups are enabled at first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemToggles {
+    disabled: FnvHashSet<String>,
+}
+
+impl SystemToggles {
+    /// Enables the systems of `group`.
+    pub fn enable(&mut self, group: &str) {
+        self.disabled.remove(group);
+    }
+
+    /// Disables the systems of `group`, the systems in several groups being disabled by any of
+    /// them.
+    pub fn disable(&mut self, group: &str) {
+        self.disabled.insert(group.to_string());
+    }
+
+    /// Enables or disables the systems of `group`.
+    pub fn set_enabled(&mut self, group: &str, enabled: bool) {
+        if enabled {
+            self.enable(group);
+        } else {
+            self.disable(group);
+        }
+    }
+
+    /// Returns true unless `group` is disabled.
+    pub fn is_enabled(&self, group: &str) -> bool {
+        !self.disabled.contains(group)
+    }
+}
+
+/// A system that runs while all its groups are enabled in the `SystemToggles` resource.
+///
+/// This is created using the [`SystemExt::toggled`] method.
+///
+/// [`SystemExt::toggled`]: trait.SystemExt.html#tymethod.toggled
+pub struct Toggled<S> {
+    system: S,
+    groups: Vec<String>,
+}
+
+impl<S> Toggled<S> {
+    /// Adds the system to another group, running it only while both groups are enabled.
+    pub fn in_group(mut self, group: &str) -> Self {
+        self.groups.push(group.to_string());
+        self
+    }
+}
+
+impl<'s, S> System<'s> for Toggled<S>
+where
+    S::SystemData: SystemData<'s>,
+    S: System<'s>,
+{
+    type SystemData = (Read<'s, SystemToggles>, S::SystemData);
+
+    fn run(&mut self, (toggles, data): Self::SystemData) {
+        if self.groups.iter().all(|group| toggles.is_enabled(group)) {
+            self.system.run(data);
+        }
+    }
+
+    fn running_time(&self) -> RunningTime {
+        self.system.running_time()
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        <Read<'_, SystemToggles> as SystemData<'_>>::setup(res);
+        self.system.setup(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{prelude::System, DispatcherBuilder, World, Write};
+
+    use super::{SystemExt, SystemToggles};
+
+    struct AddNumber(u32);
+
+    impl<'s> System<'s> for AddNumber {
+        type SystemData = Write<'s, u32>;
+
+        fn run(&mut self, mut number: Self::SystemData) {
+            *number += self.0;
+        }
+    }
+
+    #[test]
+    fn toggles_groups_at_runtime() {
+        let mut world = World::new();
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(AddNumber(1).toggled("gameplay"), "gameplay", &[])
+            .with(
+                AddNumber(10).toggled("gameplay").in_group("debug"),
+                "debug",
+                &[],
+            )
+            .build();
+        dispatcher.setup(&mut world.res);
+
+        dispatcher.dispatch(&world.res);
+        assert_eq!(11, *world.read_resource::<u32>());
+
+        world.write_resource::<SystemToggles>().disable("debug");
+        dispatcher.dispatch(&world.res);
+        assert_eq!(12, *world.read_resource::<u32>());
+
+        world.write_resource::<SystemToggles>().disable("gameplay");
+        world.write_resource::<SystemToggles>().enable("debug");
+        dispatcher.dispatch(&world.res);
+        assert_eq!(12, *world.read_resource::<u32>());
+    }
+}
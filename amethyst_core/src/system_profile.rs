@@ -0,0 +1,208 @@
+//! Timing of the systems run by the dispatchers.
+
+use std::{
+    io::{self, Write},
+    sync::Mutex,
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+use fnv::FnvHashMap;
+use serde::Serialize;
+
+use crate::timing::duration_to_secs_f64;
+
+/// Timings of a system, see `SystemProfile`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemTiming {
+    /// Duration of the last run.
+    pub last: Duration,
+    /// Moving average of the durations of the runs, following the last 16 runs or so.
+    pub average: Duration,
+    /// Duration of the longest run.
+    pub max: Duration,
+    /// The number of runs.
+    pub runs: u64,
+}
+
+impl SystemTiming {
+    fn record(&mut self, duration: Duration) {
+        self.average = if self.runs == 0 {
+            duration
+        } else {
+            (self.average * 15 + duration) / 16
+        };
+        self.last = duration;
+        self.max = self.max.max(duration);
+        self.runs += 1;
+    }
+}
+
+/// A run of a system in the chrome tracing format, with times in microseconds.
+#[derive(Debug, Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64,
+    dur: f64,
+    pid: u32,
+    tid: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Trace<'a> {
+    trace_events: &'a [TraceEvent],
+}
+
+#[derive(Debug)]
+struct ProfileData {
+    start: Instant,
+    systems: FnvHashMap<String, SystemTiming>,
+    tracing: bool,
+    trace: Vec<TraceEvent>,
+    threads: FnvHashMap<ThreadId, u32>,
+}
+
+/// Resource holding the timings of the profiled systems, to find which systems take too long.
+///
+/// Systems are profiled when wrapped with `SystemExt::profiled`, or when added to a
+/// `GameDataBuilder` with `with` or `with_fixed` after calling `with_profiling`. The systems added
+/// by bundles are not profiled by `with_profiling`. Runs can also be recorded into a trace, saved
+/// in the chrome tracing format to be opened with `chrome://tracing`.
+///
+/// Profiled systems only read this resource, so they still run in parallel.
+#[derive(Debug)]
+pub struct SystemProfile {
+    data: Mutex<ProfileData>,
+}
+
+impl Default for SystemProfile {
+    fn default() -> Self {
+        SystemProfile {
+            data: Mutex::new(ProfileData {
+                start: Instant::now(),
+                systems: FnvHashMap::default(),
+                tracing: false,
+                trace: Vec::new(),
+                threads: FnvHashMap::default(),
+            }),
+        }
+    }
+}
+
+impl SystemProfile {
+    /// Records a run of the system `name`, started at `start` and lasting `duration`.
+    pub fn record(&self, name: &str, start: Instant, duration: Duration) {
+        let mut data = self.data.lock().expect("System profile mutex poisoned");
+        let data = &mut *data;
+        match data.systems.get_mut(name) {
+            Some(timing) => timing.record(duration),
+            None => {
+                let mut timing = SystemTiming::default();
+                timing.record(duration);
+                data.systems.insert(name.to_string(), timing);
+            }
+        }
+        if data.tracing {
+            let thread_count = data.threads.len() as u32;
+            let tid = *data
+                .threads
+                .entry(thread::current().id())
+                .or_insert(thread_count);
+            let ts = duration_to_secs_f64(start.duration_since(data.start)) * 1_000_000.0;
+            data.trace.push(TraceEvent {
+                name: name.to_string(),
+                cat: "system",
+                ph: "X",
+                ts,
+                dur: duration_to_secs_f64(duration) * 1_000_000.0,
+                pid: 1,
+                tid,
+            });
+        }
+    }
+
+    /// Returns the timings of the system `name`, if it was profiled.
+    pub fn timing(&self, name: &str) -> Option<SystemTiming> {
+        let data = self.data.lock().expect("System profile mutex poisoned");
+        data.systems.get(name).cloned()
+    }
+
+    /// Returns the timings of the profiled systems, the slowest on average first.
+    pub fn timings(&self) -> Vec<(String, SystemTiming)> {
+        let data = self.data.lock().expect("System profile mutex poisoned");
+        let mut timings = data
+            .systems
+            .iter()
+            .map(|(name, timing)| (name.clone(), *timing))
+            .collect::<Vec<_>>();
+        timings.sort_by(|a, b| b.1.average.cmp(&a.1.average).then_with(|| a.0.cmp(&b.0)));
+        timings
+    }
+
+    /// Forgets the timings of the systems and the recorded trace.
+    pub fn clear(&self) {
+        let mut data = self.data.lock().expect("System profile mutex poisoned");
+        data.systems.clear();
+        data.trace.clear();
+    }
+
+    /// Starts or stops recording every run of the systems into the trace.
+    ///
+    /// The trace grows with each run, so record it for a few frames only.
+    pub fn set_tracing(&self, tracing: bool) {
+        let mut data = self.data.lock().expect("System profile mutex poisoned");
+        data.tracing = tracing;
+    }
+
+    /// Writes the recorded trace in the chrome tracing format.
+    pub fn write_chrome_trace<W: Write>(&self, writer: W) -> io::Result<()> {
+        let data = self.data.lock().expect("System profile mutex poisoned");
+        serde_json::to_writer(
+            writer,
+            &Trace {
+                trace_events: &data.trace,
+            },
+        )
+        .map_err(io::Error::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::SystemProfile;
+
+    #[test]
+    fn records_timings_and_trace() {
+        let profile = SystemProfile::default();
+        let ms = Duration::from_millis;
+        profile.set_tracing(true);
+        profile.record("physics", Instant::now(), ms(4));
+        profile.record("physics", Instant::now(), ms(20));
+        profile.record("render", Instant::now(), ms(2));
+
+        let physics = profile.timing("physics").expect("Missing physics timing");
+        assert_eq!(ms(20), physics.last);
+        assert_eq!(ms(20), physics.max);
+        assert_eq!(ms(5), physics.average);
+        assert_eq!(2, physics.runs);
+        let names = profile
+            .timings()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["physics", "render"], names);
+
+        let mut trace = Vec::new();
+        profile
+            .write_chrome_trace(&mut trace)
+            .expect("Failed to write the trace");
+        let trace = String::from_utf8(trace).expect("Trace is not UTF-8");
+        assert!(trace.starts_with(r#"{"traceEvents":[{"name":"physics","cat":"system","ph":"X""#));
+        assert_eq!(3, trace.matches(r#""ph":"X""#).count());
+    }
+}
@@ -3,6 +3,10 @@
 use std::time::{Duration, Instant};
 
 /// Frame timing values.
+///
+/// The scaled values, such as `delta_seconds`, follow the time scale and stop while the game is
+/// paused, for the gameplay. The real values, such as `delta_real_seconds`, are used by the
+/// systems which keep running in pause menus and bullet time, such as the user interface.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Time {
     /// Time elapsed since the last frame in seconds.
@@ -25,6 +29,8 @@ pub struct Time {
     absolute_time: Duration,
     ///Time multiplier. Affects returned delta_seconds, delta_time and absolute_time.
     time_scale: f32,
+    /// Stops the scaled time, keeping the time multiplier.
+    paused: bool,
     /// Fixed timestep accumulator.
     fixed_time_accumulator: f32,
     /// Fixed update interpolation alpha
@@ -92,6 +98,11 @@ impl Time {
         self.time_scale
     }
 
+    /// Returns true while the scaled time is stopped.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
     /// Gets the current interpolation alpha factor.
     pub fn interpolation_alpha(&self) -> f32 {
         self.interpolation_alpha
@@ -103,8 +114,9 @@ impl Time {
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn set_delta_seconds(&mut self, secs: f32) {
-        self.delta_seconds = secs * self.time_scale;
-        self.delta_time = secs_to_duration(secs * self.time_scale);
+        let scale = self.effective_time_scale();
+        self.delta_seconds = secs * scale;
+        self.delta_time = secs_to_duration(secs * scale);
         self.delta_real_seconds = secs;
         self.delta_real_time = secs_to_duration(secs);
 
@@ -117,8 +129,9 @@ impl Time {
     /// This should only be called by the engine.  Bad things might happen if you call this in
     /// your game.
     pub fn set_delta_time(&mut self, time: Duration) {
-        self.delta_seconds = duration_to_secs(time) * self.time_scale;
-        self.delta_time = secs_to_duration(duration_to_secs(time) * self.time_scale);
+        let scale = self.effective_time_scale();
+        self.delta_seconds = duration_to_secs(time) * scale;
+        self.delta_time = secs_to_duration(duration_to_secs(time) * scale);
         self.delta_real_seconds = duration_to_secs(time);
         self.delta_real_time = time;
 
@@ -158,6 +171,32 @@ impl Time {
         self.time_scale = multiplier;
     }
 
+    /// Stops the scaled time from the next frame, until `resume` is called.
+    ///
+    /// The scaled deltas are zero while paused, so the fixed updates and the systems using them
+    /// stop, while the systems using the real time keep running.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Restarts the scaled time from the next frame, with the time multiplier set before.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Pauses or resumes the scaled time.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    fn effective_time_scale(&self) -> f32 {
+        if self.paused {
+            0.0
+        } else {
+            self.time_scale
+        }
+    }
+
     /// Restarts the internal fixed update accumulator to the desired fixed update delta time.
     ///
     /// This should only be called by the engine.  Bad things might happen if you call this in
@@ -205,10 +244,79 @@ impl Default for Time {
             absolute_real_time: Duration::default(),
             absolute_time: Duration::default(),
             time_scale: 1.0,
+            paused: false,
         }
     }
 }
 
+/// Timing of the fixed updates, for the systems of the fixed stage of the `GameData`.
+///
+/// The fixed stage runs zero or more times per frame, once every `fixed_seconds` of game time,
+/// so its systems should use `fixed_seconds` instead of `Time::delta_seconds`. Systems rendering
+/// the state simulated by the fixed stage interpolate between its last two steps with `alpha`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FixedTime {
+    /// Time between two fixed updates in seconds.
+    fixed_seconds: f32,
+    /// The number of fixed updates run in this frame so far.
+    frame_steps: u32,
+    /// The total number of fixed updates run in this session.
+    step_number: u64,
+    /// Fraction of a fixed update left in the accumulator after the last step.
+    alpha: f32,
+}
+
+impl FixedTime {
+    /// Gets the time between two fixed updates in seconds.
+    pub fn fixed_seconds(&self) -> f32 {
+        self.fixed_seconds
+    }
+
+    /// Gets the number of fixed updates run in the current frame so far.
+    pub fn frame_steps(&self) -> u32 {
+        self.frame_steps
+    }
+
+    /// Gets the total number of fixed updates run in this session.
+    pub fn step_number(&self) -> u64 {
+        self.step_number
+    }
+
+    /// Gets the fraction, between 0.0 and 1.0, of a fixed update elapsed since the last one.
+    ///
+    /// Set once all the fixed updates of the frame are run, for interpolating between the
+    /// previous and current fixed state in the systems of the frame.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Starts the fixed updates of a frame.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn start_frame(&mut self, time: &Time) {
+        self.fixed_seconds = time.fixed_seconds();
+        self.frame_steps = 0;
+    }
+
+    /// Counts a fixed update.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn step(&mut self) {
+        self.frame_steps += 1;
+        self.step_number += 1;
+    }
+
+    /// Finishes the fixed updates of a frame, updating the interpolation alpha.
+    ///
+    /// This should only be called by the engine.  Bad things might happen if you call this in
+    /// your game.
+    pub fn finish_frame(&mut self, time: &Time) {
+        self.alpha = time.interpolation_alpha();
+    }
+}
+
 /// A stopwatch which accurately measures elapsed time.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Stopwatch {
@@ -426,6 +534,54 @@ mod tests {
         }
         assert_eq!(fixed_count, 2);
     }
+
+    // Test that pausing stops the scaled time only, and resuming keeps the time scale
+    #[test]
+    fn pause_stops_scaled_time() {
+        use super::Time;
+
+        let mut time = Time::default();
+        time.set_time_scale(0.5);
+        time.set_delta_seconds(1.0);
+        assert_eq!(0.5, time.delta_seconds());
+
+        time.pause();
+        time.set_delta_seconds(1.0);
+        assert!(time.is_paused());
+        assert_eq!(0.0, time.delta_seconds());
+        assert_eq!(1.0, time.delta_real_seconds());
+        assert_eq!(0.5, time.time_scale());
+        assert_eq!(0.5, time.absolute_time_seconds());
+        assert_eq!(2.0, time.absolute_real_time_seconds());
+
+        time.resume();
+        time.set_delta_seconds(1.0);
+        assert_eq!(0.5, time.delta_seconds());
+    }
+
+    // Test that the fixed time counts the steps of each frame, and exposes the alpha afterwards
+    #[test]
+    fn fixed_time_counts_steps() {
+        use super::{FixedTime, Time};
+
+        let mut time = Time::default();
+        let mut fixed_time = FixedTime::default();
+        time.set_fixed_seconds(0.25);
+        time.set_delta_seconds(0.625);
+
+        time.start_fixed_update();
+        fixed_time.start_frame(&time);
+        while time.step_fixed_update() {
+            fixed_time.step();
+        }
+        time.finish_fixed_update();
+        fixed_time.finish_frame(&time);
+
+        assert_eq!(0.25, fixed_time.fixed_seconds());
+        assert_eq!(2, fixed_time.frame_steps());
+        assert_eq!(2, fixed_time.step_number());
+        assert_eq!(0.5, fixed_time.alpha());
+    }
 }
 
 /// Converts a Duration to the time in seconds.
@@ -3,12 +3,15 @@
 use amethyst_error::Error;
 use specs_hierarchy::HierarchySystem;
 
-use crate::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder, transform::*};
+use crate::{
+    bundle::SystemBundle, ecs::prelude::DispatcherBuilder, named::NameIndexSystem, transform::*,
+};
 
 /// Transform bundle
 ///
-/// Will register transform components, and the `TransformSystem`.
-/// `TransformSystem` will be registered with name "transform_system".
+/// Will register transform components, the `TransformSystem` and the `NameIndexSystem`.
+/// `TransformSystem` will be registered with name "transform_system", and `NameIndexSystem` with
+/// name "name_index_system".
 ///
 /// ## Errors
 ///
@@ -16,7 +19,8 @@ use crate::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder, transform::*}
 ///
 /// ## Panics
 ///
-/// Panics in `TransformSystem` registration if the bundle is applied twice in the same dispatcher.
+/// Panics in `TransformSystem` or `NameIndexSystem` registration if the bundle is applied twice in
+/// the same dispatcher.
 ///
 #[derive(Default)]
 pub struct TransformBundle<'a> {
@@ -50,6 +54,7 @@ impl<'a, 'b, 'c> SystemBundle<'a, 'b> for TransformBundle<'c> {
             "transform_system",
             &["parent_hierarchy_system"],
         );
+        builder.add(NameIndexSystem::new(), "name_index_system", &[]);
         Ok(())
     }
 }
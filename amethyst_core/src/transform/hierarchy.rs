@@ -0,0 +1,204 @@
+//! Helpers to edit the hierarchy of entities.
+
+use amethyst_error::{format_err, Error};
+use hibitset::BitSet;
+
+use crate::{
+    ecs::prelude::{Entity, Join, World},
+    float::Float,
+    math::{Matrix3, Matrix4, Rotation3, Translation3, UnitQuaternion, Vector3, U3},
+    transform::{Parent, Transform},
+};
+
+/// Extension of the `World` to despawn, move and walk subtrees of the hierarchy.
+///
+/// The helpers read the `Parent` components directly, so they see the parents set this frame,
+/// before the `ParentHierarchy` is updated by the dispatcher.
+pub trait HierarchyExt {
+    /// Returns the descendants of `entity`, parents before their children.
+    fn descendants(&self, entity: Entity) -> Vec<Entity>;
+
+    /// Deletes `entity` with all its descendants.
+    fn despawn_recursive(&mut self, entity: Entity) -> Result<(), Error>;
+
+    /// Moves `entity` and its subtree under `parent`, or to the root when `parent` is `None`.
+    ///
+    /// The local `Transform` of `entity` is changed so that it keeps its global transform, as
+    /// computed by the `TransformSystem` on the last update. Shearing can't be kept.
+    ///
+    /// Fails if `parent` is `entity` or one of its descendants.
+    fn reparent(&mut self, entity: Entity, parent: Option<Entity>) -> Result<(), Error>;
+}
+
+impl HierarchyExt for World {
+    fn descendants(&self, entity: Entity) -> Vec<Entity> {
+        let entities = self.entities();
+        let parents = self.read_storage::<Parent>();
+        let mut found = BitSet::new();
+        let mut descendants = Vec::new();
+        let mut start = 0;
+        let mut frontier = vec![entity];
+        while !frontier.is_empty() {
+            for (child, parent) in (&*entities, &parents).join() {
+                if frontier.contains(&parent.entity) && !found.add(child.id()) {
+                    descendants.push(child);
+                }
+            }
+            frontier = descendants[start..].to_vec();
+            start = descendants.len();
+        }
+        descendants
+    }
+
+    fn despawn_recursive(&mut self, entity: Entity) -> Result<(), Error> {
+        let mut entities = self.descendants(entity);
+        entities.insert(0, entity);
+        self.delete_entities(&entities)?;
+        Ok(())
+    }
+
+    fn reparent(&mut self, entity: Entity, parent: Option<Entity>) -> Result<(), Error> {
+        if let Some(parent) = parent {
+            if parent == entity || self.descendants(entity).contains(&parent) {
+                return Err(format_err!(
+                    "Can't move {:?} under itself or its descendant {:?}",
+                    entity,
+                    parent
+                ));
+            }
+        }
+
+        let mut transforms = self.write_storage::<Transform>();
+        let parent_global = match parent {
+            Some(parent) => transforms
+                .get(parent)
+                .map(|transform| *transform.global_matrix()),
+            None => Some(Matrix4::identity()),
+        };
+        let inverse = parent_global.and_then(|global| global.try_inverse());
+        if let (Some(inverse), Some(transform)) = (inverse, transforms.get_mut(entity)) {
+            let global_matrix = transform.global_matrix;
+            *transform = decompose(&(inverse * global_matrix));
+            transform.global_matrix = global_matrix;
+        }
+
+        let mut parents = self.write_storage::<Parent>();
+        match parent {
+            Some(parent) => {
+                parents.insert(entity, Parent::new(parent))?;
+            }
+            None => {
+                parents.remove(entity);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits an affine matrix without shearing into a `Transform`.
+fn decompose(matrix: &Matrix4<Float>) -> Transform {
+    let translation = Translation3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+    let basis: Matrix3<Float> = matrix.fixed_slice::<U3, U3>(0, 0).into_owned();
+    let mut scale = Vector3::new(
+        basis.column(0).norm(),
+        basis.column(1).norm(),
+        basis.column(2).norm(),
+    );
+    if basis.determinant() < Float::from(0.0) {
+        scale.x = -scale.x;
+    }
+    let zero = Float::from(0.0);
+    if scale.x == zero || scale.y == zero || scale.z == zero {
+        return Transform::new(translation, UnitQuaternion::identity(), scale);
+    }
+    let rotation = Matrix3::from_columns(&[
+        basis.column(0) / scale.x,
+        basis.column(1) / scale.y,
+        basis.column(2) / scale.z,
+    ]);
+    let rotation =
+        UnitQuaternion::from_rotation_matrix(&Rotation3::from_matrix_unchecked(rotation));
+    Transform::new(translation, rotation, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use crate::{
+        ecs::prelude::{Builder, Entity, World},
+        math::Vector3,
+        transform::{Parent, Transform},
+    };
+
+    use super::HierarchyExt;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register::<Parent>();
+        world.register::<Transform>();
+        world
+    }
+
+    fn child(world: &mut World, parent: Entity) -> Entity {
+        world.create_entity().with(Parent::new(parent)).build()
+    }
+
+    #[test]
+    fn despawns_subtrees() {
+        let mut world = world();
+        let root = world.create_entity().build();
+        let a = child(&mut world, root);
+        let b = child(&mut world, a);
+        let c = child(&mut world, b);
+        let other = world.create_entity().build();
+
+        assert_eq!(vec![b, c], world.descendants(a));
+        world.despawn_recursive(a).expect("Failed to despawn");
+        assert!(world.is_alive(root));
+        assert!(world.is_alive(other));
+        assert!(![a, b, c].iter().any(|e| world.is_alive(*e)));
+        assert!(world.descendants(root).is_empty());
+    }
+
+    #[test]
+    fn reparent_keeps_global_transform() {
+        let mut world = world();
+        let mut parent_transform = Transform::default();
+        parent_transform.set_translation_xyz(10.0, 0.0, 0.0);
+        parent_transform.set_rotation_2d(std::f32::consts::FRAC_PI_2);
+        parent_transform.set_scale(Vector3::new(2.0.into(), 2.0.into(), 2.0.into()));
+        parent_transform.global_matrix = parent_transform.matrix();
+        let parent = world.create_entity().with(parent_transform).build();
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(4.0, 6.0, 0.0);
+        transform.global_matrix = transform.matrix();
+        let global = transform.global_matrix;
+        let entity = world.create_entity().with(transform).build();
+
+        assert!(world.reparent(parent, Some(entity)).is_ok());
+        assert!(world.reparent(entity, Some(parent)).is_err());
+        world
+            .reparent(parent, None)
+            .expect("Failed to move to root");
+        world
+            .reparent(entity, Some(parent))
+            .expect("Failed to move under parent");
+
+        assert_eq!(vec![entity], world.descendants(parent));
+        let transforms = world.read_storage::<Transform>();
+        let transform = transforms.get(entity).expect("Missing transform");
+        let parent_global = *transforms
+            .get(parent)
+            .expect("Missing parent transform")
+            .global_matrix();
+        assert_relative_eq!(
+            global,
+            parent_global * transform.matrix(),
+            epsilon = 1e-4.into()
+        );
+        assert_relative_eq!(transform.translation().x, 3.0.into(), epsilon = 1e-4.into());
+        assert_relative_eq!(transform.translation().y, 3.0.into(), epsilon = 1e-4.into());
+        assert_relative_eq!(transform.scale().x, 0.5.into(), epsilon = 1e-4.into());
+    }
+}
@@ -0,0 +1,117 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Ident, Type};
+
+pub fn impl_animation_sampling(ast: &DeriveInput) -> TokenStream {
+    if ast.generics.params.iter().next().is_some() {
+        panic!("AnimationSampling derive does not support generic structs");
+    }
+    let base = &ast.ident;
+    let vis = &ast.vis;
+    let channel = Ident::new(&format!("{}Channel", base), Span::call_site());
+    let fields = collect_animated_fields(&ast.data);
+    if fields.is_empty() {
+        panic!("AnimationSampling derive requires at least one field marked with #[animate]");
+    }
+
+    let channel_doc = format!("Channels that can be animated on `{}`", base);
+    let variants = fields.iter().map(|(_, name, variant)| {
+        let doc = format!("The `{}` field", name);
+        quote! {
+            #[doc = #doc]
+            #variant
+        }
+    });
+    let applies = fields.iter().map(|(_, name, variant)| {
+        quote! {
+            #channel::#variant => AnimatableProperty::apply_primitive(&mut self.#name, data),
+        }
+    });
+    let samples = fields.iter().map(|(_, name, variant)| {
+        quote! {
+            #channel::#variant => AnimatableProperty::to_primitive(&self.#name),
+        }
+    });
+    let defaults = fields.iter().map(|(ty, _, variant)| {
+        quote! {
+            #channel::#variant => <#ty as AnimatableProperty>::default_primitive(),
+        }
+    });
+
+    quote! {
+        #[doc = #channel_doc]
+        #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+        #vis enum #channel {
+            #( #variants, )*
+        }
+
+        impl<'a> ApplyData<'a> for #base {
+            type ApplyData = ();
+        }
+
+        impl AnimationSampling for #base {
+            type Primitive = SamplerPrimitive<f32>;
+            type Channel = #channel;
+
+            fn apply_sample(&mut self, channel: &Self::Channel, data: &Self::Primitive, _: &()) {
+                match *channel {
+                    #( #applies )*
+                }
+            }
+
+            fn current_sample(&self, channel: &Self::Channel, _: &()) -> Self::Primitive {
+                match *channel {
+                    #( #samples )*
+                }
+            }
+
+            fn default_primitive(channel: &Self::Channel) -> Self::Primitive {
+                match *channel {
+                    #( #defaults )*
+                }
+            }
+
+            fn blend_method(&self, _: &Self::Channel) -> Option<BlendMethod> {
+                Some(BlendMethod::Linear)
+            }
+        }
+    }
+}
+
+/// Returns the type, name and channel variant of the fields marked with `#[animate]`.
+fn collect_animated_fields(ast: &Data) -> Vec<(Type, Ident, Ident)> {
+    match *ast {
+        Data::Struct(ref s) => s
+            .fields
+            .iter()
+            .filter(|f| {
+                f.attrs
+                    .iter()
+                    .any(|attr| attr.path.segments[0].ident == "animate")
+            })
+            .map(|f| {
+                let name = f
+                    .ident
+                    .as_ref()
+                    .expect("AnimationSampling derive only supports named fields")
+                    .clone();
+                let variant = Ident::new(&camel_case(&name.to_string()), Span::call_site());
+                (f.ty.clone(), name, variant)
+            })
+            .collect(),
+        _ => panic!("AnimationSampling derive only supports structs"),
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -6,10 +6,25 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
+mod animation_sampling;
 mod event_reader;
 mod prefab_data;
 mod widget_id;
 
+/// Implements `AnimationSampling` for a component, animating its fields marked with
+/// `#[animate]`, which must implement `AnimatableProperty`. A `<Component>Channel` enum is
+/// generated, with a variant for each animated field named in `CamelCase`.
+///
+/// Requires that `amethyst::animation::{AnimatableProperty, AnimationSampling, ApplyData,
+/// BlendMethod, SamplerPrimitive}` and `serde::{Deserialize, Serialize}` are imported and visible
+/// in the current scope.
+#[proc_macro_derive(AnimationSampling, attributes(animate))]
+pub fn animation_sampling_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let gen = animation_sampling::impl_animation_sampling(&ast);
+    gen.into()
+}
+
 #[proc_macro_derive(EventReader, attributes(reader))]
 pub fn event_reader_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
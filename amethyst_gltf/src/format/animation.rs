@@ -3,8 +3,8 @@ use std::collections::HashMap;
 use amethyst_error::Error;
 
 use amethyst_animation::{
-    AnimationPrefab, AnimationSetPrefab, InterpolationFunction, InterpolationPrimitive, Sampler,
-    SamplerPrimitive, TransformChannel,
+    AnimationCompression, AnimationPrefab, AnimationSetPrefab, InterpolationFunction,
+    InterpolationPrimitive, Sampler, SamplerPrimitive, TransformChannel,
 };
 use amethyst_core::{Float, Transform};
 
@@ -15,10 +15,12 @@ pub fn load_animations(
     gltf: &gltf::Gltf,
     buffers: &Buffers,
     node_map: &HashMap<usize, usize>,
+    compression: Option<&AnimationCompression>,
 ) -> Result<AnimationSetPrefab<usize, Transform>, Error> {
     let mut prefab = AnimationSetPrefab::default();
     for animation in gltf.animations() {
-        let anim = load_animation(&animation, buffers)?;
+        let mut anim = load_animation(&animation, buffers)?;
+        anim.compression = compression.cloned();
         if anim
             .samplers
             .iter()
@@ -198,7 +198,12 @@ fn load_scene(
             .data_or_default(0)
             .animatable
             .get_or_insert_with(Default::default)
-            .animation_set = Some(load_animations(gltf, buffers, &node_map)?);
+            .animation_set = Some(load_animations(
+            gltf,
+            buffers,
+            &node_map,
+            options.compress_animations.as_ref(),
+        )?);
     }
 
     Ok(())
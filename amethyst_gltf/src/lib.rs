@@ -8,7 +8,7 @@ pub use crate::format::GltfSceneFormat;
 
 use std::{collections::HashMap, ops::Range};
 
-use amethyst_animation::{AnimatablePrefab, SkinnablePrefab};
+use amethyst_animation::{AnimatablePrefab, AnimationCompression, SkinnablePrefab};
 use amethyst_assets::{Handle, Prefab, PrefabData, PrefabLoaderSystem, ProgressCounter};
 use amethyst_core::{
     ecs::prelude::{Component, DenseVecStorage, Entity, Write, WriteStorage},
@@ -175,6 +175,8 @@ pub struct GltfSceneOptions {
     /// Load the given scene index, if not supplied will either load the default scene (if set),
     /// or the first scene (only if there is only one scene, otherwise an `Error` will be returned).
     pub scene_index: Option<usize>,
+    /// Compress the samplers of the loaded animations
+    pub compress_animations: Option<AnimationCompression>,
 }
 
 impl<'a> PrefabData<'a> for GltfPrefab {
@@ -0,0 +1,47 @@
+//! ECS imgui bundle
+
+use std::hash::Hash;
+
+use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
+use amethyst_error::Error;
+
+use crate::system::ImguiSystem;
+
+/// Bundle adding the `ImguiSystem` with name "imgui_system".
+///
+/// The input fed to imgui is the one of the `InputHandler<AX, AC>`, so this bundle must be added
+/// after the `InputBundle`. The systems building the UI should depend on "imgui_system", and the
+/// `DrawImgui` pass should be added to the pipeline.
+///
+/// ## Errors
+///
+/// No errors will be returned by this bundle.
+pub struct ImguiBundle<AX = String, AC = String> {
+    system: ImguiSystem<AX, AC>,
+}
+
+impl<AX, AC> Default for ImguiBundle<AX, AC> {
+    fn default() -> Self {
+        ImguiBundle::new()
+    }
+}
+
+impl<AX, AC> ImguiBundle<AX, AC> {
+    /// Creates a new imgui bundle.
+    pub fn new() -> Self {
+        ImguiBundle {
+            system: ImguiSystem::new(),
+        }
+    }
+}
+
+impl<'a, 'b, AX, AC> SystemBundle<'a, 'b> for ImguiBundle<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        builder.add(self.system, "imgui_system", &["input_system"]);
+        Ok(())
+    }
+}
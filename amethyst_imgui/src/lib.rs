@@ -0,0 +1,30 @@
+//! Immediate-mode debug UI for Amethyst, backed by imgui.
+//!
+//! The `ImguiSystem`, added by the `ImguiBundle`, feeds the input of the `InputHandler` to imgui
+//! and opens a new frame every tick. Systems build entity inspectors and tweak windows in that
+//! frame through the `ImguiState` resource, and the `DrawImgui` pass draws it.
+//!
+//! ```rust,ignore
+//! let pipe = Pipeline::build().with_stage(
+//!     Stage::with_backbuffer()
+//!         .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+//!         .with_pass(DrawFlat2D::new())
+//!         .with_pass(DrawImgui::new()),
+//! );
+//! let game_data = GameDataBuilder::default()
+//!     .with_bundle(InputBundle::<String, String>::new())?
+//!     .with_bundle(ImguiBundle::<String, String>::new())?
+//!     .with(InspectorSystem, "inspector", &["imgui_system"])
+//!     .with_bundle(RenderBundle::new(pipe, Some(config)))?;
+//! ```
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use imgui;
+
+pub use crate::{bundle::ImguiBundle, pass::DrawImgui, state::ImguiState, system::ImguiSystem};
+
+mod bundle;
+mod pass;
+mod state;
+mod system;
@@ -0,0 +1,279 @@
+//! Pass drawing the imgui frame.
+
+use gfx::{
+    buffer::Role,
+    format::{ChannelType, Format, SurfaceType},
+    preset::blend,
+    pso::buffer::{ElemStride, Element},
+    state::ColorMask,
+    traits::Pod,
+    IndexBuffer, Slice,
+};
+use glsl_layout::{vec2, vec4, Uniform};
+use imgui::{DrawList, ImDrawIdx, ImDrawVert};
+use log::error;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_core::ecs::prelude::Write;
+use amethyst_error::Error;
+use amethyst_renderer::{
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect,
+    },
+    Attributes, BufferRing, Encoder, Factory, Texture, VertexFormat,
+};
+
+use crate::state::ImguiState;
+
+const VERT_SRC: &[u8] = include_bytes!("shaders/vertex.glsl");
+const FRAG_SRC: &[u8] = include_bytes!("shaders/frag.glsl");
+
+#[derive(Copy, Clone, Debug, Uniform)]
+#[allow(dead_code)] // This is used by the shaders
+#[repr(C)]
+struct ImguiArgs {
+    invert_window_size: vec2,
+    clip: vec4,
+}
+
+/// Vertex of imgui, with its position in points and its color unpacked.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct ImguiVertex {
+    position: [f32; 2],
+    tex_coord: [f32; 2],
+    color: [f32; 4],
+}
+
+unsafe impl Pod for ImguiVertex {}
+
+impl VertexFormat for ImguiVertex {
+    const ATTRIBUTES: Attributes<'static> = &[
+        (
+            "position",
+            Element {
+                offset: 0,
+                format: Format(SurfaceType::R32_G32, ChannelType::Float),
+            },
+        ),
+        (
+            "tex_coord",
+            Element {
+                offset: 8,
+                format: Format(SurfaceType::R32_G32, ChannelType::Float),
+            },
+        ),
+        (
+            "color",
+            Element {
+                offset: 16,
+                format: Format(SurfaceType::R32_G32_B32_A32, ChannelType::Float),
+            },
+        ),
+    ];
+}
+
+impl<'a> From<&'a ImDrawVert> for ImguiVertex {
+    fn from(vertex: &'a ImDrawVert) -> Self {
+        let channel = |shift: u32| ((vertex.col >> shift) & 0xFF) as f32 / 255.0;
+        ImguiVertex {
+            position: [vertex.pos.x, vertex.pos.y],
+            // Textures are flipped vertically when uploaded.
+            tex_coord: [vertex.uv.x, 1.0 - vertex.uv.y],
+            color: [channel(0), channel(8), channel(16), channel(24)],
+        }
+    }
+}
+
+/// Draw the imgui frame of the `ImguiState`. It's recommended this be your last pass.
+///
+/// Only the font texture of imgui is supported, images drawn with other textures are drawn with
+/// the font texture.
+#[derive(Default)]
+pub struct DrawImgui {
+    font: Option<Texture>,
+    buffers: ImguiBuffers,
+}
+
+impl DrawImgui {
+    /// Create instance of `DrawImgui` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> PassData<'a> for DrawImgui {
+    type Data = Write<'a, ImguiState>;
+}
+
+impl Pass for DrawImgui {
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
+        use std::mem;
+        effect
+            .simple(VERT_SRC, FRAG_SRC)
+            .without_back_face_culling()
+            .with_raw_constant_buffer(
+                "ImguiArgs",
+                mem::size_of::<<ImguiArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_raw_vertex_buffer(
+                ImguiVertex::ATTRIBUTES,
+                ImguiVertex::size() as ElemStride,
+                0,
+            )
+            .with_texture("albedo")
+            .with_blended_output("color", ColorMask::all(), blend::ALPHA, None)
+            .build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        mut state: <Self as PassData<'_>>::Data,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("imgui_pass_apply");
+
+        if self.font.is_none() {
+            let (width, height, pixels) = state.font();
+            match Texture::from_data(pixels)
+                .with_size(width as u16, height as u16)
+                .build(&mut factory)
+            {
+                Ok(font) => self.font = Some(font),
+                Err(err) => {
+                    error!("Failed to create the imgui font texture: {}", err);
+                    state.end_frame();
+                    return;
+                }
+            }
+        }
+
+        let display = state.display();
+        let DrawImgui {
+            ref font,
+            ref mut buffers,
+        } = *self;
+        let font = font
+            .as_ref()
+            .expect("The imgui font texture was not created");
+        state.render(|draw_list| {
+            if let Err(err) = buffers.draw(&draw_list, display, font, encoder, effect, &mut factory)
+            {
+                error!("Failed to draw the imgui frame: {}", err);
+            }
+        });
+        buffers.reset();
+    }
+}
+
+/// Vertex and index buffers of the draw lists, kept between frames.
+struct ImguiBuffers {
+    vertices: Vec<ImguiVertex>,
+    vertex_buffers: BufferRing<ImguiVertex>,
+    index_buffers: BufferRing<ImDrawIdx>,
+}
+
+impl Default for ImguiBuffers {
+    fn default() -> Self {
+        ImguiBuffers {
+            vertices: Vec::new(),
+            vertex_buffers: BufferRing::new(Role::Vertex),
+            index_buffers: BufferRing::new(Role::Index),
+        }
+    }
+}
+
+impl ImguiBuffers {
+    /// Makes the buffers available for the draw lists of the next frame.
+    fn reset(&mut self) {
+        self.vertex_buffers.reset();
+        self.index_buffers.reset();
+    }
+
+    /// Draws a list of the frame, with the clipping rectangle of each of its commands.
+    fn draw(
+        &mut self,
+        draw_list: &DrawList<'_>,
+        ((width, height), (scale_x, scale_y)): ((f32, f32), (f32, f32)),
+        font: &Texture,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        factory: &mut Factory,
+    ) -> Result<(), Error> {
+        if width <= 0.0 || height <= 0.0 || draw_list.idx_buffer.is_empty() {
+            return Ok(());
+        }
+
+        self.vertices.clear();
+        self.vertices
+            .extend(draw_list.vtx_buffer.iter().map(ImguiVertex::from));
+        let vbuf = self
+            .vertex_buffers
+            .upload(factory, encoder, effect, &self.vertices)?
+            .raw()
+            .clone();
+        let ibuf = self
+            .index_buffers
+            .upload(factory, encoder, effect, draw_list.idx_buffer)?
+            .clone();
+        effect.data.vertex_bufs.push(vbuf);
+        effect.data.textures.push(font.view().clone());
+        effect.data.samplers.push(font.sampler().clone());
+
+        let mut slice = Slice {
+            start: 0,
+            end: 0,
+            base_vertex: 0,
+            instances: None,
+            buffer: IndexBuffer::Index16(ibuf),
+        };
+        for command in draw_list.cmd_buffer {
+            slice.end = slice.start + command.elem_count;
+            // The clipping rectangle is in points from the top left corner, the shader clips in
+            // pixels from the bottom left corner.
+            let clip = &command.clip_rect;
+            let args = ImguiArgs {
+                invert_window_size: [1.0 / width, 1.0 / height].into(),
+                clip: [
+                    clip.x * scale_x,
+                    (height - clip.w) * scale_y,
+                    clip.z * scale_x,
+                    (height - clip.y) * scale_y,
+                ]
+                .into(),
+            };
+            effect.update_constant_buffer("ImguiArgs", &args.std140(), encoder);
+            effect.draw(&slice, encoder);
+            slice.start = slice.end;
+        }
+
+        effect.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imgui::{ImDrawVert, ImVec2};
+
+    use super::ImguiVertex;
+
+    #[test]
+    fn unpacks_imgui_vertices() {
+        let vertex = ImguiVertex::from(&ImDrawVert {
+            pos: ImVec2::new(10.0, 20.0),
+            uv: ImVec2::new(0.25, 0.75),
+            col: 0xFF00_80FF,
+        });
+        assert_eq!(vertex.position, [10.0, 20.0]);
+        assert_eq!(vertex.tex_coord, [0.25, 0.25]);
+        assert_eq!(vertex.color, [1.0, 128.0 / 255.0, 0.0, 1.0]);
+    }
+}
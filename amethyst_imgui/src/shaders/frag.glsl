@@ -0,0 +1,19 @@
+#version 150 core
+
+uniform sampler2D albedo;
+
+in VertexData {
+  vec2 tex_coord;
+  vec4 color;
+  vec4 clip;
+} vertex;
+
+out vec4 color;
+
+void main() {
+    if (gl_FragCoord.x < vertex.clip.x || gl_FragCoord.y < vertex.clip.y
+        || gl_FragCoord.x > vertex.clip.z || gl_FragCoord.y > vertex.clip.w) {
+        discard;
+    }
+    color = texture(albedo, vertex.tex_coord) * vertex.color;
+}
@@ -0,0 +1,27 @@
+#version 150 core
+
+// std140 is a cross platform layout.
+layout (std140) uniform ImguiArgs {
+    uniform vec2 invert_window_size;
+    // Left, bottom, right and top edges of the visible area, in pixels.
+    uniform vec4 clip;
+};
+
+// Position in points, from the top left corner of the window.
+in vec2 position;
+in vec2 tex_coord;
+in vec4 color;
+
+out VertexData {
+  vec2 tex_coord;
+  vec4 color;
+  vec4 clip;
+} vertex;
+
+void main() {
+    vec2 scaled = position * invert_window_size * 2;
+    vertex.tex_coord = tex_coord;
+    vertex.color = color;
+    vertex.clip = clip;
+    gl_Position = vec4(scaled.x - 1, 1 - scaled.y, 0, 1);
+}
@@ -0,0 +1,205 @@
+//! Resource holding the imgui context
+
+use imgui::{DrawList, ImGui, ImGuiKey, Ui};
+
+use amethyst_renderer::VirtualKeyCode;
+
+/// The keys used by imgui, and the key codes they are mapped to.
+pub(crate) const KEYS: &[(ImGuiKey, VirtualKeyCode)] = &[
+    (ImGuiKey::Tab, VirtualKeyCode::Tab),
+    (ImGuiKey::LeftArrow, VirtualKeyCode::Left),
+    (ImGuiKey::RightArrow, VirtualKeyCode::Right),
+    (ImGuiKey::UpArrow, VirtualKeyCode::Up),
+    (ImGuiKey::DownArrow, VirtualKeyCode::Down),
+    (ImGuiKey::PageUp, VirtualKeyCode::PageUp),
+    (ImGuiKey::PageDown, VirtualKeyCode::PageDown),
+    (ImGuiKey::Home, VirtualKeyCode::Home),
+    (ImGuiKey::End, VirtualKeyCode::End),
+    (ImGuiKey::Delete, VirtualKeyCode::Delete),
+    (ImGuiKey::Backspace, VirtualKeyCode::Back),
+    (ImGuiKey::Enter, VirtualKeyCode::Return),
+    (ImGuiKey::Escape, VirtualKeyCode::Escape),
+    (ImGuiKey::A, VirtualKeyCode::A),
+    (ImGuiKey::C, VirtualKeyCode::C),
+    (ImGuiKey::V, VirtualKeyCode::V),
+    (ImGuiKey::X, VirtualKeyCode::X),
+    (ImGuiKey::Y, VirtualKeyCode::Y),
+    (ImGuiKey::Z, VirtualKeyCode::Z),
+];
+
+/// Resource holding the imgui context and the frame being built.
+///
+/// The `ImguiSystem` opens a new frame every tick, systems add their windows to it with `ui`, and
+/// the `DrawImgui` pass draws it. Systems building the UI should depend on "imgui_system", and
+/// are run one after the other since they all write this resource.
+///
+/// imgui has a single global context, so there must only be one `ImguiState` at a time.
+///
+/// ```rust,ignore
+/// impl<'a> System<'a> for InspectorSystem {
+///     type SystemData = (Write<'a, ImguiState>, ReadStorage<'a, Named>);
+///
+///     fn run(&mut self, (mut imgui, names): Self::SystemData) {
+///         imgui.ui(|ui| {
+///             ui.window(im_str!("Entities")).build(|| {
+///                 for name in names.join() {
+///                     ui.text(im_str!("{}", name.name));
+///                 }
+///             });
+///         });
+///     }
+/// }
+/// ```
+pub struct ImguiState {
+    // Boxed so the context keeps its address, which imgui holds on to while a frame is open.
+    imgui: Box<ImGui>,
+    frame_open: bool,
+    font: (u32, u32, Vec<u8>),
+}
+
+impl Default for ImguiState {
+    fn default() -> Self {
+        ImguiState::new()
+    }
+}
+
+impl ImguiState {
+    /// Creates the imgui context, with the keys mapped to their `VirtualKeyCode`.
+    pub fn new() -> Self {
+        let mut imgui = Box::new(ImGui::init());
+        imgui.set_ini_filename(None);
+        for &(key, code) in KEYS {
+            imgui.set_imgui_key(key, code as u8);
+        }
+        // The font atlas has to be built before the first frame.
+        let font =
+            imgui.prepare_texture(|handle| (handle.width, handle.height, handle.pixels.to_vec()));
+        ImguiState {
+            imgui,
+            frame_open: false,
+            font,
+        }
+    }
+
+    /// Builds the UI of the current frame with `f`, returning `None` if no frame is open.
+    pub fn ui<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&Ui<'_>) -> R,
+    {
+        self.current_ui().map(f)
+    }
+
+    /// Returns `true` if imgui uses the mouse, and the game should ignore it.
+    pub fn want_capture_mouse(&self) -> bool {
+        self.current_ui()
+            .map_or(false, |ui| ui.want_capture_mouse())
+    }
+
+    /// Returns `true` if imgui uses the keyboard, and the game should ignore it.
+    pub fn want_capture_keyboard(&self) -> bool {
+        self.current_ui()
+            .map_or(false, |ui| ui.want_capture_keyboard())
+    }
+
+    /// Returns the ui of the open frame, which can't outlive the borrow of the context.
+    fn current_ui(&self) -> Option<&Ui<'_>> {
+        if self.frame_open {
+            unsafe { Ui::current_ui() }
+        } else {
+            None
+        }
+    }
+
+    /// Returns the mutable context, to feed the input, `None` while a frame is open.
+    pub(crate) fn imgui_mut(&mut self) -> Option<&mut ImGui> {
+        if self.frame_open {
+            None
+        } else {
+            Some(&mut self.imgui)
+        }
+    }
+
+    /// Returns the width, height and RGBA pixels of the font atlas.
+    pub(crate) fn font(&self) -> (u32, u32, &[u8]) {
+        (self.font.0, self.font.1, &self.font.2)
+    }
+
+    /// Returns the size of the display in points, and the number of pixels per point.
+    pub(crate) fn display(&self) -> ((f32, f32), (f32, f32)) {
+        (
+            self.imgui.display_size(),
+            self.imgui.display_framebuffer_scale(),
+        )
+    }
+
+    /// Opens a new frame, discarding the current frame if it wasn't drawn.
+    pub(crate) fn new_frame(
+        &mut self,
+        size_points: (u32, u32),
+        size_pixels: (u32, u32),
+        delta_seconds: f32,
+    ) {
+        self.end_frame();
+        // The returned ui is dropped right away, `current_ui` borrows it again from imgui.
+        self.imgui.frame(size_points, size_pixels, delta_seconds);
+        self.frame_open = true;
+    }
+
+    /// Ends the current frame without drawing it.
+    pub(crate) fn end_frame(&mut self) {
+        self.render(|_| {});
+    }
+
+    /// Ends the current frame, calling `f` with each of its draw lists.
+    pub(crate) fn render<F>(&mut self, mut f: F)
+    where
+        F: FnMut(DrawList<'_>),
+    {
+        if !self.frame_open {
+            return;
+        }
+        self.frame_open = false;
+        unsafe {
+            imgui_sys::igRender();
+            let draw_data = &*imgui_sys::igGetDrawData();
+            for &cmd_list in draw_data.cmd_lists() {
+                f(DrawList {
+                    cmd_buffer: (*cmd_list).cmd_buffer.as_slice(),
+                    idx_buffer: (*cmd_list).idx_buffer.as_slice(),
+                    vtx_buffer: (*cmd_list).vtx_buffer.as_slice(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imgui::im_str;
+
+    use super::ImguiState;
+
+    // imgui has a single global context, so it is only created by this test.
+    #[test]
+    fn builds_and_renders_frames() {
+        let mut state = ImguiState::new();
+        assert!(state.ui(|_| ()).is_none());
+
+        let mut vertices = 0;
+        for _ in 0..2 {
+            state.new_frame((320, 240), (640, 480), 1.0 / 60.0);
+            assert!(state.imgui_mut().is_none());
+            state.ui(|ui| {
+                ui.window(im_str!("Inspector"))
+                    .build(|| ui.text(im_str!("Entities")))
+            });
+            vertices = 0;
+            state.render(|draw_list| vertices += draw_list.vtx_buffer.len());
+        }
+
+        assert!(vertices > 0);
+        assert!(state.ui(|_| ()).is_none());
+        assert!(state.imgui_mut().is_some());
+        assert_eq!(state.display(), ((320.0, 240.0), (2.0, 2.0)));
+    }
+}
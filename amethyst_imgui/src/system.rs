@@ -0,0 +1,128 @@
+//! The system feeding the input to imgui.
+
+use std::{hash::Hash, marker::PhantomData};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_core::{
+    ecs::prelude::{Read, ReadExpect, ReaderId, Resources, System, SystemData, Write},
+    shrev::EventChannel,
+    Time,
+};
+use amethyst_input::{InputEvent, InputHandler, ScrollDirection};
+use amethyst_renderer::{MouseButton, ScreenDimensions, VirtualKeyCode};
+
+use crate::state::{ImguiState, KEYS};
+
+/// Mouse buttons in the order used by imgui.
+const MOUSE_BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::Other(0),
+    MouseButton::Other(1),
+];
+
+/// Feeds the mouse, keyboard and typed characters of the `InputHandler<AX, AC>` to imgui, and
+/// opens a new frame in the `ImguiState`.
+///
+/// The frame of the previous tick is discarded if it wasn't drawn by the `DrawImgui` pass.
+pub struct ImguiSystem<AX = String, AC = String> {
+    input_reader: Option<ReaderId<InputEvent<AC>>>,
+    _marker: PhantomData<AX>,
+}
+
+impl<AX, AC> Default for ImguiSystem<AX, AC> {
+    fn default() -> Self {
+        ImguiSystem {
+            input_reader: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<AX, AC> ImguiSystem<AX, AC> {
+    /// Creates a new imgui system.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, AX, AC> System<'a> for ImguiSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, Time>,
+        ReadExpect<'a, ScreenDimensions>,
+        Read<'a, InputHandler<AX, AC>>,
+        Read<'a, EventChannel<InputEvent<AC>>>,
+        Write<'a, ImguiState>,
+    );
+
+    fn run(&mut self, (time, screen_dimensions, input, events, mut state): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("imgui_system");
+
+        state.end_frame();
+        let hidpi = screen_dimensions.hidpi_factor() as f32;
+        {
+            let imgui = state
+                .imgui_mut()
+                .expect("The imgui frame was not ended before feeding the input");
+
+            // imgui works in points, the mouse position is in pixels.
+            match input.mouse_position() {
+                Some((x, y)) => imgui.set_mouse_pos(x as f32 / hidpi, y as f32 / hidpi),
+                None => imgui.set_mouse_pos(-1.0, -1.0),
+            }
+            let mut buttons = [false; 5];
+            for (down, button) in buttons.iter_mut().zip(MOUSE_BUTTONS.iter()) {
+                *down = input.mouse_button_is_down(*button);
+            }
+            imgui.set_mouse_down(&buttons);
+
+            for &(_, code) in KEYS {
+                imgui.set_key(code as u8, input.key_is_down(code));
+            }
+            let down = |left, right| input.key_is_down(left) || input.key_is_down(right);
+            imgui.set_key_ctrl(down(VirtualKeyCode::LControl, VirtualKeyCode::RControl));
+            imgui.set_key_shift(down(VirtualKeyCode::LShift, VirtualKeyCode::RShift));
+            imgui.set_key_alt(down(VirtualKeyCode::LAlt, VirtualKeyCode::RAlt));
+            imgui.set_key_super(down(VirtualKeyCode::LWin, VirtualKeyCode::RWin));
+
+            let mut wheel = 0.0;
+            for event in events.read(
+                self.input_reader
+                    .as_mut()
+                    .expect("`ImguiSystem::setup` was not called before `ImguiSystem::run`"),
+            ) {
+                match *event {
+                    InputEvent::KeyTyped(character) => imgui.add_input_character(character),
+                    InputEvent::MouseWheelMoved(ScrollDirection::ScrollUp) => wheel += 1.0,
+                    InputEvent::MouseWheelMoved(ScrollDirection::ScrollDown) => wheel -= 1.0,
+                    _ => {}
+                }
+            }
+            imgui.set_mouse_wheel(wheel);
+        }
+
+        let width = screen_dimensions.width();
+        let height = screen_dimensions.height();
+        state.new_frame(
+            ((width / hidpi) as u32, (height / hidpi) as u32),
+            (width as u32, height as u32),
+            time.delta_seconds(),
+        );
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<AC>>>()
+                .register_reader(),
+        );
+    }
+}
@@ -27,3 +27,77 @@ pub enum Axis {
         dead_zone: f64,
     },
 }
+
+/// Response curve and smoothing of an axis, see `Bindings::set_axis_settings`.
+///
+/// The value of the axis is shaped by `sign(value) * |value| ^ exponent * sensitivity`, clamped
+/// to -1..1, then smoothed over time.
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AxisSettings {
+    /// Multiplier applied to the axis value.
+    pub sensitivity: f64,
+    /// Exponent of the response curve, above 1.0 gives more precision around the center of
+    /// analog sticks.
+    pub exponent: f64,
+    /// Time in seconds it takes for the axis to move about two thirds of the way to its new
+    /// value, 0.0 disables smoothing.
+    pub smoothing: f64,
+}
+
+impl Default for AxisSettings {
+    fn default() -> Self {
+        AxisSettings {
+            sensitivity: 1.0,
+            exponent: 1.0,
+            smoothing: 0.0,
+        }
+    }
+}
+
+impl AxisSettings {
+    /// Applies the response curve and sensitivity to an axis value.
+    pub fn apply(&self, value: f64) -> f64 {
+        let curved = value.abs().powf(self.exponent) * value.signum() * self.sensitivity;
+        curved.max(-1.0).min(1.0)
+    }
+
+    /// Moves the smoothed value of the axis towards `target` over `delta_seconds`.
+    pub fn smooth(&self, value: f64, target: f64, delta_seconds: f64) -> f64 {
+        if self.smoothing > 0.0 {
+            value + (target - value) * (1.0 - (-delta_seconds / self.smoothing).exp())
+        } else {
+            target
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AxisSettings;
+
+    #[test]
+    fn shapes_axis_values() {
+        let settings = AxisSettings {
+            sensitivity: 2.0,
+            exponent: 2.0,
+            smoothing: 0.0,
+        };
+        assert_eq!(0.5, settings.apply(0.5));
+        assert_eq!(-0.5, settings.apply(-0.5));
+        assert_eq!(1.0, settings.apply(0.9));
+        assert_eq!(0.25, AxisSettings::default().apply(0.25));
+        assert_eq!(1.0, settings.smooth(0.0, 1.0, 0.1));
+    }
+
+    #[test]
+    fn smooths_over_time() {
+        let settings = AxisSettings {
+            smoothing: 0.1,
+            ..Default::default()
+        };
+        let value = settings.smooth(0.0, 1.0, 0.1);
+        assert!((value - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+        assert!(settings.smooth(value, 1.0, 0.1) > value);
+    }
+}
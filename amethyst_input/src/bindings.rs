@@ -12,7 +12,7 @@ use fnv::FnvHashMap as HashMap;
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
-use super::{Axis, Button};
+use super::{Axis, AxisSettings, Button};
 
 /// Used for saving and loading input settings.
 ///
@@ -36,9 +36,16 @@ use super::{Axis, Button};
 ///     actions: {
 ///         "fire": [ [Mouse(Left)], [Key(X)] ], // Multiple bindings for one action
 ///         "reload": [ [Key(LControl), Key(R)] ] // Combinations of multiple bindings possible
+///     },
+///     // Optional response curves and smoothing of axes
+///     axis_settings: {
+///         "leftright": (sensitivity: 1.0, exponent: 2.0, smoothing: 0.1)
 ///     }
 /// )
 /// ```
+///
+/// Bindings changed at runtime, for example with `InputHandler::rebind_action`, can be saved
+/// back to a Ron file with `amethyst_config::Config::write`.
 #[derive(Derivative, Serialize, Deserialize, Clone)]
 #[derivative(Default(bound = ""))]
 pub struct Bindings<AX = String, AC = String>
@@ -52,6 +59,9 @@ where
     /// So for example if you want to quit by either "Esc" or "Ctrl+q" you would have
     /// `[[Esc], [Ctrl, Q]]`.
     pub(super) actions: HashMap<AC, SmallVec<[SmallVec<[Button; 2]>; 4]>>,
+    /// Response curves and smoothing of the axes, axes without settings are used as is.
+    #[serde(default)]
+    pub(super) axis_settings: HashMap<AX, AxisSettings>,
 }
 
 /// An enum of possible errors that can occur when binding an action or axis.
@@ -154,8 +164,8 @@ where
 
 impl<AX, AC> Bindings<AX, AC>
 where
-    AX: Hash + Eq + Clone + Display,
-    AC: Hash + Eq + Clone + Display,
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
 {
     /// Assign an axis to an ID value
     ///
@@ -187,6 +197,33 @@ where
         self.axes.get(id)
     }
 
+    /// Sets the response curve and smoothing of an axis, returning its previous settings.
+    ///
+    /// Settings are kept when the axis is removed or replaced.
+    pub fn set_axis_settings<A: Into<AX>>(
+        &mut self,
+        id: A,
+        settings: AxisSettings,
+    ) -> Option<AxisSettings> {
+        self.axis_settings.insert(id.into(), settings)
+    }
+
+    /// Returns the response curve and smoothing of an axis, if set.
+    pub fn axis_settings<A: Hash + Eq + ?Sized>(&self, id: &A) -> Option<&AxisSettings>
+    where
+        AX: Borrow<A>,
+    {
+        self.axis_settings.get(id)
+    }
+
+    /// Removes the response curve and smoothing of an axis, returning them.
+    pub fn remove_axis_settings<A: Hash + Eq + ?Sized>(&mut self, id: &A) -> Option<AxisSettings>
+    where
+        AX: Borrow<A>,
+    {
+        self.axis_settings.remove(id)
+    }
+
     /// Gets a list of all axes
     pub fn axes(&self) -> impl Iterator<Item = &AX> {
         self.axes.keys()
@@ -219,6 +256,51 @@ where
         Ok(())
     }
 
+    /// Replaces the binding of an action at `index`, as listed by `action_bindings`.
+    ///
+    /// The binding is added after the other bindings of the action if it has no binding at
+    /// `index`. If the new binding conflicts with another one, the previous binding is kept
+    /// and the conflict is returned.
+    pub fn replace_action_binding<B: IntoIterator<Item = Button>>(
+        &mut self,
+        id: AC,
+        index: usize,
+        binding: B,
+    ) -> Result<(), BindingError<AX, AC>> {
+        let bind: SmallVec<[Button; 2]> = binding.into_iter().collect();
+        let previous = match self.actions.get_mut(&id) {
+            Some(action_bindings) if index < action_bindings.len() => {
+                Some(action_bindings.remove(index))
+            }
+            _ => None,
+        };
+        if let Err(err) = self.check_action_invariants(&id, bind.as_slice()) {
+            if let Some(previous) = previous {
+                self.actions
+                    .get_mut(&id)
+                    .expect("Unreachable: The binding was just removed from this action")
+                    .insert(index, previous);
+            }
+            return Err(err);
+        }
+        let action_bindings = self.actions.entry(id).or_insert_with(SmallVec::new);
+        let index = index.min(action_bindings.len());
+        action_bindings.insert(index, bind);
+        Ok(())
+    }
+
+    /// Checks if a button or button combination can be bound to an action, returning the
+    /// existing binding it conflicts with otherwise.
+    ///
+    /// Useful to warn the player before replacing a binding in a controls settings screen.
+    pub fn check_action_binding(
+        &self,
+        id: &AC,
+        binding: &[Button],
+    ) -> Result<(), BindingError<AX, AC>> {
+        self.check_action_invariants(id, binding)
+    }
+
     /// Removes an action binding that was assigned previously.
     pub fn remove_action_binding<T: Hash + Eq + ?Sized>(
         &mut self,
@@ -276,6 +358,18 @@ where
         self.actions.keys()
     }
 
+    /// Returns true if the button is used by an action or an emulated axis.
+    pub(crate) fn is_bound(&self, button: Button) -> bool {
+        self.actions
+            .values()
+            .flat_map(|combinations| combinations.iter())
+            .any(|combination| combination.contains(&button))
+            || self.axes.values().any(|axis| match *axis {
+                Axis::Emulated { pos, neg } => pos == button || neg == button,
+                _ => false,
+            })
+    }
+
     /// Check that this structure upholds its guarantees. Should only be necessary when serializing or deserializing the bindings.
     pub fn check_invariants(&mut self) -> Result<(), BindingError<AX, AC>> {
         // The easiest way to do this is to use the existing code that checks for invariants when adding bindings.
@@ -723,4 +817,60 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn replace_action_bindings() {
+        let mut bindings = Bindings::<String, String>::new();
+        let jump = String::from("jump");
+        bindings
+            .insert_action_binding(jump.clone(), Some(Button::Key(VirtualKeyCode::Space)))
+            .unwrap();
+        bindings
+            .insert_action_binding(String::from("fire"), Some(Button::Key(VirtualKeyCode::X)))
+            .unwrap();
+
+        let conflict = Some(Button::Key(VirtualKeyCode::X));
+        assert_eq!(
+            bindings.replace_action_binding(jump.clone(), 0, conflict),
+            Err(BindingError::ComboAlreadyBound(String::from("fire")))
+        );
+        assert_eq!(
+            bindings.action_bindings("jump").collect::<Vec<_>>(),
+            vec![[Button::Key(VirtualKeyCode::Space)]]
+        );
+
+        bindings
+            .replace_action_binding(jump.clone(), 0, Some(Button::Key(VirtualKeyCode::Up)))
+            .unwrap();
+        bindings
+            .replace_action_binding(jump.clone(), 3, Some(Button::Mouse(MouseButton::Left)))
+            .unwrap();
+        assert_eq!(
+            bindings.action_bindings("jump").collect::<Vec<_>>(),
+            vec![
+                [Button::Key(VirtualKeyCode::Up)],
+                [Button::Mouse(MouseButton::Left)]
+            ]
+        );
+        assert!(bindings
+            .check_action_binding(&jump, &[Button::Key(VirtualKeyCode::Space)])
+            .is_ok());
+    }
+
+    #[test]
+    fn write_bindings_to_ron() {
+        use amethyst_config::Config;
+
+        let mut bindings = Bindings::<String, String>::new();
+        bindings
+            .insert_action_binding(String::from("jump"), Some(Button::Key(VirtualKeyCode::Up)))
+            .unwrap();
+        let path = std::env::temp_dir().join("amethyst_input_write_bindings_to_ron.ron");
+        bindings.write(&path).unwrap();
+        let loaded = Bindings::<String, String>::load_no_fallback(&path).unwrap();
+        assert_eq!(
+            loaded.action_bindings("jump").collect::<Vec<_>>(),
+            vec![[Button::Key(VirtualKeyCode::Up)]]
+        );
+    }
 }
@@ -8,7 +8,9 @@ use amethyst_config::{Config, ConfigError};
 use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
 use amethyst_error::Error;
 
-use crate::{BindingError, Bindings, InputSystem};
+use crate::{
+    BindingError, Bindings, InputRecorderSystem, InputRecording, InputReplaySystem, InputSystem,
+};
 
 #[cfg(feature = "sdl_controller")]
 use crate::sdl_events_system::ControllerMappings;
@@ -37,6 +39,8 @@ where
     AC: Hash + Eq + Clone,
 {
     bindings: Option<Bindings<AX, AC>>,
+    recording: bool,
+    replay: Option<InputRecording<AC>>,
     #[cfg(feature = "sdl_controller")]
     controller_mappings: Option<ControllerMappings>,
 }
@@ -71,6 +75,28 @@ where
         Ok(self.with_bindings(bindings))
     }
 
+    /// Record the input of every frame in the `InputRecording<AC>` resource.
+    pub fn with_recording(mut self) -> Self {
+        self.recording = true;
+        self
+    }
+
+    /// Play `recording` back instead of reading the input devices.
+    pub fn with_replay(mut self, recording: InputRecording<AC>) -> Self {
+        self.replay = Some(recording);
+        self
+    }
+
+    /// Load a recording saved with `Config::write` and play it back instead of reading the
+    /// input devices.
+    pub fn with_replay_from_file<P: AsRef<Path>>(self, file: P) -> Result<Self, ConfigError>
+    where
+        AC: DeserializeOwned + Serialize,
+    {
+        let recording = InputRecording::load_no_fallback(file)?;
+        Ok(self.with_replay(recording))
+    }
+
     /// Load SDL controller mappings from file
     #[cfg(feature = "sdl_controller")]
     pub fn with_sdl_controller_mappings(mut self, mappings: String) -> Self {
@@ -99,19 +125,34 @@ where
     AC: Hash + Eq + Clone + Send + Sync + 'static,
 {
     fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
-        #[cfg(feature = "sdl_controller")]
-        {
-            use super::SdlEventsSystem;
-            builder.add_thread_local(
-                // TODO: improve errors when migrating to failure
-                SdlEventsSystem::<AX, AC>::new(self.controller_mappings).unwrap(),
+        if let Some(recording) = self.replay {
+            builder.add(
+                InputReplaySystem::<AX, AC>::new(recording, self.bindings),
+                "input_system",
+                &[],
+            );
+        } else {
+            #[cfg(feature = "sdl_controller")]
+            {
+                use super::SdlEventsSystem;
+                builder.add_thread_local(
+                    // TODO: improve errors when migrating to failure
+                    SdlEventsSystem::<AX, AC>::new(self.controller_mappings).unwrap(),
+                );
+            }
+            builder.add(
+                InputSystem::<AX, AC>::new(self.bindings),
+                "input_system",
+                &[],
+            );
+        }
+        if self.recording {
+            builder.add(
+                InputRecorderSystem::<AX, AC>::new(),
+                "input_recorder_system",
+                &["input_system"],
             );
         }
-        builder.add(
-            InputSystem::<AX, AC>::new(self.bindings),
-            "input_system",
-            &[],
-        );
         Ok(())
     }
 }
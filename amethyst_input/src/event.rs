@@ -29,6 +29,13 @@ pub enum InputEvent<T> {
     },
     /// A unicode character was received by the window.  Good for typing.
     KeyTyped(char),
+    /// Text input was started, see `InputHandler::start_text_input`.
+    TextInputStarted,
+    /// Text was entered while text input is active, either typed or composed with an input
+    /// method. Sent at most once per frame with all the text received during the frame.
+    TextCommitted(String),
+    /// Text input was stopped.
+    TextInputEnded,
     /// A mouse button was pressed down, sent exactly once per press.
     MouseButtonPressed(MouseButton),
     /// A mouse button was released, sent exactly once per release.
@@ -98,4 +105,9 @@ pub enum InputEvent<T> {
     ActionReleased(T),
     /// The associated action has its mouse wheel moved.
     ActionWheelMoved(T),
+    /// The associated action was bound to the button, see `InputHandler::rebind_action`.
+    ActionRebound(T, Button),
+    /// The button pressed to rebind the associated action conflicts with an existing binding,
+    /// see `Bindings::check_action_binding` to find which.
+    ActionRebindConflict(T, Button),
 }
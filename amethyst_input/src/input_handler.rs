@@ -3,6 +3,7 @@
 use std::{borrow::Borrow, hash::Hash};
 
 use derivative::Derivative;
+use fnv::FnvHashMap as HashMap;
 use smallvec::SmallVec;
 use winit::{
     dpi::LogicalPosition, DeviceEvent, ElementState, Event, KeyboardInput, MouseButton,
@@ -14,6 +15,8 @@ use amethyst_core::shrev::EventChannel;
 use super::{
     controller::{ControllerButton, ControllerEvent},
     event::InputEvent::{self, *},
+    recording::InputState,
+    rumble::Rumble,
     scroll_direction::ScrollDirection,
     *,
 };
@@ -31,6 +34,8 @@ where
 {
     /// Maps inputs to actions and axes.
     pub bindings: Bindings<AX, AC>,
+    /// Named bindings layered above `bindings`, the last one has the highest priority.
+    contexts: Vec<(String, Bindings<AX, AC>)>,
     /// Encodes the VirtualKeyCode and corresponding scancode.
     pressed_keys: SmallVec<[(VirtualKeyCode, u32); 12]>,
     pressed_mouse_buttons: SmallVec<[MouseButton; 12]>,
@@ -41,6 +46,16 @@ where
     /// First number represents mapped ID visible to the user code,
     /// while second is the ID used by incoming events.
     connected_controllers: SmallVec<[(u32, u32); 8]>,
+    /// Values of the axes with smoothing, updated by `update_axes`.
+    smoothed_axes: HashMap<AX, f64>,
+    /// Rumble effects waiting for the input backend, by controller id.
+    rumbles: SmallVec<[(u32, Rumble); 4]>,
+    /// Action and index of the binding replaced by the next pressed button.
+    rebinding: Option<(AC, usize)>,
+    /// Text received since the last `TextCommitted` event, if text input is active.
+    text_input: Option<String>,
+    /// True if `TextInputStarted` was sent without a matching `TextInputEnded`.
+    text_input_started: bool,
     mouse_position: Option<(f64, f64)>,
 }
 
@@ -74,6 +89,11 @@ where
             Event::WindowEvent { ref event, .. } => match *event {
                 WindowEvent::ReceivedCharacter(c) => {
                     event_handler.single_write(KeyTyped(c));
+                    if let Some(ref mut text) = self.text_input {
+                        if !c.is_control() {
+                            text.push(c);
+                        }
+                    }
                 }
                 WindowEvent::KeyboardInput {
                     input:
@@ -96,17 +116,18 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations.iter().filter(|c| {
-                                c.contains(&Button::Key(key_code))
-                                    || c.contains(&Button::ScanCode(scancode))
-                            }) {
-                                if combination
-                                    .iter()
-                                    .all(|button| self.button_is_down(*button))
-                                {
-                                    event_handler.single_write(ActionPressed(action.clone()));
-                                }
+                        if self.rebind_pressed(Button::Key(key_code), event_handler) {
+                            return;
+                        }
+                        for (action, combination) in self.active_actions().filter(|&(_, c)| {
+                            c.contains(&Button::Key(key_code))
+                                || c.contains(&Button::ScanCode(scancode))
+                        }) {
+                            if combination
+                                .iter()
+                                .all(|button| self.button_is_down(*button))
+                            {
+                                event_handler.single_write(ActionPressed(action.clone()));
                             }
                         }
                     }
@@ -133,24 +154,22 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations {
-                                if combination.contains(&Button::Key(key_code))
-                                    && combination
-                                        .iter()
-                                        .filter(|b| b != &&Button::Key(key_code))
-                                        .all(|b| self.button_is_down(*b))
-                                {
-                                    event_handler.single_write(ActionReleased(action.clone()));
-                                }
-                                if combination.contains(&Button::ScanCode(scancode))
-                                    && combination
-                                        .iter()
-                                        .filter(|b| b != &&Button::ScanCode(scancode))
-                                        .all(|b| self.button_is_down(*b))
-                                {
-                                    event_handler.single_write(ActionReleased(action.clone()));
-                                }
+                        for (action, combination) in self.active_actions() {
+                            if combination.contains(&Button::Key(key_code))
+                                && combination
+                                    .iter()
+                                    .filter(|b| b != &&Button::Key(key_code))
+                                    .all(|b| self.button_is_down(*b))
+                            {
+                                event_handler.single_write(ActionReleased(action.clone()));
+                            }
+                            if combination.contains(&Button::ScanCode(scancode))
+                                && combination
+                                    .iter()
+                                    .filter(|b| b != &&Button::ScanCode(scancode))
+                                    .all(|b| self.button_is_down(*b))
+                            {
+                                event_handler.single_write(ActionReleased(action.clone()));
                             }
                         }
                     }
@@ -175,17 +194,18 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations
+                        if self.rebind_pressed(Button::Mouse(mouse_button), event_handler) {
+                            return;
+                        }
+                        for (action, combination) in self
+                            .active_actions()
+                            .filter(|&(_, c)| c.contains(&Button::Mouse(mouse_button)))
+                        {
+                            if combination
                                 .iter()
-                                .filter(|c| c.contains(&Button::Mouse(mouse_button)))
+                                .all(|button| self.button_is_down(*button))
                             {
-                                if combination
-                                    .iter()
-                                    .all(|button| self.button_is_down(*button))
-                                {
-                                    event_handler.single_write(ActionPressed(action.clone()));
-                                }
+                                event_handler.single_write(ActionPressed(action.clone()));
                             }
                         }
                     }
@@ -210,16 +230,14 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations {
-                                if combination.contains(&Button::Mouse(mouse_button))
-                                    && combination
-                                        .iter()
-                                        .filter(|b| b != &&Button::Mouse(mouse_button))
-                                        .all(|b| self.button_is_down(*b))
-                                {
-                                    event_handler.single_write(ActionReleased(action.clone()));
-                                }
+                        for (action, combination) in self.active_actions() {
+                            if combination.contains(&Button::Mouse(mouse_button))
+                                && combination
+                                    .iter()
+                                    .filter(|b| b != &&Button::Mouse(mouse_button))
+                                    .all(|b| self.button_is_down(*b))
+                            {
+                                event_handler.single_write(ActionReleased(action.clone()));
                             }
                         }
                     }
@@ -307,17 +325,18 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations
+                        let bound = Button::Controller(controller_id, button);
+                        if self.rebind_pressed(bound, event_handler) {
+                            return;
+                        }
+                        for (action, combination) in self.active_actions().filter(|&(_, c)| {
+                            c.contains(&Button::Controller(controller_id, button))
+                        }) {
+                            if combination
                                 .iter()
-                                .filter(|c| c.contains(&Button::Controller(controller_id, button)))
+                                .all(|button| self.button_is_down(*button))
                             {
-                                if combination
-                                    .iter()
-                                    .all(|button| self.button_is_down(*button))
-                                {
-                                    event_handler.single_write(ActionPressed(action.clone()));
-                                }
+                                event_handler.single_write(ActionPressed(action.clone()));
                             }
                         }
                     }
@@ -339,18 +358,14 @@ where
                             .iter()
                             .cloned(),
                         );
-                        for (action, combinations) in self.bindings.actions.iter() {
-                            for combination in combinations {
-                                if combination.contains(&Button::Controller(controller_id, button))
-                                    && combination
-                                        .iter()
-                                        .filter(|b| {
-                                            b != &&Button::Controller(controller_id, button)
-                                        })
-                                        .all(|b| self.button_is_down(*b))
-                                {
-                                    event_handler.single_write(ActionReleased(action.clone()));
-                                }
+                        for (action, combination) in self.active_actions() {
+                            if combination.contains(&Button::Controller(controller_id, button))
+                                && combination
+                                    .iter()
+                                    .filter(|b| b != &&Button::Controller(controller_id, button))
+                                    .all(|b| self.button_is_down(*b))
+                            {
+                                event_handler.single_write(ActionReleased(action.clone()));
                             }
                         }
                     }
@@ -484,14 +499,57 @@ where
     }
 
     /// Returns the value of an axis by the string id, if the id doesn't exist this returns None.
+    ///
+    /// The axis is looked up in the input contexts first, from the last pushed one. Its
+    /// `AxisSettings` are applied if it has any.
     pub fn axis_value<T: Hash + Eq + ?Sized>(&self, id: &T) -> Option<f64>
     where
         AX: Borrow<T>,
     {
-        self.bindings.axes.get(id).map(|a| match *a {
+        let (depth, bindings) = self
+            .layers()
+            .enumerate()
+            .find(|(_, bindings)| bindings.axes.contains_key(id))?;
+        match bindings.axis_settings.get(id) {
+            Some(settings) if settings.smoothing > 0.0 => {
+                Some(self.smoothed_axes.get(id).cloned().unwrap_or(0.0))
+            }
+            settings => {
+                let value = self.raw_axis_value(depth, &bindings.axes[id]);
+                Some(settings.map_or(value, |settings| settings.apply(value)))
+            }
+        }
+    }
+
+    /// Advances the smoothing of the axes by `delta_seconds`.
+    ///
+    /// The Amethyst game engine will automatically call this every frame if the InputHandler is
+    /// attached to the world as a resource.
+    pub fn update_axes(&mut self, delta_seconds: f64) {
+        let mut smoothed_axes = HashMap::default();
+        for (depth, bindings) in self.layers().enumerate() {
+            for (id, settings) in bindings.axis_settings.iter() {
+                if settings.smoothing <= 0.0
+                    || self.layers().take(depth).any(|b| b.axes.contains_key(id))
+                {
+                    continue;
+                }
+                if let Some(axis) = bindings.axes.get(id) {
+                    let target = settings.apply(self.raw_axis_value(depth, axis));
+                    let value = self.smoothed_axes.get(id).cloned().unwrap_or(0.0);
+                    smoothed_axes.insert(id.clone(), settings.smooth(value, target, delta_seconds));
+                }
+            }
+        }
+        self.smoothed_axes = smoothed_axes;
+    }
+
+    /// Returns the value of an axis of the layer at `depth`, without its `AxisSettings`.
+    fn raw_axis_value(&self, depth: usize, axis: &Axis) -> f64 {
+        match *axis {
             Axis::Emulated { pos, neg, .. } => {
-                let pos = self.button_is_down(pos);
-                let neg = self.button_is_down(neg);
+                let pos = !self.is_consumed(depth, pos) && self.button_is_down(pos);
+                let neg = !self.is_consumed(depth, neg) && self.button_is_down(neg);
                 if pos == neg {
                     0.0
                 } else if pos {
@@ -521,23 +579,263 @@ where
                     }
                 })
                 .unwrap_or(0.0),
-        })
+        }
     }
 
     /// Returns true if any of the actions bindings is down.
     ///
     /// If a binding represents a combination of buttons, all of them need to be down.
+    /// Bindings using a button bound in a higher input context are ignored.
     pub fn action_is_down<T: Hash + Eq + ?Sized>(&self, action: &T) -> Option<bool>
     where
         AC: Borrow<T>,
     {
-        self.bindings.actions.get(action).map(|combinations| {
-            combinations.iter().any(|combination| {
-                combination
+        if self
+            .layers()
+            .all(|bindings| !bindings.actions.contains_key(action))
+        {
+            return None;
+        }
+        Some(
+            self.active_actions()
+                .filter(|&(id, _)| <AC as Borrow<T>>::borrow(id) == action)
+                .any(|(_, combination)| {
+                    combination
+                        .iter()
+                        .all(|button| self.button_is_down(*button))
+                }),
+        )
+    }
+
+    /// Starts text input, for example when a text field is focused.
+    ///
+    /// While active, the characters received from the window, including the text composed with
+    /// an input method, are sent together as a `TextCommitted` event by `send_text_input`.
+    /// `TextInputStarted` and `TextInputEnded` are sent when text input starts and stops.
+    pub fn start_text_input(&mut self) {
+        if self.text_input.is_none() {
+            self.text_input = Some(String::new());
+        }
+    }
+
+    /// Stops text input, dropping the text not sent yet.
+    pub fn stop_text_input(&mut self) {
+        self.text_input = None;
+    }
+
+    /// Returns true if text input is active.
+    pub fn is_text_input_active(&self) -> bool {
+        self.text_input.is_some()
+    }
+
+    /// Sends the text received since the last call and the start and end of text input.
+    ///
+    /// The Amethyst game engine will automatically call this after sending the events of the
+    /// frame if the InputHandler is attached to the world as a resource.
+    pub fn send_text_input(&mut self, event_handler: &mut EventChannel<InputEvent<AC>>) {
+        match self.text_input {
+            Some(ref mut text) => {
+                if !self.text_input_started {
+                    event_handler.single_write(TextInputStarted);
+                    self.text_input_started = true;
+                }
+                if !text.is_empty() {
+                    event_handler
+                        .single_write(TextCommitted(std::mem::replace(text, String::new())));
+                }
+            }
+            None if self.text_input_started => {
+                event_handler.single_write(TextInputEnded);
+                self.text_input_started = false;
+            }
+            None => {}
+        }
+    }
+
+    /// Returns the state of the input devices, as recorded by the `InputRecorderSystem`.
+    pub fn state(&self) -> InputState {
+        InputState {
+            pressed_keys: self.pressed_keys.to_vec(),
+            pressed_mouse_buttons: self.pressed_mouse_buttons.to_vec(),
+            pressed_controller_buttons: self.pressed_controller_buttons.to_vec(),
+            controller_axes: self.controller_axes.to_vec(),
+            connected_controllers: self.connected_controllers.to_vec(),
+            mouse_position: self.mouse_position,
+        }
+    }
+
+    /// Replaces the state of the input devices without sending any event, used to replay
+    /// recorded input.
+    pub fn set_state(&mut self, state: InputState) {
+        self.pressed_keys = SmallVec::from_vec(state.pressed_keys);
+        self.pressed_mouse_buttons = SmallVec::from_vec(state.pressed_mouse_buttons);
+        self.pressed_controller_buttons = SmallVec::from_vec(state.pressed_controller_buttons);
+        self.controller_axes = SmallVec::from_vec(state.controller_axes);
+        self.connected_controllers = SmallVec::from_vec(state.connected_controllers);
+        self.mouse_position = state.mouse_position;
+    }
+
+    /// Pushes an input context above the current ones, like a menu or a vehicle.
+    ///
+    /// Its bindings take priority over the bindings of the contexts below it and of `bindings`:
+    /// a button bound in a context doesn't trigger any action or axis of the lower layers, while
+    /// other buttons still reach them.
+    ///
+    /// ```rust
+    /// # use amethyst_input::{Bindings, Button, InputHandler};
+    /// # use winit::VirtualKeyCode;
+    /// let mut input = InputHandler::<String, String>::new();
+    /// let mut menu = Bindings::new();
+    /// menu.insert_action_binding(String::from("back"), Some(Button::Key(VirtualKeyCode::Escape)))
+    ///     .expect("Failed to bind back");
+    /// // Escape now goes back in the menu instead of pausing the game
+    /// input.push_context("menu", menu);
+    /// ```
+    pub fn push_context<S: Into<String>>(&mut self, name: S, bindings: Bindings<AX, AC>) {
+        self.contexts.push((name.into(), bindings));
+    }
+
+    /// Removes the last pushed input context, returning its name and bindings.
+    pub fn pop_context(&mut self) -> Option<(String, Bindings<AX, AC>)> {
+        self.contexts.pop()
+    }
+
+    /// Returns the name of the last pushed input context, if any.
+    pub fn context(&self) -> Option<&str> {
+        self.contexts.last().map(|(name, _)| name.as_str())
+    }
+
+    /// Iterates over the names of the input contexts, from the first pushed one.
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        self.contexts.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns a mutable reference to the bindings of the input context.
+    pub fn context_bindings_mut(&mut self, name: &str) -> Option<&mut Bindings<AX, AC>> {
+        self.contexts
+            .iter_mut()
+            .rev()
+            .find(|(context, _)| context == name)
+            .map(|(_, bindings)| bindings)
+    }
+
+    /// Iterates over the input contexts from the highest priority one, ending with `bindings`.
+    fn layers(&self) -> impl Iterator<Item = &Bindings<AX, AC>> {
+        self.contexts
+            .iter()
+            .rev()
+            .map(|(_, bindings)| bindings)
+            .chain(Some(&self.bindings))
+    }
+
+    /// Returns true if the button is bound by a layer above `depth`.
+    fn is_consumed(&self, depth: usize, button: Button) -> bool {
+        self.layers()
+            .take(depth)
+            .any(|bindings| bindings.is_bound(button))
+    }
+
+    /// Iterates over the action bindings of all layers, skipping combinations using a button
+    /// consumed by a higher layer.
+    fn active_actions(&self) -> impl Iterator<Item = (&AC, &[Button])> {
+        self.layers()
+            .enumerate()
+            .flat_map(move |(depth, bindings)| {
+                bindings
+                    .actions
                     .iter()
-                    .all(|button| self.button_is_down(*button))
+                    .flat_map(|(action, combinations)| {
+                        combinations
+                            .iter()
+                            .map(move |combination| (action, combination.as_slice()))
+                    })
+                    .filter(move |&(_, combination)| {
+                        combination
+                            .iter()
+                            .all(|button| !self.is_consumed(depth, *button))
+                    })
             })
-        })
+    }
+
+    /// Binds the next pressed key, mouse button or controller button to the action, replacing
+    /// its binding at `index` as listed by `Bindings::action_bindings`.
+    ///
+    /// Sends `ActionRebound` once bound. A button conflicting with another binding is not bound
+    /// and sends `ActionRebindConflict` instead, the handler then keeps waiting for another button.
+    /// The pressed button doesn't trigger any action.
+    ///
+    /// ```rust
+    /// # use amethyst_input::InputHandler;
+    /// let mut input = InputHandler::<String, String>::new();
+    /// // "Press a key to bind jump"
+    /// input.rebind_action(String::from("jump"), 0);
+    /// ```
+    pub fn rebind_action(&mut self, action: AC, index: usize) {
+        self.rebinding = Some((action, index));
+    }
+
+    /// Returns the action waiting for a button press to be rebound, if any.
+    pub fn rebinding_action(&self) -> Option<&AC> {
+        self.rebinding.as_ref().map(|(action, _)| action)
+    }
+
+    /// Stops waiting for a button press to rebind an action, keeping its binding.
+    pub fn cancel_rebinding(&mut self) {
+        self.rebinding = None;
+    }
+
+    /// Binds the pressed button to the action being rebound, returns true if it was consumed.
+    fn rebind_pressed(
+        &mut self,
+        button: Button,
+        event_handler: &mut EventChannel<InputEvent<AC>>,
+    ) -> bool {
+        let (action, index) = match self.rebinding.take() {
+            Some(rebinding) => rebinding,
+            None => return false,
+        };
+        match self
+            .bindings
+            .replace_action_binding(action.clone(), index, Some(button))
+        {
+            Ok(()) => event_handler.single_write(ActionRebound(action, button)),
+            Err(_) => {
+                event_handler.single_write(ActionRebindConflict(action.clone(), button));
+                self.rebinding = Some((action, index));
+            }
+        }
+        true
+    }
+
+    /// Plays a rumble effect on the controller, replacing its current effect.
+    ///
+    /// Effects are queued for the input backend, like the `SdlEventsSystem` with the
+    /// `sdl_controller` feature, which plays them on its next run.
+    pub fn rumble(&mut self, controller_id: u32, rumble: Rumble) {
+        self.rumbles.retain(|(id, _)| *id != controller_id);
+        self.rumbles.push((controller_id, rumble));
+    }
+
+    /// Stops the rumble effect of the controller.
+    pub fn stop_rumble(&mut self, controller_id: u32) {
+        self.rumble(controller_id, Rumble::stop());
+    }
+
+    /// Takes the queued rumble effects of connected controllers.
+    ///
+    /// Controllers are identified by the index used in the `ControllerEvent`s sent to
+    /// `send_controller_event`. Called by input backends.
+    pub fn drain_rumbles(&mut self) -> Vec<(u32, Rumble)> {
+        let connected = &self.connected_controllers;
+        self.rumbles
+            .drain()
+            .filter_map(|(controller_id, rumble)| {
+                connected
+                    .iter()
+                    .find(|ids| ids.0 == controller_id)
+                    .map(|ids| (ids.1, rumble))
+            })
+            .collect()
     }
 
     /// Retrieve next free controller number to allocate new controller to
@@ -600,27 +898,25 @@ where
         };
 
         // check for actions being bound to any invoked mouse wheel
-        for (action, combinations) in self.bindings.actions.iter() {
-            for ref combination in combinations {
-                if let Some(dir) = dir_x {
-                    if combination.contains(&Button::MouseWheel(dir))
-                        && combination
-                            .iter()
-                            .filter(|b| **b != Button::MouseWheel(dir))
-                            .all(|b| self.button_is_down(*b))
-                    {
-                        events.push(ActionWheelMoved(action.clone()));
-                    }
+        for (action, combination) in self.active_actions() {
+            if let Some(dir) = dir_x {
+                if combination.contains(&Button::MouseWheel(dir))
+                    && combination
+                        .iter()
+                        .filter(|b| **b != Button::MouseWheel(dir))
+                        .all(|b| self.button_is_down(*b))
+                {
+                    events.push(ActionWheelMoved(action.clone()));
                 }
-                if let Some(dir) = dir_y {
-                    if combination.contains(&Button::MouseWheel(dir))
-                        && combination
-                            .iter()
-                            .filter(|b| **b != Button::MouseWheel(dir))
-                            .all(|b| self.button_is_down(*b))
-                    {
-                        events.push(ActionWheelMoved(action.clone()));
-                    }
+            }
+            if let Some(dir) = dir_y {
+                if combination.contains(&Button::MouseWheel(dir))
+                    && combination
+                        .iter()
+                        .filter(|b| **b != Button::MouseWheel(dir))
+                        .all(|b| self.button_is_down(*b))
+                {
+                    events.push(ActionWheelMoved(action.clone()));
                 }
             }
         }
@@ -985,6 +1281,175 @@ mod tests {
         assert!(!handler.button_is_down(Button::Mouse(MouseButton::Left)));
     }
 
+    #[test]
+    fn rebinds_action_on_next_press() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        let mut reader = events.register_reader();
+        handler
+            .bindings
+            .insert_action_binding(
+                String::from("jump"),
+                Some(Button::Key(VirtualKeyCode::Space)),
+            )
+            .unwrap();
+        handler
+            .bindings
+            .insert_action_binding(String::from("fire"), Some(Button::Key(VirtualKeyCode::X)))
+            .unwrap();
+        handler.rebind_action(String::from("jump"), 0);
+
+        handler.send_event(&key_press(45, VirtualKeyCode::X), &mut events, HIDPI);
+        let event_vec = events.read(&mut reader).cloned().collect::<Vec<_>>();
+        assert!(event_vec.contains(&InputEvent::ActionRebindConflict(
+            String::from("jump"),
+            Button::Key(VirtualKeyCode::X)
+        )));
+        assert!(!event_vec.contains(&InputEvent::ActionPressed(String::from("fire"))));
+        assert_eq!(handler.rebinding_action(), Some(&String::from("jump")));
+
+        handler.send_event(&key_press(104, VirtualKeyCode::Up), &mut events, HIDPI);
+        let event_vec = events.read(&mut reader).cloned().collect::<Vec<_>>();
+        assert!(event_vec.contains(&InputEvent::ActionRebound(
+            String::from("jump"),
+            Button::Key(VirtualKeyCode::Up)
+        )));
+        assert_eq!(handler.rebinding_action(), None);
+        assert_eq!(
+            handler.bindings.action_bindings("jump").collect::<Vec<_>>(),
+            vec![[Button::Key(VirtualKeyCode::Up)]]
+        );
+    }
+
+    #[test]
+    fn contexts_consume_their_buttons() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        let mut reader = events.register_reader();
+        for (action, key) in &[
+            ("pause", VirtualKeyCode::Escape),
+            ("forward", VirtualKeyCode::W),
+        ] {
+            handler
+                .bindings
+                .insert_action_binding(String::from(*action), Some(Button::Key(*key)))
+                .unwrap();
+        }
+        let mut menu = Bindings::new();
+        menu.insert_action_binding(
+            String::from("back"),
+            Some(Button::Key(VirtualKeyCode::Escape)),
+        )
+        .unwrap();
+        handler.push_context("menu", menu);
+        assert_eq!(handler.context(), Some("menu"));
+
+        handler.send_event(&key_press(1, VirtualKeyCode::Escape), &mut events, HIDPI);
+        handler.send_event(&key_press(17, VirtualKeyCode::W), &mut events, HIDPI);
+        let event_vec = events.read(&mut reader).cloned().collect::<Vec<_>>();
+        assert!(event_vec.contains(&InputEvent::ActionPressed(String::from("back"))));
+        assert!(event_vec.contains(&InputEvent::ActionPressed(String::from("forward"))));
+        assert!(!event_vec.contains(&InputEvent::ActionPressed(String::from("pause"))));
+        assert_eq!(handler.action_is_down("back"), Some(true));
+        assert_eq!(handler.action_is_down("pause"), Some(false));
+
+        assert!(handler.pop_context().is_some());
+        assert_eq!(handler.action_is_down("back"), None);
+        assert_eq!(handler.action_is_down("pause"), Some(true));
+    }
+
+    #[test]
+    fn commits_text_input() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        let mut reader = events.register_reader();
+        handler.start_text_input();
+        for c in "日本\u{8}".chars() {
+            handler.send_event(&character(c), &mut events, HIDPI);
+        }
+        handler.send_text_input(&mut events);
+        handler.stop_text_input();
+        handler.send_text_input(&mut events);
+        let event_vec = events
+            .read(&mut reader)
+            .filter(|event| match event {
+                InputEvent::KeyTyped(_) => false,
+                _ => true,
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            event_vec,
+            vec![
+                InputEvent::TextInputStarted,
+                InputEvent::TextCommitted(String::from("日本")),
+                InputEvent::TextInputEnded,
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_axis_settings() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        let keys = [
+            ("smooth", VirtualKeyCode::Up, VirtualKeyCode::Down),
+            ("scaled", VirtualKeyCode::Right, VirtualKeyCode::Left),
+        ];
+        for &(id, pos, neg) in &keys {
+            handler
+                .bindings
+                .insert_axis(
+                    id,
+                    Axis::Emulated {
+                        pos: Button::Key(pos),
+                        neg: Button::Key(neg),
+                    },
+                )
+                .unwrap();
+        }
+        handler.bindings.set_axis_settings(
+            "smooth",
+            AxisSettings {
+                smoothing: 0.1,
+                ..Default::default()
+            },
+        );
+        handler.bindings.set_axis_settings(
+            "scaled",
+            AxisSettings {
+                sensitivity: 0.5,
+                ..Default::default()
+            },
+        );
+
+        handler.send_event(&key_press(104, VirtualKeyCode::Up), &mut events, HIDPI);
+        handler.send_event(&key_press(106, VirtualKeyCode::Right), &mut events, HIDPI);
+        assert_eq!(handler.axis_value("scaled"), Some(0.5));
+        assert_eq!(handler.axis_value("smooth"), Some(0.0));
+        handler.update_axes(0.1);
+        let value = handler.axis_value("smooth").unwrap();
+        assert!((value - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+    }
+
+    #[test]
+    fn queues_rumbles_for_connected_controllers() {
+        let mut handler = InputHandler::<String, String>::new();
+        let mut events = EventChannel::<InputEvent<String>>::new();
+        handler.send_controller_event(
+            &ControllerEvent::ControllerConnected { which: 7 },
+            &mut events,
+        );
+        let rumble = Rumble::new(1.0, 0.5, std::time::Duration::from_millis(200));
+        handler.rumble(0, rumble.clone());
+        handler.rumble(1, rumble.clone());
+        assert_eq!(vec![(7, rumble)], handler.drain_rumbles());
+        assert!(handler.drain_rumbles().is_empty());
+
+        handler.stop_rumble(0);
+        assert_eq!(vec![(7, Rumble::stop())], handler.drain_rumbles());
+    }
+
     /// Compares two sets for equality, but not the order
     fn sets_are_equal<T>(a: &[T], b: &[T])
     where
@@ -1045,6 +1510,13 @@ right: `{:?}`",
         }
     }
 
+    fn character(c: char) -> Event {
+        Event::WindowEvent {
+            window_id: unsafe { WindowId::dummy() },
+            event: WindowEvent::ReceivedCharacter(c),
+        }
+    }
+
     fn mouse_press(button: MouseButton) -> Event {
         mouse_event(button, ElementState::Pressed)
     }
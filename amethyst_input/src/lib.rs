@@ -5,13 +5,15 @@
 #[cfg(feature = "sdl_controller")]
 pub use self::sdl_events_system::SdlEventsSystem;
 pub use self::{
-    axis::Axis,
+    axis::{Axis, AxisSettings},
     bindings::{BindingError, Bindings},
     bundle::{BindingsFileError, InputBundle},
     button::Button,
     controller::{ControllerAxis, ControllerButton, ControllerEvent},
     event::InputEvent,
     input_handler::InputHandler,
+    recording::{InputFrame, InputRecorderSystem, InputRecording, InputReplaySystem, InputState},
+    rumble::Rumble,
     scroll_direction::ScrollDirection,
     system::InputSystem,
     util::{
@@ -31,6 +33,8 @@ mod button;
 mod controller;
 mod event;
 mod input_handler;
+mod recording;
+mod rumble;
 mod scroll_direction;
 mod system;
 mod util;
@@ -0,0 +1,238 @@
+//! Recording and replay of the input, for bug reproduction and automated gameplay tests.
+
+use std::{hash::Hash, marker::PhantomData};
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use winit::{MouseButton, VirtualKeyCode};
+
+use amethyst_core::{
+    ecs::prelude::{Read, Resources, System, SystemData, Write},
+    shrev::{EventChannel, ReaderId},
+    timing::Time,
+};
+
+use crate::{Bindings, ControllerAxis, ControllerButton, InputEvent, InputHandler};
+
+/// State of the input devices tracked by the `InputHandler`, see `InputHandler::state`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputState {
+    /// Pressed keys and their scancodes.
+    pub pressed_keys: Vec<(VirtualKeyCode, u32)>,
+    /// Pressed mouse buttons.
+    pub pressed_mouse_buttons: Vec<MouseButton>,
+    /// Pressed controller buttons, by controller id.
+    pub pressed_controller_buttons: Vec<(u32, ControllerButton)>,
+    /// Values of the controller axes, by controller id.
+    pub controller_axes: Vec<(u32, ControllerAxis, f64)>,
+    /// Ids of the connected controllers, and the ids used by their events.
+    pub connected_controllers: Vec<(u32, u32)>,
+    /// Position of the mouse pointer, if it is in the window.
+    pub mouse_position: Option<(f64, f64)>,
+}
+
+/// Input of a single frame.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame<AC> {
+    /// State of the `InputHandler` at the end of the frame.
+    pub state: InputState,
+    /// Events sent to the `EventChannel<InputEvent<AC>>` during the frame.
+    pub events: Vec<InputEvent<AC>>,
+}
+
+/// Input recorded frame by frame by the `InputRecorderSystem`.
+///
+/// Save it to a Ron file with `amethyst_config::Config::write` and play it back with
+/// `InputBundle::with_replay`:
+///
+/// ```rust,no_run
+/// # use amethyst_config::Config;
+/// # use amethyst_input::InputRecording;
+/// # let recording = InputRecording::<String>::default();
+/// recording.write("replay.ron").expect("Failed to save the input recording");
+/// ```
+#[derive(Clone, Debug, Derivative, PartialEq, Serialize, Deserialize)]
+#[derivative(Default(bound = ""))]
+pub struct InputRecording<AC> {
+    frames: Vec<InputFrame<AC>>,
+}
+
+impl<AC> InputRecording<AC> {
+    /// Returns the recorded frames, in order.
+    pub fn frames(&self) -> &[InputFrame<AC>] {
+        &self.frames
+    }
+
+    /// Adds a frame at the end of the recording.
+    pub fn push(&mut self, frame: InputFrame<AC>) {
+        self.frames.push(frame);
+    }
+
+    /// Removes all the recorded frames.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+/// Records the input of every frame in the `InputRecording<AC>` resource.
+///
+/// Runs after the systems updating the `InputHandler`, see `InputBundle::with_recording`.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct InputRecorderSystem<AX, AC> {
+    reader: Option<ReaderId<InputEvent<AC>>>,
+    marker: PhantomData<AX>,
+}
+
+impl<AX, AC> InputRecorderSystem<AX, AC> {
+    /// Creates a new input recorder system.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, AX, AC> System<'a> for InputRecorderSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, InputHandler<AX, AC>>,
+        Read<'a, EventChannel<InputEvent<AC>>>,
+        Write<'a, InputRecording<AC>>,
+    );
+
+    fn run(&mut self, (handler, events, mut recording): Self::SystemData) {
+        let reader = self.reader.as_mut().expect(
+            "`InputRecorderSystem::setup` was not called before `InputRecorderSystem::run`",
+        );
+        recording.push(InputFrame {
+            state: handler.state(),
+            events: events.read(reader).cloned().collect(),
+        });
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<AC>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Plays an `InputRecording` back, one frame per run.
+///
+/// Replaces the systems reading the input devices, so the `InputHandler` and the input events
+/// are exactly the recorded ones. Once all the frames are played the input stays unchanged.
+pub struct InputReplaySystem<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    recording: InputRecording<AC>,
+    frame: usize,
+    bindings: Option<Bindings<AX, AC>>,
+}
+
+impl<AX, AC> InputReplaySystem<AX, AC>
+where
+    AX: Hash + Eq + Clone,
+    AC: Hash + Eq + Clone,
+{
+    /// Creates a system playing `recording`, using `bindings` for the `InputHandler`.
+    pub fn new(recording: InputRecording<AC>, bindings: Option<Bindings<AX, AC>>) -> Self {
+        InputReplaySystem {
+            recording,
+            frame: 0,
+            bindings,
+        }
+    }
+
+    /// Returns true once all the frames of the recording were played.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.recording.frames.len()
+    }
+}
+
+impl<'a, AX, AC> System<'a> for InputReplaySystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + 'static,
+{
+    type SystemData = (
+        Write<'a, InputHandler<AX, AC>>,
+        Write<'a, EventChannel<InputEvent<AC>>>,
+        Read<'a, Time>,
+    );
+
+    fn run(&mut self, (mut handler, mut output, time): Self::SystemData) {
+        if let Some(frame) = self.recording.frames.get(self.frame) {
+            handler.set_state(frame.state.clone());
+            output.iter_write(frame.events.iter().cloned());
+            self.frame += 1;
+        }
+        handler.update_axes(f64::from(time.delta_real_seconds()));
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        if let Some(ref bindings) = self.bindings {
+            res.fetch_mut::<InputHandler<AX, AC>>().bindings = bindings.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::VirtualKeyCode;
+
+    use amethyst_core::{
+        ecs::prelude::{Resources, RunNow, System},
+        shrev::EventChannel,
+    };
+
+    use super::{InputRecorderSystem, InputRecording, InputReplaySystem, InputState};
+    use crate::{Button, InputEvent, InputHandler};
+
+    type Handler = InputHandler<String, String>;
+    type Events = EventChannel<InputEvent<String>>;
+
+    #[test]
+    fn replays_recorded_input() {
+        let mut res = Resources::new();
+        let mut recorder = InputRecorderSystem::<String, String>::new();
+        System::setup(&mut recorder, &mut res);
+
+        let pressed = InputEvent::ButtonPressed(Button::Key(VirtualKeyCode::Space));
+        res.fetch_mut::<Handler>().set_state(InputState {
+            pressed_keys: vec![(VirtualKeyCode::Space, 57)],
+            ..Default::default()
+        });
+        res.fetch_mut::<Events>().single_write(pressed.clone());
+        recorder.run_now(&res);
+        res.fetch_mut::<Handler>().set_state(InputState::default());
+        recorder.run_now(&res);
+
+        let recording = res.fetch::<InputRecording<String>>().clone();
+        assert_eq!(2, recording.frames().len());
+        assert_eq!(vec![pressed.clone()], recording.frames()[0].events);
+
+        let mut res = Resources::new();
+        let mut replay = InputReplaySystem::<String, String>::new(recording, None);
+        System::setup(&mut replay, &mut res);
+        let mut reader = res.fetch_mut::<Events>().register_reader();
+        replay.run_now(&res);
+        assert!(res.fetch::<Handler>().key_is_down(VirtualKeyCode::Space));
+        assert_eq!(
+            vec![pressed],
+            res.fetch::<Events>()
+                .read(&mut reader)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        replay.run_now(&res);
+        assert!(!res.fetch::<Handler>().key_is_down(VirtualKeyCode::Space));
+        assert!(replay.is_finished());
+    }
+}
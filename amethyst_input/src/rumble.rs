@@ -0,0 +1,94 @@
+//! Force feedback effects for controllers.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// A rumble effect played by the motors of a controller, see `InputHandler::rumble`.
+///
+/// The intensity of the motors ramps up from zero over `attack`, stays at `strong` and `weak`,
+/// then ramps down to zero over the last `fade` of the effect.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Rumble {
+    /// Intensity of the strong, low frequency motor, between 0.0 and 1.0.
+    pub strong: f32,
+    /// Intensity of the weak, high frequency motor, between 0.0 and 1.0.
+    pub weak: f32,
+    /// Duration of the whole effect.
+    pub duration: Duration,
+    /// Duration of the ramp up at the start of the effect.
+    pub attack: Duration,
+    /// Duration of the ramp down at the end of the effect.
+    pub fade: Duration,
+}
+
+impl Rumble {
+    /// Creates a rumble effect with constant intensity.
+    pub fn new(strong: f32, weak: f32, duration: Duration) -> Self {
+        Rumble {
+            strong,
+            weak,
+            duration,
+            attack: Duration::from_secs(0),
+            fade: Duration::from_secs(0),
+        }
+    }
+
+    /// Creates an effect stopping the current rumble of a controller.
+    pub fn stop() -> Self {
+        Rumble::new(0.0, 0.0, Duration::from_secs(0))
+    }
+
+    /// Ramps the intensity up over `attack` and down over `fade`.
+    pub fn with_envelope(mut self, attack: Duration, fade: Duration) -> Self {
+        self.attack = attack;
+        self.fade = fade;
+        self
+    }
+
+    /// Returns the intensity of the strong and weak motors `elapsed` after the start of the
+    /// effect, or `None` once it ended.
+    pub fn intensity(&self, elapsed: Duration) -> Option<(f32, f32)> {
+        if elapsed >= self.duration {
+            return None;
+        }
+        let secs =
+            |duration: Duration| duration.as_secs() as f32 + duration.subsec_nanos() as f32 * 1e-9;
+        let remaining = self.duration - elapsed;
+        let mut envelope = 1.0f32;
+        if elapsed < self.attack {
+            envelope = envelope.min(secs(elapsed) / secs(self.attack));
+        }
+        if remaining < self.fade {
+            envelope = envelope.min(secs(remaining) / secs(self.fade));
+        }
+        Some((self.strong * envelope, self.weak * envelope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Rumble;
+
+    #[test]
+    fn follows_envelope() {
+        let rumble = Rumble::new(1.0, 0.5, Duration::from_millis(1000))
+            .with_envelope(Duration::from_millis(200), Duration::from_millis(400));
+        assert_eq!(
+            Some((0.5, 0.25)),
+            rumble.intensity(Duration::from_millis(100))
+        );
+        assert_eq!(
+            Some((1.0, 0.5)),
+            rumble.intensity(Duration::from_millis(500))
+        );
+        assert_eq!(
+            Some((0.5, 0.25)),
+            rumble.intensity(Duration::from_millis(800))
+        );
+        assert_eq!(None, rumble.intensity(Duration::from_millis(1000)));
+        assert_eq!(None, Rumble::stop().intensity(Duration::from_secs(0)));
+    }
+}
@@ -1,10 +1,11 @@
-use std::{fmt, hash::Hash, marker::PhantomData, path::PathBuf};
+use std::{fmt, hash::Hash, marker::PhantomData, path::PathBuf, time::Instant};
 
 use sdl2::{
     self,
     controller::{AddMappingError, Axis, Button, GameController},
     event::Event,
-    EventPump, GameControllerSubsystem, Sdl,
+    haptic::Haptic,
+    EventPump, GameControllerSubsystem, HapticSubsystem, Sdl,
 };
 
 use amethyst_core::{
@@ -14,9 +15,12 @@ use amethyst_core::{
 
 use super::{
     controller::{ControllerAxis, ControllerButton, ControllerEvent},
-    InputEvent, InputHandler,
+    InputEvent, InputHandler, Rumble,
 };
 
+/// Duration of the motor effects played each frame while a rumble is active, in milliseconds.
+const RUMBLE_STEP: u32 = 100;
+
 /// A collection of errors that can occur in the SDL system.
 #[derive(Debug)]
 pub enum SdlSystemError {
@@ -62,6 +66,12 @@ where
     controller_subsystem: GameControllerSubsystem,
     /// Vector of opened controllers and their corresponding joystick indices
     opened_controllers: Vec<(u32, GameController)>,
+    /// Haptic subsystem, if the platform supports force feedback
+    haptic_subsystem: Option<HapticSubsystem>,
+    /// Vector of opened haptic devices and the instance ids of their controllers
+    haptics: Vec<(u32, Haptic)>,
+    /// Vector of playing rumble effects, by controller instance id
+    rumbles: Vec<(u32, Rumble, Instant)>,
     marker: PhantomData<(AX, AC)>,
 }
 
@@ -87,6 +97,8 @@ where
             self.handle_sdl_event(&event, &mut handler, &mut output);
         }
         self.event_pump = Some(event_pump);
+
+        self.update_rumbles(&mut handler);
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -109,6 +121,8 @@ where
         let controller_subsystem = sdl_context
             .game_controller()
             .map_err(|e| SdlSystemError::ControllerSubsystemInit(e))?;
+        // Rumble is optional, controllers still work without force feedback
+        let haptic_subsystem = sdl_context.haptic().ok();
 
         match mappings {
             Some(ControllerMappings::FromPath(p)) => {
@@ -129,6 +143,9 @@ where
             event_pump: Some(event_pump),
             controller_subsystem,
             opened_controllers: vec![],
+            haptic_subsystem,
+            haptics: vec![],
+            rumbles: vec![],
             marker: PhantomData,
         })
     }
@@ -214,6 +231,53 @@ where
         if let Some(i) = index {
             self.opened_controllers.swap_remove(i);
         }
+        self.haptics.retain(|(id, _)| *id != which);
+        self.rumbles.retain(|(id, _, _)| *id != which);
+    }
+
+    /// Starts the rumbles queued in the handler and updates the motors of playing ones.
+    fn update_rumbles(&mut self, handler: &mut InputHandler<AX, AC>) {
+        let now = Instant::now();
+        for (which, rumble) in handler.drain_rumbles() {
+            self.rumbles.retain(|(id, _, _)| *id != which);
+            self.rumbles.push((which, rumble, now));
+        }
+
+        let mut rumbles = std::mem::replace(&mut self.rumbles, vec![]);
+        rumbles.retain(|(which, rumble, start)| {
+            let intensity = rumble.intensity(now.duration_since(*start));
+            if let Some(haptic) = self.haptic(*which) {
+                // SDL's rumble API drives both motors with a single strength
+                match intensity {
+                    Some((strong, weak)) => haptic.rumble_play(strong.max(weak), RUMBLE_STEP),
+                    None => haptic.rumble_stop(),
+                }
+            }
+            intensity.is_some()
+        });
+        self.rumbles = rumbles;
+    }
+
+    /// Returns the haptic device of the controller, opening it on first use.
+    fn haptic(&mut self, which: u32) -> Option<&mut Haptic> {
+        let index = match self.haptics.iter().position(|(id, _)| *id == which) {
+            Some(index) => index,
+            None => {
+                let joystick_index = self
+                    .opened_controllers
+                    .iter()
+                    .find(|(_, c)| c.instance_id() as u32 == which)
+                    .map(|(index, _)| *index)?;
+                let haptic = self
+                    .haptic_subsystem
+                    .as_ref()?
+                    .open_from_joystick_id(joystick_index)
+                    .ok()?;
+                self.haptics.push((which, haptic));
+                self.haptics.len() - 1
+            }
+        };
+        Some(&mut self.haptics[index].1)
     }
 
     fn initialize_controllers(
@@ -7,6 +7,7 @@ use winit::Event;
 use amethyst_core::{
     ecs::prelude::{Read, ReadExpect, Resources, System, Write},
     shrev::{EventChannel, ReaderId},
+    timing::Time,
 };
 use amethyst_renderer::ScreenDimensions;
 
@@ -61,9 +62,10 @@ where
         Write<'a, InputHandler<AX, AC>>,
         Write<'a, EventChannel<InputEvent<AC>>>,
         ReadExpect<'a, ScreenDimensions>,
+        Read<'a, Time>,
     );
 
-    fn run(&mut self, (input, mut handler, mut output, screen_dimensions): Self::SystemData) {
+    fn run(&mut self, (input, mut handler, mut output, screen_dimensions, time): Self::SystemData) {
         for event in input.read(
             &mut self
                 .reader
@@ -77,6 +79,8 @@ where
                 screen_dimensions.hidpi_factor(),
             );
         }
+        handler.send_text_input(&mut *output);
+        handler.update_axes(f64::from(time.delta_real_seconds()));
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -4,9 +4,12 @@
 
 #![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
 
-use fluent::bundle::FluentBundle;
+use std::collections::HashMap;
 
-use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use fluent::{bundle::FluentBundle, types::FluentValue};
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, AssetStorage, Handle, ProcessingState, SimpleFormat};
 use amethyst_core::ecs::prelude::VecStorage;
 use amethyst_error::Error;
 
@@ -45,8 +48,145 @@ pub struct Locale {
     pub bundle: FluentBundle<'static>,
 }
 
+impl Locale {
+    /// Formats the message with the given id, or returns `None` if the locale does not have it.
+    ///
+    /// Missing or invalid arguments are replaced by their name in the formatted message.
+    pub fn format(&self, id: &str, args: &HashMap<String, LocaleArg>) -> Option<String> {
+        let args = args
+            .iter()
+            .map(|(name, arg)| (name.as_str(), arg.to_fluent()))
+            .collect::<HashMap<_, _>>();
+        let args = if args.is_empty() { None } else { Some(&args) };
+        self.bundle
+            .format(id, args)
+            .map(|(message, _errors)| message)
+    }
+}
+
 impl Asset for Locale {
     const NAME: &'static str = "locale::Locale";
     type Data = Locale;
     type HandleStorage = VecStorage<LocaleHandle>;
 }
+
+/// An argument of a localised message.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum LocaleArg {
+    /// A number, which selects the plural forms of the message.
+    Number(f64),
+    /// A text.
+    Text(String),
+}
+
+impl LocaleArg {
+    fn to_fluent(&self) -> FluentValue {
+        match *self {
+            LocaleArg::Number(number) => FluentValue::from(number),
+            LocaleArg::Text(ref text) => FluentValue::from(text.as_str()),
+        }
+    }
+}
+
+impl From<f64> for LocaleArg {
+    fn from(number: f64) -> Self {
+        LocaleArg::Number(number)
+    }
+}
+
+impl From<i32> for LocaleArg {
+    fn from(number: i32) -> Self {
+        LocaleArg::Number(f64::from(number))
+    }
+}
+
+impl From<String> for LocaleArg {
+    fn from(text: String) -> Self {
+        LocaleArg::Text(text)
+    }
+}
+
+impl<'a> From<&'a str> for LocaleArg {
+    fn from(text: &'a str) -> Self {
+        LocaleArg::Text(text.to_string())
+    }
+}
+
+/// The loaded locales by name, such as "en" or "fr", and the one currently used.
+#[derive(Clone, Debug, Default)]
+pub struct Localization {
+    locales: HashMap<String, LocaleHandle>,
+    active: Option<String>,
+}
+
+impl Localization {
+    /// Registers a locale under a name.
+    pub fn insert<S: Into<String>>(&mut self, name: S, locale: LocaleHandle) {
+        self.locales.insert(name.into(), locale);
+    }
+
+    /// Returns the locale registered with this name.
+    pub fn get(&self, name: &str) -> Option<&LocaleHandle> {
+        self.locales.get(name)
+    }
+
+    /// Uses the locale registered with this name, which may be registered later.
+    pub fn set_active<S: Into<String>>(&mut self, name: S) {
+        self.active = Some(name.into());
+    }
+
+    /// Returns the name of the locale in use.
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_ref().map(String::as_str)
+    }
+
+    /// Returns the locale in use.
+    pub fn active_handle(&self) -> Option<&LocaleHandle> {
+        self.active().and_then(|name| self.get(name))
+    }
+
+    /// Formats a message with the locale in use, or returns `None` if it is not loaded or does
+    /// not have the message.
+    pub fn format(
+        &self,
+        storage: &AssetStorage<Locale>,
+        id: &str,
+        args: &HashMap<String, LocaleArg>,
+    ) -> Option<String> {
+        self.active_handle()
+            .and_then(|handle| storage.get(handle))
+            .and_then(|locale| locale.format(id, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use amethyst_assets::SimpleFormat;
+
+    use super::{LocaleArg, LocaleFormat};
+
+    #[test]
+    fn formats_messages_with_arguments() {
+        let locale = LocaleFormat
+            .import(
+                b"greeting = Hello, { $name }!\n\
+                  apples = { $count ->\n    [one] One apple\n   *[other] { $count } apples\n}\n"
+                    .to_vec(),
+                (),
+            )
+            .unwrap();
+        let mut args = HashMap::new();
+        args.insert("name".to_string(), LocaleArg::from("Ferris"));
+        args.insert("count".to_string(), LocaleArg::from(3));
+
+        assert_eq!(
+            Some("Hello, Ferris!".to_string()),
+            locale.format("greeting", &args)
+        );
+        assert_eq!(Some("3 apples".to_string()), locale.format("apples", &args));
+        assert_eq!(None, locale.format("missing", &args));
+    }
+}
@@ -6,12 +6,31 @@ pub use crate::{
     bundle::NetworkBundle,
     connection::{ConnectionState, NetConnection, NetIdentity},
     error::Result,
-    net_event::{NetEvent, NetPacket},
+    lobby::{
+        LobbyClient, LobbyEvent, LobbyMessage, LobbyRequest, LobbySystem, SessionId, SessionInfo,
+    },
+    net_event::{Channel, NetEvent, NetPacket},
     network_socket::NetSocketSystem,
+    prediction::{
+        interpolate_transform, CommandBuffer, Interpolated, Predicted, SequencedCommand,
+        SnapshotBuffer, SnapshotInterpolationSystem, TransformSnapshot, TransformSnapshotSystem,
+    },
+    replication::{
+        ApplyReplicatedSystem, Interest, NetworkId, Replicate, ReplicateComponentSystem,
+        Replicated, ReplicationBundle, ReplicationClient, ReplicationClientSystem,
+        ReplicationEvent, ReplicationMessage, ReplicationServer, ReplicationServerSystem,
+        REPLICATION_STREAM,
+    },
     server::{Host, ServerConfig},
+    simulation::NetworkSimulation,
+    stats::{ConnectionStats, NetStats},
+    websocket::{WebSocketBundle, WebSocketConfig, WebSocketSystem},
 };
 
-use std::net::SocketAddr;
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
 
 use bincode::{deserialize, serialize};
 use crossbeam_channel::Sender;
@@ -19,52 +38,119 @@ use laminar::Packet;
 use log::error;
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::{
+    net_event::{DeliveryGuarantee, OrderingGuarantee},
+    wire::WireMessage,
+};
+
 mod bundle;
 mod connection;
 mod error;
+mod lobby;
 mod net_event;
 mod network_socket;
+mod prediction;
+mod replication;
 mod server;
+mod simulation;
+mod stats;
 mod test;
+mod websocket;
+mod wire;
 
 /// Sends an event to the target NetConnection using the provided network Socket.
 /// The socket has to be bound.
+///
+/// Events larger than a datagram are split in fragments, reassembled by the receiving
+/// `NetSocketSystem`.
 pub fn send_event<T>(event: NetPacket<T>, addr: SocketAddr, sender: &Sender<Packet>)
 where
     T: Serialize,
 {
-    let ser = serialize(&event.content());
-    match ser {
-        Ok(payload) => {
-            let packet = match event.delivery_guarantee() {
-                net_event::DeliveryGuarantee::Unreliable => match event.ordering_guarantee() {
-                    net_event::OrderingGuarantee::None => Packet::unreliable(addr, payload),
-                    net_event::OrderingGuarantee::Sequenced(s) => {
-                        Packet::unreliable_sequenced(addr, payload, s)
-                    }
-                    _ => unreachable!(
-                        "Can not apply the guarantees: {:?}, {:?} to the packet",
-                        event.ordering_guarantee(),
-                        event.delivery_guarantee()
-                    ),
-                },
-                net_event::DeliveryGuarantee::Reliable => match event.ordering_guarantee() {
-                    net_event::OrderingGuarantee::None => Packet::reliable_unordered(addr, payload),
-                    net_event::OrderingGuarantee::Sequenced(s) => {
-                        Packet::reliable_sequenced(addr, payload, s)
-                    }
-                    net_event::OrderingGuarantee::Ordered(o) => {
-                        Packet::reliable_ordered(addr, payload, o)
-                    }
-                },
-            };
+    let payload = match serialize(&event.content()) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize the event: {}", e);
+            return;
+        }
+    };
+    let delivery = event.delivery_guarantee();
+    let ordering = event.ordering_guarantee();
+
+    if payload.len() <= wire::MAX_FRAGMENT_SIZE {
+        send_message(
+            &WireMessage::Event(payload),
+            addr,
+            delivery,
+            ordering,
+            sender,
+        );
+        return;
+    }
 
-            match sender.send(packet) {
-                Ok(_qty) => {}
-                Err(e) => error!("Failed to send data to network socket: {}", e),
-            }
+    let id = NEXT_FRAGMENT_ID.fetch_add(1, AtomicOrdering::Relaxed) as u16;
+    let fragments = match wire::split(id, &payload) {
+        Some(fragments) => fragments,
+        None => {
+            error!(
+                "Failed to send an event of {} bytes: too large to be fragmented",
+                payload.len()
+            );
+            return;
         }
-        Err(e) => error!("Failed to serialize the event: {}", e),
+    };
+    // Sequencing would drop the fragments arriving after a later one of the same event.
+    let ordering = match ordering {
+        OrderingGuarantee::Sequenced(_) => OrderingGuarantee::None,
+        ordering => ordering,
+    };
+    for fragment in fragments {
+        send_message(
+            &WireMessage::Fragment(fragment),
+            addr,
+            delivery,
+            ordering,
+            sender,
+        );
+    }
+}
+
+static NEXT_FRAGMENT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Sends a message to `addr` with the given guarantees.
+pub(crate) fn send_message(
+    message: &WireMessage,
+    addr: SocketAddr,
+    delivery: DeliveryGuarantee,
+    ordering: OrderingGuarantee,
+    sender: &Sender<Packet>,
+) {
+    let payload = match serialize(message) {
+        Ok(payload) => payload,
+        Err(e) => {
+            error!("Failed to serialize a network message: {}", e);
+            return;
+        }
+    };
+    let packet = match delivery {
+        DeliveryGuarantee::Unreliable => match ordering {
+            OrderingGuarantee::None => Packet::unreliable(addr, payload),
+            OrderingGuarantee::Sequenced(s) => Packet::unreliable_sequenced(addr, payload, s),
+            _ => unreachable!(
+                "Can not apply the guarantees: {:?}, {:?} to the packet",
+                ordering, delivery
+            ),
+        },
+        DeliveryGuarantee::Reliable => match ordering {
+            OrderingGuarantee::None => Packet::reliable_unordered(addr, payload),
+            OrderingGuarantee::Sequenced(s) => Packet::reliable_sequenced(addr, payload, s),
+            OrderingGuarantee::Ordered(o) => Packet::reliable_ordered(addr, payload, o),
+        },
+    };
+
+    match sender.send(packet) {
+        Ok(_qty) => {}
+        Err(e) => error!("Failed to send data to network socket: {}", e),
     }
 }
 
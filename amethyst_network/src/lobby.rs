@@ -0,0 +1,448 @@
+//! Client of a master server listing the game sessions, so players find each other without
+//! typing addresses.
+//!
+//! The master server is reached over a WebSocket, exchanging `LobbyRequest`s and `LobbyMessage`s
+//! as JSON text frames. Hosts create a session with the address of their game server, and
+//! clients list and join the sessions, then open a `NetConnection` to their host.
+
+use std::{io::ErrorKind, net::SocketAddr, thread, time::Duration};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+use url::Url;
+
+use amethyst_core::{
+    ecs::{Resources, System, SystemData, Write},
+    shrev::EventChannel,
+};
+
+/// Time the lobby thread waits for a message before sending the queued requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Identifies a session on the master server.
+pub type SessionId = u64;
+
+/// A game session listed by the master server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// The id of the session.
+    pub id: SessionId,
+    /// The name of the session, shown to the players.
+    pub name: String,
+    /// Address of the game server of the session.
+    pub host: SocketAddr,
+    /// The names of the players in the session.
+    pub players: Vec<String>,
+    /// The maximal number of players.
+    pub max_players: u32,
+}
+
+/// Requests sent to the master server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LobbyRequest {
+    /// Lists the open sessions.
+    ListSessions,
+    /// Creates a session hosted at `host`, joined by `player`.
+    CreateSession {
+        /// The name of the session.
+        name: String,
+        /// Address of the game server of the session.
+        host: SocketAddr,
+        /// The maximal number of players.
+        max_players: u32,
+        /// The name of the hosting player.
+        player: String,
+    },
+    /// Joins a session.
+    JoinSession {
+        /// The session to join.
+        session: SessionId,
+        /// The name of the joining player.
+        player: String,
+    },
+    /// Leaves the current session.
+    LeaveSession,
+}
+
+/// Messages received from the master server.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LobbyMessage {
+    /// The open sessions, answering `ListSessions`.
+    Sessions {
+        /// The sessions.
+        sessions: Vec<SessionInfo>,
+    },
+    /// The session joined by the client, answering `CreateSession` or `JoinSession`.
+    Joined {
+        /// The session.
+        session: SessionInfo,
+    },
+    /// A player joined the session of the client.
+    PlayerJoined {
+        /// The name of the player.
+        player: String,
+    },
+    /// A player left the session of the client.
+    PlayerLeft {
+        /// The name of the player.
+        player: String,
+    },
+    /// A request failed.
+    Error {
+        /// Why the request failed.
+        message: String,
+    },
+}
+
+/// Events written to the `EventChannel<LobbyEvent>` by the `LobbySystem`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LobbyEvent {
+    /// The connection to the master server was established.
+    Connected,
+    /// The connection to the master server was lost or couldn't be established.
+    Disconnected,
+    /// A message was received from the master server.
+    Message(LobbyMessage),
+}
+
+enum LobbyThreadEvent {
+    Connected,
+    Message(LobbyMessage),
+    Disconnected,
+}
+
+/// Resource connected to a master server, sending the lobby requests.
+///
+/// Requests are queued until the connection is established. The answers are written to the
+/// `EventChannel<LobbyEvent>` by the `LobbySystem`, which also keeps the `sessions` and the
+/// current `session` up to date.
+pub struct LobbyClient {
+    url: String,
+    player: String,
+    requests: Sender<LobbyRequest>,
+    events: Receiver<LobbyThreadEvent>,
+    connected: bool,
+    sessions: Vec<SessionInfo>,
+    session: Option<SessionInfo>,
+}
+
+impl LobbyClient {
+    /// Connects to the master server at `url`, such as `ws://lobby.example.com:8080`, as `player`.
+    pub fn connect<U, P>(url: U, player: P) -> Self
+    where
+        U: Into<String>,
+        P: Into<String>,
+    {
+        let url = url.into();
+        let (requests, outgoing) = crossbeam_channel::unbounded();
+        let (incoming, events) = crossbeam_channel::unbounded();
+        let thread_url = url.clone();
+        thread::spawn(move || run_lobby(&thread_url, outgoing, incoming));
+
+        LobbyClient {
+            url,
+            player: player.into(),
+            requests,
+            events,
+            connected: false,
+            sessions: Vec::new(),
+            session: None,
+        }
+    }
+
+    /// Returns the url of the master server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns `true` while connected to the master server.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Returns the sessions received with the last `list_sessions`.
+    pub fn sessions(&self) -> &[SessionInfo] {
+        &self.sessions
+    }
+
+    /// Returns the session joined by the client, with its players.
+    pub fn session(&self) -> Option<&SessionInfo> {
+        self.session.as_ref()
+    }
+
+    /// Asks the master server for the open sessions.
+    pub fn list_sessions(&self) {
+        self.send(LobbyRequest::ListSessions);
+    }
+
+    /// Creates a session whose game server listens on `host`.
+    pub fn create_session<N: Into<String>>(&self, name: N, host: SocketAddr, max_players: u32) {
+        self.send(LobbyRequest::CreateSession {
+            name: name.into(),
+            host,
+            max_players,
+            player: self.player.clone(),
+        });
+    }
+
+    /// Joins a session listed by the master server.
+    pub fn join_session(&self, session: SessionId) {
+        self.send(LobbyRequest::JoinSession {
+            session,
+            player: self.player.clone(),
+        });
+    }
+
+    /// Leaves the current session.
+    pub fn leave_session(&self) {
+        self.send(LobbyRequest::LeaveSession);
+    }
+
+    fn send(&self, request: LobbyRequest) {
+        if self.requests.send(request).is_err() {
+            error!(
+                "Failed to send a lobby request: disconnected from {}",
+                self.url
+            );
+        }
+    }
+
+    fn handle(&mut self, message: &LobbyMessage) {
+        match *message {
+            LobbyMessage::Sessions { ref sessions } => self.sessions = sessions.clone(),
+            LobbyMessage::Joined { ref session } => self.session = Some(session.clone()),
+            LobbyMessage::PlayerJoined { ref player } => {
+                if let Some(ref mut session) = self.session {
+                    session.players.push(player.clone());
+                }
+            }
+            LobbyMessage::PlayerLeft { ref player } => {
+                if let Some(ref mut session) = self.session {
+                    session.players.retain(|p| p != player);
+                }
+            }
+            LobbyMessage::Error { .. } => {}
+        }
+    }
+}
+
+fn run_lobby(url: &str, requests: Receiver<LobbyRequest>, events: Sender<LobbyThreadEvent>) {
+    let socket = Url::parse(url)
+        .map_err(|e| e.to_string())
+        .and_then(|url| tungstenite::connect(url).map_err(|e| e.to_string()));
+    let mut socket = match socket {
+        Ok((socket, _response)) => socket,
+        Err(e) => {
+            error!("Failed to connect to the master server {}: {}", url, e);
+            let _ = events.send(LobbyThreadEvent::Disconnected);
+            return;
+        }
+    };
+    if let Err(e) = socket.get_mut().set_read_timeout(Some(POLL_INTERVAL)) {
+        error!("Failed to configure the connection to {}: {}", url, e);
+        let _ = events.send(LobbyThreadEvent::Disconnected);
+        return;
+    }
+    if events.send(LobbyThreadEvent::Connected).is_err() {
+        return;
+    }
+    info!("Connected to the master server {}", url);
+    poll(&mut socket, &requests, &events);
+    let _ = events.send(LobbyThreadEvent::Disconnected);
+}
+
+// Exchanges the requests and messages until the connection or the client is closed.
+fn poll<S>(
+    socket: &mut WebSocket<S>,
+    requests: &Receiver<LobbyRequest>,
+    events: &Sender<LobbyThreadEvent>,
+) where
+    S: std::io::Read + std::io::Write,
+{
+    loop {
+        match socket.read_message() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(message) => {
+                    if events.send(LobbyThreadEvent::Message(message)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => error!("Failed to parse a lobby message: {}", e),
+            },
+            Ok(Message::Close(_)) => return,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(ref e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => {
+                info!("Connection to the master server closed: {}", e);
+                return;
+            }
+        }
+
+        loop {
+            match requests.try_recv() {
+                Ok(request) => {
+                    let text = match serde_json::to_string(&request) {
+                        Ok(text) => text,
+                        Err(e) => {
+                            error!("Failed to serialize a lobby request: {}", e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = socket.write_message(Message::Text(text)) {
+                        error!("Failed to send a lobby request: {}", e);
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.write_pending();
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Writes the messages of the master server received by the `LobbyClient` to the
+/// `EventChannel<LobbyEvent>`.
+///
+/// Does nothing while there's no `LobbyClient` resource.
+#[derive(Debug, Default)]
+pub struct LobbySystem;
+
+impl<'a> System<'a> for LobbySystem {
+    type SystemData = (
+        Option<Write<'a, LobbyClient>>,
+        Write<'a, EventChannel<LobbyEvent>>,
+    );
+
+    fn run(&mut self, (client, mut events): Self::SystemData) {
+        let mut client = match client {
+            Some(client) => client,
+            None => return,
+        };
+        let received = client.events.try_iter().collect::<Vec<_>>();
+        for event in received {
+            let event = match event {
+                LobbyThreadEvent::Connected => {
+                    client.connected = true;
+                    LobbyEvent::Connected
+                }
+                LobbyThreadEvent::Disconnected => {
+                    client.connected = false;
+                    client.session = None;
+                    LobbyEvent::Disconnected
+                }
+                LobbyThreadEvent::Message(message) => {
+                    client.handle(&message);
+                    LobbyEvent::Message(message)
+                }
+            };
+            events.single_write(event);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, thread::sleep, time::Duration};
+
+    use tungstenite::Message;
+
+    use amethyst_core::{
+        ecs::{RunNow, System, World},
+        shrev::EventChannel,
+    };
+
+    use super::{LobbyClient, LobbyEvent, LobbyMessage, LobbyRequest, LobbySystem, SessionInfo};
+
+    fn session(players: Vec<String>) -> SessionInfo {
+        SessionInfo {
+            id: 1,
+            name: "Arena".to_string(),
+            host: "127.0.0.1:21602".parse().unwrap(),
+            players,
+            max_players: 4,
+        }
+    }
+
+    // A master server answering a single client.
+    fn serve(listener: TcpListener) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut socket = tungstenite::accept(stream).unwrap();
+        loop {
+            let request = match socket.read_message() {
+                Ok(Message::Text(text)) => serde_json::from_str::<LobbyRequest>(&text).unwrap(),
+                _ => return,
+            };
+            let answers = match request {
+                LobbyRequest::ListSessions => vec![LobbyMessage::Sessions {
+                    sessions: vec![session(vec!["host".to_string()])],
+                }],
+                LobbyRequest::JoinSession { player, .. } => vec![
+                    LobbyMessage::Joined {
+                        session: session(vec!["host".to_string(), player]),
+                    },
+                    LobbyMessage::PlayerLeft {
+                        player: "host".to_string(),
+                    },
+                ],
+                _ => vec![LobbyMessage::Error {
+                    message: "Unsupported".to_string(),
+                }],
+            };
+            for answer in answers {
+                let text = serde_json::to_string(&answer).unwrap();
+                socket.write_message(Message::Text(text)).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn lists_and_joins_sessions() {
+        let listener = TcpListener::bind("127.0.0.1:21600").unwrap();
+        thread::spawn(move || serve(listener));
+
+        let mut world = World::new();
+        let mut system = LobbySystem;
+        System::setup(&mut system, &mut world.res);
+        let mut reader = world
+            .write_resource::<EventChannel<LobbyEvent>>()
+            .register_reader();
+        world.add_resource(LobbyClient::connect("ws://127.0.0.1:21600", "guest"));
+
+        world.read_resource::<LobbyClient>().list_sessions();
+        world.read_resource::<LobbyClient>().join_session(1);
+        let mut events = Vec::new();
+        for _ in 0..100 {
+            system.run_now(&world.res);
+            events.extend(
+                world
+                    .read_resource::<EventChannel<LobbyEvent>>()
+                    .read(&mut reader)
+                    .cloned(),
+            );
+            if events.len() >= 4 {
+                break;
+            }
+            sleep(Duration::from_millis(20));
+        }
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], LobbyEvent::Connected);
+        let client = world.read_resource::<LobbyClient>();
+        assert!(client.is_connected());
+        assert_eq!(client.sessions(), &[session(vec!["host".to_string()])][..]);
+        assert_eq!(client.session(), Some(&session(vec!["guest".to_string()])));
+    }
+}
@@ -26,31 +26,58 @@ impl<T> NetEvent<T>
 where
     T: Serialize + DeserializeOwned,
 {
-    pub(crate) fn from_packet(packet: laminar::Packet) -> Result<Self> {
-        match crate::deserialize_event::<T>(packet.payload()) {
-            Ok(event) => {
-                let net_event: NetEvent<T> = NetEvent::Packet(match packet.delivery_guarantee() {
-                    laminar::DeliveryGuarantee::Unreliable => match packet.order_guarantee() {
-                        laminar::OrderingGuarantee::None => NetPacket::<T>::unreliable(event),
-                        laminar::OrderingGuarantee::Sequenced(s) => {
-                            NetPacket::unreliable_sequenced(event, s)
-                        }
-                        _ => panic!("This is in no way possible"),
-                    },
-                    laminar::DeliveryGuarantee::Reliable => match packet.order_guarantee() {
-                        laminar::OrderingGuarantee::None => NetPacket::reliable_unordered(event),
-                        laminar::OrderingGuarantee::Sequenced(s) => {
-                            NetPacket::reliable_sequenced(event, s)
-                        }
-                        laminar::OrderingGuarantee::Ordered(o) => {
-                            NetPacket::reliable_ordered(event, o)
-                        }
-                    },
-                });
-
-                Ok(net_event)
+    /// Deserializes a packet event from its payload, received with the given guarantees.
+    pub(crate) fn from_payload(
+        payload: &[u8],
+        delivery: laminar::DeliveryGuarantee,
+        ordering: laminar::OrderingGuarantee,
+    ) -> Result<Self> {
+        let content = crate::deserialize_event::<T>(payload)?;
+        Ok(NetEvent::Packet(NetPacket {
+            content,
+            ordering_guarantee: ordering.into(),
+            delivery_guarantee: delivery.into(),
+        }))
+    }
+}
+
+/// The channel on which a `NetPacket` is sent, giving its delivery and ordering guarantees.
+///
+/// The streams of the sequenced and ordered channels are arranged separately, `None` being the
+/// default stream.
+///
+/// Messages too large for a single datagram are split in fragments and reassembled by the
+/// receiver. Fragments of sequenced messages are sent unordered, so large messages lose their
+/// sequencing.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    /// Packets can be dropped, duplicated or arrive without order.
+    Unreliable,
+    /// Packets can be dropped, and older packets than the last received one are discarded.
+    UnreliableSequenced(Option<u8>),
+    /// Packets are delivered, in any order.
+    ReliableUnordered,
+    /// Packets are delivered, but older packets than the last received one are discarded.
+    ReliableSequenced(Option<u8>),
+    /// Packets are delivered in the order they were sent.
+    ReliableOrdered(Option<u8>),
+}
+
+impl Channel {
+    pub(crate) fn guarantees(self) -> (DeliveryGuarantee, OrderingGuarantee) {
+        match self {
+            Channel::Unreliable => (DeliveryGuarantee::Unreliable, OrderingGuarantee::None),
+            Channel::UnreliableSequenced(s) => (
+                DeliveryGuarantee::Unreliable,
+                OrderingGuarantee::Sequenced(s),
+            ),
+            Channel::ReliableUnordered => (DeliveryGuarantee::Reliable, OrderingGuarantee::None),
+            Channel::ReliableSequenced(s) => {
+                (DeliveryGuarantee::Reliable, OrderingGuarantee::Sequenced(s))
+            }
+            Channel::ReliableOrdered(o) => {
+                (DeliveryGuarantee::Reliable, OrderingGuarantee::Ordered(o))
             }
-            Err(e) => Err(e),
         }
     }
 }
@@ -95,6 +122,15 @@ impl From<OrderingGuarantee> for laminar::OrderingGuarantee {
     }
 }
 
+impl From<laminar::DeliveryGuarantee> for DeliveryGuarantee {
+    fn from(delivery: laminar::DeliveryGuarantee) -> Self {
+        match delivery {
+            laminar::DeliveryGuarantee::Unreliable => DeliveryGuarantee::Unreliable,
+            laminar::DeliveryGuarantee::Reliable => DeliveryGuarantee::Reliable,
+        }
+    }
+}
+
 impl From<DeliveryGuarantee> for laminar::DeliveryGuarantee {
     fn from(delivery: DeliveryGuarantee) -> Self {
         match delivery {
@@ -141,6 +177,16 @@ pub struct NetPacket<T> {
 }
 
 impl<T> NetPacket<T> {
+    /// Create a new packet with the given content, sent on `channel`.
+    pub fn new(content: T, channel: Channel) -> NetPacket<T> {
+        let (delivery_guarantee, ordering_guarantee) = channel.guarantees();
+        NetPacket {
+            ordering_guarantee,
+            delivery_guarantee,
+            content,
+        }
+    }
+
     /// Create a new unreliable packet with the given content.
     ///
     /// Unreliable: Packets can be dropped, duplicated or arrive without order.
@@ -279,6 +325,23 @@ impl<T> NetPacket<T> {
         self.ordering_guarantee == OrderingGuarantee::None
     }
 
+    /// Returns the channel on which this packet is sent or was received.
+    pub fn channel(&self) -> Channel {
+        match (self.delivery_guarantee, self.ordering_guarantee) {
+            (DeliveryGuarantee::Unreliable, OrderingGuarantee::Sequenced(s)) => {
+                Channel::UnreliableSequenced(s)
+            }
+            (DeliveryGuarantee::Unreliable, _) => Channel::Unreliable,
+            (DeliveryGuarantee::Reliable, OrderingGuarantee::None) => Channel::ReliableUnordered,
+            (DeliveryGuarantee::Reliable, OrderingGuarantee::Sequenced(s)) => {
+                Channel::ReliableSequenced(s)
+            }
+            (DeliveryGuarantee::Reliable, OrderingGuarantee::Ordered(o)) => {
+                Channel::ReliableOrdered(o)
+            }
+        }
+    }
+
     /// Returns a immutable reference to the content.
     pub fn content(&self) -> &T {
         &self.content
@@ -302,7 +365,7 @@ impl<T> NetPacket<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::net_event::NetPacket;
+    use crate::net_event::{Channel, NetPacket};
 
     #[test]
     fn assure_creation_unreliable_packet() {
@@ -359,6 +422,23 @@ mod tests {
         assert_eq!(packet.is_unreliable(), false);
     }
 
+    #[test]
+    fn assure_channel_round_trip() {
+        for &channel in &[
+            Channel::Unreliable,
+            Channel::UnreliableSequenced(Some(2)),
+            Channel::ReliableUnordered,
+            Channel::ReliableSequenced(None),
+            Channel::ReliableOrdered(Some(1)),
+        ] {
+            assert_eq!(NetPacket::new(test_payload(), channel).channel(), channel);
+        }
+        assert_eq!(
+            NetPacket::reliable_ordered(test_payload(), None).channel(),
+            Channel::ReliableOrdered(None)
+        );
+    }
+
     fn test_payload() -> Vec<u8> {
         return "test".as_bytes().to_vec();
     }
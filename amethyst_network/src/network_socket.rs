@@ -1,9 +1,12 @@
 //! The network send and receive System
 
-use std::{clone::Clone, net::SocketAddr, thread};
+use std::{clone::Clone, net::SocketAddr, thread, time::Instant};
 
-use amethyst_core::ecs::{Entities, Join, Resources, System, SystemData, WriteStorage};
+use amethyst_core::ecs::{
+    Entities, Join, Read, Resources, System, SystemData, Write, WriteStorage,
+};
 
+use bincode::deserialize;
 use crossbeam_channel::{Receiver, Sender};
 use laminar::{Packet, SocketEvent};
 use log::{error, warn};
@@ -11,8 +14,12 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use super::{
     error::Result,
-    send_event,
+    net_event::{DeliveryGuarantee, OrderingGuarantee},
+    send_event, send_message,
     server::{Host, ServerConfig},
+    simulation::{NetworkSimulation, SimulationQueue},
+    stats::NetStats,
+    wire::{Reassembly, WireMessage, FRAGMENT_TIMEOUT},
     ConnectionState, NetConnection, NetEvent,
 };
 
@@ -21,6 +28,10 @@ enum InternalSocketEvent<E> {
         target: SocketAddr,
         events: Vec<NetEvent<E>>,
     },
+    SendMessage {
+        target: SocketAddr,
+        message: WireMessage,
+    },
     Stop,
 }
 
@@ -32,6 +43,12 @@ enum InternalSocketEvent<E> {
 /// Received events will be inserted into the NetReceiveBuffer resource.
 /// To send an event, add it to the NetSendBuffer resource.
 ///
+/// Each connection is pinged every `ServerConfig::ping_interval`, measuring its round trip time
+/// and packet loss in the `NetStats` resource.
+///
+/// While a `NetworkSimulation` resource is present, the received packets are delayed, dropped
+/// and duplicated following its conditions.
+///
 /// If both a connection (Connect or Connected) event is received at the same time as another event from the same connection,
 /// only the connection event will be considered and rest will be filtered out.
 // TODO: add Unchecked Event type list. Those events will be let pass the client connected filter (Example: NetEvent::Connect).
@@ -45,6 +62,12 @@ where
     // receiver from which you can read received packets.
     event_receiver: Receiver<laminar::SocketEvent>,
     config: ServerConfig,
+    // fragments of the large events being received.
+    reassembly: Reassembly,
+    // when the connections were last pinged.
+    last_ping: Option<Instant>,
+    // packets held back by the `NetworkSimulation`.
+    simulation: SimulationQueue<Packet>,
 }
 
 impl<E> NetSocketSystem<E>
@@ -69,6 +92,9 @@ where
             event_sender,
             event_receiver: udp_receive_handle,
             config,
+            reassembly: Reassembly::default(),
+            last_ping: None,
+            simulation: SimulationQueue::default(),
         })
     }
 
@@ -89,6 +115,15 @@ where
                             }
                         }
                     }
+                    InternalSocketEvent::SendMessage { target, message } => {
+                        send_message(
+                            &message,
+                            target,
+                            DeliveryGuarantee::Unreliable,
+                            OrderingGuarantee::None,
+                            &sender,
+                        );
+                    }
                     InternalSocketEvent::Stop => {
                         break;
                     }
@@ -104,18 +139,48 @@ impl<'a, E> System<'a> for NetSocketSystem<E>
 where
     E: Send + Sync + Serialize + Clone + DeserializeOwned + PartialEq + 'static,
 {
-    type SystemData = (WriteStorage<'a, NetConnection<E>>, Entities<'a>);
+    type SystemData = (
+        WriteStorage<'a, NetConnection<E>>,
+        Entities<'a>,
+        Write<'a, NetStats>,
+        Option<Read<'a, NetworkSimulation>>,
+    );
+
+    fn run(&mut self, (mut net_connections, entities, mut stats, simulation): Self::SystemData) {
+        let now = Instant::now();
+        let ping = self.last_ping.map_or(true, |last_ping| {
+            now.duration_since(last_ping) >= self.config.ping_interval
+        });
+        if ping {
+            self.last_ping = Some(now);
+        }
 
-    fn run(&mut self, (mut net_connections, entities): Self::SystemData) {
         for connection in (&mut net_connections).join() {
             match connection.state {
                 ConnectionState::Connected | ConnectionState::Connecting => {
+                    let events = connection
+                        .send_buffer_early_read()
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let connection_stats = stats.entry(connection.target_addr);
+                    connection_stats.add_sent(events.len() as u64);
                     self.event_sender
                         .send(InternalSocketEvent::SendEvents {
                             target: connection.target_addr,
-                            events: connection.send_buffer_early_read().cloned().collect(),
+                            events,
                         })
                         .expect("Unreachable: Channel will be alive until a stop event is sent");
+                    if ping {
+                        let sequence = connection_stats.ping_sent(now);
+                        self.event_sender
+                            .send(InternalSocketEvent::SendMessage {
+                                target: connection.target_addr,
+                                message: WireMessage::Ping(sequence),
+                            })
+                            .expect(
+                                "Unreachable: Channel will be alive until a stop event is sent",
+                            );
+                    }
                 }
                 ConnectionState::Disconnected => {
                     self.event_sender
@@ -125,23 +190,100 @@ where
             }
         }
 
-        for (counter, socket_event) in self.event_receiver.try_iter().enumerate() {
+        // this will prevent our system to be stuck in the iterator.
+        // After 10000 packets we will continue and leave the other packets for the next run.
+        // eventually some congestion prevention should be done.
+        let received = self
+            .event_receiver
+            .try_iter()
+            .take(self.config.max_throughput as usize + 1);
+        let socket_events = match simulation {
+            Some(ref simulation) => {
+                let mut socket_events = Vec::new();
+                for socket_event in received {
+                    match socket_event {
+                        SocketEvent::Packet(packet) => {
+                            let reliable = match packet.delivery_guarantee() {
+                                laminar::DeliveryGuarantee::Reliable => true,
+                                laminar::DeliveryGuarantee::Unreliable => false,
+                            };
+                            self.simulation
+                                .push(simulation, packet.addr(), packet, reliable, now);
+                        }
+                        other => socket_events.push(other),
+                    }
+                }
+                socket_events.extend(
+                    self.simulation
+                        .pop_ready(now)
+                        .into_iter()
+                        .map(SocketEvent::Packet),
+                );
+                socket_events
+            }
+            // deliver what was held back before the simulation was removed.
+            None => self
+                .simulation
+                .drain()
+                .into_iter()
+                .map(SocketEvent::Packet)
+                .chain(received)
+                .collect::<Vec<_>>(),
+        };
+
+        for socket_event in socket_events {
             match socket_event {
                 SocketEvent::Packet(packet) => {
                     let from_addr = packet.addr();
 
-                    match NetEvent::<E>::from_packet(packet) {
-                        Ok(event) => {
-                            for connection in (&mut net_connections).join() {
-                                if &connection.target_addr == &from_addr {
-                                    connection.receive_buffer.single_write(event.clone());
+                    let payload = match deserialize::<WireMessage>(packet.payload()) {
+                        Ok(WireMessage::Event(payload)) => Some(payload),
+                        Ok(WireMessage::Fragment(fragment)) => {
+                            self.reassembly.insert(from_addr, fragment, now)
+                        }
+                        Ok(WireMessage::Ping(sequence)) => {
+                            self.event_sender
+                                .send(InternalSocketEvent::SendMessage {
+                                    target: from_addr,
+                                    message: WireMessage::Pong(sequence),
+                                })
+                                .expect(
+                                    "Unreachable: Channel will be alive until a stop event is sent",
+                                );
+                            None
+                        }
+                        Ok(WireMessage::Pong(sequence)) => {
+                            stats.entry(from_addr).pong_received(sequence, now);
+                            None
+                        }
+                        Err(e) => {
+                            error!(
+                                "Failed to deserialize an incoming network message: {} From source: {:?}",
+                                e, from_addr
+                            );
+                            None
+                        }
+                    };
+
+                    if let Some(payload) = payload {
+                        match NetEvent::<E>::from_payload(
+                            &payload,
+                            packet.delivery_guarantee(),
+                            packet.order_guarantee(),
+                        ) {
+                            Ok(event) => {
+                                stats.entry(from_addr).add_received();
+                                for connection in (&mut net_connections).join() {
+                                    if &connection.target_addr == &from_addr {
+                                        connection.receive_buffer.single_write(event.clone());
+                                    }
                                 }
                             }
+                            Err(e) => error!(
+                                "Failed to deserialize an incoming network event: {} From source: {:?}",
+                                e, from_addr
+                            ),
                         }
-                        Err(e) => error!(
-                            "Failed to deserialize an incoming network event: {} From source: {:?}",
-                            e, from_addr
-                        ),
                     }
                 }
                 SocketEvent::Connect(addr) => {
@@ -159,6 +301,8 @@ where
                     }
                 }
                 SocketEvent::Timeout(timeout_addr) => {
+                    self.reassembly.remove_addr(timeout_addr);
+                    stats.remove(&timeout_addr);
                     for connection in (&mut net_connections).join() {
                         if connection.target_addr == timeout_addr {
                             // we can't remove the entity from the world here because it could still have events in it's buffer.
@@ -169,14 +313,9 @@ where
                     }
                 }
             };
-
-            // this will prevent our system to be stuck in the iterator.
-            // After 10000 packets we will continue and leave the other packets for the next run.
-            // eventually some congestion prevention should be done.
-            if counter >= self.config.max_throughput as usize {
-                break;
-            }
         }
+
+        self.reassembly.expire(now, FRAGMENT_TIMEOUT);
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -0,0 +1,513 @@
+//! Snapshot interpolation of the replicated entities and client side prediction.
+//!
+//! The server stamps the `Transform` of its replicated entities with its time in a
+//! `TransformSnapshot`, replicated like any other component. Clients keep the last snapshots of
+//! each entity in its `Interpolated` component, and render them slightly in the past,
+//! interpolating between the two snapshots around that time so the entities move smoothly.
+//!
+//! The entity controlled by a client is predicted instead: its inputs are applied right away,
+//! and kept in a `CommandBuffer` until the server acknowledges them. When the authoritative state
+//! is received, the commands the server hasn't processed yet are replayed over it with
+//! `CommandBuffer::reconcile`.
+
+use std::collections::VecDeque;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Join, NullStorage, Read, ReadStorage, System,
+        WriteStorage,
+    },
+    math::Translation3,
+    timing::Time,
+    Float, Transform,
+};
+
+use crate::replication::{Replicate, Replicated};
+
+/// Time in seconds after which the snapshot clock of a client jumps to the latest snapshot
+/// instead of catching up with it.
+const RESYNC_THRESHOLD: f64 = 1.0;
+
+/// Buffer of the last values of something, stamped with the time they were taken at.
+#[derive(Clone, Debug)]
+pub struct SnapshotBuffer<T> {
+    snapshots: VecDeque<(f64, T)>,
+    capacity: usize,
+}
+
+impl<T> SnapshotBuffer<T> {
+    /// Creates a buffer keeping the last `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        SnapshotBuffer {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a snapshot taken at `time`, returning `false` if it's older than the latest one.
+    pub fn push(&mut self, time: f64, value: T) -> bool {
+        if self.latest_time().map_or(false, |latest| time <= latest) {
+            return false;
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back((time, value));
+        true
+    }
+
+    /// Returns the time of the latest snapshot.
+    pub fn latest_time(&self) -> Option<f64> {
+        self.snapshots.back().map(|&(time, _)| time)
+    }
+
+    /// Returns the latest snapshot.
+    pub fn latest(&self) -> Option<&T> {
+        self.snapshots.back().map(|&(_, ref value)| value)
+    }
+
+    /// Returns the snapshots before and after `time`, with the position of `time` between them
+    /// from 0 to 1.
+    ///
+    /// Times out of the buffer give its first or last snapshot twice, the buffer doesn't
+    /// extrapolate.
+    pub fn sample(&self, time: f64) -> Option<(&T, &T, f32)> {
+        let after = self
+            .snapshots
+            .iter()
+            .position(|&(snapshot, _)| snapshot >= time);
+        match after {
+            Some(0) => self
+                .snapshots
+                .front()
+                .map(|&(_, ref value)| (value, value, 0.0)),
+            Some(after) => {
+                let (before_time, ref before) = self.snapshots[after - 1];
+                let (after_time, ref after) = self.snapshots[after];
+                let factor = (time - before_time) / (after_time - before_time);
+                Some((before, after, factor as f32))
+            }
+            None => self.latest().map(|value| (value, value, 0.0)),
+        }
+    }
+
+    /// Returns the number of snapshots in the buffer.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `true` if the buffer holds no snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Removes all the snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// Interpolates the translation, rotation and scale of two transforms.
+pub fn interpolate_transform(from: &Transform, to: &Transform, factor: f32) -> Transform {
+    let factor = Float::from(factor);
+    Transform::new(
+        Translation3::from(from.translation().lerp(to.translation(), factor)),
+        from.rotation().slerp(to.rotation(), factor),
+        from.scale().lerp(to.scale(), factor),
+    )
+}
+
+/// The `Transform` of a replicated entity at a time of the server, in seconds.
+///
+/// Set by the `TransformSnapshotSystem` on the server, and read by the
+/// `SnapshotInterpolationSystem` of the clients. It must be registered with the
+/// `ReplicationBundle` on both sides.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransformSnapshot {
+    /// Time of the server when the snapshot was taken.
+    pub time: f64,
+    /// The transform of the entity.
+    pub transform: Transform,
+}
+
+impl Component for TransformSnapshot {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Replicated for TransformSnapshot {}
+
+/// Takes a `TransformSnapshot` of every replicated entity, on the server.
+#[derive(Debug, Default)]
+pub struct TransformSnapshotSystem;
+
+impl<'a> System<'a> for TransformSnapshotSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadStorage<'a, Replicate>,
+        ReadStorage<'a, Transform>,
+        WriteStorage<'a, TransformSnapshot>,
+    );
+
+    fn run(&mut self, (entities, time, replicates, transforms, mut snapshots): Self::SystemData) {
+        let now = time.absolute_time_seconds();
+        for (entity, _, transform) in (&*entities, &replicates, &transforms).join() {
+            let snapshot = TransformSnapshot {
+                time: now,
+                transform: transform.clone(),
+            };
+            if let Err(e) = snapshots.insert(entity, snapshot) {
+                warn!(
+                    "Failed to insert the `TransformSnapshot` of {:?}: {}",
+                    entity, e
+                );
+            }
+        }
+    }
+}
+
+/// The snapshots received for an entity, interpolated into its `Transform` by the
+/// `SnapshotInterpolationSystem`.
+///
+/// It's added to the entities with a `TransformSnapshot` which don't have one, except the
+/// `Predicted` ones.
+#[derive(Clone, Debug)]
+pub struct Interpolated {
+    /// The snapshots of the `Transform` of the entity.
+    pub snapshots: SnapshotBuffer<Transform>,
+}
+
+impl Default for Interpolated {
+    fn default() -> Self {
+        Interpolated {
+            snapshots: SnapshotBuffer::new(32),
+        }
+    }
+}
+
+impl Component for Interpolated {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Renders the replicated entities `delay` seconds in the past on a client, interpolating
+/// their `TransformSnapshot`s into their `Transform`.
+///
+/// The time of the server is estimated from the latest snapshot received, and advances with the
+/// frames between the snapshots.
+#[derive(Debug)]
+pub struct SnapshotInterpolationSystem {
+    delay: f64,
+    server_time: Option<f64>,
+}
+
+impl SnapshotInterpolationSystem {
+    /// Creates a system rendering the entities `delay` seconds in the past.
+    ///
+    /// The delay should cover at least two snapshot intervals, so there's always a snapshot
+    /// after the rendered time.
+    pub fn new(delay: f64) -> Self {
+        SnapshotInterpolationSystem {
+            delay,
+            server_time: None,
+        }
+    }
+
+    /// Returns the estimated time of the server.
+    pub fn server_time(&self) -> Option<f64> {
+        self.server_time
+    }
+}
+
+impl Default for SnapshotInterpolationSystem {
+    fn default() -> Self {
+        SnapshotInterpolationSystem::new(0.1)
+    }
+}
+
+impl<'a> System<'a> for SnapshotInterpolationSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadStorage<'a, TransformSnapshot>,
+        ReadStorage<'a, Predicted>,
+        WriteStorage<'a, Interpolated>,
+        WriteStorage<'a, Transform>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, time, snapshots, predicted, mut interpolated, mut transforms) = data;
+
+        let missing = (&*entities, &snapshots, !&predicted, !&interpolated)
+            .join()
+            .map(|(entity, _, _, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in missing {
+            if let Err(e) = interpolated.insert(entity, Interpolated::default()) {
+                warn!("Failed to insert the `Interpolated` of {:?}: {}", entity, e);
+            }
+        }
+
+        let mut latest = None;
+        for (snapshot, interpolated) in (&snapshots, &mut interpolated).join() {
+            interpolated
+                .snapshots
+                .push(snapshot.time, snapshot.transform.clone());
+            latest = Some(latest.map_or(snapshot.time, |latest: f64| latest.max(snapshot.time)));
+        }
+        let latest = match latest {
+            Some(latest) => latest,
+            None => return,
+        };
+
+        let server_time = match self.server_time {
+            Some(server_time) if latest - server_time <= RESYNC_THRESHOLD => {
+                (server_time + f64::from(time.delta_seconds())).min(latest)
+            }
+            _ => latest,
+        };
+        self.server_time = Some(server_time);
+
+        let render_time = server_time - self.delay;
+        for (entity, interpolated) in (&*entities, &interpolated).join() {
+            if let Some((from, to, factor)) = interpolated.snapshots.sample(render_time) {
+                let transform = interpolate_transform(from, to, factor);
+                if let Err(e) = transforms.insert(entity, transform) {
+                    warn!("Failed to insert the `Transform` of {:?}: {}", entity, e);
+                }
+            }
+        }
+    }
+}
+
+/// Marks the entities predicted by the client, which aren't interpolated.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Predicted;
+
+impl Component for Predicted {
+    type Storage = NullStorage<Self>;
+}
+
+/// A command sent to the server, with its sequence number so the server can acknowledge it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencedCommand<C> {
+    /// The sequence number of the command.
+    pub sequence: u32,
+    /// The command.
+    pub command: C,
+}
+
+/// Ring buffer of the commands a client applied and sent but the server didn't acknowledge yet.
+///
+/// ```rust,ignore
+/// // Every frame, predict the command locally and send it.
+/// let sequence = commands.push(input);
+/// apply(&mut state, &input);
+/// send(SequencedCommand { sequence, command: input });
+///
+/// // When the server state is received, with the last command it processed.
+/// state = commands.reconcile(server.acknowledged, server.state, apply);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CommandBuffer<C> {
+    next_sequence: u32,
+    commands: VecDeque<(u32, C)>,
+    capacity: usize,
+}
+
+impl<C> Default for CommandBuffer<C> {
+    fn default() -> Self {
+        CommandBuffer::new(128)
+    }
+}
+
+impl<C> CommandBuffer<C> {
+    /// Creates a buffer keeping at most `capacity` unacknowledged commands.
+    pub fn new(capacity: usize) -> Self {
+        CommandBuffer {
+            next_sequence: 0,
+            commands: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Adds a command, returning its sequence number.
+    ///
+    /// When the buffer is full the oldest command is dropped, it won't be replayed.
+    pub fn push(&mut self, command: C) -> u32 {
+        if self.commands.len() == self.capacity {
+            warn!("Command buffer full, dropping the oldest unacknowledged command");
+            self.commands.pop_front();
+        }
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+        self.commands.push_back((sequence, command));
+        sequence
+    }
+
+    /// Drops the commands up to `sequence`, processed by the server.
+    pub fn acknowledge(&mut self, sequence: u32) {
+        while let Some(&(front, _)) = self.commands.front() {
+            // Compares the sequences modulo 2^32, so they can wrap.
+            if sequence.wrapping_sub(front) >= u32::max_value() / 2 {
+                break;
+            }
+            self.commands.pop_front();
+        }
+    }
+
+    /// Iterates over the unacknowledged commands, from the oldest.
+    pub fn unacknowledged(&self) -> impl Iterator<Item = (u32, &C)> {
+        self.commands
+            .iter()
+            .map(|&(sequence, ref command)| (sequence, command))
+    }
+
+    /// Returns the number of unacknowledged commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if all commands were acknowledged.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Acknowledges the commands up to `acknowledged`, and replays the remaining ones over the
+    /// state received from the server with `apply`, returning the predicted state.
+    pub fn reconcile<S, F>(&mut self, acknowledged: u32, state: S, mut apply: F) -> S
+    where
+        F: FnMut(&mut S, &C),
+    {
+        self.acknowledge(acknowledged);
+        let mut state = state;
+        for &(_, ref command) in &self.commands {
+            apply(&mut state, command);
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, RunNow, System, World},
+        timing::Time,
+        Transform,
+    };
+
+    use super::{
+        CommandBuffer, Interpolated, SnapshotBuffer, SnapshotInterpolationSystem, TransformSnapshot,
+    };
+
+    #[test]
+    fn samples_snapshots() {
+        let mut buffer = SnapshotBuffer::new(2);
+        assert_eq!(buffer.sample(0.0), None);
+        assert!(buffer.push(1.0, 10.0));
+        assert!(buffer.push(2.0, 20.0));
+        assert!(!buffer.push(1.5, 15.0));
+        assert_eq!(buffer.sample(0.0), Some((&10.0, &10.0, 0.0)));
+        assert_eq!(buffer.sample(1.25), Some((&10.0, &20.0, 0.25)));
+        assert_eq!(buffer.sample(3.0), Some((&20.0, &20.0, 0.0)));
+
+        assert!(buffer.push(3.0, 30.0));
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.sample(1.0), Some((&20.0, &20.0, 0.0)));
+    }
+
+    #[test]
+    fn reconciles_unacknowledged_commands() {
+        let mut commands = CommandBuffer::new(8);
+        let mut predicted = 0;
+        for step in 1..=4 {
+            commands.push(step);
+            predicted += step;
+        }
+        assert_eq!(predicted, 10);
+
+        // The server processed the first two commands, and moved the state by itself.
+        let state = commands.reconcile(1, 1 + 2 + 100, |state, step| *state += *step);
+        assert_eq!(state, 107);
+        assert_eq!(
+            commands.unacknowledged().collect::<Vec<_>>(),
+            vec![(2, &3), (3, &4)]
+        );
+    }
+
+    #[test]
+    fn interpolates_snapshots() {
+        let mut world = World::new();
+        let mut system = SnapshotInterpolationSystem::new(0.5);
+        System::setup(&mut system, &mut world.res);
+        world.write_resource::<Time>().set_delta_seconds(0.25);
+
+        let mut transform = Transform::default();
+        let entity = world
+            .create_entity()
+            .with(TransformSnapshot {
+                time: 1.0,
+                transform: transform.clone(),
+            })
+            .with(transform.clone())
+            .build();
+        system.run_now(&world.res);
+
+        transform.set_translation_x(4.0);
+        world
+            .write_storage::<TransformSnapshot>()
+            .insert(
+                entity,
+                TransformSnapshot {
+                    time: 2.0,
+                    transform,
+                },
+            )
+            .unwrap();
+        system.run_now(&world.res);
+
+        // The server time advances by 0.25 each frame from the first snapshot, so the entity is
+        // rendered at 0.75, then 1.0, then 1.25.
+        assert!(world.read_storage::<Interpolated>().get(entity).is_some());
+        assert_eq!(
+            world
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap()
+                .translation()
+                .x,
+            0.0.into()
+        );
+        system.run_now(&world.res);
+        system.run_now(&world.res);
+        assert_eq!(
+            world
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap()
+                .translation()
+                .x,
+            1.0.into()
+        );
+    }
+
+    #[test]
+    fn inserts_missing_transforms() {
+        let mut world = World::new();
+        let mut system = SnapshotInterpolationSystem::new(0.0);
+        System::setup(&mut system, &mut world.res);
+
+        let entity = world
+            .create_entity()
+            .with(TransformSnapshot {
+                time: 1.0,
+                transform: Transform::default(),
+            })
+            .build();
+        system.run_now(&world.res);
+
+        assert!(world.read_storage::<Transform>().get(entity).is_some());
+    }
+}
@@ -0,0 +1,771 @@
+//! Replication of components from a server to its clients.
+//!
+//! On the server, entities with the `Replicate` component are given a `NetworkId`, and their
+//! components of the registered `Replicated` types are sent to the clients whenever they change.
+//! Clients create an entity for each replicated entity they're told about, and keep its
+//! components in sync. A client only receives the entities within the `Interest` of its
+//! connection, they're despawned on the client when they get out of it.
+//!
+//! The messages are sent as `ReplicationMessage`s on a reliable ordered stream of the
+//! `NetConnection`s, see `ReplicationBundle`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+};
+
+use bincode::{deserialize, serialize};
+use log::error;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use amethyst_core::{
+    bundle::SystemBundle,
+    ecs::{
+        prelude::{
+            Component, DenseVecStorage, Entities, Entity, Join, NullStorage, Read, ReadStorage,
+            System, Write, WriteStorage,
+        },
+        shrev::ReaderId,
+    },
+    math::Vector3,
+    shred::DispatcherBuilder,
+    Transform,
+};
+use amethyst_error::Error;
+
+use crate::{NetConnection, NetEvent, NetPacket};
+
+/// Stream of the reliable ordered channel on which the replication messages are sent.
+pub const REPLICATION_STREAM: u8 = 200;
+
+/// Marks a component type which can be replicated, once registered with
+/// `ReplicationBundle::with_component`.
+pub trait Replicated: Component + Serialize + DeserializeOwned + Send + Sync {}
+
+/// Identifies a replicated entity on the server and all its clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NetworkId(pub u64);
+
+impl Component for NetworkId {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Marks an entity of the server to replicate to the clients.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Replicate;
+
+impl Component for Replicate {
+    type Storage = NullStorage<Self>;
+}
+
+/// Limits the entities replicated to the client of a `NetConnection`, added to the entity of the
+/// connection on the server.
+///
+/// Replicated entities with a `Transform` are only sent while they're within `radius` of the
+/// `Transform` of `focus`, usually the entity controlled by the client. Entities without a
+/// `Transform` are always sent. Connections without an `Interest` receive all entities.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interest {
+    /// Entity at the center of the area of interest.
+    pub focus: Entity,
+    /// Radius of the area of interest.
+    pub radius: f32,
+}
+
+impl Component for Interest {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Messages sent by the server to replicate its entities.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplicationMessage {
+    /// A replicated entity entered the interest of the client.
+    Spawn(NetworkId),
+    /// A replicated entity was deleted, or left the interest of the client.
+    Despawn(NetworkId),
+    /// A component of a replicated entity was added or changed.
+    Update {
+        /// The entity.
+        id: NetworkId,
+        /// The name under which the component type was registered.
+        component: String,
+        /// The component, serialized with bincode.
+        data: Vec<u8>,
+    },
+    /// A component of a replicated entity was removed.
+    Remove {
+        /// The entity.
+        id: NetworkId,
+        /// The name under which the component type was registered.
+        component: String,
+    },
+}
+
+/// Network event types carrying `ReplicationMessage`s, besides the other events of the game.
+pub trait ReplicationEvent: From<ReplicationMessage> {
+    /// Returns the replication message carried by this event, if it's one.
+    fn replication(&self) -> Option<&ReplicationMessage>;
+}
+
+impl ReplicationEvent for ReplicationMessage {
+    fn replication(&self) -> Option<&ReplicationMessage> {
+        Some(self)
+    }
+}
+
+/// Resource of the server holding the entities replicated to each client.
+#[derive(Debug, Default)]
+pub struct ReplicationServer {
+    next_id: u64,
+    relevant: HashMap<Entity, HashSet<NetworkId>>,
+}
+
+impl ReplicationServer {
+    /// Returns the ids of the entities replicated to the client of the connection `connection`.
+    pub fn relevant(&self, connection: Entity) -> Option<&HashSet<NetworkId>> {
+        self.relevant.get(&connection)
+    }
+}
+
+/// Resource of a client mapping the replicated entities to its own entities.
+#[derive(Debug, Default)]
+pub struct ReplicationClient {
+    entities: HashMap<NetworkId, Entity>,
+    pending: HashMap<String, Vec<(Entity, Option<Vec<u8>>)>>,
+}
+
+impl ReplicationClient {
+    /// Returns the entity of the client replicating the entity `id` of the server.
+    pub fn entity(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id).cloned()
+    }
+}
+
+fn send<E>(connection: &mut NetConnection<E>, message: ReplicationMessage)
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+{
+    connection
+        .send_buffer
+        .single_write(NetEvent::Packet(NetPacket::reliable_ordered(
+            E::from(message),
+            Some(REPLICATION_STREAM),
+        )));
+}
+
+fn position(transform: &Transform) -> Vector3<f32> {
+    let translation = transform.translation();
+    Vector3::new(
+        translation.x.as_f32(),
+        translation.y.as_f32(),
+        translation.z.as_f32(),
+    )
+}
+
+/// Gives a `NetworkId` to the entities to `Replicate`, and spawns and despawns them on the
+/// clients following their `Interest`.
+#[derive(Debug)]
+pub struct ReplicationServerSystem<E> {
+    _marker: PhantomData<E>,
+}
+
+impl<E> Default for ReplicationServerSystem<E> {
+    fn default() -> Self {
+        ReplicationServerSystem {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, E> System<'a> for ReplicationServerSystem<E>
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Replicate>,
+        WriteStorage<'a, NetworkId>,
+        ReadStorage<'a, Interest>,
+        ReadStorage<'a, Transform>,
+        WriteStorage<'a, NetConnection<E>>,
+        Write<'a, ReplicationServer>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, replicates, mut ids, interests, transforms, mut connections, mut server) =
+            data;
+
+        let new = (&*entities, &replicates, !&ids)
+            .join()
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        for entity in new {
+            let id = NetworkId(server.next_id);
+            server.next_id += 1;
+            if let Err(e) = ids.insert(entity, id) {
+                error!("Failed to insert the `NetworkId` of {:?}: {}", entity, e);
+            }
+        }
+
+        let replicated = (&*entities, &replicates, &ids)
+            .join()
+            .map(|(entity, _, id)| (*id, transforms.get(entity).map(position)))
+            .collect::<Vec<_>>();
+
+        let mut relevant = HashMap::new();
+        for (connection_entity, connection) in (&*entities, &mut connections).join() {
+            let interest = interests.get(connection_entity).map(|interest| {
+                let focus = transforms.get(interest.focus).map(position);
+                (focus, interest.radius)
+            });
+            let now = replicated
+                .iter()
+                .filter(|&&(_, position)| match (interest, position) {
+                    (Some((Some(focus), radius)), Some(position)) => {
+                        (position - focus).norm() <= radius
+                    }
+                    (Some((None, _)), Some(_)) => false,
+                    _ => true,
+                })
+                .map(|&(id, _)| id)
+                .collect::<HashSet<_>>();
+
+            let before = server
+                .relevant
+                .remove(&connection_entity)
+                .unwrap_or_default();
+            let mut spawned = now.difference(&before).cloned().collect::<Vec<_>>();
+            spawned.sort();
+            for id in spawned {
+                send(connection, ReplicationMessage::Spawn(id));
+            }
+            let mut despawned = before.difference(&now).cloned().collect::<Vec<_>>();
+            despawned.sort();
+            for id in despawned {
+                send(connection, ReplicationMessage::Despawn(id));
+            }
+            relevant.insert(connection_entity, now);
+        }
+        server.relevant = relevant;
+    }
+}
+
+/// Sends the changes of the components `C` of the replicated entities to the clients.
+pub struct ReplicateComponentSystem<E, C> {
+    name: String,
+    sent: HashMap<(Entity, NetworkId), Vec<u8>>,
+    _marker: PhantomData<(E, C)>,
+}
+
+impl<E, C> ReplicateComponentSystem<E, C> {
+    /// Creates a system replicating the components `C` under `name`.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        ReplicateComponentSystem {
+            name: name.into(),
+            sent: HashMap::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, E, C> System<'a> for ReplicateComponentSystem<E, C>
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+    C: Replicated,
+    C::Storage: Default,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Replicate>,
+        ReadStorage<'a, NetworkId>,
+        ReadStorage<'a, C>,
+        WriteStorage<'a, NetConnection<E>>,
+        Read<'a, ReplicationServer>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, replicates, ids, components, mut connections, server) = data;
+        let replicated = (&*entities, &replicates, &ids)
+            .join()
+            .map(|(entity, _, id)| (*id, entity))
+            .collect::<HashMap<_, _>>();
+
+        for (connection_entity, connection) in (&*entities, &mut connections).join() {
+            let relevant = match server.relevant(connection_entity) {
+                Some(relevant) => relevant,
+                None => continue,
+            };
+            let mut relevant = relevant.iter().cloned().collect::<Vec<_>>();
+            relevant.sort();
+            for id in relevant {
+                let key = (connection_entity, id);
+                let component = replicated
+                    .get(&id)
+                    .and_then(|&entity| components.get(entity));
+                match component {
+                    Some(component) => {
+                        let data = match serialize(component) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                error!("Failed to serialize a `{}` component: {}", self.name, e);
+                                continue;
+                            }
+                        };
+                        if self.sent.get(&key) != Some(&data) {
+                            send(
+                                connection,
+                                ReplicationMessage::Update {
+                                    id,
+                                    component: self.name.clone(),
+                                    data: data.clone(),
+                                },
+                            );
+                            self.sent.insert(key, data);
+                        }
+                    }
+                    None => {
+                        if self.sent.remove(&key).is_some() {
+                            send(
+                                connection,
+                                ReplicationMessage::Remove {
+                                    id,
+                                    component: self.name.clone(),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        self.sent.retain(|&(connection, id), _| {
+            server
+                .relevant(connection)
+                .map_or(false, |relevant| relevant.contains(&id))
+        });
+    }
+}
+
+/// Creates and deletes the entities replicated from the server, and queues the changes of their
+/// components for the `ApplyReplicatedSystem`s.
+///
+/// This system must run before the `NetSocketSystem`, so it registers its readers on the new
+/// connections before their first events are received.
+pub struct ReplicationClientSystem<E: 'static> {
+    readers: HashMap<Entity, ReaderId<NetEvent<E>>>,
+}
+
+impl<E> Default for ReplicationClientSystem<E> {
+    fn default() -> Self {
+        ReplicationClientSystem {
+            readers: HashMap::new(),
+        }
+    }
+}
+
+impl<'a, E> System<'a> for ReplicationClientSystem<E>
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, NetConnection<E>>,
+        WriteStorage<'a, NetworkId>,
+        Write<'a, ReplicationClient>,
+    );
+
+    fn run(&mut self, (entities, mut connections, mut ids, mut client): Self::SystemData) {
+        let mut messages = Vec::new();
+        for (connection_entity, connection) in (&*entities, &mut connections).join() {
+            let reader = self
+                .readers
+                .entry(connection_entity)
+                .or_insert_with(|| connection.receive_buffer.register_reader());
+            for event in connection.receive_buffer.read(reader) {
+                if let NetEvent::Packet(ref packet) = *event {
+                    if let Some(message) = packet.content().replication() {
+                        messages.push(message.clone());
+                    }
+                }
+            }
+        }
+        self.readers
+            .retain(|&connection, _| connections.contains(connection));
+
+        let client = &mut *client;
+        for message in messages {
+            match message {
+                ReplicationMessage::Spawn(id) => {
+                    if !client.entities.contains_key(&id) {
+                        let entity = entities.create();
+                        if let Err(e) = ids.insert(entity, id) {
+                            error!("Failed to insert the `NetworkId` of {:?}: {}", entity, e);
+                        }
+                        client.entities.insert(id, entity);
+                    }
+                }
+                ReplicationMessage::Despawn(id) => {
+                    if let Some(entity) = client.entities.remove(&id) {
+                        if let Err(e) = entities.delete(entity) {
+                            error!("Failed to delete a replicated entity: {}", e);
+                        }
+                    }
+                }
+                ReplicationMessage::Update {
+                    id,
+                    component,
+                    data,
+                } => {
+                    if let Some(&entity) = client.entities.get(&id) {
+                        client
+                            .pending
+                            .entry(component)
+                            .or_insert_with(Vec::new)
+                            .push((entity, Some(data)));
+                    }
+                }
+                ReplicationMessage::Remove { id, component } => {
+                    if let Some(&entity) = client.entities.get(&id) {
+                        client
+                            .pending
+                            .entry(component)
+                            .or_insert_with(Vec::new)
+                            .push((entity, None));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Applies the changes of the replicated components `C` received by the
+/// `ReplicationClientSystem`.
+pub struct ApplyReplicatedSystem<C> {
+    name: String,
+    _marker: PhantomData<C>,
+}
+
+impl<C> ApplyReplicatedSystem<C> {
+    /// Creates a system applying the components `C` replicated under `name`.
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        ApplyReplicatedSystem {
+            name: name.into(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, C> System<'a> for ApplyReplicatedSystem<C>
+where
+    C: Replicated,
+    C::Storage: Default,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, C>,
+        Write<'a, ReplicationClient>,
+    );
+
+    fn run(&mut self, (entities, mut components, mut client): Self::SystemData) {
+        let changes = match client.pending.remove(&self.name) {
+            Some(changes) => changes,
+            None => return,
+        };
+        for (entity, data) in changes {
+            if !entities.is_alive(entity) {
+                continue;
+            }
+            match data {
+                Some(data) => match deserialize::<C>(&data) {
+                    Ok(component) => {
+                        if let Err(e) = components.insert(entity, component) {
+                            error!("Failed to insert a `{}` component: {}", self.name, e);
+                        }
+                    }
+                    Err(e) => error!("Failed to deserialize a `{}` component: {}", self.name, e),
+                },
+                None => {
+                    components.remove(entity);
+                }
+            }
+        }
+    }
+}
+
+type AddSystem = Box<dyn FnOnce(&mut DispatcherBuilder<'_, '_>, bool)>;
+
+/// Adds the systems replicating the registered components, on the server or on a client.
+///
+/// On a client, this bundle must be added before the `NetworkBundle`.
+///
+/// ```rust,ignore
+/// let replication = ReplicationBundle::<ReplicationMessage>::server()
+///     .with_component::<Health>("health")
+///     .with_component::<Transform>("transform");
+/// ```
+pub struct ReplicationBundle<E> {
+    server: bool,
+    components: Vec<AddSystem>,
+    _marker: PhantomData<E>,
+}
+
+impl<E> ReplicationBundle<E>
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+{
+    /// Creates the bundle of a server, sending its replicated entities.
+    pub fn server() -> Self {
+        ReplicationBundle {
+            server: true,
+            components: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates the bundle of a client, receiving the replicated entities.
+    pub fn client() -> Self {
+        ReplicationBundle {
+            server: false,
+            components: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replicates the components `C` under `name`, which must be the same on the server and
+    /// the clients.
+    pub fn with_component<C>(mut self, name: &str) -> Self
+    where
+        C: Replicated,
+        C::Storage: Default,
+    {
+        let name = name.to_string();
+        self.components.push(Box::new(
+            move |builder: &mut DispatcherBuilder<'_, '_>, server: bool| {
+                if server {
+                    builder.add(
+                        ReplicateComponentSystem::<E, C>::new(name.clone()),
+                        &format!("replicate_{}", name),
+                        &["replication_server"],
+                    );
+                } else {
+                    builder.add(
+                        ApplyReplicatedSystem::<C>::new(name.clone()),
+                        &format!("apply_replicated_{}", name),
+                        &["replication_client"],
+                    );
+                }
+            },
+        ));
+        self
+    }
+}
+
+impl<'a, 'b, E> SystemBundle<'a, 'b> for ReplicationBundle<E>
+where
+    E: ReplicationEvent + Send + Sync + 'static,
+{
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        if self.server {
+            builder.add(
+                ReplicationServerSystem::<E>::default(),
+                "replication_server",
+                &[],
+            );
+        } else {
+            builder.add(
+                ReplicationClientSystem::<E>::default(),
+                "replication_client",
+                &[],
+            );
+        }
+        for add in self.components {
+            add(builder, self.server);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use amethyst_core::{
+        ecs::{
+            prelude::{Builder, Component, DenseVecStorage, Entity, RunNow, System, World},
+            shrev::ReaderId,
+        },
+        Transform,
+    };
+
+    use super::{
+        ApplyReplicatedSystem, Interest, NetworkId, Replicate, ReplicateComponentSystem,
+        Replicated, ReplicationClient, ReplicationClientSystem, ReplicationMessage,
+        ReplicationServerSystem,
+    };
+    use crate::{NetConnection, NetEvent};
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    impl Component for Health {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    impl Replicated for Health {}
+
+    type Connection = NetConnection<ReplicationMessage>;
+
+    struct Peer {
+        world: World,
+        connection: Entity,
+        systems: Vec<Box<dyn for<'a> RunNow<'a>>>,
+    }
+
+    impl Peer {
+        fn new(systems: Vec<Box<dyn for<'a> RunNow<'a>>>) -> Self {
+            let mut world = World::new();
+            world.register::<Transform>();
+            world.register::<Interest>();
+            world.register::<Replicate>();
+            world.register::<Health>();
+            let mut systems = systems;
+            for system in &mut systems {
+                system.setup(&mut world.res);
+            }
+            let connection = world
+                .create_entity()
+                .with(Connection::new("127.0.0.1:21400".parse().unwrap()))
+                .build();
+            Peer {
+                world,
+                connection,
+                systems,
+            }
+        }
+
+        fn run(&mut self) {
+            for system in &mut self.systems {
+                system.run_now(&self.world.res);
+            }
+            self.world.maintain();
+        }
+    }
+
+    fn boxed<S: for<'a> System<'a> + Send + 'static>(system: S) -> Box<dyn for<'a> RunNow<'a>> {
+        Box::new(system)
+    }
+
+    fn transfer(server: &Peer, reader: &mut ReaderId<NetEvent<ReplicationMessage>>, client: &Peer) {
+        let server_connections = server.world.read_storage::<Connection>();
+        let mut client_connections = client.world.write_storage::<Connection>();
+        let client_connection = client_connections.get_mut(client.connection).unwrap();
+        for event in server_connections
+            .get(server.connection)
+            .unwrap()
+            .send_buffer
+            .read(reader)
+        {
+            client_connection.receive_buffer.single_write(event.clone());
+        }
+    }
+
+    fn replicated_health(client: &Peer) -> Vec<Health> {
+        use amethyst_core::ecs::prelude::Join;
+        let ids = client.world.read_storage::<NetworkId>();
+        let healths = client.world.read_storage::<Health>();
+        (&ids, &healths)
+            .join()
+            .map(|(_, health)| health.clone())
+            .collect()
+    }
+
+    #[test]
+    fn replicates_entities_within_interest() {
+        let mut server = Peer::new(vec![
+            boxed(ReplicationServerSystem::<ReplicationMessage>::default()),
+            boxed(ReplicateComponentSystem::<ReplicationMessage, Health>::new(
+                "health",
+            )),
+        ]);
+        let mut client = Peer::new(vec![
+            boxed(ReplicationClientSystem::<ReplicationMessage>::default()),
+            boxed(ApplyReplicatedSystem::<Health>::new("health")),
+        ]);
+        let mut reader = server
+            .world
+            .write_storage::<Connection>()
+            .get_mut(server.connection)
+            .unwrap()
+            .send_buffer
+            .register_reader();
+
+        let focus = server
+            .world
+            .create_entity()
+            .with(Transform::default())
+            .build();
+        server
+            .world
+            .write_storage::<Interest>()
+            .insert(
+                server.connection,
+                Interest {
+                    focus,
+                    radius: 10.0,
+                },
+            )
+            .unwrap();
+        let mut far = Transform::default();
+        far.set_translation_x(20.0);
+        let near = server
+            .world
+            .create_entity()
+            .with(Replicate)
+            .with(Health(10))
+            .with(Transform::default())
+            .build();
+        server
+            .world
+            .create_entity()
+            .with(Replicate)
+            .with(Health(3))
+            .with(far)
+            .build();
+
+        // Registers the reader of the client, then replicates.
+        client.run();
+        server.run();
+        transfer(&server, &mut reader, &client);
+        client.run();
+        assert_eq!(replicated_health(&client), vec![Health(10)]);
+
+        server
+            .world
+            .write_storage::<Health>()
+            .insert(near, Health(4))
+            .unwrap();
+        server.run();
+        transfer(&server, &mut reader, &client);
+        client.run();
+        assert_eq!(replicated_health(&client), vec![Health(4)]);
+
+        server
+            .world
+            .write_storage::<Transform>()
+            .get_mut(near)
+            .unwrap()
+            .set_translation_x(15.0);
+        server.run();
+        transfer(&server, &mut reader, &client);
+        client.run();
+        assert_eq!(replicated_health(&client), vec![]);
+        assert_eq!(
+            client
+                .world
+                .read_resource::<ReplicationClient>()
+                .entities
+                .len(),
+            0
+        );
+    }
+}
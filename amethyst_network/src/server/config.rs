@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 #[derive(Clone, Debug)]
 /// The configuration used for the networking system.
@@ -13,6 +13,9 @@ pub struct ServerConfig {
     /// Make this property 'false' you prevent this behaviour.
     /// This property is enabled by default.
     pub create_net_connection_on_connect: bool,
+    /// Time between the pings sent to each connection to measure its `NetStats`.
+    /// This value is by default 1 second.
+    pub ping_interval: Duration,
 }
 
 impl ServerConfig {
@@ -26,6 +29,7 @@ impl ServerConfig {
             udp_socket_addr: ip,
             max_throughput,
             create_net_connection_on_connect,
+            ping_interval: Duration::from_secs(1),
         }
     }
 }
@@ -37,6 +41,7 @@ impl Default for ServerConfig {
             udp_socket_addr: "0.0.0.0:0".parse().unwrap(),
             max_throughput: 5000,
             create_net_connection_on_connect: true,
+            ping_interval: Duration::from_secs(1),
         }
     }
 }
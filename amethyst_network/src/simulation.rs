@@ -0,0 +1,238 @@
+//! Artificial network conditions, to test how a game behaves over a bad connection.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use rand::{thread_rng, Rng};
+
+/// Resource degrading the packets received by the `NetSocketSystem`, which only simulates the
+/// network conditions while it is present in the world.
+///
+/// Every packet is delayed by `latency`, plus or minus a random `jitter`. Unreliable packets are
+/// also dropped with a probability of `packet_loss` and received twice with a probability of
+/// `duplication`. Reliable packets are never dropped nor duplicated as they were already
+/// acknowledged by the transport, and are never delivered before a reliable packet received
+/// earlier from the same address.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkSimulation {
+    /// Delay added to every received packet.
+    pub latency: Duration,
+    /// Maximum random variation of the latency.
+    pub jitter: Duration,
+    /// Probability, from 0 to 1, of dropping an unreliable packet.
+    pub packet_loss: f32,
+    /// Probability, from 0 to 1, of receiving an unreliable packet twice.
+    pub duplication: f32,
+}
+
+impl Default for NetworkSimulation {
+    fn default() -> Self {
+        NetworkSimulation {
+            latency: Duration::from_millis(0),
+            jitter: Duration::from_millis(0),
+            packet_loss: 0.0,
+            duplication: 0.0,
+        }
+    }
+}
+
+impl NetworkSimulation {
+    /// Sets the delay added to every received packet.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Sets the maximum random variation of the latency.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Sets the probability of dropping an unreliable packet.
+    pub fn with_packet_loss(mut self, packet_loss: f32) -> Self {
+        self.packet_loss = packet_loss;
+        self
+    }
+
+    /// Sets the probability of receiving an unreliable packet twice.
+    pub fn with_duplication(mut self, duplication: f32) -> Self {
+        self.duplication = duplication;
+        self
+    }
+
+    fn delay<R: Rng>(&self, rng: &mut R) -> Duration {
+        let jitter = nanos(self.jitter);
+        if jitter == 0 {
+            return self.latency;
+        }
+        let offset = Duration::from_nanos(rng.gen_range(0, 2 * jitter + 1));
+        (self.latency + offset)
+            .checked_sub(self.jitter)
+            .unwrap_or_else(|| Duration::from_millis(0))
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos())
+}
+
+struct Delayed<T> {
+    deliver_at: Instant,
+    // breaks the ties between items delivered at the same instant.
+    sequence: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Delayed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Delayed<T> {}
+
+impl<T> PartialOrd for Delayed<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Delayed<T> {
+    // Reversed, so the `BinaryHeap` pops the earliest item first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.deliver_at, other.sequence).cmp(&(self.deliver_at, self.sequence))
+    }
+}
+
+/// Holds the received items until the simulated network delivers them.
+pub(crate) struct SimulationQueue<T> {
+    queue: BinaryHeap<Delayed<T>>,
+    // when the last reliable item from each address will be delivered.
+    last_reliable: HashMap<SocketAddr, Instant>,
+    next_sequence: u64,
+}
+
+impl<T> Default for SimulationQueue<T> {
+    fn default() -> Self {
+        SimulationQueue {
+            queue: BinaryHeap::new(),
+            last_reliable: HashMap::new(),
+            next_sequence: 0,
+        }
+    }
+}
+
+impl<T: Clone> SimulationQueue<T> {
+    /// Queues an item received from `addr` at `now`, following the `simulation` conditions.
+    pub(crate) fn push(
+        &mut self,
+        simulation: &NetworkSimulation,
+        addr: SocketAddr,
+        item: T,
+        reliable: bool,
+        now: Instant,
+    ) {
+        let mut rng = thread_rng();
+        let copies = if reliable {
+            1
+        } else if rng.gen::<f32>() < simulation.packet_loss {
+            0
+        } else if rng.gen::<f32>() < simulation.duplication {
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let mut deliver_at = now + simulation.delay(&mut rng);
+            if reliable {
+                let last = self.last_reliable.entry(addr).or_insert(deliver_at);
+                if *last > deliver_at {
+                    deliver_at = *last;
+                }
+                *last = deliver_at;
+            }
+            self.queue.push(Delayed {
+                deliver_at,
+                sequence: self.next_sequence,
+                item: item.clone(),
+            });
+            self.next_sequence += 1;
+        }
+    }
+}
+
+impl<T> SimulationQueue<T> {
+    /// Removes the items which are delivered by `now`, in order of delivery.
+    pub(crate) fn pop_ready(&mut self, now: Instant) -> Vec<T> {
+        let mut ready = Vec::new();
+        while self
+            .queue
+            .peek()
+            .map_or(false, |next| next.deliver_at <= now)
+        {
+            ready.push(self.queue.pop().expect("Unreachable: just peeked").item);
+        }
+        self.last_reliable.retain(|_, last| *last > now);
+        ready
+    }
+
+    /// Removes all the items, in order of delivery.
+    pub(crate) fn drain(&mut self) -> Vec<T> {
+        let mut items = Vec::with_capacity(self.queue.len());
+        while let Some(next) = self.queue.pop() {
+            items.push(next.item);
+        }
+        self.last_reliable.clear();
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{NetworkSimulation, SimulationQueue};
+
+    #[test]
+    fn degrades_unreliable_packets_only() {
+        let addr = "127.0.0.1:21700".parse().unwrap();
+        let now = Instant::now();
+        let mut queue = SimulationQueue::default();
+
+        let lossy = NetworkSimulation::default()
+            .with_latency(Duration::from_millis(50))
+            .with_packet_loss(1.0);
+        queue.push(&lossy, addr, 1, false, now);
+        queue.push(&lossy, addr, 2, true, now);
+        assert!(queue.pop_ready(now).is_empty());
+        assert_eq!(queue.pop_ready(now + Duration::from_millis(50)), vec![2]);
+
+        let duplicating = NetworkSimulation::default().with_duplication(1.0);
+        queue.push(&duplicating, addr, 3, false, now);
+        queue.push(&duplicating, addr, 4, true, now);
+        assert_eq!(queue.pop_ready(now), vec![3, 3, 4]);
+    }
+
+    #[test]
+    fn keeps_reliable_packets_in_order() {
+        let addr = "127.0.0.1:21700".parse().unwrap();
+        let now = Instant::now();
+        let mut queue = SimulationQueue::default();
+        let simulation = NetworkSimulation::default()
+            .with_latency(Duration::from_millis(100))
+            .with_jitter(Duration::from_millis(100));
+
+        for i in 0..20 {
+            queue.push(&simulation, addr, i, true, now);
+        }
+        let mut received = queue.pop_ready(now + Duration::from_millis(100));
+        received.extend(queue.pop_ready(now + Duration::from_millis(200)));
+        assert_eq!(received, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,154 @@
+//! Round trip time and packet loss of the connections.
+
+use std::{
+    collections::{hash_map, HashMap, VecDeque},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// Time after which a ping without answer is counted as lost.
+pub(crate) const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of pings over which the packet loss is measured.
+const LOSS_WINDOW: usize = 32;
+
+/// Resource holding the statistics of each connection, measured by the `NetSocketSystem`.
+///
+/// The round trip time and packet loss are measured by pinging the remote of each
+/// `NetConnection` every `ServerConfig::ping_interval`.
+#[derive(Debug, Default)]
+pub struct NetStats {
+    connections: HashMap<SocketAddr, ConnectionStats>,
+}
+
+impl NetStats {
+    /// Returns the statistics of the connection to `addr`.
+    pub fn get(&self, addr: &SocketAddr) -> Option<&ConnectionStats> {
+        self.connections.get(addr)
+    }
+
+    /// Iterates over the statistics of all connections.
+    pub fn iter(&self) -> hash_map::Iter<'_, SocketAddr, ConnectionStats> {
+        self.connections.iter()
+    }
+
+    pub(crate) fn entry(&mut self, addr: SocketAddr) -> &mut ConnectionStats {
+        self.connections
+            .entry(addr)
+            .or_insert_with(ConnectionStats::default)
+    }
+
+    pub(crate) fn remove(&mut self, addr: &SocketAddr) {
+        self.connections.remove(addr);
+    }
+}
+
+/// Statistics of a connection.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionStats {
+    rtt: Option<Duration>,
+    next_ping: u16,
+    pending: VecDeque<(u16, Instant)>,
+    lost: VecDeque<bool>,
+    sent_messages: u64,
+    received_messages: u64,
+}
+
+impl ConnectionStats {
+    /// Returns the smoothed round trip time, `None` until a ping was answered.
+    pub fn rtt(&self) -> Option<Duration> {
+        self.rtt
+    }
+
+    /// Returns the fraction of the last pings which weren't answered, from 0 to 1.
+    pub fn packet_loss(&self) -> f32 {
+        if self.lost.is_empty() {
+            return 0.0;
+        }
+        self.lost.iter().filter(|&&lost| lost).count() as f32 / self.lost.len() as f32
+    }
+
+    /// Returns the number of events sent to the connection.
+    pub fn sent_messages(&self) -> u64 {
+        self.sent_messages
+    }
+
+    /// Returns the number of events received from the connection.
+    pub fn received_messages(&self) -> u64 {
+        self.received_messages
+    }
+
+    pub(crate) fn add_sent(&mut self, count: u64) {
+        self.sent_messages += count;
+    }
+
+    pub(crate) fn add_received(&mut self) {
+        self.received_messages += 1;
+    }
+
+    /// Records a ping sent at `now`, returning its sequence.
+    pub(crate) fn ping_sent(&mut self, now: Instant) -> u16 {
+        while let Some(&(_, sent)) = self.pending.front() {
+            if now.duration_since(sent) < PING_TIMEOUT {
+                break;
+            }
+            self.pending.pop_front();
+            self.record_loss(true);
+        }
+        let sequence = self.next_ping;
+        self.next_ping = self.next_ping.wrapping_add(1);
+        self.pending.push_back((sequence, now));
+        sequence
+    }
+
+    /// Records the answer to the ping `sequence`, received at `now`.
+    pub(crate) fn pong_received(&mut self, sequence: u16, now: Instant) {
+        let position = match self.pending.iter().position(|&(s, _)| s == sequence) {
+            Some(position) => position,
+            None => return,
+        };
+        let (_, sent) = self
+            .pending
+            .remove(position)
+            .expect("Unreachable: just found");
+        let sample = now.duration_since(sent);
+        self.rtt = Some(match self.rtt {
+            Some(rtt) => rtt * 7 / 8 + sample / 8,
+            None => sample,
+        });
+        self.record_loss(false);
+    }
+
+    fn record_loss(&mut self, lost: bool) {
+        if self.lost.len() == LOSS_WINDOW {
+            self.lost.pop_front();
+        }
+        self.lost.push_back(lost);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ConnectionStats, PING_TIMEOUT};
+
+    #[test]
+    fn measures_rtt_and_loss() {
+        let mut stats = ConnectionStats::default();
+        let start = Instant::now();
+        assert_eq!(stats.rtt(), None);
+
+        let first = stats.ping_sent(start);
+        let second = stats.ping_sent(start);
+        stats.pong_received(first, start + Duration::from_millis(80));
+        assert_eq!(stats.rtt(), Some(Duration::from_millis(80)));
+        assert_eq!(stats.packet_loss(), 0.0);
+
+        // The second ping is lost once it times out.
+        stats.ping_sent(start + PING_TIMEOUT);
+        assert_eq!(stats.packet_loss(), 0.5);
+        stats.pong_received(second, start + PING_TIMEOUT);
+        assert_eq!(stats.packet_loss(), 0.5);
+    }
+}
@@ -86,6 +86,66 @@ mod test {
         assert_eq!(comp.receive_buffer.read(&mut rcv).count(), 100);
     }
 
+    #[test]
+    fn large_packet_fragmented() {
+        let server_addr: SocketAddr = "127.0.0.1:21208".parse().unwrap();
+        let client_addr: SocketAddr = "127.0.0.1:21210".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) =
+            build(client_addr, server_addr);
+
+        let mut conn_to_server = NetConnection::<String>::new(server_addr);
+        let mut conn_to_client = NetConnection::<String>::new(client_addr);
+
+        let packet = NetEvent::Packet(NetPacket::new(
+            "Large Message".repeat(500),
+            Channel::ReliableOrdered(None),
+        ));
+
+        conn_to_server.send_buffer.single_write(packet.clone());
+        world_cl.create_entity().with(conn_to_server).build();
+
+        let mut rcv = conn_to_client.receive_buffer.register_reader();
+        let conn_to_client_entity = world_sv.create_entity().with(conn_to_client).build();
+
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&mut world_sv.res);
+
+        let storage = world_sv.read_storage::<NetConnection<String>>();
+        let comp = storage.get(conn_to_client_entity).unwrap();
+        assert_eq!(comp.receive_buffer.read(&mut rcv).next(), Some(&packet));
+    }
+
+    #[test]
+    fn measures_round_trip_time() {
+        let server_addr: SocketAddr = "127.0.0.1:21212".parse().unwrap();
+        let client_addr: SocketAddr = "127.0.0.1:21214".parse().unwrap();
+
+        let (mut world_cl, mut cl_dispatch, mut world_sv, mut sv_dispatch) =
+            build(client_addr, server_addr);
+
+        world_cl
+            .create_entity()
+            .with(NetConnection::<String>::new(server_addr))
+            .build();
+        world_sv
+            .create_entity()
+            .with(NetConnection::<String>::new(client_addr))
+            .build();
+
+        cl_dispatch.dispatch(&mut world_cl.res);
+        sleep(Duration::from_millis(200));
+        sv_dispatch.dispatch(&mut world_sv.res);
+        sleep(Duration::from_millis(200));
+        cl_dispatch.dispatch(&mut world_cl.res);
+
+        let stats = world_cl.read_resource::<NetStats>();
+        let stats = stats.get(&server_addr).unwrap();
+        assert!(stats.rtt().is_some());
+        assert_eq!(stats.packet_loss(), 0.0);
+    }
+
     fn build<'a, 'b>(
         client_addr: SocketAddr,
         server_addr: SocketAddr,
@@ -98,6 +158,7 @@ mod test {
             udp_socket_addr: client_addr,
             max_throughput: 10000,
             create_net_connection_on_connect: false,
+            ping_interval: Duration::from_secs(1),
         };
 
         // server config
@@ -105,6 +166,7 @@ mod test {
             udp_socket_addr: server_addr,
             max_throughput: 10000,
             create_net_connection_on_connect: false,
+            ping_interval: Duration::from_secs(1),
         };
 
         let mut cl_dispatch = DispatcherBuilder::new()
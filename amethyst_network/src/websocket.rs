@@ -0,0 +1,509 @@
+//! WebSocket transport, sending the `NetConnection` events over TCP.
+//!
+//! The `WebSocketSystem` drives the same `NetConnection` components as the `NetSocketSystem`, so
+//! the rest of the game doesn't depend on the transport. Messages are binary WebSocket frames
+//! holding the same payload as the UDP datagrams, so clients written against the browser
+//! WebSocket API can talk to a native server. WebSockets are always reliable and ordered: the
+//! channel of the sent packets is ignored, and received packets are reliable ordered.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::ErrorKind,
+    marker::PhantomData,
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use bincode::{deserialize, serialize};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use log::{error, info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use tungstenite::{handshake::client::Request, Message, WebSocket};
+use url::Url;
+
+use amethyst_core::{
+    bundle::SystemBundle,
+    ecs::{Entities, Join, Resources, System, SystemData, WriteStorage},
+    shred::DispatcherBuilder,
+};
+use amethyst_error::{Error, ResultExt};
+
+use crate::{error::Result, wire::WireMessage, ConnectionState, NetConnection, NetEvent};
+
+/// Time the thread of a peer waits for a message before sending the queued ones.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The configuration of a `WebSocketSystem`.
+#[derive(Clone, Debug)]
+pub struct WebSocketConfig {
+    /// Address at which the server accepts WebSocket connections, `None` for clients.
+    pub listen_addr: Option<SocketAddr>,
+    /// Maximal number of messages handled in a frame.
+    /// This value is by default 5000.
+    pub max_throughput: u16,
+    /// If enabled a `NetConnection` will be automatically added to the world when a client
+    /// connects.
+    /// This property is enabled by default.
+    pub create_net_connection_on_connect: bool,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        WebSocketConfig {
+            listen_addr: None,
+            max_throughput: 5000,
+            create_net_connection_on_connect: true,
+        }
+    }
+}
+
+enum PeerEvent {
+    Connected(SocketAddr, Sender<Vec<u8>>),
+    Message(SocketAddr, Vec<u8>),
+    Disconnected(SocketAddr),
+    ConnectFailed(SocketAddr),
+}
+
+/// The System sending and receiving the events of the `NetConnection`s over WebSockets.
+///
+/// With a `listen_addr` it accepts the connections of clients, and never opens connections
+/// itself. Otherwise connections to servers are opened for the `NetConnection`s added to the
+/// world, their events are queued until the connection is established. `NetConnection`s whose
+/// connection failed or was closed are `ConnectionState::Disconnected`.
+pub struct WebSocketSystem<E: 'static> {
+    config: WebSocketConfig,
+    events: Receiver<PeerEvent>,
+    event_sender: Sender<PeerEvent>,
+    peers: HashMap<SocketAddr, Sender<Vec<u8>>>,
+    connecting: HashSet<SocketAddr>,
+    queued: HashMap<SocketAddr, Vec<Vec<u8>>>,
+    _marker: PhantomData<E>,
+}
+
+impl<E> WebSocketSystem<E>
+where
+    E: Serialize + Send + 'static,
+{
+    /// Creates a `WebSocketSystem`, listening on the `listen_addr` of the config if any.
+    pub fn new(config: WebSocketConfig) -> Result<Self> {
+        let (event_sender, events) = crossbeam_channel::unbounded();
+        if let Some(addr) = config.listen_addr {
+            let listener = TcpListener::bind(addr)?;
+            let sender = event_sender.clone();
+            thread::spawn(move || accept(listener, sender));
+        }
+
+        Ok(WebSocketSystem {
+            config,
+            events,
+            event_sender,
+            peers: HashMap::new(),
+            connecting: HashSet::new(),
+            queued: HashMap::new(),
+            _marker: PhantomData,
+        })
+    }
+
+    fn send(&mut self, addr: SocketAddr, message: Vec<u8>) {
+        if let Some(peer) = self.peers.get(&addr) {
+            if peer.send(message).is_err() {
+                warn!("Dropped a message to {}: disconnected", addr);
+            }
+            return;
+        }
+        if self.config.listen_addr.is_some() {
+            warn!("Dropped a message to {}: not connected", addr);
+            return;
+        }
+        self.queued
+            .entry(addr)
+            .or_insert_with(Vec::new)
+            .push(message);
+        self.open(addr);
+    }
+
+    // Opens a connection to `addr` if there is none yet, servers only accept connections.
+    fn open(&mut self, addr: SocketAddr) {
+        if self.config.listen_addr.is_some() || self.peers.contains_key(&addr) {
+            return;
+        }
+        if self.connecting.insert(addr) {
+            let sender = self.event_sender.clone();
+            thread::spawn(move || connect(addr, sender));
+        }
+    }
+}
+
+fn accept(listener: TcpListener, events: Sender<PeerEvent>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("Failed to accept a WebSocket connection: {}", e);
+                continue;
+            }
+        };
+        let events = events.clone();
+        thread::spawn(move || {
+            let addr = match stream.peer_addr() {
+                Ok(addr) => addr,
+                Err(e) => {
+                    error!("Failed to get the address of a WebSocket peer: {}", e);
+                    return;
+                }
+            };
+            match tungstenite::accept(stream) {
+                Ok(socket) => run_peer(addr, socket, events),
+                Err(e) => error!("WebSocket handshake with {} failed: {}", addr, e),
+            }
+        });
+    }
+}
+
+fn connect(addr: SocketAddr, events: Sender<PeerEvent>) {
+    let socket = TcpStream::connect(addr)
+        .map_err(|e| e.to_string())
+        .and_then(|stream| {
+            let url = Url::parse(&format!("ws://{}/", addr)).map_err(|e| e.to_string())?;
+            tungstenite::client(Request::from(url), stream)
+                .map(|(socket, _response)| socket)
+                .map_err(|e| e.to_string())
+        });
+    match socket {
+        Ok(socket) => run_peer(addr, socket, events),
+        Err(e) => {
+            error!("Failed to open a WebSocket connection to {}: {}", addr, e);
+            let _ = events.send(PeerEvent::ConnectFailed(addr));
+        }
+    }
+}
+
+// Forwards the messages of a peer until the connection or the system is closed.
+fn run_peer(addr: SocketAddr, mut socket: WebSocket<TcpStream>, events: Sender<PeerEvent>) {
+    if let Err(e) = socket.get_mut().set_read_timeout(Some(POLL_INTERVAL)) {
+        error!("Failed to configure the WebSocket of {}: {}", addr, e);
+        return;
+    }
+    let (sender, outgoing) = crossbeam_channel::unbounded();
+    if events.send(PeerEvent::Connected(addr, sender)).is_err() {
+        return;
+    }
+    info!("WebSocket connection with {} established", addr);
+
+    'peer: loop {
+        match socket.read_message() {
+            Ok(Message::Binary(data)) => {
+                if events.send(PeerEvent::Message(addr, data)).is_err() {
+                    break;
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(ref e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {}
+            Err(e) => {
+                info!("WebSocket connection with {} closed: {}", addr, e);
+                break;
+            }
+        }
+
+        loop {
+            match outgoing.try_recv() {
+                Ok(data) => {
+                    if let Err(e) = socket.write_message(Message::Binary(data)) {
+                        error!("Failed to send a WebSocket message to {}: {}", addr, e);
+                        break 'peer;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.write_pending();
+                    break 'peer;
+                }
+            }
+        }
+    }
+
+    let _ = events.send(PeerEvent::Disconnected(addr));
+}
+
+impl<'a, E> System<'a> for WebSocketSystem<E>
+where
+    E: Send + Sync + Serialize + Clone + DeserializeOwned + PartialEq + 'static,
+{
+    type SystemData = (WriteStorage<'a, NetConnection<E>>, Entities<'a>);
+
+    fn run(&mut self, (mut net_connections, entities): Self::SystemData) {
+        let mut outgoing = Vec::new();
+        let mut opening = Vec::new();
+        for connection in (&mut net_connections).join() {
+            match connection.state {
+                ConnectionState::Connected | ConnectionState::Connecting => {
+                    if connection.state == ConnectionState::Connecting {
+                        opening.push(connection.target_addr);
+                    }
+                    for event in connection.send_buffer_early_read() {
+                        if let NetEvent::Packet(ref packet) = *event {
+                            match serialize(packet.content())
+                                .and_then(|payload| serialize(&WireMessage::Event(payload)))
+                            {
+                                Ok(message) => outgoing.push((connection.target_addr, message)),
+                                Err(e) => error!("Failed to serialize the event: {}", e),
+                            }
+                        }
+                    }
+                }
+                ConnectionState::Disconnected => {
+                    // Dropping the sender closes the connection.
+                    self.peers.remove(&connection.target_addr);
+                }
+            }
+        }
+        for addr in opening {
+            self.open(addr);
+        }
+        for (addr, message) in outgoing {
+            self.send(addr, message);
+        }
+
+        // this will prevent our system to be stuck in the iterator.
+        let events = self
+            .events
+            .try_iter()
+            .take(self.config.max_throughput as usize + 1)
+            .collect::<Vec<_>>();
+        for event in events {
+            match event {
+                PeerEvent::Connected(addr, sender) => {
+                    self.connecting.remove(&addr);
+                    for message in self.queued.remove(&addr).unwrap_or_default() {
+                        let _ = sender.send(message);
+                    }
+                    self.peers.insert(addr, sender);
+
+                    let mut existing = false;
+                    for connection in (&mut net_connections).join() {
+                        if connection.target_addr == addr {
+                            existing = true;
+                            connection.state = ConnectionState::Connected;
+                            connection
+                                .receive_buffer
+                                .single_write(NetEvent::Connected(addr));
+                        }
+                    }
+                    if !existing && self.config.create_net_connection_on_connect {
+                        let mut connection: NetConnection<E> = NetConnection::new(addr);
+                        connection.state = ConnectionState::Connected;
+                        connection
+                            .receive_buffer
+                            .single_write(NetEvent::Connected(addr));
+                        entities
+                            .build_entity()
+                            .with(connection, &mut net_connections)
+                            .build();
+                    }
+                }
+                PeerEvent::Message(addr, data) => match deserialize::<WireMessage>(&data) {
+                    Ok(WireMessage::Event(payload)) => {
+                        match NetEvent::<E>::from_payload(
+                            &payload,
+                            laminar::DeliveryGuarantee::Reliable,
+                            laminar::OrderingGuarantee::Ordered(None),
+                        ) {
+                            Ok(event) => {
+                                for connection in (&mut net_connections).join() {
+                                    if connection.target_addr == addr {
+                                        connection.receive_buffer.single_write(event.clone());
+                                    }
+                                }
+                            }
+                            Err(e) => error!(
+                                "Failed to deserialize an incoming network event: {} From source: {:?}",
+                                e, addr
+                            ),
+                        }
+                    }
+                    Ok(WireMessage::Ping(sequence)) => {
+                        if let Ok(pong) = serialize(&WireMessage::Pong(sequence)) {
+                            self.send(addr, pong);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!(
+                        "Failed to deserialize an incoming network message: {} From source: {:?}",
+                        e, addr
+                    ),
+                },
+                PeerEvent::Disconnected(addr) | PeerEvent::ConnectFailed(addr) => {
+                    self.connecting.remove(&addr);
+                    self.peers.remove(&addr);
+                    self.queued.remove(&addr);
+                    for connection in (&mut net_connections).join() {
+                        if connection.target_addr == addr {
+                            connection.state = ConnectionState::Disconnected;
+                            connection
+                                .receive_buffer
+                                .single_write(NetEvent::Disconnected(addr));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+    }
+}
+
+/// A convenience bundle adding a `WebSocketSystem`, in place of the `NetworkBundle`.
+pub struct WebSocketBundle<T> {
+    config: WebSocketConfig,
+    _data: PhantomData<T>,
+}
+
+impl<T> WebSocketBundle<T> {
+    /// Creates the bundle of a server accepting WebSocket connections on `listen_addr`.
+    pub fn server(listen_addr: SocketAddr) -> Self {
+        WebSocketBundle::from_config(WebSocketConfig {
+            listen_addr: Some(listen_addr),
+            ..Default::default()
+        })
+    }
+
+    /// Creates the bundle of a client, connecting to the targets of its `NetConnection`s.
+    pub fn client() -> Self {
+        WebSocketBundle::from_config(WebSocketConfig::default())
+    }
+
+    /// Construct a new `WebSocketBundle` with the specified configuration.
+    pub fn from_config(config: WebSocketConfig) -> Self {
+        WebSocketBundle {
+            config,
+            _data: PhantomData,
+        }
+    }
+}
+
+impl<'a, 'b, T> SystemBundle<'a, 'b> for WebSocketBundle<T>
+where
+    T: Send + Sync + PartialEq + Serialize + Clone + DeserializeOwned + 'static,
+{
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> std::result::Result<(), Error> {
+        let system = WebSocketSystem::<T>::new(self.config)
+            .with_context(|_| Error::from_string("Failed to open the WebSocket system."))?;
+        builder.add(system, "net_socket", &[]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, thread::sleep, time::Duration};
+
+    use amethyst_core::{
+        ecs::{Builder, Join, World},
+        shred::{Dispatcher, DispatcherBuilder},
+    };
+
+    use super::{WebSocketConfig, WebSocketSystem};
+    use crate::{ConnectionState, NetConnection, NetEvent, NetPacket};
+
+    fn dispatcher<'a, 'b>(world: &mut World, config: WebSocketConfig) -> Dispatcher<'a, 'b> {
+        let mut dispatcher = DispatcherBuilder::new()
+            .with(WebSocketSystem::<String>::new(config).unwrap(), "s", &[])
+            .build();
+        dispatcher.setup(&mut world.res);
+        dispatcher
+    }
+
+    #[test]
+    fn disconnects_when_connecting_fails() {
+        let server_addr: SocketAddr = "127.0.0.1:21501".parse().unwrap();
+        let mut world = World::new();
+        let mut dispatch = dispatcher(&mut world, WebSocketConfig::default());
+
+        let mut connection = NetConnection::<String>::new(server_addr);
+        let mut rcv = connection.receive_buffer.register_reader();
+        let connection = world.create_entity().with(connection).build();
+
+        dispatch.dispatch(&world.res);
+        sleep(Duration::from_millis(500));
+        dispatch.dispatch(&world.res);
+
+        let connections = world.read_storage::<NetConnection<String>>();
+        let connection = connections.get(connection).unwrap();
+        assert_eq!(connection.state, ConnectionState::Disconnected);
+        assert_eq!(
+            connection
+                .receive_buffer
+                .read(&mut rcv)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![NetEvent::Disconnected(server_addr)]
+        );
+    }
+
+    #[test]
+    fn exchanges_events() {
+        let server_addr: SocketAddr = "127.0.0.1:21500".parse().unwrap();
+        let mut world_sv = World::new();
+        let mut sv_dispatch = dispatcher(
+            &mut world_sv,
+            WebSocketConfig {
+                listen_addr: Some(server_addr),
+                ..Default::default()
+            },
+        );
+        let mut world_cl = World::new();
+        let mut cl_dispatch = dispatcher(&mut world_cl, WebSocketConfig::default());
+
+        let mut conn_to_server = NetConnection::<String>::new(server_addr);
+        let mut rcv = conn_to_server.receive_buffer.register_reader();
+        conn_to_server
+            .send_buffer
+            .single_write(NetEvent::Packet(NetPacket::reliable_ordered(
+                "Hello".to_string(),
+                None,
+            )));
+        let conn_to_server = world_cl.create_entity().with(conn_to_server).build();
+
+        cl_dispatch.dispatch(&world_cl.res);
+        sleep(Duration::from_millis(500));
+        sv_dispatch.dispatch(&world_sv.res);
+        cl_dispatch.dispatch(&world_cl.res);
+
+        {
+            let mut connections = world_sv.write_storage::<NetConnection<String>>();
+            let connection = (&mut connections).join().next().unwrap();
+            assert_eq!(connection.state, ConnectionState::Connected);
+            connection
+                .send_buffer
+                .single_write(NetEvent::Packet(NetPacket::reliable_ordered(
+                    "World".to_string(),
+                    None,
+                )));
+        }
+        sv_dispatch.dispatch(&world_sv.res);
+        sleep(Duration::from_millis(500));
+        cl_dispatch.dispatch(&world_cl.res);
+
+        let connections = world_cl.read_storage::<NetConnection<String>>();
+        let received = connections
+            .get(conn_to_server)
+            .unwrap()
+            .receive_buffer
+            .read(&mut rcv)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            received,
+            vec![
+                NetEvent::Connected(server_addr),
+                NetEvent::Packet(NetPacket::reliable_ordered("World".to_string(), None)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! The messages sent in the datagrams, and the fragmentation of the large ones.
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Largest payload sent in a single message, larger events are split in fragments.
+pub(crate) const MAX_FRAGMENT_SIZE: usize = 1024;
+
+/// Largest serialized event sent or reassembled from fragments.
+pub(crate) const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Number of fragments of the largest event.
+const MAX_FRAGMENTS: usize = (MAX_MESSAGE_SIZE + MAX_FRAGMENT_SIZE - 1) / MAX_FRAGMENT_SIZE;
+
+/// Number of incomplete events buffered for each address, the oldest being dropped first.
+const MAX_PARTIALS_PER_ADDR: usize = 16;
+
+/// Size of the fragments of incomplete events buffered for all the addresses.
+const MAX_BUFFERED_BYTES: usize = 8 * 1024 * 1024;
+
+/// Time after which the fragments of an incomplete message are dropped.
+pub(crate) const FRAGMENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The payload of every datagram sent by the `NetSocketSystem`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum WireMessage {
+    /// A serialized event.
+    Event(Vec<u8>),
+    /// A part of a serialized event.
+    Fragment(Fragment),
+    /// Asks the remote to answer with a `Pong` of the same sequence, to measure the round trip.
+    Ping(u16),
+    /// Answer to a `Ping`.
+    Pong(u16),
+}
+
+/// A part of a serialized event too large to be sent as a single message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Fragment {
+    /// Identifies the fragments of the same event.
+    pub(crate) id: u16,
+    /// Position of this fragment in the event.
+    pub(crate) index: u16,
+    /// Number of fragments of the event.
+    pub(crate) count: u16,
+    /// The bytes of this fragment.
+    pub(crate) data: Vec<u8>,
+}
+
+/// Splits a serialized event in fragments of at most `MAX_FRAGMENT_SIZE` bytes.
+///
+/// Returns `None` if the event is larger than `MAX_MESSAGE_SIZE`.
+pub(crate) fn split(id: u16, payload: &[u8]) -> Option<Vec<Fragment>> {
+    if payload.len() > MAX_MESSAGE_SIZE {
+        return None;
+    }
+    let chunks = payload.chunks(MAX_FRAGMENT_SIZE);
+    let count = chunks.len() as u16;
+    Some(
+        chunks
+            .enumerate()
+            .map(|(index, data)| Fragment {
+                id,
+                index: index as u16,
+                count,
+                data: data.to_vec(),
+            })
+            .collect(),
+    )
+}
+
+struct Partial {
+    parts: Vec<Option<Vec<u8>>>,
+    missing: usize,
+    bytes: usize,
+    started: Instant,
+}
+
+impl Partial {
+    fn new(count: usize, now: Instant) -> Self {
+        Partial {
+            parts: vec![None; count],
+            missing: count,
+            bytes: 0,
+            started: now,
+        }
+    }
+}
+
+/// Collects the fragments received from each address until their event is complete.
+///
+/// Fragments of events larger than `MAX_MESSAGE_SIZE` are dropped. When an address has more than
+/// `MAX_PARTIALS_PER_ADDR` incomplete events, or the fragments of all the addresses take more
+/// than `MAX_BUFFERED_BYTES`, the oldest incomplete events are dropped.
+#[derive(Default)]
+pub(crate) struct Reassembly {
+    partials: HashMap<(SocketAddr, u16), Partial>,
+    bytes: usize,
+}
+
+impl Reassembly {
+    /// Adds a fragment, returning the serialized event if it was the last one missing.
+    pub(crate) fn insert(
+        &mut self,
+        addr: SocketAddr,
+        fragment: Fragment,
+        now: Instant,
+    ) -> Option<Vec<u8>> {
+        let count = fragment.count as usize;
+        if fragment.index as usize >= count
+            || count > MAX_FRAGMENTS
+            || fragment.data.len() > MAX_FRAGMENT_SIZE
+        {
+            return None;
+        }
+        let key = (addr, fragment.id);
+        self.make_room(key, fragment.data.len());
+        let complete = {
+            let bytes = &mut self.bytes;
+            let partial = self
+                .partials
+                .entry(key)
+                .or_insert_with(|| Partial::new(count, now));
+            if partial.parts.len() != count {
+                // The id was reused for another event, drop the old fragments.
+                *bytes -= partial.bytes;
+                *partial = Partial::new(count, now);
+            }
+            let part = &mut partial.parts[fragment.index as usize];
+            if part.is_none() {
+                partial.bytes += fragment.data.len();
+                *bytes += fragment.data.len();
+                *part = Some(fragment.data);
+                partial.missing -= 1;
+            }
+            partial.missing == 0
+        };
+        if !complete {
+            return None;
+        }
+        let partial = self.partials.remove(&key)?;
+        self.bytes -= partial.bytes;
+        Some(
+            partial
+                .parts
+                .into_iter()
+                .flat_map(|part| part.unwrap_or_default())
+                .collect(),
+        )
+    }
+
+    /// Drops the oldest other incomplete events until a fragment of `bytes` bytes of the event
+    /// `key` fits in the limits.
+    fn make_room(&mut self, key: (SocketAddr, u16), bytes: usize) {
+        let (addr, _) = key;
+        let new = !self.partials.contains_key(&key);
+        loop {
+            let from_addr = self
+                .partials
+                .keys()
+                .filter(|&&(from, _)| from == addr)
+                .count();
+            let oldest = if new && from_addr >= MAX_PARTIALS_PER_ADDR {
+                self.oldest(|other| other.0 == addr && other != key)
+            } else if self.bytes + bytes > MAX_BUFFERED_BYTES {
+                self.oldest(|other| other != key)
+            } else {
+                return;
+            };
+            match oldest.and_then(|key| self.partials.remove(&key)) {
+                Some(partial) => self.bytes -= partial.bytes,
+                None => return,
+            }
+        }
+    }
+
+    /// Returns the key of the oldest incomplete event matching `filter`.
+    fn oldest<F>(&self, filter: F) -> Option<(SocketAddr, u16)>
+    where
+        F: Fn((SocketAddr, u16)) -> bool,
+    {
+        self.partials
+            .iter()
+            .filter(|&(key, _)| filter(*key))
+            .min_by_key(|&(_, partial)| partial.started)
+            .map(|(key, _)| *key)
+    }
+
+    /// Drops the incomplete events whose first fragment was received more than `timeout` ago.
+    pub(crate) fn expire(&mut self, now: Instant, timeout: Duration) {
+        self.retain(|_, partial| now.duration_since(partial.started) < timeout);
+    }
+
+    /// Drops the incomplete events received from `addr`.
+    pub(crate) fn remove_addr(&mut self, addr: SocketAddr) {
+        self.retain(|&(from, _), _| from != addr);
+    }
+
+    fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&(SocketAddr, u16), &Partial) -> bool,
+    {
+        let bytes = &mut self.bytes;
+        self.partials.retain(|key, partial| {
+            let kept = keep(key, partial);
+            if !kept {
+                *bytes -= partial.bytes;
+            }
+            kept
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        time::{Duration, Instant},
+    };
+
+    use super::{
+        split, Fragment, Reassembly, MAX_FRAGMENTS, MAX_FRAGMENT_SIZE, MAX_MESSAGE_SIZE,
+        MAX_PARTIALS_PER_ADDR,
+    };
+
+    #[test]
+    fn reassembles_fragments_in_any_order() {
+        let addr: SocketAddr = "127.0.0.1:21300".parse().unwrap();
+        let payload = (0..MAX_FRAGMENT_SIZE * 2 + 10)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let mut fragments = split(7, &payload).unwrap();
+        assert_eq!(fragments.len(), 3);
+
+        let now = Instant::now();
+        let mut reassembly = Reassembly::default();
+        let last = fragments.remove(0);
+        for fragment in fragments {
+            assert_eq!(reassembly.insert(addr, fragment.clone(), now), None);
+            // Duplicates are ignored.
+            assert_eq!(reassembly.insert(addr, fragment, now), None);
+        }
+        assert_eq!(reassembly.insert(addr, last, now), Some(payload));
+    }
+
+    #[test]
+    fn drops_expired_fragments() {
+        let addr: SocketAddr = "127.0.0.1:21300".parse().unwrap();
+        let payload = vec![1; MAX_FRAGMENT_SIZE + 1];
+        let mut fragments = split(1, &payload).unwrap();
+
+        let now = Instant::now();
+        let mut reassembly = Reassembly::default();
+        assert_eq!(reassembly.insert(addr, fragments.remove(0), now), None);
+        reassembly.expire(now + Duration::from_secs(10), Duration::from_secs(5));
+        assert_eq!(reassembly.insert(addr, fragments.remove(0), now), None);
+    }
+
+    #[test]
+    fn limits_buffered_fragments() {
+        let addr: SocketAddr = "127.0.0.1:21300".parse().unwrap();
+        let other: SocketAddr = "127.0.0.1:21301".parse().unwrap();
+        let fragment = |id, count| Fragment {
+            id,
+            index: 0,
+            count,
+            data: vec![1; 10],
+        };
+        let now = Instant::now();
+        let mut reassembly = Reassembly::default();
+
+        let too_large = MAX_FRAGMENTS as u16 + 1;
+        assert_eq!(reassembly.insert(addr, fragment(0, too_large), now), None);
+        assert!(reassembly.partials.is_empty());
+        assert!(split(0, &vec![0; MAX_MESSAGE_SIZE + 1]).is_none());
+
+        reassembly.insert(other, fragment(0, 2), now);
+        for id in 0..MAX_PARTIALS_PER_ADDR as u16 + 4 {
+            let time = now + Duration::from_millis(u64::from(id));
+            reassembly.insert(addr, fragment(id, 2), time);
+        }
+        assert_eq!(MAX_PARTIALS_PER_ADDR + 1, reassembly.partials.len());
+        assert!(!reassembly.partials.contains_key(&(addr, 0)));
+        assert!(reassembly.partials.contains_key(&(other, 0)));
+        assert_eq!(10 * reassembly.partials.len(), reassembly.bytes);
+
+        reassembly.remove_addr(addr);
+        assert_eq!(10, reassembly.bytes);
+    }
+}
@@ -0,0 +1,87 @@
+//! ECS physics bundle
+
+use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder, math::Vector3};
+use amethyst_error::Error;
+use amethyst_renderer::Rgba;
+
+use crate::systems::{ColliderDebugSystem, PhysicsSystem};
+
+/// Bundle adding the `PhysicsSystem` with name "physics_system".
+///
+/// The `TransformSystem` should depend on it, so the moved bodies are rendered in the same
+/// frame. With `with_debug_lines`, the `ColliderDebugSystem` is added with name
+/// "collider_debug_system", drawing the colliders for the `DebugLinesPass`.
+///
+/// The bundle can be added to the fixed dispatcher with `GameDataBuilder::with_fixed_bundle`,
+/// after calling `with_fixed_step`.
+///
+/// ## Errors
+///
+/// No errors will be returned by this bundle.
+#[derive(Debug)]
+pub struct PhysicsBundle<'a> {
+    dep: &'a [&'a str],
+    gravity: Vector3<f32>,
+    fixed: bool,
+    debug_color: Option<Rgba>,
+}
+
+impl<'a> Default for PhysicsBundle<'a> {
+    fn default() -> Self {
+        PhysicsBundle::new()
+    }
+}
+
+impl<'a> PhysicsBundle<'a> {
+    /// Creates a new physics bundle, with a gravity of 9.81 units per second squared along -Y.
+    pub fn new() -> Self {
+        PhysicsBundle {
+            dep: &[],
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            fixed: false,
+            debug_color: None,
+        }
+    }
+
+    /// Sets the dependencies of the `PhysicsSystem`.
+    pub fn with_dep(mut self, dep: &'a [&'a str]) -> Self {
+        self.dep = dep;
+        self
+    }
+
+    /// Sets the gravity.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Makes the steps last `Time::fixed_seconds`, to run in the fixed dispatcher.
+    pub fn with_fixed_step(mut self) -> Self {
+        self.fixed = true;
+        self
+    }
+
+    /// Draws the colliders with the `DebugLines` resource in `color`.
+    pub fn with_debug_lines(mut self, color: Rgba) -> Self {
+        self.debug_color = Some(color);
+        self
+    }
+}
+
+impl<'a, 'b, 'c> SystemBundle<'a, 'b> for PhysicsBundle<'c> {
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        builder.add(
+            PhysicsSystem::new(self.gravity, self.fixed),
+            "physics_system",
+            self.dep,
+        );
+        if let Some(color) = self.debug_color {
+            builder.add(
+                ColliderDebugSystem::new(color),
+                "collider_debug_system",
+                &["physics_system"],
+            );
+        }
+        Ok(())
+    }
+}
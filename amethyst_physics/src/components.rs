@@ -0,0 +1,157 @@
+//! Components of the physics simulation.
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::PrefabData;
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, Entity, WriteStorage},
+    math::Vector3,
+};
+use amethyst_derive::PrefabData;
+use amethyst_error::Error;
+
+/// How a `RigidBody` is moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyStatus {
+    /// Moved by forces, gravity and contacts.
+    Dynamic,
+    /// Never moves.
+    Static,
+    /// Moved by its `Transform` or velocity only, pushing the dynamic bodies.
+    Kinematic,
+}
+
+impl Default for BodyStatus {
+    fn default() -> Self {
+        BodyStatus::Dynamic
+    }
+}
+
+/// A body simulated by the `PhysicsSystem`, which moves the `Transform` of its entity.
+///
+/// The `Transform` is taken as the position in the world, so bodies are best kept on entities
+/// without a `Parent`. The velocities are read before each step and updated after it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, PrefabData)]
+#[prefab(Component)]
+#[serde(default)]
+pub struct RigidBody {
+    /// How the body is moved.
+    pub status: BodyStatus,
+    /// Mass added to the mass given by the density of the colliders.
+    pub mass: f32,
+    /// Linear velocity, in units per second.
+    pub linear_velocity: Vector3<f32>,
+    /// Angular velocity, in radians per second around each axis.
+    pub angular_velocity: Vector3<f32>,
+    /// Damping of the linear velocity.
+    pub linear_damping: f32,
+    /// Damping of the angular velocity.
+    pub angular_damping: f32,
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        RigidBody {
+            status: BodyStatus::Dynamic,
+            mass: 0.0,
+            linear_velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+        }
+    }
+}
+
+impl RigidBody {
+    /// Creates a body with the given status.
+    pub fn new(status: BodyStatus) -> Self {
+        RigidBody {
+            status,
+            ..Default::default()
+        }
+    }
+}
+
+impl Component for RigidBody {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Shape of a `Collider`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Shape {
+    /// A sphere.
+    Ball {
+        /// Radius of the sphere.
+        radius: f32,
+    },
+    /// A box.
+    Cuboid {
+        /// Half of the size of the box along each axis.
+        half_extents: Vector3<f32>,
+    },
+    /// A cylinder with half spheres at its ends, along the Y axis.
+    Capsule {
+        /// Half of the height of the cylinder.
+        half_height: f32,
+        /// Radius of the cylinder and the spheres.
+        radius: f32,
+    },
+}
+
+/// The shape of an entity in the physics simulation.
+///
+/// The collider is attached to the `RigidBody` of its entity, or is static if the entity has no
+/// `RigidBody`. Contacts with other colliders are written to the `EventChannel<CollisionEvent>`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, PrefabData)]
+#[prefab(Component)]
+pub struct Collider {
+    /// The shape.
+    pub shape: Shape,
+    /// Position of the shape relative to the entity.
+    #[serde(default = "Vector3::zeros")]
+    pub offset: Vector3<f32>,
+    /// Density, giving the inertia of the body.
+    #[serde(default = "default_density")]
+    pub density: f32,
+    /// Friction coefficient.
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+    /// Restitution coefficient, 0 for no bounce.
+    #[serde(default)]
+    pub restitution: f32,
+    /// Sensors detect the colliders they overlap without pushing them.
+    #[serde(default)]
+    pub sensor: bool,
+}
+
+fn default_density() -> f32 {
+    1.0
+}
+
+fn default_friction() -> f32 {
+    0.5
+}
+
+impl Collider {
+    /// Creates a collider with the given shape.
+    pub fn new(shape: Shape) -> Self {
+        Collider {
+            shape,
+            offset: Vector3::zeros(),
+            density: default_density(),
+            friction: default_friction(),
+            restitution: 0.0,
+            sensor: false,
+        }
+    }
+
+    /// Makes the collider a sensor.
+    pub fn with_sensor(mut self) -> Self {
+        self.sensor = true;
+        self
+    }
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,23 @@
+//! Rigid body physics for Amethyst, backed by nphysics.
+//!
+//! Entities get a body with the `RigidBody` component and a shape with the `Collider` component,
+//! both usable in prefabs. The `PhysicsSystem`, added by the `PhysicsBundle`, steps the
+//! simulation, moves the `Transform`s of the bodies and writes the contacts between colliders to
+//! the `EventChannel<CollisionEvent>`.
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use ncollide3d;
+pub use nphysics3d;
+
+pub use crate::{
+    bundle::PhysicsBundle,
+    components::{BodyStatus, Collider, RigidBody, Shape},
+    physics::{set_isometry, to_isometry, CollisionEvent, CollisionKind, Physics},
+    systems::{ColliderDebugSystem, PhysicsSystem},
+};
+
+mod bundle;
+mod components;
+mod physics;
+mod systems;
@@ -0,0 +1,149 @@
+//! The physics world resource and the collision events.
+
+use fnv::FnvHashMap;
+use nphysics3d::{
+    object::{BodyHandle, ColliderHandle},
+    world::World as PhysicsWorld,
+};
+
+use amethyst_core::{
+    ecs::prelude::Entity,
+    math::{Isometry3, Quaternion, Translation3, UnitQuaternion, Vector3},
+    Float, Transform,
+};
+
+/// Whether two colliders started or stopped touching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionKind {
+    /// The colliders started touching, or a sensor started overlapping a collider.
+    Started,
+    /// The colliders stopped touching.
+    Stopped,
+}
+
+/// Event written to the `EventChannel<CollisionEvent>` when the colliders of two entities start
+/// or stop touching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionEvent {
+    /// The entity of the first collider.
+    pub entity1: Entity,
+    /// The entity of the second collider.
+    pub entity2: Entity,
+    /// Whether the colliders started or stopped touching.
+    pub kind: CollisionKind,
+}
+
+/// Resource holding the nphysics world, with the bodies and colliders of the entities.
+///
+/// The bodies and colliders are created and removed by the `PhysicsSystem`, following the
+/// `RigidBody` and `Collider` components. The world can be used directly for queries, such as
+/// ray casts on its collider world.
+pub struct Physics {
+    pub(crate) world: PhysicsWorld<f32>,
+    pub(crate) bodies: FnvHashMap<Entity, BodyHandle>,
+    pub(crate) colliders: FnvHashMap<Entity, ColliderHandle>,
+    pub(crate) collider_entities: FnvHashMap<ColliderHandle, Entity>,
+}
+
+impl Default for Physics {
+    fn default() -> Self {
+        Physics {
+            world: PhysicsWorld::new(),
+            bodies: FnvHashMap::default(),
+            colliders: FnvHashMap::default(),
+            collider_entities: FnvHashMap::default(),
+        }
+    }
+}
+
+impl Physics {
+    /// Returns the nphysics world.
+    pub fn world(&self) -> &PhysicsWorld<f32> {
+        &self.world
+    }
+
+    /// Returns the nphysics world, to change its settings.
+    ///
+    /// Bodies and colliders of entities must not be removed, the `PhysicsSystem` tracks them.
+    pub fn world_mut(&mut self) -> &mut PhysicsWorld<f32> {
+        &mut self.world
+    }
+
+    /// Returns the gravity.
+    pub fn gravity(&self) -> Vector3<f32> {
+        *self.world.gravity()
+    }
+
+    /// Sets the gravity.
+    pub fn set_gravity(&mut self, gravity: Vector3<f32>) {
+        self.world.set_gravity(gravity);
+    }
+
+    /// Returns the handle of the body of `entity`.
+    pub fn body(&self, entity: Entity) -> Option<BodyHandle> {
+        self.bodies.get(&entity).cloned()
+    }
+
+    /// Returns the handle of the collider of `entity`.
+    pub fn collider(&self, entity: Entity) -> Option<ColliderHandle> {
+        self.colliders.get(&entity).cloned()
+    }
+
+    /// Returns the entity of a collider.
+    pub fn collider_entity(&self, collider: ColliderHandle) -> Option<Entity> {
+        self.collider_entities.get(&collider).cloned()
+    }
+}
+
+/// Converts the translation and rotation of a `Transform` to an isometry of the physics world.
+pub fn to_isometry(transform: &Transform) -> Isometry3<f32> {
+    let translation = transform.translation();
+    let rotation = transform.rotation().quaternion();
+    Isometry3::from_parts(
+        Translation3::new(
+            translation.x.as_f32(),
+            translation.y.as_f32(),
+            translation.z.as_f32(),
+        ),
+        UnitQuaternion::new_unchecked(Quaternion::new(
+            rotation.w.as_f32(),
+            rotation.i.as_f32(),
+            rotation.j.as_f32(),
+            rotation.k.as_f32(),
+        )),
+    )
+}
+
+/// Sets the translation and rotation of a `Transform` from an isometry of the physics world.
+pub fn set_isometry(transform: &mut Transform, isometry: &Isometry3<f32>) {
+    let translation = isometry.translation.vector;
+    let rotation = isometry.rotation.quaternion();
+    transform.set_translation_xyz(translation.x, translation.y, translation.z);
+    transform.set_rotation(UnitQuaternion::new_unchecked(Quaternion::new(
+        Float::from(rotation.w),
+        Float::from(rotation.i),
+        Float::from(rotation.j),
+        Float::from(rotation.k),
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{math::Vector3, Transform};
+
+    use super::{set_isometry, to_isometry};
+
+    #[test]
+    fn converts_transforms() {
+        let mut transform = Transform::default();
+        transform
+            .set_translation_xyz(1.0, 2.0, 3.0)
+            .set_rotation_euler(0.3, 0.2, 0.1);
+        let isometry = to_isometry(&transform);
+        assert_eq!(Vector3::new(1.0, 2.0, 3.0), isometry.translation.vector);
+
+        let mut converted = Transform::default();
+        set_isometry(&mut converted, &isometry);
+        assert_eq!(transform, converted);
+    }
+}
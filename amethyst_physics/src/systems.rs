@@ -0,0 +1,576 @@
+//! Systems stepping the physics simulation and drawing the colliders.
+
+use std::f32::consts::PI;
+
+use ncollide3d::{
+    query::Proximity,
+    shape::{Ball, Capsule, Cuboid, ShapeHandle},
+};
+use nphysics3d::{
+    algebra::Velocity3,
+    material::{BasicMaterial, MaterialHandle},
+    object::{BodyStatus as PhysicsBodyStatus, ColliderDesc, RigidBodyDesc},
+    world::ContactEvent,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_core::{
+    ecs::prelude::{
+        Entities, Entity, Join, Read, ReadStorage, Resources, System, SystemData, Write,
+        WriteStorage,
+    },
+    math::{Isometry3, Point3, Vector3},
+    shrev::EventChannel,
+    timing::Time,
+    Transform,
+};
+use amethyst_renderer::{DebugLines, Rgba};
+
+use crate::{
+    components::{BodyStatus, Collider, RigidBody, Shape},
+    physics::{set_isometry, to_isometry, CollisionEvent, CollisionKind, Physics},
+};
+
+/// Steps the physics simulation, creating and removing the bodies and colliders of the
+/// entities and moving their `Transform`s.
+///
+/// The step lasts `Time::delta_seconds`, or `Time::fixed_seconds` when the system runs in the
+/// fixed dispatcher, and is skipped while the time is paused.
+#[derive(Debug)]
+pub struct PhysicsSystem {
+    gravity: Vector3<f32>,
+    fixed: bool,
+}
+
+impl PhysicsSystem {
+    /// Creates a new `PhysicsSystem` with the given gravity.
+    ///
+    /// `fixed` makes the steps last `Time::fixed_seconds`.
+    pub fn new(gravity: Vector3<f32>, fixed: bool) -> Self {
+        PhysicsSystem { gravity, fixed }
+    }
+}
+
+impl<'a> System<'a> for PhysicsSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, RigidBody>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, Transform>,
+        Read<'a, Time>,
+        Write<'a, Physics>,
+        Write<'a, EventChannel<CollisionEvent>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("physics_system");
+
+        let (entities, mut bodies, colliders, mut transforms, time, mut physics, mut events) = data;
+
+        let physics = &mut *physics;
+        remove_objects(&entities, &bodies, &colliders, physics);
+        create_objects(&entities, &bodies, &colliders, &transforms, physics);
+
+        for (entity, body, transform) in (&*entities, &bodies, &transforms).join() {
+            let rigid_body = match physics.bodies.get(&entity) {
+                Some(handle) => physics.world.rigid_body_mut(*handle),
+                None => None,
+            };
+            if let Some(rigid_body) = rigid_body {
+                let status = body_status(body.status);
+                if rigid_body.status() != status {
+                    rigid_body.set_status(status);
+                }
+                let position = to_isometry(transform);
+                if *rigid_body.position() != position {
+                    rigid_body.set_position(position);
+                }
+                let velocity = rigid_body.velocity();
+                if velocity.linear != body.linear_velocity
+                    || velocity.angular != body.angular_velocity
+                {
+                    rigid_body
+                        .set_velocity(Velocity3::new(body.linear_velocity, body.angular_velocity));
+                }
+                rigid_body.set_linear_damping(body.linear_damping);
+                rigid_body.set_angular_damping(body.angular_damping);
+            }
+        }
+
+        let step = if self.fixed {
+            time.fixed_seconds()
+        } else {
+            time.delta_seconds()
+        };
+        if time.is_paused() || step <= 0.0 {
+            return;
+        }
+        physics.world.set_timestep(step);
+        physics.world.step();
+
+        let mut moved = Vec::new();
+        for (entity, body, transform) in (&*entities, &mut bodies, &transforms).join() {
+            let rigid_body = match physics.bodies.get(&entity) {
+                Some(handle) => physics.world.rigid_body(*handle),
+                None => None,
+            };
+            if let Some(rigid_body) = rigid_body {
+                let velocity = rigid_body.velocity();
+                body.linear_velocity = velocity.linear;
+                body.angular_velocity = velocity.angular;
+                if *rigid_body.position() != to_isometry(transform) {
+                    moved.push((entity, *rigid_body.position()));
+                }
+            }
+        }
+        for (entity, position) in moved {
+            if let Some(transform) = transforms.get_mut(entity) {
+                set_isometry(transform, &position);
+            }
+        }
+
+        write_events(physics, &mut events);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        res.fetch_mut::<Physics>().set_gravity(self.gravity);
+    }
+}
+
+fn body_status(status: BodyStatus) -> PhysicsBodyStatus {
+    match status {
+        BodyStatus::Dynamic => PhysicsBodyStatus::Dynamic,
+        BodyStatus::Static => PhysicsBodyStatus::Static,
+        BodyStatus::Kinematic => PhysicsBodyStatus::Kinematic,
+    }
+}
+
+fn shape_handle(shape: &Shape) -> ShapeHandle<f32> {
+    match *shape {
+        Shape::Ball { radius } => ShapeHandle::new(Ball::new(radius)),
+        Shape::Cuboid { half_extents } => ShapeHandle::new(Cuboid::new(half_extents)),
+        Shape::Capsule {
+            half_height,
+            radius,
+        } => ShapeHandle::new(Capsule::new(half_height, radius)),
+    }
+}
+
+/// Removes the bodies and colliders of the entities which lost their components.
+fn remove_objects(
+    entities: &Entities<'_>,
+    bodies: &WriteStorage<'_, RigidBody>,
+    colliders: &ReadStorage<'_, Collider>,
+    physics: &mut Physics,
+) {
+    let removed_bodies = physics
+        .bodies
+        .keys()
+        .filter(|entity| !entities.is_alive(**entity) || !bodies.contains(**entity))
+        .cloned()
+        .collect::<Vec<Entity>>();
+    let mut handles = Vec::with_capacity(removed_bodies.len());
+    for entity in removed_bodies {
+        if let Some(handle) = physics.bodies.remove(&entity) {
+            handles.push(handle);
+        }
+        // The colliders of a body are removed with it.
+        if let Some(collider) = physics.colliders.remove(&entity) {
+            physics.collider_entities.remove(&collider);
+        }
+    }
+    if !handles.is_empty() {
+        physics.world.remove_bodies(&handles);
+    }
+
+    let removed_colliders = physics
+        .colliders
+        .keys()
+        .filter(|entity| !entities.is_alive(**entity) || !colliders.contains(**entity))
+        .cloned()
+        .collect::<Vec<Entity>>();
+    let mut handles = Vec::with_capacity(removed_colliders.len());
+    for entity in removed_colliders {
+        if let Some(collider) = physics.colliders.remove(&entity) {
+            physics.collider_entities.remove(&collider);
+            handles.push(collider);
+        }
+    }
+    if !handles.is_empty() {
+        physics.world.remove_colliders(&handles);
+    }
+}
+
+/// Creates the bodies and colliders of the entities which got their components.
+///
+/// The collider of an entity which gets a body is built again, attached to the body.
+fn create_objects(
+    entities: &Entities<'_>,
+    bodies: &WriteStorage<'_, RigidBody>,
+    colliders: &ReadStorage<'_, Collider>,
+    transforms: &WriteStorage<'_, Transform>,
+    physics: &mut Physics,
+) {
+    let mut rebuilt = Vec::new();
+    for (entity, body, transform) in (&**entities, bodies, transforms).join() {
+        if physics.bodies.contains_key(&entity) {
+            continue;
+        }
+        if let Some(collider) = physics.colliders.remove(&entity) {
+            physics.collider_entities.remove(&collider);
+            rebuilt.push(collider);
+        }
+        let handle = RigidBodyDesc::new()
+            .position(to_isometry(transform))
+            .status(body_status(body.status))
+            .mass(body.mass)
+            .velocity(Velocity3::new(body.linear_velocity, body.angular_velocity))
+            .linear_damping(body.linear_damping)
+            .angular_damping(body.angular_damping)
+            .build(&mut physics.world)
+            .handle();
+        physics.bodies.insert(entity, handle);
+    }
+    if !rebuilt.is_empty() {
+        physics.world.remove_colliders(&rebuilt);
+    }
+
+    for (entity, collider, transform) in (&**entities, colliders, transforms).join() {
+        if physics.colliders.contains_key(&entity) {
+            continue;
+        }
+        let offset =
+            Isometry3::translation(collider.offset.x, collider.offset.y, collider.offset.z);
+        let desc = ColliderDesc::new(shape_handle(&collider.shape))
+            .material(MaterialHandle::new(BasicMaterial::new(
+                collider.restitution,
+                collider.friction,
+            )))
+            .sensor(collider.sensor);
+        let handle = match physics.bodies.get(&entity) {
+            Some(body) => desc
+                .position(offset)
+                .density(collider.density)
+                .build_with_parent(*body, &mut physics.world)
+                .map(|collider| collider.handle()),
+            // The body isn't created yet.
+            None if bodies.contains(entity) => None,
+            None => Some(
+                desc.position(to_isometry(transform) * offset)
+                    .build(&mut physics.world)
+                    .handle(),
+            ),
+        };
+        if let Some(handle) = handle {
+            physics.colliders.insert(entity, handle);
+            physics.collider_entities.insert(handle, entity);
+        }
+    }
+}
+
+/// Writes the contact and proximity events of the last step.
+fn write_events(physics: &Physics, events: &mut EventChannel<CollisionEvent>) {
+    let contacts = physics
+        .world
+        .contact_events()
+        .iter()
+        .map(|event| match *event {
+            ContactEvent::Started(collider1, collider2) => {
+                (collider1, collider2, CollisionKind::Started)
+            }
+            ContactEvent::Stopped(collider1, collider2) => {
+                (collider1, collider2, CollisionKind::Stopped)
+            }
+        });
+    let proximities = physics.world.proximity_events().iter().filter_map(|event| {
+        match (event.prev_status, event.new_status) {
+            (_, Proximity::Intersecting) => {
+                Some((event.collider1, event.collider2, CollisionKind::Started))
+            }
+            (Proximity::Intersecting, _) => {
+                Some((event.collider1, event.collider2, CollisionKind::Stopped))
+            }
+            _ => None,
+        }
+    });
+    events.iter_write(
+        contacts
+            .chain(proximities)
+            .filter_map(|(collider1, collider2, kind)| {
+                let entity1 = physics.collider_entity(collider1)?;
+                let entity2 = physics.collider_entity(collider2)?;
+                Some(CollisionEvent {
+                    entity1,
+                    entity2,
+                    kind,
+                })
+            })
+            .collect::<Vec<_>>(),
+    );
+}
+
+/// Draws the outlines of the colliders with the `DebugLines` resource, rendered by the
+/// `DebugLinesPass`.
+#[derive(Debug)]
+pub struct ColliderDebugSystem {
+    color: Rgba,
+}
+
+impl ColliderDebugSystem {
+    /// Creates a new `ColliderDebugSystem` drawing the colliders with `color`.
+    pub fn new(color: Rgba) -> Self {
+        ColliderDebugSystem { color }
+    }
+}
+
+impl Default for ColliderDebugSystem {
+    fn default() -> Self {
+        ColliderDebugSystem::new(Rgba::green())
+    }
+}
+
+impl<'a> System<'a> for ColliderDebugSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Collider>,
+        Read<'a, Physics>,
+        Write<'a, DebugLines>,
+    );
+
+    fn run(&mut self, (entities, colliders, physics, mut lines): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("collider_debug_system");
+
+        for (entity, collider) in (&*entities, &colliders).join() {
+            let position = physics
+                .collider(entity)
+                .and_then(|handle| physics.world.collider(handle))
+                .map(|collider| *collider.position());
+            if let Some(position) = position {
+                draw_shape(&mut lines, &collider.shape, &position, self.color);
+            }
+        }
+    }
+}
+
+const CIRCLE_SEGMENTS: usize = 16;
+
+fn draw_shape(lines: &mut DebugLines, shape: &Shape, position: &Isometry3<f32>, color: Rgba) {
+    match *shape {
+        Shape::Ball { radius } => {
+            let center = Vector3::zeros();
+            draw_circle(
+                lines,
+                position,
+                center,
+                Vector3::x(),
+                Vector3::y(),
+                radius,
+                color,
+            );
+            draw_circle(
+                lines,
+                position,
+                center,
+                Vector3::y(),
+                Vector3::z(),
+                radius,
+                color,
+            );
+            draw_circle(
+                lines,
+                position,
+                center,
+                Vector3::z(),
+                Vector3::x(),
+                radius,
+                color,
+            );
+        }
+        Shape::Cuboid { half_extents } => {
+            let corner = |i: usize| {
+                let sign = |bit: usize| if i & bit == 0 { -1.0 } else { 1.0 };
+                position
+                    * Point3::new(
+                        sign(1) * half_extents.x,
+                        sign(2) * half_extents.y,
+                        sign(4) * half_extents.z,
+                    )
+            };
+            for i in 0..8 {
+                for bit in &[1, 2, 4] {
+                    if i & bit == 0 {
+                        lines.draw_line(corner(i), corner(i | bit), color);
+                    }
+                }
+            }
+        }
+        Shape::Capsule {
+            half_height,
+            radius,
+        } => {
+            for &y in &[-half_height, half_height] {
+                let center = Vector3::new(0.0, y, 0.0);
+                draw_circle(
+                    lines,
+                    position,
+                    center,
+                    Vector3::z(),
+                    Vector3::x(),
+                    radius,
+                    color,
+                );
+                draw_circle(
+                    lines,
+                    position,
+                    center,
+                    Vector3::x(),
+                    Vector3::y(),
+                    radius,
+                    color,
+                );
+                draw_circle(
+                    lines,
+                    position,
+                    center,
+                    Vector3::y(),
+                    Vector3::z(),
+                    radius,
+                    color,
+                );
+            }
+            for side in &[Vector3::x(), -Vector3::x(), Vector3::z(), -Vector3::z()] {
+                let start = Point3::from(side * radius - Vector3::y() * half_height);
+                let end = Point3::from(side * radius + Vector3::y() * half_height);
+                lines.draw_line(position * start, position * end, color);
+            }
+        }
+    }
+}
+
+fn draw_circle(
+    lines: &mut DebugLines,
+    position: &Isometry3<f32>,
+    center: Vector3<f32>,
+    axis1: Vector3<f32>,
+    axis2: Vector3<f32>,
+    radius: f32,
+    color: Rgba,
+) {
+    let point = |i: usize| {
+        let angle = i as f32 * 2.0 * PI / CIRCLE_SEGMENTS as f32;
+        position * Point3::from(center + (axis1 * angle.cos() + axis2 * angle.sin()) * radius)
+    };
+    for i in 0..CIRCLE_SEGMENTS {
+        lines.draw_line(point(i), point(i + 1), color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, RunNow, System, World},
+        math::Vector3,
+        shrev::EventChannel,
+        timing::Time,
+        Transform,
+    };
+
+    use super::PhysicsSystem;
+    use crate::{Collider, CollisionEvent, CollisionKind, Physics, RigidBody, Shape};
+
+    #[test]
+    fn bodies_fall_and_collide() {
+        let mut world = World::new();
+        world.register::<RigidBody>();
+        world.register::<Collider>();
+        world.register::<Transform>();
+        let mut system = PhysicsSystem::new(Vector3::new(0.0, -10.0, 0.0), false);
+        System::setup(&mut system, &mut world.res);
+        world.write_resource::<Time>().set_delta_seconds(1.0 / 60.0);
+        let mut reader = world
+            .write_resource::<EventChannel<CollisionEvent>>()
+            .register_reader();
+
+        let ground = world
+            .create_entity()
+            .with(Collider::new(Shape::Cuboid {
+                half_extents: Vector3::new(10.0, 0.5, 10.0),
+            }))
+            .with(Transform::default())
+            .build();
+        let mut transform = Transform::default();
+        transform.set_translation_y(3.0);
+        let ball = world
+            .create_entity()
+            .with(RigidBody::default())
+            .with(Collider::new(Shape::Ball { radius: 0.5 }))
+            .with(transform)
+            .build();
+
+        let mut events = Vec::new();
+        for _ in 0..120 {
+            system.run_now(&world.res);
+            events.extend(
+                world
+                    .read_resource::<EventChannel<CollisionEvent>>()
+                    .read(&mut reader)
+                    .cloned(),
+            );
+        }
+
+        let y = world
+            .read_storage::<Transform>()
+            .get(ball)
+            .expect("Missing transform")
+            .translation()
+            .y
+            .as_f32();
+        assert!(
+            y > 0.8 && y < 1.2,
+            "Ball should rest on the ground, at {}",
+            y
+        );
+        assert!(events.iter().any(|event| {
+            event.kind == CollisionKind::Started
+                && ((event.entity1, event.entity2) == (ball, ground)
+                    || (event.entity1, event.entity2) == (ground, ball))
+        }));
+    }
+
+    #[test]
+    fn attaches_colliders_to_added_bodies() {
+        let mut world = World::new();
+        world.register::<RigidBody>();
+        world.register::<Collider>();
+        world.register::<Transform>();
+        let mut system = PhysicsSystem::new(Vector3::new(0.0, -10.0, 0.0), false);
+        System::setup(&mut system, &mut world.res);
+        world.write_resource::<Time>().set_delta_seconds(1.0 / 60.0);
+
+        let entity = world
+            .create_entity()
+            .with(Collider::new(Shape::Ball { radius: 0.5 }))
+            .with(Transform::default())
+            .build();
+        system.run_now(&world.res);
+        world
+            .write_storage::<RigidBody>()
+            .insert(entity, RigidBody::default())
+            .expect("Failed to add the body");
+        system.run_now(&world.res);
+
+        let physics = world.read_resource::<Physics>();
+        let collider = physics
+            .world
+            .collider(physics.colliders[&entity])
+            .expect("Missing collider");
+        assert_eq!(physics.bodies[&entity], collider.body());
+        assert_eq!(1, physics.colliders.len());
+        assert_eq!(1, physics.collider_entities.len());
+    }
+}
@@ -6,10 +6,13 @@ use amethyst_error::{format_err, Error, ResultExt};
 
 use crate::{
     config::DisplayConfig,
+    lod::LodSystem,
     pipe::{PipelineBuild, PolyPipeline},
     sprite::SpriteSheet,
     sprite_visibility::SpriteVisibilitySortingSystem,
-    system::RenderSystem,
+    system::{pipe_rebuild, PipeRebuild, RenderSystem},
+    terrain::Heightmap,
+    trail::TrailSystem,
     visibility::VisibilitySortingSystem,
     HideHierarchySystem,
 };
@@ -28,10 +31,14 @@ where
     P: PolyPipeline,
 {
     pipe: B,
+    pipe_rebuild: Option<PipeRebuild<P>>,
     config: Option<DisplayConfig>,
     visibility_sorting: Option<&'a [&'a str]>,
     sprite_visibility_sorting: Option<&'a [&'a str]>,
+    lod: Option<&'a [&'a str]>,
+    trails: Option<&'a [&'a str]>,
     sprite_sheet_processor_enabled: bool,
+    heightmap_processor_enabled: bool,
     hide_hierarchy_system_enabled: bool,
 }
 
@@ -44,10 +51,14 @@ where
     pub fn new(pipe: B, config: Option<DisplayConfig>) -> Self {
         RenderBundle {
             pipe,
+            pipe_rebuild: None,
             config,
             visibility_sorting: None,
             sprite_visibility_sorting: None,
+            lod: None,
+            trails: None,
             sprite_sheet_processor_enabled: false,
+            heightmap_processor_enabled: false,
             hide_hierarchy_system_enabled: false,
         }
     }
@@ -64,6 +75,18 @@ where
         self
     }
 
+    /// Enable level of detail selection for `LodGroup`s, with the given dependencies
+    pub fn with_lod(mut self, dep: &'a [&'a str]) -> Self {
+        self.lod = Some(dep);
+        self
+    }
+
+    /// Enable recording the positions of `Trail`s, with the given dependencies
+    pub fn with_trails(mut self, dep: &'a [&'a str]) -> Self {
+        self.trails = Some(dep);
+        self
+    }
+
     /// Enable the sprite sheet processor
     ///
     /// If you load a `SpriteSheet` in memory as an asset `Format`, this adds the `Processor` that
@@ -73,6 +96,15 @@ where
         self
     }
 
+    /// Enable the heightmap processor
+    ///
+    /// If you load a `Heightmap` for a `Terrain` as an asset `Format`, this adds the `Processor`
+    /// that will convert it to the `Asset`.
+    pub fn with_heightmap_processor(mut self) -> Self {
+        self.heightmap_processor_enabled = true;
+        self
+    }
+
     /// Enable the [hierarchical hiding system](struct.HideHierarchySystem.html).
     /// Requires the `"parent_hierarchy_system"` to be used, which is a default part of TransformBundle.
     pub fn with_hide_hierarchy_system(mut self) -> Self {
@@ -81,6 +113,19 @@ where
     }
 }
 
+impl<'a, B, P> RenderBundle<'a, B, P>
+where
+    B: PipelineBuild<Pipeline = P> + Clone + 'static,
+    P: PolyPipeline,
+{
+    /// Enable recreating the window and graphics context when the context is lost, see
+    /// `RenderSystem::with_context_recovery`.
+    pub fn with_context_recovery(mut self) -> Self {
+        self.pipe_rebuild = Some(pipe_rebuild(self.pipe.clone()));
+        self
+    }
+}
+
 impl<'a, 'b, 'c, B, P> SystemBundle<'a, 'b> for RenderBundle<'c, B, P>
 where
     B: PipelineBuild<Pipeline = P>,
@@ -101,6 +146,12 @@ where
                 dep,
             );
         };
+        if let Some(dep) = self.lod {
+            builder.add(LodSystem::new(), "lod_system", dep);
+        }
+        if let Some(dep) = self.trails {
+            builder.add(TrailSystem::new(), "trail_system", dep);
+        }
         if self.sprite_sheet_processor_enabled {
             builder.add(
                 Processor::<SpriteSheet>::new(),
@@ -108,6 +159,9 @@ where
                 &[],
             );
         }
+        if self.heightmap_processor_enabled {
+            builder.add(Processor::<Heightmap>::new(), "heightmap_processor", &[]);
+        }
         if self.hide_hierarchy_system_enabled {
             builder.add(
                 HideHierarchySystem::default(),
@@ -117,7 +171,8 @@ where
         }
         builder.add_thread_local(
             RenderSystem::build(self.pipe, self.config)
-                .with_context(|_| format_err!("Renderer error!"))?,
+                .with_context(|_| format_err!("Renderer error!"))?
+                .with_pipe_rebuild(self.pipe_rebuild),
         );
         Ok(())
     }
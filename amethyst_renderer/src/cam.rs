@@ -2,7 +2,7 @@
 
 use amethyst_assets::PrefabData;
 use amethyst_core::{
-    ecs::prelude::{Component, Entity, HashMapStorage, Write, WriteStorage},
+    ecs::prelude::{Component, Entity, HashMapStorage, ReadExpect, Write, WriteStorage},
     math::{Matrix4, Orthographic3, Perspective3, Point2, Point3},
 };
 use amethyst_error::Error;
@@ -130,16 +130,59 @@ pub enum CameraPrefab {
 
     /// Projection matrix
     Matrix(Matrix4<f32>),
+
+    /// A projection from `CameraPreset`, fitted to the screen dimensions.
+    Preset(CameraPreset),
+}
+
+/// Named projections fitted to the `ScreenDimensions` when the camera is created.
+///
+/// Before the window is created the screen is assumed to be 1920 x 1080. Add an `AutoFov` to
+/// keep a perspective camera fitted when the window is resized.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CameraPreset {
+    /// The projection of `Camera::standard_2d`, from (-1, -1) to (1, 1).
+    Standard2D,
+
+    /// The projection of `Camera::standard_3d`, with a vertical field of view of 60 degrees.
+    Standard3D,
+
+    /// A perspective projection with the given vertical field of view, in radians.
+    Perspective {
+        /// Vertical field of view in radians.
+        fovy: f32,
+    },
+
+    /// An orthographic projection with one world unit per pixel and the origin at the lower left
+    /// corner of the screen.
+    Screen,
+}
+
+impl CameraPreset {
+    /// Build the projection for a screen of `width` x `height`.
+    pub fn projection(self, width: f32, height: f32) -> Projection {
+        match self {
+            CameraPreset::Standard2D => Projection::orthographic(-1., 1., -1., 1.),
+            CameraPreset::Standard3D => {
+                Projection::perspective(width / height, std::f32::consts::FRAC_PI_3)
+            }
+            CameraPreset::Perspective { fovy } => Projection::perspective(width / height, fovy),
+            CameraPreset::Screen => Projection::orthographic(0., width, 0., height),
+        }
+    }
 }
 
 impl<'a> PrefabData<'a> for CameraPrefab {
-    type SystemData = WriteStorage<'a, Camera>;
+    type SystemData = (
+        WriteStorage<'a, Camera>,
+        Option<ReadExpect<'a, ScreenDimensions>>,
+    );
     type Result = ();
 
     fn add_to_entity(
         &self,
         entity: Entity,
-        storage: &mut Self::SystemData,
+        (storage, screen): &mut Self::SystemData,
         _: &[Entity],
         _: &[Entity],
     ) -> Result<(), Error> {
@@ -147,6 +190,13 @@ impl<'a> PrefabData<'a> for CameraPrefab {
             CameraPrefab::Matrix(mat) => mat,
             CameraPrefab::Orthographic(ortho) => ortho.to_homogeneous(),
             CameraPrefab::Perspective(perspective) => perspective.to_homogeneous(),
+            CameraPrefab::Preset(preset) => {
+                let (width, height) = screen
+                    .as_ref()
+                    .map(|screen| (screen.width(), screen.height()))
+                    .unwrap_or((1920., 1080.));
+                Camera::from(preset.projection(width, height)).proj
+            }
         };
         storage.insert(entity, Camera { proj }).map(|_| ())?;
         Ok(())
@@ -454,3 +504,30 @@ mod serde_persp {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CameraPrefab, CameraPreset, Projection};
+
+    #[test]
+    fn deserialize_presets() {
+        let prefab: CameraPrefab = ron::de::from_str("Preset(Perspective(fovy: 1.0))").unwrap();
+        match prefab {
+            CameraPrefab::Preset(CameraPreset::Perspective { fovy }) => assert_eq!(fovy, 1.0),
+            _ => panic!("expected a perspective preset"),
+        }
+    }
+
+    #[test]
+    fn screen_preset_covers_pixels() {
+        match CameraPreset::Screen.projection(800., 600.) {
+            Projection::Orthographic(ortho) => {
+                assert_eq!(ortho.left(), 0.);
+                assert_eq!(ortho.right(), 800.);
+                assert_eq!(ortho.bottom(), 0.);
+                assert_eq!(ortho.top(), 600.);
+            }
+            _ => panic!("expected an orthographic projection"),
+        }
+    }
+}
@@ -3,6 +3,19 @@
 use serde::{Deserialize, Serialize};
 use winit::{self, Icon, MonitorId, WindowAttributes, WindowBuilder};
 
+/// How rendered frames are presented to the screen.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PresentMode {
+    /// Wait for the vertical blank before presenting a frame. This is vertical synchronization.
+    Fifo,
+    /// Present frames without waiting, replacing queued frames instead of tearing.
+    ///
+    /// Not every backend supports this mode. The OpenGL backend falls back to `Immediate`.
+    Mailbox,
+    /// Present frames as soon as they are ready, which can cause tearing.
+    Immediate,
+}
+
 /// Structure for holding the renderer configuration.
 ///
 /// # Examples
@@ -18,6 +31,7 @@ use winit::{self, Icon, MonitorId, WindowAttributes, WindowBuilder};
 ///     multisampling: 0,
 ///     visibility: true,
 ///     vsync: true,
+///     present_mode: None,
 ///     always_on_top: false,
 ///     decorations: true,
 ///     maximized: false,
@@ -54,6 +68,9 @@ pub struct DisplayConfig {
     /// Enables or disables vertical synchronization.
     pub vsync: bool,
 
+    /// How frames are presented to the screen. Takes precedence over `vsync` if set.
+    pub present_mode: Option<PresentMode>,
+
     /// Level of MSAA anti-aliasing.
     pub multisampling: u16,
 
@@ -100,11 +117,21 @@ impl Default for DisplayConfig {
             transparent: false,
             visibility: true,
             vsync: true,
+            present_mode: None,
         }
     }
 }
 
 impl DisplayConfig {
+    /// Returns the present mode to use, derived from `vsync` unless `present_mode` is set.
+    pub fn present_mode(&self) -> PresentMode {
+        match self.present_mode {
+            Some(mode) => mode,
+            None if self.vsync => PresentMode::Fifo,
+            None => PresentMode::Immediate,
+        }
+    }
+
     /// Creates a `WindowBuilder` using the values set in the DisplayConfig
     ///
     /// The `MonitorId` is needed to configure a fullscreen window
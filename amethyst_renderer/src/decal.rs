@@ -0,0 +1,37 @@
+//! Decals projected onto the scene.
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage},
+    math::Vector3,
+};
+
+use crate::tex::TextureHandle;
+
+/// Projects a texture onto the geometry inside a box, for bullet holes, splats or tire marks.
+///
+/// The box is centered on the `Transform` of the entity and extends `half_extents` along each of
+/// its local axes. The texture is projected along the local Z axis, with the local X and Y axes
+/// mapped to the U and V texture coordinates. An optional `Rgba` component tints the texture.
+///
+/// Decals are drawn by the `DrawDecals` pass.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decal {
+    /// The projected texture.
+    pub texture: TextureHandle,
+    /// Half the size of the projection box along each local axis.
+    pub half_extents: Vector3<f32>,
+}
+
+impl Decal {
+    /// Creates a decal projecting `texture` in a box with the given half extents.
+    pub fn new(texture: TextureHandle, half_extents: Vector3<f32>) -> Self {
+        Decal {
+            texture,
+            half_extents,
+        }
+    }
+}
+
+impl Component for Decal {
+    type Storage = DenseVecStorage<Self>;
+}
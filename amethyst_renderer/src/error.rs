@@ -23,6 +23,14 @@ pub(crate) enum Error {
     DecodeImageError,
     /// Failed to create texture.
     CreateTextureError,
+    /// Tried to update the vertices of a mesh built without dynamic buffers.
+    MeshNotDynamic,
+    /// A mesh has no vertex buffer with the requested attributes.
+    NoSuchVertexBuffer,
+    /// The graphics context has been lost.
+    ContextLost,
+    /// A heightmap with the given width, depth and number of heights can't be created.
+    InvalidHeightmap(usize, usize, usize),
 }
 
 impl error::Error for Error {}
@@ -48,6 +56,15 @@ impl fmt::Display for Error {
             ),
             DecodeImageError => write!(fmt, "Image decoding failed"),
             CreateTextureError => write!(fmt, "Failed to create texture from texture data"),
+            MeshNotDynamic => write!(fmt, "Mesh was not built with dynamic vertex buffers"),
+            NoSuchVertexBuffer => write!(fmt, "Mesh has no vertex buffer with these attributes"),
+            ContextLost => write!(fmt, "The graphics context has been lost"),
+            InvalidHeightmap(width, depth, heights) => write!(
+                fmt,
+                "Invalid heightmap of {}x{} samples with {} heights, it needs at least 2x2 \
+                 samples and one height per sample",
+                width, depth, heights,
+            ),
         }
     }
 }
@@ -30,6 +30,8 @@ where
     pub emission: Option<TexturePrefab<F>>,
     /// Emission texture offset
     pub emission_offset: TextureOffset,
+    /// Factor applied to the emission map
+    pub emission_intensity: f32,
     /// Normal map.
     pub normal: Option<TexturePrefab<F>>,
     /// Normal texture offset
@@ -66,6 +68,7 @@ where
             albedo_offset: TextureOffset::default(),
             emission: None,
             emission_offset: TextureOffset::default(),
+            emission_intensity: 1.0,
             normal: None,
             normal_offset: TextureOffset::default(),
             metallic: None,
@@ -123,6 +126,7 @@ where
             albedo_offset: self.albedo_offset.clone(),
             emission: load_handle(entity, &self.emission, tp_data, &mat_default.0.emission),
             emission_offset: self.emission_offset.clone(),
+            emission_intensity: self.emission_intensity,
             normal: load_handle(entity, &self.normal, tp_data, &mat_default.0.normal),
             normal_offset: self.normal_offset.clone(),
             metallic: load_handle(entity, &self.metallic, tp_data, &mat_default.0.metallic),
@@ -139,6 +143,7 @@ where
             caveat: load_handle(entity, &self.caveat, tp_data, &mat_default.0.caveat),
             caveat_offset: self.caveat_offset.clone(),
             alpha_cutoff: self.alpha_cutoff,
+            uniform: mat_default.0.uniform.clone(),
         };
         material.insert(entity, mtl)?;
         if self.transparent {
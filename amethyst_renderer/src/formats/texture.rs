@@ -49,6 +49,14 @@ pub struct TextureMetadata {
     /// This is usually `Srgb` for color textures, normalmaps & similar mostly use `Unorm`
     /// (which represents a value between `0.0` and `1.0`).
     pub channel: ChannelType,
+    /// Streams images, starting with a placeholder of at most this size in pixels.
+    ///
+    /// The placeholder is available as soon as the image is decoded, then the resolution
+    /// increases fourfold every frame. The full image is uploaded in chunks of about
+    /// `STREAMING_UPLOAD_BYTES` per frame, while the last placeholder is shown. The default is
+    /// `None`, uploading the full image at once.
+    #[serde(default)]
+    pub streaming: Option<u16>,
 }
 
 impl TextureMetadata {
@@ -62,6 +70,7 @@ impl TextureMetadata {
             format: SurfaceFormat::get_surface_type(),
             size: None,
             channel: ChannelType::Unorm,
+            streaming: None,
         }
     }
 
@@ -130,6 +139,12 @@ impl TextureMetadata {
         self.dynamic = d;
         self
     }
+
+    /// Stream images, starting with a placeholder of at most `size` pixels
+    pub fn with_streaming(mut self, size: u16) -> Self {
+        self.streaming = Some(size);
+        self
+    }
 }
 
 /// Texture data for loading
@@ -159,6 +174,10 @@ pub enum TextureData {
 
     /// Byte data
     U64(Vec<u64>, TextureMetadata),
+
+    /// Streamed image being uploaded, see `TextureMetadata::streaming`
+    #[serde(skip)]
+    Upload(TextureUpload),
 }
 
 impl From<[f32; 4]> for TextureData {
@@ -281,6 +300,19 @@ where
     }
 }
 
+/// Bytes of a streamed image uploaded every frame.
+pub const STREAMING_UPLOAD_BYTES: usize = 1 << 20;
+
+/// Streamed image being uploaded over several frames into its full resolution texture.
+#[derive(Clone, Debug)]
+pub struct TextureUpload {
+    image: ImageData,
+    options: TextureMetadata,
+    placeholder: Texture,
+    texture: Texture,
+    uploaded: u32,
+}
+
 /// ImageData provided by formats, can be interpreted as a texture.
 #[derive(Clone, Debug)]
 pub struct ImageData {
@@ -395,9 +427,14 @@ pub fn create_texture_asset(
 ) -> Result<ProcessingState<Texture>, Error> {
     use self::TextureData::*;
     let t = match data {
-        Image(image_data, options) => {
-            create_texture_asset_from_image(image_data, options, renderer)
-        }
+        Image(image_data, options) => match options.streaming {
+            Some(size)
+                if u32::from(size) < image_data.rgba.width().max(image_data.rgba.height()) =>
+            {
+                return stream_texture_asset(image_data, options, size, renderer);
+            }
+            _ => create_texture_asset_from_image(image_data, options, renderer),
+        },
 
         Rgba(color, options) => {
             let tb = apply_options(Texture::from_color_val(color), options);
@@ -447,6 +484,8 @@ pub fn create_texture_asset(
                 .create_texture(tb)
                 .with_context(|_| error::Error::BuildTextureError)
         }
+
+        Upload(upload) => return upload_texture_rows(upload, renderer),
     };
     t.map(ProcessingState::Loaded)
 }
@@ -469,6 +508,110 @@ where
     }
 }
 
+/// Create a texture of at most `size` pixels from the image, returning the image to stream the
+/// next, larger texture from.
+fn stream_texture_asset(
+    image: ImageData,
+    options: TextureMetadata,
+    size: u16,
+    renderer: &mut Renderer,
+) -> Result<ProcessingState<Texture>, Error> {
+    let rgba = sample_image(&image.rgba, size);
+    let lower = TextureMetadata {
+        size: None,
+        streaming: None,
+        ..options.clone()
+    };
+    let texture = create_texture_asset_from_image(ImageData { rgba }, lower, renderer)?;
+    let next = size.max(1).saturating_mul(4);
+    let (w, h) = image.rgba.dimensions();
+    if u32::from(next) < w.max(h) {
+        let next = TextureMetadata {
+            streaming: Some(next),
+            ..options
+        };
+        return Ok(ProcessingState::Streaming(
+            texture,
+            TextureData::Image(image, next),
+        ));
+    }
+
+    if w > u32::from(u16::max_value()) || h > u32::from(u16::max_value()) {
+        return Err(Error::from(error::Error::UnsupportedTextureSize(w, h)));
+    }
+    let tb = apply_options(
+        TextureBuilder::new(Vec::<u8>::new())
+            .with_format(SurfaceType::R8_G8_B8_A8)
+            .with_channel_type(options.channel)
+            .with_size(w as u16, h as u16),
+        TextureMetadata {
+            dynamic: true,
+            size: None,
+            ..options.clone()
+        },
+    );
+    let full = renderer
+        .create_empty_texture(tb)
+        .with_context(|_| error::Error::CreateTextureError)?;
+    Ok(ProcessingState::Streaming(
+        texture.clone(),
+        TextureData::Upload(TextureUpload {
+            image,
+            options,
+            placeholder: texture,
+            texture: full,
+            uploaded: 0,
+        }),
+    ))
+}
+
+/// Upload the next rows of a streamed image, showing the placeholder until the full texture is
+/// uploaded.
+fn upload_texture_rows(
+    mut upload: TextureUpload,
+    renderer: &mut Renderer,
+) -> Result<ProcessingState<Texture>, Error> {
+    let (w, h) = upload.image.rgba.dimensions();
+    let rows = upload_rows(w, h - upload.uploaded);
+    let row_bytes = w as usize * 4;
+    let start = upload.uploaded as usize * row_bytes;
+    let end = start + rows as usize * row_bytes;
+    renderer.update_texture_rows(
+        &upload.texture,
+        upload.options.channel,
+        upload.uploaded as u16,
+        rows as u16,
+        &upload.image.rgba[start..end],
+    )?;
+    upload.uploaded += rows;
+    if upload.uploaded < h {
+        Ok(ProcessingState::Streaming(
+            upload.placeholder.clone(),
+            TextureData::Upload(upload),
+        ))
+    } else {
+        Ok(ProcessingState::Loaded(upload.texture))
+    }
+}
+
+/// Number of rows of an image `width` pixels wide uploaded in a frame, out of `remaining` rows.
+fn upload_rows(width: u32, remaining: u32) -> u32 {
+    let rows = STREAMING_UPLOAD_BYTES / (width.max(1) as usize * 4);
+    (rows as u32).max(1).min(remaining)
+}
+
+/// Scale the image down to at most `size` pixels using nearest sampling, as streaming is about
+/// avoiding work on the main thread.
+fn sample_image(image: &RgbaImage, size: u16) -> RgbaImage {
+    let (w, h) = image.dimensions();
+    let scale = f64::from(size.max(1)) / f64::from(w.max(h));
+    let (sw, sh) = (
+        ((f64::from(w) * scale) as u32).max(1),
+        ((f64::from(h) * scale) as u32).max(1),
+    );
+    RgbaImage::from_fn(sw, sh, |x, y| *image.get_pixel(x * w / sw, y * h / sh))
+}
+
 fn create_texture_asset_from_image(
     image: ImageData,
     options: TextureMetadata,
@@ -546,7 +689,26 @@ mod serde_helper {
 
 #[cfg(test)]
 mod tests {
-    use super::TextureData;
+    use image::{Rgba, RgbaImage};
+
+    use super::{sample_image, upload_rows, TextureData, STREAMING_UPLOAD_BYTES};
+
+    #[test]
+    fn samples_streaming_placeholder() {
+        let image = RgbaImage::from_fn(64, 32, |x, _| Rgba([x as u8, 0, 0, 255]));
+        let placeholder = sample_image(&image, 8);
+        assert_eq!((8, 4), placeholder.dimensions());
+        assert_eq!(Rgba([8, 0, 0, 255]), *placeholder.get_pixel(1, 0));
+        assert_eq!((1, 1), sample_image(&image, 0).dimensions());
+    }
+
+    #[test]
+    fn uploads_streamed_images_in_chunks() {
+        let rows = STREAMING_UPLOAD_BYTES as u32 / (1024 * 4);
+        assert_eq!(rows, upload_rows(1024, 1024));
+        assert_eq!(3, upload_rows(1024, 3));
+        assert_eq!(1, upload_rows(1 << 20, 16));
+    }
 
     #[test]
     fn texture_data_from_f32_3() {
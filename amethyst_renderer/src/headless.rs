@@ -0,0 +1,79 @@
+//! Asset loading for applications which don't render, like dedicated servers.
+
+use amethyst_assets::{AssetStorage, Processor};
+use amethyst_core::{
+    bundle::SystemBundle,
+    ecs::prelude::{DispatcherBuilder, Resources, System, SystemData, Write},
+};
+use amethyst_error::Error;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use crate::{
+    mesh::{Mesh, MeshUpdates},
+    mtl::MaterialDefaults,
+    sprite::SpriteSheet,
+    system::create_default_mat,
+    terrain::Heightmap,
+    tex::Texture,
+};
+
+/// Completes the loading of meshes and textures without creating them, in place of the
+/// `RenderSystem`.
+///
+/// The assets and prefabs of the game load the same way as with a window, but the handles of
+/// meshes and textures never get an asset. The `MaterialDefaults` resource is inserted too, so
+/// materials can be loaded.
+#[derive(Debug, Default)]
+pub struct HeadlessAssetSystem;
+
+impl<'a> System<'a> for HeadlessAssetSystem {
+    type SystemData = (
+        Write<'a, AssetStorage<Mesh>>,
+        Write<'a, AssetStorage<Texture>>,
+        Write<'a, MeshUpdates>,
+    );
+
+    fn run(&mut self, (mut mesh_storage, mut texture_storage, mut mesh_updates): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("headless_asset_system");
+
+        mesh_storage.discard_processed();
+        texture_storage.discard_processed();
+        mesh_updates.clear();
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        let mat = create_default_mat(res);
+        res.insert(MaterialDefaults(mat));
+    }
+}
+
+/// Bundle for applications which don't render, like dedicated game servers.
+///
+/// Adds the `HeadlessAssetSystem` and the processors of sprite sheets and heightmaps, instead of
+/// the `RenderBundle`. Sprite sheets are loaded with all their sprites, without their texture.
+#[derive(Debug, Default)]
+pub struct HeadlessBundle;
+
+impl HeadlessBundle {
+    /// Creates a new headless bundle.
+    pub fn new() -> Self {
+        HeadlessBundle
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for HeadlessBundle {
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        builder.add(HeadlessAssetSystem, "headless_asset_system", &[]);
+        builder.add(
+            Processor::<SpriteSheet>::new(),
+            "sprite_sheet_processor",
+            &[],
+        );
+        builder.add(Processor::<Heightmap>::new(), "heightmap_processor", &[]);
+        Ok(())
+    }
+}
@@ -29,54 +29,69 @@
 pub use crate::{
     blink::{Blink, BlinkSystem},
     bundle::RenderBundle,
-    cam::{ActiveCamera, ActiveCameraPrefab, Camera, CameraPrefab, Projection},
+    cam::{ActiveCamera, ActiveCameraPrefab, Camera, CameraPrefab, CameraPreset, Projection},
     color::Rgba,
-    config::DisplayConfig,
+    config::{DisplayConfig, PresentMode},
     debug_drawing::{DebugLines, DebugLinesComponent},
+    decal::Decal,
     formats::{
         build_mesh_with_combo, create_mesh_asset, create_texture_asset, BmpFormat,
         ComboMeshCreator, GraphicsPrefab, ImageData, JpgFormat, MaterialPrefab, MeshCreator,
         MeshData, ObjFormat, PngFormat, TextureData, TextureFormat, TextureMetadata, TexturePrefab,
-        TgaFormat,
+        TextureUpload, TgaFormat, STREAMING_UPLOAD_BYTES,
     },
+    headless::{HeadlessAssetSystem, HeadlessBundle},
     hidden::{Hidden, HiddenPropagate},
     hide_system::HideHierarchySystem,
     input::{
         DeviceEvent, ElementState, Event, KeyboardInput, MouseButton, VirtualKeyCode, WindowEvent,
     },
-    light::{DirectionalLight, Light, LightPrefab, PointLight, SpotLight, SunLight},
-    mesh::{vertex_data, Mesh, MeshBuilder, MeshHandle, VertexBuffer},
-    mtl::{Material, MaterialDefaults, TextureOffset},
+    light::{
+        AreaLight, CastShadows, DirectionalLight, Light, LightPrefab, PointLight, SpotLight,
+        SunLight,
+    },
+    lod::{LodGroup, LodLevel, LodSystem, LodThreshold},
+    mesh::{vertex_data, Mesh, MeshBuilder, MeshHandle, MeshUpdates, VertexBuffer},
+    metrics::RenderMetrics,
+    mtl::{Material, MaterialDefaults, MaterialUniform, TextureOffset},
     pass::{
-        get_camera, set_vertex_args, DebugLinesParams, DrawDebugLines, DrawFlat, DrawFlat2D,
-        DrawFlatSeparate, DrawPbm, DrawPbmSeparate, DrawShaded, DrawShadedSeparate, DrawSkybox,
-        SkyboxColor,
+        get_camera, set_material_uniform, set_vertex_args, setup_material_uniform, ApplyBloom,
+        ApplyColorGrading, BlitBuffer, Bloom, BufferRing, ColorGrading, CompositeOit,
+        DebugLinesParams, DrawDebugLines, DrawDecals, DrawDepthPrepass, DrawFlat, DrawFlat2D,
+        DrawFlatSeparate, DrawPath2D, DrawPbm, DrawPbmSeparate, DrawShaded, DrawShadedSeparate,
+        DrawSkybox, DrawTerrain, DrawTrails, ExtractBloom, SkyboxColor, MATERIAL_UNIFORM,
     },
+    path2d::{Path2D, PathStroke},
     pipe::{
         ColorBuffer, Data, DepthBuffer, DepthMode, Effect, EffectBuilder, Init, Meta, NewEffect,
         Pipeline, PipelineBuild, PipelineBuilder, PipelineData, PolyPipeline, PolyStage,
-        PolyStages, Stage, StageBuilder, Target, TargetBuilder, Targets,
+        PolyStages, Stage, StageBuilder, Target, TargetBuffer, TargetBuilder, Targets,
     },
     renderer::Renderer,
     resources::{AmbientColor, ScreenDimensions, WindowMessages},
     screen_space::{ScreenSpace, ScreenSpaceSettings},
-    shape::{InternalShape, Shape, ShapePrefab, ShapeUpload},
+    shape::{InternalShape, Shape, ShapeBuilder, ShapePrefab, ShapeUpload},
     skinning::{
         AnimatedComboMeshCreator, AnimatedVertexBufferCombination, JointIds, JointTransforms,
-        JointTransformsPrefab, JointWeights,
+        JointTransformsPrefab, JointWeights, MAX_JOINTS,
     },
     sprite::{
         Flipped, Sprite, SpriteGrid, SpriteList, SpritePosition, SpriteRender, SpriteRenderPrefab,
         SpriteScenePrefab, SpriteSheet, SpriteSheetFormat, SpriteSheetHandle, SpriteSheetPrefab,
         Sprites, TextureCoordinates,
     },
-    sprite_visibility::{SpriteVisibility, SpriteVisibilitySortingSystem},
-    system::RenderSystem,
+    sprite_mask::SpriteMask,
+    sprite_visibility::{SpriteLayer, SpriteVisibility, SpriteVisibilitySortingSystem},
+    stats::{RenderStats, StageTiming},
+    system::{RenderEvent, RenderSystem},
+    terrain::{Heightmap, HeightmapFormat, HeightmapHandle, Terrain},
     tex::{
         FilterMethod, SamplerInfo, SurfaceType, Texture, TextureBuilder, TextureHandle, WrapMode,
     },
+    trail::{Trail, TrailPoint, TrailSystem},
     transparent::{
-        Blend, BlendChannel, BlendValue, ColorMask, Equation, Factor, Transparent, ALPHA, REPLACE,
+        Blend, BlendChannel, BlendValue, ColorMask, Equation, Factor, TransparencyFilter,
+        Transparent, ALPHA, REPLACE,
     },
     types::{Encoder, Factory, PipelineState, Resources},
     vertex::{
@@ -85,11 +100,13 @@ pub use crate::{
         VertexBufferCombination, VertexFormat, With,
     },
     visibility::{Visibility, VisibilitySortingSystem},
+    window::{FullscreenMode, MonitorInfo, Monitors},
 };
 
 mod error;
 pub mod mouse;
 pub mod pipe;
+pub mod window;
 
 #[macro_use]
 mod macros;
@@ -100,23 +117,32 @@ mod cam;
 mod color;
 mod config;
 mod debug_drawing;
+mod decal;
 mod formats;
+mod headless;
 mod hidden;
 mod hide_system;
 mod input;
 mod light;
+mod lod;
 mod mesh;
+mod metrics;
 mod mtl;
 mod pass;
+mod path2d;
 mod renderer;
 mod resources;
 mod screen_space;
 mod shape;
 mod skinning;
 mod sprite;
+mod sprite_mask;
 mod sprite_visibility;
+mod stats;
 mod system;
+mod terrain;
 mod tex;
+mod trail;
 mod transparent;
 mod types;
 mod vertex;
@@ -2,8 +2,8 @@
 //!
 //! TODO: Remove redundant padding once `#[repr(align(...))]` stabilizes.
 
-use amethyst_assets::{PrefabData, ProgressCounter};
-use amethyst_core::ecs::prelude::{Component, DenseVecStorage, Entity, WriteStorage};
+use amethyst_assets::PrefabData;
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage, Entity, NullStorage, WriteStorage};
 use amethyst_derive::PrefabData;
 use amethyst_error::Error;
 
@@ -18,7 +18,7 @@ use crate::{color::Rgba, resources::AmbientColor};
 pub enum Light {
     /// An area light.
     /// FIXME: Missing implementation!
-    Area,
+    Area(AreaLight),
     /// A directional light.
     Directional(DirectionalLight),
     /// A point light.
@@ -38,6 +38,8 @@ pub struct DirectionalLight {
     pub color: Rgba,
     /// Direction that the light is pointing.
     pub direction: [f32; 3], //TODO: Replace with a nalgebra type
+    /// Factor applied to the color of the light.
+    pub intensity: f32,
 }
 
 impl Default for DirectionalLight {
@@ -45,6 +47,7 @@ impl Default for DirectionalLight {
         DirectionalLight {
             color: Rgba::default(),
             direction: [-1.0, -1.0, -1.0],
+            intensity: 1.0,
         }
     }
 }
@@ -86,6 +89,7 @@ pub struct PointLight {
     /// Brightness of the light source, in lumens.
     pub intensity: f32,
     /// Maximum radius of the point light's affected area.
+    #[serde(alias = "range")]
     pub radius: f32,
     /// Smoothness of the light-to-dark transition from the center to the
     /// radius.
@@ -114,7 +118,8 @@ impl From<PointLight> for Light {
 #[derive(Clone, ConstantBuffer, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default)]
 pub struct SpotLight {
-    /// Opening angle of the light cone in radians.
+    /// Opening angle of the light cone in radians, measured from its direction.
+    #[serde(alias = "outer_angle")]
     pub angle: f32,
     /// Color of the light in RGBA8 format.
     pub color: Rgba,
@@ -127,6 +132,10 @@ pub struct SpotLight {
     /// Smoothness of the light-to-dark transition from the center to the
     /// radius.
     pub smoothness: f32,
+    /// Angle of the cone at full brightness in radians, measured like `angle`. When larger
+    /// than `0`, the light fades linearly from `inner_angle` to `angle` instead of using
+    /// `smoothness`.
+    pub inner_angle: f32,
 }
 
 impl Default for SpotLight {
@@ -138,6 +147,7 @@ impl Default for SpotLight {
             intensity: 10.0,
             range: 10.0,
             smoothness: 4.0,
+            inner_angle: 0.0,
         }
     }
 }
@@ -180,14 +190,106 @@ impl From<SunLight> for Light {
     }
 }
 
+/// A rectangular area light source, emitting light along `direction`.
+#[repr(C)]
+#[derive(Clone, ConstantBuffer, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AreaLight {
+    /// Color of the light in RGBA8 format.
+    pub color: Rgba,
+    /// Direction that the light is pointing.
+    pub direction: [f32; 3], //TODO: Replace with a nalgebra type
+    /// Brightness of the light source, in lumens.
+    pub intensity: f32,
+    /// Range/length of the light source.
+    pub range: f32,
+    /// Width and height of the emitting rectangle.
+    pub size: [f32; 2],
+}
+
+impl Default for AreaLight {
+    fn default() -> Self {
+        AreaLight {
+            color: Rgba::default(),
+            direction: [0.0, -1.0, 0.0],
+            intensity: 10.0,
+            range: 10.0,
+            size: [1.0, 1.0],
+        }
+    }
+}
+
+impl From<AreaLight> for Light {
+    fn from(area: AreaLight) -> Self {
+        Light::Area(area)
+    }
+}
+
 impl Component for Light {
     type Storage = DenseVecStorage<Self>;
 }
 
+/// Marks a `Light` as casting shadows.
+///
+/// The built-in passes don't draw shadows yet, this is read by custom shadow mapping passes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CastShadows;
+
+impl Component for CastShadows {
+    type Storage = NullStorage<Self>;
+}
+
 /// Prefab for lighting
-#[derive(Default, Clone, Serialize, Deserialize, PrefabData)]
+///
+/// Every light type can be described in a scene file, along with the ambient color of the
+/// scene:
+///
+/// ```ron
+/// light: (
+///     light: Spot((
+///         color: (1.0, 0.9, 0.8, 1.0),
+///         intensity: 5.0,
+///         range: 8.0,
+///         inner_angle: 0.3,
+///         outer_angle: 0.5,
+///         direction: (0.0, -1.0, 0.0),
+///     )),
+///     ambient_color: ((0.01, 0.01, 0.01, 1.0)),
+///     shadows: true,
+/// ),
+/// ```
+#[derive(Default, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct LightPrefab {
     light: Option<Light>,
     ambient_color: Option<AmbientColor>,
+    shadows: bool,
+}
+
+impl<'a> PrefabData<'a> for LightPrefab {
+    type SystemData = (
+        <Light as PrefabData<'a>>::SystemData,
+        <AmbientColor as PrefabData<'a>>::SystemData,
+        WriteStorage<'a, CastShadows>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        entities: &[Entity],
+        children: &[Entity],
+    ) -> Result<(), Error> {
+        if let Some(ref light) = self.light {
+            light.add_to_entity(entity, &mut system_data.0, entities, children)?;
+        }
+        if let Some(ref ambient_color) = self.ambient_color {
+            ambient_color.add_to_entity(entity, &mut system_data.1, entities, children)?;
+        }
+        if self.shadows {
+            system_data.2.insert(entity, CastShadows)?;
+        }
+        Ok(())
+    }
 }
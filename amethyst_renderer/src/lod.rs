@@ -0,0 +1,165 @@
+//! Level of detail selection for meshes.
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Join, Read, ReadStorage, System, WriteStorage,
+    },
+    math::{self as na, Point3},
+    Transform,
+};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    mesh::MeshHandle,
+};
+
+/// When a `LodLevel` may be used.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum LodThreshold {
+    /// The level is used while the entity is at most this far away from the camera.
+    Distance(f32),
+    /// The level is used while the bounding sphere of the `LodGroup` covers at least this
+    /// fraction of the screen height.
+    ScreenSize(f32),
+}
+
+impl LodThreshold {
+    /// Returns `true` if a level with this threshold may be used for an entity at `distance`
+    /// from the camera, covering `screen_size` of the screen height.
+    pub fn accepts(self, distance: f32, screen_size: f32) -> bool {
+        match self {
+            LodThreshold::Distance(max) => distance <= max,
+            LodThreshold::ScreenSize(min) => screen_size >= min,
+        }
+    }
+}
+
+/// A mesh of a `LodGroup`, with the condition for using it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LodLevel {
+    /// The mesh to draw.
+    pub mesh: MeshHandle,
+    /// When to draw the mesh.
+    pub threshold: LodThreshold,
+}
+
+/// Meshes of decreasing detail for an entity.
+///
+/// The `LodSystem` replaces the `MeshHandle` of the entity with the mesh of the first level
+/// accepting the current distance to the camera. If no level accepts it, the last level is used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LodGroup {
+    /// The levels, from most to least detailed.
+    pub levels: Vec<LodLevel>,
+    /// Radius of the bounding sphere of the meshes, used by `LodThreshold::ScreenSize`.
+    pub radius: f32,
+}
+
+impl LodGroup {
+    /// Create a new group from the given levels, ordered from most to least detailed.
+    pub fn new(levels: Vec<LodLevel>, radius: f32) -> Self {
+        LodGroup { levels, radius }
+    }
+
+    /// Returns the level to use for an entity at `distance` from the camera, covering
+    /// `screen_size` of the screen height.
+    pub fn select(&self, distance: f32, screen_size: f32) -> Option<&LodLevel> {
+        self.levels
+            .iter()
+            .find(|level| level.threshold.accepts(distance, screen_size))
+            .or_else(|| self.levels.last())
+    }
+}
+
+impl Component for LodGroup {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Selects the mesh of every `LodGroup` based on the active camera.
+///
+/// Should run after the `Transform`s have been updated for the current frame.
+#[derive(Default)]
+pub struct LodSystem;
+
+impl LodSystem {
+    /// Create a new `LodSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for LodSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, LodGroup>,
+        WriteStorage<'a, MeshHandle>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, active, camera, transform, lod_group, mut mesh): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("lod_system");
+
+        let origin = Point3::origin();
+        let camera = active
+            .entity
+            .and_then(|entity| {
+                camera
+                    .get(entity)
+                    .into_iter()
+                    .zip(transform.get(entity))
+                    .next()
+            })
+            .or_else(|| (&camera, &transform).join().next());
+        let (camera, camera_transform) = match camera {
+            Some(camera) => camera,
+            None => return,
+        };
+        let camera_position = camera_transform.global_matrix().transform_point(&origin);
+        // Scale from world size to screen height fraction, perspective projections additionally
+        // divide by the distance.
+        let projection_scale = camera.proj[(1, 1)];
+        let perspective = camera.proj[(3, 3)] == 0.0;
+
+        for (entity, transform, lod_group) in (&*entities, &transform, &lod_group).join() {
+            let position = transform.global_matrix().transform_point(&origin);
+            let distance = na::distance(&position, &camera_position).as_f32();
+            let screen_size = if perspective {
+                lod_group.radius * projection_scale / distance.max(std::f32::EPSILON)
+            } else {
+                lod_group.radius * projection_scale
+            };
+
+            if let Some(level) = lod_group.select(distance, screen_size) {
+                if mesh.get(entity) != Some(&level.mesh) {
+                    mesh.insert(entity, level.mesh.clone())
+                        .expect("Unreachable: entity is alive");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LodThreshold;
+
+    #[test]
+    fn threshold_accepts() {
+        assert!(LodThreshold::Distance(10.0).accepts(5.0, 0.0));
+        assert!(LodThreshold::Distance(10.0).accepts(10.0, 0.0));
+        assert!(!LodThreshold::Distance(10.0).accepts(15.0, 1.0));
+        assert!(LodThreshold::ScreenSize(0.25).accepts(100.0, 0.5));
+        assert!(!LodThreshold::ScreenSize(0.25).accepts(0.0, 0.1));
+    }
+}
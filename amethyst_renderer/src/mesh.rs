@@ -9,12 +9,14 @@ use gfx::Primitive;
 use log::warn;
 use serde::{Deserialize, Serialize};
 
-use amethyst_assets::Handle;
+use amethyst_assets::{AssetStorage, Handle};
 use amethyst_core::math::{Matrix4, Point3, Rotation3, Translation3, Unit, Vector3};
 use amethyst_error::Error;
 
 use crate::{
-    types::{Factory, RawBuffer, Slice},
+    error,
+    metrics::RenderMetrics,
+    types::{Encoder, Factory, RawBuffer, Slice},
     vertex::{Attributes, VertexFormat},
 };
 
@@ -35,6 +37,14 @@ pub trait VertexData {
 
     /// Build `VertexBuffer`
     fn build(&self, factory: &mut Factory) -> Result<VertexBuffer, Error>;
+
+    /// Build `VertexBuffer` as a dynamic buffer, whose initial data is uploaded through
+    /// `encoder`
+    fn build_dynamic(
+        &self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+    ) -> Result<VertexBuffer, Error>;
 }
 
 /// Construct new vertex data from raw data and vertex format
@@ -58,29 +68,63 @@ where
     }
 
     fn build(&self, factory: &mut Factory) -> Result<VertexBuffer, Error> {
-        use gfx::{
-            buffer::Role,
-            memory::{cast_slice, Bind},
-            Factory,
-        };
-
-        let verts = self.0.as_ref();
-        let slice = cast_slice(verts);
-        let stride = slice.len().checked_div(verts.len()).unwrap_or_else(|| {
-            warn!("Loading mesh with zero vertices.");
-            0
-        });
-        let role = Role::Vertex;
-        let bind = Bind::empty();
+        create_vertex_buffer(self.0.as_ref(), factory, None)
+    }
 
-        let vbuf = factory.create_buffer_immutable_raw(slice, stride, role, bind)?;
-        Ok(VertexBuffer {
-            attrs: V::ATTRIBUTES,
-            raw: vbuf,
-        })
+    fn build_dynamic(
+        &self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+    ) -> Result<VertexBuffer, Error> {
+        create_vertex_buffer(self.0.as_ref(), factory, Some(encoder))
     }
 }
 
+/// Creates the buffer of `verts`, as a dynamic buffer whose initial data is uploaded through
+/// `encoder` if given.
+fn create_vertex_buffer<V>(
+    verts: &[V],
+    factory: &mut Factory,
+    encoder: Option<&mut Encoder>,
+) -> Result<VertexBuffer, Error>
+where
+    V: VertexFormat,
+{
+    use gfx::{
+        buffer::{Info, Role},
+        memory::{cast_slice, Bind, Usage},
+        Factory,
+    };
+
+    let slice = cast_slice(verts);
+    let stride = slice.len().checked_div(verts.len()).unwrap_or_else(|| {
+        warn!("Loading mesh with zero vertices.");
+        0
+    });
+    let role = Role::Vertex;
+    let bind = Bind::empty();
+
+    let vbuf = match encoder {
+        Some(encoder) => {
+            let info = Info {
+                role,
+                usage: Usage::Dynamic,
+                bind,
+                size: slice.len(),
+                stride,
+            };
+            let vbuf = factory.create_buffer_raw(info)?;
+            update_buffer(encoder, &vbuf, 0, slice)?;
+            vbuf
+        }
+        None => factory.create_buffer_immutable_raw(slice, stride, role, bind)?,
+    };
+    Ok(VertexBuffer {
+        attrs: V::ATTRIBUTES,
+        raw: vbuf,
+    })
+}
+
 /// Set of vertex data
 #[doc(hidden)]
 pub trait VertexDataSet {
@@ -92,6 +136,14 @@ pub trait VertexDataSet {
 
     /// Build `VertexBuffer`s
     fn build(&self, factory: &mut Factory) -> Result<Self::VertexBufferIter, Error>;
+
+    /// Build `VertexBuffer`s as dynamic buffers, whose initial data is uploaded through
+    /// `encoder`
+    fn build_dynamic(
+        &self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+    ) -> Result<Self::VertexBufferIter, Error>;
 }
 
 impl<H> VertexDataSet for (H, ())
@@ -108,6 +160,15 @@ where
         let (ref head, _) = *self;
         Ok(once(head.build(factory)?))
     }
+
+    fn build_dynamic(
+        &self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+    ) -> Result<Self::VertexBufferIter, Error> {
+        let (ref head, _) = *self;
+        Ok(once(head.build_dynamic(factory, encoder)?))
+    }
 }
 
 impl<H, T> VertexDataSet for (H, T)
@@ -126,6 +187,16 @@ where
         let (ref head, ref tail) = *self;
         Ok(once(head.build(factory)?).chain(tail.build(factory)?))
     }
+
+    fn build_dynamic(
+        &self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+    ) -> Result<Self::VertexBufferIter, Error> {
+        let (ref head, ref tail) = *self;
+        let head = head.build_dynamic(factory, encoder)?;
+        Ok(once(head).chain(tail.build_dynamic(factory, encoder)?))
+    }
 }
 
 /// A handle to a mesh.
@@ -137,6 +208,7 @@ pub struct Mesh {
     slice: Slice,
     transform: Matrix4<f32>,
     vbufs: Vec<VertexBuffer>,
+    dynamic: bool,
 }
 
 impl Mesh {
@@ -185,6 +257,59 @@ impl Mesh {
         None
     }
 
+    /// Returns the size of the vertex buffers of the mesh, in bytes.
+    pub(crate) fn size_in_bytes(&self) -> usize {
+        self.vbufs.iter().map(|vbuf| vbuf.raw.get_info().size).sum()
+    }
+
+    /// Returns `true` if the vertex buffers of the mesh can be updated, see
+    /// `MeshBuilder::with_dynamic_buffers`.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
+    /// Overwrites the vertices of the buffer in format `V`, starting at vertex `offset`.
+    ///
+    /// The mesh needs to be dynamic, and the updated range needs to fit in the buffer. The
+    /// update is recorded into `encoder`, so it is executed before any later draw call.
+    pub fn update_vertices<V>(
+        &self,
+        encoder: &mut Encoder,
+        offset: usize,
+        vertices: &[V],
+    ) -> Result<(), Error>
+    where
+        V: VertexFormat,
+    {
+        use gfx::memory::cast_slice;
+
+        self.update_raw(
+            encoder,
+            V::ATTRIBUTES,
+            offset * V::size(),
+            cast_slice(vertices),
+        )
+    }
+
+    fn update_raw(
+        &self,
+        encoder: &mut Encoder,
+        attributes: Attributes<'_>,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        if !self.dynamic {
+            return Err(error::Error::MeshNotDynamic.into());
+        }
+        let vbuf = self
+            .vbufs
+            .iter()
+            .find(|vbuf| vbuf.attrs == attributes)
+            .ok_or(error::Error::NoSuchVertexBuffer)?;
+        update_buffer(encoder, &vbuf.raw, offset, data)?;
+        Ok(())
+    }
+
     /// Returns associated `Slice`
     pub fn slice(&self) -> &Slice {
         &self.slice
@@ -206,6 +331,8 @@ pub struct MeshBuilder<T> {
     prim: Primitive,
     transform: Matrix4<f32>,
     vertices: T,
+    #[serde(default)]
+    dynamic: bool,
 }
 
 impl<D, V> MeshBuilder<((D, PhantomData<V>), ())>
@@ -220,6 +347,7 @@ where
             prim: Primitive::TriangleList,
             transform: Matrix4::identity(),
             vertices: (vertex_data(verts), ()),
+            dynamic: false,
         }
     }
 }
@@ -239,6 +367,7 @@ where
             prim: self.prim,
             transform: self.transform,
             vertices: (vertex_data(verts), self.vertices),
+            dynamic: self.dynamic,
         }
     }
 
@@ -281,10 +410,41 @@ where
         self
     }
 
+    /// Creates the vertex buffers as dynamic buffers, so their vertices can be updated after
+    /// the mesh was built, see `Mesh::update_vertices` and `MeshUpdates`.
+    ///
+    /// Dynamic meshes need to be built with `build_dynamic` or `Renderer::create_mesh`.
+    pub fn with_dynamic_buffers(mut self) -> Self {
+        self.dynamic = true;
+        self
+    }
+
+    /// Returns `true` if the mesh is built with dynamic vertex buffers.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
     /// Builds and returns the new mesh.
+    ///
+    /// Meshes using dynamic buffers are built with immutable buffers instead, as uploading
+    /// their initial data requires an `Encoder`, see `build_dynamic`.
     pub fn build(self, fac: &mut Factory) -> Result<Mesh, Error> {
+        if self.dynamic {
+            warn!("Building a dynamic mesh without an encoder, its vertices cannot be updated.");
+        }
+        self.build_with(fac, None)
+    }
+
+    /// Builds and returns the new mesh using dynamic vertex buffers, recording the upload of
+    /// the initial vertices into `encoder`.
+    pub fn build_dynamic(self, fac: &mut Factory, encoder: &mut Encoder) -> Result<Mesh, Error> {
+        self.build_with(fac, Some(encoder))
+    }
+
+    fn build_with(self, fac: &mut Factory, encoder: Option<&mut Encoder>) -> Result<Mesh, Error> {
         use gfx::IndexBuffer;
         let count = self.vertices.len();
+        let dynamic = encoder.is_some();
 
         let slice = Slice {
             start: 0,
@@ -297,11 +457,101 @@ where
         Ok(Mesh {
             slice,
             transform: self.transform,
-            vbufs: self.vertices.build(fac)?.collect(),
+            vbufs: match encoder {
+                Some(encoder) => self.vertices.build_dynamic(fac, encoder)?.collect(),
+                None => self.vertices.build(fac)?.collect(),
+            },
+            dynamic,
         })
     }
 }
 
+/// Vertex updates of dynamic meshes, uploaded by the `RenderSystem` before the next frame is
+/// drawn.
+///
+/// Systems queue the new vertices of a mesh here every frame, for example to animate trails
+/// or deformable meshes, instead of recreating the mesh asset. Updates are applied in the order
+/// they were queued, and updates of meshes which are not loaded yet are dropped.
+#[derive(Debug, Default)]
+pub struct MeshUpdates {
+    updates: Vec<MeshUpdate>,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
+struct MeshUpdate {
+    mesh: MeshHandle,
+    attributes: Attributes<'static>,
+    offset: usize,
+    len: usize,
+}
+
+impl MeshUpdates {
+    /// Queues an update overwriting the vertices of the buffer in format `V` of the mesh,
+    /// starting at vertex `offset`.
+    pub fn update<V>(&mut self, mesh: &MeshHandle, offset: usize, vertices: &[V])
+    where
+        V: VertexFormat,
+    {
+        use gfx::memory::cast_slice;
+
+        let data = cast_slice(vertices);
+        self.data.extend_from_slice(data);
+        self.updates.push(MeshUpdate {
+            mesh: mesh.clone(),
+            attributes: V::ATTRIBUTES,
+            offset: offset * V::size(),
+            len: data.len(),
+        });
+    }
+
+    /// Returns `true` if no update is queued.
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+
+    /// Drops all queued updates.
+    pub(crate) fn clear(&mut self) {
+        self.updates.clear();
+        self.data.clear();
+    }
+
+    /// Records all queued updates into `encoder`, keeping the allocated memory for the updates
+    /// of the next frame. The uploads are counted in `metrics`.
+    pub(crate) fn apply(
+        &mut self,
+        storage: &AssetStorage<Mesh>,
+        encoder: &mut Encoder,
+        metrics: &mut RenderMetrics,
+    ) {
+        let mut start = 0;
+        for update in self.updates.drain(..) {
+            let data = &self.data[start..start + update.len];
+            start += update.len;
+            if let Some(mesh) = storage.get(&update.mesh) {
+                match mesh.update_raw(encoder, update.attributes, update.offset, data) {
+                    Ok(()) => metrics.record_upload(data.len()),
+                    Err(err) => warn!("Failed to update mesh vertices: {}", err),
+                }
+            }
+        }
+        self.data.clear();
+    }
+}
+
+fn update_buffer(
+    encoder: &mut Encoder,
+    buffer: &RawBuffer,
+    offset: usize,
+    data: &[u8],
+) -> Result<(), Error> {
+    use gfx::{handle::Buffer, memory::Typed};
+
+    let buffer: Buffer<_, u8> = Typed::new(buffer.clone());
+    encoder.update_buffer(&buffer, data, offset)?;
+    Ok(())
+}
+
 /// Check that attributes are sorted
 fn check_attributes_are_sorted(attrs: Attributes<'_>) -> bool {
     let mut last = 0;
@@ -0,0 +1,71 @@
+//! Per-frame draw call and resource statistics.
+
+use gfx::Primitive;
+
+use crate::types::Slice;
+
+/// Draw call and resource statistics of the last rendered frame.
+///
+/// This resource is updated by the `RenderSystem` at the end of every frame. Draw calls and
+/// buffer uploads are recorded by the effects of the pipeline, buffer uploads also include the
+/// meshes created and updated by the `RenderSystem` during the same frame. The texture figures
+/// are taken from the `AssetStorage<Texture>`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderMetrics {
+    /// Number of draw calls issued.
+    pub draw_calls: usize,
+    /// Number of instances drawn, counting one for non-instanced draws.
+    pub instances: usize,
+    /// Number of triangles drawn across all instances.
+    pub triangles: usize,
+    /// Number of buffer creations and updates with initial data.
+    pub buffer_uploads: usize,
+    /// Total amount of bytes uploaded to buffers.
+    pub buffer_upload_bytes: usize,
+    /// Number of textures currently loaded in the texture storage.
+    pub textures: usize,
+    /// Estimated memory used by the loaded textures, in bytes.
+    pub texture_memory: usize,
+}
+
+impl RenderMetrics {
+    /// Records a draw call of `slice` using the given primitive topology.
+    pub(crate) fn record_draw(&mut self, slice: &Slice, prim: Primitive) {
+        let instances = slice.instances.map(|(count, _)| count).unwrap_or(1) as usize;
+        let triangles = match prim {
+            Primitive::TriangleList
+            | Primitive::TriangleStrip
+            | Primitive::TriangleListAdjacency
+            | Primitive::TriangleStripAdjacency => slice.get_prim_count(prim) as usize * instances,
+            _ => 0,
+        };
+
+        self.draw_calls += 1;
+        self.instances += instances;
+        self.triangles += triangles;
+    }
+
+    /// Records an upload of `bytes` bytes into a GPU buffer.
+    pub(crate) fn record_upload(&mut self, bytes: usize) {
+        self.buffer_uploads += 1;
+        self.buffer_upload_bytes += bytes;
+    }
+
+    /// Adds the draw calls and buffer uploads recorded in `other`.
+    pub(crate) fn add_counts(&mut self, other: &RenderMetrics) {
+        self.draw_calls += other.draw_calls;
+        self.instances += other.instances;
+        self.triangles += other.triangles;
+        self.buffer_uploads += other.buffer_uploads;
+        self.buffer_upload_bytes += other.buffer_upload_bytes;
+    }
+
+    /// Resets the draw calls and buffer uploads, keeping the texture figures.
+    pub(crate) fn reset_counts(&mut self) {
+        self.draw_calls = 0;
+        self.instances = 0;
+        self.triangles = 0;
+        self.buffer_uploads = 0;
+        self.buffer_upload_bytes = 0;
+    }
+}
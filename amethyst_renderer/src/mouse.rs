@@ -1,7 +1,7 @@
 //! Util functions that change how the mouse moves and looks.
 
 use log::error;
-use winit::MouseCursor;
+use winit::{dpi::LogicalPosition, MouseCursor};
 
 use super::WindowMessages;
 
@@ -10,6 +10,23 @@ pub fn hide_cursor(msg: &mut WindowMessages) {
     msg.send_command(move |win| win.hide_cursor(true));
 }
 
+/// Show the cursor again after `hide_cursor`, without releasing it.
+pub fn show_cursor(msg: &mut WindowMessages) {
+    msg.send_command(move |win| win.hide_cursor(false));
+}
+
+/// Move the cursor to the center of the window, so a grabbed cursor never reaches its edges.
+pub fn center_cursor(msg: &mut WindowMessages) {
+    msg.send_command(move |win| {
+        if let Some(size) = win.get_inner_size() {
+            let center = LogicalPosition::new(size.width / 2.0, size.height / 2.0);
+            if let Err(err) = win.set_cursor_position(center) {
+                error!("Unable to center the cursor. Error: {:?}", err);
+            }
+        }
+    });
+}
+
 /// Set the cursor back to normal/visible.
 pub fn release_cursor(msg: &mut WindowMessages) {
     msg.send_command(move |win| {
@@ -1,7 +1,10 @@
 //! Physically-based material.
 
+use std::{mem, slice, sync::Arc};
+
 use amethyst_core::ecs::prelude::{Component, DenseVecStorage};
 
+use glsl_layout::Uniform;
 use serde::{Deserialize, Serialize};
 
 use crate::tex::TextureHandle;
@@ -37,6 +40,9 @@ pub struct Material {
     pub emission: TextureHandle,
     /// Emission texture offset
     pub emission_offset: TextureOffset,
+    /// Factor applied to the emission map. Values above `1.0` make the material glow when the
+    /// emission is also written to a bloom buffer, see `DrawPbm::with_emission_output`.
+    pub emission_intensity: f32,
     /// Normal map.
     pub normal: TextureHandle,
     /// Normal texture offset
@@ -57,12 +63,54 @@ pub struct Material {
     pub caveat: TextureHandle,
     /// Caveat texture offset
     pub caveat_offset: TextureOffset,
+    /// Optional user-defined uniform data, see `MaterialUniform`.
+    pub uniform: Option<MaterialUniform>,
 }
 
 impl Component for Material {
     type Storage = DenseVecStorage<Self>;
 }
 
+/// User-defined uniform payload of a `Material`.
+///
+/// The value is laid out according to `std140` rules when created, and is uploaded as-is to the
+/// `MaterialUniform` constant buffer by passes that declare it (see `setup_material_uniform` and
+/// `set_material_uniform`). In GLSL the block is declared as:
+///
+/// ```glsl
+/// layout(std140) uniform MaterialUniform {
+///     // fields matching the Rust type
+/// };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialUniform {
+    data: Arc<[u8]>,
+}
+
+impl MaterialUniform {
+    /// Create a new uniform payload from a value with a `std140` layout.
+    pub fn new<T: Uniform>(value: T) -> Self {
+        let std140 = value.std140();
+        let bytes = unsafe {
+            slice::from_raw_parts(
+                &std140 as *const T::Std140 as *const u8,
+                mem::size_of::<T::Std140>(),
+            )
+        };
+        MaterialUniform { data: bytes.into() }
+    }
+
+    /// Size of the payload in bytes.
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Raw `std140` bytes of the payload.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
 /// A resource providing default textures for `Material`.
 /// These will be be used by the renderer in case a texture
 /// handle points to a texture which is not loaded already.
@@ -0,0 +1,61 @@
+//! Pass copying a buffer of another render target.
+
+use amethyst_error::Error;
+
+use crate::{
+    pass::util::fullscreen_slice,
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, TargetBuffer,
+    },
+    types::{Encoder, Factory},
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/blit.glsl");
+
+/// Copies a buffer of a render target into the target of the stage, stretching it to fit.
+///
+/// Useful to present a scene rendered into an offscreen target on the backbuffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlitBuffer {
+    target: String,
+    buffer: TargetBuffer,
+}
+
+impl BlitBuffer {
+    /// Copy the color buffer with index `index` of the target named `target`.
+    pub fn color_buf<N: Into<String>>(target: N, index: usize) -> Self {
+        BlitBuffer {
+            target: target.into(),
+            buffer: TargetBuffer::Color(index),
+        }
+    }
+}
+
+impl<'a> PassData<'a> for BlitBuffer {
+    type Data = ();
+}
+
+impl Pass for BlitBuffer {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_target_input("source", self.target.clone(), self.buffer)
+            .with_output("color", None);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        _: (),
+    ) {
+        effect.add_target_inputs();
+        effect.draw(&fullscreen_slice(), encoder);
+        effect.clear();
+    }
+}
@@ -0,0 +1,218 @@
+//! Post-processing passes making emissive materials glow.
+
+use gfx::preset::blend::ADD;
+use gfx_core::state::{Blend, ColorMask};
+
+use amethyst_core::ecs::prelude::Read;
+use amethyst_error::Error;
+
+use crate::{
+    pass::util::fullscreen_slice,
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, EffectBuilder, NewEffect, TargetBuffer,
+    },
+    types::{Encoder, Factory},
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/bloom.glsl");
+
+/// Scale of the emission written to emission buffers, must match `EMISSION_RANGE` in `pbm.glsl`.
+///
+/// Render targets store 8 bits per channel, so the emission is divided by this value to keep
+/// intensities above `1.0` until the bloom is applied.
+const EMISSION_RANGE: f32 = 8.0;
+
+/// Bloom applied by the `ExtractBloom` and `ApplyBloom` passes.
+///
+/// The resource can be changed at any time, for example to flash the screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bloom {
+    /// Brightness of the glow added to the scene.
+    pub intensity: f32,
+    /// Distance in texels between the samples of the blur, widening the glow.
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            intensity: 1.0,
+            radius: 2.0,
+        }
+    }
+}
+
+/// Blurs an emission buffer horizontally into the target of the stage, which should have the
+/// same size as the emission buffer and be cleared to zero.
+///
+/// Emission buffers are written by passes built with `with_emission_output`, into the second
+/// color buffer of their target. The blurred emission is added to the scene by `ApplyBloom`:
+///
+/// ```rust,ignore
+/// let pipe = Pipeline::build()
+///     .with_target(Target::named("scene").with_num_color_bufs(2).with_depth_buf(true))
+///     .with_target(Target::named("bloom"))
+///     .with_stage(
+///         Stage::with_target("scene")
+///             .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+///             .with_pass(DrawPbm::<PosNormTangTex>::new().with_emission_output()),
+///     )
+///     .with_stage(
+///         Stage::with_target("bloom")
+///             .clear_target([0.0; 4], None)
+///             .with_pass(ExtractBloom::new("scene")),
+///     )
+///     .with_stage(Stage::with_target("scene").with_pass(ApplyBloom::new("bloom")))
+///     .with_stage(Stage::with_backbuffer().with_pass(BlitBuffer::color_buf("scene", 0)));
+/// ```
+///
+/// Transparent entities drawn with an emission output blend their emission over the emission
+/// behind them, like their color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractBloom {
+    target: String,
+}
+
+impl ExtractBloom {
+    /// Blur the emission stored in the second color buffer of the target named `target`.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        ExtractBloom {
+            target: target.into(),
+        }
+    }
+}
+
+impl<'a> PassData<'a> for ExtractBloom {
+    type Data = Option<Read<'a, Bloom>>;
+}
+
+impl Pass for ExtractBloom {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_target_input("source", self.target.clone(), TargetBuffer::Color(1))
+            .with_raw_global("direction")
+            .with_raw_global("scale")
+            .with_output("color", None);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        bloom: <Self as PassData<'a>>::Data,
+    ) {
+        let radius = bloom
+            .map(|bloom| bloom.radius)
+            .unwrap_or_else(|| Bloom::default().radius);
+        draw_blur(effect, encoder, [radius, 0.0], 1.0);
+    }
+}
+
+/// Blurs the result of `ExtractBloom` vertically and adds it to the target of the stage.
+///
+/// See `ExtractBloom` for how to set up the pipeline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplyBloom {
+    target: String,
+}
+
+impl ApplyBloom {
+    /// Add the bloom extracted into the first color buffer of the target named `target`.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        ApplyBloom {
+            target: target.into(),
+        }
+    }
+}
+
+impl<'a> PassData<'a> for ApplyBloom {
+    type Data = Option<Read<'a, Bloom>>;
+}
+
+impl Pass for ApplyBloom {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_target_input("source", self.target.clone(), TargetBuffer::Color(0))
+            .with_raw_global("direction")
+            .with_raw_global("scale")
+            .with_blended_output("color", ColorMask::all(), ADD, None);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        bloom: <Self as PassData<'a>>::Data,
+    ) {
+        let bloom = bloom.map(|bloom| bloom.clone()).unwrap_or_default();
+        draw_blur(
+            effect,
+            encoder,
+            [0.0, bloom.radius],
+            bloom.intensity * EMISSION_RANGE,
+        );
+    }
+}
+
+fn draw_blur(effect: &mut Effect, encoder: &mut Encoder, direction: [f32; 2], scale: f32) {
+    effect.add_target_inputs();
+    effect.update_global("direction", direction);
+    effect.update_global("scale", scale);
+    effect.draw(&fullscreen_slice(), encoder);
+    effect.clear();
+}
+
+/// Defines `EMISSION_OUTPUT` in a fragment shader, right after its `#version` directive.
+pub(crate) fn emission_shader(src: &[u8]) -> Vec<u8> {
+    let version_end = src
+        .windows(b"#version".len())
+        .position(|window| window == b"#version")
+        .and_then(|start| {
+            src[start..]
+                .iter()
+                .position(|&c| c == b'\n')
+                .map(|end| start + end + 1)
+        })
+        .unwrap_or(0);
+    let mut shader = Vec::with_capacity(src.len() + 32);
+    shader.extend_from_slice(&src[..version_end]);
+    shader.extend_from_slice(b"#define EMISSION_OUTPUT\n");
+    shader.extend_from_slice(&src[version_end..]);
+    shader
+}
+
+/// Adds the `out_emission` output of a shader built with `emission_shader`, blended like the
+/// color output of the pass.
+pub(crate) fn setup_emission_output(
+    builder: &mut EffectBuilder<'_>,
+    transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+) {
+    builder.with_extra_output(
+        "out_emission",
+        transparency.map(|(mask, blend, _)| (mask, blend)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::emission_shader;
+
+    #[test]
+    fn defines_emission_output_after_version() {
+        let shader = emission_shader(b"// Comment\n\n#version 150 core\n\nvoid main() {}\n");
+        assert_eq!(
+            &shader[..],
+            &b"// Comment\n\n#version 150 core\n#define EMISSION_OUTPUT\n\nvoid main() {}\n"[..]
+        );
+    }
+}
@@ -0,0 +1,513 @@
+//! Clustered light culling for the physically based passes.
+//!
+//! The view frustum is split into a grid of clusters, tiled in screen space and sliced along
+//! the view depth. Each frame, point and spot lights are assigned to the clusters their range
+//! overlaps, and the fragment shader only evaluates the lights of the cluster it lies in.
+//!
+//! OpenGL 3.2 has no storage buffers, so the light data, the cluster grid and the light index
+//! lists are stored in float textures, read with `texelFetch` by `pbm.glsl`.
+
+use std::mem;
+
+use gfx::{
+    format::{ChannelType, Format, SurfaceType},
+    memory::{cast_slice, Bind, Usage},
+    texture::{
+        AaMode, FilterMethod, Info, Kind, RawImageInfo, ResourceDesc, SamplerInfo, WrapMode,
+    },
+};
+use glsl_layout::*;
+use log::{error, warn};
+
+use amethyst_core::{
+    ecs::prelude::{Join, ReadStorage},
+    math::{convert, Matrix4, Point3, Vector4},
+    Transform,
+};
+
+use crate::{
+    cam::Camera,
+    light::Light,
+    pass::shaded_util::{camera_position, directional_lights, DirectionalLightPod},
+    pipe::{Effect, EffectBuilder},
+    resources::{AmbientColor, ScreenDimensions},
+    types::{Encoder, Factory, RawShaderResourceView, RawTexture, Sampler},
+};
+
+/// Number of clusters along the x, y and z axes of the view frustum.
+const CLUSTERS: [usize; 3] = [16, 9, 24];
+
+/// Width of the texture holding the light indices, must match `INDEX_WIDTH` in `pbm.glsl`.
+const INDEX_WIDTH: usize = 1024;
+
+/// Number of texels storing each light.
+const LIGHT_TEXELS: usize = 4;
+
+/// Maximum number of point and spot lights, limited by the height of the light texture.
+const MAX_LIGHTS: usize = 4096;
+
+#[derive(Clone, Copy, Debug, Uniform)]
+pub(crate) struct ClusterArgs {
+    view: mat4,
+    screen_size: vec2,
+    depth_near: float,
+    depth_far: float,
+    clusters_x: uint,
+    clusters_y: uint,
+    clusters_z: uint,
+    logarithmic: uint,
+    directional_light_count: uint,
+}
+
+/// Depth range of the clusters, in view space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DepthSlices {
+    near: f32,
+    far: f32,
+    /// Perspective projections slice the depth exponentially, so that clusters keep roughly
+    /// the same proportions. Orthographic projections slice it linearly.
+    logarithmic: bool,
+}
+
+impl DepthSlices {
+    fn new(proj: &Matrix4<f32>, inverse: &Matrix4<f32>) -> Self {
+        let depth = |z| -unproject(inverse, 0.0, 0.0, z).z;
+        let (near, far) = (depth(-1.0), depth(1.0));
+        let (near, far) = (near.min(far), far.max(near + std::f32::EPSILON));
+        DepthSlices {
+            near,
+            far,
+            logarithmic: near > 0.0 && proj[(3, 3)] == 0.0,
+        }
+    }
+
+    /// Returns the depth where the slice `k` starts.
+    fn depth(&self, k: usize) -> f32 {
+        let t = k as f32 / CLUSTERS[2] as f32;
+        if self.logarithmic {
+            self.near * (self.far / self.near).powf(t)
+        } else {
+            self.near + (self.far - self.near) * t
+        }
+    }
+
+    /// Returns the slice containing `depth`, as a fraction. `depth` must not be in front of the
+    /// near plane.
+    fn slice(&self, depth: f32) -> f32 {
+        let t = if self.logarithmic {
+            (depth / self.near).ln() / (self.far / self.near).ln()
+        } else {
+            (depth - self.near) / (self.far - self.near)
+        };
+        t * CLUSTERS[2] as f32
+    }
+}
+
+/// A float texture read by the shader as an array of data.
+#[derive(Clone)]
+struct DataTexture {
+    texture: RawTexture,
+    view: RawShaderResourceView,
+    surface: SurfaceType,
+    width: usize,
+    height: usize,
+}
+
+impl DataTexture {
+    fn create(
+        factory: &mut Factory,
+        surface: SurfaceType,
+        width: usize,
+        height: usize,
+    ) -> Option<Self> {
+        use gfx::{format::Swizzle, Factory};
+
+        let info = Info {
+            kind: Kind::D2(width as u16, height as u16, AaMode::Single),
+            levels: 1,
+            format: surface,
+            bind: Bind::SHADER_RESOURCE | Bind::TRANSFER_DST,
+            usage: Usage::Dynamic,
+        };
+        let texture = factory
+            .create_texture_raw(info, Some(ChannelType::Float), None)
+            .map_err(|err| error!("Failed to create a light cluster texture: {:?}", err))
+            .ok()?;
+        let desc = ResourceDesc {
+            channel: ChannelType::Float,
+            layer: None,
+            min: 0,
+            max: 0,
+            swizzle: Swizzle::new(),
+        };
+        let view = factory
+            .view_texture_as_shader_resource_raw(&texture, desc)
+            .map_err(|err| error!("Failed to create a light cluster texture view: {:?}", err))
+            .ok()?;
+        Some(DataTexture {
+            texture,
+            view,
+            surface,
+            width,
+            height,
+        })
+    }
+
+    /// Makes sure `slot` holds a texture with at least `height` rows, recreating it if needed.
+    fn reserve(
+        slot: &mut Option<Self>,
+        factory: &mut Factory,
+        surface: SurfaceType,
+        width: usize,
+        height: usize,
+    ) -> bool {
+        if slot.as_ref().map_or(true, |tex| tex.height < height) {
+            *slot = DataTexture::create(factory, surface, width, height.next_power_of_two());
+        }
+        slot.is_some()
+    }
+
+    /// Uploads whole rows of texels, starting at the first row.
+    fn upload(&self, encoder: &mut Encoder, rows: usize, data: &[u8]) {
+        if rows == 0 {
+            return;
+        }
+        let info = RawImageInfo {
+            xoffset: 0,
+            yoffset: 0,
+            zoffset: 0,
+            width: self.width as u16,
+            height: rows as u16,
+            depth: 1,
+            format: Format(self.surface, ChannelType::Float),
+            mipmap: 0,
+        };
+        if let Err(err) = encoder.update_texture_raw(&self.texture, None, info, data) {
+            error!("Failed to upload a light cluster texture: {:?}", err);
+        }
+    }
+}
+
+/// Light culling state of a physically based pass.
+///
+/// `update` assigns the lights to the clusters and uploads the result along with the other
+/// lighting arguments, and `bind` adds the cluster textures to the effect before each draw.
+#[derive(Clone, Default)]
+pub(crate) struct LightClusters {
+    proj: Option<Matrix4<f32>>,
+    slices: Option<DepthSlices>,
+    bounds: Vec<(Point3<f32>, Point3<f32>)>,
+    lists: Vec<Vec<u32>>,
+    lights: Vec<[f32; 4]>,
+    grid: Vec<[f32; 4]>,
+    indices: Vec<f32>,
+    light_texture: Option<DataTexture>,
+    grid_texture: Option<DataTexture>,
+    index_texture: Option<DataTexture>,
+    sampler: Option<Sampler>,
+}
+
+impl LightClusters {
+    /// Culls the lights against the clusters of the camera frustum and updates the lighting
+    /// arguments of the effect.
+    ///
+    /// Returns `false` if the cluster textures couldn't be created, in which case nothing must
+    /// be drawn.
+    pub fn update(
+        &mut self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        light: &ReadStorage<'_, Light>,
+        transform: &ReadStorage<'_, Transform>,
+        ambient: &AmbientColor,
+        camera: Option<(&Camera, &Transform)>,
+        screen: &ScreenDimensions,
+    ) -> bool {
+        let (proj, view) = camera
+            .map(|(camera, camera_transform)| {
+                let view = convert::<_, Matrix4<f32>>(*camera_transform.global_matrix())
+                    .try_inverse()
+                    .unwrap_or_else(Matrix4::identity);
+                (camera.proj, view)
+            })
+            .unwrap_or_else(|| (Matrix4::identity(), Matrix4::identity()));
+        if self.proj != Some(proj) {
+            self.build_bounds(&proj);
+        }
+        let slices = self
+            .slices
+            .expect("Unreachable: cluster bounds were just built");
+
+        self.lights.clear();
+        for list in &mut self.lists {
+            list.clear();
+        }
+        for (light, transform) in (light, transform).join() {
+            let position = convert::<_, Matrix4<f32>>(*transform.global_matrix())
+                .column(3)
+                .xyz();
+            let texels = match *light {
+                Light::Point(ref light) => {
+                    let color: [f32; 3] = light.color.into();
+                    [
+                        [position.x, position.y, position.z, light.radius],
+                        [color[0], color[1], color[2], light.intensity],
+                        [0.0; 4],
+                        [light.smoothness, 0.0, 0.0, 0.0],
+                    ]
+                }
+                Light::Spot(ref light) => {
+                    let color: [f32; 3] = light.color.into();
+                    let [x, y, z] = light.direction;
+                    [
+                        [position.x, position.y, position.z, light.range],
+                        [color[0], color[1], color[2], light.intensity],
+                        [x, y, z, light.angle.cos()],
+                        [
+                            light.smoothness,
+                            1.0,
+                            light.inner_angle.cos(),
+                            light.inner_angle,
+                        ],
+                    ]
+                }
+                _ => continue,
+            };
+            let index = self.lights.len() / LIGHT_TEXELS;
+            if index == MAX_LIGHTS {
+                warn!(
+                    "More than {} point and spot lights, the others are ignored",
+                    MAX_LIGHTS
+                );
+                break;
+            }
+            self.lights.extend_from_slice(&texels);
+
+            let center = view.transform_point(&Point3::from(position));
+            self.assign(&slices, index as u32, &center, texels[0][3].max(0.0));
+        }
+
+        self.grid.clear();
+        self.indices.clear();
+        for list in &self.lists {
+            self.grid
+                .push([self.indices.len() as f32, list.len() as f32, 0.0, 0.0]);
+            self.indices.extend(list.iter().map(|&index| index as f32));
+        }
+        let index_rows = (self.indices.len() + INDEX_WIDTH - 1) / INDEX_WIDTH;
+        self.indices.resize(index_rows * INDEX_WIDTH, 0.0);
+        let light_rows = self.lights.len() / LIGHT_TEXELS;
+
+        let [clusters_x, clusters_y, clusters_z] = CLUSTERS;
+        if DataTexture::reserve(
+            &mut self.light_texture,
+            factory,
+            SurfaceType::R32_G32_B32_A32,
+            LIGHT_TEXELS,
+            light_rows.max(1),
+        ) && DataTexture::reserve(
+            &mut self.grid_texture,
+            factory,
+            SurfaceType::R32_G32_B32_A32,
+            clusters_x * clusters_y,
+            clusters_z,
+        ) && DataTexture::reserve(
+            &mut self.index_texture,
+            factory,
+            SurfaceType::R32,
+            INDEX_WIDTH,
+            index_rows.max(1),
+        ) {
+            if let Some(ref tex) = self.light_texture {
+                tex.upload(encoder, light_rows, cast_slice(&self.lights));
+            }
+            if let Some(ref tex) = self.grid_texture {
+                tex.upload(encoder, clusters_z, cast_slice(&self.grid));
+            }
+            if let Some(ref tex) = self.index_texture {
+                tex.upload(encoder, index_rows, cast_slice(&self.indices));
+            }
+        }
+        if self.sampler.is_none() {
+            use gfx::Factory;
+            self.sampler = Some(
+                factory.create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp)),
+            );
+        }
+
+        let directional_lights = directional_lights(light);
+        let view_matrix: [[f32; 4]; 4] = view.into();
+        let cluster_args = ClusterArgs {
+            view: view_matrix.into(),
+            screen_size: [screen.width(), screen.height()].into(),
+            depth_near: slices.near,
+            depth_far: slices.far,
+            clusters_x: clusters_x as u32,
+            clusters_y: clusters_y as u32,
+            clusters_z: clusters_z as u32,
+            logarithmic: slices.logarithmic.into(),
+            directional_light_count: directional_lights.len() as u32,
+        };
+        effect.update_constant_buffer("ClusterArgs", &cluster_args.std140(), encoder);
+        effect.update_buffer("DirectionalLights", &directional_lights[..], encoder);
+        effect.update_global("ambient_color", Into::<[f32; 3]>::into(*ambient.as_ref()));
+        effect.update_global("camera_position", camera_position(camera));
+
+        self.light_texture.is_some() && self.grid_texture.is_some() && self.index_texture.is_some()
+    }
+
+    /// Adds the cluster textures to the effect, they must be bound before any other texture.
+    pub fn bind(&self, effect: &mut Effect) {
+        let textures = [&self.light_texture, &self.grid_texture, &self.index_texture];
+        if let Some(ref sampler) = self.sampler {
+            for tex in textures.iter().filter_map(|tex| tex.as_ref()) {
+                effect.data.textures.push(tex.view.clone());
+                effect.data.samplers.push(sampler.clone());
+            }
+        }
+    }
+
+    /// Computes the view space bounding box of every cluster.
+    fn build_bounds(&mut self, proj: &Matrix4<f32>) {
+        let inverse = proj.try_inverse().unwrap_or_else(Matrix4::identity);
+        let slices = DepthSlices::new(proj, &inverse);
+        let [clusters_x, clusters_y, clusters_z] = CLUSTERS;
+
+        self.bounds.clear();
+        for k in 0..clusters_z {
+            let depths = [slices.depth(k), slices.depth(k + 1)];
+            for j in 0..clusters_y {
+                for i in 0..clusters_x {
+                    let mut min = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+                    let mut max = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+                    for &(x, y) in &[(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)] {
+                        let x = -1.0 + 2.0 * x as f32 / clusters_x as f32;
+                        let y = -1.0 + 2.0 * y as f32 / clusters_y as f32;
+                        // Walk along the line from the near plane to the far plane.
+                        let near = unproject(&inverse, x, y, -1.0);
+                        let far = unproject(&inverse, x, y, 1.0);
+                        for &depth in &depths {
+                            let t = (depth + near.z) / (near.z - far.z);
+                            let corner = near + (far - near) * t;
+                            min = min.inf(&corner);
+                            max = max.sup(&corner);
+                        }
+                    }
+                    self.bounds.push((min, max));
+                }
+            }
+        }
+
+        self.lists.resize_with(self.bounds.len(), Vec::new);
+        self.proj = Some(*proj);
+        self.slices = Some(slices);
+    }
+
+    /// Adds the light to the clusters overlapped by its bounding sphere, given in view space.
+    fn assign(&mut self, slices: &DepthSlices, index: u32, center: &Point3<f32>, radius: f32) {
+        let depth = -center.z;
+        if depth + radius < slices.near || depth - radius > slices.far {
+            return;
+        }
+        let max_slice = CLUSTERS[2] - 1;
+        let first = slices.slice((depth - radius).max(slices.near)).max(0.0) as usize;
+        let last =
+            (slices.slice((depth + radius).min(slices.far)).max(0.0) as usize).min(max_slice);
+
+        let slice_len = CLUSTERS[0] * CLUSTERS[1];
+        for cluster in first.min(max_slice) * slice_len..(last + 1) * slice_len {
+            let (ref min, ref max) = self.bounds[cluster];
+            if sphere_intersects_aabb(center, radius, min, max) {
+                self.lists[cluster].push(index);
+            }
+        }
+    }
+}
+
+/// Sets up the lighting arguments read by `pbm.glsl`. Must be called before setting up the
+/// material textures, see `LightClusters::bind`.
+pub(crate) fn setup_cluster_buffers(builder: &mut EffectBuilder<'_>) {
+    builder
+        .with_raw_constant_buffer(
+            "ClusterArgs",
+            mem::size_of::<<ClusterArgs as Uniform>::Std140>(),
+            1,
+        )
+        .with_raw_constant_buffer(
+            "DirectionalLights",
+            mem::size_of::<<DirectionalLightPod as Uniform>::Std140>(),
+            16,
+        )
+        .with_raw_global("ambient_color")
+        .with_raw_global("camera_position")
+        .with_texture("cluster_lights")
+        .with_texture("cluster_grid")
+        .with_texture("cluster_indices");
+}
+
+/// Transforms a point from normalized device coordinates to view space.
+fn unproject(inverse_proj: &Matrix4<f32>, x: f32, y: f32, z: f32) -> Point3<f32> {
+    let point = inverse_proj * Vector4::new(x, y, z, 1.0);
+    Point3::from(point.xyz() / point.w)
+}
+
+fn sphere_intersects_aabb(
+    center: &Point3<f32>,
+    radius: f32,
+    min: &Point3<f32>,
+    max: &Point3<f32>,
+) -> bool {
+    let closest = center.coords.sup(&min.coords).inf(&max.coords);
+    (closest - center.coords).norm_squared() <= radius * radius
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::math::{Matrix4, Perspective3, Point3};
+
+    use super::{sphere_intersects_aabb, DepthSlices, CLUSTERS};
+
+    #[test]
+    fn perspective_slices() {
+        let proj = Perspective3::new(1.0, 1.0, 0.1, 100.0).to_homogeneous();
+        let inverse = proj.try_inverse().unwrap();
+        let slices = DepthSlices::new(&proj, &inverse);
+        assert!(slices.logarithmic);
+        assert!((slices.near - 0.1).abs() < 1e-3);
+        assert!((slices.far - 100.0).abs() < 1e-1);
+        assert!((slices.slice(slices.depth(5)) - 5.0).abs() < 1e-3);
+        assert!((slices.slice(slices.far) - CLUSTERS[2] as f32).abs() < 1e-3);
+    }
+
+    #[test]
+    fn orthographic_slices() {
+        let slices = DepthSlices::new(&Matrix4::identity(), &Matrix4::identity());
+        assert!(!slices.logarithmic);
+        assert_eq!(slices.slice(slices.near), 0.0);
+        assert_eq!(slices.depth(CLUSTERS[2]), slices.far);
+    }
+
+    #[test]
+    fn sphere_aabb() {
+        let (min, max) = (Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+        assert!(sphere_intersects_aabb(
+            &Point3::new(0.5, 0.5, 0.5),
+            0.1,
+            &min,
+            &max
+        ));
+        assert!(sphere_intersects_aabb(
+            &Point3::new(1.5, 0.5, 0.5),
+            0.6,
+            &min,
+            &max
+        ));
+        assert!(!sphere_intersects_aabb(
+            &Point3::new(2.0, 2.0, 2.0),
+            1.0,
+            &min,
+            &max
+        ));
+    }
+}
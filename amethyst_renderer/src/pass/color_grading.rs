@@ -0,0 +1,117 @@
+//! Post-processing pass grading the colors of a render target with a lookup table.
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::ecs::prelude::Read;
+use amethyst_error::Error;
+
+use crate::{
+    pass::util::{add_texture, fullscreen_slice},
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, TargetBuffer,
+    },
+    tex::{Texture, TextureHandle},
+    types::{Encoder, Factory},
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/color_grading.glsl");
+
+/// Color grading applied by the `ApplyColorGrading` pass.
+///
+/// The lookup table is a strip of `N` square slices of `N` x `N` texels, one slice per blue
+/// value, like the common 1024 x 32 PNG for a 32 x 32 x 32 table. Within a slice red increases
+/// from left to right and green from bottom to top. It should be loaded with
+/// `TextureMetadata::unorm()` so the stored colors are used as is.
+///
+/// The resource can be changed at any time to switch or fade between lookup tables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorGrading {
+    /// The lookup table, or `None` to leave the colors unchanged.
+    pub lut: Option<TextureHandle>,
+    /// How much of the graded color is mixed into the original color, from 0 to 1.
+    pub intensity: f32,
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        ColorGrading {
+            lut: None,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Copies the color buffer of a render target into the target of the stage, grading its colors
+/// according to the `ColorGrading` resource.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApplyColorGrading {
+    target: String,
+}
+
+impl ApplyColorGrading {
+    /// Grade the first color buffer of the target named `target`.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        ApplyColorGrading {
+            target: target.into(),
+        }
+    }
+}
+
+impl<'a> PassData<'a> for ApplyColorGrading {
+    type Data = (
+        Option<Read<'a, ColorGrading>>,
+        Read<'a, AssetStorage<Texture>>,
+    );
+}
+
+impl Pass for ApplyColorGrading {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_target_input("source", self.target.clone(), TargetBuffer::Color(0))
+            .with_texture("lut")
+            .with_raw_global("lut_size")
+            .with_raw_global("intensity")
+            .with_output("color", None);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        (grading, tex_storage): <Self as PassData<'a>>::Data,
+    ) {
+        effect.add_target_inputs();
+
+        let lut = grading.as_ref().and_then(|grading| {
+            grading
+                .lut
+                .as_ref()
+                .and_then(|lut| tex_storage.get(lut))
+                .map(|lut| (lut, grading.intensity))
+        });
+        match lut {
+            Some((lut, intensity)) => {
+                add_texture(effect, lut);
+                effect.update_global("lut_size", lut.size().1 as f32);
+                effect.update_global("intensity", intensity);
+            }
+            None => {
+                // Without a lookup table the source is bound in its place and left unchanged.
+                let source = effect.data.textures[0].clone();
+                let sampler = effect.data.samplers[0].clone();
+                effect.data.textures.push(source);
+                effect.data.samplers.push(sampler);
+                effect.update_global("lut_size", 1.0);
+                effect.update_global("intensity", 0.0);
+            }
+        }
+
+        effect.draw(&fullscreen_slice(), encoder);
+        effect.clear();
+    }
+}
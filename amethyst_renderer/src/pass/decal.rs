@@ -0,0 +1,176 @@
+//! Decal projection pass.
+
+use std::mem;
+
+use gfx::pso::buffer::ElemStride;
+use glsl_layout::{mat4, Uniform};
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    ecs::prelude::{Join, Read, ReadStorage},
+    math::{convert, Matrix4, Vector3},
+    transform::Transform,
+    Float,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    decal::Decal,
+    hidden::{Hidden, HiddenPropagate},
+    mesh::Mesh,
+    pass::util::{add_texture, get_camera, set_vertex_args, setup_vertex_args},
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, TargetBuffer,
+    },
+    shape::Shape,
+    tex::Texture,
+    transparent::{ColorMask, ALPHA},
+    types::{Encoder, Factory},
+    vertex::{PosTex, VertexFormat},
+    Rgba,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/decal.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/decal.glsl");
+
+#[repr(C, align(16))]
+#[derive(Clone, Copy, Debug, Uniform)]
+struct DecalArgs {
+    inv_view_proj: mat4,
+    inv_model: mat4,
+}
+
+/// Draws `Decal`s, projecting their textures onto the geometry already drawn.
+///
+/// The geometry is reconstructed from the depth buffer of the target named in `new`, so the
+/// scene must be drawn into an offscreen target with a depth buffer before this pass runs. The
+/// pass does not test or write depth, it only reads it, so it can be added to a stage drawing
+/// into that same target.
+#[derive(Clone, Debug)]
+pub struct DrawDecals {
+    target: String,
+    mesh: Option<Mesh>,
+}
+
+impl DrawDecals {
+    /// Create instance of `DrawDecals` pass, reading the depth buffer of the target `target`.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        DrawDecals {
+            target: target.into(),
+            mesh: None,
+        }
+    }
+}
+
+impl<'a> PassData<'a> for DrawDecals {
+    type Data = (
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        Read<'a, AssetStorage<Texture>>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, Decal>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Rgba>,
+    );
+}
+
+impl Pass for DrawDecals {
+    fn compile(&mut self, mut effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let verts = Shape::Cube.generate_vertices::<Vec<PosTex>>(None);
+        self.mesh = Some(Mesh::build(verts).build(&mut effect.factory)?);
+
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .with_front_face_culling()
+            .with_raw_vertex_buffer(PosTex::ATTRIBUTES, PosTex::size() as ElemStride, 0)
+            .with_raw_constant_buffer(
+                "DecalArgs",
+                mem::size_of::<<DecalArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_target_input("depth", self.target.clone(), TargetBuffer::Depth)
+            .with_texture("albedo")
+            .with_blended_output("color", ColorMask::all(), ALPHA, None);
+        setup_vertex_args(&mut builder);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        (
+            active,
+            camera,
+            tex_storage,
+            hidden,
+            hidden_prop,
+            decal,
+            transform,
+            rgba,
+        ): <Self as PassData<'a>>::Data,
+    ) {
+        let camera = match get_camera(active, &camera, &transform) {
+            Some(camera) => camera,
+            None => return,
+        };
+        let mesh = self
+            .mesh
+            .as_ref()
+            .expect("Pass doesn't seem to be compiled.");
+        let vbuf = match mesh.buffer(PosTex::ATTRIBUTES) {
+            Some(vbuf) => vbuf.clone(),
+            None => return,
+        };
+
+        // A degenerate camera, e.g. with a zero sized window, can't project any decal.
+        let view: Matrix4<f32> = match camera.1.global_matrix().try_inverse() {
+            Some(view) => convert(view),
+            None => return,
+        };
+        let inv_view_proj: [[f32; 4]; 4] = match (camera.0.proj * view).try_inverse() {
+            Some(inv_view_proj) => inv_view_proj.into(),
+            None => return,
+        };
+
+        for (decal, transform, rgba, _, _) in
+            (&decal, &transform, rgba.maybe(), !&hidden, !&hidden_prop).join()
+        {
+            let texture = match tex_storage.get(&decal.texture) {
+                Some(texture) => texture,
+                None => continue,
+            };
+            let model = transform.global_matrix()
+                * Matrix4::new_nonuniform_scaling(&convert::<_, Vector3<Float>>(
+                    decal.half_extents,
+                ));
+            let inv_model: [[f32; 4]; 4] = match convert::<_, Matrix4<f32>>(model).try_inverse() {
+                Some(inv_model) => inv_model.into(),
+                None => continue,
+            };
+
+            set_vertex_args(
+                effect,
+                encoder,
+                Some(camera),
+                &model,
+                rgba.cloned().unwrap_or(Rgba::WHITE),
+            );
+            let decal_args = DecalArgs {
+                inv_view_proj: inv_view_proj.into(),
+                inv_model: inv_model.into(),
+            };
+            effect.update_constant_buffer("DecalArgs", &decal_args.std140(), encoder);
+
+            effect.data.vertex_bufs.push(vbuf.clone());
+            effect.add_target_inputs();
+            add_texture(effect, texture);
+            effect.draw(mesh.slice(), encoder);
+            effect.clear();
+        }
+    }
+}
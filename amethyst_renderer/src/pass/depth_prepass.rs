@@ -0,0 +1,210 @@
+//! Depth only pass for opaque geometry.
+
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+use gfx::pso::buffer::ElemStride;
+use gfx_core::state::ColorMask;
+use glsl_layout::Uniform;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    transform::Transform,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    hidden::{Hidden, HiddenPropagate},
+    mesh::{Mesh, MeshHandle},
+    mtl::{Material, MaterialDefaults},
+    pass::{
+        skinning::{create_skinning_effect, setup_skinning_buffers},
+        util::{draw_mesh, get_camera, setup_textures, TextureType, VertexArgs},
+    },
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    skinning::JointTransforms,
+    tex::Texture,
+    transparent::{Transparent, REPLACE},
+    types::{Encoder, Factory},
+    vertex::{Position, Query, TexCoord},
+    visibility::Visibility,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/basic.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/depth.glsl");
+static TEXTURES: [TextureType; 1] = [TextureType::Albedo];
+
+/// Writes the depth of all opaque meshes without shading them.
+///
+/// Add this pass before the mesh passes of a stage and build those with `with_depth_prepass`, so
+/// they only test against the depth written here. Every pixel is then shaded at most once,
+/// which reduces overdraw in scenes with expensive fragment shaders. `Transparent` entities are
+/// not drawn by this pass, and texels with an albedo alpha below the `alpha_cutoff` of the
+/// material are left out.
+///
+/// Like the mesh passes, the pass only draws skinned meshes with `with_vertex_skinning`, and
+/// then only draws skinned meshes. Scenes with both need a prepass for each.
+///
+/// # Type Parameters
+///
+/// * `V`: `VertexFormat`
+#[derive(Derivative, Clone, Debug, PartialEq)]
+#[derivative(Default(bound = "V: Query<(Position, TexCoord)>"))]
+pub struct DrawDepthPrepass<V> {
+    skinning: bool,
+    _marker: PhantomData<V>,
+}
+
+impl<V> DrawDepthPrepass<V>
+where
+    V: Query<(Position, TexCoord)>,
+{
+    /// Create instance of `DrawDepthPrepass` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Enable vertex skinning
+    pub fn with_vertex_skinning(mut self) -> Self {
+        self.skinning = true;
+        self
+    }
+}
+
+impl<'a, V> PassData<'a> for DrawDepthPrepass<V>
+where
+    V: Query<(Position, TexCoord)>,
+{
+    type Data = (
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        Read<'a, AssetStorage<Mesh>>,
+        Read<'a, AssetStorage<Texture>>,
+        ReadExpect<'a, MaterialDefaults>,
+        Option<Read<'a, Visibility>>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, MeshHandle>,
+        ReadStorage<'a, Material>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, JointTransforms>,
+        ReadStorage<'a, Transparent>,
+    );
+}
+
+impl<V> Pass for DrawDepthPrepass<V>
+where
+    V: Query<(Position, TexCoord)>,
+{
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        use std::mem;
+        let mut builder = if self.skinning {
+            create_skinning_effect(effect, FRAG_SRC)
+        } else {
+            effect.simple(VERT_SRC, FRAG_SRC)
+        };
+        builder
+            .with_raw_constant_buffer(
+                "VertexArgs",
+                mem::size_of::<<VertexArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
+        if self.skinning {
+            setup_skinning_buffers(&mut builder);
+        }
+        setup_textures(&mut builder, &TEXTURES);
+        builder.with_raw_global("alpha_cutoff").with_blended_output(
+            "color",
+            ColorMask::empty(),
+            REPLACE,
+            Some(DepthMode::LessEqualWrite),
+        );
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        (
+            active,
+            camera,
+            mesh_storage,
+            tex_storage,
+            material_defaults,
+            visibility,
+            hidden,
+            hidden_prop,
+            mesh,
+            material,
+            transform,
+            joints,
+            transparent,
+        ): <Self as PassData<'a>>::Data,
+    ) {
+        let camera = get_camera(active, &camera, &transform);
+
+        let skinning = self.skinning;
+        let mut draw =
+            |joint: Option<&JointTransforms>, mesh: &MeshHandle, material: &Material, transform| {
+                // Without skinning, skinned meshes would be drawn in their bind pose.
+                if joint.is_some() && !skinning {
+                    return;
+                }
+                effect.update_global("alpha_cutoff", material.alpha_cutoff);
+                draw_mesh(
+                    encoder,
+                    effect,
+                    skinning,
+                    mesh_storage.get(mesh),
+                    joint,
+                    &tex_storage,
+                    Some(material),
+                    &material_defaults,
+                    None,
+                    camera,
+                    Some(transform),
+                    &[V::QUERIED_ATTRIBUTES],
+                    &TEXTURES,
+                );
+            };
+
+        match visibility {
+            None => {
+                for (joint, mesh, material, transform, _, _, _) in (
+                    joints.maybe(),
+                    &mesh,
+                    &material,
+                    &transform,
+                    !&transparent,
+                    !&hidden,
+                    !&hidden_prop,
+                )
+                    .join()
+                {
+                    draw(joint, mesh, material, transform);
+                }
+            }
+            Some(ref visibility) => {
+                for (joint, mesh, material, transform, _) in (
+                    joints.maybe(),
+                    &mesh,
+                    &material,
+                    &transform,
+                    &visibility.visible_unordered,
+                )
+                    .join()
+                {
+                    draw(joint, mesh, material, transform);
+                }
+            }
+        }
+    }
+}
@@ -19,12 +19,16 @@ use crate::{
     hidden::{Hidden, HiddenPropagate},
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
-    pass::util::{default_transparency, draw_mesh, get_camera, setup_textures, VertexArgs},
+    pass::util::{
+        default_transparency, draw_mesh, get_camera, mesh_depth_mode, setup_oit_outputs,
+        setup_textures, VertexArgs,
+    },
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
     tex::Texture,
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory},
     vertex::{Position, Query, TexCoord},
     visibility::Visibility,
@@ -47,6 +51,9 @@ pub struct DrawFlat<V> {
     _marker: PhantomData<(V)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    depth_prepass: bool,
+    oit: bool,
 }
 
 impl<V> DrawFlat<V>
@@ -85,6 +92,30 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only test against the depth buffer written by a `DrawDepthPrepass` earlier in the stage,
+    /// instead of writing to it.
+    pub fn with_depth_prepass(mut self) -> Self {
+        self.depth_prepass = true;
+        self
+    }
+
+    /// Draw `Transparent` entities with weighted blended order-independent transparency.
+    ///
+    /// The pass writes into a target with two color buffers, see `CompositeOit` for how to set
+    /// up the pipeline. Only `Transparent` entities are drawn, and the transparency settings of the
+    /// pass are ignored.
+    pub fn with_weighted_oit(mut self) -> Self {
+        self.oit = true;
+        self.filter = TransparencyFilter::Transparent;
+        self
+    }
 }
 
 impl<'a, V> PassData<'a> for DrawFlat<V>
@@ -104,6 +135,7 @@ where
         ReadStorage<'a, Material>,
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transparent>,
     );
 }
 
@@ -112,8 +144,9 @@ where
     V: Query<(Position, TexCoord)>,
 {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let frag_src = if self.oit { FRAG_SRC_OIT } else { FRAG_SRC };
         use std::mem;
-        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        let mut builder = effect.simple(VERT_SRC, frag_src);
         builder
             .with_raw_constant_buffer(
                 "VertexArgs",
@@ -122,10 +155,22 @@ where
             )
             .with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
         setup_textures(&mut builder, &TEXTURES);
-        match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
-        };
+        if self.oit {
+            setup_oit_outputs(&mut builder);
+        } else {
+            match self.transparency {
+                Some((mask, blend, depth)) => builder.with_blended_output(
+                    "color",
+                    mask,
+                    blend,
+                    mesh_depth_mode(depth, self.depth_prepass),
+                ),
+                None => builder.with_output(
+                    "color",
+                    mesh_depth_mode(Some(DepthMode::LessEqualWrite), self.depth_prepass),
+                ),
+            };
+        }
         builder.build()
     }
 
@@ -147,22 +192,28 @@ where
             material,
             transform,
             rgba,
+            transparent,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &transform);
 
         match visibility {
             None => {
-                for (mesh, material, transform, rgba, _, _) in (
+                for (mesh, material, transform, rgba, transparent, _, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
                     draw_mesh(
                         encoder,
                         effect,
@@ -181,34 +232,16 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, transform, rgba, _) in (
-                    &mesh,
-                    &material,
-                    &transform,
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                )
-                    .join()
-                {
-                    draw_mesh(
-                        encoder,
-                        effect,
-                        false,
-                        mesh_storage.get(mesh),
-                        None,
-                        &tex_storage,
-                        Some(material),
-                        &material_defaults,
-                        rgba,
-                        camera,
-                        Some(transform),
-                        &[V::QUERIED_ATTRIBUTES],
-                        &TEXTURES,
-                    );
-                }
-
-                for entity in &visibility.visible_ordered {
-                    if let Some(mesh) = mesh.get(*entity) {
+                if self.filter.draws_opaque() {
+                    for (mesh, material, transform, rgba, _) in (
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
                         draw_mesh(
                             encoder,
                             effect,
@@ -216,16 +249,38 @@ where
                             mesh_storage.get(mesh),
                             None,
                             &tex_storage,
-                            material.get(*entity),
+                            Some(material),
                             &material_defaults,
-                            rgba.get(*entity),
+                            rgba,
                             camera,
-                            transform.get(*entity),
+                            Some(transform),
                             &[V::QUERIED_ATTRIBUTES],
                             &TEXTURES,
                         );
                     }
                 }
+
+                if self.filter.draws_transparent() {
+                    for entity in &visibility.visible_ordered {
+                        if let Some(mesh) = mesh.get(*entity) {
+                            draw_mesh(
+                                encoder,
+                                effect,
+                                false,
+                                mesh_storage.get(mesh),
+                                None,
+                                &tex_storage,
+                                material.get(*entity),
+                                &material_defaults,
+                                rgba.get(*entity),
+                                camera,
+                                transform.get(*entity),
+                                &[V::QUERIED_ATTRIBUTES],
+                                &TEXTURES,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
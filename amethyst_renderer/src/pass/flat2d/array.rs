@@ -0,0 +1,233 @@
+//! Texture array shared by the sprites of a `DrawFlat2D` pass.
+
+use fnv::FnvHashMap as HashMap;
+use gfx::{
+    format::{ChannelType, SurfaceType},
+    memory::{Bind, Usage},
+    texture::{
+        AaMode, FilterMethod, Info, Kind, RawImageInfo, ResourceDesc, SamplerInfo, WrapMode,
+    },
+};
+use log::warn;
+
+use amethyst_assets::{Handle, WeakHandle};
+
+use crate::{
+    pipe::Effect,
+    tex::Texture,
+    types::{Encoder, Factory, RawShaderResourceView, RawTexture, Sampler},
+};
+
+/// Position of a sprite texture inside the texture array.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ArrayLayer {
+    /// Index of the layer the texture was copied to.
+    pub layer: f32,
+    /// Factor converting texture coordinates of the source texture into the layer.
+    pub uv_scale: [f32; 2],
+}
+
+/// Texture array the sprite textures are copied into, so sprites of different sheets can be
+/// drawn with a single draw call.
+///
+/// Every texture occupies the bottom left corner of its own layer. Textures are copied the first
+/// time they are drawn, and copied again when they are hot reloaded. The layer of a texture is
+/// freed once all its handles are dropped. After a context loss the pass is rebuilt with an empty
+/// array.
+#[derive(Clone, Debug)]
+pub(crate) struct SpriteTextureArray {
+    width: u16,
+    height: u16,
+    max_layers: u16,
+    channel: ChannelType,
+    array: Option<(RawTexture, RawShaderResourceView, Sampler)>,
+    layers: HashMap<u32, ArrayEntry>,
+    free_layers: Vec<u16>,
+    next_layer: u16,
+}
+
+/// Texture copied into the array, under the id of its handle.
+#[derive(Clone, Debug)]
+struct ArrayEntry {
+    // Handle ids are reused once the handles are dropped.
+    handle: WeakHandle<Texture>,
+    // Kept to notice the texture was reloaded, and so its resource isn't reused meanwhile.
+    texture: RawTexture,
+    layer: Option<ArrayLayer>,
+}
+
+impl SpriteTextureArray {
+    /// Creates an array of `max_layers` layers of `width` x `height` RGBA texels.
+    pub fn new(width: u16, height: u16, max_layers: u16, channel: ChannelType) -> Self {
+        SpriteTextureArray {
+            width,
+            height,
+            max_layers,
+            channel,
+            array: None,
+            layers: HashMap::default(),
+            free_layers: Vec::new(),
+            next_layer: 0,
+        }
+    }
+
+    /// Returns the layer holding the texture of `handle`, copying the texture into a free layer
+    /// if needed.
+    ///
+    /// Returns `None` if the texture cannot be stored in the array.
+    pub fn layer(
+        &mut self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+        handle: &Handle<Texture>,
+        texture: &Texture,
+    ) -> Option<ArrayLayer> {
+        let id = handle.id();
+        if let Some(entry) = self.layers.remove(&id) {
+            if !entry.handle.is_dead() && entry.texture == *texture.raw() {
+                let layer = entry.layer;
+                self.layers.insert(id, entry);
+                return layer;
+            }
+            self.free(entry);
+        }
+
+        let layer = self.insert(factory, encoder, texture);
+        if layer.is_none() {
+            warn!(
+                "Texture {} cannot be stored in the sprite texture array, sprites using it are not drawn",
+                id
+            );
+        }
+        self.layers.insert(
+            id,
+            ArrayEntry {
+                handle: handle.downgrade(),
+                texture: texture.raw().clone(),
+                layer,
+            },
+        );
+        layer
+    }
+
+    /// Frees the layers of the textures whose handles were all dropped.
+    pub fn maintain(&mut self) {
+        let dead = self
+            .layers
+            .iter()
+            .filter(|&(_, entry)| entry.handle.is_dead())
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        for id in dead {
+            if let Some(entry) = self.layers.remove(&id) {
+                self.free(entry);
+            }
+        }
+    }
+
+    fn free(&mut self, entry: ArrayEntry) {
+        if let Some(layer) = entry.layer {
+            self.free_layers.push(layer.layer as u16);
+        }
+    }
+
+    /// Binds the texture array to the `albedo` texture of the effect.
+    pub fn bind(&self, effect: &mut Effect) {
+        if let Some((_, ref view, ref sampler)) = self.array {
+            effect.data.textures.push(view.clone());
+            effect.data.samplers.push(sampler.clone());
+        }
+    }
+
+    fn insert(
+        &mut self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+        texture: &Texture,
+    ) -> Option<ArrayLayer> {
+        let info = *texture.raw().get_info();
+        let (width, height, _, _) = info.kind.get_dimensions();
+        if (self.free_layers.is_empty() && self.next_layer >= self.max_layers)
+            || width > self.width
+            || height > self.height
+            || info.format != SurfaceType::R8_G8_B8_A8
+        {
+            return None;
+        }
+
+        if self.array.is_none() {
+            self.array = self.create(factory);
+        }
+        let dst = &self.array.as_ref()?.0;
+
+        let layer = self.free_layers.last().cloned().unwrap_or(self.next_layer);
+        let region = |zoffset| RawImageInfo {
+            xoffset: 0,
+            yoffset: 0,
+            zoffset,
+            width,
+            height,
+            depth: 1,
+            format: info.format,
+            mipmap: 0,
+        };
+        if let Err(err) = encoder.copy_texture_to_texture_raw(
+            texture.raw(),
+            None,
+            region(0),
+            dst,
+            None,
+            region(layer),
+        ) {
+            warn!(
+                "Failed to copy texture into the sprite texture array: {:?}",
+                err
+            );
+            return None;
+        }
+        if self.free_layers.pop().is_none() {
+            self.next_layer += 1;
+        }
+
+        Some(ArrayLayer {
+            layer: f32::from(layer),
+            uv_scale: [
+                f32::from(width) / f32::from(self.width),
+                f32::from(height) / f32::from(self.height),
+            ],
+        })
+    }
+
+    fn create(
+        &self,
+        factory: &mut Factory,
+    ) -> Option<(RawTexture, RawShaderResourceView, Sampler)> {
+        use gfx::{format::Swizzle, Factory};
+
+        let info = Info {
+            kind: Kind::D2Array(self.width, self.height, self.max_layers, AaMode::Single),
+            levels: 1,
+            format: SurfaceType::R8_G8_B8_A8,
+            bind: Bind::SHADER_RESOURCE | Bind::TRANSFER_DST,
+            usage: Usage::Data,
+        };
+        let texture = factory
+            .create_texture_raw(info, Some(self.channel), None)
+            .map_err(|err| warn!("Failed to create the sprite texture array: {:?}", err))
+            .ok()?;
+        let desc = ResourceDesc {
+            channel: self.channel,
+            layer: None,
+            min: 0,
+            max: 0,
+            swizzle: Swizzle::new(),
+        };
+        let view = factory
+            .view_texture_as_shader_resource_raw(&texture, desc)
+            .map_err(|err| warn!("Failed to create the sprite texture array view: {:?}", err))
+            .ok()?;
+        let sampler =
+            factory.create_sampler(SamplerInfo::new(FilterMethod::Scale, WrapMode::Clamp));
+        Some((texture, view, sampler))
+    }
+}
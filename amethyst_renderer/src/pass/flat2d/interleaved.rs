@@ -8,9 +8,9 @@ use log::warn;
 
 use amethyst_assets::{AssetStorage, Handle};
 use amethyst_core::{
-    ecs::prelude::{Join, Read, ReadExpect, ReadStorage},
+    ecs::prelude::{Entities, Entity, Join, Read, ReadExpect, ReadStorage},
     math::{convert, one, zero, Matrix4, Vector4},
-    transform::Transform,
+    transform::{ParentHierarchy, Transform},
     Float,
 };
 use amethyst_error::Error;
@@ -21,7 +21,7 @@ use crate::{
     mesh::MeshHandle,
     pass::util::{
         add_texture, default_transparency, get_camera, set_view_args, set_view_args_screen,
-        setup_textures, ViewArgs,
+        setup_textures, BufferRing, ViewArgs,
     },
     pipe::{
         pass::{Pass, PassData},
@@ -30,14 +30,16 @@ use crate::{
     resources::ScreenDimensions,
     screen_space::{ScreenSpace, ScreenSpaceSettings},
     sprite::{Flipped, SpriteRender, SpriteSheet},
+    sprite_mask::{MaskBounds, SpriteMask},
     sprite_visibility::SpriteVisibility,
     tex::{Texture, TextureHandle},
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory, Slice},
     vertex::{Attributes, Query, VertexFormat},
     Color, Rgba,
 };
 
-use super::*;
+use super::{array::SpriteTextureArray, *};
 
 /// Draws sprites on a 2D quad.
 #[derive(Derivative, Clone, Debug)]
@@ -45,6 +47,9 @@ use super::*;
 pub struct DrawFlat2D {
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    texture_array: Option<SpriteTextureArray>,
+    masks: MaskBounds,
     batch: TextureBatch,
 }
 
@@ -84,13 +89,40 @@ where
         self
     }
 
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Copies the sprite textures into the layers of a texture array, so sprites from different
+    /// sprite sheets are drawn with a single draw call.
+    ///
+    /// Each texture needs to fit into a `width` x `height` layer and use the default
+    /// `R8_G8_B8_A8` surface type with the sRGB channel type. Sprites using textures which don't
+    /// fit, or which are drawn once all `layers` are used, are skipped with a warning.
+    pub fn with_texture_array(mut self, width: u16, height: u16, layers: u16) -> Self {
+        self.texture_array = Some(SpriteTextureArray::new(
+            width,
+            height,
+            layers,
+            ChannelType::Srgb,
+        ));
+        self
+    }
+
     fn attributes() -> Attributes<'static> {
         <SpriteInstance as Query<(DirX, DirY, Pos, OffsetU, OffsetV, Depth, Color)>>::QUERIED_ATTRIBUTES
     }
+
+    fn array_attributes() -> Attributes<'static> {
+        <SpriteArrayInstance as Query<(DirX, DirY, Pos, OffsetU, OffsetV, Depth, Color, Layer)>>::QUERIED_ATTRIBUTES
+    }
 }
 
 impl<'a> PassData<'a> for DrawFlat2D {
     type Data = (
+        Entities<'a>,
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
         Read<'a, AssetStorage<SpriteSheet>>,
@@ -107,6 +139,9 @@ impl<'a> PassData<'a> for DrawFlat2D {
         ReadStorage<'a, ScreenSpace>,
         ReadExpect<'a, ScreenDimensions>,
         Read<'a, ScreenSpaceSettings>,
+        ReadStorage<'a, Transparent>,
+        ReadStorage<'a, SpriteMask>,
+        Option<Read<'a, ParentHierarchy>>,
     );
 }
 
@@ -114,7 +149,11 @@ impl Pass for DrawFlat2D {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
         use std::mem;
 
-        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        let mut builder = if self.texture_array.is_some() {
+            effect.simple(VERT_SRC_ARRAY, FRAG_SRC_ARRAY)
+        } else {
+            effect.simple(VERT_SRC, FRAG_SRC)
+        };
         builder
             .without_back_face_culling()
             .with_raw_constant_buffer(
@@ -122,7 +161,20 @@ impl Pass for DrawFlat2D {
                 mem::size_of::<<ViewArgs as Uniform>::Std140>(),
                 1,
             )
-            .with_raw_vertex_buffer(Self::attributes(), SpriteInstance::size() as ElemStride, 1);
+            .with_raw_global("mask");
+        if self.texture_array.is_some() {
+            builder.with_raw_vertex_buffer(
+                Self::array_attributes(),
+                SpriteArrayInstance::size() as ElemStride,
+                1,
+            );
+        } else {
+            builder.with_raw_vertex_buffer(
+                Self::attributes(),
+                SpriteInstance::size() as ElemStride,
+                1,
+            );
+        }
         setup_textures(&mut builder, &TEXTURES);
         match self.transparency {
             Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
@@ -137,6 +189,7 @@ impl Pass for DrawFlat2D {
         effect: &mut Effect,
         mut factory: Factory,
         (
+            entities,
             active,
             camera,
             sprite_sheet_storage,
@@ -153,39 +206,78 @@ impl Pass for DrawFlat2D {
             screens,
             screen_dimensions,
             screen_space_settings,
+            transparent,
+            sprite_mask,
+            hierarchy,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &transform);
+        self.masks.update(
+            &entities,
+            &sprite_mask,
+            &transform,
+            hierarchy.as_ref().map(|h| &**h),
+        );
 
         match visibility {
             None => {
-                for (sprite_render, transform, flipped, rgba, _, _, screen_maybe) in (
+                for (
+                    entity,
+                    sprite_render,
+                    transform,
+                    flipped,
+                    rgba,
+                    transparent,
+                    _,
+                    _,
+                    screen_maybe,
+                ) in (
+                    &*entities,
                     &sprite_render,
                     &transform,
                     flipped.maybe(),
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                     screens.maybe(),
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
                     self.batch.add_sprite(
                         sprite_render,
                         Some(transform),
                         flipped,
                         rgba,
+                        self.masks.get(entity),
                         &sprite_sheet_storage,
                         &tex_storage,
                         screen_maybe.is_some(),
                     );
                 }
 
-                for (image_render, transform, flipped, rgba, _, _, _, screen_maybe) in (
+                for (
+                    entity,
+                    image_render,
+                    transform,
+                    flipped,
+                    rgba,
+                    transparent,
+                    _,
+                    _,
+                    _,
+                    screen_maybe,
+                ) in (
+                    &*entities,
                     &texture_handle,
                     &transform,
                     flipped.maybe(),
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                     !&mesh,
@@ -193,11 +285,16 @@ impl Pass for DrawFlat2D {
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
                     self.batch.add_image(
                         image_render,
                         Some(transform),
                         flipped,
                         rgba,
+                        self.masks.get(entity),
                         &tex_storage,
                         screen_maybe.is_some(),
                     );
@@ -206,52 +303,63 @@ impl Pass for DrawFlat2D {
                 self.batch.sort();
             }
             Some(ref visibility) => {
-                for (sprite_render, transform, flipped, rgba, _, screen_maybe) in (
-                    &sprite_render,
-                    &transform,
-                    flipped.maybe(),
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                    screens.maybe(),
-                )
-                    .join()
-                {
-                    self.batch.add_sprite(
-                        sprite_render,
-                        Some(transform),
-                        flipped,
-                        rgba,
-                        &sprite_sheet_storage,
-                        &tex_storage,
-                        screen_maybe.is_some(),
-                    );
-                }
+                if self.filter.draws_opaque() {
+                    for (entity, sprite_render, transform, flipped, rgba, _, screen_maybe) in (
+                        &*entities,
+                        &sprite_render,
+                        &transform,
+                        flipped.maybe(),
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                        screens.maybe(),
+                    )
+                        .join()
+                    {
+                        self.batch.add_sprite(
+                            sprite_render,
+                            Some(transform),
+                            flipped,
+                            rgba,
+                            self.masks.get(entity),
+                            &sprite_sheet_storage,
+                            &tex_storage,
+                            screen_maybe.is_some(),
+                        );
+                    }
 
-                for (image_render, transform, flipped, rgba, _, _, screen_maybe) in (
-                    &texture_handle,
-                    &transform,
-                    flipped.maybe(),
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                    !&mesh,
-                    screens.maybe(),
-                )
-                    .join()
-                {
-                    self.batch.add_image(
-                        image_render,
-                        Some(transform),
-                        flipped,
-                        rgba,
-                        &tex_storage,
-                        screen_maybe.is_some(),
-                    );
-                }
+                    for (entity, image_render, transform, flipped, rgba, _, _, screen_maybe) in (
+                        &*entities,
+                        &texture_handle,
+                        &transform,
+                        flipped.maybe(),
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                        !&mesh,
+                        screens.maybe(),
+                    )
+                        .join()
+                    {
+                        self.batch.add_image(
+                            image_render,
+                            Some(transform),
+                            flipped,
+                            rgba,
+                            self.masks.get(entity),
+                            &tex_storage,
+                            screen_maybe.is_some(),
+                        );
+                    }
 
-                // We are free to optimize the order of the opaque sprites.
-                self.batch.sort();
+                    // We are free to optimize the order of the opaque sprites.
+                    self.batch.sort();
+                }
 
+                // Sprites on a `SpriteLayer` are drawn in order even if they are opaque.
                 for entity in &visibility.visible_ordered {
+                    if !self.filter.accepts(transparent.contains(*entity)) {
+                        continue;
+                    }
+
                     let screen = screens.contains(*entity);
                     if let Some(sprite_render) = sprite_render.get(*entity) {
                         self.batch.add_sprite(
@@ -259,6 +367,7 @@ impl Pass for DrawFlat2D {
                             transform.get(*entity),
                             flipped.get(*entity),
                             rgba.get(*entity),
+                            self.masks.get(*entity),
                             &sprite_sheet_storage,
                             &tex_storage,
                             screen,
@@ -269,6 +378,7 @@ impl Pass for DrawFlat2D {
                             transform.get(*entity),
                             flipped.get(*entity),
                             rgba.get(*entity),
+                            self.masks.get(*entity),
                             &tex_storage,
                             screen,
                         )
@@ -276,10 +386,14 @@ impl Pass for DrawFlat2D {
                 }
             }
         }
+        if let Some(ref mut array) = self.texture_array {
+            array.maintain();
+        }
         self.batch.encode(
             encoder,
             &mut factory,
             effect,
+            self.texture_array.as_mut(),
             camera,
             &sprite_sheet_storage,
             &tex_storage,
@@ -297,6 +411,7 @@ enum TextureDrawData {
         render: SpriteRender,
         flipped: Option<Flipped>,
         rgba: Option<Rgba>,
+        mask: Option<[f32; 4]>,
         transform: Matrix4<Float>,
         screen: bool,
     },
@@ -305,6 +420,7 @@ enum TextureDrawData {
         transform: Matrix4<Float>,
         flipped: Option<Flipped>,
         rgba: Option<Rgba>,
+        mask: Option<[f32; 4]>,
         width: usize,
         height: usize,
         screen: bool,
@@ -332,12 +448,20 @@ impl TextureDrawData {
             TextureDrawData::Image { flipped, .. } => flipped,
         }
     }
+
+    pub fn mask(&self) -> Option<[f32; 4]> {
+        match self {
+            TextureDrawData::Sprite { mask, .. } => *mask,
+            TextureDrawData::Image { mask, .. } => *mask,
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
 struct TextureBatch {
     textures: Vec<TextureDrawData>,
     textures_screen: Vec<TextureDrawData>,
+    instance_buffers: BufferRing<f32>,
 }
 
 impl TextureBatch {
@@ -347,6 +471,7 @@ impl TextureBatch {
         transform: Option<&Transform>,
         flipped: Option<&Flipped>,
         rgba: Option<&Rgba>,
+        mask: Option<[f32; 4]>,
         tex_storage: &AssetStorage<Texture>,
         screen: bool,
     ) {
@@ -368,6 +493,7 @@ impl TextureBatch {
             transform: *transform.global_matrix(),
             flipped: flipped.cloned(),
             rgba: rgba.cloned(),
+            mask,
             width: texture_dims.0,
             height: texture_dims.1,
             screen,
@@ -385,6 +511,7 @@ impl TextureBatch {
         transform: Option<&Transform>,
         flipped: Option<&Flipped>,
         rgba: Option<&Rgba>,
+        mask: Option<[f32; 4]>,
         sprite_sheet_storage: &AssetStorage<SpriteSheet>,
         tex_storage: &AssetStorage<Texture>,
         screen: bool,
@@ -423,6 +550,7 @@ impl TextureBatch {
             render: sprite_render.clone(),
             flipped: flipped.cloned(),
             rgba: rgba.cloned(),
+            mask,
             transform: *transform.global_matrix(),
             screen,
         };
@@ -436,17 +564,19 @@ impl TextureBatch {
 
     /// Optimize the sprite order to generating more coherent batches.
     pub fn sort(&mut self) {
-        // Only takes the texture into account for now.
-        self.textures.sort_by(|a, b| a.tex_id().cmp(&b.tex_id()));
+        // Only takes the texture and whether the sprite is masked into account for now.
+        self.textures
+            .sort_by_key(|data| (data.tex_id(), data.mask().is_some()));
         self.textures_screen
-            .sort_by(|a, b| a.tex_id().cmp(&b.tex_id()));
+            .sort_by_key(|data| (data.tex_id(), data.mask().is_some()));
     }
 
     pub fn encode(
-        &self,
+        &mut self,
         encoder: &mut Encoder,
         factory: &mut Factory,
         effect: &mut Effect,
+        mut texture_array: Option<&mut SpriteTextureArray>,
         camera: Option<(&Camera, &Transform)>,
         sprite_sheet_storage: &AssetStorage<SpriteSheet>,
         tex_storage: &AssetStorage<Texture>,
@@ -458,9 +588,11 @@ impl TextureBatch {
             set_view_args(effect, encoder, camera);
             TextureBatch::encode_vec(
                 &self.textures,
+                &mut self.instance_buffers,
                 encoder,
                 factory,
                 effect,
+                texture_array.as_mut().map(|array| &mut **array),
                 sprite_sheet_storage,
                 tex_storage,
             );
@@ -474,9 +606,11 @@ impl TextureBatch {
             set_view_args_screen(effect, encoder, screen_dimensions, screen_space_settings);
             TextureBatch::encode_vec(
                 &self.textures_screen,
+                &mut self.instance_buffers,
                 encoder,
                 factory,
                 effect,
+                texture_array,
                 sprite_sheet_storage,
                 tex_storage,
             );
@@ -485,17 +619,15 @@ impl TextureBatch {
 
     fn encode_vec(
         textures: &Vec<TextureDrawData>,
+        instance_buffers: &mut BufferRing<f32>,
         encoder: &mut Encoder,
         factory: &mut Factory,
         effect: &mut Effect,
+        mut texture_array: Option<&mut SpriteTextureArray>,
         sprite_sheet_storage: &AssetStorage<SpriteSheet>,
         tex_storage: &AssetStorage<Texture>,
     ) {
-        use gfx::{
-            buffer,
-            memory::{Bind, Typed},
-            Factory,
-        };
+        use gfx::memory::Typed;
 
         // We might be able to improve performance here if we
         // preallocate the maximum needed capacity. We need to
@@ -595,29 +727,72 @@ impl TextureBatch {
                 }
             };
             let rgba = rgba.unwrap_or(Rgba::WHITE);
-            instance_data.extend(&[
-                dir_x.x, dir_x.y, dir_y.x, dir_y.y, pos.x, pos.y, uv_left, uv_right, uv_bottom,
-                uv_top, pos.z, rgba.0, rgba.1, rgba.2, rgba.3,
-            ]);
-            num_instances += 1;
+            match texture_array {
+                Some(ref mut array) => {
+                    let handle = quad.texture_handle();
+                    if let Some(layer) = array.layer(factory, encoder, handle, texture) {
+                        let [u_scale, v_scale] = layer.uv_scale;
+                        instance_data.extend(&[
+                            dir_x.x,
+                            dir_x.y,
+                            dir_y.x,
+                            dir_y.y,
+                            pos.x,
+                            pos.y,
+                            uv_left * u_scale,
+                            uv_right * u_scale,
+                            uv_bottom * v_scale,
+                            uv_top * v_scale,
+                            pos.z,
+                            rgba.0,
+                            rgba.1,
+                            rgba.2,
+                            rgba.3,
+                            layer.layer,
+                        ]);
+                        num_instances += 1;
+                    }
+                }
+                None => {
+                    instance_data.extend(&[
+                        dir_x.x, dir_x.y, dir_y.x, dir_y.y, pos.x, pos.y, uv_left, uv_right,
+                        uv_bottom, uv_top, pos.z, rgba.0, rgba.1, rgba.2, rgba.3,
+                    ]);
+                    num_instances += 1;
+                }
+            }
 
-            // Need to flush outstanding draw calls due to state switch (texture).
+            // Need to flush outstanding draw calls due to state switch (texture or mask).
             //
             // 1. We are at the last sprite and want to submit all pending work.
-            // 2. The next sprite will use a different texture triggering a flush.
+            // 2. The next sprite will use a different texture triggering a flush. Sprites drawn
+            //    from the texture array all share the same texture.
+            // 3. The next sprite is clipped by a different mask.
             let need_flush = i >= num_quads - 1
-                || textures[i + 1].texture_handle().id() != quad.texture_handle().id();
-
-            if need_flush {
-                add_texture(effect, texture);
+                || (texture_array.is_none()
+                    && textures[i + 1].texture_handle().id() != quad.texture_handle().id())
+                || textures[i + 1].mask() != quad.mask();
+
+            if need_flush && num_instances > 0 {
+                let attributes = match texture_array {
+                    Some(ref array) => {
+                        array.bind(effect);
+                        DrawFlat2D::array_attributes()
+                    }
+                    None => {
+                        add_texture(effect, texture);
+                        DrawFlat2D::attributes()
+                    }
+                };
 
-                let vbuf = factory
-                    .create_buffer_immutable(&instance_data, buffer::Role::Vertex, Bind::empty())
-                    .expect("Unable to create immutable buffer for `TextureBatch`");
+                let vbuf = instance_buffers
+                    .upload(factory, encoder, effect, &instance_data)
+                    .expect("Unable to upload instance buffer for `TextureBatch`");
 
-                for _ in DrawFlat2D::attributes() {
+                for _ in attributes {
                     effect.data.vertex_bufs.push(vbuf.raw().clone());
                 }
+                effect.update_global("mask", quad.mask().unwrap_or(MaskBounds::UNMASKED));
 
                 effect.draw(
                     &Slice {
@@ -641,5 +816,6 @@ impl TextureBatch {
     pub fn reset(&mut self) {
         self.textures.clear();
         self.textures_screen.clear();
+        self.instance_buffers.reset();
     }
 }
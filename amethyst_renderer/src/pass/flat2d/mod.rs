@@ -1,5 +1,6 @@
 pub use self::interleaved::DrawFlat2D;
 
+mod array;
 mod interleaved;
 
 use gfx::{
@@ -17,6 +18,8 @@ use crate::{
 
 static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/sprite.glsl");
 static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/sprite.glsl");
+static VERT_SRC_ARRAY: &[u8] = include_bytes!("../shaders/vertex/sprite_array.glsl");
+static FRAG_SRC_ARRAY: &[u8] = include_bytes!("../shaders/fragment/sprite_array.glsl");
 
 static TEXTURES: [TextureType; 1] = [TextureType::Albedo];
 
@@ -74,6 +77,15 @@ impl Attribute for Depth {
     type Repr = f32;
 }
 
+#[derive(Clone, Debug)]
+enum Layer {}
+impl Attribute for Layer {
+    const NAME: &'static str = "layer";
+    const FORMAT: Format = Format(SurfaceType::R32, ChannelType::Float);
+    const SIZE: u32 = 4;
+    type Repr = f32;
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 struct SpriteInstance {
@@ -148,3 +160,67 @@ impl With<Color> for SpriteInstance {
         format: Color::FORMAT,
     };
 }
+
+/// A `SpriteInstance` drawn from a layer of a texture array.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct SpriteArrayInstance {
+    pub sprite: SpriteInstance,
+    pub layer: f32,
+}
+
+unsafe impl Pod for SpriteArrayInstance {}
+
+impl VertexFormat for SpriteArrayInstance {
+    const ATTRIBUTES: Attributes<'static> = &[
+        (DirX::NAME, <Self as With<DirX>>::FORMAT),
+        (DirY::NAME, <Self as With<DirY>>::FORMAT),
+        (Pos::NAME, <Self as With<Pos>>::FORMAT),
+        (OffsetU::NAME, <Self as With<OffsetU>>::FORMAT),
+        (OffsetV::NAME, <Self as With<OffsetV>>::FORMAT),
+        (Depth::NAME, <Self as With<Depth>>::FORMAT),
+        (Color::NAME, <Self as With<Color>>::FORMAT),
+        (Layer::NAME, <Self as With<Layer>>::FORMAT),
+    ];
+}
+
+impl With<DirX> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<DirX>>::FORMAT;
+}
+
+impl With<DirY> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<DirY>>::FORMAT;
+}
+
+impl With<Pos> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<Pos>>::FORMAT;
+}
+
+impl With<OffsetU> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<OffsetU>>::FORMAT;
+}
+
+impl With<OffsetV> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<OffsetV>>::FORMAT;
+}
+
+impl With<Depth> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<Depth>>::FORMAT;
+}
+
+impl With<Color> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = <SpriteInstance as With<Color>>::FORMAT;
+}
+
+impl With<Layer> for SpriteArrayInstance {
+    const FORMAT: AttributeFormat = Element {
+        offset: DirX::SIZE
+            + DirY::SIZE
+            + Pos::SIZE
+            + OffsetU::SIZE
+            + OffsetV::SIZE
+            + Depth::SIZE
+            + Color::SIZE,
+        format: Layer::FORMAT,
+    };
+}
@@ -1,22 +1,44 @@
 //! Different kinds of render passes.
 //
 pub use self::{
+    blit::BlitBuffer,
+    bloom::{ApplyBloom, Bloom, ExtractBloom},
+    color_grading::{ApplyColorGrading, ColorGrading},
     debug_lines::*,
+    decal::DrawDecals,
+    depth_prepass::DrawDepthPrepass,
     flat::*,
     flat2d::*,
+    oit::CompositeOit,
+    path2d::DrawPath2D,
     pbm::*,
     shaded::*,
     skinning::set_skinning_buffers,
     skybox::*,
-    util::{get_camera, set_vertex_args},
+    terrain::DrawTerrain,
+    trail::DrawTrails,
+    util::{
+        get_camera, set_material_uniform, set_vertex_args, setup_material_uniform, BufferRing,
+        MATERIAL_UNIFORM,
+    },
 };
 
+mod blit;
+mod bloom;
+mod clustered;
+mod color_grading;
 mod debug_lines;
+mod decal;
+mod depth_prepass;
 mod flat;
 mod flat2d;
+mod oit;
+mod path2d;
 mod pbm;
 mod shaded;
 mod shaded_util;
 mod skinning;
 mod skybox;
+mod terrain;
+mod trail;
 mod util;
@@ -0,0 +1,93 @@
+//! Weighted blended order-independent transparency.
+
+use amethyst_error::Error;
+
+use crate::{
+    pass::util::fullscreen_slice,
+    pipe::{
+        pass::{Pass, PassData},
+        Effect, NewEffect, TargetBuffer,
+    },
+    transparent::{ColorMask, ALPHA},
+    types::{Encoder, Factory},
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/fullscreen.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/oit_composite.glsl");
+
+/// Blends the result of weighted blended order-independent transparency over the stage target.
+///
+/// Order-independent transparency avoids the popping of sorted transparency when many
+/// transparent surfaces overlap, at the cost of only approximating the blending order. To use it:
+///
+/// * draw the opaque scene into an offscreen target with a depth buffer,
+/// * draw the transparent entities into a target with two color buffers, sharing the depth
+///   buffer of the opaque target, using passes built with `with_weighted_oit`. This target must
+///   be cleared to zero,
+/// * compose the result over the opaque target, and copy it to the backbuffer.
+///
+/// ```rust,ignore
+/// let pipe = Pipeline::build()
+///     .with_target(Target::named("scene").with_depth_buf(true))
+///     .with_target(
+///         Target::named("oit")
+///             .with_num_color_bufs(2)
+///             .with_shared_depth_buf("scene"),
+///     )
+///     .with_stage(
+///         Stage::with_target("scene")
+///             .clear_target([0.0, 0.0, 0.0, 1.0], 1.0)
+///             .with_pass(DrawShaded::<PosNormTex>::new().with_transparency_filter(TransparencyFilter::Opaque)),
+///     )
+///     .with_stage(
+///         Stage::with_target("oit")
+///             .clear_target([0.0; 4], None)
+///             .with_pass(DrawShaded::<PosNormTex>::new().with_weighted_oit()),
+///     )
+///     .with_stage(Stage::with_target("scene").with_pass(CompositeOit::new("oit")))
+///     .with_stage(Stage::with_backbuffer().with_pass(BlitBuffer::color_buf("scene", 0)));
+/// ```
+///
+/// The accumulation buffers use the same 8 bit format as every other render target, so the
+/// result saturates when a very large number of surfaces overlap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositeOit {
+    target: String,
+}
+
+impl CompositeOit {
+    /// Compose the transparency accumulated into the target named `target`.
+    pub fn new<N: Into<String>>(target: N) -> Self {
+        CompositeOit {
+            target: target.into(),
+        }
+    }
+}
+
+impl<'a> PassData<'a> for CompositeOit {
+    type Data = ();
+}
+
+impl Pass for CompositeOit {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_target_input("accum", self.target.clone(), TargetBuffer::Color(0))
+            .with_target_input("coverage", self.target.clone(), TargetBuffer::Color(1))
+            .with_blended_output("color", ColorMask::all(), ALPHA, None);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        _factory: Factory,
+        _: (),
+    ) {
+        effect.add_target_inputs();
+        effect.draw(&fullscreen_slice(), encoder);
+        effect.clear();
+    }
+}
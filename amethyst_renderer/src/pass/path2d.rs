@@ -0,0 +1,159 @@
+//! Vector shape drawing pass.
+
+use derivative::Derivative;
+use gfx::pso::buffer::ElemStride;
+use gfx_core::state::{Blend, ColorMask};
+use glsl_layout::Uniform;
+use lyon::tessellation::{FillTessellator, StrokeTessellator, VertexBuffers};
+
+use amethyst_core::{
+    ecs::prelude::{Join, Read, ReadStorage},
+    math::{convert, Matrix4, Point3, Vector3},
+    transform::Transform,
+    Float,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    hidden::{Hidden, HiddenPropagate},
+    pass::util::{default_transparency, get_camera, set_view_args, BufferRing, ViewArgs},
+    path2d::Path2D,
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    types::{Encoder, Factory, Slice},
+    vertex::{PosColor, VertexFormat},
+    Rgba,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/path2d.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/path2d.glsl");
+
+/// Draws `Path2D` vector shapes.
+///
+/// The shapes are tessellated into triangles every frame and drawn with a single draw call.
+#[derive(Derivative)]
+#[derivative(Default(bound = "Self: Pass"), Debug)]
+pub struct DrawPath2D {
+    #[derivative(Default(value = "default_transparency()"))]
+    transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    #[derivative(Debug = "ignore")]
+    fill: FillTessellator,
+    #[derivative(Debug = "ignore")]
+    stroke: StrokeTessellator,
+    geometry: VertexBuffers<([f32; 2], Rgba), u32>,
+    vertices: Vec<PosColor>,
+    buffers: BufferRing<PosColor>,
+}
+
+impl DrawPath2D
+where
+    Self: Pass,
+{
+    /// Create instance of `DrawPath2D` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Transparency is enabled by default.
+    /// If you pass false to this function transparency will be disabled.
+    pub fn with_transparency(mut self, input: bool) -> Self {
+        if input {
+            if self.transparency.is_none() {
+                self.transparency = default_transparency();
+            }
+        } else {
+            self.transparency = None;
+        }
+        self
+    }
+}
+
+impl<'a> PassData<'a> for DrawPath2D {
+    type Data = (
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Path2D>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+    );
+}
+
+impl Pass for DrawPath2D {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        use std::mem;
+
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_raw_constant_buffer(
+                "ViewArgs",
+                mem::size_of::<<ViewArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_raw_vertex_buffer(PosColor::ATTRIBUTES, PosColor::size() as ElemStride, 0);
+        match self.transparency {
+            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
+            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
+        };
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        (active, camera, transform, paths, hidden, hidden_prop): <Self as PassData<'a>>::Data,
+    ) {
+        use gfx::memory::Typed;
+
+        let camera = get_camera(active, &camera, &transform);
+
+        self.vertices.clear();
+        for (path, transform, _, _) in (&paths, &transform, !&hidden, !&hidden_prop).join() {
+            self.geometry.vertices.clear();
+            self.geometry.indices.clear();
+            path.tessellate(&mut self.fill, &mut self.stroke, &mut self.geometry);
+
+            let matrix = convert::<Matrix4<Float>, Matrix4<f32>>(*transform.global_matrix());
+            let geometry = &self.geometry;
+            self.vertices.extend(geometry.indices.iter().map(|&i| {
+                let ([x, y], color) = geometry.vertices[i as usize];
+                let position = matrix.transform_point(&Point3::new(x, y, 0.0));
+                PosColor {
+                    position: Vector3::new(position.x, position.y, position.z),
+                    color: color.into(),
+                }
+            }));
+        }
+
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        set_view_args(effect, encoder, camera);
+        let vbuf = self
+            .buffers
+            .upload(&mut factory, encoder, effect, &self.vertices)
+            .expect("Unable to upload vertex buffer for `DrawPath2D`");
+        for _ in PosColor::ATTRIBUTES {
+            effect.data.vertex_bufs.push(vbuf.raw().clone());
+        }
+        effect.draw(
+            &Slice {
+                start: 0,
+                end: self.vertices.len() as u32,
+                base_vertex: 0,
+                instances: None,
+                buffer: Default::default(),
+            },
+            encoder,
+        );
+        effect.clear();
+        self.buffers.reset();
+    }
+}
@@ -20,15 +20,20 @@ use crate::{
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
-        shaded_util::{set_light_args, setup_light_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        bloom::{emission_shader, setup_emission_output},
+        clustered::{setup_cluster_buffers, LightClusters},
+        util::{
+            default_transparency, draw_mesh, get_camera, mesh_depth_mode, setup_textures,
+            setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
-    resources::AmbientColor,
+    resources::{AmbientColor, ScreenDimensions},
     tex::Texture,
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory},
     vertex::{Normal, Position, Query, Tangent, TexCoord},
     visibility::Visibility,
@@ -45,12 +50,21 @@ use super::*;
 /// # Type Parameters
 ///
 /// * `V`: `VertexFormat`
-#[derive(Derivative, Clone, Debug, PartialEq)]
-#[derivative(Default(bound = "V: Query<(Position, Normal, Tangent, TexCoord)>"))]
+#[derive(Derivative, Clone)]
+#[derivative(
+    Debug,
+    PartialEq,
+    Default(bound = "V: Query<(Position, Normal, Tangent, TexCoord)>")
+)]
 pub struct DrawPbm<V> {
     _marker: PhantomData<(V)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    depth_prepass: bool,
+    emission_output: bool,
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    clusters: LightClusters,
 }
 
 impl<V> DrawPbm<V>
@@ -88,6 +102,26 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only test against the depth buffer written by a `DrawDepthPrepass` earlier in the stage,
+    /// instead of writing to it.
+    pub fn with_depth_prepass(mut self) -> Self {
+        self.depth_prepass = true;
+        self
+    }
+
+    /// Also write the emission of materials, scaled by their `emission_intensity`, into the
+    /// second color buffer of the target, for `ExtractBloom` to make it glow.
+    pub fn with_emission_output(mut self) -> Self {
+        self.emission_output = true;
+        self
+    }
 }
 
 impl<'a, V> PassData<'a> for DrawPbm<V>
@@ -98,6 +132,7 @@ where
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
         Read<'a, AmbientColor>,
+        ReadExpect<'a, ScreenDimensions>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
         ReadExpect<'a, MaterialDefaults>,
@@ -109,6 +144,7 @@ where
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transparent>,
     );
 }
 
@@ -117,15 +153,31 @@ where
     V: Query<(Position, Normal, Tangent, TexCoord)>,
 {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
-        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        let frag = if self.emission_output {
+            emission_shader(FRAG_SRC)
+        } else {
+            FRAG_SRC.to_vec()
+        };
+        let mut builder = effect.simple(VERT_SRC, &frag[..]);
         builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
         setup_vertex_args(&mut builder);
-        setup_light_buffers(&mut builder);
+        setup_cluster_buffers(&mut builder);
         setup_textures(&mut builder, &TEXTURES);
         match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
+            Some((mask, blend, depth)) => builder.with_blended_output(
+                "color",
+                mask,
+                blend,
+                mesh_depth_mode(depth, self.depth_prepass),
+            ),
+            None => builder.with_output(
+                "color",
+                mesh_depth_mode(Some(DepthMode::LessEqualWrite), self.depth_prepass),
+            ),
         };
+        if self.emission_output {
+            setup_emission_output(&mut builder, self.transparency);
+        }
         builder.build()
     }
 
@@ -133,11 +185,12 @@ where
         &'a mut self,
         encoder: &mut Encoder,
         effect: &mut Effect,
-        _factory: Factory,
+        mut factory: Factory,
         (
             active,
             camera,
             ambient,
+            screen_dimensions,
             mesh_storage,
             tex_storage,
             material_defaults,
@@ -149,24 +202,43 @@ where
             transform,
             light,
             rgba,
+            transparent,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &transform);
 
-        set_light_args(effect, encoder, &light, &transform, &ambient, camera);
+        if !self.clusters.update(
+            &mut factory,
+            encoder,
+            effect,
+            &light,
+            &transform,
+            &ambient,
+            camera,
+            &screen_dimensions,
+        ) {
+            return;
+        }
 
         match visibility {
             None => {
-                for (mesh, material, transform, rgba, _, _) in (
+                for (mesh, material, transform, rgba, transparent, _, _) in (
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
+                    effect.clear();
+                    self.clusters.bind(effect);
                     draw_mesh(
                         encoder,
                         effect,
@@ -185,34 +257,18 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, transform, rgba, _) in (
-                    &mesh,
-                    &material,
-                    &transform,
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                )
-                    .join()
-                {
-                    draw_mesh(
-                        encoder,
-                        effect,
-                        false,
-                        mesh_storage.get(mesh),
-                        None,
-                        &tex_storage,
-                        Some(material),
-                        &material_defaults,
-                        rgba,
-                        camera,
-                        Some(transform),
-                        &[V::QUERIED_ATTRIBUTES],
-                        &TEXTURES,
-                    );
-                }
-
-                for entity in &visibility.visible_ordered {
-                    if let Some(mesh) = mesh.get(*entity) {
+                if self.filter.draws_opaque() {
+                    for (mesh, material, transform, rgba, _) in (
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
+                        effect.clear();
+                        self.clusters.bind(effect);
                         draw_mesh(
                             encoder,
                             effect,
@@ -220,16 +276,40 @@ where
                             mesh_storage.get(mesh),
                             None,
                             &tex_storage,
-                            material.get(*entity),
+                            Some(material),
                             &material_defaults,
-                            rgba.get(*entity),
+                            rgba,
                             camera,
-                            transform.get(*entity),
+                            Some(transform),
                             &[V::QUERIED_ATTRIBUTES],
                             &TEXTURES,
                         );
                     }
                 }
+
+                if self.filter.draws_transparent() {
+                    for entity in &visibility.visible_ordered {
+                        if let Some(mesh) = mesh.get(*entity) {
+                            effect.clear();
+                            self.clusters.bind(effect);
+                            draw_mesh(
+                                encoder,
+                                effect,
+                                false,
+                                mesh_storage.get(mesh),
+                                None,
+                                &tex_storage,
+                                material.get(*entity),
+                                &material_defaults,
+                                rgba.get(*entity),
+                                camera,
+                                transform.get(*entity),
+                                &[V::QUERIED_ATTRIBUTES],
+                                &TEXTURES,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
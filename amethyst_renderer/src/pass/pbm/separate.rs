@@ -21,17 +21,22 @@ use crate::{
     mesh::{Mesh, MeshHandle},
     mtl::{Material, MaterialDefaults},
     pass::{
-        shaded_util::{set_light_args, setup_light_buffers},
+        bloom::{emission_shader, setup_emission_output},
+        clustered::{setup_cluster_buffers, LightClusters},
         skinning::{create_skinning_effect, setup_skinning_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        util::{
+            default_transparency, draw_mesh, get_camera, mesh_depth_mode, setup_textures,
+            setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
         DepthMode, Effect, NewEffect,
     },
-    resources::AmbientColor,
+    resources::{AmbientColor, ScreenDimensions},
     skinning::JointTransforms,
     tex::Texture,
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory},
     vertex::{Attributes, Normal, Position, Separate, Tangent, TexCoord, VertexFormat},
     visibility::Visibility,
@@ -51,12 +56,17 @@ static ATTRIBUTES: [Attributes<'static>; 4] = [
 ///
 /// See the [crate level documentation](index.html) for information about interleaved and separate
 /// passes.
-#[derive(Derivative, Clone, Debug, PartialEq)]
-#[derivative(Default)]
+#[derive(Derivative, Clone)]
+#[derivative(Debug, PartialEq, Default)]
 pub struct DrawPbmSeparate {
     skinning: bool,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    depth_prepass: bool,
+    emission_output: bool,
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    clusters: LightClusters,
 }
 
 impl DrawPbmSeparate {
@@ -97,6 +107,26 @@ impl DrawPbmSeparate {
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only test against the depth buffer written by a `DrawDepthPrepass` earlier in the stage,
+    /// instead of writing to it.
+    pub fn with_depth_prepass(mut self) -> Self {
+        self.depth_prepass = true;
+        self
+    }
+
+    /// Also write the emission of materials, scaled by their `emission_intensity`, into the
+    /// second color buffer of the target, for `ExtractBloom` to make it glow.
+    pub fn with_emission_output(mut self) -> Self {
+        self.emission_output = true;
+        self
+    }
 }
 
 impl<'a> PassData<'a> for DrawPbmSeparate {
@@ -104,6 +134,7 @@ impl<'a> PassData<'a> for DrawPbmSeparate {
         Read<'a, ActiveCamera>,
         ReadStorage<'a, Camera>,
         Read<'a, AmbientColor>,
+        ReadExpect<'a, ScreenDimensions>,
         Read<'a, AssetStorage<Mesh>>,
         Read<'a, AssetStorage<Texture>>,
         ReadExpect<'a, MaterialDefaults>,
@@ -116,6 +147,7 @@ impl<'a> PassData<'a> for DrawPbmSeparate {
         ReadStorage<'a, Light>,
         ReadStorage<'a, JointTransforms>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transparent>,
     );
 }
 
@@ -124,10 +156,15 @@ impl Pass for DrawPbmSeparate {
         #[cfg(feature = "profiler")]
         profile_scope!("render_pass_pbm_compile");
 
+        let frag = if self.emission_output {
+            emission_shader(FRAG_SRC)
+        } else {
+            FRAG_SRC.to_vec()
+        };
         let mut builder = if self.skinning {
-            create_skinning_effect(effect, FRAG_SRC)
+            create_skinning_effect(effect, &frag[..])
         } else {
-            effect.simple(VERT_SRC, FRAG_SRC)
+            effect.simple(VERT_SRC, &frag[..])
         };
         builder
             .with_raw_vertex_buffer(
@@ -154,12 +191,23 @@ impl Pass for DrawPbmSeparate {
             setup_skinning_buffers(&mut builder);
         }
         setup_vertex_args(&mut builder);
-        setup_light_buffers(&mut builder);
+        setup_cluster_buffers(&mut builder);
         setup_textures(&mut builder, &TEXTURES);
         match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
+            Some((mask, blend, depth)) => builder.with_blended_output(
+                "color",
+                mask,
+                blend,
+                mesh_depth_mode(depth, self.depth_prepass),
+            ),
+            None => builder.with_output(
+                "color",
+                mesh_depth_mode(Some(DepthMode::LessEqualWrite), self.depth_prepass),
+            ),
         };
+        if self.emission_output {
+            setup_emission_output(&mut builder, self.transparency);
+        }
         builder.build()
     }
 
@@ -167,11 +215,12 @@ impl Pass for DrawPbmSeparate {
         &'a mut self,
         encoder: &mut Encoder,
         effect: &mut Effect,
-        _factory: Factory,
+        mut factory: Factory,
         (
             active,
             camera,
             ambient,
+            screen_dimensions,
             mesh_storage,
             tex_storage,
             material_defaults,
@@ -184,6 +233,7 @@ impl Pass for DrawPbmSeparate {
             light,
             joints,
             rgba,
+            transparent,
         ): <Self as PassData<'a>>::Data,
     ) {
         #[cfg(feature = "profiler")]
@@ -191,21 +241,39 @@ impl Pass for DrawPbmSeparate {
 
         let camera = get_camera(active, &camera, &transform);
 
-        set_light_args(effect, encoder, &light, &transform, &ambient, camera);
+        if !self.clusters.update(
+            &mut factory,
+            encoder,
+            effect,
+            &light,
+            &transform,
+            &ambient,
+            camera,
+            &screen_dimensions,
+        ) {
+            return;
+        }
 
         match visibility {
             None => {
-                for (joint, mesh, material, transform, rgba, _, _) in (
+                for (joint, mesh, material, transform, rgba, transparent, _, _) in (
                     joints.maybe(),
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
+                    effect.clear();
+                    self.clusters.bind(effect);
                     draw_mesh(
                         encoder,
                         effect,
@@ -224,52 +292,60 @@ impl Pass for DrawPbmSeparate {
                 }
             }
             Some(ref visibility) => {
-                for (joint, mesh, material, transform, rgba, _) in (
-                    joints.maybe(),
-                    &mesh,
-                    &material,
-                    &transform,
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                )
-                    .join()
-                {
-                    draw_mesh(
-                        encoder,
-                        effect,
-                        self.skinning,
-                        mesh_storage.get(mesh),
-                        joint,
-                        &tex_storage,
-                        Some(material),
-                        &material_defaults,
-                        rgba,
-                        camera,
-                        Some(transform),
-                        &ATTRIBUTES,
-                        &TEXTURES,
-                    );
-                }
-
-                for entity in &visibility.visible_ordered {
-                    if let Some(mesh) = mesh.get(*entity) {
+                if self.filter.draws_opaque() {
+                    for (joint, mesh, material, transform, rgba, _) in (
+                        joints.maybe(),
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
+                        effect.clear();
+                        self.clusters.bind(effect);
                         draw_mesh(
                             encoder,
                             effect,
                             self.skinning,
                             mesh_storage.get(mesh),
-                            joints.get(*entity),
+                            joint,
                             &tex_storage,
-                            material.get(*entity),
+                            Some(material),
                             &material_defaults,
-                            rgba.get(*entity),
+                            rgba,
                             camera,
-                            transform.get(*entity),
+                            Some(transform),
                             &ATTRIBUTES,
                             &TEXTURES,
                         );
                     }
                 }
+
+                if self.filter.draws_transparent() {
+                    for entity in &visibility.visible_ordered {
+                        if let Some(mesh) = mesh.get(*entity) {
+                            effect.clear();
+                            self.clusters.bind(effect);
+                            draw_mesh(
+                                encoder,
+                                effect,
+                                self.skinning,
+                                mesh_storage.get(mesh),
+                                joints.get(*entity),
+                                &tex_storage,
+                                material.get(*entity),
+                                &material_defaults,
+                                rgba.get(*entity),
+                                camera,
+                                transform.get(*entity),
+                                &ATTRIBUTES,
+                                &TEXTURES,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
@@ -21,7 +21,10 @@ use crate::{
     mtl::{Material, MaterialDefaults},
     pass::{
         shaded_util::{set_light_args, setup_light_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        util::{
+            default_transparency, draw_mesh, get_camera, mesh_depth_mode, setup_oit_outputs,
+            setup_textures, setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
@@ -29,6 +32,7 @@ use crate::{
     },
     resources::AmbientColor,
     tex::Texture,
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory},
     vertex::{Normal, Position, Query, TexCoord},
     visibility::Visibility,
@@ -51,6 +55,9 @@ pub struct DrawShaded<V> {
     _marker: PhantomData<(V)>,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    depth_prepass: bool,
+    oit: bool,
 }
 
 impl<V> DrawShaded<V>
@@ -88,6 +95,30 @@ where
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only test against the depth buffer written by a `DrawDepthPrepass` earlier in the stage,
+    /// instead of writing to it.
+    pub fn with_depth_prepass(mut self) -> Self {
+        self.depth_prepass = true;
+        self
+    }
+
+    /// Draw `Transparent` entities with weighted blended order-independent transparency.
+    ///
+    /// The pass writes into a target with two color buffers, see `CompositeOit` for how to set
+    /// up the pipeline. Only `Transparent` entities are drawn, and the transparency settings of the
+    /// pass are ignored.
+    pub fn with_weighted_oit(mut self) -> Self {
+        self.oit = true;
+        self.filter = TransparencyFilter::Transparent;
+        self
+    }
 }
 
 impl<'a, V> PassData<'a> for DrawShaded<V>
@@ -109,6 +140,7 @@ where
         ReadStorage<'a, Transform>,
         ReadStorage<'a, Light>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transparent>,
     );
 }
 
@@ -117,15 +149,28 @@ where
     V: Query<(Position, Normal, TexCoord)>,
 {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
-        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        let frag_src = if self.oit { FRAG_SRC_OIT } else { FRAG_SRC };
+        let mut builder = effect.simple(VERT_SRC, frag_src);
         builder.with_raw_vertex_buffer(V::QUERIED_ATTRIBUTES, V::size() as ElemStride, 0);
         setup_vertex_args(&mut builder);
         setup_light_buffers(&mut builder);
         setup_textures(&mut builder, &TEXTURES);
-        match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
-        };
+        if self.oit {
+            setup_oit_outputs(&mut builder);
+        } else {
+            match self.transparency {
+                Some((mask, blend, depth)) => builder.with_blended_output(
+                    "color",
+                    mask,
+                    blend,
+                    mesh_depth_mode(depth, self.depth_prepass),
+                ),
+                None => builder.with_output(
+                    "color",
+                    mesh_depth_mode(Some(DepthMode::LessEqualWrite), self.depth_prepass),
+                ),
+            };
+        }
         builder.build()
     }
 
@@ -149,6 +194,7 @@ where
             global,
             light,
             rgba,
+            transparent,
         ): <Self as PassData<'a>>::Data,
     ) {
         let camera = get_camera(active, &camera, &global);
@@ -157,16 +203,21 @@ where
 
         match visibility {
             None => {
-                for (mesh, material, global, rgba, _, _) in (
+                for (mesh, material, global, rgba, transparent, _, _) in (
                     &mesh,
                     &material,
                     &global,
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
                     draw_mesh(
                         encoder,
                         effect,
@@ -185,34 +236,16 @@ where
                 }
             }
             Some(ref visibility) => {
-                for (mesh, material, global, rgba, _) in (
-                    &mesh,
-                    &material,
-                    &global,
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                )
-                    .join()
-                {
-                    draw_mesh(
-                        encoder,
-                        effect,
-                        false,
-                        mesh_storage.get(mesh),
-                        None,
-                        &tex_storage,
-                        Some(material),
-                        &material_defaults,
-                        rgba,
-                        camera,
-                        Some(global),
-                        &[V::QUERIED_ATTRIBUTES],
-                        &TEXTURES,
-                    );
-                }
-
-                for entity in &visibility.visible_ordered {
-                    if let Some(mesh) = mesh.get(*entity) {
+                if self.filter.draws_opaque() {
+                    for (mesh, material, global, rgba, _) in (
+                        &mesh,
+                        &material,
+                        &global,
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
                         draw_mesh(
                             encoder,
                             effect,
@@ -220,16 +253,38 @@ where
                             mesh_storage.get(mesh),
                             None,
                             &tex_storage,
-                            material.get(*entity),
+                            Some(material),
                             &material_defaults,
-                            rgba.get(*entity),
+                            rgba,
                             camera,
-                            global.get(*entity),
+                            Some(global),
                             &[V::QUERIED_ATTRIBUTES],
                             &TEXTURES,
                         );
                     }
                 }
+
+                if self.filter.draws_transparent() {
+                    for entity in &visibility.visible_ordered {
+                        if let Some(mesh) = mesh.get(*entity) {
+                            draw_mesh(
+                                encoder,
+                                effect,
+                                false,
+                                mesh_storage.get(mesh),
+                                None,
+                                &tex_storage,
+                                material.get(*entity),
+                                &material_defaults,
+                                rgba.get(*entity),
+                                camera,
+                                global.get(*entity),
+                                &[V::QUERIED_ATTRIBUTES],
+                                &TEXTURES,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
@@ -7,5 +7,6 @@ use crate::pass::util::TextureType;
 
 static VERT_SRC: &[u8] = include_bytes!("../shaders/vertex/basic.glsl");
 static FRAG_SRC: &[u8] = include_bytes!("../shaders/fragment/shaded.glsl");
+static FRAG_SRC_OIT: &[u8] = include_bytes!("../shaders/fragment/shaded_oit.glsl");
 
 static TEXTURES: [TextureType; 2] = [TextureType::Albedo, TextureType::Emission];
@@ -21,7 +21,10 @@ use crate::{
     pass::{
         shaded_util::{set_light_args, setup_light_buffers},
         skinning::{create_skinning_effect, setup_skinning_buffers},
-        util::{default_transparency, draw_mesh, get_camera, setup_textures, setup_vertex_args},
+        util::{
+            default_transparency, draw_mesh, get_camera, mesh_depth_mode, setup_oit_outputs,
+            setup_textures, setup_vertex_args,
+        },
     },
     pipe::{
         pass::{Pass, PassData},
@@ -30,6 +33,7 @@ use crate::{
     resources::AmbientColor,
     skinning::JointTransforms,
     tex::Texture,
+    transparent::{TransparencyFilter, Transparent},
     types::{Encoder, Factory},
     vertex::{Attributes, Normal, Position, Separate, TexCoord, VertexFormat},
     visibility::Visibility,
@@ -54,6 +58,9 @@ pub struct DrawShadedSeparate {
     skinning: bool,
     #[derivative(Default(value = "default_transparency()"))]
     transparency: Option<(ColorMask, Blend, Option<DepthMode>)>,
+    filter: TransparencyFilter,
+    depth_prepass: bool,
+    oit: bool,
 }
 
 impl DrawShadedSeparate {
@@ -94,6 +101,30 @@ impl DrawShadedSeparate {
         self.transparency = Some((mask, blend, depth));
         self
     }
+
+    /// Restrict which entities this pass draws, see `TransparencyFilter`.
+    pub fn with_transparency_filter(mut self, filter: TransparencyFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only test against the depth buffer written by a `DrawDepthPrepass` earlier in the stage,
+    /// instead of writing to it.
+    pub fn with_depth_prepass(mut self) -> Self {
+        self.depth_prepass = true;
+        self
+    }
+
+    /// Draw `Transparent` entities with weighted blended order-independent transparency.
+    ///
+    /// The pass writes into a target with two color buffers, see `CompositeOit` for how to set
+    /// up the pipeline. Only `Transparent` entities are drawn, and the transparency settings of the
+    /// pass are ignored.
+    pub fn with_weighted_oit(mut self) -> Self {
+        self.oit = true;
+        self.filter = TransparencyFilter::Transparent;
+        self
+    }
 }
 
 impl<'a> PassData<'a> for DrawShadedSeparate {
@@ -113,16 +144,18 @@ impl<'a> PassData<'a> for DrawShadedSeparate {
         ReadStorage<'a, Light>,
         ReadStorage<'a, JointTransforms>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, Transparent>,
     );
 }
 
 impl Pass for DrawShadedSeparate {
     fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        let frag_src = if self.oit { FRAG_SRC_OIT } else { FRAG_SRC };
         debug!("Building shaded pass");
         let mut builder = if self.skinning {
-            create_skinning_effect(effect, FRAG_SRC)
+            create_skinning_effect(effect, frag_src)
         } else {
-            effect.simple(VERT_SRC, FRAG_SRC)
+            effect.simple(VERT_SRC, frag_src)
         };
         debug!("Effect compiled, adding vertex/uniform buffers");
         builder
@@ -147,10 +180,22 @@ impl Pass for DrawShadedSeparate {
         setup_vertex_args(&mut builder);
         setup_light_buffers(&mut builder);
         setup_textures(&mut builder, &TEXTURES);
-        match self.transparency {
-            Some((mask, blend, depth)) => builder.with_blended_output("color", mask, blend, depth),
-            None => builder.with_output("color", Some(DepthMode::LessEqualWrite)),
-        };
+        if self.oit {
+            setup_oit_outputs(&mut builder);
+        } else {
+            match self.transparency {
+                Some((mask, blend, depth)) => builder.with_blended_output(
+                    "color",
+                    mask,
+                    blend,
+                    mesh_depth_mode(depth, self.depth_prepass),
+                ),
+                None => builder.with_output(
+                    "color",
+                    mesh_depth_mode(Some(DepthMode::LessEqualWrite), self.depth_prepass),
+                ),
+            };
+        }
         builder.build()
     }
 
@@ -175,6 +220,7 @@ impl Pass for DrawShadedSeparate {
             light,
             joints,
             rgba,
+            transparent,
         ): <Self as PassData<'a>>::Data,
     ) {
         trace!("Drawing shaded pass");
@@ -184,17 +230,22 @@ impl Pass for DrawShadedSeparate {
 
         match visibility {
             None => {
-                for (joint, mesh, material, transform, rgba, _, _) in (
+                for (joint, mesh, material, transform, rgba, transparent, _, _) in (
                     joints.maybe(),
                     &mesh,
                     &material,
                     &transform,
                     rgba.maybe(),
+                    transparent.maybe(),
                     !&hidden,
                     !&hidden_prop,
                 )
                     .join()
                 {
+                    if !self.filter.accepts(transparent.is_some()) {
+                        continue;
+                    }
+
                     draw_mesh(
                         encoder,
                         effect,
@@ -213,52 +264,56 @@ impl Pass for DrawShadedSeparate {
                 }
             }
             Some(ref visibility) => {
-                for (joint, mesh, material, transform, rgba, _) in (
-                    joints.maybe(),
-                    &mesh,
-                    &material,
-                    &transform,
-                    rgba.maybe(),
-                    &visibility.visible_unordered,
-                )
-                    .join()
-                {
-                    draw_mesh(
-                        encoder,
-                        effect,
-                        self.skinning,
-                        mesh_storage.get(mesh),
-                        joint,
-                        &tex_storage,
-                        Some(material),
-                        &material_defaults,
-                        rgba,
-                        camera,
-                        Some(transform),
-                        &ATTRIBUTES,
-                        &TEXTURES,
-                    );
-                }
-
-                for entity in &visibility.visible_ordered {
-                    if let Some(mesh) = mesh.get(*entity) {
+                if self.filter.draws_opaque() {
+                    for (joint, mesh, material, transform, rgba, _) in (
+                        joints.maybe(),
+                        &mesh,
+                        &material,
+                        &transform,
+                        rgba.maybe(),
+                        &visibility.visible_unordered,
+                    )
+                        .join()
+                    {
                         draw_mesh(
                             encoder,
                             effect,
                             self.skinning,
                             mesh_storage.get(mesh),
-                            joints.get(*entity),
+                            joint,
                             &tex_storage,
-                            material.get(*entity),
+                            Some(material),
                             &material_defaults,
-                            rgba.get(*entity),
+                            rgba,
                             camera,
-                            transform.get(*entity),
+                            Some(transform),
                             &ATTRIBUTES,
                             &TEXTURES,
                         );
                     }
                 }
+
+                if self.filter.draws_transparent() {
+                    for entity in &visibility.visible_ordered {
+                        if let Some(mesh) = mesh.get(*entity) {
+                            draw_mesh(
+                                encoder,
+                                effect,
+                                self.skinning,
+                                mesh_storage.get(mesh),
+                                joints.get(*entity),
+                                &tex_storage,
+                                material.get(*entity),
+                                &material_defaults,
+                                rgba.get(*entity),
+                                camera,
+                                transform.get(*entity),
+                                &ATTRIBUTES,
+                                &TEXTURES,
+                            );
+                        }
+                    }
+                }
             }
         }
     }
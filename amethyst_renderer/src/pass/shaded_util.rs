@@ -79,22 +79,7 @@ pub(crate) fn set_light_args(
         })
         .collect();
 
-    let directional_lights: Vec<_> = light
-        .join()
-        .filter_map(|light| {
-            if let Light::Directional(ref light) = *light {
-                Some(
-                    DirectionalLightPod {
-                        color: light.color.into(),
-                        direction: light.direction.into(),
-                    }
-                    .std140(),
-                )
-            } else {
-                None
-            }
-        })
-        .collect();
+    let directional_lights = directional_lights(light);
 
     let spot_lights: Vec<_> = (light, transform)
         .join()
@@ -135,18 +120,48 @@ pub(crate) fn set_light_args(
 
     effect.update_global("ambient_color", Into::<[f32; 3]>::into(*ambient.as_ref()));
 
-    effect.update_global(
-        "camera_position",
-        camera
-            .as_ref()
-            .map(|&(_, ref trans)| {
-                convert::<_, Matrix4<f32>>(*trans.global_matrix())
-                    .column(3)
-                    .xyz()
-                    .into()
-            })
-            .unwrap_or([0.0; 3]),
-    );
+    effect.update_global("camera_position", camera_position(camera));
+}
+
+/// Collects the directional lights, which light every fragment.
+pub(crate) fn directional_lights(
+    light: &ReadStorage<'_, Light>,
+) -> Vec<<DirectionalLightPod as Uniform>::Std140> {
+    light
+        .join()
+        .filter_map(|light| {
+            if let Light::Directional(ref light) = *light {
+                let [r, g, b]: [f32; 3] = light.color.into();
+                let color = [
+                    r * light.intensity,
+                    g * light.intensity,
+                    b * light.intensity,
+                ];
+                Some(
+                    DirectionalLightPod {
+                        color: color.into(),
+                        direction: light.direction.into(),
+                    }
+                    .std140(),
+                )
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the world position of the camera, or the origin without a camera.
+pub(crate) fn camera_position(camera: Option<(&Camera, &Transform)>) -> [f32; 3] {
+    camera
+        .as_ref()
+        .map(|&(_, ref trans)| {
+            convert::<_, Matrix4<f32>>(*trans.global_matrix())
+                .column(3)
+                .xyz()
+                .into()
+        })
+        .unwrap_or([0.0; 3])
 }
 
 pub(crate) fn setup_light_buffers(builder: &mut EffectBuilder<'_>) {
@@ -0,0 +1,13 @@
+// Copies a render target buffer.
+
+#version 150 core
+
+uniform sampler2D source;
+
+in vec2 tex_coord;
+
+out vec4 color;
+
+void main() {
+    color = texture(source, tex_coord);
+}
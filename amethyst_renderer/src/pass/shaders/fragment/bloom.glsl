@@ -0,0 +1,23 @@
+// Blurs a render target buffer along one axis with a 9-tap gaussian kernel.
+
+#version 150 core
+
+uniform sampler2D source;
+uniform vec2 direction;
+uniform float scale;
+
+in vec2 tex_coord;
+
+out vec4 color;
+
+const float WEIGHTS[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+    vec2 offset = direction / vec2(textureSize(source, 0));
+    vec3 sum = texture(source, tex_coord).rgb * WEIGHTS[0];
+    for (int i = 1; i < 5; i++) {
+        sum += texture(source, tex_coord + offset * float(i)).rgb * WEIGHTS[i];
+        sum += texture(source, tex_coord - offset * float(i)).rgb * WEIGHTS[i];
+    }
+    color = vec4(sum * scale, 1.0);
+}
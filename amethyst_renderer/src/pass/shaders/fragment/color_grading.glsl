@@ -0,0 +1,32 @@
+// Grades a render target buffer with a lookup table stored as a strip of slices.
+
+#version 150 core
+
+uniform sampler2D source;
+uniform sampler2D lut;
+uniform float lut_size;
+uniform float intensity;
+
+in vec2 tex_coord;
+
+out vec4 color;
+
+vec3 grade(vec3 c) {
+    float max_index = lut_size - 1.0;
+    float blue = clamp(c.b, 0.0, 1.0) * max_index;
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, max_index);
+
+    vec2 texel = vec2(1.0 / (lut_size * lut_size), 1.0 / lut_size);
+    vec2 uv = (clamp(c.rg, 0.0, 1.0) * max_index + 0.5) * texel;
+
+    vec3 a = texture(lut, uv + vec2(slice0 / lut_size, 0.0)).rgb;
+    vec3 b = texture(lut, uv + vec2(slice1 / lut_size, 0.0)).rgb;
+    return mix(a, b, blue - slice0);
+}
+
+void main() {
+    vec4 source_color = texture(source, tex_coord);
+    vec3 graded = intensity > 0.0 ? grade(source_color.rgb) : source_color.rgb;
+    color = vec4(mix(source_color.rgb, graded, intensity), source_color.a);
+}
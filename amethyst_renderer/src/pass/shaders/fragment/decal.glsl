@@ -0,0 +1,29 @@
+// Projects a decal texture onto the geometry behind the projection box.
+
+#version 150 core
+
+layout (std140) uniform DecalArgs {
+    mat4 inv_view_proj;
+    mat4 inv_model;
+};
+
+uniform sampler2D depth;
+uniform sampler2D albedo;
+
+in VertexData {
+    vec4 clip_position;
+    vec4 color;
+} vertex;
+
+out vec4 color;
+
+void main() {
+    vec2 screen = vertex.clip_position.xy / vertex.clip_position.w * 0.5 + 0.5;
+    float scene_depth = texture(depth, screen).r;
+    vec4 world = inv_view_proj * vec4(screen * 2.0 - 1.0, scene_depth * 2.0 - 1.0, 1.0);
+    vec3 local = (inv_model * (world / world.w)).xyz;
+    if (any(greaterThan(abs(local), vec3(1.0)))) {
+        discard;
+    }
+    color = texture(albedo, local.xy * 0.5 + 0.5) * vertex.color;
+}
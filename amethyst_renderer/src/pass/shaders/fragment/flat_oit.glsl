@@ -0,0 +1,42 @@
+// Flat shading, writing into weighted blended order-independent transparency buffers.
+
+#version 150 core
+
+uniform sampler2D albedo;
+
+layout (std140) uniform AlbedoOffset {
+    vec2 u_offset;
+    vec2 v_offset;
+} albedo_offset;
+
+in VertexData {
+    vec3 position;
+    vec3 normal;
+    vec3 tangent;
+    vec2 tex_coord;
+    vec4 color;
+} vertex;
+
+out vec4 accum;
+out vec4 coverage;
+
+// The accumulation buffers only have 8 bits per channel, so the weights are kept below 1.0 to
+// delay saturation.
+void write_oit(vec4 color) {
+    float depth_weight = clamp(pow(1.0 - gl_FragCoord.z, 3.0) * 8.0, 0.05, 1.0);
+    float weight = color.a * depth_weight * 0.25;
+    accum = vec4(color.rgb * weight, weight);
+    coverage = vec4(color.a);
+}
+
+float tex_coord(float coord, vec2 offset) {
+    return offset.x + coord * (offset.y - offset.x);
+}
+
+vec2 tex_coords(vec2 coord, vec2 u, vec2 v) {
+    return vec2(tex_coord(coord.x, u), tex_coord(coord.y, v));
+}
+
+void main() {
+    write_oit(texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset)) * vertex.color);
+}
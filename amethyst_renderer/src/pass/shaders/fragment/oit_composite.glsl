@@ -0,0 +1,19 @@
+// Resolves the weighted blended order-independent transparency buffers.
+
+#version 150 core
+
+uniform sampler2D accum;
+uniform sampler2D coverage;
+
+in vec2 tex_coord;
+
+out vec4 color;
+
+void main() {
+    float alpha = texture(coverage, tex_coord).r;
+    if (alpha <= 0.0) {
+        discard;
+    }
+    vec4 sum = texture(accum, tex_coord);
+    color = vec4(sum.rgb / max(sum.a, 0.00001), alpha);
+}
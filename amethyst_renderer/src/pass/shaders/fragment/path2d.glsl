@@ -0,0 +1,13 @@
+// Fragment shader for colored geometry built on the CPU.
+
+#version 150 core
+
+in VertexData {
+    vec4 color;
+} vertex;
+
+out vec4 color;
+
+void main() {
+    color = vertex.color;
+}
@@ -2,21 +2,19 @@
 
 #version 150 core
 
-layout (std140) uniform FragmentArgs {
-    int point_light_count;
-    int directional_light_count;
-    int spot_light_count;
-};
-
-struct PointLight {
-    vec3 position;
-    vec3 color;
-    float pad; // Workaround for bug in mac's implementation of opengl (loads garbage when accessing members of structures in arrays with dynamic indices).
-    float intensity;
-};
-
-layout (std140) uniform PointLights {
-    PointLight plight[128];
+// Must match `INDEX_WIDTH` in `clustered.rs`.
+const int INDEX_WIDTH = 1024;
+
+layout (std140) uniform ClusterArgs {
+    mat4 cluster_view;
+    vec2 screen_size;
+    float depth_near;
+    float depth_far;
+    uint clusters_x;
+    uint clusters_y;
+    uint clusters_z;
+    uint logarithmic;
+    uint directional_light_count;
 };
 
 struct DirectionalLight {
@@ -28,19 +26,15 @@ layout (std140) uniform DirectionalLights {
     DirectionalLight dlight[16];
 };
 
-struct SpotLight {
-    vec3 position;
-    vec3 color;
-    vec3 direction;
-    float angle;
-    float intensity;
-    float range;
-    float smoothness;
-};
-
-layout (std140) uniform SpotLights {
-    SpotLight slight[128];
-};
+// Point and spot lights, 4 texels per light:
+// position and range, color and intensity, spot direction and cosine of the spot angle,
+// smoothness, type (0 for point lights, 1 for spot lights), cosine of the inner spot angle and
+// inner spot angle.
+uniform sampler2D cluster_lights;
+// Offset and number of light indices of each cluster.
+uniform sampler2D cluster_grid;
+// Indices of the lights overlapping each cluster.
+uniform sampler2D cluster_indices;
 
 uniform vec3 ambient_color;
 uniform vec3 camera_position;
@@ -49,6 +43,7 @@ uniform float alpha_cutoff;
 
 uniform sampler2D albedo;
 uniform sampler2D emission;
+uniform float emission_intensity;
 uniform sampler2D normal;
 uniform sampler2D metallic;
 uniform sampler2D roughness;
@@ -100,6 +95,14 @@ in VertexData {
 
 out vec4 out_color;
 
+#ifdef EMISSION_OUTPUT
+// Must match `EMISSION_RANGE` in `bloom.rs`.
+const float EMISSION_RANGE = 8.0;
+
+// Emission of the fragment divided by `EMISSION_RANGE`, read by the bloom passes.
+out vec4 out_emission;
+#endif
+
 const float PI = 3.14159265359;
 
 float tex_coord(float coord, vec2 offset) {
@@ -165,6 +168,20 @@ vec3 compute_light(vec3 attenuation,
     return resulting_light;
 }
 
+ivec3 cluster_coord(vec3 position) {
+    float depth = -(cluster_view * vec4(position, 1.0)).z;
+    float slice;
+    if (logarithmic != 0u) {
+        slice = log(max(depth, depth_near) / depth_near) / log(depth_far / depth_near);
+    } else {
+        slice = (depth - depth_near) / (depth_far - depth_near);
+    }
+    ivec3 count = ivec3(clusters_x, clusters_y, clusters_z);
+    vec2 tile = gl_FragCoord.xy / screen_size * vec2(count.xy);
+    ivec3 coord = ivec3(int(tile.x), int(tile.y), int(slice * float(count.z)));
+    return clamp(coord, ivec3(0), count - 1);
+}
+
 void main() {
     vec4 albedo_alpha       = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset)).rgba;
 
@@ -172,7 +189,7 @@ void main() {
     if(alpha < alpha_cutoff) discard;
 
     vec3 albedo             = albedo_alpha.rgb;
-    vec3 emission           = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset)).rgb;
+    vec3 emission           = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset)).rgb * emission_intensity;
     vec3 normal             = texture(normal, tex_coords(vertex.tex_coord, normal_offset.u_offset, normal_offset.v_offset)).rgb;
     float metallic          = texture(metallic, tex_coords(vertex.tex_coord, metallic_offset.u_offset, metallic_offset.v_offset)).r;
     float roughness         = texture(roughness, tex_coords(vertex.tex_coord, roughness_offset.u_offset, roughness_offset.v_offset)).r;
@@ -194,24 +211,7 @@ void main() {
 
     vec3 view_direction = normalize(camera_position - vertex.position);
     vec3 lighted = vec3(0.0);
-    for (int i = 0; i < point_light_count; i++) {
-        vec3 light_direction = normalize(plight[i].position - vertex.position);
-        float attenuation = plight[i].intensity / dot(light_direction, light_direction);
-
-        vec3 light = compute_light(vec3(attenuation),
-                                   plight[i].color,
-                                   view_direction,
-                                   light_direction,
-                                   albedo,
-                                   normal,
-                                   roughness2,
-                                   metallic,
-                                   fresnel_base);
-
-        lighted += light;
-    }
-
-    for (int i = 0; i < directional_light_count; i++) {
+    for (int i = 0; i < int(directional_light_count); i++) {
         vec3 light_direction = -normalize(dlight[i].direction);
         float attenuation = 1.0;
 
@@ -228,8 +228,20 @@ void main() {
         lighted += light;
     }
 
-    for (int i = 0; i < spot_light_count; i++) {
-        vec3 light_vec = slight[i].position - vertex.position;
+    ivec3 cluster = cluster_coord(vertex.position);
+    vec4 cluster_range = texelFetch(cluster_grid, ivec2(cluster.y * int(clusters_x) + cluster.x, cluster.z), 0);
+    int first_index = int(cluster_range.x);
+    int light_count = int(cluster_range.y);
+
+    for (int i = 0; i < light_count; i++) {
+        int index = first_index + i;
+        int light_index = int(texelFetch(cluster_indices, ivec2(index % INDEX_WIDTH, index / INDEX_WIDTH), 0).r);
+        vec4 position_range = texelFetch(cluster_lights, ivec2(0, light_index), 0);
+        vec4 color_intensity = texelFetch(cluster_lights, ivec2(1, light_index), 0);
+        vec4 direction_angle = texelFetch(cluster_lights, ivec2(2, light_index), 0);
+        vec4 smoothness_type = texelFetch(cluster_lights, ivec2(3, light_index), 0);
+
+        vec3 light_vec = position_range.xyz - vertex.position;
         vec3 normalized_light_vec = normalize(light_vec);
 
         // The distance between the current fragment and the "core" of the light
@@ -237,41 +249,56 @@ void main() {
 
         // The allowed "length", everything after this won't be lit.
         // Later on we are dividing by this range, so it can't be 0
-        float range = max(slight[i].range, 0.00001);
+        float range = max(position_range.w, 0.00001);
 
         // get normalized range, so everything 0..1 could be lit, everything else can't.
-        float normalized_range = light_length / max(0.00001, range);
-
-        // The attenuation for the "range". If we would only consider this, we'd have a
-        // point light instead, so we need to also check for the spot angle and direction.
-        float range_attenuation = max(0.0, 1.0 - normalized_range);
-
-        // this is actually the cosine of the angle, so it can be compared with the
-        // "dotted" frag_angle below a lot cheaper.
-        float spot_angle = max(slight[i].angle, 0.00001);
-        vec3 spot_direction = normalize(slight[i].direction);
-        float smoothness = 1.0 - slight[i].smoothness;
-
-        // Here we check if the current fragment is within the "ring" of the spotlight.
-        float frag_angle = dot(spot_direction, -normalized_light_vec);
-
-        // so that the ring_attenuation won't be > 1
-        frag_angle = max(frag_angle, spot_angle);
-
-        // How much is this outside of the ring? (let's call it "rim")
-        // Also smooth this out.
-        float rim_attenuation = pow(max((1.0 - frag_angle) / (1.0 - spot_angle), 0.00001), smoothness);
-
-        // How much is this inside the "ring"?
-        float ring_attenuation = 1.0 - rim_attenuation;
-
-        // combine the attenuations and intensity
-        float attenuation = range_attenuation * ring_attenuation * slight[i].intensity;
+        float normalized_range = light_length / range;
+
+        float attenuation;
+        if (smoothness_type.y < 0.5) {
+            // Point lights fade out towards their radius, so that they don't light fragments
+            // outside of the clusters they were assigned to.
+            float window = clamp(1.0 - pow(normalized_range, smoothness_type.x), 0.0, 1.0);
+            attenuation = color_intensity.w * window * window;
+        } else {
+            // The attenuation for the "range". If we would only consider this, we'd have a
+            // point light instead, so we need to also check for the spot angle and direction.
+            float range_attenuation = max(0.0, 1.0 - normalized_range);
+
+            // this is actually the cosine of the angle, so it can be compared with the
+            // "dotted" frag_angle below a lot cheaper.
+            float spot_angle = max(direction_angle.w, 0.00001);
+            vec3 spot_direction = normalize(direction_angle.xyz);
+            float smoothness = 1.0 - smoothness_type.x;
+
+            // Here we check if the current fragment is within the "ring" of the spotlight.
+            float frag_angle = dot(spot_direction, -normalized_light_vec);
+
+            // so that the ring_attenuation won't be > 1
+            frag_angle = max(frag_angle, spot_angle);
+
+            float ring_attenuation;
+            if (smoothness_type.w > 0.0) {
+                // Fade linearly between the inner and outer angles.
+                float inner_angle = max(smoothness_type.z, spot_angle + 0.00001);
+                ring_attenuation = clamp((frag_angle - spot_angle) / (inner_angle - spot_angle), 0.0, 1.0);
+            } else {
+                // How much is this outside of the ring? (let's call it "rim")
+                // Also smooth this out.
+                float rim_attenuation = pow(max((1.0 - frag_angle) / (1.0 - spot_angle), 0.00001), smoothness);
+
+                // How much is this inside the "ring"?
+                ring_attenuation = 1.0 - rim_attenuation;
+            }
+
+            // combine the attenuations and intensity
+            attenuation = range_attenuation * ring_attenuation * color_intensity.w;
+        }
 
         vec3 light = compute_light(vec3(attenuation),
-                                   slight[i].color,
+                                   color_intensity.rgb,
                                    view_direction,
-                                   normalize(light_vec),
+                                   normalized_light_vec,
                                    albedo,
                                    normal,
                                    roughness2,
@@ -284,4 +311,7 @@ void main() {
     vec3 color = ambient + lighted + emission;
 
     out_color = vec4(color, alpha) * vertex.color;
+#ifdef EMISSION_OUTPUT
+    out_emission = vec4(emission / EMISSION_RANGE, alpha) * vertex.color;
+#endif
 }
@@ -32,6 +32,7 @@ uniform vec3 camera_position;
 
 uniform sampler2D albedo;
 uniform sampler2D emission;
+uniform float emission_intensity;
 
 layout (std140) uniform AlbedoOffset {
     vec2 u_offset;
@@ -63,7 +64,7 @@ vec2 tex_coords(vec2 coord, vec2 u, vec2 v) {
 
 void main() {
     vec4 color = texture(albedo, tex_coords(vertex.tex_coord, albedo_offset.u_offset, albedo_offset.v_offset));
-    vec4 ecolor = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset));
+    vec4 ecolor = texture(emission, tex_coords(vertex.tex_coord, emission_offset.u_offset, emission_offset.v_offset)) * vec4(vec3(emission_intensity), 1.0);
     vec3 lighting = vec3(0.0);
     vec3 normal = normalize(vertex.normal);
     for (uint i = 0u; i < point_light_count; i++) {
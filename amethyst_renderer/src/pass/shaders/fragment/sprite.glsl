@@ -4,13 +4,20 @@
 
 uniform sampler2D albedo;
 
+// Left, bottom, right and top edges of the `SpriteMask` the sprite is clipped to.
+uniform vec4 mask;
+
 in VertexData {
     vec2 tex_uv;
     vec4 color;
+    vec2 position;
 } vertex;
 
 out vec4 color;
 
 void main() {
+    if (any(lessThan(vertex.position, mask.xy)) || any(greaterThan(vertex.position, mask.zw))) {
+        discard;
+    }
     color = texture(albedo, vertex.tex_uv) * vertex.color;
 }
@@ -0,0 +1,24 @@
+// Sprite fragment shader sampling the texture from a layer of a texture array.
+
+#version 150 core
+
+uniform sampler2DArray albedo;
+
+// Left, bottom, right and top edges of the `SpriteMask` the sprite is clipped to.
+uniform vec4 mask;
+
+in VertexData {
+    vec2 tex_uv;
+    vec4 color;
+    vec2 position;
+    float layer;
+} vertex;
+
+out vec4 color;
+
+void main() {
+    if (any(lessThan(vertex.position, mask.xy)) || any(greaterThan(vertex.position, mask.zw))) {
+        discard;
+    }
+    color = texture(albedo, vec3(vertex.tex_uv, vertex.layer)) * vertex.color;
+}
@@ -0,0 +1,32 @@
+// Fragment shader blending the terrain layers by the weights of the splat map.
+
+#version 150 core
+
+uniform float layer_tiling;
+uniform sampler2D splat;
+uniform sampler2D layer0;
+uniform sampler2D layer1;
+uniform sampler2D layer2;
+uniform sampler2D layer3;
+
+in VertexData {
+    vec3 position;
+    vec3 normal;
+    vec2 tex_coord;
+    vec4 color;
+} vertex;
+
+out vec4 color;
+
+void main() {
+    vec4 weights = texture(splat, vertex.tex_coord);
+    float total = dot(weights, vec4(1.0));
+    weights = total > 0.0 ? weights / total : vec4(0.25);
+
+    vec2 layer_coord = vertex.tex_coord * layer_tiling;
+    color = texture(layer0, layer_coord) * weights.r
+        + texture(layer1, layer_coord) * weights.g
+        + texture(layer2, layer_coord) * weights.b
+        + texture(layer3, layer_coord) * weights.a;
+    color *= vertex.color;
+}
@@ -0,0 +1,23 @@
+// Draws the projection box of a decal.
+
+#version 150 core
+
+layout (std140) uniform VertexArgs {
+    uniform mat4 proj;
+    uniform mat4 view;
+    uniform mat4 model;
+    uniform vec4 color;
+};
+
+in vec3 position;
+
+out VertexData {
+    vec4 clip_position;
+    vec4 color;
+} vertex;
+
+void main() {
+    vertex.clip_position = proj * view * model * vec4(position, 1.0);
+    vertex.color = color;
+    gl_Position = vertex.clip_position;
+}
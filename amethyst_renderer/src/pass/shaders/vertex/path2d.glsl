@@ -0,0 +1,20 @@
+// Vertex shader for colored geometry built on the CPU, already transformed into world space.
+
+#version 150 core
+
+layout (std140) uniform ViewArgs {
+    mat4 proj;
+    mat4 view;
+};
+
+in vec3 position;
+in vec4 color;
+
+out VertexData {
+    vec4 color;
+} vertex;
+
+void main() {
+    vertex.color = color;
+    gl_Position = proj * view * vec4(position, 1.0);
+}
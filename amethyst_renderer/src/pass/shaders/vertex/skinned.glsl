@@ -2,6 +2,7 @@
 
 #version 150 core
 
+// The array size must match `MAX_JOINTS`.
 layout (std140) uniform JointTransforms {
     mat4 joints[100];
 };
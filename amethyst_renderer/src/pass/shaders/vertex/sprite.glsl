@@ -21,6 +21,7 @@ in vec4 color;
 out VertexData {
     vec2 tex_uv;
     vec4 color;
+    vec2 position;
 } vertex;
 
 const vec2 positions[6] = vec2[](
@@ -47,6 +48,7 @@ void main() {
     vec2 uv = pos + tex_u * dir_x + tex_v * dir_y;
     vertex.tex_uv = texture_coords(vec2(tex_u, tex_v), u_offset, v_offset);
     vertex.color = color;
+    vertex.position = uv;
     vec4 vertex = vec4(uv, depth, 1.0);
     gl_Position = proj * view * vertex;
 }
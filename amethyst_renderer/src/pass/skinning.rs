@@ -8,7 +8,7 @@ use crate::{
     mesh::Mesh,
     pass::util::set_attribute_buffers,
     pipe::{Effect, EffectBuilder, NewEffect},
-    skinning::{JointIds, JointWeights},
+    skinning::{JointIds, JointWeights, MAX_JOINTS},
     vertex::{Attributes, Separate, VertexFormat},
 };
 
@@ -37,7 +37,11 @@ pub(crate) fn setup_skinning_buffers<'a>(builder: &mut EffectBuilder<'a>) {
             Separate::<JointWeights>::size() as ElemStride,
             0,
         )
-        .with_raw_constant_buffer("JointTransforms", mem::size_of::<[[f32; 4]; 4]>(), 100);
+        .with_raw_constant_buffer(
+            "JointTransforms",
+            mem::size_of::<[[f32; 4]; 4]>(),
+            MAX_JOINTS,
+        );
 }
 
 pub fn set_skinning_buffers(effect: &mut Effect, mesh: &Mesh) -> bool {
@@ -0,0 +1,287 @@
+//! Terrain drawing pass.
+
+use derivative::Derivative;
+use fnv::FnvHashMap as HashMap;
+use gfx::pso::buffer::ElemStride;
+use glsl_layout::Uniform;
+use log::error;
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::{
+    ecs::prelude::{Entities, Join, Read, ReadExpect, ReadStorage},
+    math::{convert, Matrix4, Point3},
+    transform::Transform,
+    Float,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    hidden::{Hidden, HiddenPropagate},
+    mesh::Mesh,
+    mtl::MaterialDefaults,
+    pass::util::{add_texture, get_camera, set_attribute_buffers, set_vertex_args, VertexArgs},
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    terrain::{Frustum, Heightmap, HeightmapHandle, Terrain},
+    tex::Texture,
+    types::{Encoder, Factory},
+    vertex::{PosNormTex, VertexFormat},
+    Rgba,
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/terrain.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/terrain.glsl");
+
+/// Draws `Terrain`s, blending their texture layers using their splat map.
+///
+/// The meshes of every chunk and level of detail are generated the first time a terrain is
+/// drawn, and regenerated when its heightmap or layout changes. Each frame, chunks outside of
+/// the view frustum are skipped and the others are drawn at the level of detail matching their
+/// distance to the camera. The pass is unlit, like `DrawFlat`.
+///
+/// Terrains without a splat map blend their layers equally, and missing layers use the
+/// default albedo of `MaterialDefaults`.
+#[derive(Derivative, Clone, Debug, Default)]
+pub struct DrawTerrain {
+    #[derivative(Debug = "ignore")]
+    cache: HashMap<u32, TerrainMeshes>,
+}
+
+#[derive(Clone)]
+struct TerrainMeshes {
+    heightmap: HeightmapHandle,
+    size: [f32; 2],
+    max_height: f32,
+    chunk_cells: usize,
+    chunks: Vec<TerrainChunk>,
+}
+
+#[derive(Clone)]
+struct TerrainChunk {
+    min: Point3<f32>,
+    max: Point3<f32>,
+    lods: Vec<Mesh>,
+}
+
+impl TerrainMeshes {
+    fn build(
+        terrain: &Terrain,
+        heightmap: &Heightmap,
+        factory: &mut Factory,
+    ) -> Result<Self, Error> {
+        let (chunks_x, chunks_z) = terrain.chunks(heightmap);
+        let mut chunks = Vec::with_capacity(chunks_x * chunks_z);
+        for z in 0..chunks_z {
+            for x in 0..chunks_x {
+                let mut lods = Vec::with_capacity(terrain.lod_levels());
+                let mut bounds = None;
+                for lod in 0..terrain.lod_levels() {
+                    let (vertices, lod_bounds) = terrain.chunk_vertices(heightmap, (x, z), lod);
+                    bounds.get_or_insert(lod_bounds);
+                    lods.push(Mesh::build(vertices).build(factory)?);
+                }
+                let (min, max) = bounds.expect("Unreachable: terrains have at least one level");
+                chunks.push(TerrainChunk { min, max, lods });
+            }
+        }
+
+        Ok(TerrainMeshes {
+            heightmap: terrain.heightmap.clone(),
+            size: terrain.size,
+            max_height: terrain.max_height,
+            chunk_cells: terrain.chunk_cells,
+            chunks,
+        })
+    }
+
+    fn matches(&self, terrain: &Terrain) -> bool {
+        self.heightmap == terrain.heightmap
+            && self.size == terrain.size
+            && self.max_height == terrain.max_height
+            && self.chunk_cells == terrain.chunk_cells
+            && self.chunks.first().map(|chunk| chunk.lods.len()) == Some(terrain.lod_levels())
+    }
+}
+
+impl DrawTerrain {
+    /// Create instance of `DrawTerrain` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> PassData<'a> for DrawTerrain {
+    type Data = (
+        Entities<'a>,
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        Read<'a, AssetStorage<Heightmap>>,
+        Read<'a, AssetStorage<Texture>>,
+        ReadExpect<'a, MaterialDefaults>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        ReadStorage<'a, Terrain>,
+        ReadStorage<'a, Transform>,
+    );
+}
+
+impl Pass for DrawTerrain {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        use std::mem;
+
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_raw_constant_buffer(
+                "VertexArgs",
+                mem::size_of::<<VertexArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_raw_vertex_buffer(PosNormTex::ATTRIBUTES, PosNormTex::size() as ElemStride, 0)
+            .with_raw_global("layer_tiling")
+            .with_texture("splat")
+            .with_texture("layer0")
+            .with_texture("layer1")
+            .with_texture("layer2")
+            .with_texture("layer3")
+            .with_output("color", Some(DepthMode::LessEqualWrite));
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        (
+            entities,
+            active,
+            camera,
+            heightmap_storage,
+            tex_storage,
+            material_defaults,
+            hidden,
+            hidden_prop,
+            terrains,
+            transform,
+        ): <Self as PassData<'a>>::Data,
+    ) {
+        let camera = get_camera(active, &camera, &transform);
+        let (frustum, camera_position) = match camera {
+            Some((camera, camera_transform)) => {
+                let camera_matrix =
+                    convert::<Matrix4<Float>, Matrix4<f32>>(*camera_transform.global_matrix());
+                let view = camera_matrix
+                    .try_inverse()
+                    .unwrap_or_else(Matrix4::identity);
+                (
+                    Some(Frustum::new(&(camera.proj * view))),
+                    Point3::from(camera_matrix.column(3).xyz()),
+                )
+            }
+            None => (None, Point3::origin()),
+        };
+        let default_albedo = tex_storage
+            .get(&material_defaults.0.albedo)
+            .expect("Default albedo texture not loaded");
+
+        let mut drawn = Vec::new();
+        for (entity, terrain, transform, _, _) in
+            (&*entities, &terrains, &transform, !&hidden, !&hidden_prop).join()
+        {
+            let heightmap = match heightmap_storage.get(&terrain.heightmap) {
+                Some(heightmap) => heightmap,
+                None => continue,
+            };
+            drawn.push(entity.id());
+
+            let up_to_date = self
+                .cache
+                .get(&entity.id())
+                .map_or(false, |meshes| meshes.matches(terrain));
+            if !up_to_date {
+                match TerrainMeshes::build(terrain, heightmap, &mut factory) {
+                    Ok(meshes) => {
+                        self.cache.insert(entity.id(), meshes);
+                    }
+                    Err(err) => {
+                        error!("Failed to build terrain meshes: {}", err);
+                        continue;
+                    }
+                }
+            }
+            let meshes = &self.cache[&entity.id()];
+
+            effect.update_global("layer_tiling", terrain.layer_tiling);
+            let global_matrix = convert::<Matrix4<Float>, Matrix4<f32>>(*transform.global_matrix());
+            for chunk in &meshes.chunks {
+                let (min, max) = world_bounds(&global_matrix, &chunk.min, &chunk.max);
+                if let Some(ref frustum) = frustum {
+                    if !frustum.intersects_aabb(&min, &max) {
+                        continue;
+                    }
+                }
+                let center = Point3::from((min.coords + max.coords) / 2.0);
+                let lod = terrain
+                    .lod((center - camera_position).norm())
+                    .min(chunk.lods.len() - 1);
+                let mesh = &chunk.lods[lod];
+
+                if !set_attribute_buffers(effect, mesh, &[PosNormTex::ATTRIBUTES]) {
+                    effect.clear();
+                    continue;
+                }
+                set_vertex_args(
+                    effect,
+                    encoder,
+                    camera,
+                    transform.global_matrix(),
+                    Rgba::WHITE,
+                );
+                let splat = terrain
+                    .splat_map
+                    .as_ref()
+                    .and_then(|splat| tex_storage.get(splat))
+                    .unwrap_or(default_albedo);
+                add_texture(effect, splat);
+                for i in 0..4 {
+                    let layer = terrain
+                        .layers
+                        .get(i)
+                        .and_then(|layer| tex_storage.get(layer))
+                        .unwrap_or(default_albedo);
+                    add_texture(effect, layer);
+                }
+
+                effect.draw(mesh.slice(), encoder);
+                effect.clear();
+            }
+        }
+
+        self.cache.retain(|id, _| drawn.contains(id));
+    }
+}
+
+/// Returns the world space bounding box of a box in the local space of an entity.
+fn world_bounds(
+    matrix: &Matrix4<f32>,
+    min: &Point3<f32>,
+    max: &Point3<f32>,
+) -> (Point3<f32>, Point3<f32>) {
+    let mut world_min = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+    let mut world_max = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+    for i in 0..8 {
+        let corner = Point3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        );
+        let corner = matrix.transform_point(&corner);
+        world_min = world_min.inf(&corner);
+        world_max = world_max.sup(&corner);
+    }
+    (world_min, world_max)
+}
@@ -0,0 +1,185 @@
+//! Trail drawing pass.
+
+use derivative::Derivative;
+use gfx::pso::buffer::ElemStride;
+use gfx_core::state::{Blend, ColorMask};
+use glsl_layout::Uniform;
+
+use amethyst_core::{
+    ecs::prelude::{Join, Read, ReadStorage},
+    math::{convert, Matrix4, Point3, Vector3},
+    transform::Transform,
+    Float,
+};
+use amethyst_error::Error;
+
+use crate::{
+    cam::{ActiveCamera, Camera},
+    hidden::{Hidden, HiddenPropagate},
+    pass::util::{get_camera, set_view_args, BufferRing, ViewArgs},
+    pipe::{
+        pass::{Pass, PassData},
+        DepthMode, Effect, NewEffect,
+    },
+    trail::Trail,
+    types::{Encoder, Factory, Slice},
+    vertex::{PosColor, VertexFormat},
+};
+
+static VERT_SRC: &[u8] = include_bytes!("shaders/vertex/path2d.glsl");
+static FRAG_SRC: &[u8] = include_bytes!("shaders/fragment/path2d.glsl");
+
+/// Draws the ribbons recorded by `Trail` components.
+///
+/// The ribbons are rebuilt every frame so they face the camera, and drawn with a single draw
+/// call. They are blended, and tested against but not written to the depth buffer.
+#[derive(Derivative, Clone, Debug)]
+#[derivative(Default(bound = "Self: Pass"))]
+pub struct DrawTrails {
+    #[derivative(Default(value = "default_trail_transparency()"))]
+    transparency: (ColorMask, Blend, Option<DepthMode>),
+    vertices: Vec<PosColor>,
+    buffers: BufferRing<PosColor>,
+}
+
+fn default_trail_transparency() -> (ColorMask, Blend, Option<DepthMode>) {
+    (
+        ColorMask::all(),
+        crate::ALPHA,
+        Some(DepthMode::LessEqualTest),
+    )
+}
+
+impl DrawTrails
+where
+    Self: Pass,
+{
+    /// Create instance of `DrawTrails` pass
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set transparency settings to custom values.
+    pub fn with_transparency_settings(
+        mut self,
+        mask: ColorMask,
+        blend: Blend,
+        depth: Option<DepthMode>,
+    ) -> Self {
+        self.transparency = (mask, blend, depth);
+        self
+    }
+}
+
+impl<'a> PassData<'a> for DrawTrails {
+    type Data = (
+        Read<'a, ActiveCamera>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Trail>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+    );
+}
+
+impl Pass for DrawTrails {
+    fn compile(&mut self, effect: NewEffect<'_>) -> Result<Effect, Error> {
+        use std::mem;
+
+        let (mask, blend, depth) = self.transparency;
+        let mut builder = effect.simple(VERT_SRC, FRAG_SRC);
+        builder
+            .without_back_face_culling()
+            .with_raw_constant_buffer(
+                "ViewArgs",
+                mem::size_of::<<ViewArgs as Uniform>::Std140>(),
+                1,
+            )
+            .with_raw_vertex_buffer(PosColor::ATTRIBUTES, PosColor::size() as ElemStride, 0)
+            .with_blended_output("color", mask, blend, depth);
+        builder.build()
+    }
+
+    fn apply<'a, 'b: 'a>(
+        &'a mut self,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        mut factory: Factory,
+        (active, camera, transform, trails, hidden, hidden_prop): <Self as PassData<'a>>::Data,
+    ) {
+        use gfx::memory::Typed;
+
+        let camera = get_camera(active, &camera, &transform);
+        let view = camera.as_ref().map(|&(camera, transform)| {
+            let matrix = convert::<Matrix4<Float>, Matrix4<f32>>(*transform.global_matrix());
+            // Orthographic projections don't divide by depth, all points are seen from the
+            // same direction.
+            let orthographic = camera.proj[(3, 3)] == 1.0;
+            (
+                Point3::from(matrix.column(3).xyz()),
+                matrix.column(2).xyz(),
+                orthographic,
+            )
+        });
+        let to_camera = |point: &Point3<f32>| match view {
+            Some((position, _, false)) => position - point,
+            Some((_, direction, true)) => direction,
+            None => Vector3::z(),
+        };
+
+        self.vertices.clear();
+        for (trail, _, _) in (&trails, !&hidden, !&hidden_prop).join() {
+            let points = trail.points().collect::<Vec<_>>();
+            if points.len() < 2 {
+                continue;
+            }
+
+            let mut previous = None;
+            for (i, point) in points.iter().enumerate() {
+                let before = points[i.saturating_sub(1)].position;
+                let after = points[(i + 1).min(points.len() - 1)].position;
+                let side = (after - before).cross(&to_camera(&point.position));
+                let side = side.try_normalize(1e-6).unwrap_or_else(Vector3::zeros);
+                let (width, color) = trail.style(point.age);
+                let offset = side * (width / 2.0);
+                let vertex = |position: Point3<f32>| PosColor {
+                    position: position.coords,
+                    color: color.into(),
+                };
+                let left = vertex(point.position - offset);
+                let right = vertex(point.position + offset);
+
+                if let Some((prev_left, prev_right)) = previous {
+                    self.vertices
+                        .extend(&[prev_left, prev_right, right, right, left, prev_left]);
+                }
+                previous = Some((left, right));
+            }
+        }
+
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        set_view_args(effect, encoder, camera);
+        let vbuf = self
+            .buffers
+            .upload(&mut factory, encoder, effect, &self.vertices)
+            .expect("Unable to upload vertex buffer for `DrawTrails`");
+        for _ in PosColor::ATTRIBUTES {
+            effect.data.vertex_bufs.push(vbuf.raw().clone());
+        }
+        effect.draw(
+            &Slice {
+                start: 0,
+                end: self.vertices.len() as u32,
+                base_vertex: 0,
+                instances: None,
+                buffer: Default::default(),
+            },
+            encoder,
+        );
+        effect.clear();
+        self.buffers.reset();
+    }
+}
@@ -1,8 +1,15 @@
 use std::mem;
 
-use gfx_core::state::{Blend, ColorMask};
+use gfx::{
+    buffer::Role,
+    handle::Buffer,
+    memory::{Bind, Usage},
+    traits::Pod,
+    IndexBuffer,
+};
+use gfx_core::state::{Blend, BlendChannel, BlendValue, ColorMask, Equation, Factor};
 use glsl_layout::*;
-use log::error;
+use log::{error, warn};
 
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
@@ -13,22 +20,26 @@ use amethyst_core::{
     math::{convert, Matrix4, Orthographic3},
     Float, Transform,
 };
+use amethyst_error::Error;
 
 use crate::{
     cam::{ActiveCamera, Camera},
     mesh::Mesh,
-    mtl::{Material, MaterialDefaults, TextureOffset},
+    mtl::{Material, MaterialDefaults, MaterialUniform, TextureOffset},
     pass::set_skinning_buffers,
     pipe::{DepthMode, Effect, EffectBuilder},
     resources::ScreenDimensions,
     screen_space::ScreenSpaceSettings,
-    skinning::JointTransforms,
+    skinning::{JointTransforms, MAX_JOINTS},
     tex::Texture,
-    types::Encoder,
+    types::{Encoder, Factory, Resources, Slice},
     vertex::Attributes,
     Rgba,
 };
 
+/// Name of the constant buffer a `Material`'s custom uniform is uploaded to.
+pub const MATERIAL_UNIFORM: &str = "MaterialUniform";
+
 pub(crate) enum TextureType {
     Albedo,
     Emission,
@@ -170,11 +181,13 @@ pub(crate) fn setup_texture_offsets(builder: &mut EffectBuilder<'_>, types: &[Te
                 mem::size_of::<<TextureOffsetPod as Uniform>::Std140>(),
                 1,
             ),
-            Emission => builder.with_raw_constant_buffer(
-                "EmissionOffset",
-                mem::size_of::<<TextureOffsetPod as Uniform>::Std140>(),
-                1,
-            ),
+            Emission => builder
+                .with_raw_constant_buffer(
+                    "EmissionOffset",
+                    mem::size_of::<<TextureOffsetPod as Uniform>::Std140>(),
+                    1,
+                )
+                .with_raw_global("emission_intensity"),
             Normal => builder.with_raw_constant_buffer(
                 "NormalOffset",
                 mem::size_of::<<TextureOffsetPod as Uniform>::Std140>(),
@@ -219,11 +232,14 @@ pub(crate) fn set_texture_offsets(
                 &TextureOffsetPod::from_offset(&material.albedo_offset).std140(),
                 encoder,
             ),
-            Emission => effect.update_constant_buffer(
-                "EmissionOffset",
-                &TextureOffsetPod::from_offset(&material.emission_offset).std140(),
-                encoder,
-            ),
+            Emission => {
+                effect.update_constant_buffer(
+                    "EmissionOffset",
+                    &TextureOffsetPod::from_offset(&material.emission_offset).std140(),
+                    encoder,
+                );
+                effect.update_global("emission_intensity", material.emission_intensity);
+            }
             Normal => effect.update_constant_buffer(
                 "NormalOffset",
                 &TextureOffsetPod::from_offset(&material.normal_offset).std140(),
@@ -264,6 +280,54 @@ pub(crate) fn setup_vertex_args(builder: &mut EffectBuilder<'_>) {
     );
 }
 
+/// Adds the `MaterialUniform` constant buffer to the effect, sized for `T`.
+///
+/// Custom passes call this to receive the data set through `Material::uniform`.
+pub fn setup_material_uniform<T: Uniform>(builder: &mut EffectBuilder<'_>) {
+    #[cfg(feature = "profiler")]
+    profile_scope!("render_setupmaterialuniform");
+
+    builder.with_raw_constant_buffer(
+        MATERIAL_UNIFORM,
+        mem::size_of::<<T as Uniform>::Std140>(),
+        1,
+    );
+}
+
+/// Uploads the custom uniform of the material, if any, into the `MaterialUniform` buffer.
+///
+/// Returns `false` if the material has no custom uniform, or if its size doesn't match the
+/// buffer set up with `setup_material_uniform`, in which case the buffer isn't updated.
+pub fn set_material_uniform(
+    effect: &mut Effect,
+    encoder: &mut Encoder,
+    material: &Material,
+) -> bool {
+    match material.uniform {
+        Some(ref uniform) if uniform_fits(effect.buffer_size(MATERIAL_UNIFORM), uniform) => {
+            effect.update_buffer(MATERIAL_UNIFORM, uniform.as_bytes(), encoder);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Checks that `uniform` has the size of the `MaterialUniform` buffer, if the effect has one.
+fn uniform_fits(buffer_size: Option<usize>, uniform: &MaterialUniform) -> bool {
+    match buffer_size {
+        Some(size) if size != uniform.size() => {
+            error!(
+                "Skipping `MaterialUniform` update: the uniform has {} bytes but the buffer was \
+                 set up for {} bytes",
+                uniform.size(),
+                size
+            );
+            false
+        }
+        _ => true,
+    }
+}
+
 /// Sets the vertex argument in the constant buffer.
 pub fn set_vertex_args(
     effect: &mut Effect,
@@ -392,6 +456,14 @@ pub(crate) fn draw_mesh(
         _ => return,
     };
 
+    // Skinned meshes need their joint matrices, drawing them with the matrices of the previous
+    // entity would be wrong.
+    let joint = match joint {
+        Some(joint) if skinning => Some(joint),
+        None if skinning => return,
+        _ => None,
+    };
+
     if !set_attribute_buffers(effect, mesh, attributes)
         || (skinning && !set_skinning_buffers(effect, mesh))
     {
@@ -407,10 +479,16 @@ pub(crate) fn draw_mesh(
         rgba.cloned().unwrap_or(Rgba::WHITE),
     );
 
-    if skinning {
-        if let Some(joint) = joint {
-            effect.update_buffer("JointTransforms", &joint.matrices[..], encoder);
+    if let Some(joint) = joint {
+        if joint.matrices.len() > MAX_JOINTS {
+            warn!(
+                "Skin has {} joints, only the first {} are used for skinning",
+                joint.matrices.len(),
+                MAX_JOINTS
+            );
         }
+        let count = joint.matrices.len().min(MAX_JOINTS);
+        effect.update_buffer("JointTransforms", &joint.matrices[..count], encoder);
     }
 
     add_textures(
@@ -445,6 +523,75 @@ pub fn get_camera<'a>(
         .or_else(|| (camera, transform).join().next())
 }
 
+/// Blend state of the accumulation buffer of weighted blended order-independent transparency.
+const OIT_ACCUM: Blend = Blend {
+    color: BlendChannel {
+        equation: Equation::Add,
+        source: Factor::One,
+        destination: Factor::One,
+    },
+    alpha: BlendChannel {
+        equation: Equation::Add,
+        source: Factor::One,
+        destination: Factor::One,
+    },
+};
+
+/// Blend state of the coverage buffer of weighted blended order-independent transparency.
+///
+/// Computes `1 - product(1 - alpha)`, so the buffer is cleared to zero like the accumulation
+/// buffer.
+const OIT_COVERAGE: Blend = Blend {
+    color: BlendChannel {
+        equation: Equation::Add,
+        source: Factor::OneMinus(BlendValue::DestColor),
+        destination: Factor::One,
+    },
+    alpha: BlendChannel {
+        equation: Equation::Add,
+        source: Factor::OneMinus(BlendValue::DestAlpha),
+        destination: Factor::One,
+    },
+};
+
+/// Sets up the `accum` and `coverage` outputs of a pass drawing with weighted blended
+/// order-independent transparency.
+pub(crate) fn setup_oit_outputs(builder: &mut EffectBuilder<'_>) {
+    builder
+        .with_blended_output(
+            "accum",
+            ColorMask::all(),
+            OIT_ACCUM,
+            Some(DepthMode::LessEqualTest),
+        )
+        .with_blended_output(
+            "coverage",
+            ColorMask::all(),
+            OIT_COVERAGE,
+            Some(DepthMode::LessEqualTest),
+        );
+}
+
+/// Depth mode of a mesh pass, which only tests against the depth buffer when it was already
+/// filled by a `DrawDepthPrepass`.
+pub(crate) fn mesh_depth_mode(depth: Option<DepthMode>, depth_prepass: bool) -> Option<DepthMode> {
+    match depth {
+        Some(_) if depth_prepass => Some(DepthMode::LessEqualTest),
+        depth => depth,
+    }
+}
+
+/// Slice drawing the full screen triangle of `shaders/vertex/fullscreen.glsl`.
+pub(crate) fn fullscreen_slice() -> Slice {
+    Slice {
+        start: 0,
+        end: 3,
+        base_vertex: 0,
+        instances: None,
+        buffer: IndexBuffer::Auto,
+    }
+}
+
 pub fn default_transparency() -> Option<(ColorMask, Blend, Option<DepthMode>)> {
     Some((
         ColorMask::all(),
@@ -452,3 +599,107 @@ pub fn default_transparency() -> Option<(ColorMask, Blend, Option<DepthMode>)> {
         Some(DepthMode::LessEqualWrite),
     ))
 }
+
+/// Smallest amount of elements allocated for a buffer of a `BufferRing`.
+const MIN_RING_CAPACITY: usize = 64;
+
+/// A ring of dynamic GPU buffers used to upload transient per-frame data, such as instance
+/// attributes or uniforms.
+///
+/// Each call to `upload` takes the next buffer of the ring and overwrites its contents. Buffers
+/// are kept between frames and only recreated when the data no longer fits, rounding the new
+/// capacity up to the next power of two. Call `reset` once at the start of every frame so the
+/// ring starts reusing buffers from the beginning.
+#[derive(Clone, Debug)]
+pub struct BufferRing<T> {
+    role: Role,
+    buffers: Vec<Buffer<Resources, T>>,
+    next: usize,
+}
+
+impl<T> BufferRing<T>
+where
+    T: Pod,
+{
+    /// Creates an empty ring of buffers used in the given role.
+    pub fn new(role: Role) -> Self {
+        BufferRing {
+            role,
+            buffers: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Makes all buffers of the ring available for uploads again.
+    pub fn reset(&mut self) {
+        self.next = 0;
+    }
+
+    /// Uploads `data` into the next buffer of the ring and returns that buffer.
+    ///
+    /// The upload is counted in the `RenderMetrics` as an upload of `effect`, which the buffer
+    /// is used with.
+    pub fn upload(
+        &mut self,
+        factory: &mut Factory,
+        encoder: &mut Encoder,
+        effect: &mut Effect,
+        data: &[T],
+    ) -> Result<&Buffer<Resources, T>, Error> {
+        use gfx::Factory;
+
+        let fits = self
+            .buffers
+            .get(self.next)
+            .map_or(false, |buffer| buffer.len() >= data.len());
+        if !fits {
+            let capacity = data.len().next_power_of_two().max(MIN_RING_CAPACITY);
+            let buffer =
+                factory.create_buffer(capacity, self.role, Usage::Dynamic, Bind::empty())?;
+            if self.next < self.buffers.len() {
+                self.buffers[self.next] = buffer;
+            } else {
+                self.buffers.push(buffer);
+            }
+        }
+
+        let buffer = &self.buffers[self.next];
+        encoder.update_buffer(buffer, data, 0)?;
+        effect.record_upload(data.len() * mem::size_of::<T>());
+        self.next += 1;
+        Ok(buffer)
+    }
+}
+
+impl<T> Default for BufferRing<T>
+where
+    T: Pod,
+{
+    fn default() -> Self {
+        BufferRing::new(Role::Vertex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+
+    use glsl_layout::{vec4, Uniform};
+
+    use crate::mtl::MaterialUniform;
+
+    use super::{uniform_fits, ViewArgs};
+
+    #[test]
+    fn skips_mismatched_material_uniform() {
+        let expected = mem::size_of::<<ViewArgs as Uniform>::Std140>();
+        let matching = MaterialUniform::new(ViewArgs {
+            proj: [[0.0; 4]; 4].into(),
+            view: [[0.0; 4]; 4].into(),
+        });
+        let mismatched = MaterialUniform::new::<vec4>([1.0, 0.0, 0.0, 1.0].into());
+        assert!(uniform_fits(Some(expected), &matching));
+        assert!(!uniform_fits(Some(expected), &mismatched));
+        assert!(uniform_fits(None, &mismatched));
+    }
+}
@@ -0,0 +1,147 @@
+//! Vector shapes drawn by the `DrawPath2D` pass.
+
+use log::warn;
+use lyon::{
+    math::{point, rect},
+    path::{builder::BorderRadii, traits::PathBuilder, Path, Winding},
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
+        StrokeVertex, VertexBuffers,
+    },
+};
+
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage};
+
+use crate::color::Rgba;
+
+/// Outline drawn along a `Path2D`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathStroke {
+    /// Color of the outline.
+    pub color: Rgba,
+    /// Width of the outline, in local units of the entity.
+    pub width: f32,
+}
+
+/// A 2D vector shape, tessellated and drawn by the `DrawPath2D` pass.
+///
+/// The path is given in the local space of the entity, on its XY plane. Shapes are filled using
+/// the even-odd rule.
+#[derive(Clone, Debug)]
+pub struct Path2D {
+    /// Outline of the shape.
+    pub path: Path,
+    /// Color the inside of the shape is filled with, if any.
+    pub fill: Option<Rgba>,
+    /// Outline drawn along the path, if any.
+    pub stroke: Option<PathStroke>,
+    /// Maximum distance between the curves of the path and the tessellated triangles.
+    pub tolerance: f32,
+}
+
+impl Path2D {
+    /// Creates a shape from a lyon path, filled with white.
+    pub fn new(path: Path) -> Self {
+        Path2D {
+            path,
+            fill: Some(Rgba::WHITE),
+            stroke: None,
+            tolerance: FillOptions::DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Creates a circle centered on the entity.
+    pub fn circle(radius: f32) -> Self {
+        let mut builder = Path::builder();
+        builder.add_circle(point(0.0, 0.0), radius, Winding::Positive);
+        Path2D::new(builder.build())
+    }
+
+    /// Creates a rectangle with rounded corners centered on the entity.
+    pub fn rounded_rectangle(width: f32, height: f32, radius: f32) -> Self {
+        let mut builder = Path::builder();
+        builder.add_rounded_rectangle(
+            &rect(-width / 2.0, -height / 2.0, width, height),
+            &BorderRadii::new(radius),
+            Winding::Positive,
+        );
+        Path2D::new(builder.build())
+    }
+
+    /// Creates a sequence of straight lines through the given points, closing the shape if
+    /// `closed` is `true`.
+    ///
+    /// Open lines are not filled, use `with_stroke` to draw them.
+    pub fn polyline(points: &[[f32; 2]], closed: bool) -> Self {
+        let mut builder = Path::builder();
+        if let Some((first, rest)) = points.split_first() {
+            builder.begin(point(first[0], first[1]));
+            for p in rest {
+                builder.line_to(point(p[0], p[1]));
+            }
+            builder.end(closed);
+        }
+        let mut path = Path2D::new(builder.build());
+        if !closed {
+            path.fill = None;
+        }
+        path
+    }
+
+    /// Sets the color the shape is filled with.
+    pub fn with_fill(mut self, color: Rgba) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Disables filling the shape, so only its outline is drawn.
+    pub fn without_fill(mut self) -> Self {
+        self.fill = None;
+        self
+    }
+
+    /// Draws an outline of the given color and width along the path.
+    pub fn with_stroke(mut self, color: Rgba, width: f32) -> Self {
+        self.stroke = Some(PathStroke { color, width });
+        self
+    }
+
+    /// Sets the tessellation tolerance, see `tolerance`.
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Tessellates the fill and stroke of the shape into triangles, appending the local
+    /// position and color of the vertices to `geometry`.
+    pub(crate) fn tessellate(
+        &self,
+        fill: &mut FillTessellator,
+        stroke: &mut StrokeTessellator,
+        geometry: &mut VertexBuffers<([f32; 2], Rgba), u32>,
+    ) {
+        if let Some(color) = self.fill {
+            let options = FillOptions::tolerance(self.tolerance);
+            let mut builder = BuffersBuilder::new(geometry, |vertex: FillVertex<'_>| {
+                (vertex.position().to_array(), color)
+            });
+            if let Err(err) = fill.tessellate_path(&self.path, &options, &mut builder) {
+                warn!("Failed to tessellate path fill: {:?}", err);
+            }
+        }
+
+        if let Some(PathStroke { color, width }) = self.stroke {
+            let options = StrokeOptions::tolerance(self.tolerance).with_line_width(width);
+            let mut builder = BuffersBuilder::new(geometry, |vertex: StrokeVertex<'_, '_>| {
+                (vertex.position().to_array(), color)
+            });
+            if let Err(err) = stroke.tessellate_path(&self.path, &options, &mut builder) {
+                warn!("Failed to tessellate path stroke: {:?}", err);
+            }
+        }
+    }
+}
+
+impl Component for Path2D {
+    type Storage = DenseVecStorage<Self>;
+}
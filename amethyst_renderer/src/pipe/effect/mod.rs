@@ -16,6 +16,7 @@ use gfx::{
     pso::buffer::{ElemStride, InstanceRate},
     shade::{core::UniformValue, ProgramError, ToUniform},
     state::{Blend, ColorMask, Comparison, CullFace, Depth, MultiSample, Rasterizer, Stencil},
+    texture::{FilterMethod, SamplerInfo, WrapMode},
     traits::Pod,
     Primitive, ShaderSet,
 };
@@ -25,8 +26,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error,
-    pipe::Target,
-    types::{Encoder, Factory, PipelineState, Resources, Slice},
+    metrics::RenderMetrics,
+    pipe::{Target, Targets},
+    types::{Encoder, Factory, PipelineState, RawShaderResourceView, Resources, Sampler, Slice},
     vertex::Attributes,
 };
 
@@ -38,6 +40,39 @@ pub enum DepthMode {
     LessEqualWrite,
 }
 
+/// A buffer of a render target which can be read by an `Effect` as a texture.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TargetBuffer {
+    /// The color buffer with the given index.
+    Color(usize),
+    /// The depth-stencil buffer.
+    Depth,
+}
+
+impl TargetBuffer {
+    fn view(self, target: &Target) -> Option<RawShaderResourceView> {
+        match self {
+            TargetBuffer::Color(i) => target
+                .color_buf(i)
+                .and_then(|cb| cb.as_input.as_ref())
+                .map(|view| view.raw().clone()),
+            TargetBuffer::Depth => target
+                .depth_buf()
+                .and_then(|db| db.as_input.as_ref())
+                .map(|view| view.raw().clone()),
+        }
+    }
+}
+
+/// A render target buffer bound to an `Effect`, refreshed whenever the targets are rebuilt.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TargetInput {
+    target: String,
+    buffer: TargetBuffer,
+    view: RawShaderResourceView,
+    sampler: Sampler,
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub(crate) enum ProgramSource<'a> {
     Simple(&'a [u8], &'a [u8]),
@@ -74,7 +109,12 @@ pub struct Effect {
     pub pso: PipelineState<Meta>,
     pub data: Data,
     const_bufs: HashMap<String, usize>,
+    const_buf_sizes: HashMap<String, usize>,
     globals: HashMap<String, usize>,
+    target_inputs: Vec<TargetInput>,
+    prim: Primitive,
+    #[derivative(PartialEq = "ignore")]
+    metrics: RenderMetrics,
 }
 
 impl Effect {
@@ -90,6 +130,12 @@ impl Effect {
         }
     }
 
+    /// Returns the size in bytes of the constant buffer `name`, as requested when the effect was
+    /// built.
+    pub fn buffer_size<N: AsRef<str>>(&self, name: N) -> Option<usize> {
+        self.const_buf_sizes.get(name.as_ref()).cloned()
+    }
+
     /// FIXME: Update raw buffer without transmute, use `Result` somehow.
     pub fn update_buffer<N, T>(&mut self, name: N, data: &[T], enc: &mut Encoder)
     where
@@ -99,6 +145,8 @@ impl Effect {
         match self.const_bufs.get(name.as_ref()) {
             Some(i) => {
                 let raw = &self.data.const_bufs[*i];
+                self.metrics
+                    .record_upload(data.len() * std::mem::size_of::<T>());
                 enc.update_buffer::<T>(
                     unsafe { &*(raw as *const RawBuffer<_> as *const Buffer<_, _>) },
                     &data[..],
@@ -124,6 +172,7 @@ impl Effect {
         match self.const_bufs.get(name.as_ref()) {
             Some(i) => {
                 let raw = &self.data.const_bufs[*i];
+                self.metrics.record_upload(std::mem::size_of::<T>());
                 enc.update_constant_buffer::<T>(
                     unsafe { &*(raw as *const RawBuffer<_> as *const Buffer<_, _>) },
                     &data,
@@ -138,6 +187,32 @@ impl Effect {
         }
     }
 
+    /// Adds the render target buffers requested with `EffectBuilder::with_target_input` to the
+    /// textures of this effect. They are added in the order they were requested.
+    pub fn add_target_inputs(&mut self) {
+        for input in &self.target_inputs {
+            self.data.textures.push(input.view.clone());
+            self.data.samplers.push(input.sampler.clone());
+        }
+    }
+
+    /// Replaces the render target buffers read by this effect with the ones from `targets`.
+    pub(crate) fn update_target_inputs(&mut self, targets: &Targets) {
+        for input in &mut self.target_inputs {
+            match targets
+                .get(&input.target)
+                .and_then(|target| input.buffer.view(target))
+            {
+                Some(view) => input.view = view,
+                None => warn!(
+                    "Target input for effect could not be updated! Buffer {:?} of target {:?} \
+                     not found",
+                    input.buffer, input.target
+                ),
+            }
+        }
+    }
+
     pub fn clear(&mut self) {
         self.data.textures.clear();
         self.data.samplers.clear();
@@ -145,55 +220,101 @@ impl Effect {
     }
 
     pub fn draw(&mut self, slice: &Slice, enc: &mut Encoder) {
+        self.metrics.record_draw(slice, self.prim);
         enc.draw(&slice, &self.pso, &self.data);
     }
+
+    /// Counts an upload of `bytes` bytes into a buffer used by this effect.
+    pub(crate) fn record_upload(&mut self, bytes: usize) {
+        self.metrics.record_upload(bytes);
+    }
+
+    /// Adds the draw calls and buffer uploads recorded by this effect to `metrics`, and resets
+    /// them.
+    pub(crate) fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        metrics.add_counts(&self.metrics);
+        self.metrics.reset_counts();
+    }
 }
 
 pub struct NewEffect<'f> {
     pub factory: &'f mut Factory,
     out: &'f Target,
+    targets: &'f Targets,
     multisampling: u16,
 }
 
 impl<'f> NewEffect<'f> {
-    pub(crate) fn new(fac: &'f mut Factory, out: &'f Target, multisampling: u16) -> Self {
+    pub(crate) fn new(
+        fac: &'f mut Factory,
+        out: &'f Target,
+        targets: &'f Targets,
+        multisampling: u16,
+    ) -> Self {
         NewEffect {
             factory: fac,
             out,
+            targets,
             multisampling,
         }
     }
 
+    /// Returns the render target the effect will draw into.
+    pub fn target(&self) -> &Target {
+        self.out
+    }
+
     pub fn simple<S: Into<&'f [u8]>>(self, vs: S, ps: S) -> EffectBuilder<'f> {
         let src = ProgramSource::Simple(vs.into(), ps.into());
-        EffectBuilder::new(self.factory, self.out, self.multisampling, src)
+        EffectBuilder::new(
+            self.factory,
+            self.out,
+            self.targets,
+            self.multisampling,
+            src,
+        )
     }
 
     pub fn geom<S: Into<&'f [u8]>>(self, vs: S, gs: S, ps: S) -> EffectBuilder<'f> {
         let src = ProgramSource::Geometry(vs.into(), gs.into(), ps.into());
-        EffectBuilder::new(self.factory, self.out, self.multisampling, src)
+        EffectBuilder::new(
+            self.factory,
+            self.out,
+            self.targets,
+            self.multisampling,
+            src,
+        )
     }
 
     pub fn tess<S: Into<&'f [u8]>>(self, vs: S, hs: S, ds: S, ps: S) -> EffectBuilder<'f> {
         let src = ProgramSource::Tessellated(vs.into(), hs.into(), ds.into(), ps.into());
-        EffectBuilder::new(self.factory, self.out, self.multisampling, src)
+        EffectBuilder::new(
+            self.factory,
+            self.out,
+            self.targets,
+            self.multisampling,
+            src,
+        )
     }
 }
 
 pub struct EffectBuilder<'a> {
     factory: &'a mut Factory,
     out: &'a Target,
+    targets: &'a Targets,
     init: Init<'a>,
     prim: Primitive,
     prog: ProgramSource<'a>,
     rast: Rasterizer,
     const_bufs: Vec<BufferInfo>,
+    target_inputs: Vec<(String, TargetBuffer)>,
 }
 
 impl<'a> EffectBuilder<'a> {
     pub(crate) fn new(
         fac: &'a mut Factory,
         out: &'a Target,
+        targets: &'a Targets,
         multisampling: u16,
         src: ProgramSource<'a>,
     ) -> Self {
@@ -204,11 +325,13 @@ impl<'a> EffectBuilder<'a> {
         EffectBuilder {
             factory: fac,
             out,
+            targets,
             init: Init::default(),
             prim: Primitive::TriangleList,
             rast,
             prog: src,
             const_bufs: Vec::new(),
+            target_inputs: Vec::new(),
         }
     }
 
@@ -218,6 +341,12 @@ impl<'a> EffectBuilder<'a> {
         self
     }
 
+    /// Cull front faces instead of back faces, e.g. to draw volumes the camera may be inside of.
+    pub fn with_front_face_culling(&mut self) -> &mut Self {
+        self.rast.cull_face = CullFace::Front;
+        self
+    }
+
     /// Adds a global constant to this `Effect`.
     pub fn with_raw_global(&mut self, name: &'a str) -> &mut Self {
         self.init.globals.push(name);
@@ -310,6 +439,23 @@ impl<'a> EffectBuilder<'a> {
         self
     }
 
+    /// Adds another output to the PSO, written into the next color buffer of the target.
+    ///
+    /// The depth settings of the previous outputs are kept. Blended and non-blended outputs are
+    /// bound to the color buffers separately, so all the outputs of a PSO should be of the same
+    /// kind.
+    pub fn with_extra_output(
+        &mut self,
+        name: &'a str,
+        blend: Option<(ColorMask, Blend)>,
+    ) -> &mut Self {
+        match blend {
+            Some((mask, blend)) => self.init.out_blends.push((name, mask, blend)),
+            None => self.init.out_colors.push(name),
+        }
+        self
+    }
+
     /// Adds a texture sampler to this `Effect`.
     pub fn with_texture(&mut self, name: &'a str) -> &mut Self {
         self.init.samplers.push(name);
@@ -317,6 +463,22 @@ impl<'a> EffectBuilder<'a> {
         self
     }
 
+    /// Adds a buffer of another render target as a texture input to this `Effect`.
+    ///
+    /// The buffer is bound to the sampler `name` by calling `Effect::add_target_inputs`, and is
+    /// kept up to date when the targets are resized. A stage must not read from its own target.
+    pub fn with_target_input<N: Into<String>>(
+        &mut self,
+        name: &'a str,
+        target_name: N,
+        buffer: TargetBuffer,
+    ) -> &mut Self {
+        self.init.samplers.push(name);
+        self.init.textures.push(name);
+        self.target_inputs.push((target_name.into(), buffer));
+        self
+    }
+
     /// Adds a vertex buffer to this `Effect`.
     pub fn with_raw_vertex_buffer(
         &mut self,
@@ -342,6 +504,13 @@ impl<'a> EffectBuilder<'a> {
         let mut data = Data::default();
 
         debug!("Creating raw constant buffers");
+        let const_buf_sizes = self
+            .init
+            .const_bufs
+            .iter()
+            .zip(&self.const_bufs)
+            .map(|(name, info)| (name.to_string(), info.size))
+            .collect::<HashMap<_, _>>();
         let const_bufs = self
             .init
             .const_bufs
@@ -388,12 +557,37 @@ impl<'a> EffectBuilder<'a> {
             .depth_buf()
             .map(|db| (db.as_output.clone(), (0, 0)));
 
+        debug!("Resolving target inputs");
+        let targets = self.targets;
+        let target_inputs = self
+            .target_inputs
+            .drain(..)
+            .map(|(target, buffer)| {
+                let view = targets
+                    .get(&target)
+                    .and_then(|t| buffer.view(t))
+                    .ok_or_else(|| error::Error::NoSuchTarget(target.clone()))?;
+                let sampler =
+                    fac.create_sampler(SamplerInfo::new(FilterMethod::Bilinear, WrapMode::Clamp));
+                Ok(TargetInput {
+                    target,
+                    buffer,
+                    view,
+                    sampler,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
         debug!("Finished building effect");
         Ok(Effect {
             pso,
             data,
             const_bufs,
+            const_buf_sizes,
             globals,
+            target_inputs,
+            prim: self.prim,
+            metrics: RenderMetrics::default(),
         })
     }
 }
@@ -17,12 +17,14 @@
 //! ```
 
 pub use self::{
-    effect::{Data, DepthMode, Effect, EffectBuilder, Init, Meta, NewEffect},
+    effect::{Data, DepthMode, Effect, EffectBuilder, Init, Meta, NewEffect, TargetBuffer},
     pipe::{Pipeline, PipelineBuild, PipelineBuilder, PipelineData, PolyPipeline, PolyStages},
     stage::{PolyStage, Stage, StageBuilder},
     target::{ColorBuffer, DepthBuffer, Target, TargetBuilder, Targets},
 };
 
+pub(crate) use self::target::link_shared_depth_bufs;
+
 pub mod pass;
 
 mod effect;
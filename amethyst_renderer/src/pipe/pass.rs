@@ -4,7 +4,8 @@ use amethyst_core::ecs::prelude::SystemData;
 use amethyst_error::Error;
 
 use crate::{
-    pipe::{Effect, NewEffect, Target},
+    metrics::RenderMetrics,
+    pipe::{Effect, NewEffect, Target, Targets},
     types::{Encoder, Factory},
 };
 
@@ -47,9 +48,10 @@ where
         mut pass: P,
         fac: &mut Factory,
         out: &Target,
+        targets: &Targets,
         multisampling: u16,
     ) -> Result<Self, Error> {
-        let effect = pass.compile(NewEffect::new(fac, out, multisampling))?;
+        let effect = pass.compile(NewEffect::new(fac, out, targets, multisampling))?;
         Ok(CompiledPass {
             effect,
             inner: pass,
@@ -70,8 +72,13 @@ impl<P> CompiledPass<P> {
         self.inner.apply(encoder, &mut self.effect, factory, data)
     }
 
+    /// Adds the draw calls and buffer uploads of the pass to `metrics`, and resets them.
+    pub fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        self.effect.take_metrics(metrics);
+    }
+
     /// Distributes new target data to the pass.
-    pub fn new_target(&mut self, target: &Target) {
+    pub fn new_target(&mut self, target: &Target, targets: &Targets) {
         // Distribute new targets that don't blend.
         self.effect.data.out_colors.clear();
         self.effect
@@ -88,5 +95,8 @@ impl<P> CompiledPass<P> {
 
         // Distribute new depth buffer
         self.effect.data.out_depth = target.depth_buf().map(|db| (db.as_output.clone(), (0, 0)));
+
+        // Distribute new target inputs
+        self.effect.update_target_inputs(targets);
     }
 }
@@ -4,7 +4,11 @@ use hetseq::*;
 use amethyst_core::ecs::prelude::SystemData;
 use amethyst_error::Error;
 
-use crate::types::{Encoder, Factory};
+use crate::{
+    metrics::RenderMetrics,
+    stats::StageTiming,
+    types::{Encoder, Factory},
+};
 
 use super::{stage::*, target::*};
 
@@ -47,6 +51,12 @@ pub trait PolyStages: for<'a> StagesData<'a> {
 
     /// Distributes new targets
     fn new_targets(&mut self, new_targets: &HashMap<String, Target>);
+
+    /// Appends the timings of all stages to `out`.
+    fn timings(&self, out: &mut Vec<StageTiming>);
+
+    /// Adds the draw calls and buffer uploads of all stages to `metrics`, and resets them.
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics);
 }
 
 impl<'a, HS> StagesData<'a> for List<(HS, List<()>)>
@@ -74,6 +84,16 @@ where
         let List((ref mut hs, _)) = *self;
         HS::new_targets(hs, new_targets);
     }
+
+    fn timings(&self, out: &mut Vec<StageTiming>) {
+        let List((ref hs, _)) = *self;
+        out.push(hs.timing());
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        let List((ref mut hs, _)) = *self;
+        hs.take_metrics(metrics);
+    }
 }
 
 impl<'a, HS, TS> StagesData<'a> for List<(HS, TS)>
@@ -105,6 +125,18 @@ where
         HS::new_targets(hs, new_targets);
         TS::new_targets(ts, new_targets);
     }
+
+    fn timings(&self, out: &mut Vec<StageTiming>) {
+        let List((ref hs, ref ts)) = *self;
+        out.push(hs.timing());
+        ts.timings(out);
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        let List((ref mut hs, ref mut ts)) = *self;
+        hs.take_metrics(metrics);
+        ts.take_metrics(metrics);
+    }
 }
 
 /// The data requested from the `ecs::World` by the Pipeline.
@@ -128,6 +160,13 @@ pub trait PolyPipeline: for<'a> PipelineData<'a> {
 
     /// Returns an immutable reference to all targets and their name strings.
     fn targets(&self) -> &HashMap<String, Target>;
+
+    /// Appends the encoding timings of the last frame for every stage to `out`.
+    fn stage_timings(&self, out: &mut Vec<StageTiming>);
+
+    /// Adds the draw calls and buffer uploads recorded by the passes to `metrics`, and resets
+    /// them.
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics);
 }
 
 impl<'a, L> PipelineData<'a> for Pipeline<L>
@@ -159,6 +198,14 @@ where
     fn targets(&self) -> &HashMap<String, Target> {
         self.targets()
     }
+
+    fn stage_timings(&self, out: &mut Vec<StageTiming>) {
+        self.stages.timings(out);
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        self.stages.take_metrics(metrics);
+    }
 }
 
 /// Constructs a new pipeline with the given render targets and layers.
@@ -240,6 +287,7 @@ where
             .collect::<Result<Targets, Error>>()?;
 
         targets.insert("".into(), out.clone());
+        link_shared_depth_bufs(&mut targets)?;
 
         // TODO: Remove this attribute when rustfmt plays nice.
         #[rustfmt::skip] // try is a reserved keyword in Rust 2018, must preserve keyword escape.
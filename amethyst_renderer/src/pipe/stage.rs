@@ -1,5 +1,7 @@
 //! A stage in the rendering pipeline.
 
+use std::time::{Duration, Instant};
+
 use derivative::Derivative;
 use fnv::FnvHashMap as HashMap;
 use hetseq::*;
@@ -10,10 +12,12 @@ use amethyst_error::Error;
 
 use crate::{
     error,
+    metrics::RenderMetrics,
     pipe::{
         pass::{CompiledPass, Pass, PassData},
         Target, Targets,
     },
+    stats::StageTiming,
     types::{Encoder, Factory},
 };
 
@@ -23,6 +27,7 @@ pub struct Stage<L> {
     clear_color: Option<[f32; 4]>,
     clear_depth: Option<f32>,
     enabled: bool,
+    elapsed: Duration,
     passes: L,
     target_name: String,
     target: Target,
@@ -70,7 +75,10 @@ pub trait Passes: for<'a> PassesData<'a> {
     );
 
     /// Distributes new targets
-    fn new_target(&mut self, new_target: &Target);
+    fn new_target(&mut self, new_target: &Target, new_targets: &Targets);
+
+    /// Adds the draw calls and buffer uploads of the passes to `metrics`, and resets them.
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics);
 }
 
 impl<'a, HP> PassesData<'a> for List<(CompiledPass<HP>, List<()>)>
@@ -94,9 +102,14 @@ where
         hp.apply(encoder, factory, hd);
     }
 
-    fn new_target(&mut self, new_target: &Target) {
+    fn new_target(&mut self, new_target: &Target, new_targets: &Targets) {
+        let List((ref mut hp, _)) = *self;
+        hp.new_target(new_target, new_targets);
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
         let List((ref mut hp, _)) = *self;
-        hp.new_target(new_target);
+        hp.take_metrics(metrics);
     }
 }
 
@@ -124,10 +137,16 @@ where
         tp.apply(encoder, factory, td);
     }
 
-    fn new_target(&mut self, new_target: &Target) {
+    fn new_target(&mut self, new_target: &Target, new_targets: &Targets) {
         let List((ref mut hp, ref mut tp)) = *self;
-        hp.new_target(new_target);
-        tp.new_target(new_target);
+        hp.new_target(new_target, new_targets);
+        tp.new_target(new_target, new_targets);
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        let List((ref mut hp, ref mut tp)) = *self;
+        hp.take_metrics(metrics);
+        tp.take_metrics(metrics);
     }
 }
 
@@ -148,6 +167,12 @@ pub trait PolyStage: for<'a> StageData<'a> {
 
     /// Distributes new targets
     fn new_targets(&mut self, new_targets: &HashMap<String, Target>);
+
+    /// Returns the time spent encoding this stage during the last frame.
+    fn timing(&self) -> StageTiming;
+
+    /// Adds the draw calls and buffer uploads of the stage to `metrics`, and resets them.
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics);
 }
 
 impl<'a, L> StageData<'a> for Stage<L>
@@ -167,6 +192,8 @@ where
         factory: Factory,
         data: <L as PassesData<'b>>::Data,
     ) {
+        let start = Instant::now();
+
         if let Some(color) = self.clear_color {
             self.target.clear_color(encoder, color);
         }
@@ -176,19 +203,31 @@ where
         }
 
         self.passes.apply(encoder, factory, data);
+        self.elapsed = start.elapsed();
     }
 
     fn new_targets(&mut self, new_targets: &HashMap<String, Target>) {
         match new_targets.get(&self.target_name) {
             Some(target) => {
                 self.target = target.clone();
-                self.passes.new_target(target);
+                self.passes.new_target(target, new_targets);
             }
             None => {
                 error!("Target name {:?} not found!", self.target_name);
             }
         }
     }
+
+    fn timing(&self) -> StageTiming {
+        StageTiming {
+            target: self.target_name.clone(),
+            encode: self.elapsed,
+        }
+    }
+
+    fn take_metrics(&mut self, metrics: &mut RenderMetrics) {
+        self.passes.take_metrics(metrics);
+    }
 }
 
 /// Constructs a new rendering stage.
@@ -256,13 +295,14 @@ impl<Q> StageBuilder<Q> {
         let passes = self
             .passes
             .into_list()
-            .fmap(CompilePass::new(fac, &out, multisampling))
+            .fmap(CompilePass::new(fac, &out, targets, multisampling))
             .r#try()?;
 
         Ok(Stage {
             clear_color: self.clear_color,
             clear_depth: self.clear_depth,
             enabled: self.enabled,
+            elapsed: Duration::default(),
             passes,
             target: out,
             target_name: self.target_name,
@@ -286,14 +326,21 @@ impl<Q> StageBuilder<Queue<Q>> {
 pub struct CompilePass<'a> {
     factory: &'a mut Factory,
     target: &'a Target,
+    targets: &'a Targets,
     multisampling: u16,
 }
 
 impl<'a> CompilePass<'a> {
-    fn new(factory: &'a mut Factory, target: &'a Target, multisampling: u16) -> Self {
+    fn new(
+        factory: &'a mut Factory,
+        target: &'a Target,
+        targets: &'a Targets,
+        multisampling: u16,
+    ) -> Self {
         CompilePass {
             factory,
             target,
+            targets,
             multisampling,
         }
     }
@@ -305,7 +352,13 @@ where
 {
     type Output = Result<CompiledPass<P>, Error>;
     fn call_once(self, (pass,): (P,)) -> Result<CompiledPass<P>, Error> {
-        CompiledPass::compile(pass, self.factory, self.target, self.multisampling)
+        CompiledPass::compile(
+            pass,
+            self.factory,
+            self.target,
+            self.targets,
+            self.multisampling,
+        )
     }
 }
 impl<'a, P> HetFnMut<(P,)> for CompilePass<'a>
@@ -313,6 +366,12 @@ where
     P: Pass,
 {
     fn call_mut(&mut self, (pass,): (P,)) -> Result<CompiledPass<P>, Error> {
-        CompiledPass::compile(pass, self.factory, self.target, self.multisampling)
+        CompiledPass::compile(
+            pass,
+            self.factory,
+            self.target,
+            self.targets,
+            self.multisampling,
+        )
     }
 }
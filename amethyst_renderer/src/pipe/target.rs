@@ -7,8 +7,9 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-use crate::types::{
-    DepthStencilView, Encoder, Factory, RenderTargetView, ShaderResourceView, Window,
+use crate::{
+    error,
+    types::{DepthStencilView, Encoder, Factory, RenderTargetView, ShaderResourceView, Window},
 };
 
 /// Target color buffer.
@@ -41,6 +42,7 @@ pub struct Target {
     color_bufs: Vec<ColorBuffer>,
     depth_buf: Option<DepthBuffer>,
     size: (u32, u32),
+    builder: Option<TargetBuilder>,
 }
 
 impl Target {
@@ -50,6 +52,7 @@ impl Target {
             color_bufs: vec![cb],
             depth_buf: Some(db),
             size,
+            builder: None,
         }
     }
 
@@ -97,6 +100,11 @@ impl Target {
         self.depth_buf.as_ref()
     }
 
+    /// Returns the builder this target was created with, if it is not the main target.
+    pub(crate) fn builder(&self) -> Option<&TargetBuilder> {
+        self.builder.as_ref()
+    }
+
     /// Creates the Direct3D 11 backend.
     #[cfg(all(feature = "d3d11", target_os = "windows"))]
     pub fn resize_main_target(window: &Window) -> Result<(Device, Factory, Target), Error> {
@@ -135,6 +143,8 @@ pub struct TargetBuilder {
     name: String,
     has_depth_buf: bool,
     num_color_bufs: usize,
+    #[serde(default)]
+    shared_depth_buf: Option<String>,
 }
 
 impl TargetBuilder {
@@ -145,6 +155,7 @@ impl TargetBuilder {
             name: name.into(),
             has_depth_buf: false,
             num_color_bufs: 1,
+            shared_depth_buf: None,
         }
     }
 
@@ -165,6 +176,17 @@ impl TargetBuilder {
         self
     }
 
+    /// Uses the depth-stencil buffer of the render target with the given name instead of
+    /// creating a new one.
+    ///
+    /// This allows passes drawing into this target to depth test against geometry drawn into the
+    /// other target. Both targets must have the same size, and the other target can not be the
+    /// backbuffer.
+    pub fn with_shared_depth_buf<N: Into<String>>(mut self, target_name: N) -> Self {
+        self.shared_depth_buf = Some(target_name.into());
+        self
+    }
+
     /// Specifies a custom target size.
     pub fn with_size(mut self, size: (u32, u32)) -> Self {
         self.custom_size = Some(size);
@@ -182,6 +204,7 @@ impl TargetBuilder {
         #[cfg(feature = "profiler")]
         profile_scope!("render_target_build");
 
+        let builder = self.clone();
         let size = self.custom_size.unwrap_or(size);
 
         let color_bufs = (0..self.num_color_bufs)
@@ -195,7 +218,7 @@ impl TargetBuilder {
             })
             .collect::<Result<_, Error>>()?;
 
-        let depth_buf = if self.has_depth_buf {
+        let depth_buf = if self.has_depth_buf && self.shared_depth_buf.is_none() {
             let (w, h) = (size.0 as u16, size.1 as u16);
             let (_, res, dt) = fac.create_depth_stencil(w, h)?;
             let depth = DepthBuffer {
@@ -211,8 +234,33 @@ impl TargetBuilder {
             color_bufs,
             depth_buf,
             size,
+            builder: Some(builder),
         };
 
         Ok((self.name, target))
     }
 }
+
+/// Hands out the depth-stencil buffers of targets built with `with_shared_depth_buf`.
+pub(crate) fn link_shared_depth_bufs(targets: &mut Targets) -> Result<(), Error> {
+    let shared = targets
+        .iter()
+        .filter_map(|(name, target)| {
+            target
+                .builder()
+                .and_then(|b| b.shared_depth_buf.clone())
+                .map(|source| (name.clone(), source))
+        })
+        .collect::<Vec<_>>();
+
+    for (name, source) in shared {
+        let depth_buf = targets
+            .get(&source)
+            .and_then(|t| t.depth_buf.clone())
+            .ok_or_else(|| error::Error::NoSuchTarget(source.clone()))?;
+        if let Some(target) = targets.get_mut(&name) {
+            target.depth_buf = Some(depth_buf);
+        }
+    }
+    Ok(())
+}
@@ -1,3 +1,6 @@
+use std::time::Instant;
+
+use amethyst_assets::AssetStorage;
 use amethyst_error::{format_err, Error};
 use fnv::FnvHashMap as HashMap;
 use gfx::memory::Pod;
@@ -7,13 +10,16 @@ use winit::{dpi::LogicalSize, EventsLoop, Window as WinitWindow, WindowBuilder};
 use thread_profiler::profile_scope;
 
 use crate::{
-    config::DisplayConfig,
+    config::{DisplayConfig, PresentMode},
     error,
-    mesh::{Mesh, MeshBuilder, VertexDataSet},
+    mesh::{Mesh, MeshBuilder, MeshUpdates, VertexDataSet},
+    metrics::RenderMetrics,
     pipe::{
-        ColorBuffer, DepthBuffer, PipelineBuild, PipelineData, PolyPipeline, Target, TargetBuilder,
+        link_shared_depth_bufs, ColorBuffer, DepthBuffer, PipelineBuild, PipelineData,
+        PolyPipeline, Target, TargetBuilder,
     },
-    tex::{Texture, TextureBuilder},
+    stats::RenderStats,
+    tex::{ChannelType, Texture, TextureBuilder},
     types::{ColorFormat, DepthFormat, Device, Encoder, Factory, Window},
 };
 
@@ -30,6 +36,9 @@ pub struct Renderer {
     multisampling: u16,
     cached_size: LogicalSize,
     cached_hidpi_factor: f64,
+    stats: RenderStats,
+    config: DisplayConfig,
+    context_lost: bool,
 }
 
 impl Renderer {
@@ -53,7 +62,21 @@ impl Renderer {
     where
         T: VertexDataSet,
     {
-        mb.build(&mut self.factory)
+        if mb.is_dynamic() {
+            mb.build_dynamic(&mut self.factory, &mut self.encoder)
+        } else {
+            mb.build(&mut self.factory)
+        }
+    }
+
+    /// Records the vertex updates queued for dynamic meshes, counting the uploads in `metrics`.
+    pub(crate) fn update_meshes(
+        &mut self,
+        updates: &mut MeshUpdates,
+        storage: &AssetStorage<Mesh>,
+        metrics: &mut RenderMetrics,
+    ) {
+        updates.apply(storage, &mut self.encoder, metrics);
     }
 
     /// Builds a new texture resource.
@@ -65,6 +88,30 @@ impl Renderer {
         tb.build(&mut self.factory)
     }
 
+    /// Builds a new texture resource without uploading its data, see `update_texture_rows`.
+    pub(crate) fn create_empty_texture<D, T>(
+        &mut self,
+        tb: TextureBuilder<D, T>,
+    ) -> Result<Texture, Error>
+    where
+        D: AsRef<[T]>,
+        T: Pod + Copy,
+    {
+        tb.build_empty(&mut self.factory)
+    }
+
+    /// Records the upload of rows of texels into a texture, starting at row `y` from the top.
+    pub(crate) fn update_texture_rows(
+        &mut self,
+        texture: &Texture,
+        channel: ChannelType,
+        y: u16,
+        rows: u16,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        texture.update_rows(&mut self.encoder, channel, y, rows, data)
+    }
+
     /// Builds a new renderer pipeline.
     pub fn create_pipe<B, P>(&mut self, pb: B) -> Result<P, Error>
     where
@@ -74,9 +121,24 @@ impl Renderer {
         pb.build(&mut self.factory, &self.main_target, self.multisampling)
     }
 
+    /// Returns `true` if the window has a surface to draw on, which isn't the case while it is
+    /// minimized.
+    pub fn surface_available(&self) -> bool {
+        self.window()
+            .get_inner_size()
+            .map_or(false, |size| size.width >= 1.0 && size.height >= 1.0)
+    }
+
     /// Draws a scene with the given pipeline.
+    ///
+    /// Nothing is drawn while the surface isn't available. Fails if the frame couldn't be
+    /// presented, in which case `context_lost` tells whether the renderer must be recreated.
     #[allow(clippy::float_cmp)] // cmp just used to recognize change
-    pub fn draw<'a, P>(&mut self, pipe: &mut P, data: <P as PipelineData<'a>>::Data)
+    pub fn draw<'a, P>(
+        &mut self,
+        pipe: &mut P,
+        data: <P as PipelineData<'a>>::Data,
+    ) -> Result<(), Error>
     where
         P: PolyPipeline,
     {
@@ -84,6 +146,13 @@ impl Renderer {
         #[cfg(feature = "opengl")]
         use glutin::dpi::PhysicalSize;
 
+        if self.context_lost {
+            return Err(error::Error::ContextLost.into());
+        }
+        if !self.surface_available() {
+            return Ok(());
+        }
+
         if let Some(size) = self.window().get_inner_size() {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_draw_size");
@@ -101,12 +170,18 @@ impl Renderer {
         {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_draw_pipeapply");
+            let start = Instant::now();
             pipe.apply(&mut self.encoder, self.factory.clone(), data);
+            self.stats.encode = start.elapsed();
+            self.stats.stages.clear();
+            pipe.stage_timings(&mut self.stats.stages);
         }
         {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_draw_encoderflush");
+            let start = Instant::now();
             self.encoder.flush(&mut self.device);
+            self.stats.flush = start.elapsed();
         }
         {
             #[cfg(feature = "profiler")]
@@ -116,11 +191,116 @@ impl Renderer {
         {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_draw_swapbuffers");
+            let start = Instant::now();
             #[cfg(feature = "opengl")]
-            self.window
-                .swap_buffers()
-                .expect("OpenGL context has been lost");
+            if let Err(err) = self.window.swap_buffers() {
+                return Err(match err {
+                    glutin::ContextError::ContextLost => {
+                        self.context_lost = true;
+                        Error::from(error::Error::ContextLost)
+                    }
+                    err => Error::new(err),
+                });
+            }
+            self.stats.present = start.elapsed();
+        }
+        self.stats.frame_number += 1;
+        Ok(())
+    }
+
+    /// Returns `true` once the graphics context was lost, until the renderer is recreated.
+    pub fn context_lost(&self) -> bool {
+        self.context_lost
+    }
+
+    /// Recreates the window and the graphics context, after the context was lost.
+    ///
+    /// Every resource created with the previous context is invalid, so the pipelines, meshes and
+    /// textures have to be created again.
+    pub fn recreate(&mut self) -> Result<(), Error> {
+        let window_builder = self
+            .config
+            .clone()
+            .to_windowbuilder(self.events.get_primary_monitor());
+        let Backend(device, mut factory, main_target, window) =
+            init_backend(window_builder, &self.events, &self.config)?;
+
+        self.cached_size = window
+            .get_inner_size()
+            .ok_or_else(|| format_err!("Unable to fetch window size, as the window went away."))?;
+        self.cached_hidpi_factor = window.get_hidpi_factor();
+        self.encoder = factory.create_command_buffer().into();
+        self.device = device;
+        self.factory = factory;
+        self.main_target = main_target;
+        self.window = window;
+        self.context_lost = false;
+        Ok(())
+    }
+
+    /// Changes how frames are presented to the screen.
+    ///
+    /// On OpenGL this changes the swap interval of the current context, which requires the
+    /// `WGL_EXT_swap_control`, `GLX_MESA_swap_control` or `GLX_SGI_swap_control` extension.
+    /// `GLX_SGI_swap_control` can't disable vertical synchronization, so it is only used for
+    /// `PresentMode::Fifo`. `PresentMode::Mailbox` is treated as `PresentMode::Immediate`.
+    ///
+    /// The applied mode is kept in the `DisplayConfig`, so it survives the recreation of the
+    /// context.
+    #[cfg(feature = "opengl")]
+    pub fn set_present_mode(&mut self, mode: PresentMode) -> Result<(), Error> {
+        use std::mem;
+
+        use glutin::GlContext;
+
+        type SwapInterval = extern "system" fn(i32) -> i32;
+
+        let interval = match mode {
+            PresentMode::Fifo => 1,
+            PresentMode::Mailbox | PresentMode::Immediate => 0,
+        };
+        // `wglSwapIntervalEXT` returns `TRUE` on success, the GLX functions return 0.
+        let functions = [
+            ("wglSwapIntervalEXT", true),
+            ("glXSwapIntervalMESA", false),
+            ("glXSwapIntervalSGI", false),
+        ];
+        for &(name, success) in &functions {
+            if name == "glXSwapIntervalSGI" && interval == 0 {
+                continue;
+            }
+            let address = self.window.get_proc_address(name);
+            if address.is_null() {
+                continue;
+            }
+            let swap_interval: SwapInterval = unsafe { mem::transmute(address) };
+            if (swap_interval(interval) != 0) != success {
+                return Err(format_err!(
+                    "Failed to change the swap interval with {}",
+                    name
+                ));
+            }
+            self.config.present_mode = Some(mode);
+            return Ok(());
         }
+        Err(format_err!(
+            "Changing the present mode to {:?} is not supported by the OpenGL context",
+            mode
+        ))
+    }
+
+    /// Changes how frames are presented to the screen.
+    #[cfg(not(feature = "opengl"))]
+    pub fn set_present_mode(&mut self, mode: PresentMode) -> Result<(), Error> {
+        Err(format_err!(
+            "Changing the present mode to {:?} at runtime is not supported by this backend",
+            mode
+        ))
+    }
+
+    /// Returns the timings of the last frame drawn.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
     }
 
     /// Retrieve a mutable borrow of the events loop
@@ -134,13 +314,17 @@ impl Renderer {
         let mut targets = HashMap::default();
         targets.insert("".to_string(), self.main_target.clone());
         for (key, value) in pipe.targets().iter().filter(|&(k, _)| !k.is_empty()) {
-            let (key, target) = TargetBuilder::new(key.clone())
-                .with_num_color_bufs(value.color_bufs().len())
-                .with_depth_buf(value.depth_buf().is_some())
+            let builder = value.builder().cloned().unwrap_or_else(|| {
+                TargetBuilder::new(key.clone())
+                    .with_num_color_bufs(value.color_bufs().len())
+                    .with_depth_buf(value.depth_buf().is_some())
+            });
+            let (key, target) = builder
                 .build(&mut self.factory, new_size)
                 .expect("Unable to create new target when resizing");
             targets.insert(key, target);
         }
+        link_shared_depth_bufs(&mut targets).expect("Unable to share depth buffers when resizing");
         pipe.new_targets(targets);
     }
 
@@ -225,6 +409,9 @@ impl RendererBuilder {
             multisampling: self.config.multisampling,
             cached_size,
             cached_hidpi_factor,
+            stats: RenderStats::default(),
+            config: self.config,
+            context_lost: false,
         })
     }
 }
@@ -306,14 +493,18 @@ fn init_backend(
 
     let ctx = glutin::ContextBuilder::new()
         .with_multisampling(config.multisampling)
-        .with_vsync(config.vsync);
+        .with_vsync(config.present_mode() == PresentMode::Fifo);
     #[cfg(target_os = "macos")]
     let ctx = ctx
         .with_gl_profile(GlProfile::Core)
         .with_gl(GlRequest::Latest);
+    #[cfg(feature = "gl_debug")]
+    let ctx = ctx.with_gl_debug_flag(true);
 
     let (win, dev, fac, color, depth) =
         gfx_window_glutin::init::<ColorFormat, DepthFormat>(wb, ctx, el);
+    #[cfg(feature = "gl_debug")]
+    gl_debug::enable_debug_output(&win);
     let size = win
         .get_inner_size()
         .ok_or(error::Error::WindowDestroyed)?
@@ -332,3 +523,60 @@ fn init_backend(
 
     Ok(Backend(dev, fac, main_target, win))
 }
+
+/// Forwards the messages of an OpenGL debug context to the log.
+#[cfg(feature = "gl_debug")]
+mod gl_debug {
+    use std::{ffi::CStr, mem, os::raw::c_char, ptr};
+
+    use glutin::GlContext;
+    use log::{debug, error, info, warn};
+
+    use crate::types::Window;
+
+    const DEBUG_OUTPUT: u32 = 0x92E0;
+    const DEBUG_OUTPUT_SYNCHRONOUS: u32 = 0x8242;
+    const DEBUG_SEVERITY_HIGH: u32 = 0x9146;
+    const DEBUG_SEVERITY_MEDIUM: u32 = 0x9147;
+    const DEBUG_SEVERITY_LOW: u32 = 0x9148;
+
+    type DebugProc = extern "system" fn(u32, u32, u32, u32, i32, *const c_char, *mut ());
+    type DebugMessageCallback = extern "system" fn(DebugProc, *const ());
+    type Enable = extern "system" fn(u32);
+
+    extern "system" fn log_message(
+        _source: u32,
+        _ty: u32,
+        id: u32,
+        severity: u32,
+        _length: i32,
+        message: *const c_char,
+        _user: *mut (),
+    ) {
+        let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+        match severity {
+            DEBUG_SEVERITY_HIGH => error!("OpenGL [{}]: {}", id, message),
+            DEBUG_SEVERITY_MEDIUM => warn!("OpenGL [{}]: {}", id, message),
+            DEBUG_SEVERITY_LOW => info!("OpenGL [{}]: {}", id, message),
+            _ => debug!("OpenGL [{}]: {}", id, message),
+        }
+    }
+
+    /// Installs the debug message callback, requires `KHR_debug` or OpenGL 4.3.
+    pub fn enable_debug_output(window: &Window) {
+        let callback = window.get_proc_address("glDebugMessageCallback");
+        let enable = window.get_proc_address("glEnable");
+        if callback.is_null() || enable.is_null() {
+            warn!("OpenGL debug output is not supported by the context");
+            return;
+        }
+
+        unsafe {
+            let callback: DebugMessageCallback = mem::transmute(callback);
+            let enable: Enable = mem::transmute(enable);
+            enable(DEBUG_OUTPUT);
+            enable(DEBUG_OUTPUT_SYNCHRONOUS);
+            callback(log_message, ptr::null());
+        }
+    }
+}
@@ -8,7 +8,7 @@ use amethyst_assets::PrefabData;
 use amethyst_core::ecs::{Entity, Write};
 use amethyst_error::Error;
 
-use crate::color::Rgba;
+use crate::{color::Rgba, config::PresentMode};
 
 /// The ambient color of a scene
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -48,6 +48,7 @@ pub struct WindowMessages {
     // right now as of 2017-10-02 because FnOnce isn't object safe.  It might
     // be possible as soon as FnBox stabilizes.  For now I'll use FnMut instead.
     pub(crate) queue: SmallVec<[Box<dyn FnMut(&Window) + Send + Sync + 'static>; 2]>,
+    pub(crate) present_mode: Option<PresentMode>,
 }
 
 impl WindowMessages {
@@ -63,6 +64,12 @@ impl WindowMessages {
     {
         self.queue.push(Box::new(command));
     }
+
+    /// Change how frames are presented next frame, for example to toggle vertical
+    /// synchronization from a settings menu.
+    pub fn set_present_mode(&mut self, mode: PresentMode) {
+        self.present_mode = Some(mode);
+    }
 }
 
 /// World resource that stores screen dimensions.
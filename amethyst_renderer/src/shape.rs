@@ -13,7 +13,7 @@ use shred_derive::SystemData;
 use amethyst_assets::{AssetStorage, Handle, Loader, PrefabData, Progress, ProgressCounter};
 use amethyst_core::{
     ecs::prelude::{Entity, Read, ReadExpect, WriteStorage},
-    math::{Vector2, Vector3},
+    math::{Matrix3, Matrix4, Point3, Vector2, Vector3, U3},
 };
 use amethyst_error::Error;
 
@@ -103,6 +103,9 @@ pub enum Shape {
     Plane(Option<(usize, usize)>),
     /// Circle, located in the XY plane, number of points around the circle
     Circle(usize),
+    /// Capsule along the z axis with a radius of 1, half the height of the cylindrical part,
+    /// number of points around the radius >= 3, number of rings on each hemisphere >= 1
+    Capsule(f32, usize, usize),
 }
 
 /// `SystemData` needed to upload a `Shape` directly to create a `MeshHandle`
@@ -218,11 +221,197 @@ impl Shape {
                 scale,
             ),
             Shape::Circle(u) => generate_vertices(Circle::new(u), scale),
+            Shape::Capsule(half_height, segments, rings) => {
+                generate_capsule(half_height, segments, rings, scale)
+            }
         };
         InternalShape(vertices)
     }
 }
 
+/// Builder combining shapes and custom triangles into a single `MeshData`.
+///
+/// ```rust,no_run
+/// use amethyst_core::math::{Matrix4, Vector3};
+/// use amethyst_renderer::{PosNormTangTex, Shape, ShapeBuilder};
+///
+/// let data = ShapeBuilder::new()
+///     .with_shape(&Shape::Cylinder(16, None), Some((0.2, 0.2, 1.0)))
+///     .with_transformed_shape(
+///         &Shape::Cone(16),
+///         Some((0.5, 0.5, 0.5)),
+///         &Matrix4::new_translation(&Vector3::new(0.0, 0.0, 1.5)),
+///     )
+///     .build::<Vec<PosNormTangTex>>();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ShapeBuilder {
+    vertices: Vec<VertexFormat>,
+}
+
+impl ShapeBuilder {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds the triangles of a shape, scaled by the given amounts along the x, y, z axes.
+    pub fn with_shape(mut self, shape: &Shape, scale: Option<(f32, f32, f32)>) -> Self {
+        self.vertices.extend(shape.generate_internal(scale).0);
+        self
+    }
+
+    /// Adds the triangles of a scaled shape, moved by the given transformation.
+    pub fn with_transformed_shape(
+        mut self,
+        shape: &Shape,
+        scale: Option<(f32, f32, f32)>,
+        transform: &Matrix4<f32>,
+    ) -> Self {
+        let direction_matrix = transform.fixed_slice::<U3, U3>(0, 0).into_owned();
+        let normal_matrix = direction_matrix
+            .try_inverse()
+            .map(|m| m.transpose())
+            .unwrap_or_else(Matrix3::identity);
+        self.vertices
+            .extend(shape.generate_internal(scale).0.into_iter().map(
+                |(position, normal, tex_coord, tangent)| {
+                    let position = transform.transform_point(&Point3::from(position));
+                    let normal = (normal_matrix * Vector3::from(normal)).normalize();
+                    let tangent = (direction_matrix * Vector3::from(tangent)).normalize();
+                    (
+                        position.coords.into(),
+                        normal.into(),
+                        tex_coord,
+                        tangent.into(),
+                    )
+                },
+            ));
+        self
+    }
+
+    /// Adds a triangle given its vertices as `(position, normal, tex_coord)`, in counter
+    /// clockwise order.
+    ///
+    /// The tangent of the vertices is computed from the texture coordinates.
+    pub fn with_triangle(mut self, vertices: [([f32; 3], [f32; 3], [f32; 2]); 3]) -> Self {
+        let [a, b, c] = vertices;
+        let edge1 = Vector3::from(b.0) - Vector3::from(a.0);
+        let edge2 = Vector3::from(c.0) - Vector3::from(a.0);
+        let (du1, dv1) = (b.2[0] - a.2[0], b.2[1] - a.2[1]);
+        let (du2, dv2) = (c.2[0] - a.2[0], c.2[1] - a.2[1]);
+        let det = du1 * dv2 - du2 * dv1;
+        let uv_tangent = if det.abs() > std::f32::EPSILON {
+            Some((edge1 * dv2 - edge2 * dv1) / det)
+        } else {
+            None
+        };
+
+        for &(position, normal, tex_coord) in &[a, b, c] {
+            let normal = Vector3::from(normal);
+            let tangent = uv_tangent
+                .map(|t| t - normal * normal.dot(&t))
+                .filter(|t| t.norm_squared() > std::f32::EPSILON)
+                .unwrap_or_else(|| normal.cross(&Vector3::y()).cross(&normal))
+                .normalize();
+            self.vertices
+                .push((position, normal.into(), tex_coord, tangent.into()));
+        }
+        self
+    }
+
+    /// Returns the number of vertices added so far.
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Returns `true` if nothing was added to the builder.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Builds the `MeshData`, in vertex format `V`.
+    ///
+    /// ### Type parameters:
+    ///
+    /// `V`: Vertex format to use, must to be one of:
+    ///     * `Vec<PosTex>`
+    ///     * `Vec<PosNormTex>`
+    ///     * `Vec<PosNormTangTex>`
+    ///     * `ComboMeshCreator`
+    pub fn build<V>(self) -> MeshData
+    where
+        V: From<InternalShape> + Into<MeshData>,
+    {
+        V::from(InternalShape(self.vertices)).into()
+    }
+
+    /// Builds the vertices, in format `V`.
+    pub fn build_vertices<V>(self) -> V
+    where
+        V: From<InternalShape>,
+    {
+        V::from(InternalShape(self.vertices))
+    }
+}
+
+fn generate_capsule(
+    half_height: f32,
+    segments: usize,
+    rings: usize,
+    scale: Option<(f32, f32, f32)>,
+) -> Vec<VertexFormat> {
+    use std::f32::consts::PI;
+
+    let segments = segments.max(3);
+    let rings = rings.max(1);
+    let (sx, sy, sz) = scale.unwrap_or((1.0, 1.0, 1.0));
+    let total_height = 2.0 * (half_height + 1.0);
+
+    // Rows of vertices from the top pole to the bottom pole, each row being a polar angle and
+    // the offset of the hemisphere it belongs to.
+    let rows = (0..=rings)
+        .map(|i| (i as f32 / rings as f32 * PI / 2.0, half_height))
+        .chain((0..=rings).map(|i| ((1.0 + i as f32 / rings as f32) * PI / 2.0, -half_height)))
+        .collect::<Vec<_>>();
+
+    let vertex = |row: usize, column: usize| -> VertexFormat {
+        let (polar, offset) = rows[row];
+        let azimuth = column as f32 / segments as f32 * 2.0 * PI;
+        let sphere = Vector3::new(
+            polar.sin() * azimuth.cos(),
+            polar.sin() * azimuth.sin(),
+            polar.cos(),
+        );
+        let position = Vector3::new(sphere.x * sx, sphere.y * sy, (sphere.z + offset) * sz);
+        let normal = Vector3::new(sphere.x * sx, sphere.y * sy, sphere.z * sz).normalize();
+        let tangent = Vector3::new(-azimuth.sin() * sx, azimuth.cos() * sy, 0.0).normalize();
+        let tex_coord = [
+            column as f32 / segments as f32,
+            (sphere.z + offset + half_height + 1.0) / total_height,
+        ];
+        (position.into(), normal.into(), tex_coord, tangent.into())
+    };
+
+    let mut vertices = Vec::with_capacity((rows.len() - 1) * segments * 6);
+    for row in 0..rows.len() - 1 {
+        for column in 0..segments {
+            // The first row collapses into the top pole, the last one into the bottom pole.
+            if row != 0 {
+                vertices.push(vertex(row, column));
+                vertices.push(vertex(row + 1, column));
+                vertices.push(vertex(row, column + 1));
+            }
+            if row + 2 != rows.len() {
+                vertices.push(vertex(row, column + 1));
+                vertices.push(vertex(row + 1, column));
+                vertices.push(vertex(row + 1, column + 1));
+            }
+        }
+    }
+    vertices
+}
+
 fn generate_vertices<F, P, G>(generator: G, scale: Option<(f32, f32, f32)>) -> Vec<VertexFormat>
 where
     F: EmitTriangles<Vertex = Vertex>,
@@ -339,6 +528,32 @@ impl From<InternalShape> for ComboMeshCreator {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capsule() {
+        let vertices = Shape::Capsule(0.5, 8, 4).generate_vertices::<Vec<PosNormTex>>(None);
+        // Each hemisphere has one row of triangles at the pole and three rows of quads, plus
+        // the quads of the cylindrical part.
+        assert_eq!(vertices.len(), (2 * (1 + 3 * 2) + 2) * 8 * 3);
+        for v in vertices {
+            assert!(v.position.z.abs() <= 1.5 + 1e-5);
+            assert!((v.normal.norm() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_shape_builder() {
+        let builder = ShapeBuilder::new()
+            .with_shape(&Shape::Cube, None)
+            .with_triangle([
+                ([0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0]),
+                ([1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [1.0, 0.0]),
+                ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0]),
+            ]);
+        assert_eq!(builder.len(), 36 + 3);
+        let vertices = builder.build_vertices::<Vec<PosNormTangTex>>();
+        assert_eq!(vertices[36].tangent, Vector3::new(1.0, 0.0, 0.0));
+    }
+
     #[test]
     fn test_plane() {
         println!(
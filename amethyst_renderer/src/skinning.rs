@@ -14,6 +14,11 @@ use crate::{
     vertex::{Attribute, Color, Normal, Position, Separate, Tangent, TexCoord},
 };
 
+/// Maximum number of joint matrices of a `JointTransforms` uploaded by the skinning passes.
+///
+/// Must match the size of the `JointTransforms` uniform block in `shaders/vertex/skinned.glsl`.
+pub const MAX_JOINTS: usize = 100;
+
 /// Type for joint weights attribute of vertex
 #[derive(Clone, Debug)]
 pub enum JointWeights {}
@@ -35,6 +40,9 @@ impl Attribute for JointIds {
 }
 
 /// Transform storage for the skin, should be attached to all mesh entities that use a skin
+///
+/// Passes with vertex skinning enabled only draw entities with this component, and upload at
+/// most `MAX_JOINTS` matrices per entity.
 #[derive(Debug, Clone)]
 pub struct JointTransforms {
     /// Skin entity
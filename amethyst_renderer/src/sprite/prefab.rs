@@ -1,10 +1,10 @@
 use log::warn;
 use serde::{Deserialize, Serialize};
 
-use amethyst_assets::{AssetStorage, PrefabData, ProgressCounter};
+use amethyst_assets::{AssetStorage, PrefabData, PrefabExport, ProgressCounter, WeakHandle};
 use amethyst_core::{
-    ecs::{Entity, Read, Write, WriteStorage},
-    Transform,
+    ecs::{Entities, Entity, Read, ReadStorage, Write, WriteStorage},
+    Parent, Transform,
 };
 use amethyst_error::Error;
 
@@ -165,19 +165,34 @@ impl<'a> PrefabData<'a> for SpriteSheetPrefab {
     }
 }
 
+/// Sprite sheets loaded by prefabs, referenced by `SpriteRenderPrefab`.
+///
+/// Sheets are kept as weak handles, so they are unloaded along with the prefabs and entities
+/// using them.
 #[derive(Clone, Debug, Default)]
-pub struct SpriteSheetLoadedSet(pub Vec<(Option<String>, SpriteSheetHandle)>);
+pub struct SpriteSheetLoadedSet(pub Vec<(Option<String>, WeakHandle<SpriteSheet>)>);
 
 impl SpriteSheetLoadedSet {
-    fn get(&self, reference: &SpriteSheetReference) -> Option<&SpriteSheetHandle> {
-        match reference {
+    fn get(&self, reference: &SpriteSheetReference) -> Option<SpriteSheetHandle> {
+        let handle = match reference {
             SpriteSheetReference::Index(index) => self.0.get(*index).map(|(_, handle)| handle),
             SpriteSheetReference::Name(name) => self
                 .0
                 .iter()
-                .find(|s| s.0.as_ref() == Some(name))
+                .find(|s| s.0.as_ref() == Some(name) && !s.1.is_dead())
                 .map(|(_, handle)| handle),
-        }
+        };
+        handle.and_then(WeakHandle::upgrade)
+    }
+
+    fn reference(&self, handle: &SpriteSheetHandle) -> Option<SpriteSheetReference> {
+        self.0
+            .iter()
+            .position(|(_, weak)| weak.upgrade().as_ref() == Some(handle))
+            .map(|index| match self.0[index].0 {
+                Some(ref name) => SpriteSheetReference::Name(name.clone()),
+                None => SpriteSheetReference::Index(index),
+            })
     }
 }
 
@@ -243,7 +258,7 @@ impl<'a> PrefabData<'a> for SpriteRenderPrefab {
         _: &mut ProgressCounter,
         system_data: &mut Self::SystemData,
     ) -> Result<bool, Error> {
-        if let Some(handle) = (*system_data.1).get(&self.sheet).cloned() {
+        if let Some(handle) = (*system_data.1).get(&self.sheet) {
             self.handle = Some(handle);
             Ok(false)
         } else {
@@ -253,7 +268,62 @@ impl<'a> PrefabData<'a> for SpriteRenderPrefab {
     }
 }
 
+impl<'a> PrefabExport<'a> for SpriteRenderPrefab {
+    type SystemData = (
+        ReadStorage<'a, SpriteRender>,
+        Read<'a, SpriteSheetLoadedSet>,
+    );
+
+    fn export(
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        _entities: &[Entity],
+    ) -> Result<Option<Self>, Error> {
+        let render = match system_data.0.get(entity) {
+            Some(render) => render,
+            None => return Ok(None),
+        };
+        match system_data.1.reference(&render.sprite_sheet) {
+            Some(sheet) => Ok(Some(SpriteRenderPrefab {
+                sheet,
+                sprite_number: render.sprite_number,
+                handle: Some(render.sprite_sheet.clone()),
+            })),
+            None => {
+                let message = format!(
+                    "`SpriteSheet` with id {} was not loaded by a prefab.",
+                    render.sprite_sheet.id()
+                );
+                Err(Error::from_string(message))
+            }
+        }
+    }
+}
+
 /// Prefab for loading a full scene with sprites.
+///
+/// Besides its own sprite and transform, a scene can declare `children`, each created as a new
+/// entity with a `Parent` pointing to the entity of the scene. Their transforms are relative to
+/// the parent, and they can use the sprite sheets of their ancestors by name:
+///
+/// ```ron
+/// (
+///     sheet: Sheet(texture: File("texture/ship.png", Png, (channel: Srgb)), sprites: [...], name: "ship"),
+///     render: (sheet: "ship", sprite_number: 0),
+///     transform: (translation: (200.0, 100.0, 0.0)),
+///     children: [
+///         (
+///             render: (sheet: "ship", sprite_number: 1),
+///             transform: (translation: (-16.0, 0.0, 0.1)),
+///         ),
+///     ],
+/// )
+/// ```
+///
+/// Exporting a scene with `export_prefab` writes each entity with its sprite and transform,
+/// parented through the prefab instead of `children`. Sprite sheets are referenced by name, or by
+/// index when they have none, so they must be loaded by another prefab before loading the
+/// exported scene.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SpriteScenePrefab {
     /// Sprite sheets
@@ -262,6 +332,9 @@ pub struct SpriteScenePrefab {
     pub render: Option<SpriteRenderPrefab>,
     /// Add `Transform` to the `Entity`
     pub transform: Option<Transform>,
+    /// Child entities, parented to the `Entity`
+    #[serde(default)]
+    pub children: Vec<SpriteScenePrefab>,
 }
 
 impl<'a> PrefabData<'a> for SpriteScenePrefab {
@@ -269,6 +342,8 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
         <SpriteSheetPrefab as PrefabData<'a>>::SystemData,
         <SpriteRenderPrefab as PrefabData<'a>>::SystemData,
         <Transform as PrefabData<'a>>::SystemData,
+        Entities<'a>,
+        WriteStorage<'a, Parent>,
     );
     type Result = ();
 
@@ -285,6 +360,11 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
         if let Some(transform) = &self.transform {
             transform.add_to_entity(entity, &mut system_data.2, entities, children)?;
         }
+        for child in &self.children {
+            let child_entity = system_data.3.create();
+            system_data.4.insert(child_entity, Parent { entity })?;
+            child.add_to_entity(child_entity, system_data, entities, &[])?;
+        }
         Ok(())
     }
 
@@ -299,7 +379,7 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
                 ret = true;
             }
             let sheet = match sheet {
-                SpriteSheetPrefab::Handle(handle) => handle.clone(),
+                SpriteSheetPrefab::Handle((name, handle)) => (name.clone(), handle.downgrade()),
                 _ => unreachable!(),
             };
             ((system_data.1).1).0.push(sheet);
@@ -307,10 +387,40 @@ impl<'a> PrefabData<'a> for SpriteScenePrefab {
         if let Some(ref mut render) = &mut self.render {
             render.load_sub_assets(progress, &mut system_data.1)?;
         }
+        for child in &mut self.children {
+            if child.load_sub_assets(progress, system_data)? {
+                ret = true;
+            }
+        }
         Ok(ret)
     }
 }
 
+impl<'a> PrefabExport<'a> for SpriteScenePrefab {
+    type SystemData = (
+        <SpriteRenderPrefab as PrefabExport<'a>>::SystemData,
+        <Transform as PrefabExport<'a>>::SystemData,
+    );
+
+    fn export(
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        entities: &[Entity],
+    ) -> Result<Option<Self>, Error> {
+        let render = SpriteRenderPrefab::export(entity, &mut system_data.0, entities)?;
+        let transform = Transform::export(entity, &mut system_data.1, entities)?;
+        if render.is_none() && transform.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(SpriteScenePrefab {
+            sheet: None,
+            render,
+            transform,
+            children: Vec::new(),
+        }))
+    }
+}
+
 impl Sprites {
     fn build_sprites(&self) -> Vec<Sprite> {
         match self {
@@ -433,7 +543,7 @@ mod tests {
     use super::*;
     use crate::Texture;
     use amethyst_assets::{Handle, Loader};
-    use amethyst_core::ecs::{Builder, Read, ReadExpect, World};
+    use amethyst_core::ecs::{Builder, Join, Read, ReadExpect, World};
     use rayon::ThreadPoolBuilder;
     use std::sync::Arc;
 
@@ -467,7 +577,7 @@ mod tests {
                 &data.1,
             );
             let index = (data.2).0.len();
-            (data.2).0.push((None, spritesheet.clone()));
+            (data.2).0.push((None, spritesheet.downgrade()));
             (SpriteSheetReference::Index(index), spritesheet)
         })
     }
@@ -553,6 +663,47 @@ mod tests {
         assert_eq!(handle, render.sprite_sheet);
     }
 
+    #[test]
+    fn sprite_scene_prefab_children() {
+        let mut world = setup_sprite_world();
+        world.register::<Transform>();
+        world.register::<Parent>();
+        let (sheet, handle) = add_sheet(&mut world);
+        let render = |sprite_number| SpriteRenderPrefab {
+            sheet: sheet.clone(),
+            sprite_number,
+            handle: None,
+        };
+        let mut prefab = SpriteScenePrefab {
+            sheet: None,
+            render: Some(render(0)),
+            transform: None,
+            children: vec![SpriteScenePrefab {
+                sheet: None,
+                render: Some(render(1)),
+                transform: Some(Transform::default()),
+                children: vec![],
+            }],
+        };
+        prefab
+            .load_sub_assets(&mut ProgressCounter::default(), &mut world.system_data())
+            .unwrap();
+        let entity = world.create_entity().build();
+        prefab
+            .add_to_entity(entity, &mut world.system_data(), &[entity], &[])
+            .unwrap();
+        let parents = world.read_storage::<Parent>();
+        let renders = world.read_storage::<SpriteRender>();
+        let (child, _) = (&world.entities(), &parents)
+            .join()
+            .find(|(_, parent)| parent.entity == entity)
+            .expect("child entity was not created");
+        let render = renders.get(child).unwrap();
+        assert_eq!(1, render.sprite_number);
+        assert_eq!(handle, render.sprite_sheet);
+        assert!(world.read_storage::<Transform>().get(child).is_some());
+    }
+
     #[test]
     fn grid_col_row() {
         let sprites = SpriteGrid {
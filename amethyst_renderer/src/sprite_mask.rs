@@ -0,0 +1,112 @@
+use fnv::FnvHashMap as HashMap;
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, Entities, Entity, Join, ReadStorage},
+    math::{convert, Matrix4, Point3},
+    transform::ParentHierarchy,
+    Float, Transform,
+};
+
+/// Restricts the rendering of all sprites and images below this entity in the transform
+/// hierarchy to a rectangle.
+///
+/// The rectangle is given in the local space of the entity and follows its `Transform`.
+/// Rotated masks clip to the axis aligned bounding box of the rotated rectangle. Nested masks
+/// are intersected. The masking entity itself is not clipped.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpriteMask {
+    /// Left edge of the rectangle.
+    pub left: f32,
+    /// Right edge of the rectangle.
+    pub right: f32,
+    /// Bottom edge of the rectangle.
+    pub bottom: f32,
+    /// Top edge of the rectangle.
+    pub top: f32,
+}
+
+impl SpriteMask {
+    /// Creates a mask of the given size centered on the entity.
+    pub fn new(width: f32, height: f32) -> Self {
+        SpriteMask {
+            left: -width / 2.0,
+            right: width / 2.0,
+            bottom: -height / 2.0,
+            top: height / 2.0,
+        }
+    }
+
+    /// Returns the `[left, bottom, right, top]` bounds of the mask after applying the global
+    /// matrix of the entity.
+    pub fn bounds(&self, transform: &Transform) -> [f32; 4] {
+        let matrix = convert::<Matrix4<Float>, Matrix4<f32>>(*transform.global_matrix());
+        let mut bounds = [std::f32::MAX, std::f32::MAX, std::f32::MIN, std::f32::MIN];
+        for &(x, y) in &[
+            (self.left, self.bottom),
+            (self.right, self.bottom),
+            (self.right, self.top),
+            (self.left, self.top),
+        ] {
+            let corner = matrix.transform_point(&Point3::new(x, y, 0.0));
+            bounds = [
+                bounds[0].min(corner.x),
+                bounds[1].min(corner.y),
+                bounds[2].max(corner.x),
+                bounds[3].max(corner.y),
+            ];
+        }
+        bounds
+    }
+}
+
+impl Component for SpriteMask {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Bounds of the masks applying to each masked entity, collected once per frame.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MaskBounds {
+    bounds: HashMap<u32, [f32; 4]>,
+}
+
+impl MaskBounds {
+    /// Bounds used for entities that are not masked.
+    pub const UNMASKED: [f32; 4] = [std::f32::MIN, std::f32::MIN, std::f32::MAX, std::f32::MAX];
+
+    /// Collects the bounds of every entity below a `SpriteMask` in the hierarchy.
+    pub fn update(
+        &mut self,
+        entities: &Entities<'_>,
+        masks: &ReadStorage<'_, SpriteMask>,
+        transforms: &ReadStorage<'_, Transform>,
+        hierarchy: Option<&ParentHierarchy>,
+    ) {
+        self.bounds.clear();
+        let hierarchy = match hierarchy {
+            Some(hierarchy) => hierarchy,
+            None => return,
+        };
+
+        for (entity, mask, transform) in (&**entities, masks, transforms).join() {
+            let [left, bottom, right, top] = mask.bounds(transform);
+            for child in hierarchy.all_children_iter(entity) {
+                let bounds = self
+                    .bounds
+                    .entry(child.id())
+                    .or_insert(MaskBounds::UNMASKED);
+                *bounds = [
+                    bounds[0].max(left),
+                    bounds[1].max(bottom),
+                    bounds[2].min(right),
+                    bounds[3].min(top),
+                ];
+            }
+        }
+    }
+
+    /// Returns the `[left, bottom, right, top]` bounds the entity is clipped to, if it is masked.
+    pub fn get(&self, entity: Entity) -> Option<[f32; 4]> {
+        self.bounds.get(&entity.id()).cloned()
+    }
+}
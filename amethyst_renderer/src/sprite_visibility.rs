@@ -1,9 +1,12 @@
 use std::cmp::Ordering;
 
 use hibitset::BitSet;
+use serde::{Deserialize, Serialize};
 
 use amethyst_core::{
-    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, Write},
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, Write,
+    },
     math::{Point3, Vector3},
     Float, Transform,
 };
@@ -25,8 +28,24 @@ pub struct SpriteVisibility {
     pub visible_ordered: Vec<Entity>,
 }
 
+/// Explicit drawing layer of a sprite.
+///
+/// Sprites drawn in order are sorted by layer first, lower layers being drawn first, and then by
+/// their position on the Z axis. Entities without this component are on layer `0`. Sprites on a
+/// layer are always drawn in order, even if they are not `Transparent`, so overlays and shadows
+/// at the same depth as other sprites are drawn consistently above or below them.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize,
+)]
+pub struct SpriteLayer(pub i32);
+
+impl Component for SpriteLayer {
+    type Storage = DenseVecStorage<Self>;
+}
+
 /// Determines what entities to be drawn. Will also sort transparent entities back to front based on
-/// position on the Z axis.
+/// their `SpriteLayer` and position on the Z axis. Entities with equal layer and position are
+/// kept in entity order, so their draw order does not change from frame to frame.
 ///
 /// The sprite render pass should draw all sprites without semi-transparent pixels, then draw the
 /// sprites with semi-transparent pixels from far to near.
@@ -42,7 +61,8 @@ pub struct SpriteVisibilitySortingSystem {
 #[derive(Clone)]
 struct Internals {
     entity: Entity,
-    transparent: bool,
+    ordered: bool,
+    layer: i32,
     centroid: Point3<Float>,
     from_camera: Vector3<Float>,
 }
@@ -65,6 +85,7 @@ impl<'a> System<'a> for SpriteVisibilitySortingSystem {
         ReadStorage<'a, Transparent>,
         ReadStorage<'a, Transform>,
         ReadStorage<'a, ScreenSpace>,
+        ReadStorage<'a, SpriteLayer>,
     );
 
     fn run(
@@ -79,6 +100,7 @@ impl<'a> System<'a> for SpriteVisibilitySortingSystem {
             transparent,
             transform,
             screen_spaces,
+            layers,
         ): Self::SystemData,
     ) {
         let origin = Point3::origin();
@@ -117,7 +139,8 @@ impl<'a> System<'a> for SpriteVisibilitySortingSystem {
                     (
                         Internals {
                             entity,
-                            transparent: transparent.contains(entity),
+                            ordered: transparent.contains(entity) || layers.contains(entity),
+                            layer: layers.get(entity).map_or(0, |layer| layer.0),
                             centroid,
                             from_camera: centroid - camera_centroid,
                         },
@@ -132,19 +155,24 @@ impl<'a> System<'a> for SpriteVisibilitySortingSystem {
         );
         self.transparent.clear();
         self.transparent
-            .extend(self.centroids.iter().filter(|c| c.transparent).cloned());
+            .extend(self.centroids.iter().filter(|c| c.ordered).cloned());
 
         // Note: Smaller Z values are placed first, so that semi-transparent sprite colors blend
         // correctly. This is opposite to the mesh visibility sorting system.
         self.transparent.sort_by(|a, b| {
-            a.centroid
-                .z
-                .partial_cmp(&b.centroid.z)
-                .unwrap_or(Ordering::Equal)
+            a.layer
+                .cmp(&b.layer)
+                .then_with(|| {
+                    a.centroid
+                        .z
+                        .partial_cmp(&b.centroid.z)
+                        .unwrap_or(Ordering::Equal)
+                })
+                .then_with(|| a.entity.id().cmp(&b.entity.id()))
         });
         visibility.visible_unordered.clear();
         for c in &self.centroids {
-            if !c.transparent {
+            if !c.ordered {
                 visibility.visible_unordered.add(c.entity.id());
             }
         }
@@ -155,3 +183,76 @@ impl<'a> System<'a> for SpriteVisibilitySortingSystem {
             .extend(self.transparent.iter().map(|c| c.entity));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        bundle::SystemBundle,
+        ecs::prelude::{Builder, DispatcherBuilder, World},
+        Transform, TransformBundle,
+    };
+
+    use crate::transparent::Transparent;
+
+    use super::{SpriteLayer, SpriteVisibility, SpriteVisibilitySortingSystem};
+
+    fn transform(z: f32) -> Transform {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(0.0, 0.0, z);
+        transform
+    }
+
+    #[test]
+    fn sorts_by_layer_then_z_then_entity() {
+        let mut world = World::new();
+        let mut builder = DispatcherBuilder::new();
+        TransformBundle::new().build(&mut builder).unwrap();
+        builder.add(
+            SpriteVisibilitySortingSystem::new(),
+            "sprite_visibility",
+            &["transform_system"],
+        );
+        let mut dispatcher = builder.build();
+        dispatcher.setup(&mut world.res);
+
+        // An opaque sprite on a layer above transparent sprites at the same depth.
+        let overlay = world
+            .create_entity()
+            .with(transform(-3.0))
+            .with(SpriteLayer(1))
+            .build();
+        let first = world
+            .create_entity()
+            .with(transform(-3.0))
+            .with(Transparent)
+            .build();
+        let second = world
+            .create_entity()
+            .with(transform(-3.0))
+            .with(Transparent)
+            .build();
+        let background = world
+            .create_entity()
+            .with(transform(-1.0))
+            .with(Transparent)
+            .with(SpriteLayer(-1))
+            .build();
+        let shadow = world
+            .create_entity()
+            .with(transform(-1.0))
+            .with(SpriteLayer(-1))
+            .build();
+        let opaque = world.create_entity().with(transform(-2.0)).build();
+
+        for _ in 0..2 {
+            dispatcher.dispatch(&world.res);
+            let visibility = world.read_resource::<SpriteVisibility>();
+            assert_eq!(
+                visibility.visible_ordered,
+                vec![background, shadow, first, second, overlay]
+            );
+            assert!(visibility.visible_unordered.contains(opaque.id()));
+            assert!(!visibility.visible_unordered.contains(overlay.id()));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Frame timing statistics collected by the renderer.
+
+use std::time::Duration;
+
+/// Time spent recording the commands of a single pipeline stage.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StageTiming {
+    /// Name of the target the stage renders into. Empty for the backbuffer.
+    pub target: String,
+    /// Time spent clearing the target and encoding all passes of the stage.
+    pub encode: Duration,
+}
+
+/// Timings of the last rendered frame.
+///
+/// This resource is written by the `RenderSystem` after every frame. The `gfx` backend
+/// used by this renderer does not expose timestamp queries, so the timings are measured
+/// on the CPU: `encode` covers command recording, while `flush` and `present` include
+/// the time the driver blocks on the GPU.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// Number of frames rendered so far.
+    pub frame_number: u64,
+    /// Time spent encoding the whole pipeline.
+    pub encode: Duration,
+    /// Time spent submitting the encoded commands to the device.
+    pub flush: Duration,
+    /// Time spent presenting the frame.
+    pub present: Duration,
+    /// Per stage encoding timings, in pipeline order.
+    pub stages: Vec<StageTiming>,
+}
+
+impl RenderStats {
+    /// Total time spent rendering the last frame.
+    pub fn frame_time(&self) -> Duration {
+        self.encode + self.flush + self.present
+    }
+
+    /// Returns the timing of the first stage rendering into `target`.
+    pub fn stage(&self, target: &str) -> Option<&StageTiming> {
+        self.stages.iter().find(|stage| stage.target == target)
+    }
+}
@@ -11,7 +11,7 @@ use winit::{DeviceEvent, Event, WindowEvent};
 #[cfg(feature = "profiler")]
 use thread_profiler::profile_scope;
 
-use amethyst_assets::{AssetStorage, HotReloadStrategy};
+use amethyst_assets::{AssetStorage, HotReloadStrategy, ProcessingState};
 use amethyst_core::{
     ecs::prelude::{Read, ReadExpect, Resources, RunNow, SystemData, Write, WriteExpect},
     shrev::EventChannel,
@@ -22,22 +22,58 @@ use amethyst_error::Error;
 use crate::{
     config::DisplayConfig,
     formats::{create_mesh_asset, create_texture_asset},
-    mesh::Mesh,
+    mesh::{Mesh, MeshUpdates},
+    metrics::RenderMetrics,
     mtl::{Material, MaterialDefaults},
     pipe::{PipelineBuild, PipelineData, PolyPipeline},
     renderer::Renderer,
     resources::{ScreenDimensions, WindowMessages},
+    stats::RenderStats,
     tex::Texture,
+    window::Monitors,
 };
 
+/// Rebuilds the pipeline after the graphics context was recreated.
+pub(crate) type PipeRebuild<P> = Box<dyn Fn(&mut Renderer) -> Result<P, Error>>;
+
+/// Events written by the `RenderSystem` when it can't draw, so games can show a notice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderEvent {
+    /// The window has no surface to draw on, usually because it is minimized. Nothing is drawn
+    /// until `SurfaceRestored`.
+    SurfaceLost,
+    /// The window has a surface to draw on again.
+    SurfaceRestored,
+    /// The graphics context was lost, for example after a GPU reset or a driver update. Nothing
+    /// is drawn until it is recreated, which only happens if it was enabled with
+    /// `RenderSystem::with_context_recovery`.
+    ContextLost,
+    /// The window and graphics context were recreated, and the pipeline rebuilt. The meshes and
+    /// textures loaded before `ContextLost` belonged to the lost context and were removed from
+    /// their storages. The ones loaded with hot reloading enabled in the `Loader` are loaded again,
+    /// the others, like the ones created with `Loader::load_from_data`, must be loaded again by
+    /// the game.
+    ContextRestored,
+}
+
 /// Rendering system.
+///
+/// Frames aren't drawn while the window is minimized or the graphics context is lost, which is
+/// notified with `RenderEvent`s.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct RenderSystem<P> {
     pipe: P,
     #[derivative(Debug = "ignore")]
     renderer: Renderer,
+    #[derivative(Debug = "ignore")]
+    pipe_rebuild: Option<PipeRebuild<P>>,
     cached_size: (f64, f64),
+    monitors_dirty: bool,
+    surface_lost: bool,
+    recovering: bool,
+    // Meshes created and updated since the last frame.
+    uploads: RenderMetrics,
     // This only exists to allow the system to re-use a vec allocation
     // during event compression.  It's length 0 except during `fn render`.
     event_vec: Vec<Event>,
@@ -87,25 +123,97 @@ where
         Self {
             pipe,
             renderer,
+            pipe_rebuild: None,
             cached_size,
+            monitors_dirty: true,
+            surface_lost: false,
+            recovering: false,
+            uploads: RenderMetrics::default(),
             event_vec: Vec::with_capacity(20),
         }
     }
 
+    /// Enables recreating the window and graphics context when the context is lost, rebuilding
+    /// the pipeline from `pipe`.
+    pub fn with_context_recovery<B>(self, pipe: B) -> Self
+    where
+        B: PipelineBuild<Pipeline = P> + Clone + 'static,
+    {
+        self.with_pipe_rebuild(Some(pipe_rebuild(pipe)))
+    }
+
+    pub(crate) fn with_pipe_rebuild(mut self, pipe_rebuild: Option<PipeRebuild<P>>) -> Self {
+        self.pipe_rebuild = pipe_rebuild;
+        self
+    }
+
+    fn recover(&mut self, res: &Resources) {
+        let pipe_rebuild = match self.pipe_rebuild {
+            Some(ref pipe_rebuild) => pipe_rebuild,
+            None => return,
+        };
+        if self.renderer.context_lost() {
+            if let Err(err) = self.renderer.recreate() {
+                error!("Failed to recreate the renderer: {}", err);
+                return;
+            }
+            self.monitors_dirty = true;
+        }
+        match pipe_rebuild(&mut self.renderer) {
+            Ok(pipe) => self.pipe = pipe,
+            Err(err) => {
+                error!("Failed to rebuild the pipeline: {}", err);
+                return;
+            }
+        }
+        self.recovering = false;
+
+        // The meshes and textures belong to the lost context, load them again in the new one
+        let pool = res.fetch::<Arc<ThreadPool>>();
+        res.fetch_mut::<AssetStorage<Mesh>>()
+            .reload_all(|_| {}, &pool);
+        res.fetch_mut::<AssetStorage<Texture>>()
+            .reload_all(|_| {}, &pool);
+        let mat = create_default_mat(res);
+        res.fetch_mut::<MaterialDefaults>().0 = mat;
+        res.fetch_mut::<EventChannel<RenderEvent>>()
+            .single_write(RenderEvent::ContextRestored);
+    }
+
     fn asset_loading(
         &mut self,
-        (time, pool, strategy, mut mesh_storage, mut texture_storage): AssetLoadingData<'_>,
+        (
+            time,
+            pool,
+            strategy,
+            mut mesh_storage,
+            mut texture_storage,
+            mut metrics,
+            mut mesh_updates,
+        ): AssetLoadingData<'_>,
     ) {
         use std::ops::Deref;
 
         let strategy = strategy.as_ref().map(Deref::deref);
 
+        let renderer = &mut self.renderer;
+        let uploads = &mut self.uploads;
         mesh_storage.process(
-            |d| create_mesh_asset(d, &mut self.renderer),
+            |d| {
+                let state = create_mesh_asset(d, renderer)?;
+                if let ProcessingState::Loaded(ref mesh) = state {
+                    uploads.record_upload(mesh.size_in_bytes());
+                }
+                Ok(state)
+            },
             time.frame_number(),
             &**pool,
             strategy,
         );
+        if !mesh_updates.is_empty() {
+            self.renderer
+                .update_meshes(&mut mesh_updates, &mesh_storage, &mut self.uploads);
+        }
 
         texture_storage.process(
             |d| create_texture_asset(d, &mut self.renderer),
@@ -113,14 +221,35 @@ where
             &**pool,
             strategy,
         );
+
+        let stats = texture_storage.stats();
+        metrics.textures = stats.assets;
+        metrics.texture_memory = stats.bytes;
     }
 
-    fn window_management(&mut self, (mut window_messages, mut screen_dimensions): WindowData<'_>) {
+    fn window_management(
+        &mut self,
+        (mut window_messages, mut screen_dimensions, mut monitors): WindowData<'_>,
+    ) {
         // Process window commands
+        if !window_messages.queue.is_empty() {
+            self.monitors_dirty = true;
+        }
         for mut command in window_messages.queue.drain() {
             command(self.renderer.window());
         }
 
+        if let Some(mode) = window_messages.present_mode.take() {
+            if let Err(err) = self.renderer.set_present_mode(mode) {
+                error!("Failed to change the present mode: {}", err);
+            }
+        }
+
+        if self.monitors_dirty {
+            *monitors = Monitors::from_window(self.renderer.window());
+            self.monitors_dirty = false;
+        }
+
         let width = screen_dimensions.w;
         let height = screen_dimensions.h;
 
@@ -149,12 +278,54 @@ where
         screen_dimensions.update_hidpi_factor(hidpi);
     }
 
-    fn render(&mut self, (mut event_handler, data): RenderData<'_, P>) {
-        self.renderer.draw(&mut self.pipe, data);
+    fn render(
+        &mut self,
+        (mut event_handler, mut render_events, mut stats, mut metrics, data): RenderData<'_, P>,
+    ) {
+        let surface_available = self.renderer.surface_available();
+        if surface_available == self.surface_lost {
+            self.surface_lost = !surface_available;
+            render_events.single_write(if surface_available {
+                RenderEvent::SurfaceRestored
+            } else {
+                RenderEvent::SurfaceLost
+            });
+        }
+
+        if !self.recovering {
+            match self.renderer.draw(&mut self.pipe, data) {
+                Ok(()) => {
+                    stats.clone_from(self.renderer.stats());
+                    metrics.reset_counts();
+                    metrics.add_counts(&self.uploads);
+                    self.uploads.reset_counts();
+                    self.pipe.take_metrics(&mut *metrics);
+                }
+                Err(err) => {
+                    error!("Failed to draw a frame: {}", err);
+                    if self.renderer.context_lost() {
+                        self.recovering = true;
+                        render_events.single_write(RenderEvent::ContextLost);
+                    }
+                }
+            }
+        }
+
         let events = &mut self.event_vec;
         self.renderer.events_mut().poll_events(|new_event| {
             compress_events(events, new_event);
         });
+        self.monitors_dirty |= events.iter().any(|event| match event {
+            Event::WindowEvent {
+                event: WindowEvent::Moved(_),
+                ..
+            }
+            | Event::WindowEvent {
+                event: WindowEvent::HiDpiFactorChanged(_),
+                ..
+            } => true,
+            _ => false,
+        });
         event_handler.iter_write(events.drain(..));
     }
 }
@@ -165,12 +336,21 @@ type AssetLoadingData<'a> = (
     Option<Read<'a, HotReloadStrategy>>,
     Write<'a, AssetStorage<Mesh>>,
     Write<'a, AssetStorage<Texture>>,
+    Write<'a, RenderMetrics>,
+    Write<'a, MeshUpdates>,
 );
 
-type WindowData<'a> = (Write<'a, WindowMessages>, WriteExpect<'a, ScreenDimensions>);
+type WindowData<'a> = (
+    Write<'a, WindowMessages>,
+    WriteExpect<'a, ScreenDimensions>,
+    Write<'a, Monitors>,
+);
 
 type RenderData<'a, P> = (
     Write<'a, EventChannel<Event>>,
+    Write<'a, EventChannel<RenderEvent>>,
+    Write<'a, RenderStats>,
+    Write<'a, RenderMetrics>,
     <P as PipelineData<'a>>::Data,
 );
 
@@ -181,6 +361,9 @@ where
     fn run_now(&mut self, res: &'a Resources) {
         #[cfg(feature = "profiler")]
         profile_scope!("render_system");
+        if self.recovering {
+            self.recover(res);
+        }
         {
             #[cfg(feature = "profiler")]
             profile_scope!("render_system_assetloading");
@@ -216,7 +399,15 @@ where
     }
 }
 
-fn create_default_mat(res: &mut Resources) -> Material {
+pub(crate) fn pipe_rebuild<B, P>(pipe: B) -> PipeRebuild<P>
+where
+    B: PipelineBuild<Pipeline = P> + Clone + 'static,
+    P: PolyPipeline,
+{
+    Box::new(move |renderer| renderer.create_pipe(pipe.clone()))
+}
+
+pub(crate) fn create_default_mat(res: &Resources) -> Material {
     use crate::mtl::TextureOffset;
 
     use amethyst_assets::Loader;
@@ -247,6 +438,7 @@ fn create_default_mat(res: &mut Resources) -> Material {
         albedo_offset: TextureOffset::default(),
         emission,
         emission_offset: TextureOffset::default(),
+        emission_intensity: 1.0,
         normal,
         normal_offset: TextureOffset::default(),
         metallic,
@@ -257,6 +449,7 @@ fn create_default_mat(res: &mut Resources) -> Material {
         ambient_occlusion_offset: TextureOffset::default(),
         caveat,
         caveat_offset: TextureOffset::default(),
+        uniform: None,
     }
 }
 
@@ -0,0 +1,349 @@
+//! Heightmap based terrain, drawn by the `DrawTerrain` pass.
+
+use image::GenericImageView;
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use amethyst_core::{
+    ecs::prelude::{Component, DenseVecStorage, VecStorage},
+    math::{Matrix4, Point3, Vector2, Vector3, Vector4},
+};
+use amethyst_error::Error;
+
+use crate::{error, formats::ImageData, tex::TextureHandle, vertex::PosNormTex};
+
+/// An asset handle to a heightmap.
+pub type HeightmapHandle = Handle<Heightmap>;
+
+/// Grid of heights, in the range `[0, 1]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heightmap {
+    width: usize,
+    depth: usize,
+    heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Creates a heightmap of `width` x `depth` samples, stored row by row.
+    ///
+    /// Fails if the number of heights doesn't match the dimensions, or if the heightmap has
+    /// less than 2 samples along any axis.
+    pub fn new(width: usize, depth: usize, heights: Vec<f32>) -> Result<Self, Error> {
+        if width < 2 || depth < 2 || width * depth != heights.len() {
+            return Err(error::Error::InvalidHeightmap(width, depth, heights.len()).into());
+        }
+        Ok(Heightmap {
+            width,
+            depth,
+            heights,
+        })
+    }
+
+    /// Creates a heightmap from the luminance of an image, failing if the image is smaller than
+    /// 2x2 pixels.
+    pub fn from_image(image: &ImageData) -> Result<Self, Error> {
+        let (width, depth) = image.rgba.dimensions();
+        let heights = image
+            .rgba
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, _] = pixel.data;
+                (0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b)) / 255.0
+            })
+            .collect();
+        Heightmap::new(width as usize, depth as usize, heights)
+    }
+
+    /// Number of samples along the x axis.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Number of samples along the z axis.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the height at the given sample, clamping the coordinates to the heightmap.
+    pub fn get(&self, x: isize, z: isize) -> f32 {
+        let x = x.max(0).min(self.width as isize - 1) as usize;
+        let z = z.max(0).min(self.depth as isize - 1) as usize;
+        self.heights[z * self.width + x]
+    }
+}
+
+impl Asset for Heightmap {
+    const NAME: &'static str = "renderer::Heightmap";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<Heightmap> for Result<ProcessingState<Heightmap>, Error> {
+    fn from(heightmap: Heightmap) -> Result<ProcessingState<Heightmap>, Error> {
+        Ok(ProcessingState::Loaded(heightmap))
+    }
+}
+
+/// Loads a `Heightmap` from a grayscale image, in any format supported by the `image` crate.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HeightmapFormat;
+
+impl SimpleFormat<Heightmap> for HeightmapFormat {
+    const NAME: &'static str = "HEIGHTMAP";
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<Heightmap, Error> {
+        let image = image::load_from_memory(&bytes)?;
+        let (width, depth) = image.dimensions();
+        let heights = image
+            .to_luma()
+            .into_raw()
+            .into_iter()
+            .map(|luma| f32::from(luma) / 255.0)
+            .collect();
+        Heightmap::new(width as usize, depth as usize, heights)
+    }
+}
+
+/// A terrain generated from a `Heightmap`.
+///
+/// The terrain covers `size` units along the x and z axes starting at the origin of the
+/// entity, with heights from `0` to `max_height` along the y axis. It is split into square
+/// chunks of `chunk_cells` cells, which are culled and simplified independently by the
+/// `DrawTerrain` pass.
+///
+/// The terrain is textured by blending up to 4 `layers`, weighted by the red, green, blue and
+/// alpha channels of the `splat_map` stretched over the whole terrain. The layers are repeated
+/// `layer_tiling` times across the terrain, so they should use `WrapMode::Tile`.
+#[derive(Clone, Debug)]
+pub struct Terrain {
+    /// Heights of the terrain.
+    pub heightmap: HeightmapHandle,
+    /// Extent of the terrain along the x and z axes.
+    pub size: [f32; 2],
+    /// Height of the terrain where the heightmap is `1`.
+    pub max_height: f32,
+    /// Number of heightmap cells along each side of a chunk.
+    pub chunk_cells: usize,
+    /// Distances from the camera up to which each level of detail is used. Level `i` uses
+    /// every `2^i`th sample of the heightmap; chunks further away than the last distance use
+    /// the next level.
+    pub lod_distances: Vec<f32>,
+    /// Texture holding the blend weights of the layers.
+    pub splat_map: Option<TextureHandle>,
+    /// Textures blended over the terrain.
+    pub layers: Vec<TextureHandle>,
+    /// Number of times the layers are repeated across the terrain.
+    pub layer_tiling: f32,
+}
+
+impl Terrain {
+    /// Creates an untextured terrain of the given size from a heightmap.
+    pub fn new(heightmap: HeightmapHandle, size: [f32; 2], max_height: f32) -> Self {
+        Terrain {
+            heightmap,
+            size,
+            max_height,
+            chunk_cells: 32,
+            lod_distances: vec![50.0, 100.0, 200.0],
+            splat_map: None,
+            layers: Vec::new(),
+            layer_tiling: 1.0,
+        }
+    }
+
+    /// Sets the splat map and the layers it blends, see `Terrain`.
+    pub fn with_layers(
+        mut self,
+        splat_map: TextureHandle,
+        layers: Vec<TextureHandle>,
+        tiling: f32,
+    ) -> Self {
+        self.splat_map = Some(splat_map);
+        self.layers = layers;
+        self.layer_tiling = tiling;
+        self
+    }
+
+    /// Number of levels of detail generated for each chunk.
+    pub fn lod_levels(&self) -> usize {
+        self.lod_distances.len() + 1
+    }
+
+    /// Returns the level of detail to use for a chunk at `distance` from the camera.
+    pub fn lod(&self, distance: f32) -> usize {
+        self.lod_distances
+            .iter()
+            .position(|&max| distance <= max)
+            .unwrap_or_else(|| self.lod_distances.len())
+    }
+
+    /// Number of chunks along the x and z axes.
+    pub(crate) fn chunks(&self, heightmap: &Heightmap) -> (usize, usize) {
+        let chunk_cells = self.chunk_cells.max(1);
+        let cells = |samples: usize| (samples - 1 + chunk_cells - 1) / chunk_cells;
+        (cells(heightmap.width()), cells(heightmap.depth()))
+    }
+
+    /// Builds the vertices of a chunk at the given level of detail, as a triangle list in the
+    /// local space of the entity, along with its bounding box.
+    ///
+    /// Chunks have skirts hanging below their edges, hiding the cracks between neighbouring
+    /// chunks using different levels of detail.
+    pub(crate) fn chunk_vertices(
+        &self,
+        heightmap: &Heightmap,
+        chunk: (usize, usize),
+        lod: usize,
+    ) -> (Vec<PosNormTex>, (Point3<f32>, Point3<f32>)) {
+        let chunk_cells = self.chunk_cells.max(1);
+        let step = (1 << lod).min(chunk_cells);
+        let cell_size = Vector2::new(
+            self.size[0] / (heightmap.width() - 1) as f32,
+            self.size[1] / (heightmap.depth() - 1) as f32,
+        );
+
+        let start = (chunk.0 * chunk_cells, chunk.1 * chunk_cells);
+        let end = (
+            (start.0 + chunk_cells).min(heightmap.width() - 1),
+            (start.1 + chunk_cells).min(heightmap.depth() - 1),
+        );
+        // Sample positions along each axis, always including the chunk borders.
+        let samples = |start: usize, end: usize| {
+            let mut samples = (start..end).step_by(step).collect::<Vec<_>>();
+            samples.push(end);
+            samples
+        };
+        let xs = samples(start.0, end.0);
+        let zs = samples(start.1, end.1);
+
+        let vertex = |x: usize, z: usize, drop: f32| {
+            let (xi, zi) = (x as isize, z as isize);
+            let height = |x, z| heightmap.get(x, z) * self.max_height;
+            let normal = Vector3::new(
+                (height(xi - 1, zi) - height(xi + 1, zi)) / (2.0 * cell_size.x),
+                1.0,
+                (height(xi, zi - 1) - height(xi, zi + 1)) / (2.0 * cell_size.y),
+            )
+            .normalize();
+            PosNormTex {
+                position: Vector3::new(
+                    x as f32 * cell_size.x,
+                    height(xi, zi) - drop,
+                    z as f32 * cell_size.y,
+                ),
+                normal,
+                tex_coord: Vector2::new(
+                    x as f32 / (heightmap.width() - 1) as f32,
+                    z as f32 / (heightmap.depth() - 1) as f32,
+                ),
+            }
+        };
+
+        let mut vertices = Vec::with_capacity(xs.len() * zs.len() * 6);
+        let mut quad = |a: PosNormTex, b: PosNormTex, c: PosNormTex, d: PosNormTex| {
+            vertices.extend_from_slice(&[a, b, c, c, d, a]);
+        };
+        for z in zs.windows(2) {
+            for x in xs.windows(2) {
+                quad(
+                    vertex(x[0], z[0], 0.0),
+                    vertex(x[0], z[1], 0.0),
+                    vertex(x[1], z[1], 0.0),
+                    vertex(x[1], z[0], 0.0),
+                );
+            }
+        }
+
+        let skirt = self.max_height * 0.05 + cell_size.x.max(cell_size.y) * step as f32;
+        let borders = [
+            xs.iter().map(|&x| (x, start.1)).collect::<Vec<_>>(),
+            xs.iter().map(|&x| (x, end.1)).collect(),
+            zs.iter().map(|&z| (start.0, z)).collect(),
+            zs.iter().map(|&z| (end.0, z)).collect(),
+        ];
+        for border in &borders {
+            for edge in border.windows(2) {
+                let ((x0, z0), (x1, z1)) = (edge[0], edge[1]);
+                quad(
+                    vertex(x0, z0, 0.0),
+                    vertex(x1, z1, 0.0),
+                    vertex(x1, z1, skirt),
+                    vertex(x0, z0, skirt),
+                );
+            }
+        }
+
+        let mut min = Point3::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+        let mut max = Point3::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+        for v in &vertices {
+            let p = Point3::from(v.position);
+            min = min.inf(&p);
+            max = max.sup(&p);
+        }
+        (vertices, (min, max))
+    }
+}
+
+impl Component for Terrain {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The planes of a view frustum, extracted from a projection and view matrix.
+#[derive(Clone, Debug)]
+pub(crate) struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Extracts the frustum of the clip space of the given view projection matrix.
+    pub fn new(view_proj: &Matrix4<f32>) -> Self {
+        let row = |i| view_proj.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Frustum {
+            planes: [w + x, w - x, w + y, w - y, w + z, w - z],
+        }
+    }
+
+    /// Returns `false` if the axis aligned box is completely outside of the frustum.
+    pub fn intersects_aabb(&self, min: &Point3<f32>, max: &Point3<f32>) -> bool {
+        self.planes.iter().all(|plane| {
+            // Test the corner of the box furthest along the plane normal.
+            let corner = Vector4::new(
+                if plane.x >= 0.0 { max.x } else { min.x },
+                if plane.y >= 0.0 { max.y } else { min.y },
+                if plane.z >= 0.0 { max.z } else { min.z },
+                1.0,
+            );
+            plane.dot(&corner) >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::math::{Matrix4, Point3};
+
+    use super::{Frustum, Heightmap};
+
+    #[test]
+    fn rejects_invalid_heightmaps() {
+        assert!(Heightmap::new(2, 2, vec![0.0; 4]).is_ok());
+        assert!(Heightmap::new(1, 4, vec![0.0; 4]).is_err());
+        assert!(Heightmap::new(4, 0, Vec::new()).is_err());
+        assert!(Heightmap::new(2, 3, vec![0.0; 4]).is_err());
+    }
+
+    #[test]
+    fn frustum_culls_aabb() {
+        let frustum = Frustum::new(&Matrix4::identity());
+        assert!(
+            frustum.intersects_aabb(&Point3::new(-0.5, -0.5, -0.5), &Point3::new(0.5, 0.5, 0.5))
+        );
+        assert!(frustum.intersects_aabb(&Point3::new(0.5, 0.5, 0.5), &Point3::new(2.0, 2.0, 2.0)));
+        assert!(
+            !frustum.intersects_aabb(&Point3::new(1.5, -0.5, -0.5), &Point3::new(2.0, 0.5, 0.5))
+        );
+    }
+}
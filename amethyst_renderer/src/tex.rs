@@ -11,12 +11,14 @@ use std::marker::PhantomData;
 
 use amethyst_assets::{Asset, Handle};
 use amethyst_core::ecs::prelude::DenseVecStorage;
-use amethyst_error::Error;
+use amethyst_error::{format_err, Error};
 
 use crate::{
     error,
     formats::TextureData,
-    types::{ChannelFormat, Factory, RawShaderResourceView, RawTexture, Sampler, SurfaceFormat},
+    types::{
+        ChannelFormat, Encoder, Factory, RawShaderResourceView, RawTexture, Sampler, SurfaceFormat,
+    },
 };
 
 /// A handle to a `Texture` asset.
@@ -51,17 +53,79 @@ impl Texture {
         &self.view
     }
 
+    /// Returns the raw texture resource.
+    pub(crate) fn raw(&self) -> &RawTexture {
+        &self.texture
+    }
+
     /// Returns the texture's dimensions ``(width, height)``
     pub fn size(&self) -> (usize, usize) {
         let (w, h, _, _) = self.texture.get_info().kind.get_dimensions();
         (w as usize, h as usize)
     }
+
+    /// Returns an estimate of the memory used by the base level of the texture, in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        let info = self.texture.get_info();
+        let (w, h, d, _) = info.kind.get_dimensions();
+        let layers = info.kind.get_num_slices().unwrap_or(1);
+        let texels = w as usize * h as usize * d.max(1) as usize * layers as usize;
+        texels * (info.format.get_total_bits() / 8) as usize
+    }
+
+    /// Records the upload of `rows` rows of texels of the base level into `encoder`, starting at
+    /// row `y` from the top of the image.
+    ///
+    /// `data` holds the rows from top to bottom, like the data given to `TextureBuilder`.
+    pub(crate) fn update_rows(
+        &self,
+        encoder: &mut Encoder,
+        channel: ChannelType,
+        y: u16,
+        rows: u16,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        use gfx::{format::Format, texture::RawImageInfo};
+
+        let info = self.texture.get_info();
+        let (w, h, _, _) = info.kind.get_dimensions();
+        let mut yoffset = y;
+        let mut flipped;
+        let mut data = data;
+        if cfg!(feature = "opengl") {
+            // OpenGL textures are stored bottom up, see `TextureBuilder::build`
+            let row = data.len() / rows.max(1) as usize;
+            flipped = Vec::with_capacity(data.len());
+            for chunk in data.chunks(row.max(1)).rev() {
+                flipped.extend_from_slice(chunk);
+            }
+            data = &flipped;
+            yoffset = h - y - rows;
+        }
+        let image = RawImageInfo {
+            xoffset: 0,
+            yoffset,
+            zoffset: 0,
+            width: w,
+            height: rows,
+            depth: 1,
+            format: Format(info.format, channel),
+            mipmap: 0,
+        };
+        encoder
+            .update_texture_raw(&self.texture, None, image, data)
+            .map_err(|err| format_err!("Failed to update texture: {:?}", err))
+    }
 }
 
 impl Asset for Texture {
     const NAME: &'static str = "renderer::Texture";
     type Data = TextureData;
     type HandleStorage = DenseVecStorage<TextureHandle>;
+
+    fn memory_size(&self) -> usize {
+        self.size_in_bytes()
+    }
 }
 
 /// Builds new textures.
@@ -161,7 +225,7 @@ where
     pub fn build(self, fac: &mut Factory) -> Result<Texture, Error> {
         use std::mem::size_of;
 
-        use gfx::{format::Swizzle, memory::cast_slice, texture::ResourceDesc, Factory};
+        use gfx::{memory::cast_slice, Factory};
 
         // This variable has to live here to make sure the flipped
         // buffer lives long enough. (If one exists)
@@ -197,6 +261,19 @@ where
             Some(self.channel_type),
             Some((&[cast_slice(data)], Mipmap::Provided)),
         )?;
+        self.finish(fac, tex)
+    }
+
+    /// Builds the texture without its data, which is uploaded later with `Texture::update_rows`.
+    pub(crate) fn build_empty(self, fac: &mut Factory) -> Result<Texture, Error> {
+        use gfx::Factory;
+
+        let tex = fac.create_texture_raw(self.info, Some(self.channel_type), None)?;
+        self.finish(fac, tex)
+    }
+
+    fn finish(&self, fac: &mut Factory, tex: RawTexture) -> Result<Texture, Error> {
+        use gfx::{format::Swizzle, texture::ResourceDesc, Factory};
 
         let desc = ResourceDesc {
             channel: self.channel_type,
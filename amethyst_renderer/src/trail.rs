@@ -0,0 +1,161 @@
+//! Module for the Trail component and TrailSystem.
+
+use std::collections::VecDeque;
+
+use amethyst_core::{
+    ecs::{Component, DenseVecStorage, Join, Read, ReadStorage, System, WriteStorage},
+    math::{convert, Point3, Vector3},
+    Time, Transform,
+};
+
+use crate::color::Rgba;
+
+/// A position recorded by a `Trail`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrailPoint {
+    /// World position of the entity when the point was recorded.
+    pub position: Point3<f32>,
+    /// Time since the point was recorded, in seconds.
+    pub age: f32,
+}
+
+/// # Trail Component
+/// Records the recent positions of the entity it is attached to, drawn as a ribbon facing the
+/// camera by the `DrawTrails` pass.
+///
+/// Width and color are interpolated from their start value at the entity to their end value
+/// at the oldest point, based on the age of the points.
+#[derive(Clone, Debug)]
+pub struct Trail {
+    /// Time in seconds a point stays in the trail.
+    pub lifetime: f32,
+    /// Minimum distance the entity needs to travel before a new point is recorded.
+    pub min_distance: f32,
+    /// Maximum number of points recorded, the oldest points being dropped first.
+    pub max_points: usize,
+    /// Width of the ribbon at the entity.
+    pub start_width: f32,
+    /// Width of the ribbon at the end of the lifetime of a point.
+    pub end_width: f32,
+    /// Color of the ribbon at the entity.
+    pub start_color: Rgba,
+    /// Color of the ribbon at the end of the lifetime of a point.
+    pub end_color: Rgba,
+    /// Whether new points are recorded. The existing points keep aging when disabled.
+    pub emitting: bool,
+    /// Whether to use the scaled or unscaled time.
+    pub absolute_time: bool,
+    points: VecDeque<TrailPoint>,
+}
+
+impl Trail {
+    /// Creates a trail of constant `width` fading from `color` to transparent over `lifetime`
+    /// seconds.
+    pub fn new(lifetime: f32, width: f32, color: Rgba) -> Self {
+        Trail {
+            lifetime,
+            min_distance: width / 2.0,
+            max_points: 256,
+            start_width: width,
+            end_width: width,
+            start_color: color,
+            end_color: Rgba(color.0, color.1, color.2, 0.0),
+            emitting: true,
+            absolute_time: false,
+            points: VecDeque::new(),
+        }
+    }
+
+    /// Recorded points, from the oldest to the most recent.
+    pub fn points(&self) -> impl Iterator<Item = &TrailPoint> {
+        self.points.iter()
+    }
+
+    /// Removes all recorded points.
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Returns the width and color of the ribbon at a point of the given age.
+    pub fn style(&self, age: f32) -> (f32, Rgba) {
+        let t = if self.lifetime > 0.0 {
+            (age / self.lifetime).min(1.0).max(0.0)
+        } else {
+            1.0
+        };
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let (start, end) = (self.start_color, self.end_color);
+        (
+            lerp(self.start_width, self.end_width),
+            Rgba(
+                lerp(start.0, end.0),
+                lerp(start.1, end.1),
+                lerp(start.2, end.2),
+                lerp(start.3, end.3),
+            ),
+        )
+    }
+
+    fn update(&mut self, position: Point3<f32>, delta: f32) {
+        for point in &mut self.points {
+            point.age += delta;
+        }
+        while self
+            .points
+            .front()
+            .map_or(false, |point| point.age > self.lifetime)
+        {
+            self.points.pop_front();
+        }
+
+        if !self.emitting {
+            return;
+        }
+        let moved = self.points.back().map_or(true, |point| {
+            (point.position - position).norm() >= self.min_distance
+        });
+        if moved {
+            self.points.push_back(TrailPoint { position, age: 0.0 });
+            while self.points.len() > self.max_points {
+                self.points.pop_front();
+            }
+        }
+    }
+}
+
+impl Component for Trail {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// System recording the positions of entities with a `Trail` component.
+///
+/// Note that this should run after `GlobalTransform` has been updated for the current frame.
+#[derive(Default)]
+pub struct TrailSystem;
+
+impl TrailSystem {
+    /// Returns a new trail system
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a> System<'a> for TrailSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        ReadStorage<'a, Transform>,
+        WriteStorage<'a, Trail>,
+    );
+
+    fn run(&mut self, (time, transforms, mut trails): Self::SystemData) {
+        for (transform, trail) in (&transforms, &mut trails).join() {
+            let delta = if trail.absolute_time {
+                time.delta_real_seconds()
+            } else {
+                time.delta_seconds()
+            };
+            let position: Vector3<f32> = convert(transform.global_matrix().column(3).xyz());
+            trail.update(Point3::from(position), delta);
+        }
+    }
+}
@@ -3,6 +3,8 @@ pub use gfx_core::state::{Blend, BlendChannel, BlendValue, ColorMask, Equation,
 
 use amethyst_core::ecs::{prelude::Component, storage::NullStorage};
 
+use serde::{Deserialize, Serialize};
+
 /// Transparent mesh component
 #[derive(Clone, Debug, Default)]
 pub struct Transparent;
@@ -10,3 +12,64 @@ pub struct Transparent;
 impl Component for Transparent {
     type Storage = NullStorage<Self>;
 }
+
+/// Selects which entities a pass draws, based on the `Transparent` component.
+///
+/// By default a pass draws both opaque and transparent entities, so the transparent entities of
+/// one pass end up below the opaque entities drawn by the passes after it. To avoid this, add
+/// `Opaque` instances of the passes to one stage and `Transparent` instances of the passes to a
+/// later stage. Transparent entities are then blended back to front (as sorted by
+/// `VisibilitySortingSystem` or `SpriteVisibilitySortingSystem`) on top of the complete opaque
+/// scene. Transparent passes should usually only test depth, for example with
+/// `with_transparency_settings(ColorMask::all(), ALPHA, Some(DepthMode::LessEqualTest))`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TransparencyFilter {
+    /// Draw all entities.
+    All,
+    /// Only draw entities without the `Transparent` component.
+    Opaque,
+    /// Only draw entities with the `Transparent` component.
+    Transparent,
+}
+
+impl Default for TransparencyFilter {
+    fn default() -> Self {
+        TransparencyFilter::All
+    }
+}
+
+impl TransparencyFilter {
+    /// Returns `true` if an entity with the given transparency should be drawn.
+    pub fn accepts(self, transparent: bool) -> bool {
+        if transparent {
+            self.draws_transparent()
+        } else {
+            self.draws_opaque()
+        }
+    }
+
+    /// Returns `true` if entities without `Transparent` should be drawn.
+    pub fn draws_opaque(self) -> bool {
+        self != TransparencyFilter::Transparent
+    }
+
+    /// Returns `true` if entities with `Transparent` should be drawn.
+    pub fn draws_transparent(self) -> bool {
+        self != TransparencyFilter::Opaque
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransparencyFilter;
+
+    #[test]
+    fn filter_accepts() {
+        assert!(TransparencyFilter::All.accepts(true));
+        assert!(TransparencyFilter::All.accepts(false));
+        assert!(TransparencyFilter::Opaque.accepts(false));
+        assert!(!TransparencyFilter::Opaque.accepts(true));
+        assert!(TransparencyFilter::Transparent.accepts(true));
+        assert!(!TransparencyFilter::Transparent.accepts(false));
+    }
+}
@@ -0,0 +1,128 @@
+//! Util functions that change the window at runtime, and information about the monitors
+//! it can be displayed on.
+//!
+//! Custom cursor images and video mode queries are not supported by the windowing backend;
+//! the cursor can only be changed to one of the system cursors using `mouse::set_mouse_cursor`.
+
+use amethyst_error::Error;
+use log::error;
+use serde::{Deserialize, Serialize};
+use winit::{Icon, MonitorId, Window};
+
+use crate::{formats::ImageData, resources::WindowMessages};
+
+/// How the window is displayed on its monitor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FullscreenMode {
+    /// A regular decorated window.
+    Windowed,
+    /// An undecorated window covering the whole monitor.
+    Borderless,
+    /// Exclusive fullscreen on the monitor the window is on.
+    Exclusive,
+}
+
+/// Information about a monitor connected to the system.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// Human readable name of the monitor, if available.
+    pub name: Option<String>,
+    /// Resolution of the monitor in physical pixels.
+    pub dimensions: (u32, u32),
+    /// Position of the top left corner of the monitor on the desktop, in physical pixels.
+    pub position: (i32, i32),
+    /// The ratio between physical and logical pixels on this monitor.
+    pub hidpi_factor: f64,
+}
+
+impl MonitorInfo {
+    fn new(monitor: &MonitorId) -> Self {
+        let dimensions = monitor.get_dimensions();
+        let position = monitor.get_position();
+        MonitorInfo {
+            name: monitor.get_name(),
+            dimensions: (dimensions.width as u32, dimensions.height as u32),
+            position: (position.x as i32, position.y as i32),
+            hidpi_factor: monitor.get_hidpi_factor(),
+        }
+    }
+}
+
+/// World resource listing the available monitors.
+///
+/// This resource is refreshed by the `RenderSystem` whenever the window is moved or changed
+/// through `WindowMessages`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Monitors {
+    /// All monitors connected to the system.
+    pub monitors: Vec<MonitorInfo>,
+    /// Index of the monitor the window is currently on.
+    pub current: Option<usize>,
+}
+
+impl Monitors {
+    /// Collects the monitors available to the given window.
+    pub fn from_window(window: &Window) -> Self {
+        let monitors: Vec<_> = window
+            .get_available_monitors()
+            .map(|monitor| MonitorInfo::new(&monitor))
+            .collect();
+        let current = MonitorInfo::new(&window.get_current_monitor());
+        let current = monitors.iter().position(|monitor| *monitor == current);
+        Monitors { monitors, current }
+    }
+
+    /// Returns the monitor the window is currently on.
+    pub fn current(&self) -> Option<&MonitorInfo> {
+        self.current.and_then(|i| self.monitors.get(i))
+    }
+}
+
+/// Sets the window icon.
+pub fn set_icon(msg: &mut WindowMessages, icon: Icon) {
+    msg.send_command(move |win| win.set_window_icon(Some(icon.clone())));
+}
+
+/// Sets the window icon from image data, for example loaded as a texture asset.
+pub fn set_icon_from_image(msg: &mut WindowMessages, image: &ImageData) -> Result<(), Error> {
+    let (width, height) = image.rgba.dimensions();
+    let icon = Icon::from_rgba(image.rgba.clone().into_raw(), width, height)?;
+    set_icon(msg, icon);
+    Ok(())
+}
+
+/// Switches the window between windowed, borderless and exclusive fullscreen mode on the
+/// monitor it is currently on.
+pub fn set_fullscreen(msg: &mut WindowMessages, mode: FullscreenMode) {
+    msg.send_command(move |win| match mode {
+        FullscreenMode::Windowed => {
+            win.set_fullscreen(None);
+            win.set_decorations(true);
+            win.set_maximized(false);
+        }
+        FullscreenMode::Borderless => {
+            let monitor = win.get_current_monitor();
+            let hidpi = monitor.get_hidpi_factor();
+            let position = monitor.get_position();
+            win.set_fullscreen(None);
+            win.set_decorations(false);
+            win.set_position(position.to_logical(hidpi));
+            win.set_inner_size(monitor.get_dimensions().to_logical(hidpi));
+        }
+        FullscreenMode::Exclusive => {
+            let monitor = win.get_current_monitor();
+            win.set_fullscreen(Some(monitor));
+        }
+    });
+}
+
+/// Moves the window to the monitor with the given index in `Monitors`.
+pub fn move_to_monitor(msg: &mut WindowMessages, index: usize) {
+    msg.send_command(move |win| match win.get_available_monitors().nth(index) {
+        Some(monitor) => {
+            let position = monitor.get_position();
+            win.set_position(position.to_logical(monitor.get_hidpi_factor()));
+        }
+        None => error!("Unable to move the window: no monitor with index {}", index),
+    });
+}
@@ -0,0 +1,62 @@
+//! ECS scripting bundle
+
+use std::{borrow::Borrow, hash::Hash};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use amethyst_assets::Processor;
+use amethyst_core::{
+    bundle::SystemBundle,
+    ecs::prelude::{Component, DispatcherBuilder},
+};
+use amethyst_error::Error;
+
+use crate::{script::Script, system::ScriptSystem};
+
+/// Bundle adding the `Processor<Script>` with name "script_processor", and the `ScriptSystem`
+/// as a thread local system.
+///
+/// The input handler read by the scripts is an `InputHandler<AX, AC>`.
+///
+/// ## Errors
+///
+/// No errors will be returned by this bundle.
+pub struct ScriptingBundle<AX = String, AC = String> {
+    system: ScriptSystem<AX, AC>,
+}
+
+impl<AX, AC> Default for ScriptingBundle<AX, AC> {
+    fn default() -> Self {
+        ScriptingBundle::new()
+    }
+}
+
+impl<AX, AC> ScriptingBundle<AX, AC> {
+    /// Creates a new scripting bundle, without any component available to the scripts.
+    pub fn new() -> Self {
+        ScriptingBundle {
+            system: ScriptSystem::new(),
+        }
+    }
+
+    /// Makes the component `C` available to the scripts, under `name`.
+    pub fn with_component<C>(mut self, name: &str) -> Self
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned,
+    {
+        self.system = self.system.with_component::<C>(name);
+        self
+    }
+}
+
+impl<'a, 'b, AX, AC> SystemBundle<'a, 'b> for ScriptingBundle<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+{
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        builder.add(Processor::<Script>::new(), "script_processor", &[]);
+        builder.add_thread_local(self.system);
+        Ok(())
+    }
+}
@@ -0,0 +1,21 @@
+//! Lua scripting for Amethyst, backed by rlua.
+//!
+//! `Script`s are assets loaded with the `ScriptFormat`, attached to entities with their
+//! `ScriptHandle`. The `ScriptSystem`, added by the `ScriptingBundle`, runs the `update` function
+//! of each script for every entity it is attached to, and gives scripts access to the world,
+//! the registered components, the input and the `EventChannel<ScriptEvent>`.
+
+#![warn(missing_docs, rust_2018_idioms, rust_2018_compatibility)]
+
+pub use rlua;
+
+pub use crate::{
+    bundle::ScriptingBundle,
+    script::{Script, ScriptEvent, ScriptFormat, ScriptHandle},
+    system::{LuaEntity, ScriptSystem},
+};
+
+mod bundle;
+mod script;
+mod system;
+mod value;
@@ -0,0 +1,63 @@
+//! The script asset.
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, Handle, ProcessingState, SimpleFormat};
+use amethyst_core::ecs::prelude::DenseVecStorage;
+use amethyst_error::Error;
+
+/// A Lua script, run by the `ScriptSystem` for the entities holding its handle.
+///
+/// The script is run once when it is loaded, and again whenever it is hot reloaded. It can then
+/// define the functions called by the `ScriptSystem`:
+///
+/// * `update(entity, dt)`, every frame for every entity holding the script.
+/// * `on_event(entity, name, data)`, for every `ScriptEvent` and every entity holding the script.
+///
+/// Each script has its own global variables, which are kept when it is reloaded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Script {
+    /// The Lua source of the script.
+    pub source: String,
+}
+
+impl Asset for Script {
+    const NAME: &'static str = "scripting::Script";
+    type Data = Self;
+    type HandleStorage = DenseVecStorage<ScriptHandle>;
+}
+
+impl From<Script> for Result<ProcessingState<Script>, Error> {
+    fn from(script: Script) -> Result<ProcessingState<Script>, Error> {
+        Ok(ProcessingState::Loaded(script))
+    }
+}
+
+/// A handle to a script, running it for the entity when used as a component.
+pub type ScriptHandle = Handle<Script>;
+
+/// Loads Lua scripts.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptFormat;
+
+impl SimpleFormat<Script> for ScriptFormat {
+    const NAME: &'static str = "LUA";
+
+    type Options = ();
+
+    fn import(&self, bytes: Vec<u8>, _: ()) -> Result<Script, Error> {
+        Ok(Script {
+            source: String::from_utf8(bytes)?,
+        })
+    }
+}
+
+/// An event sent by a script with `events.emit(name, data)`, or sent to the scripts by writing
+/// it to the `EventChannel<ScriptEvent>`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScriptEvent {
+    /// The name of the event.
+    pub name: String,
+    /// The data of the event, a table in Lua.
+    pub data: serde_json::Value,
+}
@@ -0,0 +1,492 @@
+//! The system running the scripts.
+
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, marker::PhantomData};
+
+use log::error;
+use rlua::{
+    Context, Error as LuaError, Function, Lua, RegistryKey, Scope, StdLib, Table, UserData,
+    Value as LuaValue,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+use amethyst_assets::{AssetStorage, WeakHandle};
+use amethyst_core::{
+    ecs::{
+        prelude::{Component, Entity, Join, ReadStorage, Resources, RunNow, SystemData},
+        world::EntitiesRes,
+    },
+    shrev::{EventChannel, ReaderId},
+    ComponentRegistration, ComponentRegistry, Time,
+};
+use amethyst_error::Error;
+use amethyst_input::InputHandler;
+
+use crate::{
+    script::{Script, ScriptEvent, ScriptHandle},
+    value::{from_lua, to_lua},
+};
+
+/// An entity in Lua, passed to the functions of the scripts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LuaEntity(pub Entity);
+
+impl UserData for LuaEntity {}
+
+/// A script run by the system, with its own global variables.
+struct LoadedScript {
+    // Handle ids are reused once the handles are dropped, the globals are only kept when the
+    // script of the same handle is reloaded.
+    handle: WeakHandle<Script>,
+    source: String,
+    env: Option<RegistryKey>,
+}
+
+/// Runs the `Script`s of the entities holding a `ScriptHandle`, see `Script`.
+///
+/// The scripts can use these global tables, with the names of the components registered with
+/// `with_component` or in the `ComponentRegistry` resource, and of the axes and actions of the
+/// `InputHandler<AX, AC>`:
+///
+/// * `world.create()`, `world.delete(entity)` and `world.is_alive(entity)`.
+/// * `world.get(entity, component)`, returning the component as a table or `nil`,
+///   `world.set(entity, component, table)`, `world.add(entity, component)` inserting its
+///   default value, and `world.remove(entity, component)`.
+/// * `input.action_is_down(action)`, `input.axis_value(axis)` and `input.mouse_position()`.
+/// * `events.emit(name, data)`, writing a `ScriptEvent`.
+///
+/// Components are converted to tables with their `Serialize` and `Deserialize` implementations.
+/// The components registered with `with_component` hide the ones of the `ComponentRegistry`.
+/// Errors of a script are logged, and don't stop the other scripts.
+///
+/// Only the base, table, string and math libraries of Lua are available, without `dofile` and
+/// `loadfile`, so scripts can't access the file system or run processes.
+///
+/// Lua isn't thread safe, so the system is thread local.
+pub struct ScriptSystem<AX = String, AC = String> {
+    lua: Lua,
+    components: ComponentRegistry,
+    scripts: HashMap<u32, LoadedScript>,
+    event_reader: Option<ReaderId<ScriptEvent>>,
+    _marker: PhantomData<(AX, AC)>,
+}
+
+impl<AX, AC> Default for ScriptSystem<AX, AC> {
+    fn default() -> Self {
+        ScriptSystem {
+            lua: sandbox(),
+            components: ComponentRegistry::new(),
+            scripts: HashMap::new(),
+            event_reader: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<AX, AC> ScriptSystem<AX, AC> {
+    /// Creates a new script system, without any component available to the scripts.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Makes the component `C` available to the scripts, under `name`.
+    pub fn with_component<C>(mut self, name: &str) -> Self
+    where
+        C: Component + Send + Sync + Serialize + DeserializeOwned,
+    {
+        self.components.register::<C>(name);
+        self
+    }
+}
+
+impl<'a, AX, AC> RunNow<'a> for ScriptSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+{
+    fn run_now(&mut self, res: &'a Resources) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("script_system");
+
+        let delta_seconds = res.fetch::<Time>().delta_seconds();
+        let events = res
+            .fetch::<EventChannel<ScriptEvent>>()
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`ScriptSystem::setup` was not called before `ScriptSystem::run_now`"),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+        let instances = {
+            let entities = res.fetch::<EntitiesRes>();
+            let handles = ReadStorage::<ScriptHandle>::fetch(res);
+            (&*entities, &handles)
+                .join()
+                .map(|(entity, handle)| (entity, handle.clone()))
+                .collect::<Vec<_>>()
+        };
+        let sources = {
+            let storage = res.fetch::<AssetStorage<Script>>();
+            let mut sources = HashMap::new();
+            for &(_, ref handle) in &instances {
+                let loaded = self
+                    .scripts
+                    .get(&handle.id())
+                    .filter(|loaded| !loaded.handle.is_dead());
+                if let Some(script) = storage.get(handle) {
+                    if loaded.map_or(true, |loaded| loaded.source != script.source) {
+                        sources.insert(handle.id(), (handle.downgrade(), script.source.clone()));
+                    }
+                }
+            }
+            sources
+        };
+
+        let input = res.try_fetch::<InputHandler<AX, AC>>();
+        let input = input.as_ref().map(|input| &**input);
+        let shared = res.try_fetch::<ComponentRegistry>();
+        let shared = shared.as_ref().map(|shared| &**shared);
+        let components = &self.components;
+        let scripts = &mut self.scripts;
+
+        self.lua.context(|ctx| {
+            // Forget the scripts which aren't used anymore.
+            scripts.retain(|id, _| instances.iter().any(|&(_, ref handle)| handle.id() == *id));
+            ctx.expire_registry_values();
+
+            let result: Result<(), LuaError> = ctx.scope(|scope| {
+                install_api(ctx, scope, res, components, shared, input)?;
+
+                for (id, (handle, source)) in sources {
+                    let previous = scripts
+                        .remove(&id)
+                        .filter(|loaded| !loaded.handle.is_dead())
+                        .and_then(|loaded| loaded.env);
+                    let env = match load(ctx, id, &source, previous.as_ref()) {
+                        Ok(env) => Some(ctx.create_registry_value(env)?),
+                        Err(err) => {
+                            error!("Failed to load script {}: {}", id, err);
+                            previous
+                        }
+                    };
+                    scripts.insert(
+                        id,
+                        LoadedScript {
+                            handle,
+                            source,
+                            env,
+                        },
+                    );
+                }
+
+                for &(entity, ref handle) in &instances {
+                    let env = match scripts.get(&handle.id()) {
+                        Some(LoadedScript {
+                            env: Some(ref env), ..
+                        }) => ctx.registry_value::<Table<'_>>(env)?,
+                        _ => continue,
+                    };
+                    if let Err(err) = run(ctx, &env, entity, delta_seconds, &events) {
+                        error!("Error in script {}: {}", handle.id(), err);
+                    }
+                }
+                Ok(())
+            });
+            if let Err(err) = result {
+                error!("Failed to run the scripts: {}", err);
+            }
+        });
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        ReadStorage::<ScriptHandle>::setup(res);
+        res.entry::<AssetStorage<Script>>()
+            .or_insert_with(AssetStorage::default);
+        res.entry::<Time>().or_insert_with(Time::default);
+        self.event_reader = Some(
+            res.entry::<EventChannel<ScriptEvent>>()
+                .or_insert_with(EventChannel::default)
+                .register_reader(),
+        );
+        self.components.setup(res);
+    }
+}
+
+/// Creates a Lua state with the libraries available to the scripts.
+fn sandbox() -> Lua {
+    let lua = Lua::new_with(StdLib::BASE | StdLib::TABLE | StdLib::STRING | StdLib::MATH);
+    lua.context(|ctx| {
+        let globals = ctx.globals();
+        for name in &["dofile", "loadfile"] {
+            if let Err(err) = globals.set(*name, LuaValue::Nil) {
+                error!("Failed to remove {} from the scripts: {}", name, err);
+            }
+        }
+    });
+    lua
+}
+
+/// Runs the source of a script in a new environment, keeping the globals of `previous`.
+fn load<'lua>(
+    ctx: Context<'lua>,
+    id: u32,
+    source: &str,
+    previous: Option<&RegistryKey>,
+) -> Result<Table<'lua>, LuaError> {
+    let env = ctx.create_table()?;
+    if let Some(previous) = previous {
+        for pair in ctx
+            .registry_value::<Table<'_>>(previous)?
+            .pairs::<LuaValue<'_>, LuaValue<'_>>()
+        {
+            let (key, value) = pair?;
+            env.set(key, value)?;
+        }
+    }
+    let meta = ctx.create_table()?;
+    meta.set("__index", ctx.globals())?;
+    env.set_metatable(Some(meta));
+
+    ctx.load(source)
+        .set_name(&format!("script {}", id))?
+        .set_environment(env.clone())?
+        .exec()?;
+    Ok(env)
+}
+
+/// Calls the functions of a script for `entity`.
+fn run<'lua>(
+    ctx: Context<'lua>,
+    env: &Table<'lua>,
+    entity: Entity,
+    delta_seconds: f32,
+    events: &[ScriptEvent],
+) -> Result<(), LuaError> {
+    if !events.is_empty() {
+        if let Some(on_event) = env.get::<_, Option<Function<'_>>>("on_event")? {
+            for event in events {
+                on_event.call::<_, ()>((
+                    LuaEntity(entity),
+                    event.name.as_str(),
+                    to_lua(ctx, &event.data)?,
+                ))?;
+            }
+        }
+    }
+    if let Some(update) = env.get::<_, Option<Function<'_>>>("update")? {
+        update.call::<_, ()>((LuaEntity(entity), delta_seconds))?;
+    }
+    Ok(())
+}
+
+/// Sets the `world`, `input` and `events` tables for the duration of `scope`.
+fn install_api<'lua, 'scope, AX, AC>(
+    ctx: Context<'lua>,
+    scope: &Scope<'lua, 'scope>,
+    res: &'scope Resources,
+    components: &'scope ComponentRegistry,
+    shared: Option<&'scope ComponentRegistry>,
+    input: Option<&'scope InputHandler<AX, AC>>,
+) -> Result<(), LuaError>
+where
+    AX: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + Borrow<str> + 'static,
+{
+    let component = move |name: &str| -> Result<&'scope ComponentRegistration, LuaError> {
+        components
+            .get(name)
+            .or_else(|| shared.and_then(|shared| shared.get(name)))
+            .ok_or_else(|| LuaError::RuntimeError(format!("Unknown component {:?}", name)))
+    };
+    let runtime_error = |err: Error| LuaError::RuntimeError(err.to_string());
+
+    let world = ctx.create_table()?;
+    world.set(
+        "create",
+        scope.create_function(move |_, ()| Ok(LuaEntity(res.fetch::<EntitiesRes>().create())))?,
+    )?;
+    world.set(
+        "delete",
+        scope.create_function(move |_, entity: LuaEntity| {
+            res.fetch::<EntitiesRes>()
+                .delete(entity.0)
+                .map_err(|err| LuaError::RuntimeError(err.to_string()))
+        })?,
+    )?;
+    world.set(
+        "is_alive",
+        scope.create_function(move |_, entity: LuaEntity| {
+            Ok(res.fetch::<EntitiesRes>().is_alive(entity.0))
+        })?,
+    )?;
+    world.set(
+        "get",
+        scope.create_function(
+            move |ctx, (entity, name): (LuaEntity, String)| match component(&name)?
+                .serialize(res, entity.0)
+                .map_err(runtime_error)?
+            {
+                Some(value) => to_lua(ctx, &value),
+                None => Ok(LuaValue::Nil),
+            },
+        )?,
+    )?;
+    world.set(
+        "set",
+        scope.create_function(
+            move |_, (entity, name, value): (LuaEntity, String, LuaValue<'_>)| {
+                component(&name)?
+                    .deserialize(res, entity.0, from_lua(value)?)
+                    .map_err(runtime_error)
+            },
+        )?,
+    )?;
+    world.set(
+        "add",
+        scope.create_function(move |_, (entity, name): (LuaEntity, String)| {
+            component(&name)?
+                .insert_default(res, entity.0)
+                .map_err(runtime_error)
+        })?,
+    )?;
+    world.set(
+        "remove",
+        scope.create_function(move |_, (entity, name): (LuaEntity, String)| {
+            component(&name)?.remove(res, entity.0);
+            Ok(())
+        })?,
+    )?;
+    ctx.globals().set("world", world)?;
+
+    let input_table = ctx.create_table()?;
+    input_table.set(
+        "action_is_down",
+        scope.create_function(move |_, action: String| {
+            Ok(input.and_then(|input| input.action_is_down(action.as_str())))
+        })?,
+    )?;
+    input_table.set(
+        "axis_value",
+        scope.create_function(move |_, axis: String| {
+            Ok(input.and_then(|input| input.axis_value(axis.as_str())))
+        })?,
+    )?;
+    input_table.set(
+        "mouse_position",
+        scope.create_function(move |_, ()| {
+            Ok(match input.and_then(InputHandler::mouse_position) {
+                Some((x, y)) => (Some(x), Some(y)),
+                None => (None, None),
+            })
+        })?,
+    )?;
+    ctx.globals().set("input", input_table)?;
+
+    let events = ctx.create_table()?;
+    events.set(
+        "emit",
+        scope.create_function(move |_, (name, data): (String, LuaValue<'_>)| {
+            let data = from_lua(data)?;
+            res.fetch_mut::<EventChannel<ScriptEvent>>()
+                .single_write(ScriptEvent { name, data });
+            Ok(())
+        })?,
+    )?;
+    ctx.globals().set("events", events)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rayon::ThreadPoolBuilder;
+    use serde::{Deserialize, Serialize};
+
+    use amethyst_assets::{AssetStorage, Loader};
+    use amethyst_core::{
+        ecs::prelude::{Builder, Component, DenseVecStorage, RunNow, World},
+        shrev::EventChannel,
+    };
+
+    use super::ScriptSystem;
+    use crate::script::{Script, ScriptEvent};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Counter {
+        value: u32,
+    }
+
+    impl Component for Counter {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    #[test]
+    fn scripts_update_components_and_emit_events() {
+        let mut world = World::new();
+        let mut system = ScriptSystem::<String, String>::new().with_component::<Counter>("Counter");
+        RunNow::setup(&mut system, &mut world.res);
+        let mut reader = world
+            .write_resource::<EventChannel<ScriptEvent>>()
+            .register_reader();
+
+        let pool = Arc::new(ThreadPoolBuilder::default().build().unwrap());
+        let loader = Loader::new(".", pool.clone());
+        let source = r#"
+            function update(entity, dt)
+                local counter = world.get(entity, "Counter") or { value = 0 }
+                counter.value = counter.value + 1
+                world.set(entity, "Counter", counter)
+                if counter.value == 2 then
+                    events.emit("counted", { value = counter.value })
+                end
+            end
+        "#;
+        let handle = {
+            let mut storage = world.write_resource::<AssetStorage<Script>>();
+            let handle = loader.load_from_data(
+                Script {
+                    source: source.to_owned(),
+                },
+                (),
+                &storage,
+            );
+            storage.process(Into::into, 0, &pool, None);
+            handle
+        };
+        let entity = world.create_entity().with(handle).build();
+
+        system.run_now(&world.res);
+        system.run_now(&world.res);
+
+        assert_eq!(
+            world.read_storage::<Counter>().get(entity),
+            Some(&Counter { value: 2 })
+        );
+        let events = world
+            .read_resource::<EventChannel<ScriptEvent>>()
+            .read(&mut reader)
+            .map(|event| event.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec!["counted".to_owned()]);
+    }
+
+    #[test]
+    fn scripts_only_use_safe_libraries() {
+        super::sandbox().context(|ctx| {
+            let available = |name: &str| {
+                ctx.load(&format!("return {} ~= nil", name))
+                    .eval::<bool>()
+                    .unwrap()
+            };
+            assert!(available("string.format"));
+            assert!(available("math.floor"));
+            assert!(!available("io"));
+            assert!(!available("os"));
+            assert!(!available("dofile"));
+        });
+    }
+}
@@ -0,0 +1,103 @@
+//! Conversion of the values exchanged with the scripts.
+
+use rlua::{Context, Error as LuaError, Value as LuaValue};
+use serde_json::{Map, Number, Value};
+
+/// Converts a value to Lua, objects and arrays becoming tables.
+pub(crate) fn to_lua<'lua>(ctx: Context<'lua>, value: &Value) -> Result<LuaValue<'lua>, LuaError> {
+    Ok(match *value {
+        Value::Null => LuaValue::Nil,
+        Value::Bool(b) => LuaValue::Boolean(b),
+        Value::Number(ref n) => match n.as_i64() {
+            Some(i) => LuaValue::Integer(i),
+            None => LuaValue::Number(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(ref s) => LuaValue::String(ctx.create_string(s)?),
+        Value::Array(ref values) => {
+            let table = ctx.create_table()?;
+            for (i, value) in values.iter().enumerate() {
+                table.set(i + 1, to_lua(ctx, value)?)?;
+            }
+            LuaValue::Table(table)
+        }
+        Value::Object(ref values) => {
+            let table = ctx.create_table()?;
+            for (key, value) in values {
+                table.set(key.as_str(), to_lua(ctx, value)?)?;
+            }
+            LuaValue::Table(table)
+        }
+    })
+}
+
+/// Converts a value from Lua.
+///
+/// Tables with a sequence become arrays, other tables become objects with string keys.
+pub(crate) fn from_lua(value: LuaValue<'_>) -> Result<Value, LuaError> {
+    Ok(match value {
+        LuaValue::Nil => Value::Null,
+        LuaValue::Boolean(b) => Value::Bool(b),
+        LuaValue::Integer(i) => Value::from(i),
+        LuaValue::Number(n) => {
+            Value::Number(Number::from_f64(n).ok_or_else(|| {
+                LuaError::RuntimeError(format!("Can't convert {} to a number", n))
+            })?)
+        }
+        LuaValue::String(s) => Value::String(s.to_str()?.to_owned()),
+        LuaValue::Table(table) => {
+            if table.raw_len() > 0 {
+                let values = table
+                    .sequence_values::<LuaValue<'_>>()
+                    .map(|value| value.and_then(from_lua))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Value::Array(values)
+            } else {
+                let values = table
+                    .pairs::<String, LuaValue<'_>>()
+                    .map(|pair| pair.and_then(|(key, value)| Ok((key, from_lua(value)?))))
+                    .collect::<Result<Map<_, _>, _>>()?;
+                Value::Object(values)
+            }
+        }
+        other => {
+            return Err(LuaError::RuntimeError(format!(
+                "Can't convert a Lua {} to a value",
+                type_name(&other)
+            )));
+        }
+    })
+}
+
+fn type_name(value: &LuaValue<'_>) -> &'static str {
+    match *value {
+        LuaValue::LightUserData(_) => "light userdata",
+        LuaValue::Function(_) => "function",
+        LuaValue::Thread(_) => "thread",
+        LuaValue::UserData(_) => "userdata",
+        LuaValue::Error(_) => "error",
+        _ => "value",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rlua::Lua;
+    use serde_json::json;
+
+    use super::{from_lua, to_lua};
+
+    #[test]
+    fn converts_values_back_and_forth() {
+        let value = json!({
+            "name": "crate",
+            "solid": true,
+            "health": 3,
+            "scale": 1.5,
+            "tags": ["wood", "loot"],
+        });
+        Lua::new().context(|ctx| {
+            let lua = to_lua(ctx, &value).unwrap();
+            assert_eq!(from_lua(lua).unwrap(), value);
+        });
+    }
+}
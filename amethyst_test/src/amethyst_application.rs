@@ -709,6 +709,7 @@ where
             loaded_icon: None,
             icon: None,
             vsync: true,
+            present_mode: None,
             multisampling: 0, // Must be multiple of 2, use 0 to disable
             visibility,
             always_on_top: false,
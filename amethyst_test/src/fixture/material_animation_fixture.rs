@@ -44,6 +44,7 @@ impl MaterialAnimationFixture {
                     (0, MaterialChannel::AlbedoTexture, texture_animation_handle),
                     (0, MaterialChannel::AlbedoOffset, sampler_animation_handle),
                 ],
+                events: vec![],
             };
 
             loader.load_from_data::<Animation<Material>, ()>(animation, (), &world.read_resource())
@@ -62,6 +62,7 @@ impl SpriteRenderAnimationFixture {
                         sprite_index_animation_handle,
                     ),
                 ],
+                events: vec![],
             };
 
             loader.load_from_data::<Animation<SpriteRender>, ()>(
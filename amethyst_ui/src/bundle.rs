@@ -10,11 +10,15 @@ use amethyst_core::{bundle::SystemBundle, ecs::prelude::DispatcherBuilder};
 use amethyst_error::Error;
 use amethyst_renderer::{BlinkSystem, TextureFormat};
 
+#[cfg(feature = "locale")]
+use crate::UiLocalizationSystem;
 use crate::{
-    CacheSelectionOrderSystem, FontAsset, FontFormat, NoCustomUi, ResizeSystem,
+    CacheSelectionOrderSystem, FontAsset, FontFormat, NoCustomUi, ResizeSystem, ScrollViewSystem,
     SelectionKeyboardSystem, SelectionMouseSystem, TextEditingInputSystem, TextEditingMouseSystem,
-    ToNativeWidget, UiButtonActionRetriggerSystem, UiButtonSystem, UiLoaderSystem, UiMouseSystem,
-    UiSoundRetriggerSystem, UiSoundSystem, UiTransformSystem, WidgetId,
+    ToNativeWidget, UiButtonActionRetriggerSystem, UiButtonSystem, UiDragSystem, UiDropdownSystem,
+    UiLoaderSystem, UiMouseSystem, UiNavigationSystem, UiSliderSystem, UiSoundRetriggerSystem,
+    UiSoundSystem, UiTheme, UiThemeSystem, UiToggleSystem, UiTransformSystem, UiTweenSystem,
+    UiWorldAttachmentSystem, WidgetId,
 };
 
 /// UI bundle
@@ -25,10 +29,25 @@ use crate::{
 /// Will fail with error 'No resource with the given id' if the InputBundle is not added.
 #[derive(new)]
 pub struct UiBundle<A = String, B = String, C = NoCustomUi, W = u32, G = ()> {
+    #[new(default)]
+    #[cfg_attr(not(feature = "locale"), allow(dead_code))]
+    localization: bool,
     #[new(default)]
     _marker: PhantomData<(A, B, C, W, G)>,
 }
 
+impl<A, B, C, W, G> UiBundle<A, B, C, W, G> {
+    /// Add the `UiLocalizationSystem`, showing the `UiTextLocalized` texts in the active locale.
+    ///
+    /// The `Processor<Locale>` isn't added by the bundle, the application adds it along with the
+    /// other processors of its assets.
+    #[cfg(feature = "locale")]
+    pub fn with_localization(mut self) -> Self {
+        self.localization = true;
+        self
+    }
+}
+
 impl<'a, 'b, A, B, C, W, G> SystemBundle<'a, 'b> for UiBundle<A, B, C, W, G>
 where
     A: Send + Sync + Eq + Hash + Clone + 'static,
@@ -49,16 +68,44 @@ where
             "ui_loader",
             &[],
         );
+        builder.add(
+            ScrollViewSystem::<A, B>::new(),
+            "ui_scroll_view_system",
+            &[],
+        );
+        builder.add(UiTweenSystem::new(), "ui_tween_system", &[]);
+        builder.add(
+            UiWorldAttachmentSystem::new(),
+            "ui_world_attachment_system",
+            &["transform_system"],
+        );
         builder.add(
             UiTransformSystem::default(),
             "ui_transform",
-            &["transform_system"],
+            &[
+                "transform_system",
+                "ui_scroll_view_system",
+                "ui_tween_system",
+                "ui_world_attachment_system",
+            ],
         );
         builder.add(
             Processor::<FontAsset>::new(),
             "font_processor",
             &["ui_loader"],
         );
+        builder.add(Processor::<UiTheme>::new(), "ui_theme_processor", &[]);
+        builder.add(
+            UiThemeSystem::new(),
+            "ui_theme_system",
+            &["ui_loader", "ui_theme_processor"],
+        );
+        #[cfg(feature = "locale")]
+        {
+            if self.localization {
+                builder.add(UiLocalizationSystem::new(), "ui_localization_system", &[]);
+            }
+        }
         builder.add(
             CacheSelectionOrderSystem::<G>::new(),
             "selection_order_cache",
@@ -75,13 +122,18 @@ where
             // Because when you press tab, you want to override the previously selected elements.
             &["ui_mouse_selection"],
         );
+        builder.add(
+            UiNavigationSystem::<G, A, B>::new(),
+            "ui_navigation_system",
+            &["ui_mouse_selection", "ui_keyboard_selection"],
+        );
         builder.add(
             TextEditingMouseSystem::new(),
             "ui_text_editing_mouse_system",
             &["ui_mouse_selection", "ui_keyboard_selection"],
         );
         builder.add(
-            TextEditingInputSystem::new(),
+            TextEditingInputSystem::<A, B>::new(),
             "ui_text_editing_input_system",
             // Hard requirement. The system assumes the text to edit is selected.
             &["ui_mouse_selection", "ui_keyboard_selection"],
@@ -103,6 +155,26 @@ where
             "ui_button_action_retrigger_system",
             &["ui_button_system"],
         );
+        builder.add(
+            UiSliderSystem::<A, B>::new(),
+            "ui_slider_system",
+            &["ui_mouse_system"],
+        );
+        builder.add(
+            UiToggleSystem::new(),
+            "ui_toggle_system",
+            &["ui_mouse_system"],
+        );
+        builder.add(
+            UiDropdownSystem::<A, B>::new(),
+            "ui_dropdown_system",
+            &["ui_mouse_system"],
+        );
+        builder.add(
+            UiDragSystem::<A, B>::new(),
+            "ui_drag_system",
+            &["ui_mouse_system"],
+        );
         builder.add(UiSoundSystem::new(), "ui_sound_system", &[]);
         builder.add(
             UiSoundRetriggerSystem::new(),
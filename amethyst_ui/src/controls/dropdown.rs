@@ -0,0 +1,247 @@
+use std::{hash::Hash, marker::PhantomData};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReaderId, Resources,
+        System, SystemData, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+    Parent,
+};
+use amethyst_input::InputHandler;
+use amethyst_renderer::{MouseButton, ScreenDimensions, TextureHandle};
+
+use crate::{
+    Anchor, Interactable, UiEvent, UiEventType, UiText, UiTransform, UiValue, UiValueChange,
+};
+
+/// Z of the option list relative to the dropdown, so it covers the elements below it.
+const OPTIONS_Z: f32 = 100.0;
+
+/// A button showing the selected option, which opens the list of all the options when clicked.
+///
+/// The entity must be `Interactable` and have a `UiText`, updated by the `UiDropdownSystem` to
+/// show the selected option. The options are listed below the dropdown with the same size, text
+/// style and image, and the list closes when clicking anywhere else.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiDropdown {
+    /// The options to choose from.
+    pub options: Vec<String>,
+    /// Index of the selected option.
+    pub selected: usize,
+    #[serde(skip)]
+    option_entities: Vec<Entity>,
+}
+
+impl UiDropdown {
+    /// Creates a closed dropdown with the option at index `selected` chosen.
+    pub fn new(options: Vec<String>, selected: usize) -> Self {
+        UiDropdown {
+            options,
+            selected,
+            option_entities: Vec::new(),
+        }
+    }
+
+    /// Returns the text of the selected option.
+    pub fn selected_option(&self) -> Option<&str> {
+        self.options.get(self.selected).map(String::as_str)
+    }
+
+    /// Returns true while the list of options is shown.
+    pub fn is_open(&self) -> bool {
+        !self.option_entities.is_empty()
+    }
+
+    fn close(&mut self, entities: &Entities<'_>) {
+        for entity in self.option_entities.drain(..) {
+            let _ = entities.delete(entity);
+        }
+    }
+}
+
+impl Component for UiDropdown {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Opens and closes the `UiDropdown`s, and selects the clicked options.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+pub struct UiDropdownSystem<A, B> {
+    event_reader: Option<ReaderId<UiEvent>>,
+    was_down: bool,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> UiDropdownSystem<A, B> {
+    /// Creates a new `UiDropdownSystem`.
+    pub fn new() -> Self {
+        UiDropdownSystem {
+            event_reader: None,
+            was_down: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for UiDropdownSystem<A, B>
+where
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, UiDropdown>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, TextureHandle>,
+        WriteStorage<'a, Interactable>,
+        WriteStorage<'a, Parent>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<UiValueChange>>,
+        Read<'a, InputHandler<A, B>>,
+        ReadExpect<'a, ScreenDimensions>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut dropdowns,
+            mut transforms,
+            mut texts,
+            mut images,
+            mut interactables,
+            mut parents,
+            ui_events,
+            mut changes,
+            input,
+            screen_dimensions,
+        ): Self::SystemData,
+    ) {
+        // Pressing the mouse outside of an open dropdown closes it.
+        let down = input.mouse_button_is_down(MouseButton::Left);
+        if down && !self.was_down {
+            if let Some((x, y)) = input.mouse_position() {
+                let (x, y) = (x as f32, screen_dimensions.height() - y as f32);
+                let inside = |entity: Entity| {
+                    transforms
+                        .get(entity)
+                        .map_or(false, |transform| transform.position_inside(x, y))
+                };
+                for (entity, dropdown) in (&*entities, &mut dropdowns).join() {
+                    if dropdown.is_open()
+                        && !inside(entity)
+                        && !dropdown.option_entities.iter().any(|e| inside(*e))
+                    {
+                        dropdown.close(&entities);
+                    }
+                }
+            }
+        }
+        self.was_down = down;
+
+        let mut opened = Vec::new();
+        for event in ui_events.read(
+            self.event_reader
+                .as_mut()
+                .expect("`UiDropdownSystem::setup` was not called before `UiDropdownSystem::run`"),
+        ) {
+            if event.event_type != UiEventType::Click {
+                continue;
+            }
+            if let Some(dropdown) = dropdowns.get_mut(event.target) {
+                if dropdown.is_open() {
+                    dropdown.close(&entities);
+                } else {
+                    opened.push(event.target);
+                }
+                continue;
+            }
+            let clicked_option = (&*entities, &dropdowns)
+                .join()
+                .find_map(|(entity, dropdown)| {
+                    dropdown
+                        .option_entities
+                        .iter()
+                        .position(|option| *option == event.target)
+                        .map(|index| (entity, index))
+                });
+            if let Some((entity, index)) = clicked_option {
+                let dropdown = dropdowns
+                    .get_mut(entity)
+                    .expect("Unreachable: The dropdown was just found");
+                if dropdown.selected != index {
+                    dropdown.selected = index;
+                    changes.single_write(UiValueChange::new(entity, UiValue::Index(index)));
+                }
+                dropdown.close(&entities);
+            }
+        }
+
+        for entity in opened {
+            let (id, width, height) = match transforms.get(entity) {
+                Some(t) => (t.id.clone(), t.pixel_width, t.pixel_height),
+                None => continue,
+            };
+            let style = texts
+                .get(entity)
+                .map(|t| (t.font.clone(), t.color, t.font_size, t.align.clone()));
+            let image = images.get(entity).cloned();
+            let dropdown = dropdowns
+                .get_mut(entity)
+                .expect("Unreachable: The dropdown was just clicked");
+            for (index, option) in dropdown.options.iter().enumerate() {
+                let option_entity = entities.create();
+                let transform = UiTransform::new(
+                    format!("{}_option_{}", id, index),
+                    Anchor::BottomMiddle,
+                    Anchor::TopMiddle,
+                    0.0,
+                    -(index as f32) * height,
+                    OPTIONS_Z,
+                    width,
+                    height,
+                );
+                transforms
+                    .insert(option_entity, transform)
+                    .expect("Unreachable: The option entity was just created");
+                parents
+                    .insert(option_entity, Parent { entity })
+                    .expect("Unreachable: The option entity was just created");
+                interactables
+                    .insert(option_entity, Interactable)
+                    .expect("Unreachable: The option entity was just created");
+                if let Some(ref image) = image {
+                    images
+                        .insert(option_entity, image.clone())
+                        .expect("Unreachable: The option entity was just created");
+                }
+                if let Some((ref font, color, font_size, ref align)) = style {
+                    let mut text = UiText::new(font.clone(), option.clone(), color, font_size);
+                    text.align = align.clone();
+                    texts
+                        .insert(option_entity, text)
+                        .expect("Unreachable: The option entity was just created");
+                }
+                dropdown.option_entities.push(option_entity);
+            }
+        }
+
+        for (dropdown, text) in (&dropdowns, &mut texts).join() {
+            if let Some(option) = dropdown.selected_option() {
+                if text.text != option {
+                    text.text = option.to_string();
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+}
@@ -0,0 +1,50 @@
+//! Interactive widgets holding a value: sliders, checkboxes, radio buttons and dropdowns.
+//!
+//! Changes made by the user are sent to the `EventChannel<UiValueChange>`.
+
+mod dropdown;
+mod slider;
+mod toggle;
+
+pub use self::{
+    dropdown::{UiDropdown, UiDropdownSystem},
+    slider::{UiSlider, UiSliderSystem},
+    toggle::{UiToggle, UiToggleSystem},
+};
+
+use amethyst_core::ecs::prelude::Entity;
+
+use crate::event::TargetedEvent;
+
+/// Value of an interactive widget.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UiValue {
+    /// State of a `UiToggle`.
+    Bool(bool),
+    /// Value of a `UiSlider`.
+    Number(f32),
+    /// Index of the option selected in a `UiDropdown`.
+    Index(usize),
+}
+
+/// Sent when the user changes the value of a widget.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiValueChange {
+    /// The widget whose value changed.
+    pub target: Entity,
+    /// The new value.
+    pub value: UiValue,
+}
+
+impl UiValueChange {
+    /// Creates a new `UiValueChange`.
+    pub fn new(target: Entity, value: UiValue) -> Self {
+        UiValueChange { target, value }
+    }
+}
+
+impl TargetedEvent for UiValueChange {
+    fn get_target(&self) -> Entity {
+        self.target
+    }
+}
@@ -0,0 +1,191 @@
+use std::{hash::Hash, marker::PhantomData};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReaderId, Resources,
+        System, SystemData, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+    ParentHierarchy,
+};
+use amethyst_input::InputHandler;
+use amethyst_renderer::MouseButton;
+
+use crate::{UiEvent, UiEventType, UiTransform, UiValue, UiValueChange};
+
+/// A horizontal slider choosing a number between `min` and `max`.
+///
+/// The entity is the track of the slider and must be `Interactable`. Its first child is the
+/// handle, moved along the track by the `UiSliderSystem`; it should be transparent so clicks go
+/// through to the track.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UiSlider {
+    /// Value at the left end of the track.
+    pub min: f32,
+    /// Value at the right end of the track.
+    pub max: f32,
+    /// Current value.
+    pub value: f32,
+    /// Interval between the values the user can pick, or zero for any value.
+    #[serde(default)]
+    pub step: f32,
+}
+
+impl UiSlider {
+    /// Creates a continuous slider between `min` and `max`.
+    pub fn new(min: f32, max: f32, value: f32) -> Self {
+        UiSlider {
+            min,
+            max,
+            value,
+            step: 0.0,
+        }
+    }
+
+    /// Only lets the user pick multiples of `step` from `min`.
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Returns the position of the value between `min` (0.0) and `max` (1.0).
+    pub fn fraction(&self) -> f32 {
+        if self.max == self.min {
+            0.0
+        } else {
+            ((self.value - self.min) / (self.max - self.min))
+                .min(1.0)
+                .max(0.0)
+        }
+    }
+
+    /// Returns the value at the position between `min` (0.0) and `max` (1.0), snapped to `step`.
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        let value = self.min + fraction.min(1.0).max(0.0) * (self.max - self.min);
+        if self.step > 0.0 {
+            let snapped = self.min + ((value - self.min) / self.step).round() * self.step;
+            snapped
+                .min(self.min.max(self.max))
+                .max(self.min.min(self.max))
+        } else {
+            value
+        }
+    }
+}
+
+impl Component for UiSlider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Lets the user drag the `UiSlider`s with the left mouse button, and places their handle.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+pub struct UiSliderSystem<A, B> {
+    event_reader: Option<ReaderId<UiEvent>>,
+    dragging: Option<Entity>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> UiSliderSystem<A, B> {
+    /// Creates a new `UiSliderSystem`.
+    pub fn new() -> Self {
+        UiSliderSystem {
+            event_reader: None,
+            dragging: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for UiSliderSystem<A, B>
+where
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, UiSlider>,
+        ReadExpect<'a, ParentHierarchy>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<UiValueChange>>,
+        Read<'a, InputHandler<A, B>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut transforms, mut sliders, hierarchy, ui_events, mut changes, input): Self::SystemData,
+    ) {
+        for event in ui_events.read(
+            self.event_reader
+                .as_mut()
+                .expect("`UiSliderSystem::setup` was not called before `UiSliderSystem::run`"),
+        ) {
+            if event.event_type == UiEventType::ClickStart && sliders.contains(event.target) {
+                self.dragging = Some(event.target);
+            }
+        }
+
+        if !input.mouse_button_is_down(MouseButton::Left) {
+            self.dragging = None;
+        }
+        if let (Some(entity), Some((x, _))) = (self.dragging, input.mouse_position()) {
+            let (left, width) = match transforms.get(entity) {
+                Some(track) => (track.pixel_x - track.pixel_width / 2.0, track.pixel_width),
+                None => (0.0, 0.0),
+            };
+            if let Some(slider) = sliders.get_mut(entity) {
+                if width > 0.0 {
+                    let value = slider.value_at((x as f32 - left) / width);
+                    if value != slider.value {
+                        slider.value = value;
+                        changes.single_write(UiValueChange::new(entity, UiValue::Number(value)));
+                    }
+                }
+            }
+        }
+
+        // The handle is anchored in the middle of the track, and only written to when it moves.
+        for (entity, slider) in (&*entities, &sliders).join() {
+            let width = match transforms.get(entity) {
+                Some(track) => track.pixel_width,
+                None => continue,
+            };
+            let handle = match hierarchy.children(entity).first() {
+                Some(handle) => *handle,
+                None => continue,
+            };
+            let x = (slider.fraction() - 0.5) * width;
+            let moved = transforms
+                .get(handle)
+                .map_or(false, |handle| (handle.local_x - x).abs() > 0.01);
+            if moved {
+                if let Some(handle) = transforms.get_mut(handle) {
+                    handle.local_x = x;
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UiSlider;
+
+    #[test]
+    fn snaps_values_to_step() {
+        let slider = UiSlider::new(10.0, 20.0, 15.0).with_step(2.0);
+        assert_eq!(0.5, slider.fraction());
+        assert_eq!(10.0, slider.value_at(-0.5));
+        assert_eq!(14.0, slider.value_at(0.38));
+        assert_eq!(20.0, slider.value_at(0.98));
+        assert_eq!(17.5, UiSlider::new(10.0, 20.0, 0.0).value_at(0.75));
+    }
+}
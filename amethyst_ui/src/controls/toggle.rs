@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReaderId, Resources,
+        System, SystemData, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+    ParentHierarchy,
+};
+use amethyst_renderer::Hidden;
+
+use crate::{UiEvent, UiEventType, UiValue, UiValueChange};
+
+/// A checkbox, or a radio button when it belongs to a `group`.
+///
+/// The entity must be `Interactable`. Its first child is the check mark, hidden by the
+/// `UiToggleSystem` while the toggle is not checked.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiToggle {
+    /// Whether the toggle is checked.
+    pub checked: bool,
+    /// Name of the radio group: checking a toggle unchecks the others of the same group, and
+    /// clicking a checked radio button does nothing.
+    pub group: Option<String>,
+}
+
+impl UiToggle {
+    /// Creates a checkbox.
+    pub fn checkbox(checked: bool) -> Self {
+        UiToggle {
+            checked,
+            group: None,
+        }
+    }
+
+    /// Creates a radio button of the group.
+    pub fn radio<S: Into<String>>(group: S, checked: bool) -> Self {
+        UiToggle {
+            checked,
+            group: Some(group.into()),
+        }
+    }
+}
+
+impl Component for UiToggle {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Checks and unchecks the clicked `UiToggle`s, and shows their check mark.
+#[derive(Default)]
+pub struct UiToggleSystem {
+    event_reader: Option<ReaderId<UiEvent>>,
+}
+
+impl UiToggleSystem {
+    /// Creates a new `UiToggleSystem`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> System<'a> for UiToggleSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, UiToggle>,
+        WriteStorage<'a, Hidden>,
+        ReadExpect<'a, ParentHierarchy>,
+        Read<'a, EventChannel<UiEvent>>,
+        Write<'a, EventChannel<UiValueChange>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut toggles, mut hidden, hierarchy, ui_events, mut changes): Self::SystemData,
+    ) {
+        for event in ui_events.read(
+            self.event_reader
+                .as_mut()
+                .expect("`UiToggleSystem::setup` was not called before `UiToggleSystem::run`"),
+        ) {
+            if event.event_type == UiEventType::Click {
+                click(event.target, &entities, &mut toggles, &mut changes);
+            }
+        }
+
+        for (entity, toggle) in (&*entities, &toggles).join() {
+            if let Some(mark) = hierarchy.children(entity).first() {
+                if toggle.checked && hidden.contains(*mark) {
+                    hidden.remove(*mark);
+                } else if !toggle.checked && !hidden.contains(*mark) {
+                    hidden
+                        .insert(*mark, Hidden)
+                        .expect("Unreachable: The check mark entity is alive");
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+}
+
+/// Toggles the clicked entity, and unchecks the other radio buttons of its group.
+fn click(
+    target: Entity,
+    entities: &Entities<'_>,
+    toggles: &mut WriteStorage<'_, UiToggle>,
+    changes: &mut EventChannel<UiValueChange>,
+) {
+    let group = match toggles.get_mut(target) {
+        Some(ref toggle) if toggle.group.is_some() && toggle.checked => return,
+        Some(toggle) => {
+            toggle.checked = !toggle.checked;
+            changes.single_write(UiValueChange::new(target, UiValue::Bool(toggle.checked)));
+            toggle.group.clone()
+        }
+        None => return,
+    };
+    if let Some(group) = group {
+        for (entity, toggle) in (&**entities, toggles).join() {
+            if entity != target && toggle.checked && toggle.group.as_ref() == Some(&group) {
+                toggle.checked = false;
+                changes.single_write(UiValueChange::new(entity, UiValue::Bool(false)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, World},
+        shrev::EventChannel,
+    };
+
+    use super::{click, UiToggle};
+    use crate::{UiValue, UiValueChange};
+
+    #[test]
+    fn radio_buttons_uncheck_their_group() {
+        let mut world = World::new();
+        world.register::<UiToggle>();
+        let a = world
+            .create_entity()
+            .with(UiToggle::radio("size", true))
+            .build();
+        let b = world
+            .create_entity()
+            .with(UiToggle::radio("size", false))
+            .build();
+        let other = world
+            .create_entity()
+            .with(UiToggle::radio("color", true))
+            .build();
+        let checkbox = world.create_entity().with(UiToggle::checkbox(true)).build();
+
+        let mut changes = EventChannel::<UiValueChange>::new();
+        let mut reader = changes.register_reader();
+        for target in vec![b, b, checkbox] {
+            click(
+                target,
+                &world.entities(),
+                &mut world.write_storage(),
+                &mut changes,
+            );
+        }
+
+        let toggles = world.read_storage::<UiToggle>();
+        let checked = |e| toggles.get(e).unwrap().checked;
+        assert_eq!(
+            (false, true, true, false),
+            (checked(a), checked(b), checked(other), checked(checkbox))
+        );
+        assert_eq!(
+            vec![
+                UiValueChange::new(b, UiValue::Bool(true)),
+                UiValueChange::new(a, UiValue::Bool(false)),
+                UiValueChange::new(checkbox, UiValue::Bool(false)),
+            ],
+            changes.read(&mut reader).cloned().collect::<Vec<_>>()
+        );
+    }
+}
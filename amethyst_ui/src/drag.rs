@@ -0,0 +1,392 @@
+use std::{hash::Hash, marker::PhantomData};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::{
+        prelude::{
+            Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReadStorage,
+            ReaderId, Resources, System, SystemData, Write, WriteStorage,
+        },
+        storage::NullStorage,
+    },
+    math::Vector2,
+    shrev::EventChannel,
+};
+use amethyst_input::InputHandler;
+use amethyst_renderer::{MouseButton, Rgba, ScreenDimensions, TextureHandle};
+
+use crate::{Anchor, ScaleMode, UiEvent, UiEventType, UiText, UiTransform};
+
+/// Distance in pixels the mouse has to move while pressed before a drag starts, so clicks
+/// are not mistaken for drags.
+const DRAG_THRESHOLD: f32 = 4.0;
+
+/// Z of the ghost images, so they are drawn over the rest of the ui.
+const GHOST_Z: f32 = 1000.0;
+
+/// A ui element which can be dragged with the mouse and dropped on `Droppable` elements.
+///
+/// The entity must be `Interactable`. The `UiDragSystem` moves the element, or a ghost copy of
+/// its image and text, with the mouse and puts it back in place when dropped: move it in
+/// response to the `Dropped` event to keep it where it was dropped.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Draggable {
+    /// Alpha of the ghost copy dragged instead of the element, which then stays in place.
+    pub ghost_alpha: Option<f32>,
+}
+
+impl Draggable {
+    /// Drags the element itself.
+    pub fn new() -> Self {
+        Draggable { ghost_alpha: None }
+    }
+
+    /// Drags a translucent copy of the element instead of the element itself.
+    pub fn with_ghost(alpha: f32) -> Self {
+        Draggable {
+            ghost_alpha: Some(alpha),
+        }
+    }
+}
+
+impl Component for Draggable {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// A ui element on which `Draggable` elements can be dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Droppable;
+
+impl Component for Droppable {
+    type Storage = NullStorage<Self>;
+}
+
+/// The drag in progress.
+struct Drag {
+    source: Entity,
+    ghost: Option<Entity>,
+    /// Mouse position when the drag started.
+    mouse_start: (f32, f32),
+    /// Local position of the dragged element when the drag started.
+    position_start: (f32, f32),
+    /// Local units per pixel of the dragged element.
+    scale: (f32, f32),
+    element_offset: Vector2<f32>,
+    source_opaque: bool,
+    started: bool,
+    hovered: Option<Entity>,
+}
+
+/// Moves the `Draggable` elements with the mouse and sends the `DragStart`, `Dragging`,
+/// `DragOver`, `Dropped` and `DragCancel` ui events, targeting the dragged element.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+pub struct UiDragSystem<A, B> {
+    event_reader: Option<ReaderId<UiEvent>>,
+    drag: Option<Drag>,
+    _marker: PhantomData<(A, B)>,
+}
+
+impl<A, B> UiDragSystem<A, B> {
+    /// Creates a new `UiDragSystem`.
+    pub fn new() -> Self {
+        UiDragSystem {
+            event_reader: None,
+            drag: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for UiDragSystem<A, B>
+where
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Draggable>,
+        ReadStorage<'a, Droppable>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, TextureHandle>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, Rgba>,
+        Write<'a, EventChannel<UiEvent>>,
+        Read<'a, InputHandler<A, B>>,
+        ReadExpect<'a, ScreenDimensions>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            draggables,
+            droppables,
+            mut transforms,
+            mut images,
+            mut texts,
+            mut colors,
+            mut events,
+            input,
+            screen_dimensions,
+        ): Self::SystemData,
+    ) {
+        let pressed = events
+            .read(
+                self.event_reader
+                    .as_mut()
+                    .expect("`UiDragSystem::setup` was not called before `UiDragSystem::run`"),
+            )
+            .filter(|event| event.event_type == UiEventType::ClickStart)
+            .map(|event| event.target)
+            .filter(|target| draggables.contains(*target))
+            .last();
+        let mouse = input
+            .mouse_position()
+            .map(|(x, y)| (x as f32, screen_dimensions.height() - y as f32));
+
+        if self.drag.is_none() {
+            if let (Some(source), Some(mouse)) = (pressed, mouse) {
+                if let Some(transform) = transforms.get(source) {
+                    let scale = match transform.scale_mode {
+                        ScaleMode::Pixel => (1.0, 1.0),
+                        ScaleMode::Percent => (
+                            transform.width / transform.pixel_width.max(1.0),
+                            transform.height / transform.pixel_height.max(1.0),
+                        ),
+                    };
+                    self.drag = Some(Drag {
+                        source,
+                        ghost: None,
+                        mouse_start: mouse,
+                        position_start: (transform.local_x, transform.local_y),
+                        scale,
+                        element_offset: Vector2::new(
+                            mouse.0 - transform.pixel_x,
+                            mouse.1 - transform.pixel_y,
+                        ),
+                        source_opaque: transform.opaque,
+                        started: false,
+                        hovered: None,
+                    });
+                }
+            }
+        }
+
+        let mut drag = match self.drag.take() {
+            Some(drag) => drag,
+            None => return,
+        };
+        if !entities.is_alive(drag.source) || !transforms.contains(drag.source) {
+            if let Some(ghost) = drag.ghost {
+                let _ = entities.delete(ghost);
+            }
+            return;
+        }
+
+        if !input.mouse_button_is_down(MouseButton::Left) {
+            if drag.started {
+                let dropped_on = mouse.and_then(|mouse| {
+                    drop_target(mouse, &drag, &entities, &droppables, &transforms)
+                });
+                let event_type = match dropped_on {
+                    Some(dropped_on) => UiEventType::Dropped { dropped_on },
+                    None => UiEventType::DragCancel,
+                };
+                let transform = transforms
+                    .get_mut(drag.source)
+                    .expect("Unreachable: The dragged element was just checked");
+                transform.opaque = drag.source_opaque;
+                match drag.ghost {
+                    Some(ghost) => {
+                        let _ = entities.delete(ghost);
+                    }
+                    None => {
+                        transform.local_x = drag.position_start.0;
+                        transform.local_y = drag.position_start.1;
+                    }
+                }
+                events.single_write(UiEvent::new(event_type, drag.source));
+            }
+            return;
+        }
+
+        let mouse = match mouse {
+            Some(mouse) => mouse,
+            None => {
+                self.drag = Some(drag);
+                return;
+            }
+        };
+        let (dx, dy) = (mouse.0 - drag.mouse_start.0, mouse.1 - drag.mouse_start.1);
+        if !drag.started {
+            if dx * dx + dy * dy < DRAG_THRESHOLD * DRAG_THRESHOLD {
+                self.drag = Some(drag);
+                return;
+            }
+            drag.started = true;
+            let transform = transforms
+                .get_mut(drag.source)
+                .expect("Unreachable: The dragged element was just checked");
+            // The dragged element lets the mouse through to the elements it is dropped on.
+            transform.opaque = false;
+            if let Some(alpha) = draggables.get(drag.source).and_then(|d| d.ghost_alpha) {
+                let ghost_transform = UiTransform::new(
+                    format!("{}_ghost", transform.id),
+                    Anchor::BottomLeft,
+                    Anchor::Middle,
+                    transform.pixel_x,
+                    transform.pixel_y,
+                    GHOST_Z,
+                    transform.pixel_width,
+                    transform.pixel_height,
+                )
+                .as_transparent();
+                drag.position_start = (transform.pixel_x, transform.pixel_y);
+                drag.scale = (1.0, 1.0);
+
+                let ghost = entities.create();
+                transforms
+                    .insert(ghost, ghost_transform)
+                    .expect("Unreachable: The ghost entity was just created");
+                if let Some(image) = images.get(drag.source).cloned() {
+                    images
+                        .insert(ghost, image)
+                        .expect("Unreachable: The ghost entity was just created");
+                }
+                if let Some(text) = texts.get(drag.source).cloned() {
+                    texts
+                        .insert(ghost, text)
+                        .expect("Unreachable: The ghost entity was just created");
+                }
+                let Rgba(r, g, b, a) = colors.get(drag.source).cloned().unwrap_or(Rgba::WHITE);
+                colors
+                    .insert(ghost, Rgba(r, g, b, a * alpha))
+                    .expect("Unreachable: The ghost entity was just created");
+                drag.ghost = Some(ghost);
+            }
+            events.single_write(UiEvent::new(UiEventType::DragStart, drag.source));
+        }
+
+        let moved = drag.ghost.unwrap_or(drag.source);
+        if let Some(transform) = transforms.get_mut(moved) {
+            let x = drag.position_start.0 + dx * drag.scale.0;
+            let y = drag.position_start.1 + dy * drag.scale.1;
+            if transform.local_x != x || transform.local_y != y {
+                transform.local_x = x;
+                transform.local_y = y;
+                events.single_write(UiEvent::new(
+                    UiEventType::Dragging {
+                        element_offset: drag.element_offset,
+                    },
+                    drag.source,
+                ));
+            }
+        }
+
+        let hovered = drop_target(mouse, &drag, &entities, &droppables, &transforms);
+        if hovered != drag.hovered {
+            drag.hovered = hovered;
+            events.single_write(UiEvent::new(UiEventType::DragOver { hovered }, drag.source));
+        }
+        self.drag = Some(drag);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.event_reader = Some(res.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+    }
+}
+
+/// Returns the top most `Droppable` element under the mouse, other than the dragged one.
+fn drop_target(
+    mouse: (f32, f32),
+    drag: &Drag,
+    entities: &Entities<'_>,
+    droppables: &ReadStorage<'_, Droppable>,
+    transforms: &WriteStorage<'_, UiTransform>,
+) -> Option<Entity> {
+    (&**entities, droppables, transforms)
+        .join()
+        .filter(|(entity, _, transform)| {
+            *entity != drag.source
+                && Some(*entity) != drag.ghost
+                && transform.position_inside(mouse.0, mouse.1)
+        })
+        .max_by(|(_, _, t1), (_, _, t2)| {
+            t1.global_z
+                .partial_cmp(&t2.global_z)
+                .expect("Unexpected NaN")
+        })
+        .map(|(entity, _, _)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::prelude::{Builder, World};
+
+    use super::{drop_target, Drag, Droppable};
+    use crate::{Anchor, UiTransform};
+
+    fn transform(x: f32, z: f32) -> UiTransform {
+        UiTransform::new(
+            String::new(),
+            Anchor::BottomLeft,
+            Anchor::Middle,
+            x,
+            50.0,
+            z,
+            100.0,
+            100.0,
+        )
+    }
+
+    #[test]
+    fn drops_on_top_most_droppable() {
+        let mut world = World::new();
+        world.register::<UiTransform>();
+        world.register::<Droppable>();
+        let source = world
+            .create_entity()
+            .with(transform(50.0, 10.0))
+            .with(Droppable)
+            .build();
+        let low = world
+            .create_entity()
+            .with(transform(50.0, 1.0))
+            .with(Droppable)
+            .build();
+        let high = world
+            .create_entity()
+            .with(transform(100.0, 2.0))
+            .with(Droppable)
+            .build();
+        world.create_entity().with(transform(50.0, 5.0)).build();
+
+        let drag = Drag {
+            source,
+            ghost: None,
+            mouse_start: (0.0, 0.0),
+            position_start: (0.0, 0.0),
+            scale: (1.0, 1.0),
+            element_offset: [0.0, 0.0].into(),
+            source_opaque: true,
+            started: true,
+            hovered: None,
+        };
+        let target = |x| {
+            drop_target(
+                (x, 50.0),
+                &drag,
+                &world.entities(),
+                &world.read_storage(),
+                &world.write_storage(),
+            )
+        };
+        assert_eq!(Some(low), target(20.0));
+        assert_eq!(Some(high), target(80.0));
+        assert_eq!(None, target(200.0));
+    }
+}
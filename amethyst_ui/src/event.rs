@@ -38,16 +38,25 @@ pub enum UiEventType {
     HoverStart,
     /// When the cursor stops being over an element.
     HoverStop,
+    /// When starting to drag a `Draggable` Ui element.
+    DragStart,
     /// When dragging a `Draggable` Ui element.
     Dragging {
         /// The position of the mouse relative to the center of the transform when the drag started.
         element_offset: Vector2<f32>,
     },
+    /// When a dragged `Draggable` Ui element enters or leaves a `Droppable` element.
+    DragOver {
+        /// The `Droppable` entity under the mouse, if any.
+        hovered: Option<Entity>,
+    },
     /// When stopping to drag a `Draggable` Ui element.
     Dropped {
         /// The entity on which the dragged object was dropped.
         dropped_on: Entity,
     },
+    /// When stopping to drag a `Draggable` Ui element outside of any `Droppable` element.
+    DragCancel,
     /// When the value of a UiText element has been changed by user input.
     ValueChange,
     /// When the value of a UiText element has been committed by user action.
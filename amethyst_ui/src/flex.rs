@@ -0,0 +1,444 @@
+//! Flexbox-style layout of the children of a ui element.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::ecs::prelude::{Component, DenseVecStorage, FlaggedStorage};
+
+use crate::layout::Rect;
+
+/// Direction in which a `UiFlex` container places its children.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum FlexDirection {
+    /// Places the children from left to right.
+    Row,
+    /// Places the children from right to left.
+    RowReverse,
+    /// Places the children from top to bottom.
+    Column,
+    /// Places the children from bottom to top.
+    ColumnReverse,
+}
+
+/// How a `UiFlex` container distributes the free space of a line between its children.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum FlexJustify {
+    /// Packs the children at the start of the line.
+    Start,
+    /// Packs the children at the end of the line.
+    End,
+    /// Packs the children in the middle of the line.
+    Center,
+    /// Puts the free space between the children, the first and last ones touch the edges.
+    SpaceBetween,
+    /// Puts the same free space on both sides of every child.
+    SpaceAround,
+}
+
+/// How a `UiFlex` container places its children across its direction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum FlexAlign {
+    /// Places the children at the top of a row, or the left of a column.
+    Start,
+    /// Places the children at the bottom of a row, or the right of a column.
+    End,
+    /// Centers the children across the line.
+    Center,
+    /// Stretches the children to the size of the line.
+    Stretch,
+}
+
+/// Space kept around a `UiFlexItem`, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Margin {
+    /// Space on the left of the element.
+    pub left: f32,
+    /// Space on the right of the element.
+    pub right: f32,
+    /// Space above the element.
+    pub top: f32,
+    /// Space below the element.
+    pub bottom: f32,
+}
+
+impl Margin {
+    /// Creates a margin of the same size on every side.
+    pub fn uniform(margin: f32) -> Self {
+        Margin {
+            left: margin,
+            right: margin,
+            top: margin,
+            bottom: margin,
+        }
+    }
+}
+
+/// Lays out the children of a ui element like a flexbox container, in place of their anchor,
+/// pivot and position.
+///
+/// Children are placed one after the other along `direction`, in the order they were added to
+/// the container, and start a new line when `wrap` is set and the current line is full.
+/// Their size comes from their `UiTransform`, so children using `ScaleMode::Percent` get a
+/// percentage of the size of the container.
+///
+/// Add a `UiFlexItem` to a child to give it margins or to make it grow into the free space.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiFlex {
+    /// Direction in which the children are placed.
+    pub direction: FlexDirection,
+    /// Starts a new line when the children overflow the container.
+    pub wrap: bool,
+    /// Distribution of the free space along the direction.
+    pub justify: FlexJustify,
+    /// Placement of the children across the direction.
+    pub align: FlexAlign,
+    /// Space between the edges of the container and its children, in pixels.
+    pub padding: f32,
+    /// Space between two children, and between two lines, in pixels.
+    pub spacing: f32,
+}
+
+impl Default for UiFlex {
+    fn default() -> Self {
+        UiFlex::new(FlexDirection::Row)
+    }
+}
+
+impl UiFlex {
+    /// Creates a container placing its children in `direction`, packed at the start.
+    pub fn new(direction: FlexDirection) -> Self {
+        UiFlex {
+            direction,
+            wrap: false,
+            justify: FlexJustify::Start,
+            align: FlexAlign::Start,
+            padding: 0.0,
+            spacing: 0.0,
+        }
+    }
+
+    /// Starts a new line when the children overflow the container.
+    pub fn with_wrap(mut self) -> Self {
+        self.wrap = true;
+        self
+    }
+
+    /// Sets the distribution of the free space along the direction.
+    pub fn with_justify(mut self, justify: FlexJustify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// Sets the placement of the children across the direction.
+    pub fn with_align(mut self, align: FlexAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Sets the space between the edges of the container and its children.
+    pub fn with_padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets the space between two children, and between two lines.
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    fn is_row(&self) -> bool {
+        self.direction == FlexDirection::Row || self.direction == FlexDirection::RowReverse
+    }
+
+    fn is_reverse(&self) -> bool {
+        self.direction == FlexDirection::RowReverse
+            || self.direction == FlexDirection::ColumnReverse
+    }
+
+    /// Sizes of the item along and across the direction.
+    fn sizes(&self, item: &FlexEntry) -> (f32, f32) {
+        if self.is_row() {
+            (item.width, item.height)
+        } else {
+            (item.height, item.width)
+        }
+    }
+
+    /// Margins of the item before and after it along the direction.
+    fn main_margins(&self, margin: &Margin) -> (f32, f32) {
+        match self.direction {
+            FlexDirection::Row => (margin.left, margin.right),
+            FlexDirection::RowReverse => (margin.right, margin.left),
+            FlexDirection::Column => (margin.top, margin.bottom),
+            FlexDirection::ColumnReverse => (margin.bottom, margin.top),
+        }
+    }
+
+    /// Margins of the item before and after it across the direction.
+    fn cross_margins(&self, margin: &Margin) -> (f32, f32) {
+        if self.is_row() {
+            (margin.top, margin.bottom)
+        } else {
+            (margin.left, margin.right)
+        }
+    }
+
+    fn outer_main(&self, item: &FlexEntry) -> f32 {
+        let (before, after) = self.main_margins(&item.flex.margin);
+        self.sizes(item).0 + before + after
+    }
+
+    fn outer_cross(&self, item: &FlexEntry) -> f32 {
+        let (before, after) = self.cross_margins(&item.flex.margin);
+        self.sizes(item).1 + before + after
+    }
+
+    /// Splits the items into lines fitting in `main_size`.
+    fn lines(&self, items: &[FlexEntry], main_size: f32) -> Vec<Range<usize>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut used = 0.0;
+        for (i, item) in items.iter().enumerate() {
+            let outer = self.outer_main(item);
+            if self.wrap && i > start && used + self.spacing + outer > main_size {
+                lines.push(start..i);
+                start = i;
+                used = 0.0;
+            }
+            if i > start {
+                used += self.spacing;
+            }
+            used += outer;
+        }
+        if start < items.len() {
+            lines.push(start..items.len());
+        }
+        lines
+    }
+
+    /// Places `items`, given with the size they would have without the container, inside
+    /// `container`. Returns the rectangles of the items in the same order.
+    pub(crate) fn layout(&self, container: Rect, items: &[FlexEntry]) -> Vec<Rect> {
+        let (main_size, cross_size) = if self.is_row() {
+            (container.width, container.height)
+        } else {
+            (container.height, container.width)
+        };
+        let main_size = main_size - self.padding * 2.0;
+        let cross_size = cross_size - self.padding * 2.0;
+        let left = container.x - container.width / 2.0 + self.padding;
+        let right = container.x + container.width / 2.0 - self.padding;
+        let top = container.y + container.height / 2.0 - self.padding;
+        let bottom = container.y - container.height / 2.0 + self.padding;
+
+        let mut rects = vec![Rect::default(); items.len()];
+        let mut cross_pos = 0.0;
+        for line in self.lines(items, main_size) {
+            let line_items = &items[line.clone()];
+            let count = line_items.len() as f32;
+            let used = line_items
+                .iter()
+                .map(|item| self.outer_main(item))
+                .sum::<f32>()
+                + self.spacing * (count - 1.0);
+            let mut lengths = line_items
+                .iter()
+                .map(|item| self.sizes(item).0)
+                .collect::<Vec<_>>();
+            let grow = line_items.iter().map(|item| item.flex.grow).sum::<f32>();
+            let mut free = (main_size - used).max(0.0);
+            if free > 0.0 && grow > 0.0 {
+                for (length, item) in lengths.iter_mut().zip(line_items) {
+                    *length += free * item.flex.grow / grow;
+                }
+                free = 0.0;
+            }
+            let (mut main_pos, gap) = match self.justify {
+                FlexJustify::Start => (0.0, self.spacing),
+                FlexJustify::End => (free, self.spacing),
+                FlexJustify::Center => (free / 2.0, self.spacing),
+                FlexJustify::SpaceBetween if count > 1.0 => {
+                    (0.0, self.spacing + free / (count - 1.0))
+                }
+                FlexJustify::SpaceBetween => (0.0, self.spacing),
+                FlexJustify::SpaceAround => (free / count / 2.0, self.spacing + free / count),
+            };
+            let line_cross = if self.wrap {
+                line_items
+                    .iter()
+                    .map(|item| self.outer_cross(item))
+                    .fold(0.0, f32::max)
+            } else {
+                cross_size
+            };
+
+            for (i, (item, length)) in line.zip(line_items.iter().zip(lengths)) {
+                let (main_before, main_after) = self.main_margins(&item.flex.margin);
+                let (cross_before, cross_after) = self.cross_margins(&item.flex.margin);
+                main_pos += main_before;
+                let main_center = main_pos + length / 2.0;
+                main_pos += length + main_after + gap;
+
+                let cross = self.sizes(item).1;
+                let free_cross = line_cross - cross_before - cross_after;
+                let (cross_offset, cross) = match self.align {
+                    FlexAlign::Start => (cross_before, cross),
+                    FlexAlign::End => (line_cross - cross_after - cross, cross),
+                    FlexAlign::Center => (cross_before + (free_cross - cross) / 2.0, cross),
+                    FlexAlign::Stretch => (cross_before, free_cross),
+                };
+                let cross_center = cross_pos + cross_offset + cross / 2.0;
+
+                rects[i] = match (self.is_row(), self.is_reverse()) {
+                    (true, reverse) => Rect {
+                        x: if reverse {
+                            right - main_center
+                        } else {
+                            left + main_center
+                        },
+                        y: top - cross_center,
+                        width: length,
+                        height: cross,
+                    },
+                    (false, reverse) => Rect {
+                        x: left + cross_center,
+                        y: if reverse {
+                            bottom + main_center
+                        } else {
+                            top - main_center
+                        },
+                        width: cross,
+                        height: length,
+                    },
+                };
+            }
+            cross_pos += line_cross + self.spacing;
+        }
+        rects
+    }
+}
+
+impl Component for UiFlex {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Flex settings of a child of a `UiFlex` container.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiFlexItem {
+    /// Space kept around the element.
+    pub margin: Margin,
+    /// Share of the free space of its line the element grows into, relative to the other
+    /// elements of the line. Zero keeps its size.
+    pub grow: f32,
+}
+
+impl UiFlexItem {
+    /// Sets the space kept around the element.
+    pub fn with_margin(mut self, margin: Margin) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Sets the share of the free space the element grows into.
+    pub fn with_grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+}
+
+impl Component for UiFlexItem {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// A child of a `UiFlex` container, with its size in pixels.
+#[derive(Clone, Debug)]
+pub(crate) struct FlexEntry {
+    pub width: f32,
+    pub height: f32,
+    pub flex: UiFlexItem,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(width: f32, height: f32) -> FlexEntry {
+        FlexEntry {
+            width,
+            height,
+            flex: UiFlexItem::default(),
+        }
+    }
+
+    fn container() -> Rect {
+        Rect {
+            x: 50.0,
+            y: 50.0,
+            width: 100.0,
+            height: 100.0,
+        }
+    }
+
+    #[test]
+    fn places_row_with_spacing_and_margins() {
+        let flex = UiFlex::new(FlexDirection::Row)
+            .with_padding(10.0)
+            .with_spacing(5.0)
+            .with_align(FlexAlign::Center);
+        let mut second = entry(20.0, 10.0);
+        second.flex = UiFlexItem::default().with_margin(Margin::uniform(2.0));
+        let rects = flex.layout(container(), &[entry(20.0, 20.0), second]);
+        assert_eq!(
+            Rect {
+                x: 20.0,
+                y: 50.0,
+                width: 20.0,
+                height: 20.0
+            },
+            rects[0]
+        );
+        assert_eq!(
+            Rect {
+                x: 47.0,
+                y: 50.0,
+                width: 20.0,
+                height: 10.0
+            },
+            rects[1]
+        );
+    }
+
+    #[test]
+    fn justifies_grows_and_stretches_columns() {
+        let flex = UiFlex::new(FlexDirection::Column)
+            .with_justify(FlexJustify::SpaceBetween)
+            .with_align(FlexAlign::Stretch);
+        let rects = flex.layout(container(), &[entry(10.0, 20.0), entry(10.0, 20.0)]);
+        assert_eq!(
+            (50.0, 90.0, 100.0),
+            (rects[0].x, rects[0].y, rects[0].width)
+        );
+        assert_eq!(10.0, rects[1].y);
+
+        let mut growing = entry(10.0, 20.0);
+        growing.flex = UiFlexItem::default().with_grow(1.0);
+        let rects = flex.layout(container(), &[entry(10.0, 20.0), growing]);
+        assert_eq!(80.0, rects[1].height);
+        assert_eq!(40.0, rects[1].y);
+    }
+
+    #[test]
+    fn wraps_into_lines() {
+        let flex = UiFlex::new(FlexDirection::RowReverse).with_wrap();
+        let items = vec![entry(40.0, 10.0), entry(40.0, 20.0), entry(40.0, 10.0)];
+        let rects = flex.layout(container(), &items);
+        assert_eq!((80.0, 95.0), (rects[0].x, rects[0].y));
+        assert_eq!((40.0, 90.0), (rects[1].x, rects[1].y));
+        assert_eq!((80.0, 75.0), (rects[2].x, rects[2].y));
+    }
+}
@@ -1,3 +1,4 @@
+use fnv::FnvHashMap;
 use gfx_glyph::{HorizontalAlign, VerticalAlign};
 use serde::{Deserialize, Serialize};
 
@@ -6,14 +7,14 @@ use thread_profiler::profile_scope;
 
 use amethyst_core::{
     ecs::prelude::{
-        BitSet, ComponentEvent, Join, ReadExpect, ReadStorage, ReaderId, Resources, System,
+        BitSet, ComponentEvent, Entity, Join, ReadExpect, ReadStorage, ReaderId, Resources, System,
         WriteStorage,
     },
     HierarchyEvent, Parent, ParentHierarchy,
 };
 use amethyst_renderer::ScreenDimensions;
 
-use super::UiTransform;
+use crate::{flex::FlexEntry, ScrollView, UiFlex, UiFlexItem, UiTransform};
 
 /// Indicates if the position and margins should be calculated in pixel or
 /// relative to their parent size.
@@ -124,15 +125,83 @@ pub enum Stretch {
     },
 }
 
+/// Rectangle in pixels, centered on `x` and `y`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    fn left(&self) -> f32 {
+        self.x - self.width / 2.0
+    }
+
+    fn right(&self) -> f32 {
+        self.x + self.width / 2.0
+    }
+
+    fn bottom(&self) -> f32 {
+        self.y - self.height / 2.0
+    }
+
+    fn top(&self) -> f32 {
+        self.y + self.height / 2.0
+    }
+
+    /// Returns the left, bottom, right and top edges, as used by the ui shaders.
+    pub fn edges(&self) -> [f32; 4] {
+        [self.left(), self.bottom(), self.right(), self.top()]
+    }
+
+    /// Checks if the position is in the rectangle.
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.left() && x <= self.right() && y >= self.bottom() && y <= self.top()
+    }
+
+    /// Checks if the other rectangle is entirely in this one.
+    pub fn encloses(&self, other: &Rect) -> bool {
+        other.left() >= self.left()
+            && other.right() <= self.right()
+            && other.bottom() >= self.bottom()
+            && other.top() <= self.top()
+    }
+
+    /// Returns the part of the rectangle also covered by `other`.
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let left = self.left().max(other.left());
+        let right = self.right().min(other.right()).max(left);
+        let bottom = self.bottom().max(other.bottom());
+        let top = self.top().min(other.top()).max(bottom);
+        Rect {
+            x: (left + right) / 2.0,
+            y: (bottom + top) / 2.0,
+            width: right - left,
+            height: top - bottom,
+        }
+    }
+}
+
 /// Manages the `Parent` component on entities having `UiTransform`
 /// It does almost the same as the `TransformSystem`, but with some differences,
 /// like `UiTransform` alignment and stretching.
+///
+/// Children of an entity with a `UiFlex` component are placed by the flex layout instead, and
+/// children of a `ScrollView` are moved by its scroll offset and clipped to it.
 #[derive(Default)]
 pub struct UiTransformSystem {
     transform_modified: BitSet,
 
     transform_events_id: Option<ReaderId<ComponentEvent>>,
 
+    flex_events_id: Option<ReaderId<ComponentEvent>>,
+
+    flex_item_events_id: Option<ReaderId<ComponentEvent>>,
+
+    scroll_view_events_id: Option<ReaderId<ComponentEvent>>,
+
     parent_events_id: Option<ReaderId<HierarchyEvent>>,
 
     screen_size: (f32, f32),
@@ -144,9 +213,13 @@ impl<'a> System<'a> for UiTransformSystem {
         ReadStorage<'a, Parent>,
         ReadExpect<'a, ScreenDimensions>,
         ReadExpect<'a, ParentHierarchy>,
+        ReadStorage<'a, UiFlex>,
+        ReadStorage<'a, UiFlexItem>,
+        ReadStorage<'a, ScrollView>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (mut transforms, parents, screen_dim, hierarchy) = data;
+        let (mut transforms, parents, screen_dim, hierarchy, flexes, flex_items, scroll_views) =
+            data;
         #[cfg(feature = "profiler")]
         profile_scope!("ui_parent_system");
 
@@ -169,6 +242,36 @@ impl<'a> System<'a> for UiTransformSystem {
                 ComponentEvent::Removed(_id) => {}
             });
 
+        // Any change to the flex settings or the scrolling moves the children of the container.
+        let flex_events =
+            flexes.channel().read(self.flex_events_id.as_mut().expect(
+                "`UiTransformSystem::setup` was not called before `UiTransformSystem::run`",
+            ));
+        let flex_item_events =
+            flex_items
+                .channel()
+                .read(self.flex_item_events_id.as_mut().expect(
+                    "`UiTransformSystem::setup` was not called before `UiTransformSystem::run`",
+                ));
+        let scroll_view_events =
+            scroll_views
+                .channel()
+                .read(self.scroll_view_events_id.as_mut().expect(
+                    "`UiTransformSystem::setup` was not called before `UiTransformSystem::run`",
+                ));
+        for event in flex_events
+            .chain(flex_item_events)
+            .chain(scroll_view_events)
+        {
+            match event {
+                ComponentEvent::Inserted(id)
+                | ComponentEvent::Modified(id)
+                | ComponentEvent::Removed(id) => {
+                    self_transform_modified.add(*id);
+                }
+            }
+        }
+
         for event in
             hierarchy
                 .changed()
@@ -211,13 +314,34 @@ impl<'a> System<'a> for UiTransformSystem {
             });
 
         // Compute transforms with parents.
+        let mut flex_laid_out = BitSet::new();
+        let mut flex_rects = FnvHashMap::default();
         for entity in hierarchy.all() {
             {
-                let self_dirty = self_transform_modified.contains(entity.id());
                 let parent_entity = match parents.get(*entity) {
                     Some(p) => p.entity,
                     None => continue, // Skip this entity iteration, as its dirty
                 };
+                if let Some(flex) = flexes.get(parent_entity) {
+                    // Lay out all the children of the container when reaching the first one.
+                    if !flex_laid_out.add(parent_entity.id()) {
+                        let children = hierarchy.children(parent_entity);
+                        let dirty = screen_resized
+                            || self_transform_modified.contains(parent_entity.id())
+                            || children
+                                .iter()
+                                .any(|child| self_transform_modified.contains(child.id()));
+                        if dirty {
+                            for (child, rect) in
+                                layout_flex(flex, parent_entity, children, &transforms, &flex_items)
+                            {
+                                self_transform_modified.add(child.id());
+                                flex_rects.insert(child, rect);
+                            }
+                        }
+                    }
+                }
+                let self_dirty = self_transform_modified.contains(entity.id());
                 let parent_dirty = self_transform_modified.contains(parent_entity.id());
                 if parent_dirty || self_dirty || screen_resized {
                     let parent_transform_copy = transforms.get(parent_entity).cloned();
@@ -236,39 +360,11 @@ impl<'a> System<'a> for UiTransformSystem {
                         parent_transform_copy.pixel_y + parent_transform_copy.pixel_height * norm.1;
                     transform.global_z = parent_transform_copy.global_z + transform.local_z;
 
-                    let new_size = match transform.stretch {
-                        Stretch::NoStretch => (transform.width, transform.height),
-                        Stretch::X { x_margin } => (
-                            parent_transform_copy.pixel_width - x_margin * 2.0,
-                            transform.height,
-                        ),
-                        Stretch::Y { y_margin } => (
-                            transform.width,
-                            parent_transform_copy.pixel_height - y_margin * 2.0,
-                        ),
-                        Stretch::XY {
-                            keep_aspect_ratio: false,
-                            x_margin,
-                            y_margin,
-                        } => (
-                            parent_transform_copy.pixel_width - x_margin * 2.0,
-                            parent_transform_copy.pixel_height - y_margin * 2.0,
-                        ),
-                        Stretch::XY {
-                            keep_aspect_ratio: true,
-                            x_margin,
-                            y_margin,
-                        } => {
-                            let scale = f32::min(
-                                (parent_transform_copy.pixel_width - x_margin * 2.0)
-                                    / transform.width,
-                                (parent_transform_copy.pixel_height - y_margin * 2.0)
-                                    / transform.height,
-                            );
-
-                            (transform.width * scale, transform.height * scale)
-                        }
-                    };
+                    let new_size = stretched_size(
+                        transform,
+                        parent_transform_copy.pixel_width,
+                        parent_transform_copy.pixel_height,
+                    );
                     transform.width = new_size.0;
                     transform.height = new_size.1;
                     match transform.scale_mode {
@@ -292,6 +388,23 @@ impl<'a> System<'a> for UiTransformSystem {
                     let pivot_norm = transform.pivot.norm_offset();
                     transform.pixel_x += transform.pixel_width * -pivot_norm.0;
                     transform.pixel_y += transform.pixel_height * -pivot_norm.1;
+                    if let Some(rect) = flex_rects.get(entity) {
+                        transform.pixel_x = rect.x;
+                        transform.pixel_y = rect.y;
+                        transform.pixel_width = rect.width;
+                        transform.pixel_height = rect.height;
+                    }
+                    transform.clip = parent_transform_copy.clip;
+                    if let Some(scroll_view) = scroll_views.get(parent_entity) {
+                        let (offset_x, offset_y) = scroll_view.offset();
+                        transform.pixel_x -= offset_x;
+                        transform.pixel_y += offset_y;
+                        let view = parent_transform_copy.rect();
+                        transform.clip = Some(match transform.clip {
+                            Some(clip) => clip.intersection(&view),
+                            None => view,
+                        });
+                    }
                 }
             }
             // Populate the modifications we just did.
@@ -323,6 +436,78 @@ impl<'a> System<'a> for UiTransformSystem {
         self.parent_events_id = Some(res.fetch_mut::<ParentHierarchy>().track());
         let mut transforms = WriteStorage::<UiTransform>::fetch(res);
         self.transform_events_id = Some(transforms.register_reader());
+        self.flex_events_id = Some(WriteStorage::<UiFlex>::fetch(res).register_reader());
+        self.flex_item_events_id = Some(WriteStorage::<UiFlexItem>::fetch(res).register_reader());
+        self.scroll_view_events_id = Some(WriteStorage::<ScrollView>::fetch(res).register_reader());
+    }
+}
+
+/// Places the children of a `UiFlex` container, returning their rectangle in pixels.
+fn layout_flex(
+    flex: &UiFlex,
+    container: Entity,
+    children: &[Entity],
+    transforms: &WriteStorage<'_, UiTransform>,
+    flex_items: &ReadStorage<'_, UiFlexItem>,
+) -> Vec<(Entity, Rect)> {
+    let container = match transforms.get(container) {
+        Some(transform) => transform.rect(),
+        None => return Vec::new(),
+    };
+    let (entities, entries): (Vec<_>, Vec<_>) = children
+        .iter()
+        .filter_map(|child| {
+            let transform = transforms.get(*child)?;
+            let (width, height) = pixel_size(transform, container.width, container.height);
+            let entry = FlexEntry {
+                width,
+                height,
+                flex: flex_items.get(*child).cloned().unwrap_or_default(),
+            };
+            Some((*child, entry))
+        })
+        .unzip();
+    entities
+        .into_iter()
+        .zip(flex.layout(container, &entries))
+        .collect()
+}
+
+/// Size of the element stretched in a parent of the given size, before applying its scale mode.
+fn stretched_size(transform: &UiTransform, parent_width: f32, parent_height: f32) -> (f32, f32) {
+    match transform.stretch {
+        Stretch::NoStretch => (transform.width, transform.height),
+        Stretch::X { x_margin } => (parent_width - x_margin * 2.0, transform.height),
+        Stretch::Y { y_margin } => (transform.width, parent_height - y_margin * 2.0),
+        Stretch::XY {
+            keep_aspect_ratio: false,
+            x_margin,
+            y_margin,
+        } => (
+            parent_width - x_margin * 2.0,
+            parent_height - y_margin * 2.0,
+        ),
+        Stretch::XY {
+            keep_aspect_ratio: true,
+            x_margin,
+            y_margin,
+        } => {
+            let scale = f32::min(
+                (parent_width - x_margin * 2.0) / transform.width,
+                (parent_height - y_margin * 2.0) / transform.height,
+            );
+
+            (transform.width * scale, transform.height * scale)
+        }
+    }
+}
+
+/// Size of the element in pixels, in a parent of the given size.
+fn pixel_size(transform: &UiTransform, parent_width: f32, parent_height: f32) -> (f32, f32) {
+    let (width, height) = stretched_size(transform, parent_width, parent_height);
+    match transform.scale_mode {
+        ScaleMode::Pixel => (width, height),
+        ScaleMode::Percent => (width * parent_width, height * parent_height),
     }
 }
 
@@ -335,32 +520,9 @@ where
         transform.pixel_x = screen_dim.width() / 2.0 + screen_dim.width() * norm.0;
         transform.pixel_y = screen_dim.height() / 2.0 + screen_dim.height() * norm.1;
         transform.global_z = transform.local_z;
+        transform.clip = None;
 
-        let new_size = match transform.stretch {
-            Stretch::NoStretch => (transform.width, transform.height),
-            Stretch::X { x_margin } => (screen_dim.width() - x_margin * 2.0, transform.height),
-            Stretch::Y { y_margin } => (transform.width, screen_dim.height() - y_margin * 2.0),
-            Stretch::XY {
-                keep_aspect_ratio: false,
-                x_margin,
-                y_margin,
-            } => (
-                screen_dim.width() - x_margin * 2.0,
-                screen_dim.height() - y_margin * 2.0,
-            ),
-            Stretch::XY {
-                keep_aspect_ratio: true,
-                x_margin,
-                y_margin,
-            } => {
-                let scale = f32::min(
-                    (screen_dim.width() - x_margin * 2.0) / transform.width,
-                    (screen_dim.height() - y_margin * 2.0) / transform.height,
-                );
-
-                (transform.width * scale, transform.height * scale)
-            }
-        };
+        let new_size = stretched_size(transform, screen_dim.width(), screen_dim.height());
         transform.width = new_size.0;
         transform.height = new_size.1;
         match transform.scale_mode {
@@ -8,8 +8,14 @@ pub use self::{
         UiButton, UiButtonAction, UiButtonActionRetrigger, UiButtonActionRetriggerSystem,
         UiButtonActionType, UiButtonBuilder, UiButtonBuilderResources, UiButtonSystem,
     },
+    controls::{
+        UiDropdown, UiDropdownSystem, UiSlider, UiSliderSystem, UiToggle, UiToggleSystem, UiValue,
+        UiValueChange,
+    },
+    drag::{Draggable, Droppable, UiDragSystem},
     event::{targeted, Interactable, UiEvent, UiEventType, UiMouseSystem},
     event_retrigger::{EventReceiver, EventRetriggerSystem},
+    flex::{FlexAlign, FlexDirection, FlexJustify, Margin, UiFlex, UiFlexItem},
     font::{
         default::get_default_font,
         systemfont::{default_system_font, get_all_font_handles, list_system_font_families},
@@ -17,39 +23,62 @@ pub use self::{
     format::{FontAsset, FontFormat, FontHandle, OtfFormat, TtfFormat},
     label::{UiLabel, UiLabelBuilder, UiLabelBuilderResources},
     layout::{Anchor, ScaleMode, Stretch, UiTransformSystem},
+    markup::UiIcons,
+    navigation::{NavigationDirection, UiNavigationActions, UiNavigationSystem},
     pass::DrawUi,
     prefab::{
-        NoCustomUi, ToNativeWidget, UiCreator, UiFormat, UiImagePrefab, UiLoader, UiLoaderSystem,
-        UiPrefab, UiTextBuilder, UiTransformBuilder, UiWidget,
+        NoCustomUi, ToNativeWidget, UiControlPrefab, UiCreator, UiDropdownBuilder, UiFormat,
+        UiImagePrefab, UiLoader, UiLoaderSystem, UiPrefab, UiSliderBuilder, UiTextBuilder,
+        UiToggleBuilder, UiTransformBuilder, UiWidget,
     },
     resize::{ResizeSystem, UiResize},
+    scroll::{ScrollView, ScrollViewSystem},
     selection::{Selectable, Selected, SelectionKeyboardSystem, SelectionMouseSystem},
     selection_order_cache::{CacheSelectionOrderSystem, CachedSelectionOrder},
     sound::{UiPlaySoundAction, UiSoundRetrigger, UiSoundRetriggerSystem, UiSoundSystem},
     text::{LineMode, TextEditing, TextEditingMouseSystem, UiText},
     text_editing::TextEditingInputSystem,
+    theme::{
+        CurrentUiTheme, UiNinePatch, UiStyle, UiStyleName, UiTheme, UiThemeHandle, UiThemeSystem,
+    },
     transform::{UiFinder, UiTransform},
+    tween::{Easing, Tween, UiTweenEvent, UiTweenEventType, UiTweenSystem, UiTweens},
     widgets::{Widget, WidgetId, Widgets},
+    world_attachment::{OffScreen, UiWorldAttachment, UiWorldAttachmentSystem},
 };
 
+#[cfg(feature = "locale")]
+pub use self::localization::{UiLocalizationSystem, UiTextLocalized};
+
 pub(crate) use amethyst_core::ecs::prelude::Entity;
 pub(crate) use paste;
 
 mod bundle;
 mod button;
+mod controls;
+mod drag;
 mod event;
 mod event_retrigger;
+mod flex;
 mod font;
 mod format;
 mod label;
 mod layout;
+#[cfg(feature = "locale")]
+mod localization;
+mod markup;
+mod navigation;
 mod pass;
 mod prefab;
 mod resize;
+mod scroll;
 mod selection;
 mod selection_order_cache;
 mod sound;
 mod text;
 mod text_editing;
+mod theme;
 mod transform;
+mod tween;
 mod widgets;
+mod world_attachment;
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::AssetStorage;
+use amethyst_core::ecs::prelude::{
+    BitSet, Component, ComponentEvent, DenseVecStorage, Entities, FlaggedStorage, Join, Read,
+    ReadStorage, ReaderId, Resources, System, SystemData, WriteStorage,
+};
+use amethyst_locale::{Locale, LocaleArg, LocaleHandle, Localization};
+
+use crate::UiText;
+
+/// Shows a message of the active locale of the `Localization` in the `UiText` of the entity.
+///
+/// The text is updated by the `UiLocalizationSystem` when the key or arguments change and when
+/// another locale is activated. It is left as is while the locale is loading or when it does not
+/// have the message.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiTextLocalized {
+    /// Id of the message.
+    pub key: String,
+    /// Arguments of the message, by name.
+    pub args: HashMap<String, LocaleArg>,
+}
+
+impl UiTextLocalized {
+    /// Shows the message with this id.
+    pub fn new<S: Into<String>>(key: S) -> Self {
+        UiTextLocalized {
+            key: key.into(),
+            args: HashMap::new(),
+        }
+    }
+
+    /// Sets an argument of the message.
+    pub fn with_arg<S: Into<String>, A: Into<LocaleArg>>(mut self, name: S, arg: A) -> Self {
+        self.args.insert(name.into(), arg.into());
+        self
+    }
+}
+
+impl Component for UiTextLocalized {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Sets the `UiText`s of the `UiTextLocalized` elements from the active locale.
+#[derive(Default)]
+pub struct UiLocalizationSystem {
+    localized_reader: Option<ReaderId<ComponentEvent>>,
+    /// The locale of the texts, once loaded.
+    applied: Option<LocaleHandle>,
+    dirty: BitSet,
+}
+
+impl UiLocalizationSystem {
+    /// Creates a new `UiLocalizationSystem`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> System<'a> for UiLocalizationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, UiTextLocalized>,
+        WriteStorage<'a, UiText>,
+        Read<'a, Localization>,
+        Read<'a, AssetStorage<Locale>>,
+    );
+
+    fn run(&mut self, (entities, localized, mut texts, localization, locales): Self::SystemData) {
+        for event in localized
+            .channel()
+            .read(self.localized_reader.as_mut().expect(
+                "`UiLocalizationSystem::setup` was not called before `UiLocalizationSystem::run`",
+            ))
+        {
+            match event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.dirty.add(*id);
+                }
+                ComponentEvent::Removed(_) => {}
+            }
+        }
+
+        let loaded = localization
+            .active_handle()
+            .filter(|handle| locales.get(handle).is_some());
+        if loaded != self.applied.as_ref() {
+            self.applied = loaded.cloned();
+            for (entity, _) in (&*entities, &localized).join() {
+                self.dirty.add(entity.id());
+            }
+        }
+        let locale = match self.applied.as_ref().and_then(|handle| locales.get(handle)) {
+            Some(locale) => locale,
+            // Texts are localized once the locale is loaded.
+            None => return,
+        };
+
+        for (localized, text, _) in (&localized, &mut texts, &self.dirty).join() {
+            if let Some(message) = locale.format(&localized.key, &localized.args) {
+                if text.text != message {
+                    text.text = message;
+                }
+            }
+        }
+        self.dirty.clear();
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.localized_reader = Some(WriteStorage::<UiTextLocalized>::fetch(res).register_reader());
+    }
+}
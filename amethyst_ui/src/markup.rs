@@ -0,0 +1,196 @@
+//! Markup of rich `UiText`s.
+//!
+//! Tags are written between square brackets and nest:
+//!
+//! - `[b]bold[/b]` and `[i]italic[/i]`, drawn with the `bold_font` and `italic_font` of the text,
+//! - `[color=#ff8000]orange[/color]`, with an optional alpha as in `#ff800080`,
+//! - `[icon=coin]` draws the image registered under that name in the `UiIcons`,
+//! - `[[` is a literal `[`.
+//!
+//! Anything else between brackets is shown as written.
+
+use std::collections::HashMap;
+
+use amethyst_renderer::TextureHandle;
+
+/// Text drawn in place of an icon, giving it the width of a wide letter.
+pub(crate) const ICON_PLACEHOLDER: &str = "M";
+
+/// Images shown inline in the rich texts, by name.
+#[derive(Clone, Debug, Default)]
+pub struct UiIcons {
+    icons: HashMap<String, TextureHandle>,
+}
+
+impl UiIcons {
+    /// Registers an image shown by the `[icon=name]` tag.
+    pub fn insert<S: Into<String>>(&mut self, name: S, image: TextureHandle) {
+        self.icons.insert(name.into(), image);
+    }
+
+    /// Returns the image registered with this name.
+    pub fn get(&self, name: &str) -> Option<&TextureHandle> {
+        self.icons.get(name)
+    }
+}
+
+/// A run of text sharing the same style, or an inline icon.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Span {
+    /// Text of the span, the `ICON_PLACEHOLDER` for icons.
+    pub text: String,
+    pub color: [f32; 4],
+    pub bold: bool,
+    pub italic: bool,
+    /// Name of the icon shown by this span.
+    pub icon: Option<String>,
+}
+
+/// Splits a marked up text into spans, with `color` as the color of untagged text.
+pub(crate) fn parse(markup: &str, color: [f32; 4]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut colors = vec![color];
+    let (mut bold, mut italic) = (0usize, 0usize);
+    let mut text = String::new();
+    let mut rest = markup;
+
+    while let Some(open) = rest.find('[') {
+        text.push_str(&rest[..open]);
+        rest = &rest[open..];
+        if rest.starts_with("[[") {
+            text.push('[');
+            rest = &rest[2..];
+            continue;
+        }
+        let close = match rest.find(']') {
+            Some(close) => close,
+            None => break,
+        };
+        let tag = &rest[1..close];
+        let current = *colors
+            .last()
+            .expect("Unreachable: The base color is never removed");
+        let flush = |text: &mut String, spans: &mut Vec<Span>| {
+            if !text.is_empty() {
+                spans.push(Span {
+                    text: text.split_off(0),
+                    color: current,
+                    bold: bold > 0,
+                    italic: italic > 0,
+                    icon: None,
+                });
+            }
+        };
+        let known = match tag {
+            "b" | "/b" | "i" | "/i" | "/color" => {
+                flush(&mut text, &mut spans);
+                match tag {
+                    "b" => bold += 1,
+                    "/b" => bold = bold.saturating_sub(1),
+                    "i" => italic += 1,
+                    "/i" => italic = italic.saturating_sub(1),
+                    _ if colors.len() > 1 => {
+                        colors.pop();
+                    }
+                    _ => {}
+                }
+                true
+            }
+            _ if tag.starts_with("color=") => match parse_color(&tag["color=".len()..]) {
+                Some(new_color) => {
+                    flush(&mut text, &mut spans);
+                    colors.push(new_color);
+                    true
+                }
+                None => false,
+            },
+            _ if tag.starts_with("icon=") => {
+                flush(&mut text, &mut spans);
+                spans.push(Span {
+                    text: ICON_PLACEHOLDER.to_string(),
+                    color: [0.0; 4],
+                    bold: false,
+                    italic: false,
+                    icon: Some(tag["icon=".len()..].to_string()),
+                });
+                true
+            }
+            _ => false,
+        };
+        if !known {
+            text.push_str(&rest[..=close]);
+        }
+        rest = &rest[close + 1..];
+    }
+
+    text.push_str(rest);
+    if !text.is_empty() {
+        spans.push(Span {
+            text,
+            color: *colors
+                .last()
+                .expect("Unreachable: The base color is never removed"),
+            bold: bold > 0,
+            italic: italic > 0,
+            icon: None,
+        });
+    }
+    spans
+}
+
+/// Parses a `#rrggbb` or `#rrggbbaa` color.
+fn parse_color(hex: &str) -> Option<[f32; 4]> {
+    if !hex.starts_with('#') || (hex.len() != 7 && hex.len() != 9) {
+        return None;
+    }
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+            .map(|value| f32::from(value) / 255.0)
+    };
+    let alpha = if hex.len() == 9 { channel(7)? } else { 1.0 };
+    Some([channel(1)?, channel(3)?, channel(5)?, alpha])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Span, ICON_PLACEHOLDER};
+
+    fn span(text: &str, color: [f32; 4], bold: bool, italic: bool) -> Span {
+        Span {
+            text: text.to_string(),
+            color,
+            bold,
+            italic,
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn parses_nested_tags() {
+        let white = [1.0; 4];
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let spans = parse(
+            "Get [b]10 [color=#ff0000]gold[i]![/i][/color][/b] [icon=coin] [[x] [y]",
+            white,
+        );
+        assert_eq!(
+            vec![
+                span("Get ", white, false, false),
+                span("10 ", white, true, false),
+                span("gold", red, true, false),
+                span("!", red, true, true),
+                span(" ", white, false, false),
+                Span {
+                    text: ICON_PLACEHOLDER.to_string(),
+                    color: [0.0; 4],
+                    bold: false,
+                    italic: false,
+                    icon: Some("coin".to_string()),
+                },
+                span(" [x] [y]", white, false, false),
+            ],
+            spans
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::{hash::Hash, marker::PhantomData};
+
+use amethyst_core::{
+    ecs::prelude::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
+    shrev::EventChannel,
+};
+use amethyst_input::InputHandler;
+use amethyst_renderer::{Hidden, HiddenPropagate, VirtualKeyCode};
+
+use crate::{
+    CachedSelectionOrder, Selectable, Selected, TextEditing, UiEvent, UiEventType, UiTransform,
+};
+
+/// A direction in which the focus moves between the ui elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NavigationDirection {
+    /// Towards the top of the screen.
+    Up,
+    /// Towards the bottom of the screen.
+    Down,
+    /// Towards the left of the screen.
+    Left,
+    /// Towards the right of the screen.
+    Right,
+}
+
+impl NavigationDirection {
+    fn vector(self) -> (f32, f32) {
+        match self {
+            NavigationDirection::Up => (0.0, 1.0),
+            NavigationDirection::Down => (0.0, -1.0),
+            NavigationDirection::Left => (-1.0, 0.0),
+            NavigationDirection::Right => (1.0, 0.0),
+        }
+    }
+}
+
+/// Input actions moving the focus and activating the focused element, for gamepads or custom
+/// key bindings. The arrow keys, Enter and Space always work.
+#[derive(Clone, Debug)]
+pub struct UiNavigationActions<B> {
+    /// Moves the focus up.
+    pub up: Option<B>,
+    /// Moves the focus down.
+    pub down: Option<B>,
+    /// Moves the focus left.
+    pub left: Option<B>,
+    /// Moves the focus right.
+    pub right: Option<B>,
+    /// Clicks the focused element.
+    pub activate: Option<B>,
+}
+
+impl<B> Default for UiNavigationActions<B> {
+    fn default() -> Self {
+        UiNavigationActions {
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            activate: None,
+        }
+    }
+}
+
+/// Moves the focus to the nearest `Selectable` element in the direction of the pressed arrow key
+/// or navigation action, and sends a `Click` event to the focused element when Enter, Space or
+/// the activate action is pressed, so menus can be used without a mouse.
+///
+/// Like the `SelectionKeyboardSystem`, it sends `Blur` and `Focus` events when the focus moves,
+/// for the elements to highlight themselves. Nothing happens while an editable text is focused,
+/// as it uses these keys.
+///
+/// Generic Types:
+/// - G: Selection Group of the `Selectable`s.
+/// - A, B: The A and B generic parameters of the InputHandler<A,B>.
+pub struct UiNavigationSystem<G, A, B> {
+    was_down: [bool; 5],
+    _marker: PhantomData<(G, A, B)>,
+}
+
+impl<G, A, B> UiNavigationSystem<G, A, B> {
+    /// Creates a new `UiNavigationSystem`.
+    pub fn new() -> Self {
+        UiNavigationSystem {
+            was_down: [false; 5],
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, G, A, B> System<'a> for UiNavigationSystem<G, A, B>
+where
+    G: Send + Sync + PartialEq + 'static,
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, CachedSelectionOrder>,
+        ReadStorage<'a, Selectable<G>>,
+        WriteStorage<'a, Selected>,
+        ReadStorage<'a, TextEditing>,
+        ReadStorage<'a, UiTransform>,
+        ReadStorage<'a, Hidden>,
+        ReadStorage<'a, HiddenPropagate>,
+        Read<'a, InputHandler<A, B>>,
+        Read<'a, UiNavigationActions<B>>,
+        Write<'a, EventChannel<UiEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            cached,
+            selectables,
+            mut selecteds,
+            editing,
+            transforms,
+            hidden,
+            hidden_propagate,
+            input,
+            actions,
+            mut ui_events,
+        ): Self::SystemData,
+    ) {
+        let is_down = |keys: &[VirtualKeyCode], action: &Option<B>| {
+            keys.iter().any(|key| input.key_is_down(*key))
+                || action
+                    .as_ref()
+                    .and_then(|action| input.action_is_down(action))
+                    .unwrap_or(false)
+        };
+        let down = [
+            is_down(&[VirtualKeyCode::Up], &actions.up),
+            is_down(&[VirtualKeyCode::Down], &actions.down),
+            is_down(&[VirtualKeyCode::Left], &actions.left),
+            is_down(&[VirtualKeyCode::Right], &actions.right),
+            is_down(
+                &[
+                    VirtualKeyCode::Return,
+                    VirtualKeyCode::NumpadEnter,
+                    VirtualKeyCode::Space,
+                ],
+                &actions.activate,
+            ),
+        ];
+        let mut pressed = down;
+        for (pressed, was_down) in pressed.iter_mut().zip(&self.was_down) {
+            *pressed &= !was_down;
+        }
+        self.was_down = down;
+
+        let focused = cached
+            .highest_order_selected_index(&selecteds)
+            .and_then(|index| cached.cache.get(index))
+            .map(|&(_, entity)| entity);
+        if let Some(focused) = focused {
+            let consumes_inputs = selectables
+                .get(focused)
+                .map_or(false, |selectable| selectable.consumes_inputs);
+            if consumes_inputs || editing.contains(focused) {
+                return;
+            }
+        }
+
+        if pressed[4] {
+            if let Some(focused) = focused {
+                ui_events.single_write(UiEvent::new(UiEventType::Click, focused));
+            }
+        }
+
+        let direction = [
+            NavigationDirection::Up,
+            NavigationDirection::Down,
+            NavigationDirection::Left,
+            NavigationDirection::Right,
+        ]
+        .iter()
+        .zip(&pressed)
+        .find(|(_, pressed)| **pressed)
+        .map(|(direction, _)| *direction);
+        let direction = match direction {
+            Some(direction) => direction,
+            None => return,
+        };
+
+        let target = match focused.and_then(|focused| transforms.get(focused)) {
+            Some(from) => {
+                let candidates = (
+                    &*entities,
+                    &selectables,
+                    &transforms,
+                    !&hidden,
+                    !&hidden_propagate,
+                )
+                    .join()
+                    .filter(|(entity, _, _, _, _)| Some(*entity) != focused)
+                    .map(|(entity, _, transform, _, _)| {
+                        (entity, (transform.pixel_x, transform.pixel_y))
+                    });
+                nearest_in_direction((from.pixel_x, from.pixel_y), direction, candidates)
+            }
+            // Focuses the first element when nothing is focused yet.
+            None => cached.cache.first().map(|&(_, entity)| entity),
+        };
+
+        if let Some(target) = target {
+            for (entity, _) in (&*entities, &selecteds).join() {
+                ui_events.single_write(UiEvent::new(UiEventType::Blur, entity));
+            }
+            selecteds.clear();
+            selecteds
+                .insert(target, Selected)
+                .expect("unreachable: We are inserting");
+            ui_events.single_write(UiEvent::new(UiEventType::Focus, target));
+        }
+    }
+}
+
+/// Returns the candidate closest to `from` in the direction, favouring the ones aligned with it.
+fn nearest_in_direction<I>(
+    from: (f32, f32),
+    direction: NavigationDirection,
+    candidates: I,
+) -> Option<Entity>
+where
+    I: Iterator<Item = (Entity, (f32, f32))>,
+{
+    let (dx, dy) = direction.vector();
+    candidates
+        .filter_map(|(entity, (x, y))| {
+            let (offset_x, offset_y) = (x - from.0, y - from.1);
+            let along = offset_x * dx + offset_y * dy;
+            if along <= 0.0 {
+                return None;
+            }
+            let across = (offset_x * dy - offset_y * dx).abs();
+            Some((entity, along + 2.0 * across))
+        })
+        .min_by(|(_, score1), (_, score2)| score1.partial_cmp(score2).expect("Unexpected NaN"))
+        .map(|(entity, _)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::prelude::{Builder, World};
+
+    use super::{nearest_in_direction, NavigationDirection};
+
+    #[test]
+    fn navigates_to_aligned_elements() {
+        let mut world = World::new();
+        let entities = (0..4)
+            .map(|_| world.create_entity().build())
+            .collect::<Vec<_>>();
+        // The focused element, one on its right, one far above it and a closer one above but
+        // far to the side.
+        let positions = vec![
+            (100.0, 100.0),
+            (200.0, 100.0),
+            (120.0, 300.0),
+            (250.0, 150.0),
+        ];
+        let candidates = || {
+            entities
+                .iter()
+                .cloned()
+                .zip(positions.iter().cloned())
+                .skip(1)
+        };
+
+        let from = (100.0, 100.0);
+        assert_eq!(
+            Some(entities[1]),
+            nearest_in_direction(from, NavigationDirection::Right, candidates())
+        );
+        assert_eq!(
+            Some(entities[2]),
+            nearest_in_direction(from, NavigationDirection::Up, candidates())
+        );
+        assert_eq!(
+            None,
+            nearest_in_direction(from, NavigationDirection::Left, candidates())
+        );
+    }
+}
@@ -34,7 +34,7 @@ use amethyst_renderer::{
     Shape, Texture, TextureData, TextureHandle, TextureMetadata, VertexFormat,
 };
 
-use super::*;
+use super::{layout::Rect, markup, *};
 
 const VERT_SRC: &[u8] = include_bytes!("shaders/vertex.glsl");
 const FRAG_SRC: &[u8] = include_bytes!("shaders/frag.glsl");
@@ -47,8 +47,13 @@ struct VertexArgs {
     coord: vec2,
     dimension: vec2,
     color: vec4,
+    clip: vec4,
+    tex_rect: vec4,
 }
 
+/// Texture rectangle covering the whole texture, as offset and size.
+const FULL_TEX_RECT: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
 #[derive(Clone, Debug, Default)]
 struct CachedDrawOrder {
     pub cached: BitSet,
@@ -107,6 +112,8 @@ impl<'a> PassData<'a> for DrawUi {
         ReadStorage<'a, HiddenPropagate>,
         ReadStorage<'a, Selected>,
         ReadStorage<'a, Rgba>,
+        ReadStorage<'a, UiNinePatch>,
+        Read<'a, UiIcons>,
     );
 }
 
@@ -154,6 +161,8 @@ impl Pass for DrawUi {
             hidden_prop,
             selecteds,
             rgba,
+            nine_patches,
+            icons,
         ): <Self as PassData<'_>>::Data,
     ) {
         #[cfg(feature = "profiler")]
@@ -227,6 +236,13 @@ impl Pass for DrawUi {
             1. / screen_dimensions.height(),
         ];
 
+        let screen_clip = Rect {
+            x: screen_dimensions.width() / 2.0,
+            y: screen_dimensions.height() / 2.0,
+            width: screen_dimensions.width(),
+            height: screen_dimensions.height(),
+        };
+
         let mesh = self
             .mesh
             .as_ref()
@@ -269,24 +285,39 @@ impl Pass for DrawUi {
                 .get(entity)
                 .expect("Unreachable: Entity is guaranteed to be present based on earlier actions");
             let rgba: [f32; 4] = rgba.get(entity).cloned().unwrap_or(Rgba::WHITE).into();
+            // Children of a `ScrollView` are cut at its edges by the fragment shader.
+            let clip = ui_transform.clip.unwrap_or(screen_clip).edges();
             if let Some(image) = ui_image
                 .get(entity)
                 .and_then(|image| tex_storage.get(&image))
             {
                 #[cfg(feature = "profiler")]
                 profile_scope!("ui_pass_draw_uiimage");
-                let vertex_args = VertexArgs {
-                    invert_window_size: invert_window_size.into(),
-                    // Coordinates are middle centered. It makes it easier to do layouting in most cases.
-                    coord: [ui_transform.pixel_x, ui_transform.pixel_y].into(),
-                    dimension: [ui_transform.pixel_width, ui_transform.pixel_height].into(),
-                    color: rgba.into(),
+                // Coordinates are middle centered. It makes it easier to do layouting in most cases.
+                let center = [ui_transform.pixel_x, ui_transform.pixel_y];
+                let size = [ui_transform.pixel_width, ui_transform.pixel_height];
+                let parts = match nine_patches.get(entity) {
+                    Some(nine_patch) => {
+                        let (width, height) = image.size();
+                        nine_patch.parts(center, size, [width as f32, height as f32])
+                    }
+                    None => vec![(center, size, FULL_TEX_RECT)],
                 };
 
-                effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
                 effect.data.textures.push(image.view().clone());
                 effect.data.samplers.push(image.sampler().clone());
-                effect.draw(mesh.slice(), encoder);
+                for (coord, dimension, tex_rect) in parts {
+                    let vertex_args = VertexArgs {
+                        invert_window_size: invert_window_size.into(),
+                        coord: coord.into(),
+                        dimension: dimension.into(),
+                        color: rgba.into(),
+                        clip: clip.into(),
+                        tex_rect: tex_rect.into(),
+                    };
+                    effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
+                    effect.draw(mesh.slice(), encoder);
+                }
                 effect.data.textures.clear();
                 effect.data.samplers.clear();
             }
@@ -294,20 +325,38 @@ impl Pass for DrawUi {
             if let Some(ui_text) = ui_text.get_mut(entity) {
                 #[cfg(feature = "profiler")]
                 profile_scope!("ui_pass_draw_uitext");
-                // Maintain glyph brushes.
-                if ui_text.brush_id.is_none() || ui_text.font != ui_text.cached_font {
+                // Maintain glyph brushes. Bold and italic fonts are only used once loaded.
+                let loaded = |font: &FontHandle| font_storage.get(font).is_some();
+                let style_fonts = (
+                    ui_text.bold_font.clone().filter(loaded),
+                    ui_text.italic_font.clone().filter(loaded),
+                );
+                if ui_text.brush_id.is_none()
+                    || ui_text.font != ui_text.cached_font
+                    || style_fonts != ui_text.cached_style_fonts
+                {
                     let font = match font_storage.get(&ui_text.font) {
                         Some(font) => font,
                         None => continue,
                     };
+                    let mut fonts = vec![font.0.clone()];
+                    fonts.extend(
+                        style_fonts
+                            .0
+                            .iter()
+                            .chain(style_fonts.1.iter())
+                            .filter_map(|font| font_storage.get(font))
+                            .map(|font| font.0.clone()),
+                    );
 
                     self.glyph_brushes.insert(
                         self.next_brush_cache_id,
-                        GlyphBrushBuilder::using_font(font.0.clone()).build(factory.clone()),
+                        GlyphBrushBuilder::using_fonts(fonts).build(factory.clone()),
                     );
 
                     ui_text.brush_id = Some(self.next_brush_cache_id);
                     ui_text.cached_font = ui_text.font.clone();
+                    ui_text.cached_style_fonts = style_fonts;
                     self.next_brush_cache_id += 1;
                 } else if let Some(brush_id) = ui_text.brush_id {
                     unused_glyph_brushes.remove(&brush_id);
@@ -326,6 +375,18 @@ impl Pass for DrawUi {
                     None
                 };
                 let rendered_string = password_string.as_ref().unwrap_or(&ui_text.text);
+                // Markup is shown as written while editing.
+                let spans = if ui_text.markup && editing.is_none() && !ui_text.password {
+                    Some(markup::parse(&ui_text.text, ui_text.color))
+                } else {
+                    None
+                };
+                let (bold_id, italic_id) = match ui_text.cached_style_fonts {
+                    (Some(_), Some(_)) => (FontId(1), FontId(2)),
+                    (Some(_), None) => (FontId(1), FontId(0)),
+                    (None, Some(_)) => (FontId(0), FontId(1)),
+                    (None, None) => (FontId(0), FontId(0)),
+                };
                 let hidpi = screen_dimensions.hidpi_factor() as f32;
                 let size = ui_text.font_size;
                 let scale = Scale::uniform(size);
@@ -376,13 +437,28 @@ impl Pass for DrawUi {
                             },
                         ]
                     })
-                    .unwrap_or_else(|| {
-                        vec![SectionText {
+                    .unwrap_or_else(|| match spans {
+                        Some(ref spans) => spans
+                            .iter()
+                            .map(|span| SectionText {
+                                text: &span.text,
+                                scale: scale,
+                                color: multiply_colors(span.color, rgba),
+                                font_id: if span.bold {
+                                    bold_id
+                                } else if span.italic {
+                                    italic_id
+                                } else {
+                                    FontId(0)
+                                },
+                            })
+                            .collect(),
+                        None => vec![SectionText {
                             text: rendered_string,
                             scale: scale,
                             color: multiply_colors(ui_text.color, rgba),
                             font_id: FontId(0),
-                        }]
+                        }],
                     });
 
                 let layout = match ui_text.line_mode {
@@ -487,6 +563,8 @@ impl Pass for DrawUi {
                             .into(),
                             dimension: [width, height].into(),
                             color: rgba.into(),
+                            clip: clip.into(),
+                            tex_rect: FULL_TEX_RECT.into(),
                         };
                         effect.update_constant_buffer("VertexArgs", &vertex_args.std140(), encoder);
                         effect.draw(mesh.slice(), encoder);
@@ -494,7 +572,10 @@ impl Pass for DrawUi {
                     effect.data.textures.clear();
                     effect.data.samplers.clear();
                 }
-                // Render text
+                // Render text, unless it doesn't fit in its `ScrollView`.
+                if ui_transform
+                    .clip
+                    .map_or(true, |clip| clip.encloses(&ui_transform.rect()))
                 {
                     #[cfg(feature = "profiler")]
                     profile_scope!("ui_pass_draw_uitext_rendertext");
@@ -511,6 +592,54 @@ impl Pass for DrawUi {
                     ) {
                         error!("Unable to draw text! Error: {:?}", err);
                     }
+
+                    // Render the inline icons of the markup over their placeholders.
+                    let ascent = brush
+                        .fonts()
+                        .get(0)
+                        .expect("Unable to get first font of brush")
+                        .v_metrics(Scale::uniform(ui_text.font_size))
+                        .ascent;
+                    let mut index = 0;
+                    for span in spans.iter().flatten() {
+                        let image = span
+                            .icon
+                            .as_ref()
+                            .and_then(|name| icons.get(name))
+                            .and_then(|image| tex_storage.get(image));
+                        if let (Some(image), Some(glyph)) =
+                            (image, ui_text.cached_glyphs.get(index))
+                        {
+                            let width = glyph.unpositioned().h_metrics().advance_width / hidpi;
+                            let mut pos = glyph.position();
+                            pos.x /= hidpi;
+                            pos.y /= hidpi;
+                            let vertex_args = VertexArgs {
+                                invert_window_size: invert_window_size.into(),
+                                // gfx-glyph uses y down so we need to convert to y up
+                                coord: [
+                                    pos.x + width / 2.0,
+                                    screen_dimensions.height() - pos.y + ascent / 2.0,
+                                ]
+                                .into(),
+                                dimension: [width, ui_text.font_size].into(),
+                                color: rgba.into(),
+                                clip: clip.into(),
+                                tex_rect: FULL_TEX_RECT.into(),
+                            };
+                            effect.data.textures.push(image.view().clone());
+                            effect.data.samplers.push(image.sampler().clone());
+                            effect.update_constant_buffer(
+                                "VertexArgs",
+                                &vertex_args.std140(),
+                                encoder,
+                            );
+                            effect.draw(mesh.slice(), encoder);
+                            effect.data.textures.clear();
+                            effect.data.samplers.clear();
+                        }
+                        index += span.text.chars().count();
+                    }
                 }
                 // Render cursor
                 if selecteds.contains(entity) {
@@ -577,8 +706,13 @@ impl Pass for DrawUi {
                                 y: 0.0,
                             });
                             // gfx-glyph uses y down so we need to convert to y up
-                            pos.y =
-                                screen_dimensions.height() - ui_transform.pixel_y + ascent / 2.0;
+                            pos.y = match (glyph, &ui_text.line_mode) {
+                                // Multi-line text puts the cursor on the line of its glyph.
+                                (Some(glyph), LineMode::Wrap) => glyph.position().y / hidpi,
+                                _ => {
+                                    screen_dimensions.height() - ui_transform.pixel_y + ascent / 2.0
+                                }
+                            };
 
                             let mut x = pos.x;
                             if let Some(glyph) = glyph {
@@ -595,6 +729,8 @@ impl Pass for DrawUi {
                                 coord: [x, screen_dimensions.height() - y + ascent / 2.0].into(),
                                 dimension: [width, height].into(),
                                 color: rgba.into(),
+                                clip: clip.into(),
+                                tex_rect: FULL_TEX_RECT.into(),
                             };
                             effect.update_constant_buffer(
                                 "VertexArgs",
@@ -17,9 +17,10 @@ use amethyst_renderer::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    get_default_font, Anchor, FontAsset, FontFormat, Interactable, LineMode, Selectable, Stretch,
-    TextEditing, UiButton, UiButtonAction, UiButtonActionRetrigger, UiButtonActionType,
-    UiPlaySoundAction, UiSoundRetrigger, UiText, UiTransform, WidgetId, Widgets,
+    get_default_font, Anchor, Draggable, Droppable, FontAsset, FontFormat, Interactable, LineMode,
+    ScrollView, Selectable, Stretch, TextEditing, UiButton, UiButtonAction,
+    UiButtonActionRetrigger, UiButtonActionType, UiDropdown, UiFlex, UiFlexItem, UiPlaySoundAction,
+    UiSlider, UiSoundRetrigger, UiStyleName, UiText, UiToggle, UiTransform, WidgetId, Widgets,
 };
 
 /// Loadable `UiTransform` data.
@@ -69,6 +70,18 @@ pub struct UiTransformBuilder<G> {
     /// this ordering backwards.
     // TODO: Make full prefab for Selectable.
     pub selectable: Option<u32>,
+    /// Lays out the children of this element as a flexbox container.
+    pub flex: Option<UiFlex>,
+    /// Margins and growth of this element in its flexbox container.
+    pub flex_item: Option<UiFlexItem>,
+    /// Makes the children of this element scrollable, hiding them outside of it.
+    pub scroll_view: Option<ScrollView>,
+    /// Name of the style of the `CurrentUiTheme` applied to this element.
+    pub style: Option<String>,
+    /// Lets the mouse drag this element, which should also be `mouse_reactive`.
+    pub draggable: Option<Draggable>,
+    /// Lets dragged elements be dropped on this element.
+    pub droppable: bool,
     #[serde(skip)]
     _phantom: PhantomData<G>,
 }
@@ -127,6 +140,45 @@ impl<G> UiTransformBuilder<G> {
         self.stretch = Some(stretch);
         self
     }
+
+    /// Set flexbox layout of the children
+    pub fn with_flex(mut self, flex: UiFlex) -> Self {
+        self.flex = Some(flex);
+        self
+    }
+
+    /// Set flexbox item settings
+    pub fn with_flex_item(mut self, flex_item: UiFlexItem) -> Self {
+        self.flex_item = Some(flex_item);
+        self
+    }
+
+    /// Set scrolling of the children
+    pub fn with_scroll_view(mut self, scroll_view: ScrollView) -> Self {
+        self.scroll_view = Some(scroll_view);
+        self
+    }
+
+    /// Set the name of the theme style
+    pub fn with_style<S>(mut self, style: S) -> Self
+    where
+        S: ToString,
+    {
+        self.style = Some(style.to_string());
+        self
+    }
+
+    /// Set as draggable with the mouse
+    pub fn with_draggable(mut self, draggable: Draggable) -> Self {
+        self.draggable = Some(draggable);
+        self
+    }
+
+    /// Set as a drop target of dragged elements
+    pub fn droppable(mut self) -> Self {
+        self.droppable = true;
+        self
+    }
 }
 
 impl<'a, G> PrefabData<'a> for UiTransformBuilder<G>
@@ -138,6 +190,12 @@ where
         WriteStorage<'a, Interactable>,
         WriteStorage<'a, HiddenPropagate>,
         WriteStorage<'a, Selectable<G>>,
+        WriteStorage<'a, UiFlex>,
+        WriteStorage<'a, UiFlexItem>,
+        WriteStorage<'a, ScrollView>,
+        WriteStorage<'a, UiStyleName>,
+        WriteStorage<'a, Draggable>,
+        WriteStorage<'a, Droppable>,
     );
     type Result = ();
 
@@ -180,6 +238,30 @@ where
             system_data.3.insert(entity, Selectable::<G>::new(u))?;
         }
 
+        if let Some(ref flex) = self.flex {
+            system_data.4.insert(entity, flex.clone())?;
+        }
+
+        if let Some(flex_item) = self.flex_item {
+            system_data.5.insert(entity, flex_item)?;
+        }
+
+        if let Some(ref scroll_view) = self.scroll_view {
+            system_data.6.insert(entity, scroll_view.clone())?;
+        }
+
+        if let Some(ref style) = self.style {
+            system_data.7.insert(entity, UiStyleName(style.clone()))?;
+        }
+
+        if let Some(ref draggable) = self.draggable {
+            system_data.8.insert(entity, draggable.clone())?;
+        }
+
+        if self.droppable {
+            system_data.9.insert(entity, Droppable)?;
+        }
+
         Ok(())
     }
 }
@@ -213,6 +295,15 @@ where
     /// Optionally make the text editable
     #[serde(default)]
     pub editable: Option<TextEditingPrefab>,
+    /// Should the text be parsed as markup, with styled spans and inline icons?
+    #[serde(default)]
+    pub markup: bool,
+    /// Font of the bold spans of the markup
+    #[serde(default)]
+    pub bold_font: Option<AssetPrefab<FontAsset, F>>,
+    /// Font of the italic spans of the markup
+    #[serde(default)]
+    pub italic_font: Option<AssetPrefab<FontAsset, F>>,
 }
 
 /// Loadable `TextEditing` data
@@ -227,6 +318,8 @@ pub struct TextEditingPrefab {
     pub selected_background_color: [f32; 4],
     /// Use block cursor instead of line cursor
     pub use_block_cursor: bool,
+    /// Max number of lines with `LineMode::Wrap`
+    pub max_lines: Option<usize>,
 }
 
 impl Default for TextEditingPrefab {
@@ -236,6 +329,7 @@ impl Default for TextEditingPrefab {
             selected_text_color: [0., 0., 0., 1.],
             selected_background_color: [1., 1., 1., 1.],
             use_block_cursor: false,
+            max_lines: None,
         }
     }
 }
@@ -266,6 +360,13 @@ where
             .add_to_entity(entity, fonts, &[], &[])?;
         let mut ui_text = UiText::new(font_handle, self.text.clone(), self.color, self.font_size);
         ui_text.password = self.password;
+        ui_text.markup = self.markup;
+        if let Some(ref bold_font) = self.bold_font {
+            ui_text.bold_font = Some(bold_font.add_to_entity(entity, fonts, &[], &[])?);
+        }
+        if let Some(ref italic_font) = self.italic_font {
+            ui_text.italic_font = Some(italic_font.add_to_entity(entity, fonts, &[], &[])?);
+        }
 
         if let Some(ref align) = self.align {
             ui_text.align = align.clone();
@@ -277,15 +378,14 @@ where
 
         texts.insert(entity, ui_text)?;
         if let Some(ref editing) = self.editable {
-            editables.insert(
-                entity,
-                TextEditing::new(
-                    editing.max_length,
-                    editing.selected_text_color,
-                    editing.selected_background_color,
-                    editing.use_block_cursor,
-                ),
-            )?;
+            let mut text_editing = TextEditing::new(
+                editing.max_length,
+                editing.selected_text_color,
+                editing.selected_background_color,
+                editing.use_block_cursor,
+            );
+            text_editing.max_lines = editing.max_lines;
+            editables.insert(entity, text_editing)?;
         }
         Ok(())
     }
@@ -297,12 +397,16 @@ where
     ) -> Result<bool, Error> {
         let (_, _, ref mut fonts) = system_data;
 
-        self.font
+        let bold = self.bold_font.load_sub_assets(progress, fonts)?;
+        let italic = self.italic_font.load_sub_assets(progress, fonts)?;
+        let regular = self
+            .font
             .get_or_insert_with(|| {
                 let (ref loader, _, ref storage) = fonts;
                 AssetPrefab::Handle(get_default_font(loader, storage))
             })
-            .load_sub_assets(progress, fonts)
+            .load_sub_assets(progress, fonts)?;
+        Ok(regular || bold || italic)
     }
 }
 
@@ -555,6 +659,134 @@ where
     }
 }
 
+/// Loadable `UiSlider` data
+///
+/// ### Type parameters:
+///
+/// - `I`: `Format` used for loading `Texture`s
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UiSliderBuilder<I = TextureFormat>
+where
+    I: Format<Texture, Options = TextureMetadata>,
+{
+    /// Value at the left end of the track
+    pub min: f32,
+    /// Value at the right end of the track
+    pub max: f32,
+    /// Initial value
+    pub value: f32,
+    /// Interval between the values the user can pick, or zero for any value
+    #[serde(default)]
+    pub step: f32,
+    /// Image of the track
+    #[serde(default)]
+    pub track_image: Option<TexturePrefab<I>>,
+    /// Image of the handle
+    #[serde(default)]
+    pub handle_image: Option<TexturePrefab<I>>,
+    /// Width of the handle
+    pub handle_width: f32,
+    /// Height of the handle
+    pub handle_height: f32,
+}
+
+/// Loadable `UiToggle` data, for checkboxes and radio buttons
+///
+/// ### Type parameters:
+///
+/// - `I`: `Format` used for loading `Texture`s
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UiToggleBuilder<I = TextureFormat>
+where
+    I: Format<Texture, Options = TextureMetadata>,
+{
+    /// Initial state
+    #[serde(default)]
+    pub checked: bool,
+    /// Radio group, or `None` for a checkbox
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Image of the box
+    #[serde(default)]
+    pub image: Option<TexturePrefab<I>>,
+    /// Image of the check mark, shown when checked
+    #[serde(default)]
+    pub mark_image: Option<TexturePrefab<I>>,
+    /// Space between the edges of the box and the check mark
+    #[serde(default)]
+    pub mark_margin: f32,
+}
+
+/// Loadable `UiDropdown` data
+///
+/// ### Type parameters:
+///
+/// - `I`: `Format` used for loading `Texture`s
+/// - `F`: `Format` used for loading fonts
+#[derive(Deserialize, Serialize, Clone)]
+pub struct UiDropdownBuilder<I = TextureFormat, F = FontFormat>
+where
+    I: Format<Texture, Options = TextureMetadata>,
+    F: Format<FontAsset, Options = ()>,
+{
+    /// Options to choose from
+    pub options: Vec<String>,
+    /// Index of the initially selected option
+    #[serde(default)]
+    pub selected: usize,
+    /// Font size
+    pub font_size: f32,
+    /// Font
+    #[serde(default)]
+    pub font: Option<AssetPrefab<FontAsset, F>>,
+    /// Text color
+    pub text_color: [f32; 4],
+    /// Image of the dropdown and of its options
+    #[serde(default)]
+    pub image: Option<TexturePrefab<I>>,
+}
+
+/// Loadable interactive widget components, see `UiSlider`, `UiToggle` and `UiDropdown`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum UiControlPrefab {
+    /// Slider
+    Slider(UiSlider),
+    /// Checkbox or radio button
+    Toggle(UiToggle),
+    /// Dropdown
+    Dropdown(UiDropdown),
+}
+
+impl<'a> PrefabData<'a> for UiControlPrefab {
+    type SystemData = (
+        WriteStorage<'a, UiSlider>,
+        WriteStorage<'a, UiToggle>,
+        WriteStorage<'a, UiDropdown>,
+    );
+    type Result = ();
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        system_data: &mut Self::SystemData,
+        _: &[Entity],
+        _: &[Entity],
+    ) -> Result<(), Error> {
+        match self {
+            UiControlPrefab::Slider(slider) => {
+                system_data.0.insert(entity, slider.clone())?;
+            }
+            UiControlPrefab::Toggle(toggle) => {
+                system_data.1.insert(entity, toggle.clone())?;
+            }
+            UiControlPrefab::Dropdown(dropdown) => {
+                system_data.2.insert(entity, dropdown.clone())?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Loadable ui components
 ///
 /// ### Type parameters:
@@ -609,6 +841,27 @@ pub enum UiWidget<
         /// Button
         button: UiButtonBuilder<A, I, F, W>,
     },
+    /// Slider widget
+    Slider {
+        /// Spatial information of the track
+        transform: UiTransformBuilder<G>,
+        /// Slider
+        slider: UiSliderBuilder<I>,
+    },
+    /// Checkbox or radio button widget
+    Toggle {
+        /// Spatial information
+        transform: UiTransformBuilder<G>,
+        /// Toggle
+        toggle: UiToggleBuilder<I>,
+    },
+    /// Dropdown widget
+    Dropdown {
+        /// Spatial information
+        transform: UiTransformBuilder<G>,
+        /// Dropdown
+        dropdown: UiDropdownBuilder<I, F>,
+    },
     /// Custom UI widget
     Custom(Box<C>),
 }
@@ -628,6 +881,9 @@ where
             UiWidget::Image { ref transform, .. } => Some(transform),
             UiWidget::Label { ref transform, .. } => Some(transform),
             UiWidget::Button { ref transform, .. } => Some(transform),
+            UiWidget::Slider { ref transform, .. } => Some(transform),
+            UiWidget::Toggle { ref transform, .. } => Some(transform),
+            UiWidget::Dropdown { ref transform, .. } => Some(transform),
             UiWidget::Custom(_) => None,
         }
     }
@@ -647,6 +903,15 @@ where
             UiWidget::Button {
                 ref mut transform, ..
             } => Some(transform),
+            UiWidget::Slider {
+                ref mut transform, ..
+            } => Some(transform),
+            UiWidget::Toggle {
+                ref mut transform, ..
+            } => Some(transform),
+            UiWidget::Dropdown {
+                ref mut transform, ..
+            } => Some(transform),
             UiWidget::Custom(_) => None,
         }
     }
@@ -733,6 +998,7 @@ type UiPrefabData<
     Option<UiImagePrefab<I>>,
     Option<UiTextBuilder<F>>,
     Option<UiButtonBuilder<A, I, F, W>>,
+    Option<UiControlPrefab>,
     D,
 );
 
@@ -809,14 +1075,14 @@ fn walk_ui_tree<A, I, F, C, W>(
             prefab
                 .entity(current_index)
                 .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((Some(transform), Some(image), None, None, custom_data));
+                .set_data((Some(transform), Some(image), None, None, None, custom_data));
         }
 
         UiWidget::Label { transform, text } => {
             prefab
                 .entity(current_index)
                 .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((Some(transform), None, Some(text), None, custom_data));
+                .set_data((Some(transform), None, Some(text), None, None, custom_data));
         }
 
         UiWidget::Container {
@@ -827,7 +1093,7 @@ fn walk_ui_tree<A, I, F, C, W>(
             prefab
                 .entity(current_index)
                 .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
-                .set_data((Some(transform), background, None, None, custom_data));
+                .set_data((Some(transform), background, None, None, None, custom_data));
 
             for child_widget in children {
                 let child_index = prefab.add(Some(current_index), None);
@@ -844,6 +1110,9 @@ fn walk_ui_tree<A, I, F, C, W>(
                 password: false,
                 align: None,
                 line_mode: None,
+                markup: false,
+                bold_font: None,
+                italic_font: None,
                 text: button.text.clone(),
                 font_size: button.font_size,
             };
@@ -858,6 +1127,7 @@ fn walk_ui_tree<A, I, F, C, W>(
                     }),
                     None,
                     Some(button),
+                    None,
                     custom_data,
                 ));
 
@@ -868,10 +1138,118 @@ fn walk_ui_tree<A, I, F, C, W>(
                     None,
                     Some(text),
                     None,
+                    None,
+                    Default::default(),
+                )),
+            );
+        }
+
+        UiWidget::Slider {
+            mut transform,
+            slider,
+        } => {
+            let handle_transform = slider_handle_transform(
+                transform.id.clone(),
+                slider.handle_width,
+                slider.handle_height,
+            );
+            let control =
+                UiSlider::new(slider.min, slider.max, slider.value).with_step(slider.step);
+            transform.mouse_reactive = true;
+
+            prefab
+                .entity(current_index)
+                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
+                .set_data((
+                    Some(transform),
+                    slider.track_image.map(|image| UiImagePrefab { image }),
+                    None,
+                    None,
+                    Some(UiControlPrefab::Slider(control)),
+                    custom_data,
+                ));
+
+            prefab.add(
+                Some(current_index),
+                Some((
+                    Some(handle_transform),
+                    slider.handle_image.map(|image| UiImagePrefab { image }),
+                    None,
+                    None,
+                    None,
+                    Default::default(),
+                )),
+            );
+        }
+
+        UiWidget::Toggle {
+            mut transform,
+            toggle,
+        } => {
+            let mark_transform = toggle_mark_transform(transform.id.clone(), toggle.mark_margin);
+            let control = UiToggle {
+                checked: toggle.checked,
+                group: toggle.group,
+            };
+            transform.mouse_reactive = true;
+
+            prefab
+                .entity(current_index)
+                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
+                .set_data((
+                    Some(transform),
+                    toggle.image.map(|image| UiImagePrefab { image }),
+                    None,
+                    None,
+                    Some(UiControlPrefab::Toggle(control)),
+                    custom_data,
+                ));
+
+            prefab.add(
+                Some(current_index),
+                Some((
+                    Some(mark_transform),
+                    toggle.mark_image.map(|image| UiImagePrefab { image }),
+                    None,
+                    None,
+                    None,
                     Default::default(),
                 )),
             );
         }
+
+        UiWidget::Dropdown {
+            mut transform,
+            dropdown,
+        } => {
+            let control = UiDropdown::new(dropdown.options, dropdown.selected);
+            let text = UiTextBuilder {
+                color: dropdown.text_color,
+                editable: None,
+                font: dropdown.font,
+                password: false,
+                align: None,
+                line_mode: None,
+                markup: false,
+                bold_font: None,
+                italic_font: None,
+                text: control.selected_option().unwrap_or("").to_string(),
+                font_size: dropdown.font_size,
+            };
+            transform.mouse_reactive = true;
+
+            prefab
+                .entity(current_index)
+                .expect("Unreachable: `Prefab` entity should always be set when walking ui tree")
+                .set_data((
+                    Some(transform),
+                    dropdown.image.map(|image| UiImagePrefab { image }),
+                    Some(text),
+                    None,
+                    Some(UiControlPrefab::Dropdown(control)),
+                    custom_data,
+                ));
+        }
     }
 }
 
@@ -1022,3 +1400,27 @@ fn button_text_transform<G>(mut id: String) -> UiTransformBuilder<G> {
         })
         .transparent()
 }
+
+fn slider_handle_transform<G>(mut id: String, width: f32, height: f32) -> UiTransformBuilder<G> {
+    id.push_str("_handle");
+    UiTransformBuilder::default()
+        .with_id(id)
+        .with_position(0., 0., 1.)
+        .with_size(width, height)
+        .with_anchor(Anchor::Middle)
+        .transparent()
+}
+
+fn toggle_mark_transform<G>(mut id: String, margin: f32) -> UiTransformBuilder<G> {
+    id.push_str("_mark");
+    UiTransformBuilder::default()
+        .with_id(id)
+        .with_position(0., 0., 1.)
+        .with_anchor(Anchor::Middle)
+        .with_stretch(Stretch::XY {
+            x_margin: margin,
+            y_margin: margin,
+            keep_aspect_ratio: false,
+        })
+        .transparent()
+}
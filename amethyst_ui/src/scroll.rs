@@ -0,0 +1,302 @@
+//! Scrollable containers clipping their children.
+
+use std::{hash::Hash, marker::PhantomData};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, FlaggedStorage, Join, Read, ReadExpect,
+        ReadStorage, ReaderId, Resources, System, SystemData, WriteStorage,
+    },
+    shrev::EventChannel,
+    ParentHierarchy,
+};
+use amethyst_input::{InputEvent, InputHandler, ScrollDirection};
+use amethyst_renderer::{MouseButton, ScreenDimensions};
+
+use crate::UiTransform;
+
+/// A container showing a scrollable part of its children, clipped to its `UiTransform`.
+///
+/// The children are moved by the scroll offset, and everything outside of the container is
+/// hidden: images are cut at its edges, while texts are only drawn if they fit entirely.
+///
+/// The `ScrollViewSystem` scrolls the container under the mouse pointer with the mouse wheel,
+/// or by dragging it with the left mouse button if `draggable` is set.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScrollView {
+    /// Allows scrolling to the left and right.
+    pub horizontal: bool,
+    /// Allows scrolling up and down.
+    pub vertical: bool,
+    /// Distance scrolled by a step of the mouse wheel, in pixels.
+    pub wheel_step: f32,
+    /// Allows scrolling by dragging the content with the left mouse button.
+    pub draggable: bool,
+    #[serde(skip)]
+    offset: (f32, f32),
+    #[serde(skip)]
+    max_offset: (f32, f32),
+}
+
+impl Default for ScrollView {
+    fn default() -> Self {
+        ScrollView::new(false, true)
+    }
+}
+
+impl ScrollView {
+    /// Creates a draggable container scrolling in the given directions.
+    pub fn new(horizontal: bool, vertical: bool) -> Self {
+        ScrollView {
+            horizontal,
+            vertical,
+            wheel_step: 40.0,
+            draggable: true,
+            offset: (0.0, 0.0),
+            max_offset: (0.0, 0.0),
+        }
+    }
+
+    /// Sets the distance scrolled by a step of the mouse wheel.
+    pub fn with_wheel_step(mut self, wheel_step: f32) -> Self {
+        self.wheel_step = wheel_step;
+        self
+    }
+
+    /// Sets whether the content can be dragged with the left mouse button.
+    pub fn with_draggable(mut self, draggable: bool) -> Self {
+        self.draggable = draggable;
+        self
+    }
+
+    /// Returns how far the content is scrolled to the right and down, in pixels.
+    pub fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    /// Returns the largest offset, where the end of the content reaches the edges of the
+    /// container. Updated by the `ScrollViewSystem`.
+    pub fn max_offset(&self) -> (f32, f32) {
+        self.max_offset
+    }
+
+    /// Returns the offset as a fraction of the largest offset, between 0.0 and 1.0.
+    /// Useful to place scroll bars.
+    pub fn relative_offset(&self) -> (f32, f32) {
+        let relative = |offset: f32, max: f32| if max > 0.0 { offset / max } else { 0.0 };
+        (
+            relative(self.offset.0, self.max_offset.0),
+            relative(self.offset.1, self.max_offset.1),
+        )
+    }
+
+    /// Scrolls to the offset, limited to the size of the content and the allowed directions.
+    pub fn set_offset(&mut self, x: f32, y: f32) {
+        let clamp = |value: f32, allowed: bool, max: f32| {
+            if allowed {
+                value.min(max).max(0.0)
+            } else {
+                0.0
+            }
+        };
+        self.offset = (
+            clamp(x, self.horizontal, self.max_offset.0),
+            clamp(y, self.vertical, self.max_offset.1),
+        );
+    }
+
+    /// Scrolls by the given distance to the right and down.
+    pub fn scroll_by(&mut self, x: f32, y: f32) {
+        let (offset_x, offset_y) = self.offset;
+        self.set_offset(offset_x + x, offset_y + y);
+    }
+
+    fn set_max_offset(&mut self, max_offset: (f32, f32)) {
+        self.max_offset = max_offset;
+        let (x, y) = self.offset;
+        self.set_offset(x, y);
+    }
+}
+
+impl Component for ScrollView {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Scrolls the `ScrollView`s with the mouse wheel and by dragging them, and keeps their offset
+/// within the size of their content.
+///
+/// The generic types A and B represent the A and B generic parameter of the InputHandler<A,B>.
+pub struct ScrollViewSystem<A, B> {
+    input_reader: Option<ReaderId<InputEvent<B>>>,
+    dragging: Option<(Entity, (f32, f32))>,
+    was_down: bool,
+    _marker: PhantomData<A>,
+}
+
+impl<A, B> ScrollViewSystem<A, B> {
+    /// Creates a new `ScrollViewSystem`.
+    pub fn new() -> Self {
+        ScrollViewSystem {
+            input_reader: None,
+            dragging: None,
+            was_down: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, A, B> System<'a> for ScrollViewSystem<A, B>
+where
+    A: Send + Sync + Eq + Hash + Clone + 'static,
+    B: Send + Sync + Eq + Hash + Clone + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, UiTransform>,
+        WriteStorage<'a, ScrollView>,
+        ReadExpect<'a, ParentHierarchy>,
+        Read<'a, InputHandler<A, B>>,
+        Read<'a, EventChannel<InputEvent<B>>>,
+        ReadExpect<'a, ScreenDimensions>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, transforms, mut scroll_views, hierarchy, input, events, screen_dimensions): Self::SystemData,
+    ) {
+        // Only write to the views that change, as any modification moves their children.
+        let mut max_offsets = Vec::new();
+        for (entity, transform, scroll_view) in (&*entities, &transforms, &scroll_views).join() {
+            let max_offset = content_max_offset(
+                transform,
+                scroll_view,
+                hierarchy.children(entity),
+                &transforms,
+            );
+            if max_offset != scroll_view.max_offset {
+                max_offsets.push((entity, max_offset));
+            }
+        }
+        for (entity, max_offset) in max_offsets {
+            if let Some(scroll_view) = scroll_views.get_mut(entity) {
+                scroll_view.set_max_offset(max_offset);
+            }
+        }
+
+        let mouse = input
+            .mouse_position()
+            .map(|(x, y)| (x as f32, screen_dimensions.height() - y as f32));
+        let find_hovered = |draggable_only: bool| {
+            let (x, y) = mouse?;
+            (&*entities, &transforms, &scroll_views)
+                .join()
+                .filter(|(_, transform, scroll_view)| {
+                    (scroll_view.draggable || !draggable_only) && transform.position_inside(x, y)
+                })
+                .max_by(|(_, t1, _), (_, t2, _)| {
+                    t1.global_z
+                        .partial_cmp(&t2.global_z)
+                        .expect("Unexpected NaN")
+                })
+                .map(|(entity, _, _)| entity)
+        };
+        let hovered = find_hovered(false);
+        let hovered_draggable = find_hovered(true);
+
+        let mut scrolled = (0.0, 0.0);
+        for event in events.read(
+            self.input_reader
+                .as_mut()
+                .expect("`ScrollViewSystem::setup` was not called before `ScrollViewSystem::run`"),
+        ) {
+            if let InputEvent::MouseWheelMoved(direction) = *event {
+                match direction {
+                    ScrollDirection::ScrollUp => scrolled.1 -= 1.0,
+                    ScrollDirection::ScrollDown => scrolled.1 += 1.0,
+                    ScrollDirection::ScrollLeft => scrolled.0 -= 1.0,
+                    ScrollDirection::ScrollRight => scrolled.0 += 1.0,
+                }
+            }
+        }
+        if scrolled != (0.0, 0.0) {
+            if let Some(scroll_view) = hovered.and_then(|e| scroll_views.get_mut(e)) {
+                let step = scroll_view.wheel_step;
+                scroll_view.scroll_by(scrolled.0 * step, scrolled.1 * step);
+            }
+        }
+
+        let down = input.mouse_button_is_down(MouseButton::Left);
+        if !down {
+            self.dragging = None;
+        } else if let Some((x, y)) = mouse {
+            if let Some((entity, (last_x, last_y))) = self.dragging {
+                if (x, y) != (last_x, last_y) {
+                    if let Some(scroll_view) = scroll_views.get_mut(entity) {
+                        // The content follows the mouse pointer.
+                        scroll_view.scroll_by(last_x - x, y - last_y);
+                    }
+                }
+                self.dragging = Some((entity, (x, y)));
+            } else if !self.was_down {
+                self.dragging = hovered_draggable.map(|entity| (entity, (x, y)));
+            }
+        }
+        self.was_down = down;
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<B>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Returns the largest offset of the view, for the content made of its children.
+fn content_max_offset(
+    view: &UiTransform,
+    scroll_view: &ScrollView,
+    children: &[Entity],
+    transforms: &ReadStorage<'_, UiTransform>,
+) -> (f32, f32) {
+    let left = view.pixel_x - view.pixel_width / 2.0;
+    let top = view.pixel_y + view.pixel_height / 2.0;
+    let (offset_x, offset_y) = scroll_view.offset;
+    let (width, height) = children
+        .iter()
+        .filter_map(|child| transforms.get(*child))
+        .fold((0.0f32, 0.0f32), |(width, height), child| {
+            // Children are moved by the offset, so it is added back.
+            let right = child.pixel_x + child.pixel_width / 2.0 + offset_x;
+            let bottom = child.pixel_y - child.pixel_height / 2.0 - offset_y;
+            (width.max(right - left), height.max(top - bottom))
+        });
+    (
+        (width - view.pixel_width).max(0.0),
+        (height - view.pixel_height).max(0.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScrollView;
+
+    #[test]
+    fn clamps_offset_to_content() {
+        let mut scroll_view = ScrollView::new(false, true);
+        scroll_view.set_max_offset((50.0, 200.0));
+        scroll_view.scroll_by(30.0, 150.0);
+        assert_eq!((0.0, 150.0), scroll_view.offset());
+        assert_eq!((0.0, 0.75), scroll_view.relative_offset());
+        scroll_view.scroll_by(0.0, 100.0);
+        assert_eq!((0.0, 200.0), scroll_view.offset());
+        scroll_view.set_max_offset((0.0, 120.0));
+        assert_eq!((0.0, 120.0), scroll_view.offset());
+        scroll_view.set_offset(0.0, -10.0);
+        assert_eq!((0.0, 0.0), scroll_view.offset());
+    }
+}
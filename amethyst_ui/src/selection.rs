@@ -125,7 +125,7 @@ where
                     }
                     selecteds.clear();
 
-                    let target = if !modifiers.shift {
+                    let target = if modifiers.shift {
                         // Up
                         if highest > 0 {
                             cached.cache.get(highest - 1).unwrap_or_else(|| cached.cache.last()
@@ -8,6 +8,10 @@ layout (std140) uniform VertexArgs {
     uniform vec2 coord;
     uniform vec2 dimension;
     uniform vec4 color;
+    // Left, bottom, right and top edges of the visible area, in pixels.
+    uniform vec4 clip;
+    // Offset and size of the drawn part of the texture.
+    uniform vec4 tex_rect;
 };
 
 // Square [-1.0,1.0]
@@ -18,6 +22,7 @@ out VertexData {
   vec4 position;
   vec2 tex_coord;
   vec4 color;
+  vec4 clip;
 } vertex;
 
 void main() {
@@ -38,7 +43,8 @@ void main() {
     // Recenter the whole viewport.
     vertex.position += vec4(-1, -1, 0, 0);
 
-    vertex.tex_coord = tex_coord;
+    vertex.tex_coord = tex_rect.xy + tex_coord * tex_rect.zw;
     vertex.color = color;
+    vertex.clip = clip;
     gl_Position = vertex.position;
 }
@@ -4,6 +4,7 @@ use derivative::Derivative;
 use gfx_glyph::{Point, PositionedGlyph};
 use serde::{Deserialize, Serialize};
 use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
 use winit::{ElementState, Event, MouseButton, WindowEvent};
 
 use amethyst_core::{
@@ -46,9 +47,22 @@ pub struct UiText {
     pub line_mode: LineMode,
     /// How to align the text within its `UiTransform`.
     pub align: Anchor,
+    /// If true the text is parsed as markup: `[b]bold[/b]`, `[i]italic[/i]`,
+    /// `[color=#ff8000]colored[/color]` spans and `[icon=name]` images from the `UiIcons`, with
+    /// `[[` for a literal `[`. Not applied while the text is edited.
+    pub markup: bool,
+    /// Font of the bold spans of the markup, the regular font if `None`.
+    #[serde(skip)]
+    pub bold_font: Option<FontHandle>,
+    /// Font of the italic spans of the markup, the regular font if `None`.
+    #[serde(skip)]
+    pub italic_font: Option<FontHandle>,
     /// Cached FontHandle, used to detect changes to the font.
     #[serde(skip)]
     pub(crate) cached_font: FontHandle,
+    /// Cached bold and italic fonts, used to detect changes to them.
+    #[serde(skip)]
+    pub(crate) cached_style_fonts: (Option<FontHandle>, Option<FontHandle>),
     /// Cached glyph positions, used to process mouse highlighting
     #[derivative(Debug = "ignore")]
     #[serde(skip)]
@@ -76,7 +90,11 @@ impl UiText {
             password: false,
             line_mode: LineMode::Single,
             align: Anchor::Middle,
+            markup: false,
+            bold_font: None,
+            italic_font: None,
             cached_font: font,
+            cached_style_fonts: (None, None),
             cached_glyphs: Vec::new(),
             brush_id: None,
         }
@@ -94,6 +112,9 @@ pub struct TextEditing {
     pub cursor_position: isize,
     /// The maximum graphemes permitted in this string.
     pub max_length: usize,
+    /// The maximum number of lines permitted in this string with `LineMode::Wrap`, or `None`
+    /// for no limit.
+    pub max_lines: Option<usize>,
     /// The amount and direction of glyphs highlighted relative to the cursor.
     pub highlight_vector: isize,
     /// The color of the text itself when highlighted.
@@ -110,6 +131,13 @@ pub struct TextEditing {
     /// is greater than or equal to 1.0 / CURSOR_BLINK_RATE it should be reset to 0.  When the
     /// player types it should be reset to 0.
     pub(crate) cursor_blink_timer: f32,
+
+    /// Previous texts and cursor positions, restored by undo.
+    pub(crate) undo_stack: Vec<(String, isize)>,
+    /// Undone texts and cursor positions, restored by redo.
+    pub(crate) redo_stack: Vec<(String, isize)>,
+    /// Cursor position after the last typed text, typing from there is undone with it.
+    pub(crate) typing_end: Option<isize>,
 }
 
 impl TextEditing {
@@ -123,27 +151,45 @@ impl TextEditing {
         TextEditing {
             cursor_position: 0,
             max_length,
+            max_lines: None,
             highlight_vector: 0,
             selected_text_color,
             selected_background_color,
             use_block_cursor,
             cursor_blink_timer: 0.0,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            typing_end: None,
         }
     }
+
+    /// Limits the number of lines of the text with `LineMode::Wrap`.
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
 }
 
 impl Component for TextEditing {
     type Storage = DenseVecStorage<Self>;
 }
 
+/// Maximum time between two clicks selecting a word, in seconds.
+const DOUBLE_CLICK_TIME: f64 = 0.5;
+
 /// This system processes the underlying UI data as needed.
+///
+/// Clicking moves the cursor, dragging or Shift+clicking highlights text and double clicking
+/// highlights a word.
 pub struct TextEditingMouseSystem {
     /// A reader for winit events.
     reader: Option<ReaderId<Event>>,
     /// This is set to true while the left mouse button is pressed.
     left_mouse_button_pressed: bool,
-    /// The screen coordinates of the mouse
+    /// The screen coordinates of the mouse, with y going down like the glyph positions.
     mouse_position: (f32, f32),
+    /// The time and glyph index of the last click, to detect double clicks.
+    last_click: Option<(f64, isize)>,
 }
 
 impl TextEditingMouseSystem {
@@ -153,6 +199,7 @@ impl TextEditingMouseSystem {
             reader: None,
             left_mouse_button_pressed: false,
             mouse_position: (0., 0.),
+            last_click: None,
         }
     }
 }
@@ -206,24 +253,15 @@ impl<'a> System<'a> for TextEditingMouseSystem {
                         ..
                     } => {
                         let hidpi = screen_dimensions.hidpi_factor() as f32;
-                        self.mouse_position = (
-                            position.x as f32 * hidpi,
-                            (screen_dimensions.height() - position.y as f32) * hidpi,
-                        );
+                        self.mouse_position =
+                            (position.x as f32 * hidpi, position.y as f32 * hidpi);
                         if self.left_mouse_button_pressed {
                             let (mouse_x, mouse_y) = self.mouse_position;
-                            text_editing.highlight_vector = closest_glyph_index_to_mouse(
-                                mouse_x,
-                                mouse_y,
-                                text.cached_glyphs.iter(),
-                            ) - text_editing.cursor_position;
-                            // The end of the text, while not a glyph, is still something
-                            // you'll likely want to click your cursor to, so if the cursor is
-                            // near the end of the text, check if we should put it at the end
-                            // of the text.
-                            if should_advance_to_end(mouse_x, text_editing, text) {
-                                text_editing.highlight_vector += 1;
-                            }
+                            let index = glyph_index_at(mouse_x, mouse_y, text);
+                            // The cursor follows the mouse while the other end of the
+                            // highlighted text stays where the drag started.
+                            text_editing.highlight_vector += text_editing.cursor_position - index;
+                            text_editing.cursor_position = index;
                         }
                     }
                     Event::WindowEvent {
@@ -231,6 +269,7 @@ impl<'a> System<'a> for TextEditingMouseSystem {
                             WindowEvent::MouseInput {
                                 button: MouseButton::Left,
                                 state,
+                                modifiers,
                                 ..
                             },
                         ..
@@ -242,20 +281,28 @@ impl<'a> System<'a> for TextEditingMouseSystem {
                                 // If we focused an editable text field be sure to position the cursor
                                 // in it.
                                 let (mouse_x, mouse_y) = self.mouse_position;
-                                text_editing.highlight_vector = 0;
-                                text_editing.cursor_position = closest_glyph_index_to_mouse(
-                                    mouse_x,
-                                    mouse_y,
-                                    text.cached_glyphs.iter(),
-                                );
-
-                                // The end of the text, while not a glyph, is still something
-                                // you'll likely want to click your cursor to, so if the cursor is
-                                // near the end of the text, check if we should put it at the end
-                                // of the text.
-                                if should_advance_to_end(mouse_x, text_editing, text) {
-                                    text_editing.cursor_position += 1;
+                                let index = glyph_index_at(mouse_x, mouse_y, text);
+                                let now = time.absolute_real_time_seconds();
+                                let double_click = self.last_click.map_or(false, |(at, last)| {
+                                    last == index && now - at < DOUBLE_CLICK_TIME
+                                });
+                                if double_click {
+                                    let (start, end) = word_at(&text.text, index);
+                                    text_editing.cursor_position = end;
+                                    text_editing.highlight_vector = start - end;
+                                    self.last_click = None;
+                                } else {
+                                    if modifiers.shift {
+                                        text_editing.highlight_vector +=
+                                            text_editing.cursor_position - index;
+                                    } else {
+                                        text_editing.highlight_vector = 0;
+                                    }
+                                    text_editing.cursor_position = index;
+                                    self.last_click = Some((now, index));
                                 }
+                                text_editing.typing_end = None;
+                                text_editing.cursor_blink_timer = 0.0;
                             }
                             ElementState::Released => {
                                 self.left_mouse_button_pressed = false;
@@ -275,20 +322,35 @@ impl<'a> System<'a> for TextEditingMouseSystem {
     }
 }
 
-fn should_advance_to_end(mouse_x: f32, text_editing: &mut TextEditing, text: &mut UiText) -> bool {
-    let cursor_pos = text_editing.cursor_position + text_editing.highlight_vector;
-    let len = text.cached_glyphs.len() as isize;
-    if cursor_pos + 1 == len {
+/// Returns the index of the glyph under the mouse.
+fn glyph_index_at(mouse_x: f32, mouse_y: f32, text: &UiText) -> isize {
+    let index = closest_glyph_index_to_mouse(mouse_x, mouse_y, text.cached_glyphs.iter());
+    // The end of the text, while not a glyph, is still something you'll likely want to click
+    // your cursor to, so if the mouse is past the middle of the last glyph put it at the end of
+    // the text.
+    if index + 1 == text.cached_glyphs.len() as isize {
         if let Some(last_glyph) = text.cached_glyphs.last() {
             let last_glyph_x = last_glyph.position().x;
             let advance_width = last_glyph.unpositioned().h_metrics().advance_width;
             if mouse_x - last_glyph_x > advance_width / 2.0 {
-                return true;
+                return index + 1;
             }
         }
     }
+    index
+}
 
-    false
+/// Returns the grapheme indices of the start and end of the word at `index`.
+fn word_at(text: &str, index: isize) -> (isize, isize) {
+    let mut start = 0;
+    for word in text.split_word_bounds() {
+        let end = start + word.graphemes(true).count() as isize;
+        if index < end {
+            return (start, end);
+        }
+        start = end;
+    }
+    (start, start)
 }
 
 fn closest_glyph_index_to_mouse<'a, 'b: 'a, I>(mouse_x: f32, mouse_y: f32, i: I) -> isize
@@ -1,4 +1,4 @@
-use std::ops::Range;
+use std::{hash::Hash, marker::PhantomData, mem, ops::Range};
 
 use clipboard::{ClipboardContext, ClipboardProvider};
 use log::error;
@@ -11,38 +11,70 @@ use amethyst_core::{
     ecs::prelude::{Entities, Join, Read, ReadStorage, Resources, System, Write, WriteStorage},
     shrev::{EventChannel, ReaderId},
 };
+use amethyst_input::{InputEvent, InputHandler};
+
+/// Number of changes to an editable text that can be undone.
+const MAX_UNDO: usize = 100;
 
 /// System managing the keyboard inputs for the editable text fields.
 /// ## Features
 /// * Adds and removes text.
 /// * Moves selection cursor.
 /// * Grows and shrinks selected text zone.
+/// * Cuts, copies and pastes with the clipboard.
+/// * Undoes and redoes changes with Ctrl+Z and Ctrl+Y or Ctrl+Shift+Z.
+/// * Moves between lines and adds line breaks with Shift+Enter in `LineMode::Wrap`.
+///
+/// Text input is started on the `InputHandler` while an editable text field is selected, so
+/// text composed with an input method is inserted as it is committed.
 #[derive(Default)]
-pub struct TextEditingInputSystem {
+pub struct TextEditingInputSystem<AX, AC> {
     /// A reader for winit events.
     reader: Option<ReaderId<Event>>,
+    /// A reader for input events.
+    input_reader: Option<ReaderId<InputEvent<AC>>>,
+    phantom: PhantomData<AX>,
 }
 
-impl TextEditingInputSystem {
+impl<AX, AC> TextEditingInputSystem<AX, AC> {
     /// Creates a new instance of this system
     pub fn new() -> Self {
-        Self { reader: None }
+        Self {
+            reader: None,
+            input_reader: None,
+            phantom: PhantomData,
+        }
     }
 }
 
-impl<'a> System<'a> for TextEditingInputSystem {
+impl<'a, AX, AC> System<'a> for TextEditingInputSystem<AX, AC>
+where
+    AX: Hash + Eq + Clone + Send + Sync + 'static,
+    AC: Hash + Eq + Clone + Send + Sync + 'static,
+{
     type SystemData = (
         Entities<'a>,
         WriteStorage<'a, UiText>,
         WriteStorage<'a, TextEditing>,
         ReadStorage<'a, Selected>,
         Read<'a, EventChannel<Event>>,
+        Read<'a, EventChannel<InputEvent<AC>>>,
+        Write<'a, InputHandler<AX, AC>>,
         Write<'a, EventChannel<UiEvent>>,
     );
 
     fn run(
         &mut self,
-        (entities, mut texts, mut editables, selecteds, events, mut edit_events): Self::SystemData,
+        (
+            entities,
+            mut texts,
+            mut editables,
+            selecteds,
+            events,
+            input_events,
+            mut input_handler,
+            mut edit_events,
+        ): Self::SystemData,
     ) {
         for text in (&mut texts).join() {
             if (*text.text).chars().any(is_combining_mark) {
@@ -63,33 +95,6 @@ impl<'a> System<'a> for TextEditingInputSystem {
                     .next()
             {
                 match *event {
-                    Event::WindowEvent {
-                        event: WindowEvent::ReceivedCharacter(input),
-                        ..
-                    } => {
-                        if should_skip_char(input) {
-                            continue;
-                        }
-                        focused_edit.cursor_blink_timer = 0.0;
-                        delete_highlighted(focused_edit, focused_text);
-                        let start_byte = focused_text
-                            .text
-                            .grapheme_indices(true)
-                            .nth(focused_edit.cursor_position as usize)
-                            .map(|i| i.0)
-                            .unwrap_or_else(|| {
-                                // We are either in a 0 length string, or at the end of a string
-                                // This line returns the correct byte index for both.
-                                focused_text.text.len()
-                            });
-                        if focused_text.text.graphemes(true).count() < focused_edit.max_length {
-                            focused_text.text.insert(start_byte, input);
-                            focused_edit.cursor_position += 1;
-
-                            edit_events
-                                .single_write(UiEvent::new(UiEventType::ValueChange, entity));
-                        }
-                    }
                     Event::WindowEvent {
                         event:
                             WindowEvent::KeyboardInput {
@@ -104,53 +109,78 @@ impl<'a> System<'a> for TextEditingInputSystem {
                             },
                         ..
                     } => match v_keycode {
-                        VirtualKeyCode::Home | VirtualKeyCode::Up => {
-                            focused_edit.highlight_vector = if modifiers.shift {
-                                focused_edit.cursor_position
+                        VirtualKeyCode::Home | VirtualKeyCode::Up
+                            if v_keycode == VirtualKeyCode::Home
+                                || focused_text.line_mode == LineMode::Single =>
+                        {
+                            let to = if focused_text.line_mode == LineMode::Wrap
+                                && !ctrl_or_cmd(&modifiers)
+                            {
+                                line_start(&focused_text.text, focused_edit.cursor_position)
                             } else {
                                 0
                             };
-                            focused_edit.cursor_position = 0;
-                            focused_edit.cursor_blink_timer = 0.0;
+                            move_cursor(focused_edit, to, modifiers.shift);
                         }
-                        VirtualKeyCode::End | VirtualKeyCode::Down => {
-                            let glyph_len = focused_text.text.graphemes(true).count() as isize;
-                            focused_edit.highlight_vector = if modifiers.shift {
-                                focused_edit.cursor_position - glyph_len
+                        VirtualKeyCode::End | VirtualKeyCode::Down
+                            if v_keycode == VirtualKeyCode::End
+                                || focused_text.line_mode == LineMode::Single =>
+                        {
+                            let to = if focused_text.line_mode == LineMode::Wrap
+                                && !ctrl_or_cmd(&modifiers)
+                            {
+                                line_end(&focused_text.text, focused_edit.cursor_position)
                             } else {
-                                0
+                                focused_text.text.graphemes(true).count() as isize
                             };
-                            focused_edit.cursor_position = glyph_len;
-                            focused_edit.cursor_blink_timer = 0.0;
+                            move_cursor(focused_edit, to, modifiers.shift);
+                        }
+                        VirtualKeyCode::Up | VirtualKeyCode::Down => {
+                            let to = vertical_move(
+                                &focused_text.text,
+                                focused_edit.cursor_position,
+                                v_keycode == VirtualKeyCode::Up,
+                            );
+                            move_cursor(focused_edit, to, modifiers.shift);
                         }
                         VirtualKeyCode::Back => {
-                            if !delete_highlighted(focused_edit, focused_text)
-                                && focused_edit.cursor_position > 0
-                            {
-                                if let Some((byte, len)) = focused_text
-                                    .text
-                                    .grapheme_indices(true)
-                                    .nth(focused_edit.cursor_position as usize - 1)
-                                    .map(|i| (i.0, i.1.len()))
-                                {
-                                    focused_text.text.drain(byte..(byte + len));
-                                    focused_edit.cursor_position -= 1;
-                                }
+                            let changed =
+                                change_text(focused_edit, focused_text, false, |edit, text| {
+                                    if !delete_highlighted(edit, text) && edit.cursor_position > 0 {
+                                        if let Some((byte, len)) = text
+                                            .text
+                                            .grapheme_indices(true)
+                                            .nth(edit.cursor_position as usize - 1)
+                                            .map(|i| (i.0, i.1.len()))
+                                        {
+                                            text.text.drain(byte..(byte + len));
+                                            edit.cursor_position -= 1;
+                                        }
+                                    }
+                                });
+                            if changed {
+                                edit_events
+                                    .single_write(UiEvent::new(UiEventType::ValueChange, entity));
                             }
                         }
                         VirtualKeyCode::Delete => {
-                            if !delete_highlighted(focused_edit, focused_text) {
-                                if let Some((start_byte, start_glyph_len)) = focused_text
-                                    .text
-                                    .grapheme_indices(true)
-                                    .nth(focused_edit.cursor_position as usize)
-                                    .map(|i| (i.0, i.1.len()))
-                                {
-                                    focused_edit.cursor_blink_timer = 0.0;
-                                    focused_text
-                                        .text
-                                        .drain(start_byte..(start_byte + start_glyph_len));
-                                }
+                            let changed =
+                                change_text(focused_edit, focused_text, false, |edit, text| {
+                                    if !delete_highlighted(edit, text) {
+                                        if let Some((start_byte, start_glyph_len)) = text
+                                            .text
+                                            .grapheme_indices(true)
+                                            .nth(edit.cursor_position as usize)
+                                            .map(|i| (i.0, i.1.len()))
+                                        {
+                                            text.text
+                                                .drain(start_byte..(start_byte + start_glyph_len));
+                                        }
+                                    }
+                                });
+                            if changed {
+                                edit_events
+                                    .single_write(UiEvent::new(UiEventType::ValueChange, entity));
                             }
                         }
                         VirtualKeyCode::Left => {
@@ -223,7 +253,10 @@ impl<'a> System<'a> for TextEditingInputSystem {
                         }
                         VirtualKeyCode::X => {
                             if ctrl_or_cmd(&modifiers) {
-                                let new_clip = extract_highlighted(focused_edit, focused_text);
+                                let mut new_clip = String::new();
+                                change_text(focused_edit, focused_text, false, |edit, text| {
+                                    new_clip = extract_highlighted(edit, text);
+                                });
                                 if !new_clip.is_empty() {
                                     match ClipboardProvider::new().and_then(
                                         |mut ctx: ClipboardContext| ctx.set_contents(new_clip),
@@ -256,30 +289,25 @@ impl<'a> System<'a> for TextEditingInputSystem {
                         }
                         VirtualKeyCode::V => {
                             if ctrl_or_cmd(&modifiers) {
-                                delete_highlighted(focused_edit, focused_text);
-
                                 match ClipboardProvider::new()
                                     .and_then(|mut ctx: ClipboardContext| ctx.get_contents())
                                 {
                                     Ok(contents) => {
-                                        let index = cursor_byte_index(focused_edit, focused_text);
-                                        let empty_space = focused_edit.max_length
-                                            - focused_text.text.graphemes(true).count();
-                                        let contents = contents
-                                            .graphemes(true)
-                                            .take(empty_space)
-                                            .fold(String::new(), |mut init, new| {
-                                                init.push_str(new);
-                                                init
-                                            });
-                                        focused_text.text.insert_str(index, &contents);
-                                        focused_edit.cursor_position +=
-                                            contents.graphemes(true).count() as isize;
-
-                                        edit_events.single_write(UiEvent::new(
-                                            UiEventType::ValueChange,
-                                            entity,
-                                        ));
+                                        let changed = change_text(
+                                            focused_edit,
+                                            focused_text,
+                                            false,
+                                            |edit, text| {
+                                                delete_highlighted(edit, text);
+                                                insert_text(edit, text, &contents);
+                                            },
+                                        );
+                                        if changed {
+                                            edit_events.single_write(UiEvent::new(
+                                                UiEventType::ValueChange,
+                                                entity,
+                                            ));
+                                        }
                                     }
                                     Err(e) => error!(
                                         "Error occured when pasting contents of clipboard: {:?}",
@@ -288,41 +316,37 @@ impl<'a> System<'a> for TextEditingInputSystem {
                                 }
                             }
                         }
-                        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
-                            match focused_text.line_mode {
-                                LineMode::Single => {
+                        VirtualKeyCode::Z | VirtualKeyCode::Y => {
+                            if ctrl_or_cmd(&modifiers) {
+                                let changed = if v_keycode == VirtualKeyCode::Z && !modifiers.shift
+                                {
+                                    undo(focused_edit, focused_text)
+                                } else {
+                                    redo(focused_edit, focused_text)
+                                };
+                                if changed {
                                     edit_events.single_write(UiEvent::new(
-                                        UiEventType::ValueCommit,
+                                        UiEventType::ValueChange,
                                         entity,
                                     ));
                                 }
-                                LineMode::Wrap => {
-                                    if modifiers.shift {
-                                        if focused_text.text.graphemes(true).count()
-                                            < focused_edit.max_length
-                                        {
-                                            let start_byte = focused_text
-                                                .text
-                                                .grapheme_indices(true)
-                                                .nth(focused_edit.cursor_position as usize)
-                                                .map(|i| i.0)
-                                                .unwrap_or_else(|| focused_text.text.len());
-
-                                            focused_text.text.insert(start_byte, '\n');
-                                            focused_edit.cursor_position += 1;
-
-                                            edit_events.single_write(UiEvent::new(
-                                                UiEventType::ValueChange,
-                                                entity,
-                                            ));
-                                        }
-                                    } else {
-                                        edit_events.single_write(UiEvent::new(
-                                            UiEventType::ValueCommit,
-                                            entity,
-                                        ));
-                                    }
+                            }
+                        }
+                        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                            if focused_text.line_mode == LineMode::Wrap && modifiers.shift {
+                                let changed =
+                                    change_text(focused_edit, focused_text, false, |edit, text| {
+                                        insert_text(edit, text, "\n");
+                                    });
+                                if changed {
+                                    edit_events.single_write(UiEvent::new(
+                                        UiEventType::ValueChange,
+                                        entity,
+                                    ));
                                 }
+                            } else {
+                                edit_events
+                                    .single_write(UiEvent::new(UiEventType::ValueCommit, entity));
                             }
                         }
                         _ => {}
@@ -331,12 +355,196 @@ impl<'a> System<'a> for TextEditingInputSystem {
                 }
             }
         }
+
+        let focused = (&editables, &selecteds).join().next().is_some();
+        if focused != input_handler.is_text_input_active() {
+            if focused {
+                input_handler.start_text_input();
+            } else {
+                input_handler.stop_text_input();
+            }
+        }
+
+        for event in input_events.read(
+            self.input_reader
+                .as_mut()
+                .expect("`UiKeyboardSystem::setup` was not called before `UiKeyboardSystem::run`"),
+        ) {
+            if let InputEvent::TextCommitted(ref input) = *event {
+                if let Some((entity, ref mut focused_text, ref mut focused_edit, _)) =
+                    (&*entities, &mut texts, &mut editables, &selecteds)
+                        .join()
+                        .next()
+                {
+                    if change_text(focused_edit, focused_text, true, |edit, text| {
+                        delete_highlighted(edit, text);
+                        insert_text(edit, text, input);
+                    }) {
+                        edit_events.single_write(UiEvent::new(UiEventType::ValueChange, entity));
+                    }
+                }
+            }
+        }
     }
 
     fn setup(&mut self, res: &mut Resources) {
         use amethyst_core::ecs::prelude::SystemData;
         Self::SystemData::setup(res);
         self.reader = Some(res.fetch_mut::<EventChannel<Event>>().register_reader());
+        self.input_reader = Some(
+            res.fetch_mut::<EventChannel<InputEvent<AC>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Applies a change to the text, saving the previous text so it can be undone.
+/// Returns true if the text changed.
+///
+/// Consecutive typed text is undone at once.
+fn change_text<F>(edit: &mut TextEditing, text: &mut UiText, typing: bool, change: F) -> bool
+where
+    F: FnOnce(&mut TextEditing, &mut UiText),
+{
+    let previous = (text.text.clone(), edit.cursor_position);
+    change(edit, text);
+    if text.text == previous.0 {
+        return false;
+    }
+    if !typing || edit.typing_end != Some(previous.1) {
+        edit.undo_stack.push(previous);
+        if edit.undo_stack.len() > MAX_UNDO {
+            edit.undo_stack.remove(0);
+        }
+    }
+    edit.redo_stack.clear();
+    edit.typing_end = if typing {
+        Some(edit.cursor_position)
+    } else {
+        None
+    };
+    edit.cursor_blink_timer = 0.0;
+    true
+}
+
+/// Restores the text before the last change, returns true if there was one.
+fn undo(edit: &mut TextEditing, text: &mut UiText) -> bool {
+    match edit.undo_stack.pop() {
+        Some((previous, cursor_position)) => {
+            let current = mem::replace(&mut text.text, previous);
+            edit.redo_stack.push((current, edit.cursor_position));
+            restore_cursor(edit, cursor_position);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Applies the last undone change again, returns true if there was one.
+fn redo(edit: &mut TextEditing, text: &mut UiText) -> bool {
+    match edit.redo_stack.pop() {
+        Some((next, cursor_position)) => {
+            let current = mem::replace(&mut text.text, next);
+            edit.undo_stack.push((current, edit.cursor_position));
+            restore_cursor(edit, cursor_position);
+            true
+        }
+        None => false,
+    }
+}
+
+fn restore_cursor(edit: &mut TextEditing, cursor_position: isize) {
+    edit.cursor_position = cursor_position;
+    edit.highlight_vector = 0;
+    edit.typing_end = None;
+    edit.cursor_blink_timer = 0.0;
+}
+
+/// Moves the cursor, growing or shrinking the highlighted text if `select` is true.
+fn move_cursor(edit: &mut TextEditing, to: isize, select: bool) {
+    edit.highlight_vector = if select {
+        edit.cursor_position + edit.highlight_vector - to
+    } else {
+        0
+    };
+    edit.cursor_position = to;
+    edit.cursor_blink_timer = 0.0;
+}
+
+/// Inserts text at the cursor, within the maximum length and number of lines.
+fn insert_text(edit: &mut TextEditing, text: &mut UiText, input: &str) {
+    let mut new_lines = match (&text.line_mode, edit.max_lines) {
+        (LineMode::Single, _) => 0,
+        (LineMode::Wrap, None) => usize::max_value(),
+        (LineMode::Wrap, Some(max_lines)) => {
+            max_lines.saturating_sub(text.text.matches('\n').count() + 1)
+        }
+    };
+    let input = input
+        .replace("\r\n", "\n")
+        .chars()
+        .filter(|c| {
+            if *c == '\n' && new_lines > 0 {
+                new_lines -= 1;
+                true
+            } else {
+                !should_skip_char(*c)
+            }
+        })
+        .collect::<String>();
+    let index = cursor_byte_index(edit, text);
+    let empty_space = edit
+        .max_length
+        .saturating_sub(text.text.graphemes(true).count());
+    let input = input.graphemes(true).take(empty_space).collect::<String>();
+    if input.is_empty() {
+        return;
+    }
+    text.text.insert_str(index, &input);
+    // Combining characters can merge with the grapheme before the cursor, so count the graphemes
+    // instead of adding the inserted ones.
+    edit.cursor_position = text.text[..index + input.len()].graphemes(true).count() as isize;
+}
+
+/// Returns the grapheme index of the start of the line of the cursor.
+fn line_start(text: &str, cursor_position: isize) -> isize {
+    text.graphemes(true)
+        .take(cursor_position as usize)
+        .enumerate()
+        .filter(|&(_, g)| g == "\n" || g == "\r\n")
+        .last()
+        .map_or(0, |(i, _)| i as isize + 1)
+}
+
+/// Returns the grapheme index of the end of the line of the cursor.
+fn line_end(text: &str, cursor_position: isize) -> isize {
+    let graphemes = text.graphemes(true).collect::<Vec<_>>();
+    graphemes
+        .iter()
+        .enumerate()
+        .skip(cursor_position as usize)
+        .find(|&(_, g)| *g == "\n" || *g == "\r\n")
+        .map_or(graphemes.len(), |(i, _)| i) as isize
+}
+
+/// Returns the position of the cursor moved to the line above or below, keeping its column
+/// when possible.
+fn vertical_move(text: &str, cursor_position: isize, up: bool) -> isize {
+    let start = line_start(text, cursor_position);
+    let column = cursor_position - start;
+    if up {
+        if start == 0 {
+            return 0;
+        }
+        let above = line_start(text, start - 1);
+        (above + column).min(start - 1)
+    } else {
+        let end = line_end(text, cursor_position);
+        let len = text.graphemes(true).count() as isize;
+        if end == len {
+            return len;
+        }
+        (end + 1 + column).min(line_end(text, end + 1))
     }
 }
 
@@ -354,7 +562,9 @@ fn read_highlighted<'a>(edit: &TextEditing, text: &'a UiText) -> &'a str {
 /// Removes the highlighted text and returns it in a String.
 fn extract_highlighted(edit: &mut TextEditing, text: &mut UiText) -> String {
     let range = highlighted_bytes(edit, text);
-    edit.cursor_position = range.start as isize;
+    edit.cursor_position = edit
+        .cursor_position
+        .min(edit.cursor_position + edit.highlight_vector);
     edit.highlight_vector = 0;
     text.text.drain(range).collect::<String>()
 }
@@ -363,7 +573,9 @@ fn extract_highlighted(edit: &mut TextEditing, text: &mut UiText) -> String {
 fn delete_highlighted(edit: &mut TextEditing, text: &mut UiText) -> bool {
     if edit.highlight_vector != 0 {
         let range = highlighted_bytes(edit, text);
-        edit.cursor_position = range.start as isize;
+        edit.cursor_position = edit
+            .cursor_position
+            .min(edit.cursor_position + edit.highlight_vector);
         edit.highlight_vector = 0;
         text.text.drain(range);
         return true;
@@ -405,8 +617,8 @@ fn highlighted_bytes(edit: &TextEditing, text: &UiText) -> Range<usize> {
 
 fn should_skip_char(input: char) -> bool {
     // Ignore obsolete control characters, and tab characters we can't render
-    // properly anyways.  Also ignore newline characters since we don't
-    // support multi-line text at the moment.
+    // properly anyways.  Newline characters are only kept by `insert_text` for
+    // multi-line text.
     input < '\u{20}'
     // Ignore delete character too
     || input == '\u{7F}'
@@ -416,3 +628,20 @@ fn should_skip_char(input: char) -> bool {
     || (input >= '\u{F0000}' && input <= '\u{FFFFF}')
     || (input >= '\u{100000}' && input <= '\u{10FFFF}')
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{line_end, line_start, vertical_move};
+
+    #[test]
+    fn moves_between_lines() {
+        let text = "first\nab\nthird";
+        assert_eq!(6, line_start(text, 8));
+        assert_eq!(8, line_end(text, 6));
+        assert_eq!(2, vertical_move(text, 8, true));
+        assert_eq!(8, vertical_move(text, 4, false));
+        assert_eq!(10, vertical_move(text, 7, false));
+        assert_eq!(0, vertical_move(text, 3, true));
+        assert_eq!(14, vertical_move(text, 12, false));
+    }
+}
@@ -0,0 +1,388 @@
+//! Data-driven styling of the ui elements.
+
+use std::{collections::HashMap, path::Path};
+
+use fnv::FnvHashMap;
+use serde::{Deserialize, Serialize};
+
+use amethyst_assets::{Asset, AssetStorage, Handle, Loader, ProcessingState};
+use amethyst_core::{
+    ecs::prelude::{
+        BitSet, Component, ComponentEvent, DenseVecStorage, Entities, Entity, FlaggedStorage, Join,
+        Read, ReadExpect, ReadStorage, ReaderId, Resources, System, SystemData, VecStorage,
+        WriteStorage,
+    },
+    ParentHierarchy,
+};
+use amethyst_error::Error;
+use amethyst_renderer::{Rgba, Texture, TextureFormat, TextureHandle, TextureMetadata};
+
+use crate::{FontAsset, FontHandle, TtfFormat, UiFlex, UiText};
+
+/// Appearance of a class of ui elements, referenced by name from a `UiStyleName`.
+///
+/// Every property left to `None` keeps the value the element was created with. Fonts and
+/// images are paths, loaded by the `UiThemeSystem` the first time they are used.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiStyle {
+    /// Path of the TrueType or OpenType font of the text.
+    pub font: Option<String>,
+    /// Height of a line of text in pixels.
+    pub font_size: Option<f32>,
+    /// Color of the text.
+    pub text_color: Option<[f32; 4]>,
+    /// Path of the image, a png, jpg, bmp or tga file.
+    pub image: Option<String>,
+    /// Color multiplied with the image.
+    pub image_color: Option<[f32; 4]>,
+    /// Borders of the image kept unscaled, see `UiNinePatch`.
+    pub nine_patch: Option<UiNinePatch>,
+    /// Space between the edges of the element and its children, applied to its `UiFlex`.
+    pub padding: Option<f32>,
+}
+
+/// A set of named styles, loaded from a ron file with `RonFormat`:
+///
+/// ```ron
+/// (
+///     styles: {
+///         "button": (
+///             font_size: Some(20.0),
+///             text_color: Some((0.1, 0.1, 0.1, 1.0)),
+///             image: Some("ui/button.png"),
+///             nine_patch: Some((left: 8.0, right: 8.0, top: 8.0, bottom: 8.0)),
+///         ),
+///     },
+/// )
+/// ```
+///
+/// Make it the `CurrentUiTheme` to apply it. Loading it with hot reloading enabled restyles
+/// the ui whenever the file changes.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiTheme {
+    /// Styles by name.
+    pub styles: HashMap<String, UiStyle>,
+}
+
+impl UiTheme {
+    /// Returns the style with this name.
+    pub fn style(&self, name: &str) -> Option<&UiStyle> {
+        self.styles.get(name)
+    }
+}
+
+/// A handle to a `UiTheme` asset.
+pub type UiThemeHandle = Handle<UiTheme>;
+
+impl Asset for UiTheme {
+    const NAME: &'static str = "ui::Theme";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl Into<Result<ProcessingState<UiTheme>, Error>> for UiTheme {
+    fn into(self) -> Result<ProcessingState<UiTheme>, Error> {
+        Ok(ProcessingState::Loaded(self))
+    }
+}
+
+/// The theme styling the ui, if any.
+#[derive(Clone, Debug, Default)]
+pub struct CurrentUiTheme(pub Option<UiThemeHandle>);
+
+/// Name of the style of the theme applied to this element.
+///
+/// The text properties of the style apply to the `UiText` of the element, or to the texts of
+/// its unstyled children when it has none, like the text of a button.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UiStyleName(pub String);
+
+impl Component for UiStyleName {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Draws the image of an element in nine parts, so its borders keep their size while the
+/// middle is stretched to fill the element.
+///
+/// The borders are in pixels of the image, and shrink when the element is too small for them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UiNinePatch {
+    /// Width of the left border.
+    pub left: f32,
+    /// Width of the right border.
+    pub right: f32,
+    /// Height of the top border.
+    pub top: f32,
+    /// Height of the bottom border.
+    pub bottom: f32,
+}
+
+impl UiNinePatch {
+    /// Creates borders of the same size on every side.
+    pub fn uniform(border: f32) -> Self {
+        UiNinePatch {
+            left: border,
+            right: border,
+            top: border,
+            bottom: border,
+        }
+    }
+
+    /// Returns the center, size and texture rectangle (offset and size, in texture coordinates
+    /// starting at the bottom left) of each part, for an element of the given center and size.
+    pub(crate) fn parts(
+        &self,
+        center: [f32; 2],
+        size: [f32; 2],
+        texture_size: [f32; 2],
+    ) -> Vec<([f32; 2], [f32; 2], [f32; 4])> {
+        // Start and length of the three segments along an axis, on screen and in the texture.
+        let segments = |center: f32, size: f32, texture_size: f32, low: f32, high: f32| {
+            let scale = if low + high > size {
+                size / (low + high)
+            } else {
+                1.0
+            };
+            let (low_px, high_px) = (low * scale, high * scale);
+            let start = center - size / 2.0;
+            let (low_tex, high_tex) = (low / texture_size, high / texture_size);
+            [
+                (start, low_px, 0.0, low_tex),
+                (
+                    start + low_px,
+                    size - low_px - high_px,
+                    low_tex,
+                    1.0 - low_tex - high_tex,
+                ),
+                (start + size - high_px, high_px, 1.0 - high_tex, high_tex),
+            ]
+        };
+        let columns = segments(center[0], size[0], texture_size[0], self.left, self.right);
+        let rows = segments(center[1], size[1], texture_size[1], self.bottom, self.top);
+        let mut parts = Vec::with_capacity(9);
+        for &(y, height, v, v_size) in &rows {
+            for &(x, width, u, u_size) in &columns {
+                if width > 0.0 && height > 0.0 {
+                    parts.push((
+                        [x + width / 2.0, y + height / 2.0],
+                        [width, height],
+                        [u, v, u_size, v_size],
+                    ));
+                }
+            }
+        }
+        parts
+    }
+}
+
+impl Component for UiNinePatch {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Applies the styles of the `CurrentUiTheme` to the elements with a `UiStyleName`.
+///
+/// Elements are restyled when their style name is set, and all of them when the theme changes,
+/// including when its file is hot reloaded.
+#[derive(Default)]
+pub struct UiThemeSystem {
+    style_reader: Option<ReaderId<ComponentEvent>>,
+    applied: Option<UiTheme>,
+    restyled: BitSet,
+    fonts: FnvHashMap<String, FontHandle>,
+    textures: FnvHashMap<String, TextureHandle>,
+}
+
+impl UiThemeSystem {
+    /// Creates a new `UiThemeSystem`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> System<'a> for UiThemeSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, UiStyleName>,
+        Read<'a, CurrentUiTheme>,
+        Read<'a, AssetStorage<UiTheme>>,
+        ReadExpect<'a, ParentHierarchy>,
+        ReadExpect<'a, Loader>,
+        Read<'a, AssetStorage<FontAsset>>,
+        Read<'a, AssetStorage<Texture>>,
+        WriteStorage<'a, UiText>,
+        WriteStorage<'a, TextureHandle>,
+        WriteStorage<'a, Rgba>,
+        WriteStorage<'a, UiNinePatch>,
+        WriteStorage<'a, UiFlex>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            style_names,
+            current,
+            themes,
+            hierarchy,
+            loader,
+            font_storage,
+            texture_storage,
+            mut texts,
+            mut images,
+            mut colors,
+            mut nine_patches,
+            mut flexes,
+        ): Self::SystemData,
+    ) {
+        self.restyled.clear();
+        for event in style_names.channel().read(
+            self.style_reader
+                .as_mut()
+                .expect("`UiThemeSystem::setup` was not called before `UiThemeSystem::run`"),
+        ) {
+            match event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.restyled.add(*id);
+                }
+                ComponentEvent::Removed(_) => {}
+            }
+        }
+
+        let theme = current.0.as_ref().and_then(|handle| themes.get(handle));
+        if theme != self.applied.as_ref() {
+            self.applied = theme.cloned();
+            for (entity, _) in (&*entities, &style_names).join() {
+                self.restyled.add(entity.id());
+            }
+        }
+        let theme = match self.applied {
+            Some(ref theme) => theme,
+            None => return,
+        };
+        let (fonts, textures) = (&mut self.fonts, &mut self.textures);
+
+        for (entity, style_name, _) in (&*entities, &style_names, &self.restyled).join() {
+            let style = match theme.style(&style_name.0) {
+                Some(style) => style,
+                None => continue,
+            };
+
+            let font = style.font.as_ref().map(|path| {
+                fonts
+                    .entry(path.clone())
+                    .or_insert_with(|| loader.load(path.as_str(), TtfFormat, (), (), &font_storage))
+                    .clone()
+            });
+            let text_entities: Vec<Entity> = if texts.contains(entity) {
+                vec![entity]
+            } else {
+                hierarchy
+                    .children(entity)
+                    .iter()
+                    .filter(|child| !style_names.contains(**child))
+                    .cloned()
+                    .collect()
+            };
+            for text_entity in text_entities {
+                if let Some(text) = texts.get_mut(text_entity) {
+                    if let Some(ref font) = font {
+                        text.font = font.clone();
+                    }
+                    if let Some(font_size) = style.font_size {
+                        text.font_size = font_size;
+                    }
+                    if let Some(text_color) = style.text_color {
+                        text.color = text_color;
+                    }
+                }
+            }
+
+            if let Some(ref path) = style.image {
+                let image = textures
+                    .entry(path.clone())
+                    .or_insert_with(|| {
+                        loader.load(
+                            path.as_str(),
+                            texture_format(path),
+                            TextureMetadata::srgb(),
+                            (),
+                            &texture_storage,
+                        )
+                    })
+                    .clone();
+                images
+                    .insert(entity, image)
+                    .expect("Unreachable: Styled entities are alive");
+            }
+            if let Some([r, g, b, a]) = style.image_color {
+                colors
+                    .insert(entity, Rgba(r, g, b, a))
+                    .expect("Unreachable: Styled entities are alive");
+            }
+            if let Some(nine_patch) = style.nine_patch {
+                nine_patches
+                    .insert(entity, nine_patch)
+                    .expect("Unreachable: Styled entities are alive");
+            }
+            if let (Some(padding), Some(flex)) = (style.padding, flexes.get_mut(entity)) {
+                flex.padding = padding;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.style_reader = Some(WriteStorage::<UiStyleName>::fetch(res).register_reader());
+    }
+}
+
+/// Picks the image format from the extension of the path, defaulting to png.
+fn texture_format(path: &str) -> TextureFormat {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    match extension.as_ref().map(String::as_str) {
+        Some("jpg") | Some("jpeg") => TextureFormat::Jpg,
+        Some("bmp") => TextureFormat::Bmp,
+        Some("tga") => TextureFormat::Tga,
+        _ => TextureFormat::Png,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UiNinePatch;
+
+    #[test]
+    fn nine_patch_keeps_borders() {
+        let patch = UiNinePatch {
+            left: 10.0,
+            right: 10.0,
+            top: 5.0,
+            bottom: 15.0,
+        };
+        let parts = patch.parts([100.0, 50.0], [60.0, 40.0], [40.0, 40.0]);
+        assert_eq!(9, parts.len());
+        assert_eq!(
+            ([75.0, 37.5], [10.0, 15.0], [0.0, 0.0, 0.25, 0.375]),
+            parts[0]
+        );
+        assert_eq!(
+            ([100.0, 55.0], [40.0, 20.0], [0.25, 0.375, 0.5, 0.5]),
+            parts[4]
+        );
+        assert_eq!(
+            ([125.0, 67.5], [10.0, 5.0], [0.75, 0.875, 0.25, 0.125]),
+            parts[8]
+        );
+
+        // Borders shrink to fit, and the empty middle is skipped.
+        let parts = patch.parts([0.0, 0.0], [10.0, 40.0], [40.0, 40.0]);
+        assert_eq!(6, parts.len());
+        assert_eq!([5.0, 15.0], parts[0].1);
+    }
+}
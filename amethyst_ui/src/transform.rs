@@ -7,7 +7,7 @@ use amethyst_core::ecs::prelude::{
 use serde::{Deserialize, Serialize};
 use shred_derive::SystemData;
 
-use super::{Anchor, ScaleMode, Stretch};
+use super::{layout::Rect, Anchor, ScaleMode, Stretch};
 
 /// Utility `SystemData` for finding UI entities based on `UiTransform` id
 #[derive(SystemData)]
@@ -66,6 +66,10 @@ pub struct UiTransform {
     pub(crate) pixel_width: f32,
     /// Height in pixels, used for rendering.  Duplicate of `height` if `scale_mode == ScaleMode::Pixel`.
     pub(crate) pixel_height: f32,
+    /// Area outside of which the element is hidden, set by the `UiTransformSystem` for the
+    /// children of a `ScrollView`.
+    #[serde(skip)]
+    pub(crate) clip: Option<Rect>,
     /// The scale mode indicates if the position is in pixel or is relative (%) (WIP!) to the parent's size.
     pub scale_mode: ScaleMode,
     /// Indicates if actions on the ui can go through this element.
@@ -104,6 +108,7 @@ impl UiTransform {
             global_z: z,
             pixel_width: width,
             pixel_height: height,
+            clip: None,
             scale_mode: ScaleMode::Pixel,
             opaque: true,
             pd: PhantomData,
@@ -119,11 +124,23 @@ impl UiTransform {
     }
 
     /// Checks if the input position is in the UiTransform rectangle.
+    /// Positions hidden by a `ScrollView` are not considered inside.
     pub fn position_inside(&self, x: f32, y: f32) -> bool {
         x > self.pixel_x - self.pixel_width / 2.0
             && y > self.pixel_y - self.pixel_height / 2.0
             && x < self.pixel_x + self.pixel_width / 2.0
             && y < self.pixel_y + self.pixel_height / 2.0
+            && self.clip.map_or(true, |clip| clip.contains(x, y))
+    }
+
+    /// Rectangle covered by the element in pixels, as computed by the `UiTransformSystem`.
+    pub(crate) fn rect(&self) -> Rect {
+        Rect {
+            x: self.pixel_x,
+            y: self.pixel_y,
+            width: self.pixel_width,
+            height: self.pixel_height,
+        }
     }
 
     /// Renders this UI element by evaluating transform as a percentage of the parent size,
@@ -0,0 +1,340 @@
+//! Animation of the position, size and color of ui elements.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, System, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+    timing::Time,
+};
+use amethyst_renderer::Rgba;
+pub use amethyst_utils::tween::Easing;
+
+use crate::UiTransform;
+
+/// A step of a `UiTweens` animation, moving the properties it sets from their value when the
+/// step starts to the given values.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Tween {
+    /// Name sent with the `UiTweenEvent`s of this step.
+    pub name: Option<String>,
+    /// Duration of the animation in seconds.
+    pub duration: f32,
+    /// Wait before the animation starts, in seconds.
+    pub delay: f32,
+    /// Rate of change of the animation.
+    pub easing: Easing,
+    /// Local position to reach.
+    pub position: Option<(f32, f32)>,
+    /// Size to reach.
+    pub size: Option<(f32, f32)>,
+    /// Color of the `Rgba` component to reach.
+    pub color: Option<[f32; 4]>,
+    /// Alpha of the `Rgba` component to reach, applied after `color`.
+    pub alpha: Option<f32>,
+}
+
+impl Tween {
+    /// Creates a step lasting `duration` seconds and animating nothing yet.
+    pub fn new(duration: f32, easing: Easing) -> Self {
+        Tween {
+            duration,
+            easing,
+            ..Default::default()
+        }
+    }
+
+    /// Names the step in its events.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Waits before animating.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Moves the element to a local position.
+    pub fn to_position(mut self, x: f32, y: f32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Resizes the element.
+    pub fn to_size(mut self, width: f32, height: f32) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// Changes the color of the element.
+    pub fn to_color(mut self, color: [f32; 4]) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Fades the element.
+    pub fn to_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+}
+
+/// What happened to a step of a `UiTweens` animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiTweenEventType {
+    /// The step started, after its delay.
+    Started,
+    /// The step reached its end values.
+    Finished,
+}
+
+/// Sent when a step of a `UiTweens` animation starts or finishes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiTweenEvent {
+    /// The animated entity.
+    pub target: Entity,
+    /// Name of the step.
+    pub name: Option<String>,
+    /// What happened.
+    pub event_type: UiTweenEventType,
+}
+
+/// Values animated by the tweens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TweenValues {
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+    pub color: [f32; 4],
+}
+
+/// A sequence of `Tween`s animating the `UiTransform` and `Rgba` of an entity, played one
+/// after the other by the `UiTweenSystem`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UiTweens {
+    steps: VecDeque<Tween>,
+    /// Plays the steps again once they are all finished.
+    pub looping: bool,
+    elapsed: f32,
+    start: Option<TweenValues>,
+}
+
+impl UiTweens {
+    /// Creates an animation made of a single step.
+    pub fn new(tween: Tween) -> Self {
+        UiTweens::default().then(tween)
+    }
+
+    /// Adds a step played after the others.
+    pub fn then(mut self, tween: Tween) -> Self {
+        self.steps.push_back(tween);
+        self
+    }
+
+    /// Plays the steps again once they are all finished.
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Adds a step played after the others.
+    pub fn push(&mut self, tween: Tween) {
+        self.steps.push_back(tween);
+    }
+
+    /// Returns true once all the steps are finished.
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Plays the animation for `delta` seconds, writing the animated values.
+    pub(crate) fn advance(
+        &mut self,
+        mut delta: f32,
+        values: &mut TweenValues,
+        mut event: impl FnMut(Option<String>, UiTweenEventType),
+    ) {
+        // Bounds the steps finished in one frame, so looping zero-length steps can not hang.
+        let mut remaining_steps = self.steps.len() + 1;
+        while remaining_steps > 0 {
+            remaining_steps -= 1;
+            let step = match self.steps.front() {
+                Some(step) => step,
+                None => return,
+            };
+            let was_started = self.elapsed >= step.delay && self.start.is_some();
+            self.elapsed += delta;
+            if self.elapsed < step.delay {
+                return;
+            }
+            if !was_started {
+                self.start = Some(*values);
+                event(step.name.clone(), UiTweenEventType::Started);
+            }
+            let start = self
+                .start
+                .expect("Unreachable: The start values were just set");
+
+            let end = step.delay + step.duration;
+            let progress = if step.duration > 0.0 {
+                ((self.elapsed - step.delay) / step.duration).min(1.0)
+            } else {
+                1.0
+            };
+            let eased = step.easing.apply(progress);
+            let lerp = |from: f32, to: f32| from + (to - from) * eased;
+            if let Some((x, y)) = step.position {
+                values.position = (lerp(start.position.0, x), lerp(start.position.1, y));
+            }
+            if let Some((width, height)) = step.size {
+                values.size = (lerp(start.size.0, width), lerp(start.size.1, height));
+            }
+            if let Some(color) = step.color {
+                for i in 0..4 {
+                    values.color[i] = lerp(start.color[i], color[i]);
+                }
+            }
+            if let Some(alpha) = step.alpha {
+                values.color[3] = lerp(start.color[3], alpha);
+            }
+
+            if self.elapsed < end {
+                return;
+            }
+            // The time left after the end of this step is used by the next one.
+            delta = self.elapsed - end;
+            self.elapsed = 0.0;
+            self.start = None;
+            let step = self
+                .steps
+                .pop_front()
+                .expect("Unreachable: The step was just played");
+            event(step.name.clone(), UiTweenEventType::Finished);
+            if self.looping {
+                self.steps.push_back(step);
+            }
+        }
+    }
+}
+
+impl Component for UiTweens {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Plays the `UiTweens` animations, removing them once finished, and sends `UiTweenEvent`s.
+///
+/// Animations use the real time, so they are not affected by `Time::time_scale`.
+#[derive(Default)]
+pub struct UiTweenSystem;
+
+impl UiTweenSystem {
+    /// Creates a new `UiTweenSystem`.
+    pub fn new() -> Self {
+        UiTweenSystem
+    }
+}
+
+impl<'a> System<'a> for UiTweenSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, UiTweens>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, Rgba>,
+        Read<'a, Time>,
+        Write<'a, EventChannel<UiTweenEvent>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut tweens, mut transforms, mut colors, time, mut events): Self::SystemData,
+    ) {
+        let delta = time.delta_real_seconds();
+        let mut finished = Vec::new();
+        for (entity, tweens, transform) in (&*entities, &mut tweens, &mut transforms).join() {
+            let color = colors.get(entity).cloned().unwrap_or(Rgba::WHITE);
+            let mut values = TweenValues {
+                position: (transform.local_x, transform.local_y),
+                size: (transform.width, transform.height),
+                color: color.into(),
+            };
+            let before = values;
+            tweens.advance(delta, &mut values, |name, event_type| {
+                events.single_write(UiTweenEvent {
+                    target: entity,
+                    name,
+                    event_type,
+                });
+            });
+
+            if values.position != before.position || values.size != before.size {
+                transform.local_x = values.position.0;
+                transform.local_y = values.position.1;
+                transform.width = values.size.0;
+                transform.height = values.size.1;
+            }
+            if values.color != before.color {
+                colors
+                    .insert(entity, Rgba::from(values.color))
+                    .expect("Unreachable: Animated entities are alive");
+            }
+            if tweens.is_finished() {
+                finished.push(entity);
+            }
+        }
+        for entity in finished {
+            tweens.remove(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Easing, Tween, TweenValues, UiTweenEventType, UiTweens};
+
+    #[test]
+    fn plays_steps_in_sequence() {
+        let mut tweens = UiTweens::new(
+            Tween::new(1.0, Easing::Linear)
+                .with_name("slide")
+                .to_position(10.0, 0.0),
+        )
+        .then(
+            Tween::new(0.5, Easing::Linear)
+                .with_delay(0.5)
+                .with_name("fade")
+                .to_alpha(0.0),
+        );
+        let mut values = TweenValues {
+            position: (0.0, 0.0),
+            size: (100.0, 50.0),
+            color: [1.0; 4],
+        };
+        let mut events = Vec::new();
+
+        tweens.advance(0.5, &mut values, |name, event| events.push((name, event)));
+        assert_eq!((5.0, 0.0), values.position);
+        tweens.advance(1.25, &mut values, |name, event| events.push((name, event)));
+        assert_eq!((10.0, 0.0), values.position);
+        assert_eq!(0.5, values.color[3]);
+        tweens.advance(1.0, &mut values, |name, event| events.push((name, event)));
+        assert_eq!([1.0, 1.0, 1.0, 0.0], values.color);
+        assert!(tweens.is_finished());
+
+        let name = |name: &str| Some(name.to_string());
+        assert_eq!(
+            vec![
+                (name("slide"), UiTweenEventType::Started),
+                (name("slide"), UiTweenEventType::Finished),
+                (name("fade"), UiTweenEventType::Started),
+                (name("fade"), UiTweenEventType::Finished),
+            ],
+            events
+        );
+    }
+}
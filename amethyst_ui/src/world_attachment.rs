@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadExpect, ReadStorage, System,
+        WriteStorage,
+    },
+    math::{convert, Matrix4, Point3, Vector3, Vector4},
+    Transform,
+};
+use amethyst_renderer::{ActiveCamera, Camera, Hidden, ScreenDimensions};
+
+use crate::UiTransform;
+
+/// What to do with a `UiWorldAttachment` element when its entity is not on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum OffScreen {
+    /// Keeps following the entity outside of the screen, hidden while it is behind the camera.
+    Follow,
+    /// Hides the element with a `Hidden` component.
+    Hide,
+    /// Keeps the element on the border of the screen, in the direction of the entity, at least
+    /// `margin` pixels away from the edges.
+    Clamp {
+        /// Distance from the edges of the screen, in pixels.
+        margin: f32,
+    },
+}
+
+impl Default for OffScreen {
+    fn default() -> Self {
+        OffScreen::Hide
+    }
+}
+
+/// Places the ui element over an entity of the world, as seen by the active camera, such as a
+/// health bar over a character.
+///
+/// The element must be a root element anchored to the bottom left of the screen: its local
+/// position is set in pixels by the `UiWorldAttachmentSystem` each frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiWorldAttachment {
+    /// The entity followed, which must have a `Transform`.
+    pub entity: Entity,
+    /// Offset from the position of the entity, in world units.
+    pub offset: Vector3<f32>,
+    /// What to do with the element when the entity is not on the screen.
+    pub off_screen: OffScreen,
+}
+
+impl UiWorldAttachment {
+    /// Follows the entity, with an offset in world units, and hides the element off screen.
+    pub fn new(entity: Entity, offset: Vector3<f32>) -> Self {
+        UiWorldAttachment {
+            entity,
+            offset,
+            off_screen: OffScreen::default(),
+        }
+    }
+
+    /// Sets what to do with the element when the entity is not on the screen.
+    pub fn with_off_screen(mut self, off_screen: OffScreen) -> Self {
+        self.off_screen = off_screen;
+        self
+    }
+}
+
+impl Component for UiWorldAttachment {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Moves the `UiWorldAttachment` elements over the entities they follow.
+///
+/// Uses the `ActiveCamera`, or the first camera when there is none, like the renderer.
+#[derive(Default)]
+pub struct UiWorldAttachmentSystem;
+
+impl UiWorldAttachmentSystem {
+    /// Creates a new `UiWorldAttachmentSystem`.
+    pub fn new() -> Self {
+        UiWorldAttachmentSystem
+    }
+}
+
+impl<'a> System<'a> for UiWorldAttachmentSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, UiWorldAttachment>,
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, Camera>,
+        Read<'a, ActiveCamera>,
+        ReadExpect<'a, ScreenDimensions>,
+        WriteStorage<'a, UiTransform>,
+        WriteStorage<'a, Hidden>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            attachments,
+            transforms,
+            cameras,
+            active,
+            screen_dimensions,
+            mut ui_transforms,
+            mut hidden,
+        ): Self::SystemData,
+    ) {
+        let camera = active
+            .entity
+            .and_then(|entity| Some((cameras.get(entity)?, transforms.get(entity)?)))
+            .or_else(|| (&cameras, &transforms).join().next());
+        let view_proj = match camera {
+            Some((camera, transform)) => {
+                let view = transform
+                    .global_matrix()
+                    .try_inverse()
+                    .expect("Unable to get inverse of camera transform");
+                camera.proj * convert::<_, Matrix4<f32>>(view)
+            }
+            None => return,
+        };
+        let screen = (screen_dimensions.width(), screen_dimensions.height());
+
+        for (entity, attachment, ui_transform) in
+            (&*entities, &attachments, &mut ui_transforms).join()
+        {
+            let world = match transforms.get(attachment.entity) {
+                Some(transform) => {
+                    let global: Matrix4<f32> = convert(*transform.global_matrix());
+                    global.transform_point(&Point3::origin()) + attachment.offset
+                }
+                None => continue,
+            };
+            let (x, y, visible) = match place(&view_proj, world, screen, attachment.off_screen) {
+                Some(position) => (position.0, position.1, true),
+                None => (ui_transform.local_x, ui_transform.local_y, false),
+            };
+
+            if ui_transform.local_x != x || ui_transform.local_y != y {
+                ui_transform.local_x = x;
+                ui_transform.local_y = y;
+            }
+            if visible && hidden.contains(entity) {
+                hidden.remove(entity);
+            } else if !visible && !hidden.contains(entity) {
+                hidden
+                    .insert(entity, Hidden)
+                    .expect("Unreachable: Attached elements are alive");
+            }
+        }
+    }
+}
+
+/// Returns the position in pixels from the bottom left of the screen of an element following
+/// the `world` point, or `None` when it must be hidden.
+fn place(
+    view_proj: &Matrix4<f32>,
+    world: Point3<f32>,
+    (width, height): (f32, f32),
+    off_screen: OffScreen,
+) -> Option<(f32, f32)> {
+    let clip = view_proj * Vector4::new(world.x, world.y, world.z, 1.0);
+    let in_front = clip.w > 0.0;
+    let (mut x, mut y) = if clip.w.abs() > std::f32::EPSILON {
+        (clip.x / clip.w, clip.y / clip.w)
+    } else {
+        (clip.x, clip.y)
+    };
+    let on_screen = in_front && x.abs() <= 1.0 && y.abs() <= 1.0;
+    match off_screen {
+        OffScreen::Follow if !in_front => return None,
+        OffScreen::Hide if !on_screen => return None,
+        OffScreen::Clamp { .. } if !in_front => {
+            // The projection mirrors the points behind the camera.
+            x = -x;
+            y = -y;
+        }
+        _ => {}
+    }
+
+    let mut position = ((x + 1.0) / 2.0 * width, (y + 1.0) / 2.0 * height);
+    if let OffScreen::Clamp { margin } = off_screen {
+        if !on_screen {
+            // Pushes the element to the border along the line from the center of the screen.
+            let (dx, dy) = (position.0 - width / 2.0, position.1 - height / 2.0);
+            let (half_width, half_height) = (
+                (width / 2.0 - margin).max(0.0),
+                (height / 2.0 - margin).max(0.0),
+            );
+            let scale = (half_width / dx.abs().max(std::f32::EPSILON))
+                .min(half_height / dy.abs().max(std::f32::EPSILON))
+                .min(1.0);
+            position = (width / 2.0 + dx * scale, height / 2.0 + dy * scale);
+        }
+    }
+    Some(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::math::{Matrix4, Point3};
+
+    use super::{place, OffScreen};
+
+    #[test]
+    fn places_points_on_the_screen() {
+        // Maps x and y from -1 to 1 over the screen, looking towards negative z.
+        let view_proj = Matrix4::new_orthographic(-1.0, 1.0, -1.0, 1.0, 0.1, 100.0);
+        let screen = (200.0, 100.0);
+        let point = |x, y| Point3::new(x, y, -1.0);
+
+        assert_eq!(
+            Some((150.0, 25.0)),
+            place(&view_proj, point(0.5, -0.5), screen, OffScreen::Hide)
+        );
+        assert_eq!(
+            None,
+            place(&view_proj, point(2.0, 0.0), screen, OffScreen::Hide)
+        );
+        assert_eq!(
+            Some((300.0, 50.0)),
+            place(&view_proj, point(2.0, 0.0), screen, OffScreen::Follow)
+        );
+        assert_eq!(
+            Some((190.0, 50.0)),
+            place(
+                &view_proj,
+                point(2.0, 0.0),
+                screen,
+                OffScreen::Clamp { margin: 10.0 }
+            )
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! Simple 2D movement colliding with static shapes and tiles, for games which don't need a
+//! physics engine.
+//!
+//! Entities with a `Velocity`, a `Collider` and a `Transform` are moved by the
+//! `KinematicSystem` in the XY plane. They stop against the entities with a `Collider` and no
+//! `Velocity`, and against the solid tiles of the `TileLayer` resource. Moving entities don't
+//! collide with each other.
+
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize};
+
+use amethyst_assets::PrefabData;
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, Write,
+        WriteStorage,
+    },
+    math::Vector2,
+    shrev::EventChannel,
+    timing::Time,
+    Float, Transform,
+};
+use amethyst_derive::PrefabData;
+use amethyst_error::Error;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+/// Velocity of an entity moved by the `KinematicSystem`, in units per second.
+///
+/// The component of the velocity along the normal of a collision is cancelled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, PrefabData)]
+#[prefab(Component)]
+pub struct Velocity(pub Vector2<f32>);
+
+impl Component for Velocity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Shape of an entity in the XY plane, centered on its translation.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, PrefabData)]
+#[prefab(Component)]
+pub enum Collider {
+    /// An axis aligned box, from its half size.
+    Aabb(Vector2<f32>),
+    /// A circle, from its radius.
+    Circle(f32),
+}
+
+impl Collider {
+    /// Half size of the bounding box of the shape.
+    fn half_extents(&self) -> Vector2<f32> {
+        match *self {
+            Collider::Aabb(half_extents) => half_extents,
+            Collider::Circle(radius) => Vector2::new(radius, radius),
+        }
+    }
+
+    /// Range of the position of `self` on `axis` where it overlaps `other`, if it does at its
+    /// position on the other axis.
+    fn overlap(
+        &self,
+        position: Vector2<f32>,
+        other: &Collider,
+        other_position: Vector2<f32>,
+        axis: usize,
+    ) -> Option<(f32, f32)> {
+        let across = 1 - axis;
+        let distance = (position[across] - other_position[across]).abs();
+        let extent = match (*self, *other) {
+            (Collider::Aabb(a), Collider::Aabb(b)) => {
+                if distance >= a[across] + b[across] {
+                    return None;
+                }
+                a[axis] + b[axis]
+            }
+            (Collider::Circle(radius), Collider::Aabb(half_extents))
+            | (Collider::Aabb(half_extents), Collider::Circle(radius)) => {
+                let gap = (distance - half_extents[across]).max(0.0);
+                if gap >= radius {
+                    return None;
+                }
+                half_extents[axis] + (radius * radius - gap * gap).sqrt()
+            }
+            (Collider::Circle(a), Collider::Circle(b)) => {
+                let radius = a + b;
+                if distance >= radius {
+                    return None;
+                }
+                (radius * radius - distance * distance).sqrt()
+            }
+        };
+        Some((other_position[axis] - extent, other_position[axis] + extent))
+    }
+}
+
+impl Component for Collider {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Resource holding a grid of solid tiles, blocking the entities moved by the
+/// `KinematicSystem`.
+///
+/// Tile `(0, 0)` has its lower left corner at the origin, `x` grows to the right and `y` up.
+///
+/// Deserializing a layer fails if it doesn't have one `solid` value per tile.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TileLayer {
+    origin: Vector2<f32>,
+    tile_size: Vector2<f32>,
+    width: u32,
+    height: u32,
+    solid: Vec<bool>,
+}
+
+impl TileLayer {
+    /// Creates a layer of `width` by `height` empty tiles of size `tile_size`, at the origin.
+    pub fn new(width: u32, height: u32, tile_size: Vector2<f32>) -> Self {
+        TileLayer {
+            origin: Vector2::zeros(),
+            tile_size,
+            width,
+            height,
+            solid: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Moves the lower left corner of the layer to `origin`.
+    pub fn with_origin(mut self, origin: Vector2<f32>) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Returns the number of tiles along `x` and `y`.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns `true` if the tile at `(x, y)` is solid, `false` outside of the layer.
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.solid[self.index(x, y)]
+    }
+
+    /// Makes the tile at `(x, y)` solid or empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile is outside of the layer.
+    pub fn set_solid(&mut self, x: u32, y: u32, solid: bool) {
+        assert!(
+            x < self.width && y < self.height,
+            "Tile ({}, {}) is outside of the layer",
+            x,
+            y
+        );
+        let index = self.index(x, y);
+        self.solid[index] = solid;
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Returns the tile containing `point`.
+    pub fn tile_at(&self, point: Vector2<f32>) -> Option<(u32, u32)> {
+        let x = ((point.x - self.origin.x) / self.tile_size.x).floor();
+        let y = ((point.y - self.origin.y) / self.tile_size.y).floor();
+        if x >= 0.0 && y >= 0.0 && x < self.width as f32 && y < self.height as f32 {
+            Some((x as u32, y as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the center of the tile at `(x, y)`.
+    pub fn tile_center(&self, x: u32, y: u32) -> Vector2<f32> {
+        self.origin
+            + Vector2::new(
+                (x as f32 + 0.5) * self.tile_size.x,
+                (y as f32 + 0.5) * self.tile_size.y,
+            )
+    }
+
+    /// Returns the solid tiles overlapping the box from `min` to `max`.
+    fn solid_tiles(&self, min: Vector2<f32>, max: Vector2<f32>) -> Vec<(u32, u32)> {
+        let range = |min: f32, max: f32, origin: f32, size: f32, count: u32| {
+            let first = ((min - origin) / size).floor().max(0.0);
+            let last = ((max - origin) / size).floor().min(count as f32 - 1.0);
+            (first as u32, last)
+        };
+        let (x0, x1) = range(min.x, max.x, self.origin.x, self.tile_size.x, self.width);
+        let (y0, y1) = range(min.y, max.y, self.origin.y, self.tile_size.y, self.height);
+        let mut tiles = Vec::new();
+        let mut y = y0;
+        while y as f32 <= y1 {
+            let mut x = x0;
+            while x as f32 <= x1 {
+                if self.is_solid(x, y) {
+                    tiles.push((x, y));
+                }
+                x += 1;
+            }
+            y += 1;
+        }
+        tiles
+    }
+}
+
+impl<'de> Deserialize<'de> for TileLayer {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "TileLayer")]
+        struct Data {
+            origin: Vector2<f32>,
+            tile_size: Vector2<f32>,
+            width: u32,
+            height: u32,
+            solid: Vec<bool>,
+        }
+
+        let data = Data::deserialize(deserializer)?;
+        let tiles = data.width as usize * data.height as usize;
+        if data.solid.len() != tiles {
+            return Err(D::Error::custom(format!(
+                "Tile layer of {}x{} tiles has {} solid values",
+                data.width,
+                data.height,
+                data.solid.len()
+            )));
+        }
+        Ok(TileLayer {
+            origin: data.origin,
+            tile_size: data.tile_size,
+            width: data.width,
+            height: data.height,
+            solid: data.solid,
+        })
+    }
+}
+
+/// What a moving entity collided with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionTarget {
+    /// An entity with a `Collider` and no `Velocity`.
+    Entity(Entity),
+    /// A solid tile of the `TileLayer`.
+    Tile(u32, u32),
+}
+
+/// Event written by the `KinematicSystem` when a moving entity is stopped.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KinematicCollision {
+    /// The moving entity.
+    pub entity: Entity,
+    /// What it collided with.
+    pub target: CollisionTarget,
+    /// Normal of the contact, pointing towards the moving entity.
+    pub normal: Vector2<f32>,
+}
+
+/// Maximum number of steps an entity is moved in per axis and frame by the `KinematicSystem`.
+pub const MAX_KINEMATIC_STEPS: u32 = 64;
+
+/// Moves the entities with a `Velocity` and a `Collider`, stopping them against the static
+/// colliders and tiles.
+///
+/// Each axis is moved separately, in steps no longer than the half size of the collider so
+/// fast entities don't go through thin obstacles. The number of steps is limited to
+/// `MAX_KINEMATIC_STEPS` per axis and frame, so the steps of very fast entities are longer.
+#[derive(Debug, Default)]
+pub struct KinematicSystem;
+
+impl KinematicSystem {
+    /// Creates a new `KinematicSystem`.
+    pub fn new() -> Self {
+        KinematicSystem
+    }
+}
+
+impl<'a> System<'a> for KinematicSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Collider>,
+        WriteStorage<'a, Velocity>,
+        WriteStorage<'a, Transform>,
+        Read<'a, Time>,
+        Option<Read<'a, TileLayer>>,
+        Write<'a, EventChannel<KinematicCollision>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, colliders, mut velocities, mut transforms, time, tiles, mut events): Self::SystemData,
+    ) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("kinematic_system");
+
+        let delta = time.delta_seconds();
+        if delta <= 0.0 {
+            return;
+        }
+        let statics = (&*entities, &colliders, &transforms, !&velocities)
+            .join()
+            .map(|(entity, collider, transform, _)| (entity, *collider, translation(transform)))
+            .collect::<Vec<_>>();
+
+        let mut moved = Vec::new();
+        for (entity, collider, velocity, transform) in
+            (&*entities, &colliders, &mut velocities, &transforms).join()
+        {
+            if velocity.0 == Vector2::zeros() {
+                continue;
+            }
+            let mut position = translation(transform);
+            for axis in 0..2 {
+                let distance = velocity.0[axis] * delta;
+                if distance == 0.0 {
+                    continue;
+                }
+                let half_extents = collider.half_extents();
+                let max_step = half_extents.x.min(half_extents.y).max(0.01);
+                let steps = (distance.abs() / max_step)
+                    .ceil()
+                    .max(1.0)
+                    .min(MAX_KINEMATIC_STEPS as f32);
+                for _ in 0..steps as u32 {
+                    position[axis] += distance / steps;
+                    let hit = resolve(
+                        collider,
+                        position,
+                        axis,
+                        distance > 0.0,
+                        &statics,
+                        tiles.as_ref().map(|tiles| &**tiles),
+                    );
+                    if let Some((target, resolved)) = hit {
+                        position[axis] = resolved;
+                        velocity.0[axis] = 0.0;
+                        let mut normal = Vector2::zeros();
+                        normal[axis] = if distance > 0.0 { -1.0 } else { 1.0 };
+                        events.single_write(KinematicCollision {
+                            entity,
+                            target,
+                            normal,
+                        });
+                        break;
+                    }
+                }
+            }
+            moved.push((entity, position));
+        }
+
+        for (entity, position) in moved {
+            if let Some(transform) = transforms.get_mut(entity) {
+                transform.set_translation_x(position.x);
+                transform.set_translation_y(position.y);
+            }
+        }
+    }
+}
+
+fn translation(transform: &Transform) -> Vector2<f32> {
+    Vector2::new(
+        Float::as_f32(transform.translation().x),
+        Float::as_f32(transform.translation().y),
+    )
+}
+
+/// Finds the static collider or tile overlapped by `collider` after moving on `axis`, and the
+/// position on `axis` where it touches it.
+fn resolve(
+    collider: &Collider,
+    position: Vector2<f32>,
+    axis: usize,
+    positive: bool,
+    statics: &[(Entity, Collider, Vector2<f32>)],
+    tiles: Option<&TileLayer>,
+) -> Option<(CollisionTarget, f32)> {
+    let mut hit: Option<(CollisionTarget, f32)> = None;
+    let mut check = |target: CollisionTarget, other: &Collider, other_position: Vector2<f32>| {
+        if let Some((min, max)) = collider.overlap(position, other, other_position, axis) {
+            if position[axis] > min && position[axis] < max {
+                let resolved = if positive { min } else { max };
+                let closer = match hit {
+                    Some((_, current)) if positive => resolved < current,
+                    Some((_, current)) => resolved > current,
+                    None => true,
+                };
+                if closer {
+                    hit = Some((target, resolved));
+                }
+            }
+        }
+    };
+
+    for &(entity, ref other, other_position) in statics {
+        check(CollisionTarget::Entity(entity), other, other_position);
+    }
+    if let Some(tiles) = tiles {
+        let half_extents = collider.half_extents();
+        let tile = Collider::Aabb(tiles.tile_size / 2.0);
+        for (x, y) in tiles.solid_tiles(position - half_extents, position + half_extents) {
+            check(CollisionTarget::Tile(x, y), &tile, tiles.tile_center(x, y));
+        }
+    }
+    hit
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, RunNow, System, World},
+        math::Vector2,
+        shrev::EventChannel,
+        timing::Time,
+        Transform,
+    };
+
+    use super::{
+        Collider, CollisionTarget, KinematicCollision, KinematicSystem, TileLayer, Velocity,
+    };
+
+    fn world() -> (World, KinematicSystem) {
+        let mut world = World::new();
+        world.register::<Collider>();
+        world.register::<Velocity>();
+        world.register::<Transform>();
+        let mut system = KinematicSystem::new();
+        System::setup(&mut system, &mut world.res);
+        world.write_resource::<Time>().set_delta_seconds(0.1);
+        (world, system)
+    }
+
+    fn position(world: &World, entity: amethyst_core::ecs::prelude::Entity) -> Vector2<f32> {
+        super::translation(
+            world
+                .read_storage::<Transform>()
+                .get(entity)
+                .expect("Missing transform"),
+        )
+    }
+
+    #[test]
+    fn stops_on_tiles() {
+        let (mut world, mut system) = world();
+        let mut tiles = TileLayer::new(4, 4, Vector2::new(1.0, 1.0));
+        tiles.set_solid(1, 0, true);
+        world.add_resource(tiles);
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(1.5, 3.0, 0.0);
+        let entity = world
+            .create_entity()
+            .with(Collider::Aabb(Vector2::new(0.25, 0.25)))
+            .with(Velocity(Vector2::new(0.0, -100.0)))
+            .with(transform)
+            .build();
+        let mut reader = world
+            .write_resource::<EventChannel<KinematicCollision>>()
+            .register_reader();
+
+        system.run_now(&world.res);
+        assert_eq!(Vector2::new(1.5, 1.25), position(&world, entity));
+        assert_eq!(
+            Vector2::zeros(),
+            world.read_storage::<Velocity>().get(entity).unwrap().0
+        );
+        let events = world
+            .read_resource::<EventChannel<KinematicCollision>>()
+            .read(&mut reader)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(1, events.len());
+        assert_eq!(CollisionTarget::Tile(1, 0), events[0].target);
+        assert_eq!(Vector2::new(0.0, 1.0), events[0].normal);
+    }
+
+    #[test]
+    fn rejects_tile_layers_of_wrong_size() {
+        let layer = TileLayer::new(3, 2, Vector2::new(1.0, 1.0));
+        let ron = ron::ser::to_string(&layer).expect("Failed to serialize the layer");
+        assert_eq!(
+            layer,
+            ron::de::from_str::<TileLayer>(&ron).expect("Failed to deserialize the layer")
+        );
+        let wrong = ron.replace("width:3", "width:4");
+        assert_ne!(ron, wrong);
+        assert!(ron::de::from_str::<TileLayer>(&wrong).is_err());
+    }
+
+    #[test]
+    fn slides_along_static_colliders() {
+        let (mut world, mut system) = world();
+        let mut transform = Transform::default();
+        transform.set_translation_x(2.0);
+        let wall = world
+            .create_entity()
+            .with(Collider::Aabb(Vector2::new(0.5, 5.0)))
+            .with(transform)
+            .build();
+        let entity = world
+            .create_entity()
+            .with(Collider::Circle(0.5))
+            .with(Velocity(Vector2::new(20.0, 1.0)))
+            .with(Transform::default())
+            .build();
+
+        system.run_now(&world.res);
+        let position = position(&world, entity);
+        assert_eq!(1.0, position.x);
+        assert!((position.y - 0.1).abs() < 1e-6);
+        let velocity = world.read_storage::<Velocity>().get(entity).unwrap().0;
+        assert_eq!(Vector2::new(0.0, 1.0), velocity);
+        assert!(world.is_alive(wall));
+    }
+}
@@ -8,9 +8,12 @@ pub mod app_root_dir;
 pub mod auto_fov;
 pub mod circular_buffer;
 pub mod fps_counter;
+pub mod kinematic;
 pub mod ortho_camera;
+pub mod pathfinding;
 pub mod removal;
 pub mod render;
 pub mod scene;
 pub mod tag;
 pub mod time_destroy;
+pub mod tween;
@@ -0,0 +1,443 @@
+//! A* pathfinding on grids of tiles.
+//!
+//! Paths are searched in a `NavGrid`, holding the cost of walking on each tile, which can be
+//! built from the solid tiles of a `TileLayer`. They're found right away with `find_path`, or in
+//! parallel by the `PathfindingSystem` for the entities with a `PathRequest`.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use log::error;
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize};
+
+use amethyst_core::ecs::prelude::{
+    Component, DenseVecStorage, Entities, ParJoin, ParallelIterator, Read, System, WriteStorage,
+};
+
+use crate::kinematic::TileLayer;
+
+#[cfg(feature = "profiler")]
+use thread_profiler::profile_scope;
+
+/// A tile of a grid, from its column and row.
+pub type Tile = (u32, u32);
+
+/// Resource holding the cost of walking on each tile of a grid, or whether it's blocked.
+///
+/// Tiles are numbered like the ones of a `TileLayer`. Diagonal moves cost the average of the
+/// costs of their tiles times `√2`, and can't cut the corner of a blocked tile.
+///
+/// Deserializing a grid fails if it doesn't have one cost per tile.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct NavGrid {
+    width: u32,
+    height: u32,
+    costs: Vec<Option<f32>>,
+    diagonals: bool,
+}
+
+impl NavGrid {
+    /// Creates a grid of `width` by `height` tiles of cost 1, without diagonal moves.
+    pub fn new(width: u32, height: u32) -> Self {
+        NavGrid {
+            width,
+            height,
+            costs: vec![Some(1.0); width as usize * height as usize],
+            diagonals: false,
+        }
+    }
+
+    /// Creates a grid of the size of `layer`, where its solid tiles are blocked.
+    pub fn from_tile_layer(layer: &TileLayer) -> Self {
+        let (width, height) = layer.size();
+        let mut grid = NavGrid::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if layer.is_solid(x, y) {
+                    grid.set_blocked(x, y);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Allows or forbids diagonal moves.
+    pub fn with_diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
+    /// Returns the number of tiles along `x` and `y`.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Returns the cost of walking on a tile, `None` if it's blocked or outside of the grid.
+    pub fn cost(&self, x: u32, y: u32) -> Option<f32> {
+        if x < self.width && y < self.height {
+            self.costs[self.index(x, y)]
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if a tile is blocked or outside of the grid.
+    pub fn is_blocked(&self, x: u32, y: u32) -> bool {
+        self.cost(x, y).is_none()
+    }
+
+    /// Sets the cost of walking on a tile, unblocking it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile is outside of the grid, or the cost isn't strictly positive.
+    pub fn set_cost(&mut self, x: u32, y: u32, cost: f32) {
+        assert!(
+            cost > 0.0,
+            "Tile costs must be strictly positive, got {}",
+            cost
+        );
+        let index = self.checked_index(x, y);
+        self.costs[index] = Some(cost);
+    }
+
+    /// Blocks a tile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tile is outside of the grid.
+    pub fn set_blocked(&mut self, x: u32, y: u32) {
+        let index = self.checked_index(x, y);
+        self.costs[index] = None;
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    fn checked_index(&self, x: u32, y: u32) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "Tile ({}, {}) is outside of the grid",
+            x,
+            y
+        );
+        self.index(x, y)
+    }
+
+    /// Returns the lowest cost of the open tiles, to keep the heuristic admissible.
+    fn min_cost(&self) -> f32 {
+        self.costs
+            .iter()
+            .filter_map(|cost| *cost)
+            .fold(None, |min: Option<f32>, cost| {
+                Some(min.map_or(cost, |min| min.min(cost)))
+            })
+            .unwrap_or(1.0)
+    }
+
+    fn neighbours(&self, (x, y): Tile, out: &mut Vec<(Tile, f32)>) {
+        out.clear();
+        let cost = match self.cost(x, y) {
+            Some(cost) => cost,
+            None => return,
+        };
+        let open = |dx: i64, dy: i64| {
+            let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+            if nx < 0 || ny < 0 {
+                None
+            } else {
+                self.cost(nx as u32, ny as u32)
+                    .map(|next| ((nx as u32, ny as u32), next))
+            }
+        };
+        for &(dx, dy) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some((tile, next)) = open(dx, dy) {
+                out.push((tile, (cost + next) / 2.0));
+            }
+        }
+        if self.diagonals {
+            for &(dx, dy) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                if open(dx, 0).is_none() || open(0, dy).is_none() {
+                    continue;
+                }
+                if let Some((tile, next)) = open(dx, dy) {
+                    out.push((tile, (cost + next) / 2.0 * std::f32::consts::SQRT_2));
+                }
+            }
+        }
+    }
+
+    fn heuristic(&self, (x, y): Tile, (gx, gy): Tile, min_cost: f32) -> f32 {
+        let dx = (x as f32 - gx as f32).abs();
+        let dy = (y as f32 - gy as f32).abs();
+        let distance = if self.diagonals {
+            dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy)
+        } else {
+            dx + dy
+        };
+        distance * min_cost
+    }
+}
+
+impl<'de> Deserialize<'de> for NavGrid {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "NavGrid")]
+        struct Data {
+            width: u32,
+            height: u32,
+            costs: Vec<Option<f32>>,
+            diagonals: bool,
+        }
+
+        let data = Data::deserialize(deserializer)?;
+        let tiles = data.width as usize * data.height as usize;
+        if data.costs.len() != tiles {
+            return Err(D::Error::custom(format!(
+                "Navigation grid of {}x{} tiles has {} costs",
+                data.width,
+                data.height,
+                data.costs.len()
+            )));
+        }
+        Ok(NavGrid {
+            width: data.width,
+            height: data.height,
+            costs: data.costs,
+            diagonals: data.diagonals,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Open {
+    estimate: f32,
+    cost: f32,
+    tile: Tile,
+}
+
+impl Eq for Open {}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, to pop the lowest estimate from the `BinaryHeap`.
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds the cheapest path from `start` to `goal` with A*.
+///
+/// The path starts with `start` and ends with `goal`. Returns `None` if one of them is blocked
+/// or the goal can't be reached.
+pub fn find_path(grid: &NavGrid, start: Tile, goal: Tile) -> Option<Vec<Tile>> {
+    if grid.is_blocked(start.0, start.1) || grid.is_blocked(goal.0, goal.1) {
+        return None;
+    }
+    let min_cost = grid.min_cost();
+    let mut open = BinaryHeap::new();
+    let mut costs = HashMap::new();
+    let mut came_from = HashMap::new();
+    let mut neighbours = Vec::new();
+    costs.insert(start, 0.0);
+    open.push(Open {
+        estimate: grid.heuristic(start, goal, min_cost),
+        cost: 0.0,
+        tile: start,
+    });
+
+    while let Some(Open { cost, tile, .. }) = open.pop() {
+        if tile == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if costs.get(&tile).map_or(false, |&best| cost > best) {
+            continue;
+        }
+        grid.neighbours(tile, &mut neighbours);
+        for &(next, step) in &neighbours {
+            let next_cost = cost + step;
+            if costs.get(&next).map_or(true, |&best| next_cost < best) {
+                costs.insert(next, next_cost);
+                came_from.insert(next, tile);
+                open.push(Open {
+                    estimate: next_cost + grid.heuristic(next, goal, min_cost),
+                    cost: next_cost,
+                    tile: next,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Asks the `PathfindingSystem` for a path, which replaces this component with a `Path`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathRequest {
+    /// The first tile of the path.
+    pub start: Tile,
+    /// The last tile of the path.
+    pub goal: Tile,
+}
+
+impl Component for PathRequest {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Path found by the `PathfindingSystem` for a `PathRequest`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Path {
+    /// The tiles of the path from the start to the goal, empty if no path was found.
+    pub tiles: Vec<Tile>,
+}
+
+impl Path {
+    /// Returns `true` if a path was found.
+    pub fn found(&self) -> bool {
+        !self.tiles.is_empty()
+    }
+}
+
+impl Component for Path {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Answers the `PathRequest`s in the `NavGrid` resource, searching the paths in parallel.
+///
+/// Requests wait while there's no `NavGrid`.
+#[derive(Debug, Default)]
+pub struct PathfindingSystem;
+
+impl PathfindingSystem {
+    /// Creates a new `PathfindingSystem`.
+    pub fn new() -> Self {
+        PathfindingSystem
+    }
+}
+
+impl<'a> System<'a> for PathfindingSystem {
+    type SystemData = (
+        Entities<'a>,
+        Option<Read<'a, NavGrid>>,
+        WriteStorage<'a, PathRequest>,
+        WriteStorage<'a, Path>,
+    );
+
+    fn run(&mut self, (entities, grid, mut requests, mut paths): Self::SystemData) {
+        #[cfg(feature = "profiler")]
+        profile_scope!("pathfinding_system");
+
+        let grid = match grid {
+            Some(grid) => grid,
+            None => return,
+        };
+        let found = (&*entities, &requests)
+            .par_join()
+            .map(|(entity, request)| {
+                let tiles = find_path(&grid, request.start, request.goal).unwrap_or_default();
+                (entity, Path { tiles })
+            })
+            .collect::<Vec<_>>();
+        for (entity, path) in found {
+            requests.remove(entity);
+            if let Err(e) = paths.insert(entity, path) {
+                error!("Failed to insert the path of {:?}: {}", entity, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::{
+        ecs::prelude::{Builder, RunNow, World},
+        math::Vector2,
+    };
+
+    use super::{find_path, NavGrid, Path, PathRequest, PathfindingSystem};
+    use crate::kinematic::TileLayer;
+
+    #[test]
+    fn finds_cheapest_path() {
+        let mut grid = NavGrid::new(3, 3);
+        grid.set_blocked(1, 0);
+        grid.set_cost(1, 1, 10.0);
+        assert_eq!(
+            Some(vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2), (2, 1), (2, 0)]),
+            find_path(&grid, (0, 0), (2, 0))
+        );
+        grid.set_blocked(1, 2);
+        assert_eq!(
+            Some(vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]),
+            find_path(&grid, (0, 0), (2, 0))
+        );
+        grid.set_blocked(1, 1);
+        assert_eq!(None, find_path(&grid, (0, 0), (2, 0)));
+    }
+
+    #[test]
+    fn rejects_grids_of_wrong_size() {
+        let grid = NavGrid::new(2, 2);
+        let ron = ron::ser::to_string(&grid).expect("Failed to serialize the grid");
+        assert_eq!(
+            grid,
+            ron::de::from_str::<NavGrid>(&ron).expect("Failed to deserialize the grid")
+        );
+        let wrong = ron.replace("height:2", "height:3");
+        assert_ne!(ron, wrong);
+        assert!(ron::de::from_str::<NavGrid>(&wrong).is_err());
+    }
+
+    #[test]
+    fn diagonals_dont_cut_corners() {
+        let mut layer = TileLayer::new(2, 2, Vector2::new(1.0, 1.0));
+        layer.set_solid(1, 0, true);
+        let grid = NavGrid::from_tile_layer(&layer).with_diagonals(true);
+        assert_eq!(
+            Some(vec![(0, 0), (0, 1), (1, 1)]),
+            find_path(&grid, (0, 0), (1, 1))
+        );
+        let grid = NavGrid::new(2, 2).with_diagonals(true);
+        assert_eq!(Some(vec![(0, 0), (1, 1)]), find_path(&grid, (0, 0), (1, 1)));
+    }
+
+    #[test]
+    fn answers_requests() {
+        let mut world = World::new();
+        world.register::<PathRequest>();
+        world.register::<Path>();
+        world.add_resource(NavGrid::new(2, 1));
+        let entity = world
+            .create_entity()
+            .with(PathRequest {
+                start: (0, 0),
+                goal: (1, 0),
+            })
+            .build();
+
+        PathfindingSystem::new().run_now(&world.res);
+        let paths = world.read_storage::<Path>();
+        assert_eq!(vec![(0, 0), (1, 0)], paths.get(entity).unwrap().tiles);
+        assert!(world.read_storage::<PathRequest>().get(entity).is_none());
+    }
+}
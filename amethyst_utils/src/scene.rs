@@ -6,7 +6,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use amethyst_assets::{Format, PrefabData, ProgressCounter};
 use amethyst_controls::ControlTagPrefab;
-use amethyst_core::{ecs::prelude::Entity, Transform};
+use amethyst_core::{ecs::prelude::Entity, Named, Transform};
 use amethyst_derive::PrefabData;
 use amethyst_error::Error;
 use amethyst_renderer::{
@@ -14,7 +14,7 @@ use amethyst_renderer::{
     TextureFormat,
 };
 
-use crate::removal::Removal;
+use crate::{auto_fov::AutoFov, removal::Removal};
 
 /// Basic `Prefab` scene node, meant to be used for fast prototyping, and most likely replaced
 /// for more complex scenarios.
@@ -38,10 +38,12 @@ where
     R: PartialEq + Debug + Clone + Send + Sync + 'static,
     V: From<InternalShape> + Into<MeshData>,
 {
+    name: Option<Named>,
     graphics: Option<GraphicsPrefab<V, M, TextureFormat>>,
     transform: Option<Transform>,
     light: Option<LightPrefab>,
     camera: Option<CameraPrefab>,
+    auto_fov: Option<AutoFov>,
     control_tag: Option<ControlTagPrefab>,
     removal: Option<Removal<R>>,
 }
@@ -55,10 +57,12 @@ where
 {
     fn default() -> Self {
         BasicScenePrefab {
+            name: None,
             graphics: None,
             transform: None,
             light: None,
             camera: None,
+            auto_fov: None,
             control_tag: None,
             removal: None,
         }
@@ -1,10 +1,13 @@
 //! Provides a small simple tag component for identifying entities.
 
-use std::marker::PhantomData;
+use std::{hash::Hash, marker::PhantomData};
+
+use fnv::FnvHashMap;
 
 use amethyst_assets::PrefabData;
 use amethyst_core::ecs::prelude::{
-    Component, Entities, Entity, Join, NullStorage, ReadStorage, WriteStorage,
+    BitSet, Component, ComponentEvent, DenseVecStorage, Entities, Entity, FlaggedStorage, Join,
+    NullStorage, ReadStorage, ReaderId, Resources, System, Write, WriteStorage,
 };
 use amethyst_derive::PrefabData;
 use amethyst_error::Error;
@@ -63,3 +66,235 @@ where
             .next()
     }
 }
+
+/// Component holding the tags of an entity, as names or values of an enum.
+///
+/// Unlike `Tag`, tags don't need a type per category, so they can be written in prefabs as
+/// `Tags(["enemy", "flying"])`. The entities with a tag are found with the `TagIndex`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, PrefabData)]
+#[prefab(Component)]
+pub struct Tags<T = String>(pub Vec<T>)
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static;
+
+impl<T> Tags<T>
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    /// Creates the tags from a list.
+    pub fn new<I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Tags(tags.into_iter().collect())
+    }
+
+    /// Returns `true` if `tag` is one of the tags.
+    pub fn has(&self, tag: &T) -> bool {
+        self.0.contains(tag)
+    }
+
+    /// Adds `tag`, if it isn't there yet.
+    pub fn insert(&mut self, tag: T) {
+        if !self.has(&tag) {
+            self.0.push(tag);
+        }
+    }
+
+    /// Removes `tag`.
+    pub fn remove(&mut self, tag: &T) {
+        self.0.retain(|t| t != tag);
+    }
+}
+
+impl<T> Component for Tags<T>
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+/// Resource holding the entities of each tag in a `BitSet`, kept up to date by the
+/// `TagIndexSystem`.
+///
+/// The sets can be joined with storages to iterate the entities with a tag:
+///
+/// ```rust,ignore
+/// for (entity, transform, _) in (&*entities, &transforms, index.tagged(&"enemy".into())).join() {
+///     // ...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct TagIndex<T = String>
+where
+    T: Eq + Hash,
+{
+    tagged: FnvHashMap<T, BitSet>,
+    empty: BitSet,
+}
+
+impl<T> Default for TagIndex<T>
+where
+    T: Eq + Hash,
+{
+    fn default() -> Self {
+        TagIndex {
+            tagged: FnvHashMap::default(),
+            empty: BitSet::new(),
+        }
+    }
+}
+
+impl<T> TagIndex<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Returns the set of the ids of the entities with `tag`, to use in joins.
+    pub fn tagged(&self, tag: &T) -> &BitSet {
+        self.tagged.get(tag).unwrap_or(&self.empty)
+    }
+
+    /// Returns `true` if `entity` has `tag`.
+    pub fn has(&self, entity: Entity, tag: &T) -> bool {
+        self.tagged(tag).contains(entity.id())
+    }
+
+    fn remove(&mut self, id: u32) {
+        for set in self.tagged.values_mut() {
+            set.remove(id);
+        }
+    }
+}
+
+/// Keeps the `TagIndex` in sync with the `Tags` components.
+#[derive(Debug)]
+pub struct TagIndexSystem<T = String> {
+    modified: BitSet,
+    reader: Option<ReaderId<ComponentEvent>>,
+    _m: PhantomData<T>,
+}
+
+impl<T> TagIndexSystem<T> {
+    /// Creates a new `TagIndexSystem`.
+    pub fn new() -> Self {
+        TagIndexSystem {
+            modified: BitSet::new(),
+            reader: None,
+            _m: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for TagIndexSystem<T> {
+    fn default() -> Self {
+        TagIndexSystem::new()
+    }
+}
+
+impl<'a, T> System<'a> for TagIndexSystem<T>
+where
+    T: Clone + Eq + Hash + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Tags<T>>,
+        Write<'a, TagIndex<T>>,
+    );
+
+    fn run(&mut self, (entities, tags, mut index): Self::SystemData) {
+        self.modified.clear();
+        let events = tags.channel().read(
+            self.reader
+                .as_mut()
+                .expect("`TagIndexSystem::setup` was not called before `TagIndexSystem::run`"),
+        );
+        for event in events {
+            match *event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    self.modified.add(id);
+                    index.remove(id);
+                }
+                ComponentEvent::Removed(id) => {
+                    self.modified.remove(id);
+                    index.remove(id);
+                }
+            }
+        }
+        for (entity, tags, _) in (&*entities, &tags, &self.modified).join() {
+            for tag in &tags.0 {
+                index
+                    .tagged
+                    .entry(tag.clone())
+                    .or_insert_with(BitSet::new)
+                    .add(entity.id());
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        use amethyst_core::ecs::prelude::SystemData;
+        Self::SystemData::setup(res);
+        self.reader = Some(WriteStorage::<Tags<T>>::fetch(res).register_reader());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::prelude::{Builder, Join, RunNow, System, World};
+
+    use super::{TagIndex, TagIndexSystem, Tags};
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+    enum Kind {
+        Enemy,
+        Pickup,
+    }
+
+    #[test]
+    fn joins_tagged_entities() {
+        let mut world = World::new();
+        let mut system = TagIndexSystem::<Kind>::new();
+        System::setup(&mut system, &mut world.res);
+        let enemy = world
+            .create_entity()
+            .with(Tags::new(vec![Kind::Enemy]))
+            .build();
+        let both = world
+            .create_entity()
+            .with(Tags::new(vec![Kind::Enemy, Kind::Pickup]))
+            .build();
+        let pickup = world
+            .create_entity()
+            .with(Tags::new(vec![Kind::Pickup]))
+            .build();
+        system.run_now(&world.res);
+
+        let enemies = |world: &World| {
+            let index = world.read_resource::<TagIndex<Kind>>();
+            (&*world.entities(), index.tagged(&Kind::Enemy))
+                .join()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![enemy, both], enemies(&world));
+
+        world
+            .write_storage::<Tags<Kind>>()
+            .get_mut(both)
+            .expect("Missing tags")
+            .remove(&Kind::Enemy);
+        world
+            .write_storage::<Tags<Kind>>()
+            .get_mut(pickup)
+            .expect("Missing tags")
+            .insert(Kind::Enemy);
+        world.delete_entity(enemy).expect("Failed to delete");
+        world.maintain();
+        system.run_now(&world.res);
+
+        assert_eq!(vec![pickup], enemies(&world));
+        let index = world.read_resource::<TagIndex<Kind>>();
+        assert!(index.has(both, &Kind::Pickup));
+        assert!(!index.has(enemy, &Kind::Enemy));
+    }
+}
@@ -0,0 +1,421 @@
+//! One-shot interpolation of a component towards a target value, with easing curves.
+//!
+//! Lighter than the animation pipeline, for the small effects of the gameplay, such as a pickup
+//! bouncing to the inventory or a hit flash:
+//!
+//! ```rust,ignore
+//! use amethyst::{
+//!     core::{math::Vector3, Transform},
+//!     utils::tween::{Easing, Tween, TweenSystem},
+//! };
+//!
+//! let mut target = transform.clone();
+//! target.set_scale(Vector3::new(2.0.into(), 2.0.into(), 2.0.into()));
+//! world
+//!     .create_entity()
+//!     .with(transform)
+//!     .with(Tween::new(target, 0.3).with_easing(Easing::BackOut).with_yoyo())
+//!     .build();
+//!
+//! // Added to the dispatcher:
+//! TweenSystem::<Transform>::new()
+//! ```
+
+use std::{f32::consts::PI, marker::PhantomData};
+
+use serde::{Deserialize, Serialize};
+
+use amethyst_core::{
+    ecs::prelude::{
+        Component, DenseVecStorage, Entities, Entity, Join, Read, System, Write, WriteStorage,
+    },
+    shrev::EventChannel,
+    timing::Time,
+    Float, Transform,
+};
+use amethyst_renderer::Rgba;
+
+/// Rate of change of a tween over its duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly, quadratic.
+    QuadIn,
+    /// Ends slowly, quadratic.
+    QuadOut,
+    /// Starts and ends slowly, quadratic.
+    QuadInOut,
+    /// Starts slowly, cubic.
+    CubicIn,
+    /// Ends slowly, cubic.
+    CubicOut,
+    /// Starts and ends slowly, cubic.
+    CubicInOut,
+    /// Starts and ends slowly, following a sine.
+    SineInOut,
+    /// Overshoots the end value and comes back.
+    BackOut,
+    /// Bounces on the end value.
+    BounceOut,
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+impl Easing {
+    /// Returns the eased progress, for a linear progress between 0.0 and 1.0.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - 4.0 * (1.0 - t).powi(3)
+                }
+            }
+            Easing::SineInOut => (1.0 - (PI * t).cos()) / 2.0,
+            Easing::BackOut => {
+                let overshoot = 1.701_58;
+                let t = t - 1.0;
+                1.0 + t * t * ((overshoot + 1.0) * t + overshoot)
+            }
+            Easing::BounceOut => {
+                let (n, d) = (7.5625, 2.75);
+                if t < 1.0 / d {
+                    n * t * t
+                } else if t < 2.0 / d {
+                    let t = t - 1.5 / d;
+                    n * t * t + 0.75
+                } else if t < 2.5 / d {
+                    let t = t - 2.25 / d;
+                    n * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d;
+                    n * t * t + 0.984_375
+                }
+            }
+        }
+    }
+}
+
+/// Components which can be interpolated by a `Tween`.
+pub trait Tweenable: Component + Clone {
+    /// Returns the value at `t` between `from` (0.0) and `to` (1.0). `t` goes out of this range
+    /// with the easings overshooting their end value.
+    fn tween(from: &Self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for Transform {
+    /// Interpolates the translation, rotation and scale.
+    fn tween(from: &Self, to: &Self, t: f32) -> Self {
+        let t = Float::from(t);
+        let rotation = from
+            .rotation()
+            .try_slerp(to.rotation(), t, Float::from(1.0e-6))
+            // Opposite rotations have no shortest path.
+            .unwrap_or_else(|| from.rotation().nlerp(to.rotation(), t));
+        let mut transform = to.clone();
+        transform.set_translation(from.translation().lerp(to.translation(), t));
+        transform.set_rotation(rotation);
+        transform.set_scale(from.scale().lerp(to.scale(), t));
+        transform
+    }
+}
+
+impl Tweenable for Rgba {
+    fn tween(from: &Self, to: &Self, t: f32) -> Self {
+        let lerp = |from: f32, to: f32| from + (to - from) * t;
+        Rgba(
+            lerp(from.0, to.0),
+            lerp(from.1, to.1),
+            lerp(from.2, to.2),
+            lerp(from.3, to.3),
+        )
+    }
+}
+
+/// How many times a `Tween` is played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TweenRepeat {
+    /// Plays the tween once.
+    Once,
+    /// Plays the tween the given number of times.
+    Count(u32),
+    /// Plays the tween until the component is removed.
+    Forever,
+}
+
+impl Default for TweenRepeat {
+    fn default() -> Self {
+        TweenRepeat::Once
+    }
+}
+
+/// Interpolates the `T` component of the entity from its value when the tween starts to `to`.
+///
+/// Played by the `TweenSystem<T>`, which removes it once finished.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tween<T> {
+    /// Value to reach.
+    pub to: T,
+    /// Duration of one play of the tween, in seconds.
+    pub duration: f32,
+    /// Wait before the tween starts, in seconds.
+    pub delay: f32,
+    /// Rate of change of the value.
+    pub easing: Easing,
+    /// How many times the tween is played.
+    pub repeat: TweenRepeat,
+    /// Plays every other repetition backwards, from `to` back to the start value.
+    pub yoyo: bool,
+    /// Uses the real time instead of the scaled time, so the tween goes on while the game is
+    /// paused or slowed down.
+    pub absolute_time: bool,
+    from: Option<T>,
+    elapsed: f32,
+}
+
+impl<T> Tween<T> {
+    /// Creates a tween reaching `to` in `duration` seconds, played once at constant speed.
+    pub fn new(to: T, duration: f32) -> Self {
+        Tween {
+            to,
+            duration,
+            delay: 0.0,
+            easing: Easing::Linear,
+            repeat: TweenRepeat::Once,
+            yoyo: false,
+            absolute_time: false,
+            from: None,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Changes the rate of change of the value.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Waits before starting.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Plays the tween several times.
+    pub fn with_repeat(mut self, repeat: TweenRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Plays every other repetition backwards.
+    pub fn with_yoyo(mut self) -> Self {
+        self.yoyo = true;
+        self
+    }
+
+    /// Ignores the time scale and the pause, for the tweens of the pause menus.
+    pub fn with_absolute_time(mut self) -> Self {
+        self.absolute_time = true;
+        self
+    }
+
+    /// Returns true once the delay is over.
+    pub fn is_started(&self) -> bool {
+        self.from.is_some()
+    }
+}
+
+impl<T> Component for Tween<T>
+where
+    T: Tweenable,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// What happened to a `Tween`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TweenEventType {
+    /// The tween started, after its delay.
+    Started,
+    /// The tween reached its end value, and was removed.
+    Completed,
+}
+
+/// Sent by the `TweenSystem`s when a `Tween` starts or completes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TweenEvent {
+    /// The tweened entity.
+    pub entity: Entity,
+    /// What happened.
+    pub event_type: TweenEventType,
+}
+
+/// Plays the `Tween<T>`s, removing them once completed, and sends `TweenEvent`s.
+///
+/// Tweens use the scaled time, so they follow `Time::time_scale` and stop while the game is
+/// paused, unless their `absolute_time` is set.
+pub struct TweenSystem<T> {
+    m: PhantomData<T>,
+}
+
+impl<T> Default for TweenSystem<T> {
+    fn default() -> Self {
+        TweenSystem { m: PhantomData }
+    }
+}
+
+impl<T> TweenSystem<T> {
+    /// Creates a new `TweenSystem`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<'a, T> System<'a> for TweenSystem<T>
+where
+    T: Tweenable,
+{
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Tween<T>>,
+        WriteStorage<'a, T>,
+        Read<'a, Time>,
+        Write<'a, EventChannel<TweenEvent>>,
+    );
+
+    fn run(&mut self, (entities, mut tweens, mut values, time, mut events): Self::SystemData) {
+        let mut completed = Vec::new();
+        for (entity, tween, value) in (&*entities, &mut tweens, &mut values).join() {
+            tween.elapsed += if tween.absolute_time {
+                time.delta_real_seconds()
+            } else {
+                time.delta_seconds()
+            };
+            if tween.elapsed < tween.delay {
+                continue;
+            }
+            if tween.from.is_none() {
+                tween.from = Some(value.clone());
+                events.single_write(TweenEvent {
+                    entity,
+                    event_type: TweenEventType::Started,
+                });
+            }
+            let (t, finished) = progress(
+                tween.elapsed - tween.delay,
+                tween.duration,
+                tween.repeat,
+                tween.yoyo,
+            );
+            let from = tween
+                .from
+                .as_ref()
+                .expect("Unreachable: The start value was just set");
+            *value = T::tween(from, &tween.to, tween.easing.apply(t));
+            if finished {
+                completed.push(entity);
+                events.single_write(TweenEvent {
+                    entity,
+                    event_type: TweenEventType::Completed,
+                });
+            }
+        }
+        for entity in completed {
+            tweens.remove(entity);
+        }
+    }
+}
+
+/// Returns the linear progress of a tween playing for `elapsed` seconds since its delay, and
+/// whether it is finished.
+///
+/// Tweens without duration finish at once, on their end value.
+fn progress(elapsed: f32, duration: f32, repeat: TweenRepeat, yoyo: bool) -> (f32, bool) {
+    if duration <= 0.0 {
+        return (1.0, true);
+    }
+    let plays = match repeat {
+        TweenRepeat::Once => Some(1),
+        TweenRepeat::Count(count) => Some(count.max(1)),
+        TweenRepeat::Forever => None,
+    };
+    let position = elapsed / duration;
+    let (play, fraction, finished) = match plays {
+        Some(plays) if position >= plays as f32 => (plays - 1, 1.0, true),
+        _ => (position.floor() as u32, position.fract(), false),
+    };
+    if yoyo && play % 2 == 1 {
+        (1.0 - fraction, finished)
+    } else {
+        (fraction, finished)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_renderer::Rgba;
+
+    use super::{progress, Easing, TweenRepeat, Tweenable};
+
+    #[test]
+    fn easings_start_and_end() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::CubicInOut,
+            Easing::SineInOut,
+            Easing::BackOut,
+            Easing::BounceOut,
+        ];
+        for easing in &easings {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{:?}", easing);
+        }
+        assert_eq!(0.5, Easing::QuadInOut.apply(0.5));
+    }
+
+    #[test]
+    fn repeats_and_yoyos() {
+        assert_eq!((0.5, false), progress(1.0, 2.0, TweenRepeat::Once, false));
+        assert_eq!((1.0, true), progress(3.0, 2.0, TweenRepeat::Once, true));
+        assert_eq!(
+            (0.25, false),
+            progress(2.5, 2.0, TweenRepeat::Count(2), false)
+        );
+        assert_eq!(
+            (0.75, false),
+            progress(2.5, 2.0, TweenRepeat::Count(2), true)
+        );
+        assert_eq!((0.0, true), progress(4.0, 2.0, TweenRepeat::Count(2), true));
+        assert_eq!(
+            (0.5, false),
+            progress(101.0, 2.0, TweenRepeat::Forever, false)
+        );
+        assert_eq!((1.0, true), progress(0.0, 0.0, TweenRepeat::Forever, false));
+
+        let color = Rgba::tween(&Rgba::WHITE, &Rgba(0.0, 0.0, 0.0, 0.0), 0.25);
+        assert_eq!(Rgba(0.75, 0.75, 0.75, 0.75), color);
+    }
+}
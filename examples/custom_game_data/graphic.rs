@@ -105,6 +105,7 @@ pub fn add_graphics_to_world(world: &mut World) {
     let light: Light = DirectionalLight {
         color: [0.2; 4].into(),
         direction: [-1.0; 3],
+        ..DirectionalLight::default()
     }.into();
 
     world.create_entity().with(light).build();
@@ -2,7 +2,7 @@
 
 use amethyst::{
     assets::{PrefabLoader, PrefabLoaderSystem, RonFormat},
-    controls::{FlyControlBundle, HideCursor},
+    controls::{CursorMode, FlyControlBundle},
     core::transform::TransformBundle,
     input::{is_key_down, is_mouse_button_down, InputBundle},
     prelude::*,
@@ -35,11 +35,9 @@ impl SimpleState for ExampleState {
         let StateData { world, .. } = data;
         if let StateEvent::Window(event) = &event {
             if is_key_down(&event, VirtualKeyCode::Escape) {
-                let mut hide_cursor = world.write_resource::<HideCursor>();
-                hide_cursor.hide = false;
+                *world.write_resource::<CursorMode>() = CursorMode::Free;
             } else if is_mouse_button_down(&event, MouseButton::Left) {
-                let mut hide_cursor = world.write_resource::<HideCursor>();
-                hide_cursor.hide = true;
+                *world.write_resource::<CursorMode>() = CursorMode::Locked;
             }
         }
         Trans::None
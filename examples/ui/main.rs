@@ -66,6 +66,7 @@ impl SimpleState for Example {
                 info!("Input Event detected: {:?}.", input);
                 Trans::None
             }
+            StateEvent::Custom(_) => Trans::None,
         }
     }
 
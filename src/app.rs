@@ -18,7 +18,7 @@ use crate::{
     core::{
         frame_limiter::{FrameLimiter, FrameRateLimitConfig, FrameRateLimitStrategy},
         shrev::{EventChannel, ReaderId},
-        timing::{Stopwatch, Time},
+        timing::{FixedTime, Stopwatch, Time},
         ArcThreadPool, EventReader, Named,
     },
     ecs::{
@@ -27,6 +27,7 @@ use crate::{
     },
     error::Error,
     game_data::DataInit,
+    scope::Persistent,
     state::{State, StateData, StateMachine, TransEvent},
     state_event::{StateEvent, StateEventReader},
     ui::UiEvent,
@@ -258,38 +259,35 @@ where
             .expect("Tried to start state machine without any states present");
     }
 
-    // React to window close events
+    // React to window close events, and throttle the frame rate while the window is unfocused
     fn should_close(&mut self) -> bool {
-        if self.ignore_window_close {
-            false
-        } else {
-            use crate::renderer::WindowEvent;
-            let world = &mut self.world;
-            let reader_id = &mut self.event_reader_id;
-            world.exec(|ev: Read<'_, EventChannel<Event>>| {
-                ev.read(reader_id).any(|e| {
-                    if cfg!(target_os = "ios") {
-                        if let Event::WindowEvent {
+        use crate::renderer::WindowEvent;
+        let ignore_window_close = self.ignore_window_close;
+        let world = &mut self.world;
+        let reader_id = &mut self.event_reader_id;
+        world.exec(
+            |(ev, mut frame_limiter): (Read<'_, EventChannel<Event>>, Write<'_, FrameLimiter>)| {
+                let mut close = false;
+                for e in ev.read(reader_id) {
+                    match *e {
+                        Event::WindowEvent {
+                            event: WindowEvent::Focused(focused),
+                            ..
+                        } => frame_limiter.set_focused(focused),
+                        Event::WindowEvent {
                             event: WindowEvent::Destroyed,
                             ..
-                        } = e
-                        {
-                            true
-                        } else {
-                            false
-                        }
-                    } else if let Event::WindowEvent {
-                        event: WindowEvent::CloseRequested,
-                        ..
-                    } = e
-                    {
-                        true
-                    } else {
-                        false
+                        } if cfg!(target_os = "ios") => close = true,
+                        Event::WindowEvent {
+                            event: WindowEvent::CloseRequested,
+                            ..
+                        } if !cfg!(target_os = "ios") => close = true,
+                        _ => {}
                     }
-                })
-            })
-        }
+                }
+                close && !ignore_window_close
+            },
+        )
     }
 
     /// Advances the game world by one tick.
@@ -320,6 +318,13 @@ where
             }
         }
 
+        {
+            let rate = self.states.frame_rate_limit();
+            self.world
+                .write_resource::<FrameLimiter>()
+                .set_state_rate(rate);
+        }
+
         {
             #[cfg(feature = "profiler")]
             profile_scope!("run_callback_queue");
@@ -352,14 +357,19 @@ where
             profile_scope!("fixed_update");
 
             {
-                self.world.write_resource::<Time>().start_fixed_update();
+                let mut time = self.world.write_resource::<Time>();
+                time.start_fixed_update();
+                self.world.write_resource::<FixedTime>().start_frame(&time);
             }
             while { self.world.write_resource::<Time>().step_fixed_update() } {
+                self.world.write_resource::<FixedTime>().step();
                 self.states
                     .fixed_update(StateData::new(&mut self.world, &mut self.data));
             }
             {
-                self.world.write_resource::<Time>().finish_fixed_update();
+                let mut time = self.world.write_resource::<Time>();
+                time.finish_fixed_update();
+                self.world.write_resource::<FixedTime>().finish_frame(&time);
             }
 
             #[cfg(feature = "profiler")]
@@ -534,9 +544,11 @@ where
         world.add_resource(FrameLimiter::default());
         world.add_resource(Stopwatch::default());
         world.add_resource(Time::default());
+        world.add_resource(FixedTime::default());
         world.add_resource(CallbackQueue::default());
 
         world.register::<Named>();
+        world.register::<Persistent>();
 
         Ok(ApplicationBuilder {
             initial_state,
@@ -780,9 +792,27 @@ where
     /// # Returns
     ///
     /// This function returns the ApplicationBuilder after modifying it.
-    pub fn with_frame_limit(mut self, strategy: FrameRateLimitStrategy, max_fps: u32) -> Self {
+    pub fn with_frame_limit(self, strategy: FrameRateLimitStrategy, max_fps: u32) -> Self {
+        self.world
+            .write_resource::<FrameLimiter>()
+            .set_rate(strategy, max_fps);
+        self
+    }
+
+    /// Sets the maximum frames per second of this game while its window is not focused, such as
+    /// when it is minimized.
+    ///
+    /// # Parameters
+    ///
+    /// `max_fps`: the maximum frames per second this game will run at in the background.
+    ///
+    /// # Returns
+    ///
+    /// This function returns the ApplicationBuilder after modifying it.
+    pub fn with_background_frame_limit(self, max_fps: u32) -> Self {
         self.world
-            .add_resource(FrameLimiter::new(strategy, max_fps));
+            .write_resource::<FrameLimiter>()
+            .set_background_rate(Some(max_fps));
         self
     }
 
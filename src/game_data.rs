@@ -4,7 +4,7 @@ use crate::{
     core::{
         ecs::prelude::{Dispatcher, DispatcherBuilder, System, World},
         math::RealField,
-        ArcThreadPool, SystemBundle,
+        ArcThreadPool, SystemBundle, SystemExt,
     },
     error::Error,
     renderer::pipe::pass::Pass,
@@ -22,23 +22,43 @@ pub trait DataInit<T> {
 /// field.
 pub struct GameData<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
+    fixed_dispatcher: Option<Dispatcher<'a, 'b>>,
 }
 
 impl<'a, 'b> GameData<'a, 'b> {
     /// Create new game data
     pub fn new(dispatcher: Dispatcher<'a, 'b>) -> Self {
-        GameData { dispatcher }
+        GameData {
+            dispatcher,
+            fixed_dispatcher: None,
+        }
+    }
+
+    /// Sets the dispatcher of the fixed stage, run by `fixed_update`.
+    pub fn with_fixed_dispatcher(mut self, fixed_dispatcher: Dispatcher<'a, 'b>) -> Self {
+        self.fixed_dispatcher = Some(fixed_dispatcher);
+        self
     }
 
     /// Update game data
     pub fn update(&mut self, world: &World) {
         self.dispatcher.dispatch(&world.res);
     }
+
+    /// Run the systems of the fixed stage, once per fixed update
+    pub fn fixed_update(&mut self, world: &World) {
+        if let Some(ref mut fixed_dispatcher) = self.fixed_dispatcher {
+            fixed_dispatcher.dispatch(&world.res);
+        }
+    }
 }
 
 /// Builder for default game data
 pub struct GameDataBuilder<'a, 'b, N: RealField = f32> {
     disp_builder: DispatcherBuilder<'a, 'b>,
+    fixed_disp_builder: Option<DispatcherBuilder<'a, 'b>>,
+    profiling: bool,
+    groups: Vec<String>,
     _marker: PhantomData<N>,
 }
 
@@ -53,10 +73,77 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     pub fn new() -> Self {
         GameDataBuilder {
             disp_builder: DispatcherBuilder::new(),
+            fixed_disp_builder: None,
+            profiling: false,
+            groups: Vec::new(),
             _marker: PhantomData,
         }
     }
 
+    /// Times the systems added after this call with `with` and `with_fixed` in the
+    /// `SystemProfile` resource, by their name.
+    ///
+    /// The systems added by bundles, and the thread-local systems which have no name, are not
+    /// timed unless they are wrapped with `SystemExt::profiled`. Unnamed systems are recorded as
+    /// `"unnamed"`.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// // The average duration of "nop" is then found with
+    /// // `world.read_resource::<SystemProfile>().timing("nop")`.
+    /// GameDataBuilder::<f32>::default()
+    ///     .with_profiling()
+    ///     .with(NopSystem, "nop", &[]);
+    /// ~~~
+    pub fn with_profiling(mut self) -> Self {
+        self.profiling = true;
+        self
+    }
+
+    /// Adds the systems added by `build` with `with` and `with_fixed` to `group`, so they can be
+    /// disabled and enabled at runtime with the `SystemToggles` resource.
+    ///
+    /// Groups can be nested, the systems running only while all their groups are enabled. The
+    /// systems added by bundles do not join the group.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// // The "enemy_ai" system stops running after
+    /// // `world.write_resource::<SystemToggles>().disable("gameplay")`.
+    /// GameDataBuilder::<f32>::default()
+    ///     .with_group("gameplay", |builder| builder.with(NopSystem, "enemy_ai", &[]))
+    ///     .with(NopSystem, "menu", &[]);
+    /// ~~~
+    pub fn with_group<F>(mut self, group: &str, build: F) -> Self
+    where
+        F: FnOnce(Self) -> Self,
+    {
+        self.groups.push(group.to_string());
+        let mut builder = build(self);
+        builder.groups.pop();
+        builder
+    }
+
     /// Inserts a barrier which assures that all systems added before the
     /// barrier are executed before the ones after this barrier.
     ///
@@ -149,7 +236,72 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
     where
         for<'c> S: System<'c> + Send + 'a,
     {
-        self.disp_builder.add(system, name, dependencies);
+        add_system(
+            &mut self.disp_builder,
+            system,
+            name,
+            dependencies,
+            self.profiling,
+            &self.groups,
+        );
+        self
+    }
+
+    /// Adds a given system to the fixed stage.
+    ///
+    /// The systems of the fixed stage are run zero or more times per frame, with the
+    /// `State::fixed_update`s, at the rate set by `ApplicationBuilder::with_fixed_step_length`.
+    /// They should use the `FixedTime` resource instead of `Time::delta_seconds`. They can not
+    /// depend on the systems of the frame, which run after them.
+    ///
+    /// __Note:__ all dependencies must be added to the fixed stage before you add the system.
+    ///
+    /// # Parameters
+    ///
+    /// - `system`: The system that is to be added to the fixed stage.
+    /// - `name`: A unique string to identify the system by in the fixed stage.
+    /// - `dependencies`: A list of named systems of the fixed stage that _must_ have completed
+    ///                 running before this system is permitted to run.
+    ///
+    /// # Returns
+    ///
+    /// This function returns GameDataBuilder after it has modified it.
+    ///
+    /// # Panics
+    ///
+    /// Panics like `with`, for the names of the fixed stage.
+    ///
+    /// # Examples
+    ///
+    /// ~~~no_run
+    /// use amethyst::prelude::*;
+    /// use amethyst::ecs::prelude::System;
+    ///
+    /// struct NopSystem;
+    /// impl<'a> System<'a> for NopSystem {
+    ///     type SystemData = ();
+    ///     fn run(&mut self, _: Self::SystemData) {}
+    /// }
+    ///
+    /// GameDataBuilder::<f32>::default()
+    ///     // Runs at the fixed rate, such as 60 times per second
+    ///     .with_fixed(NopSystem, "physics", &[])
+    ///     // Runs once per frame
+    ///     .with(NopSystem, "interpolation", &[]);
+    /// ~~~
+    pub fn with_fixed<S>(mut self, system: S, name: &str, dependencies: &[&str]) -> Self
+    where
+        for<'c> S: System<'c> + Send + 'a,
+    {
+        add_system(
+            self.fixed_disp_builder
+                .get_or_insert_with(DispatcherBuilder::new),
+            system,
+            name,
+            dependencies,
+            self.profiling,
+            &self.groups,
+        );
         self
     }
 
@@ -226,6 +378,24 @@ impl<'a, 'b, N: RealField + Default> GameDataBuilder<'a, 'b, N> {
         Ok(self)
     }
 
+    /// Add a given ECS bundle to the fixed stage.
+    ///
+    /// See `with_fixed` for how the systems of the fixed stage are run.
+    ///
+    /// # Errors
+    ///
+    /// See each individual bundle for a description of the errors it could produce.
+    pub fn with_fixed_bundle<B>(mut self, bundle: B) -> Result<Self, Error>
+    where
+        B: SystemBundle<'a, 'b>,
+    {
+        bundle.build(
+            self.fixed_disp_builder
+                .get_or_insert_with(DispatcherBuilder::new),
+        )?;
+        Ok(self)
+    }
+
     /// Create a basic renderer with a single given `Pass`, and optional support for the `DrawUi` pass.
     ///
     /// Will set the clear color to black.
@@ -271,11 +441,54 @@ impl<'a, 'b> DataInit<GameData<'a, 'b>> for GameDataBuilder<'a, 'b> {
         let pool = world.read_resource::<ArcThreadPool>().clone();
 
         #[cfg(not(no_threading))]
-        let mut dispatcher = self.disp_builder.with_pool(pool).build();
+        let mut dispatcher = self.disp_builder.with_pool(pool.clone()).build();
         #[cfg(no_threading)]
         let mut dispatcher = self.disp_builder.build();
         dispatcher.setup(&mut world.res);
-        GameData::new(dispatcher)
+        let game_data = GameData::new(dispatcher);
+
+        match self.fixed_disp_builder {
+            Some(fixed_disp_builder) => {
+                #[cfg(not(no_threading))]
+                let mut fixed_dispatcher = fixed_disp_builder.with_pool(pool).build();
+                #[cfg(no_threading)]
+                let mut fixed_dispatcher = fixed_disp_builder.build();
+                fixed_dispatcher.setup(&mut world.res);
+                game_data.with_fixed_dispatcher(fixed_dispatcher)
+            }
+            None => game_data,
+        }
+    }
+}
+
+/// Adds the system to the dispatcher, timed and in the groups if any.
+fn add_system<'a, 'b, S>(
+    disp_builder: &mut DispatcherBuilder<'a, 'b>,
+    system: S,
+    name: &str,
+    dependencies: &[&str],
+    profiling: bool,
+    groups: &[String],
+) where
+    for<'c> S: System<'c> + Send + 'a,
+{
+    let profile_name = if name.is_empty() { "unnamed" } else { name };
+    match (profiling, groups.split_first()) {
+        (false, None) => disp_builder.add(system, name, dependencies),
+        (true, None) => disp_builder.add(system.profiled(profile_name), name, dependencies),
+        (false, Some((first, rest))) => {
+            let toggled = rest.iter().fold(system.toggled(first), |toggled, group| {
+                toggled.in_group(group)
+            });
+            disp_builder.add(toggled, name, dependencies);
+        }
+        (true, Some((first, rest))) => {
+            let toggled = rest.iter().fold(
+                system.profiled(profile_name).toggled(first),
+                |toggled, group| toggled.in_group(group),
+            );
+            disp_builder.add(toggled, name, dependencies);
+        }
     }
 }
 
@@ -67,12 +67,18 @@ pub use amethyst_derive as derive;
 pub use amethyst_error as error;
 #[cfg(feature = "gltf")]
 pub use amethyst_gltf as gltf;
+#[cfg(feature = "imgui")]
+pub use amethyst_imgui as imgui;
 pub use amethyst_input as input;
 #[cfg(feature = "locale")]
 pub use amethyst_locale as locale;
 #[cfg(feature = "network")]
 pub use amethyst_network as network;
+#[cfg(feature = "physics")]
+pub use amethyst_physics as physics;
 pub use amethyst_renderer as renderer;
+#[cfg(feature = "scripting")]
+pub use amethyst_scripting as scripting;
 pub use amethyst_ui as ui;
 pub use amethyst_utils as utils;
 pub use winit;
@@ -87,11 +93,13 @@ pub use self::{
     error::Error,
     game_data::{DataInit, GameData, GameDataBuilder},
     logger::{start_logger, LevelFilter as LogLevelFilter, Logger, LoggerConfig, StdoutLog},
+    scope::{Persistent, ScopedResources},
     state::{
-        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, StateMachine, Trans,
-        TransEvent,
+        EmptyState, EmptyTrans, SimpleState, SimpleTrans, SimpleTransEvent,
+        SimpleTransEventChannel, State, StateData, StateMachine, Trans, TransEvent,
+        TransEventChannel,
     },
-    state_event::{StateEvent, StateEventReader},
+    state_event::{CustomEvent, StateEvent, StateEventChannel, StateEventReader},
 };
 
 /// Convenience alias for use in main functions that uses Amethyst.
@@ -103,5 +111,6 @@ mod app;
 mod callback_queue;
 mod game_data;
 mod logger;
+mod scope;
 mod state;
 mod state_event;
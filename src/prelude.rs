@@ -7,8 +7,10 @@ pub use crate::{
     core::{SystemExt, WithNamed},
     ecs::prelude::{Builder, World},
     game_data::{DataInit, GameData, GameDataBuilder},
+    scope::{Persistent, ScopedResources},
     state::{
-        EmptyState, EmptyTrans, SimpleState, SimpleTrans, State, StateData, Trans, TransEvent,
+        EmptyState, EmptyTrans, SimpleState, SimpleTrans, SimpleTransEventChannel, State,
+        StateData, Trans, TransEvent,
     },
-    state_event::StateEvent,
+    state_event::{CustomEvent, StateEvent, StateEventChannel},
 };
@@ -0,0 +1,114 @@
+//! Cleanup of the entities and resources created by a `State` when it stops.
+
+use std::collections::HashSet;
+
+use log::error;
+
+use crate::ecs::{
+    prelude::{Component, Entity, Join, NullStorage, World},
+    storage::MaskedStorage,
+};
+
+/// Marker component keeping an entity alive when the scoped state that created it stops.
+///
+/// See `State::is_scoped`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Persistent;
+
+impl Component for Persistent {
+    type Storage = NullStorage<Self>;
+}
+
+type RemoveResource = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// Resource removing resources when the state that added them stops.
+///
+/// Resources are persistent unless they're added to the scope of the active state:
+///
+/// ```rust,ignore
+/// fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+///     data.world.add_resource(Level::new(3));
+///     data.world.write_resource::<ScopedResources>().add::<Level>();
+/// }
+/// ```
+#[derive(Default)]
+pub struct ScopedResources {
+    scopes: Vec<Vec<RemoveResource>>,
+}
+
+impl ScopedResources {
+    /// Removes the resource `R` when the active state stops.
+    pub fn add<R>(&mut self)
+    where
+        R: Send + Sync + 'static,
+    {
+        if self.scopes.is_empty() {
+            self.scopes.push(Vec::new());
+        }
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("Unreachable: scope just pushed");
+        scope.push(Box::new(|world: &mut World| {
+            world.res.remove::<R>();
+        }));
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop_scope(&mut self) -> Vec<RemoveResource> {
+        self.scopes.pop().unwrap_or_else(Vec::new)
+    }
+}
+
+/// The scope of a state on the stack of the `StateMachine`.
+#[derive(Debug, Default)]
+pub(crate) struct StateScope {
+    /// The entities alive when a scoped state started, `None` for other states.
+    entities: Option<HashSet<Entity>>,
+}
+
+impl StateScope {
+    /// Opens the scope of a state about to start.
+    pub(crate) fn enter(world: &mut World, scoped: bool) -> Self {
+        scoped_resources(world).push_scope();
+        let entities = if scoped {
+            Some(world.entities().join().collect())
+        } else {
+            None
+        };
+        StateScope { entities }
+    }
+
+    /// Closes the scope of a stopped state, removing its scoped resources and, if it's scoped,
+    /// the entities it created which aren't `Persistent`.
+    pub(crate) fn exit(self, world: &mut World) {
+        let removes = scoped_resources(world).pop_scope();
+        for remove in removes {
+            remove(world);
+        }
+
+        if let Some(existing) = self.entities {
+            if !world.res.has_value::<MaskedStorage<Persistent>>() {
+                world.register::<Persistent>();
+            }
+            let created = (&*world.entities(), !&world.read_storage::<Persistent>())
+                .join()
+                .map(|(entity, _)| entity)
+                .filter(|entity| !existing.contains(entity))
+                .collect::<Vec<_>>();
+            if let Err(e) = world.delete_entities(&created) {
+                error!("Failed to delete the entities of a scoped state: {}", e);
+            }
+        }
+    }
+}
+
+fn scoped_resources(world: &mut World) -> crate::shred::FetchMut<'_, ScopedResources> {
+    world
+        .res
+        .entry::<ScopedResources>()
+        .or_insert_with(ScopedResources::default)
+}
@@ -4,7 +4,9 @@ use amethyst_input::is_close_requested;
 
 use derivative::Derivative;
 
-use crate::{ecs::prelude::World, GameData, StateEvent};
+use crate::{
+    core::shrev::EventChannel, ecs::prelude::World, scope::StateScope, GameData, StateEvent,
+};
 
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
@@ -69,6 +71,22 @@ pub enum Trans<T, E> {
 /// Transitions will be executed sequentially by Amethyst's `CoreApplication` update loop.
 pub type TransEvent<T, E> = Box<dyn Fn() -> Trans<T, E> + Send + Sync + 'static>;
 
+/// The `EventChannel` of the `TransEvent`s, which the systems write to for changing the states
+/// without depending on them.
+pub type TransEventChannel<T, E> = EventChannel<TransEvent<T, E>>;
+
+/// A `TransEvent` for `SimpleState`s.
+/// # Example:
+/// ```rust, ignore
+/// world
+///     .write_resource::<SimpleTransEventChannel>()
+///     .single_write(Box::new(|| Trans::Push(Box::new(PauseMenu))));
+/// ```
+pub type SimpleTransEvent = TransEvent<GameData<'static, 'static>, StateEvent>;
+
+/// The `EventChannel` of the `SimpleTransEvent`s.
+pub type SimpleTransEventChannel = TransEventChannel<GameData<'static, 'static>, StateEvent>;
+
 /// An empty `Trans`. Made to be used with `EmptyState`.
 pub type EmptyTrans = Trans<(), StateEvent>;
 
@@ -118,6 +136,20 @@ pub trait State<T, E: Send + Sync + 'static> {
     /// even when this is not the active state,
     /// as long as this state is on the [StateMachine](struct.StateMachine.html)'s state-stack.
     fn shadow_update(&mut self, _data: StateData<'_, T>) {}
+
+    /// Maximum frame rate while this is the active state, overriding the rate of the
+    /// `FrameLimiter`, such as a low rate for a menu over a paused game. `Some(0)` removes the
+    /// limit, and `None` keeps the rate of the `FrameLimiter`.
+    fn frame_rate_limit(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns `true` to delete the entities created since this state started when it stops,
+    /// except the `Persistent` ones. Resources added to the `ScopedResources` are removed
+    /// whether the state is scoped or not.
+    fn is_scoped(&self) -> bool {
+        false
+    }
 }
 
 /// An empty `State` trait. It contains no `StateData` or custom `StateEvent`.
@@ -168,6 +200,20 @@ pub trait EmptyState {
     /// even when this is not the active state,
     /// as long as this state is on the [StateMachine](struct.StateMachine.html)'s state-stack.
     fn shadow_update(&mut self, _data: StateData<'_, ()>) {}
+
+    /// Maximum frame rate while this is the active state, overriding the rate of the
+    /// `FrameLimiter`, such as a low rate for a menu over a paused game. `Some(0)` removes the
+    /// limit, and `None` keeps the rate of the `FrameLimiter`.
+    fn frame_rate_limit(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns `true` to delete the entities created since this state started when it stops,
+    /// except the `Persistent` ones. Resources added to the `ScopedResources` are removed
+    /// whether the state is scoped or not.
+    fn is_scoped(&self) -> bool {
+        false
+    }
 }
 
 impl<T: EmptyState> State<(), StateEvent> for T {
@@ -221,6 +267,14 @@ impl<T: EmptyState> State<(), StateEvent> for T {
     fn shadow_update(&mut self, data: StateData<'_, ()>) {
         self.shadow_update(data);
     }
+
+    fn frame_rate_limit(&self) -> Option<u32> {
+        self.frame_rate_limit()
+    }
+
+    fn is_scoped(&self) -> bool {
+        self.is_scoped()
+    }
 }
 
 /// A simple `State` trait. It contains `GameData` as its `StateData` and no custom `StateEvent`.
@@ -275,6 +329,20 @@ pub trait SimpleState {
     /// even when this is not the active state,
     /// as long as this state is on the [StateMachine](struct.StateMachine.html)'s state-stack.
     fn shadow_update(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+
+    /// Maximum frame rate while this is the active state, overriding the rate of the
+    /// `FrameLimiter`, such as a low rate for a menu over a paused game. `Some(0)` removes the
+    /// limit, and `None` keeps the rate of the `FrameLimiter`.
+    fn frame_rate_limit(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns `true` to delete the entities created since this state started when it stops,
+    /// except the `Persistent` ones. Resources added to the `ScopedResources` are removed
+    /// whether the state is scoped or not.
+    fn is_scoped(&self) -> bool {
+        false
+    }
 }
 
 impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
@@ -311,8 +379,10 @@ impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
 
     /// Executed repeatedly at stable, predictable intervals (1/60th of a second
     /// by default).
-    fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
-        self.fixed_update(data)
+    fn fixed_update(&mut self, mut data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let r = self.fixed_update(StateData::new(&mut *data.world, &mut *data.data));
+        data.data.fixed_update(&data.world);
+        r
     }
 
     /// Executed on every frame immediately, as fast as the engine will allow (taking into account the frame rate limit).
@@ -336,6 +406,14 @@ impl<T: SimpleState> State<GameData<'static, 'static>, StateEvent> for T {
     fn shadow_update(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         self.shadow_update(data);
     }
+
+    fn frame_rate_limit(&self) -> Option<u32> {
+        self.frame_rate_limit()
+    }
+
+    fn is_scoped(&self) -> bool {
+        self.is_scoped()
+    }
 }
 
 /// A simple stack-based state machine (pushdown automaton).
@@ -345,6 +423,7 @@ pub struct StateMachine<'a, T, E> {
     running: bool,
     #[derivative(Debug = "ignore")]
     state_stack: Vec<Box<dyn State<T, E> + 'a>>,
+    scopes: Vec<StateScope>,
 }
 
 impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
@@ -353,6 +432,7 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         StateMachine {
             running: false,
             state_stack: vec![Box::new(initial_state)],
+            scopes: Vec::new(),
         }
     }
 
@@ -361,14 +441,28 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
         self.running
     }
 
+    /// Returns the frame rate limit of the active state, if any.
+    pub fn frame_rate_limit(&self) -> Option<u32> {
+        if self.running {
+            self.state_stack
+                .last()
+                .and_then(|state| state.frame_rate_limit())
+        } else {
+            None
+        }
+    }
+
     /// Initializes the state machine.
     pub fn start(&mut self, data: StateData<'_, T>) -> Result<(), StateError> {
         if !self.running {
+            let StateData { world, data } = data;
             let state = self
                 .state_stack
                 .last_mut()
                 .ok_or(StateError::NoStatesPresent)?;
-            state.on_start(data);
+            self.scopes
+                .push(StateScope::enter(world, state.is_scoped()));
+            state.on_start(StateData { world, data });
             self.running = true;
         }
         Ok(())
@@ -442,8 +536,11 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
             let StateData { world, data } = data;
             if let Some(mut state) = self.state_stack.pop() {
                 state.on_stop(StateData { world, data });
+                self.exit_scope(world);
             }
 
+            self.scopes
+                .push(StateScope::enter(world, state.is_scoped()));
             self.state_stack.push(state);
 
             //State was just pushed, thus pop will always succeed
@@ -460,6 +557,8 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
                 state.on_pause(StateData { world, data });
             }
 
+            self.scopes
+                .push(StateScope::enter(world, state.is_scoped()));
             self.state_stack.push(state);
 
             //State was just pushed, thus pop will always succeed
@@ -475,6 +574,7 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
             let StateData { world, data } = data;
             if let Some(mut state) = self.state_stack.pop() {
                 state.on_stop(StateData { world, data });
+                self.exit_scope(world);
             }
 
             if let Some(state) = self.state_stack.last_mut() {
@@ -491,16 +591,25 @@ impl<'a, T, E: Send + Sync + 'static> StateMachine<'a, T, E> {
             let StateData { world, data } = data;
             while let Some(mut state) = self.state_stack.pop() {
                 state.on_stop(StateData { world, data });
+                self.exit_scope(world);
             }
 
             self.running = false;
         }
     }
+
+    /// Cleans up after the state just removed from the stack.
+    fn exit_scope(&mut self, world: &mut World) {
+        if let Some(scope) = self.scopes.pop() {
+            scope.exit(world);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Persistent, ScopedResources};
 
     struct State1(u8);
     struct State2;
@@ -540,4 +649,53 @@ mod tests {
         sm.update(StateData::new(&mut world, &mut ()));
         assert!(!sm.is_running());
     }
+
+    struct Level;
+
+    impl State<(), ()> for Level {
+        fn on_start(&mut self, data: StateData<'_, ()>) {
+            data.world.create_entity().build();
+            data.world.create_entity().with(Persistent).build();
+            data.world.add_resource(0u32);
+            data.world.write_resource::<ScopedResources>().add::<u32>();
+        }
+
+        fn update(&mut self, _: StateData<'_, ()>) -> Trans<(), ()> {
+            Trans::Pop
+        }
+
+        fn is_scoped(&self) -> bool {
+            true
+        }
+    }
+
+    struct Menu;
+
+    impl State<(), ()> for Menu {
+        fn update(&mut self, _: StateData<'_, ()>) -> Trans<(), ()> {
+            Trans::Push(Box::new(Level))
+        }
+    }
+
+    #[test]
+    fn scoped_state_cleans_up() {
+        use crate::ecs::prelude::{Builder, Join, World};
+
+        let mut world = World::new();
+        world.register::<Persistent>();
+        let menu_entity = world.create_entity().build();
+
+        let mut sm = StateMachine::new(Menu);
+        sm.start(StateData::new(&mut world, &mut ())).unwrap();
+        sm.update(StateData::new(&mut world, &mut ()));
+        assert_eq!(3, world.entities().join().count());
+        assert!(world.res.has_value::<u32>());
+
+        sm.update(StateData::new(&mut world, &mut ()));
+        world.maintain();
+        let alive = world.entities().join().collect::<Vec<_>>();
+        assert_eq!(2, alive.len());
+        assert!(alive.contains(&menu_entity));
+        assert!(!world.res.has_value::<u32>());
+    }
 }
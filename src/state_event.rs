@@ -1,3 +1,9 @@
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    sync::Arc,
+};
+
 use crate::{
     core::{
         ecs::{Read, Resources, SystemData},
@@ -23,4 +29,83 @@ where
     Ui(UiEvent),
     /// Events sent by the input system.
     Input(InputEvent<T>),
+    /// Events of user defined types, sent by the systems through the `StateEventChannel`.
+    Custom(CustomEvent),
+}
+
+/// Event queue through which the systems send `CustomEvent`s to the states, received as
+/// `StateEvent::Custom` in `handle_event`.
+///
+/// # Example:
+/// ```rust, ignore
+/// enum GameEvent {
+///     LevelCompleted(u32),
+/// }
+///
+/// // In a system:
+/// world
+///     .write_resource::<StateEventChannel>()
+///     .single_write(CustomEvent::new(GameEvent::LevelCompleted(3)));
+///
+/// // In the state:
+/// if let StateEvent::Custom(event) = &event {
+///     if let Some(GameEvent::LevelCompleted(level)) = event.downcast_ref::<GameEvent>() {
+///         return Trans::Switch(Box::new(LevelSelect::new(*level)));
+///     }
+/// }
+/// ```
+pub type StateEventChannel = EventChannel<CustomEvent>;
+
+/// An event of any type sent to the states, see `StateEventChannel`.
+///
+/// Cloning the event is cheap, as it is shared.
+#[derive(Clone)]
+pub struct CustomEvent(Arc<dyn Any + Send + Sync>);
+
+impl CustomEvent {
+    /// Wraps an event of a user defined type.
+    pub fn new<E>(event: E) -> Self
+    where
+        E: Any + Send + Sync,
+    {
+        CustomEvent(Arc::new(event))
+    }
+
+    /// Returns true if the event is of type `E`.
+    pub fn is<E: Any>(&self) -> bool {
+        self.0.is::<E>()
+    }
+
+    /// Returns the event if it is of type `E`.
+    pub fn downcast_ref<E: Any>(&self) -> Option<&E> {
+        self.0.downcast_ref::<E>()
+    }
+}
+
+impl Debug for CustomEvent {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> FmtResult {
+        fmt.write_str("CustomEvent(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CustomEvent;
+
+    #[derive(Debug, PartialEq)]
+    enum GameEvent {
+        LevelCompleted(u32),
+    }
+
+    #[test]
+    fn downcasts_custom_events() {
+        let event = CustomEvent::new(GameEvent::LevelCompleted(3));
+        assert!(event.is::<GameEvent>());
+        assert!(!event.is::<u32>());
+        assert_eq!(
+            Some(&GameEvent::LevelCompleted(3)),
+            event.clone().downcast_ref::<GameEvent>()
+        );
+        assert_eq!(None, event.downcast_ref::<String>());
+    }
 }